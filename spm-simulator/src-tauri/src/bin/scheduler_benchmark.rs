@@ -221,6 +221,7 @@ async fn reset_and_seed_data(
             created_at: Set(Some(now)),
             updated_at: Set(Some(now)),
             import_batch_id: Set(None),
+            coiling_temp: Set(None),
             ..Default::default()
         });
 
@@ -383,7 +384,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
         for run_idx in 0..runs {
             let t0 = Instant::now();
-            let output = scheduler::auto_schedule(plan_id, strategy_id).await?;
+            let output = scheduler::auto_schedule(get_db(), plan_id, strategy_id).await?;
             let elapsed_ms = t0.elapsed().as_secs_f64() * 1000.0;
             let gap = analyze_schedule_idle_gaps(plan_id, Some(30)).await?;
 
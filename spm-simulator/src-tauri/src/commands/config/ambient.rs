@@ -0,0 +1,137 @@
+use super::types::{write_operation_log, AmbientTemperatureImportResult};
+use crate::AppError;
+
+/// 日期列可用表头
+const DATE_HEADERS: [&str; 4] = ["date", "record_date", "日期", "记录日期"];
+/// 气温列可用表头
+const TEMP_HEADERS: [&str; 5] = ["avg_temp", "temp", "temperature", "平均气温", "气温"];
+
+#[tauri::command]
+pub async fn get_ambient_temperatures(
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<crate::models::ambient_temperature::Model>, AppError> {
+    use crate::db::get_db;
+    use crate::models::ambient_temperature::{self, Entity as AmbientTemperature};
+    use sea_orm::*;
+
+    let db = get_db();
+    let mut query = AmbientTemperature::find();
+    if let Some(start) = start_date.filter(|v| !v.trim().is_empty()) {
+        query = query.filter(ambient_temperature::Column::RecordDate.gte(start.trim()));
+    }
+    if let Some(end) = end_date.filter(|v| !v.trim().is_empty()) {
+        query = query.filter(ambient_temperature::Column::RecordDate.lte(end.trim()));
+    }
+    let rows = query
+        .order_by_asc(ambient_temperature::Column::RecordDate)
+        .all(db)
+        .await?;
+    Ok(rows)
+}
+
+/// 导入日气温 CSV（列：日期 + 平均气温），同日期覆盖
+#[tauri::command]
+pub async fn import_ambient_temperature_csv(
+    file_path: String,
+) -> Result<AmbientTemperatureImportResult, AppError> {
+    use crate::db::get_db;
+    use crate::models::ambient_temperature::{self, Entity as AmbientTemperature};
+    use sea_orm::*;
+
+    let mut reader = csv::Reader::from_path(&file_path)
+        .map_err(|e| AppError::FileFormatError(format!("无法打开CSV文件: {}", e)))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::FileFormatError(format!("无法读取CSV表头: {}", e)))?
+        .iter()
+        .map(|h| h.trim().trim_start_matches('\u{feff}').to_ascii_lowercase())
+        .collect();
+    let date_idx = headers
+        .iter()
+        .position(|h| DATE_HEADERS.contains(&h.as_str()))
+        .ok_or_else(|| AppError::FieldMappingMissing("CSV缺少日期列(date/日期)".to_string()))?;
+    let temp_idx = headers
+        .iter()
+        .position(|h| TEMP_HEADERS.contains(&h.as_str()))
+        .ok_or_else(|| {
+            AppError::FieldMappingMissing("CSV缺少气温列(avg_temp/平均气温)".to_string())
+        })?;
+
+    let mut result = AmbientTemperatureImportResult::default();
+    let mut parsed: Vec<(String, f64)> = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let line_no = idx + 2;
+        result.total_rows += 1;
+        let record = match record {
+            Ok(v) => v,
+            Err(err) => {
+                result.skipped_rows += 1;
+                result
+                    .warnings
+                    .push(format!("第{}行读取失败: {}", line_no, err));
+                continue;
+            }
+        };
+        let raw_date = record.get(date_idx).unwrap_or("").trim();
+        let raw_temp = record.get(temp_idx).unwrap_or("").trim();
+        let date = ["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d"]
+            .iter()
+            .find_map(|fmt| chrono::NaiveDate::parse_from_str(raw_date, fmt).ok());
+        let temp = raw_temp.parse::<f64>().ok().filter(|v| v.is_finite());
+        match (date, temp) {
+            (Some(d), Some(t)) => parsed.push((d.format("%Y-%m-%d").to_string(), t)),
+            _ => {
+                result.skipped_rows += 1;
+                result.warnings.push(format!(
+                    "第{}行格式无效: 日期='{}', 气温='{}'",
+                    line_no, raw_date, raw_temp
+                ));
+            }
+        }
+    }
+
+    let source = std::path::Path::new(&file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_string());
+
+    let db = get_db();
+    let tx = db.begin().await?;
+    for (record_date, avg_temp) in parsed {
+        let existing = AmbientTemperature::find()
+            .filter(ambient_temperature::Column::RecordDate.eq(record_date.clone()))
+            .one(&tx)
+            .await?;
+        if let Some(row) = existing {
+            let mut active: ambient_temperature::ActiveModel = row.into();
+            active.avg_temp = Set(avg_temp);
+            active.source = Set(source.clone());
+            active.update(&tx).await?;
+        } else {
+            let active = ambient_temperature::ActiveModel {
+                record_date: Set(record_date),
+                avg_temp: Set(avg_temp),
+                source: Set(source.clone()),
+                created_at: Set(Some(chrono::Utc::now())),
+                ..Default::default()
+            };
+            active.insert(&tx).await?;
+        }
+        result.imported += 1;
+    }
+    tx.commit().await?;
+
+    write_operation_log(
+        "import_ambient_temperature_csv",
+        Some("ambient_temperature"),
+        None,
+        Some(format!(
+            "导入日气温: 成功 {} 行，跳过 {} 行",
+            result.imported, result.skipped_rows
+        )),
+    )
+    .await;
+
+    Ok(result)
+}
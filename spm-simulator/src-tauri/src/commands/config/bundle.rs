@@ -0,0 +1,84 @@
+use super::types::write_operation_log;
+use crate::services::config_bundle_service;
+use crate::AppError;
+use std::collections::{BTreeMap, HashMap};
+
+/// 导出配置包，返回各分区记录数
+#[tauri::command]
+pub async fn export_config_bundle(
+    file_path: String,
+    sections: Option<Vec<String>>,
+) -> Result<BTreeMap<String, usize>, AppError> {
+    use crate::db::get_db;
+
+    let db = get_db();
+    let bundle = config_bundle_service::build_bundle(db, sections.as_deref()).await?;
+    let counts: BTreeMap<String, usize> = bundle
+        .sections
+        .iter()
+        .map(|(name, rows)| (name.clone(), rows.len()))
+        .collect();
+
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| AppError::DataConversionError(format!("序列化失败: {}", e)))?;
+    std::fs::write(&file_path, json)?;
+
+    write_operation_log(
+        "export",
+        Some("config_bundle"),
+        None,
+        Some(format!(
+            "导出配置包 v{}: {} 个分区 -> {}",
+            bundle.version,
+            counts.len(),
+            file_path
+        )),
+    )
+    .await;
+
+    Ok(counts)
+}
+
+/// 导入配置包；dry_run 时只返回差异。modes 为分区 -> skip/merge/replace，默认 merge
+#[tauri::command]
+pub async fn import_config_bundle(
+    file_path: String,
+    modes: Option<HashMap<String, String>>,
+    dry_run: bool,
+) -> Result<config_bundle_service::ConfigBundleReport, AppError> {
+    use crate::db::get_db;
+
+    let db = get_db();
+    let content = std::fs::read_to_string(&file_path)?;
+    let bundle = config_bundle_service::parse_bundle(&content)?;
+    let report =
+        config_bundle_service::import_bundle(db, &bundle, &modes.unwrap_or_default(), dry_run)
+            .await?;
+
+    if report.applied {
+        let summary: Vec<String> = report
+            .sections
+            .iter()
+            .filter(|s| s.added + s.updated + s.removed > 0)
+            .map(|s| format!("{}+{}~{}-{}", s.label, s.added, s.updated, s.removed))
+            .collect();
+        write_operation_log(
+            "import",
+            Some("config_bundle"),
+            None,
+            Some(format!(
+                "导入配置包 v{} <- {}: {}",
+                report.version,
+                file_path,
+                if summary.is_empty() {
+                    "无变化".to_string()
+                } else {
+                    summary.join(", ")
+                }
+            )),
+        )
+        .await;
+    }
+
+    Ok(report)
+}
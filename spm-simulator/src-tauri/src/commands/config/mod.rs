@@ -1,19 +1,25 @@
+mod ambient;
+mod bundle;
 mod maintenance;
 mod priority_batch;
 mod priority_customer;
 mod priority_dimension;
 mod priority_io;
+mod priority_preview;
 mod priority_product;
 mod priority_weight;
 mod strategy;
 mod system;
 mod types;
 
+pub use ambient::*;
+pub use bundle::*;
 pub use maintenance::*;
 pub use priority_batch::*;
 pub use priority_customer::*;
 pub use priority_dimension::*;
 pub use priority_io::*;
+pub use priority_preview::*;
 pub use priority_product::*;
 pub use priority_weight::*;
 pub use strategy::*;
@@ -0,0 +1,86 @@
+use super::types::PriorityWhatIfInput;
+use crate::engine::constants::DEFAULT_ROLL_TONNAGE_THRESHOLD;
+use crate::engine::priority::{self, PriorityContext, PriorityWhatIfReport};
+use crate::AppError;
+
+/// 优先级权重预演：按拟定权重/维度分在内存中重算，不写库
+#[tauri::command]
+pub async fn preview_priority_weights(
+    input: PriorityWhatIfInput,
+) -> Result<PriorityWhatIfReport, AppError> {
+    use crate::db::get_db;
+    use crate::models::material::{self, Entity as Material};
+    use sea_orm::*;
+
+    let db = get_db();
+    let baseline = priority::load_priority_context(db).await?;
+    let proposed = apply_what_if_overrides(&baseline, &input)?;
+
+    let materials = Material::find()
+        .filter(material::Column::Status.eq("pending"))
+        .all(db)
+        .await?;
+
+    let tonnage_window = input
+        .tonnage_window
+        .filter(|v| v.is_finite() && *v > 0.0)
+        .unwrap_or(DEFAULT_ROLL_TONNAGE_THRESHOLD);
+
+    Ok(priority::simulate_priority_change(
+        &materials,
+        &baseline,
+        &proposed,
+        input.top_n.unwrap_or(20).clamp(1, 500),
+        tonnage_window,
+        input.bucket_size.unwrap_or(100).max(1),
+    ))
+}
+
+fn apply_what_if_overrides(
+    baseline: &PriorityContext,
+    input: &PriorityWhatIfInput,
+) -> Result<PriorityContext, AppError> {
+    let mut ctx = baseline.clone();
+
+    if let Some(weights) = &input.weights {
+        for (dimension_type, &weight) in weights {
+            if !weight.is_finite() || !(0.0..=1.0).contains(&weight) {
+                return Err(AppError::ConstraintViolation(format!(
+                    "权重必须在 0.0~1.0 之间: {}={}",
+                    dimension_type, weight
+                )));
+            }
+            match dimension_type.trim() {
+                "assessment" => ctx.weights.assessment = weight,
+                "delivery" => ctx.weights.delivery = weight,
+                "contract" => ctx.weights.contract = weight,
+                "customer" => ctx.weights.customer = weight,
+                "batch" => ctx.weights.batch = weight,
+                "product_type" => ctx.weights.product_type = weight,
+                other => {
+                    return Err(AppError::InvalidInput(format!("未知的权重维度: {}", other)));
+                }
+            }
+        }
+    }
+
+    for item in input.dimension_scores.iter().flatten() {
+        let code = item.code.trim().to_string();
+        if code.is_empty() {
+            return Err(AppError::InvalidInput("维度编码不能为空".to_string()));
+        }
+        let target = match item.dimension_type.trim() {
+            "delivery" => &mut ctx.delivery_scores,
+            "contract" => &mut ctx.contract_scores,
+            "customer" => &mut ctx.customer_scores,
+            "batch" => &mut ctx.batch_scores,
+            "product_type" => &mut ctx.product_type_scores,
+            other => {
+                return Err(AppError::InvalidInput(format!("未知的维度类型: {}", other)));
+            }
+        };
+        target.insert(code, item.score);
+    }
+
+    Ok(ctx)
+}
@@ -1,6 +1,8 @@
 use super::types::{
     write_operation_log, CreateStrategyInput, StrategyTemplateFile, UpdateStrategyInput,
+    ValidateStrategyInput,
 };
+use crate::services::{strategy_revision_service, strategy_schema_service};
 use crate::AppError;
 
 #[tauri::command]
@@ -28,6 +30,13 @@ pub async fn create_strategy_template(
     use sea_orm::*;
 
     let db = get_db();
+    strategy_schema_service::ensure_valid(
+        &input.sort_weights,
+        &input.constraints,
+        input.soft_constraints.as_deref(),
+        &input.eval_weights,
+        &input.temper_rules,
+    )?;
 
     let template = strategy_template::ActiveModel {
         name: Set(input.name),
@@ -42,7 +51,11 @@ pub async fn create_strategy_template(
         ..Default::default()
     };
 
-    let result = template.insert(db).await?;
+    // 模板与初始修订同事务写入，避免出现没有修订的模板
+    let tx = db.begin().await?;
+    let result = template.insert(&tx).await?;
+    strategy_revision_service::record_revision(&tx, &result, Some("初始版本".to_string())).await?;
+    tx.commit().await?;
 
     write_operation_log(
         "create",
@@ -71,6 +84,10 @@ pub async fn update_strategy_template(
         .ok_or(AppError::Internal("策略模板不存在".to_string()))?;
     let old_name = template.name.clone();
 
+    let tx = db.begin().await?;
+    // 修改前的内容保留为修订，修改后再生成新修订
+    strategy_revision_service::ensure_current_revision(&tx, &template).await?;
+
     let mut active: crate::models::strategy_template::ActiveModel = template.into();
 
     if let Some(name) = input.name {
@@ -99,7 +116,18 @@ pub async fn update_strategy_template(
     }
 
     active.updated_at = Set(Some(chrono::Utc::now()));
-    let result = active.update(db).await?;
+    let result = active.update(&tx).await?;
+    // 校验合并后的完整内容，失败时事务回滚
+    strategy_schema_service::ensure_valid(
+        &result.sort_weights,
+        &result.constraints,
+        result.soft_constraints.as_deref(),
+        &result.eval_weights,
+        &result.temper_rules,
+    )?;
+    let change_note = input.change_note.filter(|v| !v.trim().is_empty());
+    strategy_revision_service::revise_if_changed(&tx, &result, change_note).await?;
+    tx.commit().await?;
 
     write_operation_log(
         "update",
@@ -178,6 +206,19 @@ pub async fn set_default_strategy(
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn validate_strategy_config(
+    input: ValidateStrategyInput,
+) -> Result<strategy_schema_service::StrategyValidationReport, AppError> {
+    Ok(strategy_schema_service::validate_strategy(
+        &input.sort_weights,
+        &input.constraints,
+        input.soft_constraints.as_deref(),
+        &input.eval_weights,
+        &input.temper_rules,
+    ))
+}
+
 #[tauri::command]
 pub async fn export_strategy_template(id: i32, file_path: String) -> Result<(), AppError> {
     use crate::db::get_db;
@@ -229,10 +270,18 @@ pub async fn import_strategy_template(
     let input: StrategyTemplateFile = serde_json::from_str(&content)
         .map_err(|e| AppError::DataConversionError(format!("JSON格式错误: {}", e)))?;
     let imported_name = input.name.clone();
-
+    strategy_schema_service::ensure_valid(
+        &input.sort_weights,
+        &input.constraints,
+        input.soft_constraints.as_deref(),
+        &input.eval_weights,
+        &input.temper_rules,
+    )?;
+
+    let tx = db.begin().await?;
     let exists = Template::find()
         .filter(strategy_template::Column::Name.eq(input.name.clone()))
-        .one(db)
+        .one(&tx)
         .await?;
     if exists.is_some() {
         return Err(AppError::TemplateDuplicate(input.name));
@@ -251,7 +300,10 @@ pub async fn import_strategy_template(
         ..Default::default()
     };
 
-    let result = active.insert(db).await?;
+    let result = active.insert(&tx).await?;
+    strategy_revision_service::record_revision(&tx, &result, Some(format!("导入自 {}", file_path)))
+        .await?;
+    tx.commit().await?;
 
     write_operation_log(
         "import",
@@ -263,3 +315,121 @@ pub async fn import_strategy_template(
 
     Ok(result)
 }
+
+#[tauri::command]
+pub async fn get_strategy_revisions(
+    strategy_id: i32,
+) -> Result<Vec<crate::models::strategy_revision::Model>, AppError> {
+    use crate::db::get_db;
+    use crate::models::strategy_template::Entity as Template;
+    use sea_orm::*;
+
+    let db = get_db();
+    let template = Template::find_by_id(strategy_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::Internal("策略模板不存在".to_string()))?;
+    // 旧模板首次查看时补记初始修订
+    strategy_revision_service::ensure_current_revision(db, &template).await?;
+
+    strategy_revision_service::list_revisions(db, strategy_id).await
+}
+
+#[tauri::command]
+pub async fn diff_strategy_revisions(
+    strategy_id: i32,
+    from_revision_id: i32,
+    to_revision_id: i32,
+) -> Result<strategy_revision_service::StrategyRevisionDiff, AppError> {
+    use crate::db::get_db;
+
+    let db = get_db();
+    let from = strategy_revision_service::find_revision(db, strategy_id, from_revision_id).await?;
+    let to = strategy_revision_service::find_revision(db, strategy_id, to_revision_id).await?;
+
+    Ok(strategy_revision_service::diff_revisions(&from, &to))
+}
+
+#[tauri::command]
+pub async fn export_strategy_revision_diff(
+    strategy_id: i32,
+    from_revision_id: i32,
+    to_revision_id: i32,
+    file_path: String,
+) -> Result<usize, AppError> {
+    let diff = diff_strategy_revisions(strategy_id, from_revision_id, to_revision_id).await?;
+    let to_cell = |value: &Option<serde_json::Value>| {
+        value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+    };
+
+    let path = std::path::PathBuf::from(&file_path);
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let mut writer = csv::Writer::from_path(&file_path)
+        .map_err(|e| AppError::DataConversionError(format!("创建CSV失败: {}", e)))?;
+    writer
+        .write_record(["section", "path", "kind", "before", "after"])
+        .map_err(|e| AppError::DataConversionError(format!("写CSV表头失败: {}", e)))?;
+    for change in &diff.changes {
+        writer
+            .write_record([
+                change.section.as_str(),
+                change.path.as_str(),
+                change.kind.as_str(),
+                &to_cell(&change.before),
+                &to_cell(&change.after),
+            ])
+            .map_err(|e| AppError::DataConversionError(format!("写CSV数据失败: {}", e)))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| AppError::DataConversionError(format!("写CSV失败: {}", e)))?;
+
+    write_operation_log(
+        "export",
+        Some("strategy_template"),
+        Some(strategy_id),
+        Some(format!(
+            "导出策略修订差异: r{} -> r{}, {} 项 -> {}",
+            diff.from_revision_no,
+            diff.to_revision_no,
+            diff.changes.len(),
+            file_path
+        )),
+    )
+    .await;
+
+    Ok(diff.changes.len())
+}
+
+#[tauri::command]
+pub async fn restore_strategy_revision(
+    strategy_id: i32,
+    revision_id: i32,
+) -> Result<crate::models::strategy_template::Model, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    let tx = db.begin().await?;
+    let (restored, revision) =
+        strategy_revision_service::restore_revision(&tx, strategy_id, revision_id).await?;
+    tx.commit().await?;
+
+    write_operation_log(
+        "update",
+        Some("strategy_template"),
+        Some(strategy_id),
+        Some(format!(
+            "恢复策略模板 {}: {}",
+            restored.name,
+            revision.change_note.as_deref().unwrap_or_default()
+        )),
+    )
+    .await;
+
+    Ok(restored)
+}
@@ -160,3 +160,62 @@ async fn priority_template_and_data_export_should_work() {
     let _ = std::fs::remove_file(&csv_data_path);
     let _ = std::fs::remove_file(&xlsx_data_path);
 }
+
+#[tokio::test]
+async fn strategy_create_and_import_record_initial_revision() {
+    use crate::services::strategy_revision_service;
+    use sea_orm::EntityTrait;
+
+    ensure_test_db_initialized().await;
+    let db = crate::db::get_db();
+    let seed = unique_suffix();
+    let base = crate::models::strategy_template::Entity::find()
+        .one(db)
+        .await
+        .unwrap()
+        .expect("缺少默认策略模板");
+
+    // 模板与初始修订同事务写入（单连接池下事务内不得再走全局连接）
+    let created = create_strategy_template(CreateStrategyInput {
+        name: format!("新建策略-{}", seed),
+        description: None,
+        sort_weights: base.sort_weights.clone(),
+        constraints: base.constraints.clone(),
+        soft_constraints: base.soft_constraints.clone(),
+        eval_weights: base.eval_weights.clone(),
+        temper_rules: base.temper_rules.clone(),
+    })
+    .await
+    .expect("创建策略模板失败");
+    let revisions = strategy_revision_service::list_revisions(db, created.id)
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 1);
+
+    let json_path = std::env::temp_dir().join(format!("strategy_import_{}.json", seed));
+    let file = serde_json::json!({
+        "name": format!("导入策略-{}", seed),
+        "description": null,
+        "sort_weights": base.sort_weights,
+        "constraints": base.constraints,
+        "soft_constraints": base.soft_constraints,
+        "eval_weights": base.eval_weights,
+        "temper_rules": base.temper_rules,
+    });
+    std::fs::write(&json_path, file.to_string()).expect("写入策略文件失败");
+    let path = json_path.to_string_lossy().to_string();
+    let imported = import_strategy_template(path.clone())
+        .await
+        .expect("导入策略模板失败");
+    let revisions = strategy_revision_service::list_revisions(db, imported.id)
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 1);
+    // 重名检查与写入在同一事务内
+    assert!(matches!(
+        import_strategy_template(path).await,
+        Err(crate::AppError::TemplateDuplicate(_))
+    ));
+
+    let _ = std::fs::remove_file(&json_path);
+}
@@ -47,6 +47,18 @@ pub struct UpdateStrategyInput {
     pub soft_constraints: Option<String>,
     pub eval_weights: Option<String>,
     pub temper_rules: Option<String>,
+    /// 修订说明
+    pub change_note: Option<String>,
+}
+
+/// 策略配置校验（编辑中的五个 JSON 部分）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidateStrategyInput {
+    pub sort_weights: String,
+    pub constraints: String,
+    pub soft_constraints: Option<String>,
+    pub eval_weights: String,
+    pub temper_rules: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -140,6 +152,33 @@ pub struct PriorityConfigImportResult {
     pub warnings: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AmbientTemperatureImportResult {
+    pub total_rows: usize,
+    pub imported: usize,
+    pub skipped_rows: usize,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriorityScoreOverride {
+    /// delivery / contract / customer / batch / product_type
+    pub dimension_type: String,
+    pub code: String,
+    pub score: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PriorityWhatIfInput {
+    /// dimension_type → 拟定权重（0.0~1.0），未给出的维度沿用当前配置
+    pub weights: Option<HashMap<String, f64>>,
+    pub dimension_scores: Option<Vec<PriorityScoreOverride>>,
+    pub top_n: Option<usize>,
+    /// 前 N 吨窗口，默认取换辊吨位阈值
+    pub tonnage_window: Option<f64>,
+    pub bucket_size: Option<i32>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StrategyTemplateFile {
     pub name: String,
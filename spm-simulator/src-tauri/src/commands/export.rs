@@ -65,6 +65,56 @@ pub async fn export_plan_csv(
     })
 }
 
+#[tauri::command]
+pub async fn export_crane_pick_list(
+    plan_id: i32,
+    file_path: String,
+) -> Result<ExportResult, AppError> {
+    log::info!("导出行车吊运清单: plan_id={}, path={}", plan_id, file_path);
+    let row_count = export_service::export_crane_pick_list(plan_id, &file_path).await?;
+    Ok(ExportResult {
+        row_count,
+        file_path,
+    })
+}
+
+/// 导出方案 HTML 报告（单文件，内联样式与 SVG 图表）
+#[tauri::command]
+pub async fn export_plan_html_report(
+    plan_id: i32,
+    file_path: String,
+    forecast_days: Option<i32>,
+) -> Result<ExportResult, AppError> {
+    log::info!("导出方案HTML报告: plan_id={}, path={}", plan_id, file_path);
+    let data = crate::commands::schedule::load_plan_report_data(plan_id, forecast_days).await?;
+    let html = crate::services::report_service::render_plan_report(&data);
+
+    let path = std::path::PathBuf::from(&file_path);
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    std::fs::write(&path, html)?;
+
+    let row_count = data.coils.len();
+    write_operation_log(
+        "export_plan_html_report",
+        Some("plan"),
+        Some(plan_id),
+        Some(format!(
+            "导出方案HTML报告: rows={} path={}",
+            row_count, file_path
+        )),
+    )
+    .await;
+
+    Ok(ExportResult {
+        row_count,
+        file_path,
+    })
+}
+
 #[tauri::command]
 pub async fn export_materials_excel(
     file_path: String,
@@ -1,3 +1,6 @@
+use crate::services::import_validation_service::CompiledRules;
+use crate::services::mapping_suggest_service::{self, MappingSuggestionResult};
+use crate::services::source_reader_service::{self, SourceReadOptions};
 use crate::AppError;
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +10,18 @@ pub struct CreateFieldMappingInput {
     pub source_type: String,
     pub mappings: String,
     pub value_transforms: Option<String>,
+    /// 导入校验规则（JSON 数组）
+    pub validation_rules: Option<String>,
+    /// 工作表名称或序号（1 起始）
+    pub sheet: Option<String>,
+    /// 表头行（1 起始）
+    pub header_row: Option<i32>,
+    /// 数据起始行（1 起始）
+    pub data_start_row: Option<i32>,
+    /// CSV 分隔符（auto 自动识别）
+    pub csv_delimiter: Option<String>,
+    /// CSV 编码（auto / utf-8 / gbk）
+    pub csv_encoding: Option<String>,
     pub is_default: Option<bool>,
 }
 
@@ -16,6 +31,18 @@ pub struct UpdateFieldMappingInput {
     pub source_type: Option<String>,
     pub mappings: Option<String>,
     pub value_transforms: Option<String>,
+    /// 导入校验规则（JSON 数组）
+    pub validation_rules: Option<String>,
+    /// 工作表名称或序号（1 起始）
+    pub sheet: Option<String>,
+    /// 表头行（1 起始）
+    pub header_row: Option<i32>,
+    /// 数据起始行（1 起始）
+    pub data_start_row: Option<i32>,
+    /// CSV 分隔符（auto 自动识别）
+    pub csv_delimiter: Option<String>,
+    /// CSV 编码（auto / utf-8 / gbk）
+    pub csv_encoding: Option<String>,
     pub is_default: Option<bool>,
 }
 
@@ -25,6 +52,19 @@ pub struct FilePreviewResult {
     pub headers: Vec<String>,
     pub sample_rows: Vec<Vec<String>>,
     pub total_rows: usize,
+    /// 文件内全部工作表（CSV 为空）
+    pub sheet_names: Vec<String>,
+    /// 实际读取的工作表
+    pub sheet_name: Option<String>,
+    /// 识别出的 CSV 编码
+    pub encoding: Option<String>,
+    /// 识别出的 CSV 分隔符
+    pub delimiter: Option<String>,
+}
+
+/// 空字符串视为未设置
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
 }
 
 #[tauri::command]
@@ -71,6 +111,8 @@ pub async fn create_field_mapping(
         serde_json::from_str::<serde_json::Value>(transforms)
             .map_err(|e| AppError::DataConversionError(format!("转换规则JSON格式错误: {}", e)))?;
     }
+    CompiledRules::parse(input.validation_rules.as_deref())?;
+    source_reader_service::validate_read_rows(input.header_row, input.data_start_row)?;
 
     let db = get_db();
 
@@ -79,6 +121,12 @@ pub async fn create_field_mapping(
         source_type: Set(input.source_type),
         mappings: Set(input.mappings),
         value_transforms: Set(input.value_transforms),
+        validation_rules: Set(non_empty(input.validation_rules)),
+        sheet: Set(non_empty(input.sheet)),
+        header_row: Set(input.header_row),
+        data_start_row: Set(input.data_start_row),
+        csv_delimiter: Set(non_empty(input.csv_delimiter)),
+        csv_encoding: Set(non_empty(input.csv_encoding)),
         is_default: Set(input.is_default),
         ..Default::default()
     };
@@ -120,6 +168,28 @@ pub async fn update_field_mapping(
             .map_err(|e| AppError::DataConversionError(format!("转换规则JSON格式错误: {}", e)))?;
         active.value_transforms = Set(Some(transforms));
     }
+    if let Some(rules) = input.validation_rules {
+        // 空字符串表示清除规则
+        CompiledRules::parse(Some(&rules))?;
+        active.validation_rules = Set(non_empty(Some(rules)));
+    }
+    // 读取配置：空字符串表示清除；行号以合并后的值校验
+    if input.header_row.is_some() || input.data_start_row.is_some() {
+        let header_row = input.header_row.or(*active.header_row.as_ref());
+        let data_start_row = input.data_start_row.or(*active.data_start_row.as_ref());
+        source_reader_service::validate_read_rows(header_row, data_start_row)?;
+        active.header_row = Set(header_row);
+        active.data_start_row = Set(data_start_row);
+    }
+    if let Some(sheet) = input.sheet {
+        active.sheet = Set(non_empty(Some(sheet)));
+    }
+    if let Some(delimiter) = input.csv_delimiter {
+        active.csv_delimiter = Set(non_empty(Some(delimiter)));
+    }
+    if let Some(encoding) = input.csv_encoding {
+        active.csv_encoding = Set(non_empty(Some(encoding)));
+    }
     if let Some(is_default) = input.is_default {
         active.is_default = Set(Some(is_default));
     }
@@ -140,90 +210,94 @@ pub async fn delete_field_mapping(id: i32) -> Result<(), AppError> {
     Ok(())
 }
 
+/// 预览文件表头与样例行；未指定读取选项时使用映射模板上的配置
 #[tauri::command]
-pub async fn preview_file_headers(file_path: String) -> Result<FilePreviewResult, AppError> {
-    use calamine::{open_workbook, Data, Reader, Xlsx};
-    use std::path::Path;
-
-    let path = Path::new(&file_path);
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
-    match ext.as_str() {
-        "xlsx" | "xls" => {
-            let mut workbook: Xlsx<_> = open_workbook(&file_path)
-                .map_err(|e| AppError::FileFormatError(format!("无法打开Excel文件: {}", e)))?;
-
-            let sheet_name =
-                workbook.sheet_names().first().cloned().ok_or_else(|| {
-                    AppError::FileFormatError("Excel文件中没有工作表".to_string())
-                })?;
-
-            let range = workbook
-                .worksheet_range(&sheet_name)
-                .map_err(|e| AppError::FileFormatError(format!("无法读取工作表: {}", e)))?;
-
-            let rows: Vec<Vec<String>> = range
-                .rows()
-                .map(|row| {
-                    row.iter()
-                        .map(|cell| match cell {
-                            Data::String(v) => v.clone(),
-                            Data::Int(v) => v.to_string(),
-                            Data::Float(v) => v.to_string(),
-                            Data::Bool(v) => v.to_string(),
-                            _ => String::new(),
-                        })
-                        .collect()
-                })
-                .collect();
-
-            if rows.is_empty() {
-                return Err(AppError::FileFormatError("文件为空".to_string()));
-            }
-
-            let headers = rows[0].clone();
-            let sample_rows: Vec<Vec<String>> = rows[1..].iter().take(20).cloned().collect();
-            let total_rows = if rows.len() > 1 { rows.len() - 1 } else { 0 };
-
-            Ok(FilePreviewResult {
-                headers,
-                sample_rows,
-                total_rows,
-            })
-        }
-        "csv" => {
-            let mut reader = csv::Reader::from_path(&file_path)
-                .map_err(|e| AppError::FileFormatError(format!("无法打开CSV文件: {}", e)))?;
-
-            let headers: Vec<String> = reader
-                .headers()
-                .map_err(|e| AppError::FileFormatError(format!("无法读取CSV表头: {}", e)))?
-                .iter()
-                .map(|h| h.to_string())
-                .collect();
-
-            let mut sample_rows = Vec::new();
-            let mut total_rows = 0usize;
-            for r in reader.records().flatten() {
-                total_rows += 1;
-                if sample_rows.len() < 20 {
-                    sample_rows.push(r.iter().map(|f| f.to_string()).collect());
-                }
-            }
-
-            Ok(FilePreviewResult {
-                headers,
-                sample_rows,
-                total_rows,
-            })
-        }
-        _ => Err(AppError::FileFormatError(format!(
-            "不支持的文件格式: .{}",
-            ext
-        ))),
+pub async fn preview_file_headers(
+    file_path: String,
+    mapping_id: Option<i32>,
+    read_options: Option<SourceReadOptions>,
+) -> Result<FilePreviewResult, AppError> {
+    use crate::db::get_db;
+    use crate::models::field_mapping::Entity as Mapping;
+    use sea_orm::*;
+
+    let template_options = match mapping_id {
+        Some(id) => Mapping::find_by_id(id)
+            .one(get_db())
+            .await?
+            .map(|m| SourceReadOptions::from(&m))
+            .unwrap_or_default(),
+        None => SourceReadOptions::default(),
+    };
+    let options = read_options.unwrap_or_default().or(template_options);
+    let source = source_reader_service::read_source(&file_path, &options)?;
+
+    Ok(FilePreviewResult {
+        headers: source.headers,
+        sample_rows: source.rows.iter().take(20).cloned().collect(),
+        total_rows: source.rows.len(),
+        sheet_names: source.sheet_names,
+        sheet_name: source.sheet_name,
+        encoding: source.encoding,
+        delimiter: source.delimiter.map(|d| d.to_string()),
+    })
+}
+
+/// 按表头相似度与列值特征生成映射建议
+#[tauri::command]
+pub async fn suggest_field_mappings(
+    file_path: String,
+    read_options: Option<SourceReadOptions>,
+    min_confidence: Option<f64>,
+) -> Result<MappingSuggestionResult, AppError> {
+    let source = source_reader_service::read_source(&file_path, &read_options.unwrap_or_default())?;
+    Ok(mapping_suggest_service::suggest_mappings(
+        &source.headers,
+        &source.rows,
+        min_confidence.unwrap_or(mapping_suggest_service::DEFAULT_MIN_CONFIDENCE),
+    ))
+}
+
+/// 按映射建议直接创建模板（连同读取选项一并保存）
+#[tauri::command]
+pub async fn create_field_mapping_from_suggestions(
+    file_path: String,
+    template_name: String,
+    read_options: Option<SourceReadOptions>,
+    min_confidence: Option<f64>,
+) -> Result<crate::models::field_mapping::Model, AppError> {
+    let read_options = read_options.unwrap_or_default();
+    let source = source_reader_service::read_source(&file_path, &read_options)?;
+    let result = mapping_suggest_service::suggest_mappings(
+        &source.headers,
+        &source.rows,
+        min_confidence.unwrap_or(mapping_suggest_service::DEFAULT_MIN_CONFIDENCE),
+    );
+    if result.mappings.is_empty() {
+        return Err(AppError::FieldMappingMissing(
+            "未能从文件表头推断出任何字段映射".to_string(),
+        ));
     }
+    let mappings = serde_json::to_string(&result.mappings)
+        .map_err(|e| AppError::DataConversionError(format!("映射配置序列化失败: {}", e)))?;
+    let source_type = if source.sheet_name.is_some() {
+        "excel"
+    } else {
+        "csv"
+    };
+
+    create_field_mapping(CreateFieldMappingInput {
+        template_name,
+        source_type: source_type.to_string(),
+        mappings,
+        value_transforms: None,
+        validation_rules: None,
+        sheet: read_options.sheet,
+        header_row: read_options.header_row.map(|r| r as i32),
+        data_start_row: read_options.data_start_row.map(|r| r as i32),
+        csv_delimiter: read_options.csv_delimiter,
+        csv_encoding: read_options.csv_encoding,
+        is_default: Some(false),
+    })
+    .await
 }
@@ -1,3 +1,4 @@
+use crate::services::source_reader_service::SourceReadOptions;
 use crate::utils::log::write_operation_log as write_log_full;
 use crate::AppError;
 use sea_orm::prelude::Expr;
@@ -37,6 +38,9 @@ pub struct ImportResult {
     pub failed: usize,
     pub skipped: usize,
     pub overwritten: usize,
+    pub new_count: usize,
+    pub duplicate_count: usize,
+    pub changed_count: usize,
     pub errors: Vec<String>,
 }
 
@@ -52,22 +56,67 @@ async fn write_operation_log(action: &str, detail: Option<String>, target_id: Op
     write_log_full("material", action, Some("material"), target_id, detail).await;
 }
 
+/// 批量人工更新后，对比更新前后的指定字段写入变更历史
+async fn record_manual_changes<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    before: &[crate::models::material::Model],
+    column: crate::models::material::Column,
+    action: &str,
+) -> Result<(), AppError> {
+    use crate::models::material::Entity as Material;
+    use crate::services::material_history_service;
+    use sea_orm::*;
+
+    let after: std::collections::HashMap<i32, crate::models::material::Model> = Material::find()
+        .filter(crate::models::material::Column::Id.is_in(before.iter().map(|m| m.id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.id, m))
+        .collect();
+    let records: Vec<_> = before
+        .iter()
+        .filter_map(|old| after.get(&old.id).map(|new| (old, new)))
+        .flat_map(|(old, new)| material_history_service::diff_models(old, new, &[column]))
+        .collect();
+    material_history_service::record_changes(
+        db,
+        &records,
+        material_history_service::SOURCE_MANUAL,
+        Some(action),
+    )
+    .await
+}
+
 #[tauri::command]
 pub async fn import_materials(
     file_path: String,
     mapping_id: Option<i32>,
     conflict_mode: Option<String>,
+    all_or_nothing: Option<bool>,
+    merge_policy: Option<String>,
+    read_options: Option<SourceReadOptions>,
 ) -> Result<ImportResult, AppError> {
     let mode = conflict_mode.as_deref().unwrap_or("skip");
+    let all_or_nothing = all_or_nothing.unwrap_or(false);
+    let options = crate::services::import_service::ImportOptions {
+        conflict_mode: mode.to_string(),
+        all_or_nothing,
+        merge_policy: crate::services::import_service::MergePolicy::parse(merge_policy.as_deref())?,
+        read_options: read_options.unwrap_or_default(),
+    };
     log::info!(
-        "Importing materials from: {}, mapping_id: {:?}, conflict_mode: {}",
+        "Importing materials from: {}, mapping_id: {:?}, conflict_mode: {}, all_or_nothing: {}",
         file_path,
         mapping_id,
-        mode
+        mode,
+        all_or_nothing
     );
 
-    let summary =
-        crate::services::import_service::import_from_excel(&file_path, mapping_id, mode).await?;
+    let summary = crate::services::import_service::import_file_with_source(
+        &file_path, mapping_id, &options, None,
+    )
+    .await?;
 
     let result = ImportResult {
         batch_id: summary.batch_id,
@@ -76,14 +125,26 @@ pub async fn import_materials(
         failed: summary.failed,
         skipped: summary.skipped,
         overwritten: summary.overwritten,
+        new_count: summary.new_count,
+        duplicate_count: summary.duplicate_count,
+        changed_count: summary.changed_count,
         errors: summary.errors,
     };
 
     write_operation_log(
         "import",
         Some(format!(
-            "导入材料: 文件={}, mode={}, 成功={}, 跳过={}, 覆盖={}, 失败={}",
-            file_path, mode, result.success, result.skipped, result.overwritten, result.failed
+            "导入材料: 文件={}, mode={}, 整批={}, 新增={}, 变化={}, 重复={}, 成功={}, 跳过={}, 覆盖={}, 失败={}",
+            file_path,
+            mode,
+            all_or_nothing,
+            result.new_count,
+            result.changed_count,
+            result.duplicate_count,
+            result.success,
+            result.skipped,
+            result.overwritten,
+            result.failed
         )),
         None,
     )
@@ -92,6 +153,25 @@ pub async fn import_materials(
     Ok(result)
 }
 
+/// 预览导入对已存在钢卷的字段变化（from → to），不写库
+#[tauri::command]
+pub async fn preview_material_import(
+    file_path: String,
+    mapping_id: Option<i32>,
+    conflict_mode: Option<String>,
+    merge_policy: Option<String>,
+    read_options: Option<SourceReadOptions>,
+) -> Result<crate::services::import_service::ImportChangePreview, AppError> {
+    use crate::services::import_service::{self, ImportOptions, MergePolicy};
+
+    let options = ImportOptions {
+        merge_policy: MergePolicy::parse(merge_policy.as_deref())?,
+        read_options: read_options.unwrap_or_default(),
+        ..ImportOptions::new(conflict_mode.as_deref().unwrap_or("merge"))
+    };
+    import_service::preview_import_changes(&file_path, mapping_id, &options).await
+}
+
 #[tauri::command]
 pub async fn test_import_materials(
     file_path: String,
@@ -99,12 +179,16 @@ pub async fn test_import_materials(
     mappings_json: Option<String>,
     value_transforms: Option<String>,
     sample_limit: Option<usize>,
+    validation_rules: Option<String>,
+    read_options: Option<SourceReadOptions>,
 ) -> Result<crate::services::import_service::ImportTestResult, AppError> {
     let result = crate::services::import_service::test_import_from_file(
         &file_path,
         mapping_id,
         mappings_json.as_deref(),
         value_transforms.as_deref(),
+        validation_rules.as_deref(),
+        read_options.as_ref(),
         sample_limit,
     )
     .await?;
@@ -122,6 +206,41 @@ pub async fn test_import_materials(
     Ok(result)
 }
 
+/// 导出导入文件的行级错误工作簿（规则与 test_import_materials 相同）
+#[tauri::command]
+pub async fn export_import_error_workbook(
+    file_path: String,
+    output_path: String,
+    mapping_id: Option<i32>,
+    mappings_json: Option<String>,
+    value_transforms: Option<String>,
+    validation_rules: Option<String>,
+    read_options: Option<SourceReadOptions>,
+) -> Result<crate::services::import_service::ImportErrorWorkbook, AppError> {
+    let result = crate::services::import_service::export_import_error_workbook(
+        &file_path,
+        mapping_id,
+        mappings_json.as_deref(),
+        value_transforms.as_deref(),
+        validation_rules.as_deref(),
+        read_options.as_ref(),
+        &output_path,
+    )
+    .await?;
+
+    write_operation_log(
+        "import_error_export",
+        Some(format!(
+            "导出导入错误明细: 文件={}, 输出={}, total={}, error_rows={}",
+            file_path, output_path, result.total, result.error_rows
+        )),
+        None,
+    )
+    .await;
+
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_materials(
     filter: Option<MaterialFilter>,
@@ -192,45 +311,61 @@ pub async fn get_materials(
     })
 }
 
+/// 人工变更材料状态：冻结（frozen）、解冻（pending）或生产中材料人工完工（completed）
+///
+/// planned / in_production 由方案确认与生产实绩驱动，冻结与解冻须填写原因。
 #[tauri::command]
-pub async fn update_material_status(ids: Vec<i32>, status: String) -> Result<u64, AppError> {
-    use crate::db::get_db;
-    use crate::models::material::Entity as Material;
-    use sea_orm::*;
+pub async fn update_material_status(
+    ids: Vec<i32>,
+    status: String,
+    reason: Option<String>,
+) -> Result<u64, AppError> {
+    let affected = crate::services::material_lifecycle_service::apply_manual_status(
+        &ids,
+        &status,
+        reason.as_deref(),
+    )
+    .await?;
 
-    let valid_statuses = ["pending", "completed", "frozen"];
-    if !valid_statuses.contains(&status.as_str()) {
-        return Err(AppError::ConstraintViolation(format!(
-            "无效的材料状态: {}，允许值: {:?}",
-            status, valid_statuses
-        )));
-    }
+    write_operation_log(
+        "update",
+        Some(format!(
+            "批量更新材料状态为 {}: {} 条{}",
+            status,
+            affected,
+            reason
+                .as_deref()
+                .map(|r| format!(", 原因: {}", r))
+                .unwrap_or_default()
+        )),
+        None,
+    )
+    .await;
 
-    let db = get_db();
-    let result = Material::update_many()
-        .col_expr(
-            crate::models::material::Column::Status,
-            Expr::value(&status),
-        )
-        .col_expr(
-            crate::models::material::Column::UpdatedAt,
-            Expr::current_timestamp().into(),
-        )
-        .filter(crate::models::material::Column::Id.is_in(ids))
-        .exec(db)
-        .await?;
+    Ok(affected)
+}
+
+/// 接收生产实绩反馈（开工/完工/中止），驱动材料生命周期
+#[tauri::command]
+pub async fn report_production_feedback(
+    entries: Vec<crate::services::material_lifecycle_service::ProductionFeedback>,
+) -> Result<crate::services::material_lifecycle_service::FeedbackResult, AppError> {
+    let result =
+        crate::services::material_lifecycle_service::apply_production_feedback(&entries).await?;
 
     write_operation_log(
-        "update",
+        "production_feedback",
         Some(format!(
-            "批量更新材料状态为 {}: {} 条",
-            status, result.rows_affected
+            "生产实绩反馈: {} 条, 生效 {} 条, 失败 {} 条",
+            entries.len(),
+            result.applied,
+            result.errors.len()
         )),
         None,
     )
     .await;
 
-    Ok(result.rows_affected)
+    Ok(result)
 }
 
 #[tauri::command]
@@ -239,7 +374,11 @@ pub async fn update_material_priority(ids: Vec<i32>, priority: i32) -> Result<u6
     use crate::models::material::Entity as Material;
     use sea_orm::*;
 
-    let db = get_db();
+    let txn = get_db().begin().await?;
+    let before = Material::find()
+        .filter(crate::models::material::Column::Id.is_in(ids.clone()))
+        .all(&txn)
+        .await?;
     let result = Material::update_many()
         .col_expr(
             crate::models::material::Column::PriorityManualAdjust,
@@ -250,8 +389,16 @@ pub async fn update_material_priority(ids: Vec<i32>, priority: i32) -> Result<u6
             Expr::current_timestamp().into(),
         )
         .filter(crate::models::material::Column::Id.is_in(ids))
-        .exec(db)
+        .exec(&txn)
         .await?;
+    record_manual_changes(
+        &txn,
+        &before,
+        crate::models::material::Column::PriorityManualAdjust,
+        "update_material_priority",
+    )
+    .await?;
+    txn.commit().await?;
 
     write_operation_log(
         "update",
@@ -266,10 +413,55 @@ pub async fn update_material_priority(ids: Vec<i32>, priority: i32) -> Result<u6
     Ok(result.rows_affected)
 }
 
+/// 查询钢卷的字段变更时间线
+#[tauri::command]
+pub async fn get_material_history(
+    material_id: Option<i32>,
+    coil_id: Option<String>,
+    field: Option<String>,
+    limit: Option<u64>,
+) -> Result<Vec<crate::models::material_change_log::Model>, AppError> {
+    crate::services::material_history_service::get_timeline(
+        material_id,
+        coil_id.as_deref(),
+        field.as_deref(),
+        limit,
+    )
+    .await
+}
+
+/// 将选中的字段变更恢复为旧值
+#[tauri::command]
+pub async fn restore_material_fields(
+    change_ids: Vec<i32>,
+    force: Option<bool>,
+) -> Result<crate::services::material_history_service::RestoreFieldsResult, AppError> {
+    let result = crate::services::material_history_service::restore_field_values(
+        &change_ids,
+        force.unwrap_or(false),
+    )
+    .await?;
+
+    write_operation_log(
+        "restore_fields",
+        Some(format!(
+            "恢复材料字段: 选中 {} 条变更, 恢复 {} 个字段, 冲突 {} 个, 跳过 {} 个",
+            change_ids.len(),
+            result.restored,
+            result.conflicts.len(),
+            result.skipped.len()
+        )),
+        None,
+    )
+    .await;
+
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn refresh_temper_status() -> Result<RefreshResult, AppError> {
     let (total, tempered, waiting) =
-        crate::services::temp_service::refresh_all_temper_status().await?;
+        crate::services::temp_service::refresh_all_temper_status(crate::db::get_db()).await?;
 
     let result = RefreshResult {
         total,
@@ -429,6 +621,9 @@ pub async fn replace_all_materials(
         failed: summary.failed,
         skipped: summary.skipped,
         overwritten: summary.overwritten,
+        new_count: summary.new_count,
+        duplicate_count: summary.duplicate_count,
+        changed_count: summary.changed_count,
         errors: summary.errors,
     };
 
@@ -448,3 +643,56 @@ pub async fn replace_all_materials(
         import: import_result,
     })
 }
+
+/// 立即执行一次目录监控导入（使用当前 import_watch 配置，不要求已启用）
+#[tauri::command]
+pub async fn run_material_import_watch(
+) -> Result<crate::services::import_watch_service::ImportWatchRunResult, AppError> {
+    use crate::services::import_watch_service;
+
+    let config = import_watch_service::load_import_watch_config().await?;
+    import_watch_service::run_import_watch_once(&config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::get_db;
+    use crate::models::material;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    #[tokio::test]
+    async fn priority_update_records_history_in_one_transaction() {
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_priority_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+
+        let coil = material::ActiveModel {
+            coil_id: Set(format!("P{}", seed)),
+            steel_grade: Set("Q235".to_string()),
+            thickness: Set(2.5),
+            width: Set(1250.0),
+            weight: Set(20.0),
+            coiling_time: Set(chrono::Utc::now()),
+            status: Set(Some("pending".to_string())),
+            ..Default::default()
+        }
+        .insert(get_db())
+        .await
+        .expect("写入测试材料失败");
+
+        assert_eq!(update_material_priority(vec![coil.id], 3).await.unwrap(), 1);
+        let timeline = crate::services::material_history_service::get_timeline(
+            Some(coil.id),
+            None,
+            Some("priority_manual_adjust"),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0].new_value.as_deref(), Some("3"));
+    }
+}
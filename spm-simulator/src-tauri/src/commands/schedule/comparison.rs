@@ -1,4 +1,5 @@
 use super::risk::RiskViolationItem;
+use crate::engine::plan_compare::{self, CompareCoil, PlanPairAnalysis};
 use crate::utils::log::write_operation_log;
 use crate::AppError;
 use serde::{Deserialize, Serialize};
@@ -34,6 +35,8 @@ pub struct PlanComparisonResult {
     pub only_a_coils: Vec<String>,
     pub only_b_coils: Vec<String>,
     pub sequence_changes: Vec<SequenceChangeItem>,
+    /// 序列相似度、班次差异、换辊点对齐与交期结果差异
+    pub analysis: PlanPairAnalysis,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +54,7 @@ pub struct PlanOverlapItem {
     pub common_count: i32,
     pub only_a_count: i32,
     pub only_b_count: i32,
+    pub analysis: PlanPairAnalysis,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -127,6 +131,61 @@ pub fn build_comparison_side(
     }
 }
 
+/// 白班开始时刻，用于判断夜班跨零点后的计划完工时刻归属次日
+async fn load_day_start() -> Result<chrono::NaiveTime, AppError> {
+    use crate::db::get_db;
+    use crate::models::system_config;
+    use sea_orm::*;
+
+    let value = system_config::Entity::find()
+        .filter(system_config::Column::ConfigGroup.eq("shift"))
+        .filter(system_config::Column::ConfigKey.eq("day_start"))
+        .one(get_db())
+        .await?
+        .map(|c| c.config_value);
+    Ok(value
+        .and_then(|v| chrono::NaiveTime::parse_from_str(&v, "%H:%M").ok())
+        .unwrap_or_else(|| chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default()))
+}
+
+pub fn build_compare_coils(
+    items: &[crate::models::schedule_item::Model],
+    mat_map: &std::collections::HashMap<i32, &crate::models::material::Model>,
+    day_start: chrono::NaiveTime,
+) -> Vec<CompareCoil> {
+    use chrono::{Duration, NaiveDate, NaiveTime};
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let mat = mat_map.get(&item.material_id)?;
+            let planned_end = NaiveDate::parse_from_str(&item.shift_date, "%Y-%m-%d")
+                .ok()
+                .zip(
+                    item.planned_end
+                        .as_deref()
+                        .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M").ok()),
+                )
+                .map(|(date, end)| {
+                    // 夜班跨零点后的时刻归属次日
+                    if item.shift_type == "night" && end < day_start {
+                        (date + Duration::days(1)).and_time(end)
+                    } else {
+                        date.and_time(end)
+                    }
+                });
+            Some(CompareCoil {
+                coil_id: mat.coil_id.clone(),
+                weight: mat.weight,
+                shift_key: format!("{} {}", item.shift_date, item.shift_type),
+                is_roll_change: item.is_roll_change.unwrap_or(false),
+                planned_end,
+                due_date: mat.due_date.map(|d| d.naive_utc()),
+            })
+        })
+        .collect()
+}
+
 #[tauri::command]
 pub async fn compare_plans(
     plan_a_id: i32,
@@ -242,6 +301,12 @@ pub async fn compare_plans(
             .then_with(|| a.sequence_a.cmp(&b.sequence_a))
     });
 
+    let day_start = load_day_start().await?;
+    let analysis = plan_compare::analyze_pair(
+        &build_compare_coils(&items_a, &mat_map, day_start),
+        &build_compare_coils(&items_b, &mat_map, day_start),
+    );
+
     Ok(PlanComparisonResult {
         plan_a: side_a,
         plan_b: side_b,
@@ -252,6 +317,7 @@ pub async fn compare_plans(
         only_a_coils: only_a,
         only_b_coils: only_b,
         sequence_changes,
+        analysis,
     })
 }
 
@@ -478,6 +544,207 @@ pub async fn export_compare_sequence_excel(
         )
         .map_err(|e| AppError::FileError(e.to_string()))?;
 
+    let analysis = &comparison.analysis;
+    let similarity = &analysis.similarity;
+    let alignment = &analysis.roll_change_alignment;
+    let due = &analysis.due_outcome;
+    let metric_rows: [(&str, String); 5] = [
+        (
+            "Kendall tau",
+            similarity
+                .kendall_tau
+                .map(|t| format!("{:.4}", t))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        (
+            "最长公共子序列",
+            format!(
+                "{} 卷 (相似度 {:.2}%)",
+                similarity.lcs_length,
+                similarity.lcs_similarity * 100.0
+            ),
+        ),
+        (
+            "换辊点对齐",
+            format!(
+                "A {} / B {} / 对齐 {} (平均偏移 {:.1})",
+                alignment.count_a,
+                alignment.count_b,
+                alignment.aligned_count,
+                alignment.mean_abs_offset
+            ),
+        ),
+        (
+            "延误卷数",
+            format!("A {} / B {}", due.late_count_a, due.late_count_b),
+        ),
+        (
+            "交期结果变化",
+            format!(
+                "转为延误 {} / 转为按期 {}",
+                due.became_late, due.became_on_time
+            ),
+        ),
+    ];
+    for (idx, (label, value)) in metric_rows.iter().enumerate() {
+        let row = (8 + idx) as u32;
+        summary
+            .write_string_with_format(row, 0, *label, &summary_label_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        summary
+            .write_string_with_format(row, 1, value, &summary_value_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+    }
+
+    // 班次差异
+    let shift_sheet = workbook.add_worksheet();
+    shift_sheet
+        .set_name("班次差异")
+        .map_err(|e| AppError::FileError(e.to_string()))?;
+    let shift_headers: [(&str, f64); 9] = [
+        ("班次", 18.0),
+        ("A卷数", 10.0),
+        ("B卷数", 10.0),
+        ("卷数变化", 10.0),
+        ("A吨位", 12.0),
+        ("B吨位", 12.0),
+        ("吨位变化", 12.0),
+        ("仅A在本班", 30.0),
+        ("仅B在本班", 30.0),
+    ];
+    for (col, (title, width)) in shift_headers.iter().enumerate() {
+        shift_sheet
+            .write_string_with_format(0, col as u16, *title, &header_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        shift_sheet
+            .set_column_width(col as u16, *width)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+    }
+    for (idx, item) in analysis.shift_diffs.iter().enumerate() {
+        let row = (idx + 1) as u32;
+        shift_sheet
+            .write_string_with_format(row, 0, &item.shift_key, &data_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        let numbers = [
+            item.count_a as f64,
+            item.count_b as f64,
+            item.count_delta as f64,
+            item.weight_a,
+            item.weight_b,
+            item.weight_delta,
+        ];
+        for (offset, value) in numbers.iter().enumerate() {
+            shift_sheet
+                .write_number_with_format(row, (offset + 1) as u16, *value, &center_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+        }
+        shift_sheet
+            .write_string_with_format(row, 7, item.only_a_coils.join(","), &data_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        shift_sheet
+            .write_string_with_format(row, 8, item.only_b_coils.join(","), &data_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+    }
+
+    // 换辊点对齐
+    let roll_sheet = workbook.add_worksheet();
+    roll_sheet
+        .set_name("换辊点对齐")
+        .map_err(|e| AppError::FileError(e.to_string()))?;
+    let roll_headers: [(&str, f64); 7] = [
+        ("A序号", 10.0),
+        ("A换辊卷", 22.0),
+        ("A累计吨位", 12.0),
+        ("B序号", 10.0),
+        ("B换辊卷", 22.0),
+        ("B累计吨位", 12.0),
+        ("偏移(B-A)", 12.0),
+    ];
+    for (col, (title, width)) in roll_headers.iter().enumerate() {
+        roll_sheet
+            .write_string_with_format(0, col as u16, *title, &header_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        roll_sheet
+            .set_column_width(col as u16, *width)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+    }
+    for (idx, item) in alignment.items.iter().enumerate() {
+        let row = (idx + 1) as u32;
+        for (base_col, point) in [(0u16, &item.point_a), (3u16, &item.point_b)] {
+            let Some(point) = point else {
+                roll_sheet
+                    .write_string_with_format(row, base_col + 1, "-", &center_fmt)
+                    .map_err(|e| AppError::FileError(e.to_string()))?;
+                continue;
+            };
+            roll_sheet
+                .write_number_with_format(row, base_col, point.position as f64, &center_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+            roll_sheet
+                .write_string_with_format(row, base_col + 1, &point.coil_id, &data_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+            roll_sheet
+                .write_number_with_format(row, base_col + 2, point.cumulative_weight, &center_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+        }
+        match item.offset {
+            Some(offset) => roll_sheet
+                .write_number_with_format(row, 6, offset as f64, &center_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?,
+            None => roll_sheet
+                .write_string_with_format(row, 6, "未对齐", &center_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?,
+        };
+    }
+
+    // 交期结果差异
+    let due_sheet = workbook.add_worksheet();
+    due_sheet
+        .set_name("交期结果差异")
+        .map_err(|e| AppError::FileError(e.to_string()))?;
+    let due_headers: [(&str, f64); 7] = [
+        ("卷号", 22.0),
+        ("交期", 18.0),
+        ("A完工", 18.0),
+        ("B完工", 18.0),
+        ("A延误(分钟)", 12.0),
+        ("B延误(分钟)", 12.0),
+        ("结果变化", 12.0),
+    ];
+    for (col, (title, width)) in due_headers.iter().enumerate() {
+        due_sheet
+            .write_string_with_format(0, col as u16, *title, &header_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        due_sheet
+            .set_column_width(col as u16, *width)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+    }
+    for (idx, item) in due.items.iter().enumerate() {
+        let row = (idx + 1) as u32;
+        for (col, value) in [&item.coil_id, &item.due_date, &item.end_a, &item.end_b]
+            .into_iter()
+            .enumerate()
+        {
+            due_sheet
+                .write_string_with_format(row, col as u16, value, &data_fmt)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+        }
+        due_sheet
+            .write_number_with_format(row, 4, item.late_minutes_a as f64, &center_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        due_sheet
+            .write_number_with_format(row, 5, item.late_minutes_b as f64, &center_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        let outcome = if item.late_minutes_b > 0 {
+            "转为延误"
+        } else {
+            "转为按期"
+        };
+        due_sheet
+            .write_string_with_format(row, 6, outcome, &center_fmt)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+    }
+
     workbook
         .save(&file_path)
         .map_err(|e| AppError::FileError(e.to_string()))?;
@@ -505,12 +772,6 @@ pub async fn compare_plans_multi(
     use crate::models::{material, schedule_item, schedule_plan};
     use sea_orm::*;
 
-    if plan_ids.len() < 2 || plan_ids.len() > 3 {
-        return Err(AppError::ConstraintViolation(
-            "仅支持2-3个方案对比".to_string(),
-        ));
-    }
-
     let mut dedup = Vec::with_capacity(plan_ids.len());
     for id in plan_ids {
         if !dedup.contains(&id) {
@@ -522,11 +783,6 @@ pub async fn compare_plans_multi(
             "请至少选择两个不同方案".to_string(),
         ));
     }
-    if dedup.len() > 3 {
-        return Err(AppError::ConstraintViolation(
-            "最多仅支持3个方案".to_string(),
-        ));
-    }
 
     let db = get_db();
 
@@ -568,6 +824,9 @@ pub async fn compare_plans_multi(
         std::collections::HashMap::new();
     let mut coil_set_map: std::collections::HashMap<i32, std::collections::HashSet<String>> =
         std::collections::HashMap::new();
+    let mut compare_coil_map: std::collections::HashMap<i32, Vec<CompareCoil>> =
+        std::collections::HashMap::new();
+    let day_start = load_day_start().await?;
 
     for plan in &plan_models {
         let items = items_map.get(&plan.id).cloned().unwrap_or_default();
@@ -578,6 +837,7 @@ pub async fn compare_plans_multi(
             .collect();
         plan_side_map.insert(plan.id, side);
         coil_set_map.insert(plan.id, coil_set);
+        compare_coil_map.insert(plan.id, build_compare_coils(&items, &mat_map, day_start));
     }
 
     let plans = dedup
@@ -600,6 +860,10 @@ pub async fn compare_plans_multi(
             let common_count = a_set.intersection(b_set).count() as i32;
             let only_a_count = a_set.difference(b_set).count() as i32;
             let only_b_count = b_set.difference(a_set).count() as i32;
+            let analysis = match (compare_coil_map.get(&a_id), compare_coil_map.get(&b_id)) {
+                (Some(a_coils), Some(b_coils)) => plan_compare::analyze_pair(a_coils, b_coils),
+                _ => PlanPairAnalysis::default(),
+            };
 
             overlaps.push(PlanOverlapItem {
                 plan_a_id: a_id,
@@ -607,6 +871,7 @@ pub async fn compare_plans_multi(
                 common_count,
                 only_a_count,
                 only_b_count,
+                analysis,
             });
         }
     }
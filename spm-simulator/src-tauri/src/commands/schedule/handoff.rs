@@ -0,0 +1,53 @@
+use crate::models::plan_dispatch;
+use crate::services::handoff_service::{self, InboxPollResult};
+use crate::AppError;
+
+/// 查询方案下发记录（按时间倒序），plan_id 为空时返回全部
+#[tauri::command]
+pub async fn get_plan_dispatches(
+    plan_id: Option<i32>,
+) -> Result<Vec<plan_dispatch::Model>, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let mut query = plan_dispatch::Entity::find();
+    if let Some(pid) = plan_id {
+        query = query.filter(plan_dispatch::Column::PlanId.eq(pid));
+    }
+    Ok(query
+        .order_by_desc(plan_dispatch::Column::Id)
+        .all(get_db())
+        .await?)
+}
+
+/// 手动（重新）下发已确认方案到 outbox
+#[tauri::command]
+pub async fn dispatch_plan_to_outbox(plan_id: i32) -> Result<plan_dispatch::Model, AppError> {
+    use crate::db::get_db;
+    use crate::models::schedule_plan;
+    use sea_orm::*;
+
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(get_db())
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    if !crate::services::plan_workflow_service::is_effective(
+        crate::services::plan_workflow_service::status_of(&plan),
+    ) {
+        return Err(AppError::ConstraintViolation(
+            "仅已确认或执行中的方案可以下发".to_string(),
+        ));
+    }
+    let config = handoff_service::load_handoff_config().await?;
+    handoff_service::dispatch_plan(plan_id, &config).await
+}
+
+/// 立即扫描一次回执目录
+#[tauri::command]
+pub async fn poll_plan_inbox() -> Result<InboxPollResult, AppError> {
+    let config = handoff_service::load_handoff_config().await?;
+    let dir = config
+        .inbox_dir
+        .ok_or_else(|| AppError::InvalidInput("未配置回执目录 inbox_dir".to_string()))?;
+    handoff_service::poll_inbox(&dir).await
+}
@@ -98,8 +98,8 @@ pub async fn get_plan_versions(plan_id: i32) -> Result<Vec<PlanVersionItem>, App
     Ok(result)
 }
 
-async fn find_plan_root_id(
-    db: &sea_orm::DatabaseConnection,
+pub(super) async fn find_plan_root_id<C: sea_orm::ConnectionTrait>(
+    db: &C,
     plan_id: i32,
 ) -> Result<i32, AppError> {
     use crate::models::schedule_plan::Entity as Plan;
@@ -126,6 +126,39 @@ async fn find_plan_root_id(
     Ok(root_id)
 }
 
+/// 方案所在版本树内的最大版本号（自根方案起广度遍历全部子版本）
+pub(super) async fn max_tree_version<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    plan_id: i32,
+) -> Result<i32, AppError> {
+    use crate::models::schedule_plan;
+    use sea_orm::*;
+
+    let root_id = find_plan_root_id(db, plan_id).await?;
+    let mut tree_ids = vec![root_id];
+    let mut idx = 0usize;
+    while idx < tree_ids.len() {
+        let children = schedule_plan::Entity::find()
+            .filter(schedule_plan::Column::ParentId.eq(tree_ids[idx]))
+            .all(db)
+            .await?;
+        for child in children {
+            if !tree_ids.contains(&child.id) {
+                tree_ids.push(child.id);
+            }
+        }
+        idx += 1;
+    }
+    Ok(schedule_plan::Entity::find()
+        .filter(schedule_plan::Column::Id.is_in(tree_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| p.version.unwrap_or(1))
+        .max()
+        .unwrap_or(1))
+}
+
 #[tauri::command]
 pub async fn rollback_plan_version(
     plan_id: i32,
@@ -142,6 +175,7 @@ pub async fn rollback_plan_version(
     }
 
     let db = get_db();
+    crate::services::plan_workflow_service::ensure_plan_editable(db, plan_id).await?;
     let current = schedule_plan::Entity::find_by_id(plan_id)
         .one(db)
         .await?
@@ -166,6 +200,10 @@ pub async fn rollback_plan_version(
         .await?;
 
     let tx = db.begin().await?;
+    let target_ids: Vec<i32> = target_items.iter().map(|it| it.material_id).collect();
+    crate::services::material_lifecycle_service::ensure_ids_available(&tx, &target_ids, plan_id)
+        .await?;
+    let before = crate::services::undo_service::capture(&tx, plan_id).await?;
 
     schedule_item::Entity::delete_many()
         .filter(schedule_item::Column::PlanId.eq(plan_id))
@@ -205,8 +243,21 @@ pub async fn rollback_plan_version(
     active_plan.risk_count_medium = Set(target.risk_count_medium);
     active_plan.risk_count_low = Set(target.risk_count_low);
     active_plan.risk_summary = Set(target.risk_summary);
+    active_plan.strategy_revision_id = Set(target.strategy_revision_id);
     active_plan.updated_at = Set(Some(chrono::Utc::now()));
     let updated = active_plan.update(&tx).await?;
+    crate::services::undo_service::record(
+        &tx,
+        plan_id,
+        "rollback_version",
+        format!(
+            "版本回滚到 plan_id={} (v{})",
+            target_plan_id,
+            target.version.unwrap_or(1)
+        ),
+        &before,
+    )
+    .await?;
 
     tx.commit().await?;
 
@@ -226,3 +277,146 @@ pub async fn rollback_plan_version(
 
     Ok(updated)
 }
+
+/// 以撤销历史中某条记录处的状态派生新版本方案（草稿）
+#[tauri::command]
+pub async fn fork_plan_from_undo_entry(
+    plan_id: i32,
+    entry_id: Option<i32>,
+    name: Option<String>,
+) -> Result<crate::models::schedule_plan::Model, AppError> {
+    use crate::db::get_db;
+    use crate::models::{schedule_item, schedule_plan};
+    use sea_orm::*;
+
+    let db = get_db();
+    let preview = crate::services::undo_service::preview_at(db, plan_id, entry_id).await?;
+    let source = preview.plan;
+
+    let name = name
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| format!("{} (历史派生)", source.name));
+
+    let tx = db.begin().await?;
+    // 新版本号取版本链内最大值 + 1
+    let max_version = max_tree_version(&tx, plan_id).await?;
+    let forked = schedule_plan::ActiveModel {
+        plan_no: Set(format!(
+            "SP-{}",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f")
+        )),
+        name: Set(name),
+        period_type: Set(source.period_type.clone()),
+        start_date: Set(source.start_date.clone()),
+        end_date: Set(source.end_date.clone()),
+        strategy_id: Set(source.strategy_id),
+        strategy_revision_id: Set(source.strategy_revision_id),
+        status: Set(Some("draft".to_string())),
+        version: Set(Some(max_version + 1)),
+        parent_id: Set(Some(plan_id)),
+        total_count: Set(source.total_count),
+        total_weight: Set(source.total_weight),
+        roll_change_count: Set(source.roll_change_count),
+        score_overall: Set(source.score_overall),
+        score_sequence: Set(source.score_sequence),
+        score_delivery: Set(source.score_delivery),
+        score_efficiency: Set(source.score_efficiency),
+        risk_count_high: Set(source.risk_count_high),
+        risk_count_medium: Set(source.risk_count_medium),
+        risk_count_low: Set(source.risk_count_low),
+        risk_summary: Set(source.risk_summary.clone()),
+        ignored_risks: Set(source.ignored_risks.clone()),
+        remarks: Set(source.remarks.clone()),
+        ..Default::default()
+    }
+    .insert(&tx)
+    .await?;
+
+    let item_ids: Vec<i32> = preview.items.iter().map(|it| it.material_id).collect();
+    crate::services::material_lifecycle_service::ensure_ids_available(&tx, &item_ids, forked.id)
+        .await?;
+
+    for item in preview.items {
+        schedule_item::ActiveModel {
+            plan_id: Set(forked.id),
+            material_id: Set(item.material_id),
+            sequence: Set(item.sequence),
+            shift_date: Set(item.shift_date),
+            shift_no: Set(item.shift_no),
+            shift_type: Set(item.shift_type),
+            planned_start: Set(item.planned_start),
+            planned_end: Set(item.planned_end),
+            cumulative_weight: Set(item.cumulative_weight),
+            is_roll_change: Set(item.is_roll_change),
+            is_locked: Set(item.is_locked),
+            lock_reason: Set(item.lock_reason),
+            risk_flags: Set(item.risk_flags),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    write_operation_log(
+        "plan",
+        "fork_from_history",
+        Some("plan"),
+        Some(forked.id),
+        Some(format!(
+            "从方案 {} 撤销历史派生 v{}: entry_id={}",
+            source.plan_no,
+            forked.version.unwrap_or(1),
+            preview
+                .entry_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "initial".to_string())
+        )),
+    )
+    .await;
+
+    Ok(forked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::get_db;
+    use crate::models::schedule_plan;
+    use sea_orm::{ActiveModelTrait, Set, TransactionTrait};
+
+    #[tokio::test]
+    async fn max_tree_version_spans_the_whole_version_tree() {
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_version_tree_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let insert = |suffix: &str, version: i32, parent_id: Option<i32>| {
+            schedule_plan::ActiveModel {
+                plan_no: Set(format!("TREE-{}-{}", seed, suffix)),
+                name: Set(format!("版本树{}", suffix)),
+                period_type: Set("daily".to_string()),
+                start_date: Set("2026-02-13".to_string()),
+                end_date: Set("2026-02-13".to_string()),
+                version: Set(Some(version)),
+                parent_id: Set(parent_id),
+                ..Default::default()
+            }
+            .insert(db)
+        };
+        let root = insert("root", 1, None).await.unwrap();
+        let child = insert("child", 2, Some(root.id)).await.unwrap();
+        insert("grandchild", 4, Some(child.id)).await.unwrap();
+        let sibling = insert("sibling", 3, Some(root.id)).await.unwrap();
+
+        // 从任一节点出发都覆盖整棵树；事务连接上同样可用
+        assert_eq!(max_tree_version(db, sibling.id).await.unwrap(), 4);
+        let tx = db.begin().await.unwrap();
+        assert_eq!(max_tree_version(&tx, root.id).await.unwrap(), 4);
+        tx.rollback().await.unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+use crate::models::{material, schedule_item, schedule_plan, strategy_template};
+use crate::services::plan_json_service::{
+    self, CoilAttributeMismatch, PlanDocItem, PLAN_JSON_SCHEMA_VERSION,
+};
+use crate::utils::log::write_operation_log;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportPlanJsonResult {
+    pub file_path: String,
+    pub item_count: usize,
+    pub schema_version: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportPlanJsonOptions {
+    /// 新方案名称，缺省沿用文件中的方案名
+    pub name: Option<String>,
+    /// 指定本地策略模板；缺省按文件中的策略快照匹配或新建
+    pub strategy_id: Option<i32>,
+    /// 存在本地缺失的钢卷时是否继续导入（缺省 true，缺失钢卷跳过）
+    pub allow_missing: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportPlanJsonResult {
+    pub plan: schedule_plan::Model,
+    pub source_plan_no: String,
+    pub schema_version: u32,
+    pub imported_count: usize,
+    /// 本地材料表中不存在的卷号
+    pub missing_coils: Vec<String>,
+    /// 属性快照与本地材料不一致的钢卷
+    pub attribute_mismatches: Vec<CoilAttributeMismatch>,
+    pub strategy_id: Option<i32>,
+    /// 是否根据策略快照新建了策略模板
+    pub strategy_created: bool,
+}
+
+/// 导出方案为版本化 JSON（方案信息、策略快照、排程项与钢卷属性、换辊点、评分）
+#[tauri::command]
+pub async fn export_plan_json(
+    plan_id: i32,
+    file_path: String,
+) -> Result<ExportPlanJsonResult, AppError> {
+    let doc = plan_json_service::build_plan_document(plan_id).await?;
+
+    let path = std::path::PathBuf::from(&file_path);
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let json = serde_json::to_string_pretty(&doc)
+        .map_err(|e| AppError::DataConversionError(format!("序列化失败: {}", e)))?;
+    std::fs::write(&path, json)?;
+
+    let item_count = doc.items.len();
+    write_operation_log(
+        "export",
+        "export_plan_json",
+        Some("plan"),
+        Some(plan_id),
+        Some(format!(
+            "导出方案JSON: {} ({}) items={} path={}",
+            doc.plan.name, doc.plan.plan_no, item_count, file_path
+        )),
+    )
+    .await;
+
+    Ok(ExportPlanJsonResult {
+        file_path,
+        item_count,
+        schema_version: PLAN_JSON_SCHEMA_VERSION,
+    })
+}
+
+/// 从方案 JSON 导入为新草稿方案，按 coil_id 匹配本地材料
+#[tauri::command]
+pub async fn import_plan_json(
+    file_path: String,
+    options: Option<ImportPlanJsonOptions>,
+) -> Result<ImportPlanJsonResult, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let options = options.unwrap_or_default();
+    let content = std::fs::read_to_string(&file_path)?;
+    let doc = plan_json_service::parse_plan_document(&content)?;
+
+    let db = get_db();
+    let coil_ids: Vec<String> = doc.items.iter().map(|it| it.coil_id.clone()).collect();
+    let local: std::collections::HashMap<String, material::Model> = if coil_ids.is_empty() {
+        std::collections::HashMap::new()
+    } else {
+        material::Entity::find()
+            .filter(material::Column::CoilId.is_in(coil_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.coil_id.clone(), m))
+            .collect()
+    };
+
+    let mut items: Vec<&PlanDocItem> = doc.items.iter().collect();
+    items.sort_by_key(|it| it.sequence);
+    let mut missing_coils = Vec::new();
+    let mut attribute_mismatches = Vec::new();
+    let mut matched: Vec<(&PlanDocItem, &material::Model)> = Vec::new();
+    for item in items {
+        match local.get(&item.coil_id) {
+            Some(m) => {
+                attribute_mismatches.extend(plan_json_service::compare_coil_attributes(
+                    &item.coil_id,
+                    &item.coil,
+                    m,
+                ));
+                matched.push((item, m));
+            }
+            None => missing_coils.push(item.coil_id.clone()),
+        }
+    }
+    if !missing_coils.is_empty() && !options.allow_missing.unwrap_or(true) {
+        return Err(AppError::ConstraintViolation(format!(
+            "本地缺失 {} 个钢卷: {}",
+            missing_coils.len(),
+            missing_coils.join(",")
+        )));
+    }
+    if matched.is_empty() && !doc.items.is_empty() {
+        return Err(AppError::ConstraintViolation(
+            "文件中的钢卷在本地均不存在，无法导入".to_string(),
+        ));
+    }
+
+    // 未显式指定策略时将使用文件中的策略快照，须先通过结构校验
+    if let (None, Some(snapshot)) = (options.strategy_id, &doc.strategy) {
+        crate::services::strategy_schema_service::ensure_valid(
+            &snapshot.sort_weights,
+            &snapshot.constraints,
+            snapshot.soft_constraints.as_deref(),
+            &snapshot.eval_weights,
+            &snapshot.temper_rules,
+        )?;
+    }
+
+    let tx = db.begin().await?;
+
+    // 策略：显式指定 > 同名且配置一致的本地模板 > 按快照新建
+    let mut strategy_created = false;
+    let strategy = if let Some(sid) = options.strategy_id {
+        Some(
+            strategy_template::Entity::find_by_id(sid)
+                .one(&tx)
+                .await?
+                .ok_or_else(|| AppError::InvalidInput(format!("策略模板不存在: {}", sid)))?,
+        )
+    } else if let Some(snapshot) = &doc.strategy {
+        let same_name = strategy_template::Entity::find()
+            .filter(strategy_template::Column::Name.eq(snapshot.name.clone()))
+            .one(&tx)
+            .await?;
+        let reusable = same_name.as_ref().filter(|s| {
+            s.sort_weights == snapshot.sort_weights
+                && s.constraints == snapshot.constraints
+                && s.soft_constraints == snapshot.soft_constraints
+                && s.eval_weights == snapshot.eval_weights
+                && s.temper_rules == snapshot.temper_rules
+        });
+        match reusable {
+            Some(s) => Some(s.clone()),
+            None => {
+                let name = if same_name.is_some() {
+                    format!(
+                        "{} (导入 {})",
+                        snapshot.name,
+                        chrono::Local::now().format("%Y%m%d%H%M%S")
+                    )
+                } else {
+                    snapshot.name.clone()
+                };
+                let created = strategy_template::ActiveModel {
+                    name: Set(name),
+                    description: Set(snapshot.description.clone()),
+                    is_default: Set(Some(false)),
+                    is_system: Set(Some(false)),
+                    sort_weights: Set(snapshot.sort_weights.clone()),
+                    constraints: Set(snapshot.constraints.clone()),
+                    soft_constraints: Set(snapshot.soft_constraints.clone()),
+                    eval_weights: Set(snapshot.eval_weights.clone()),
+                    temper_rules: Set(snapshot.temper_rules.clone()),
+                    ..Default::default()
+                }
+                .insert(&tx)
+                .await?;
+                strategy_created = true;
+                Some(created)
+            }
+        }
+    } else {
+        None
+    };
+    let strategy_id = strategy.as_ref().map(|s| s.id);
+    // 方案绑定所用策略的修订：新建模板记录初始修订，已有模板取（或补记）当前修订
+    let strategy_revision_id = match &strategy {
+        Some(template) if strategy_created => Some(
+            crate::services::strategy_revision_service::record_revision(
+                &tx,
+                template,
+                Some(format!("导入自方案 {}", doc.plan.plan_no)),
+            )
+            .await?
+            .id,
+        ),
+        Some(template) => Some(
+            crate::services::strategy_revision_service::ensure_current_revision(&tx, template)
+                .await?
+                .id,
+        ),
+        None => None,
+    };
+
+    let plan = schedule_plan::ActiveModel {
+        plan_no: Set(format!(
+            "SP-{}",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f")
+        )),
+        name: Set(options
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{} (导入)", doc.plan.name))),
+        period_type: Set(doc.plan.period_type.clone()),
+        start_date: Set(doc.plan.start_date.clone()),
+        end_date: Set(doc.plan.end_date.clone()),
+        strategy_id: Set(strategy_id),
+        strategy_revision_id: Set(strategy_revision_id),
+        status: Set(Some("draft".to_string())),
+        version: Set(Some(1)),
+        remarks: Set(Some(format!(
+            "从 {} 导入（schema v{}），缺失钢卷 {} 个",
+            doc.plan.plan_no,
+            doc.schema_version,
+            missing_coils.len()
+        ))),
+        created_at: Set(Some(chrono::Utc::now())),
+        updated_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(&tx)
+    .await?;
+
+    let matched_ids: Vec<i32> = matched.iter().map(|(_, m)| m.id).collect();
+    crate::services::material_lifecycle_service::ensure_ids_available(&tx, &matched_ids, plan.id)
+        .await?;
+
+    for (i, (item, m)) in matched.iter().enumerate() {
+        schedule_item::ActiveModel {
+            plan_id: Set(plan.id),
+            material_id: Set(m.id),
+            sequence: Set(i as i32 + 1),
+            shift_date: Set(item.shift_date.clone()),
+            shift_no: Set(item.shift_no),
+            shift_type: Set(item.shift_type.clone()),
+            planned_start: Set(item.planned_start.clone()),
+            planned_end: Set(item.planned_end.clone()),
+            cumulative_weight: Set(item.cumulative_weight),
+            is_roll_change: Set(Some(item.is_roll_change)),
+            is_locked: Set(Some(item.is_locked)),
+            lock_reason: Set(item.lock_reason.clone()),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+    }
+    if strategy_id.is_some() {
+        super::risk::recalculate_risk_flags(&tx, plan.id).await?;
+    }
+    tx.commit().await?;
+    let plan = schedule_plan::Entity::find_by_id(plan.id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan.id))?;
+
+    let imported_count = matched.len();
+    write_operation_log(
+        "import",
+        "import_plan_json",
+        Some("plan"),
+        Some(plan.id),
+        Some(format!(
+            "导入方案JSON: {} -> {} items={} missing={} mismatches={} path={}",
+            doc.plan.plan_no,
+            plan.plan_no,
+            imported_count,
+            missing_coils.len(),
+            attribute_mismatches.len(),
+            file_path
+        )),
+    )
+    .await;
+
+    Ok(ImportPlanJsonResult {
+        plan,
+        source_plan_no: doc.plan.plan_no,
+        schema_version: doc.schema_version,
+        imported_count,
+        missing_coils,
+        attribute_mismatches,
+        strategy_id,
+        strategy_created,
+    })
+}
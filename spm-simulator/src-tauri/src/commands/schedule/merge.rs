@@ -0,0 +1,270 @@
+use crate::engine::plan_merge::{self, MergeConflict, MergeOutcome, PlanDiff, PlanEntry};
+use crate::models::{schedule_item, schedule_plan};
+use crate::utils::log::write_operation_log;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanMergePreview {
+    pub ancestor_plan_id: i32,
+    pub outcome: MergeOutcome,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanMergeResult {
+    pub plan: schedule_plan::Model,
+    pub ancestor_plan_id: i32,
+    pub ours_diff: PlanDiff,
+    pub theirs_diff: PlanDiff,
+    pub conflicts: Vec<MergeConflict>,
+    pub applied_theirs: usize,
+}
+
+/// 加载方案排程项（按序）及对应的合并条目
+async fn load_plan_entries(
+    plan_id: i32,
+) -> Result<(Vec<schedule_item::Model>, Vec<PlanEntry>), AppError> {
+    use crate::db::get_db;
+    use crate::models::material;
+    use sea_orm::*;
+
+    let db = get_db();
+    schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(db)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|it| it.material_id).collect();
+    let coil_map: std::collections::HashMap<i32, String> = if mat_ids.is_empty() {
+        std::collections::HashMap::new()
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.coil_id))
+            .collect()
+    };
+    let entries = items
+        .iter()
+        .map(|it| PlanEntry {
+            material_id: it.material_id,
+            coil_id: coil_map
+                .get(&it.material_id)
+                .cloned()
+                .unwrap_or_else(|| it.material_id.to_string()),
+            is_locked: it.is_locked.unwrap_or(false),
+        })
+        .collect();
+    Ok((items, entries))
+}
+
+/// 沿 parent_id 查找两个版本最近的共同祖先（含自身）
+async fn find_common_ancestor(a: i32, b: i32) -> Result<i32, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    let mut chain_a = Vec::new();
+    let mut cursor = Some(a);
+    while let Some(id) = cursor {
+        if chain_a.contains(&id) {
+            break;
+        }
+        chain_a.push(id);
+        cursor = schedule_plan::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::PlanNotFound(id))?
+            .parent_id;
+    }
+
+    let mut visited = Vec::new();
+    let mut cursor = Some(b);
+    while let Some(id) = cursor {
+        if chain_a.contains(&id) {
+            return Ok(id);
+        }
+        if visited.contains(&id) {
+            break;
+        }
+        visited.push(id);
+        cursor = schedule_plan::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::PlanNotFound(id))?
+            .parent_id;
+    }
+    Err(AppError::ConstraintViolation(
+        "两个方案不在同一版本链中，无法合并".to_string(),
+    ))
+}
+
+/// 结构化比较两个方案版本（新增 / 移除 / 移动 / 锁定变化）
+#[tauri::command]
+pub async fn diff_plan_versions(
+    base_plan_id: i32,
+    target_plan_id: i32,
+) -> Result<PlanDiff, AppError> {
+    let (_, base) = load_plan_entries(base_plan_id).await?;
+    let (_, target) = load_plan_entries(target_plan_id).await?;
+    Ok(plan_merge::diff_plans(&base, &target))
+}
+
+/// 预览三方合并（不落库）
+#[tauri::command]
+pub async fn preview_plan_merge(
+    ours_plan_id: i32,
+    theirs_plan_id: i32,
+) -> Result<PlanMergePreview, AppError> {
+    let ancestor_plan_id = find_common_ancestor(ours_plan_id, theirs_plan_id).await?;
+    let (_, base) = load_plan_entries(ancestor_plan_id).await?;
+    let (_, ours) = load_plan_entries(ours_plan_id).await?;
+    let (_, theirs) = load_plan_entries(theirs_plan_id).await?;
+    Ok(PlanMergePreview {
+        ancestor_plan_id,
+        outcome: plan_merge::merge_plans(&base, &ours, &theirs),
+    })
+}
+
+/// 三方合并两个兄弟版本，生成以 ours 为父版本的新版本
+#[tauri::command]
+pub async fn merge_plan_versions(
+    ours_plan_id: i32,
+    theirs_plan_id: i32,
+    name: Option<String>,
+) -> Result<PlanMergeResult, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    if ours_plan_id == theirs_plan_id {
+        return Err(AppError::ConstraintViolation("不能与自身合并".to_string()));
+    }
+
+    let db = get_db();
+    let ancestor_plan_id = find_common_ancestor(ours_plan_id, theirs_plan_id).await?;
+    let (base_items, base) = load_plan_entries(ancestor_plan_id).await?;
+    let (ours_items, ours) = load_plan_entries(ours_plan_id).await?;
+    let (theirs_items, theirs) = load_plan_entries(theirs_plan_id).await?;
+    let outcome = plan_merge::merge_plans(&base, &ours, &theirs);
+
+    let ours_plan = schedule_plan::Entity::find_by_id(ours_plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(ours_plan_id))?;
+    let theirs_plan = schedule_plan::Entity::find_by_id(theirs_plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(theirs_plan_id))?;
+
+    // 排程项属性来源：ours > theirs > 祖先
+    let source_item = |material_id: i32| -> Option<&schedule_item::Model> {
+        ours_items
+            .iter()
+            .chain(theirs_items.iter())
+            .chain(base_items.iter())
+            .find(|it| it.material_id == material_id)
+    };
+
+    let tx = db.begin().await?;
+    // 新版本号 = 版本树内最大版本 + 1
+    let max_version = super::history::max_tree_version(&tx, ours_plan_id).await?;
+    let plan = schedule_plan::ActiveModel {
+        plan_no: Set(format!(
+            "SP-{}",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f")
+        )),
+        name: Set(name.unwrap_or_else(|| format!("{} (合并)", ours_plan.name))),
+        period_type: Set(ours_plan.period_type.clone()),
+        start_date: Set(ours_plan.start_date.clone()),
+        end_date: Set(ours_plan.end_date.clone()),
+        strategy_id: Set(ours_plan.strategy_id),
+        strategy_revision_id: Set(ours_plan.strategy_revision_id),
+        status: Set(Some("draft".to_string())),
+        parent_id: Set(Some(ours_plan_id)),
+        version: Set(Some(max_version + 1)),
+        remarks: Set(Some(format!(
+            "合并 v{} 与 v{}（共同祖先 #{}），冲突 {} 项",
+            ours_plan.version.unwrap_or(1),
+            theirs_plan.version.unwrap_or(1),
+            ancestor_plan_id,
+            outcome.conflicts.len()
+        ))),
+        created_at: Set(Some(chrono::Utc::now())),
+        updated_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(&tx)
+    .await?;
+
+    // 合并结果中的材料须仍可排入（未被其他生效方案锁定、未冻结、未投产）
+    let merged_ids: Vec<i32> = outcome.merged.iter().map(|e| e.material_id).collect();
+    crate::services::material_lifecycle_service::ensure_ids_available(&tx, &merged_ids, plan.id)
+        .await?;
+
+    for (i, entry) in outcome.merged.iter().enumerate() {
+        let Some(src) = source_item(entry.material_id) else {
+            continue;
+        };
+        schedule_item::ActiveModel {
+            plan_id: Set(plan.id),
+            material_id: Set(entry.material_id),
+            sequence: Set(i as i32 + 1),
+            shift_date: Set(src.shift_date.clone()),
+            shift_no: Set(src.shift_no),
+            shift_type: Set(src.shift_type.clone()),
+            planned_start: Set(src.planned_start.clone()),
+            planned_end: Set(src.planned_end.clone()),
+            cumulative_weight: Set(src.cumulative_weight),
+            is_roll_change: Set(src.is_roll_change),
+            is_locked: Set(Some(entry.is_locked)),
+            lock_reason: Set(if entry.is_locked {
+                src.lock_reason.clone()
+            } else {
+                None
+            }),
+            risk_flags: Set(src.risk_flags.clone()),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+    }
+    if plan.strategy_id.is_some() {
+        super::risk::recalculate_risk_flags(&tx, plan.id).await?;
+    }
+    tx.commit().await?;
+    let plan = schedule_plan::Entity::find_by_id(plan.id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan.id))?;
+
+    write_operation_log(
+        "plan",
+        "merge_version",
+        Some("plan"),
+        Some(plan.id),
+        Some(format!(
+            "版本合并: ours={} theirs={} ancestor={}, 应用 theirs 改动 {} 项, 冲突 {} 项",
+            ours_plan_id,
+            theirs_plan_id,
+            ancestor_plan_id,
+            outcome.applied_theirs,
+            outcome.conflicts.len()
+        )),
+    )
+    .await;
+
+    Ok(PlanMergeResult {
+        plan,
+        ancestor_plan_id,
+        ours_diff: outcome.ours_diff,
+        theirs_diff: outcome.theirs_diff,
+        conflicts: outcome.conflicts,
+        applied_theirs: outcome.applied_theirs,
+    })
+}
@@ -1,15 +1,31 @@
 mod comparison;
+mod handoff;
 mod history;
+mod interchange;
 mod logs;
+mod merge;
 mod operations;
 mod plan;
+mod report;
 mod risk;
+mod robustness;
+mod roll_change;
+mod simulation;
 mod undo;
+mod yard;
 
 pub use comparison::*;
+pub use handoff::*;
 pub use history::*;
+pub use interchange::*;
 pub use logs::*;
+pub use merge::*;
 pub use operations::*;
 pub use plan::*;
+pub(crate) use report::load_plan_report_data;
 pub use risk::*;
+pub use robustness::*;
+pub use roll_change::*;
+pub use simulation::*;
 pub use undo::*;
+pub use yard::*;
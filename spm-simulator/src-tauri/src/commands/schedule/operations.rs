@@ -1,4 +1,5 @@
 use super::plan::ScheduleResult;
+use crate::services::undo_service;
 use crate::utils::log::write_operation_log;
 use crate::AppError;
 use chrono::Timelike;
@@ -28,13 +29,22 @@ pub struct IdleGapSummary {
 
 #[tauri::command]
 pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleResult, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
     log::info!(
         "Auto scheduling plan {} with strategy {}",
         plan_id,
         strategy_id
     );
 
-    let output = crate::engine::scheduler::auto_schedule(plan_id, strategy_id).await?;
+    let db = get_db();
+    let tx = db.begin().await?;
+    crate::services::plan_workflow_service::ensure_plan_editable(&tx, plan_id).await?;
+    let before = undo_service::capture(&tx, plan_id).await?;
+    let output = crate::engine::scheduler::auto_schedule(&tx, plan_id, strategy_id).await?;
+    // 已确认方案重排后同步材料锁定
+    crate::services::material_lifecycle_service::sync_plan_reservations(&tx, plan_id).await?;
 
     let result = ScheduleResult {
         plan_id: output.plan_id,
@@ -50,6 +60,18 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleRes
         scheduler_mode_used: Some(output.scheduler_mode_used.clone()),
         fallback_triggered: Some(output.fallback_triggered),
     };
+    undo_service::record(
+        &tx,
+        plan_id,
+        "auto_schedule",
+        format!(
+            "自动排程: strategy_id={}, {} 条",
+            strategy_id, result.total_count
+        ),
+        &before,
+    )
+    .await?;
+    tx.commit().await?;
 
     write_operation_log(
         "schedule",
@@ -184,6 +206,7 @@ pub async fn add_to_schedule(
     );
 
     let db = get_db();
+    crate::services::plan_workflow_service::ensure_plan_editable(db, plan_id).await?;
 
     if material_ids.is_empty() {
         return Ok(vec![]);
@@ -246,6 +269,10 @@ pub async fn add_to_schedule(
         )));
     }
 
+    // 校验：材料未被其他已确认方案锁定，且未冻结、未投产
+    let candidates: Vec<material::Model> = material_map.values().cloned().collect();
+    crate::services::material_lifecycle_service::ensure_available(&candidates, plan_id)?;
+
     // 校验：同一方案中不允许重复添加
     let existing = schedule_item::Entity::find()
         .filter(schedule_item::Column::PlanId.eq(plan_id))
@@ -285,6 +312,8 @@ pub async fn add_to_schedule(
         )));
     }
 
+    let tx = db.begin().await?;
+    let before = undo_service::capture(&tx, plan_id).await?;
     let shift_by = dedup_ids.len() as i32;
     if shift_by > 0 {
         schedule_item::Entity::update_many()
@@ -294,7 +323,7 @@ pub async fn add_to_schedule(
             )
             .filter(schedule_item::Column::PlanId.eq(plan_id))
             .filter(schedule_item::Column::Sequence.gte(insert_position))
-            .exec(db)
+            .exec(&tx)
             .await?;
     }
 
@@ -313,9 +342,20 @@ pub async fn add_to_schedule(
             is_locked: Set(Some(false)),
             ..Default::default()
         };
-        let result = item.insert(db).await?;
+        let result = item.insert(&tx).await?;
         created.push(result);
     }
+    // 已确认方案新增的材料立即锁定
+    crate::services::material_lifecycle_service::sync_plan_reservations(&tx, plan_id).await?;
+    undo_service::record(
+        &tx,
+        plan_id,
+        "add_material",
+        format!("添加材料 {} 条到位置 {}", created.len(), insert_position),
+        &before,
+    )
+    .await?;
+    tx.commit().await?;
 
     write_operation_log(
         "schedule",
@@ -339,12 +379,25 @@ pub async fn remove_from_schedule(plan_id: i32, item_ids: Vec<i32>) -> Result<u6
     use crate::models::schedule_item::Entity as Item;
     use sea_orm::*;
 
-    let db = get_db();
+    let tx = get_db().begin().await?;
+    crate::services::plan_workflow_service::ensure_plan_editable(&tx, plan_id).await?;
+    let before = undo_service::capture(&tx, plan_id).await?;
     let result = Item::delete_many()
         .filter(crate::models::schedule_item::Column::PlanId.eq(plan_id))
         .filter(crate::models::schedule_item::Column::Id.is_in(item_ids))
-        .exec(db)
+        .exec(&tx)
         .await?;
+    // 已确认方案移出的材料释放锁定
+    crate::services::material_lifecycle_service::sync_plan_reservations(&tx, plan_id).await?;
+    undo_service::record(
+        &tx,
+        plan_id,
+        "remove_material",
+        format!("移除排程项 {} 条", result.rows_affected),
+        &before,
+    )
+    .await?;
+    tx.commit().await?;
 
     write_operation_log(
         "schedule",
@@ -375,12 +428,13 @@ pub async fn move_schedule_item(
         plan_id
     );
 
-    let db = get_db();
+    let tx = get_db().begin().await?;
+    crate::services::plan_workflow_service::ensure_plan_editable(&tx, plan_id).await?;
 
     let mut items = schedule_item::Entity::find()
         .filter(schedule_item::Column::PlanId.eq(plan_id))
         .order_by_asc(schedule_item::Column::Sequence)
-        .all(db)
+        .all(&tx)
         .await?;
 
     let old_idx = items
@@ -394,6 +448,7 @@ pub async fn move_schedule_item(
         return Ok(());
     }
 
+    let before = undo_service::capture(&tx, plan_id).await?;
     let item = items.remove(old_idx);
     items.insert(new_idx, item);
 
@@ -403,10 +458,19 @@ pub async fn move_schedule_item(
             schedule_item::Entity::update_many()
                 .col_expr(schedule_item::Column::Sequence, Expr::value(seq))
                 .filter(schedule_item::Column::Id.eq(it.id))
-                .exec(db)
+                .exec(&tx)
                 .await?;
         }
     }
+    undo_service::record(
+        &tx,
+        plan_id,
+        "move_item",
+        format!("移动排程项 {} 到位置 {}", item_id, new_idx + 1),
+        &before,
+    )
+    .await?;
+    tx.commit().await?;
 
     write_operation_log(
         "schedule",
@@ -433,7 +497,9 @@ pub async fn lock_schedule_items(
     use crate::models::schedule_item::Entity as Item;
     use sea_orm::*;
 
-    let db = get_db();
+    let tx = get_db().begin().await?;
+    crate::services::plan_workflow_service::ensure_plan_editable(&tx, plan_id).await?;
+    let before = undo_service::capture(&tx, plan_id).await?;
     let result = Item::update_many()
         .col_expr(
             crate::models::schedule_item::Column::IsLocked,
@@ -441,8 +507,21 @@ pub async fn lock_schedule_items(
         )
         .filter(crate::models::schedule_item::Column::PlanId.eq(plan_id))
         .filter(crate::models::schedule_item::Column::Id.is_in(item_ids))
-        .exec(db)
+        .exec(&tx)
         .await?;
+    undo_service::record(
+        &tx,
+        plan_id,
+        if locked { "lock" } else { "unlock" },
+        format!(
+            "{}排程项 {} 条",
+            if locked { "锁定" } else { "解锁" },
+            result.rows_affected
+        ),
+        &before,
+    )
+    .await?;
+    tx.commit().await?;
 
     write_operation_log(
         "schedule",
@@ -477,3 +556,85 @@ pub async fn get_schedule_items(
 
     Ok(items)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::get_db;
+    use crate::models::{material, schedule_plan, strategy_template};
+    use sea_orm::*;
+
+    #[tokio::test]
+    async fn auto_schedule_and_roll_reoptimize_complete_inside_transactions() {
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_operations_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let today = chrono::Utc::now().date_naive();
+        for i in 0..3 {
+            material::ActiveModel {
+                coil_id: Set(format!("OP{}-{}", seed, i)),
+                steel_grade: Set("Q235".to_string()),
+                thickness: Set(2.5),
+                width: Set(1250.0 - i as f64 * 50.0),
+                weight: Set(20.0),
+                coiling_time: Set(chrono::Utc::now() - chrono::Duration::days(10)),
+                status: Set(Some("pending".to_string())),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .expect("写入测试材料失败");
+        }
+        let strategy = strategy_template::Entity::find()
+            .order_by_desc(strategy_template::Column::IsDefault)
+            .one(db)
+            .await
+            .unwrap()
+            .expect("缺少默认策略模板");
+        let plan = schedule_plan::ActiveModel {
+            plan_no: Set(format!("OP-{}", seed)),
+            name: Set("OP".to_string()),
+            period_type: Set("daily".to_string()),
+            start_date: Set(today.format("%Y-%m-%d").to_string()),
+            end_date: Set(today.format("%Y-%m-%d").to_string()),
+            strategy_id: Set(Some(strategy.id)),
+            status: Set(Some("draft".to_string())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        // 单连接池下事务内不得再经全局连接读写，否则取连接超时
+        let result = auto_schedule(plan.id, strategy.id).await.unwrap();
+        assert!(result.total_count >= 3);
+        let output = super::super::reoptimize_roll_changes(plan.id)
+            .await
+            .unwrap();
+        let plan = schedule_plan::Entity::find_by_id(plan.id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(plan.roll_change_count, Some(output.after_count as i32));
+        assert!(plan.score_overall.is_some());
+
+        // 状态流转的守卫读取（含风险分析）同样在事务连接上完成
+        super::super::update_plan_status(plan.id, "submitted".to_string(), None, None)
+            .await
+            .unwrap();
+        let approved = super::super::update_plan_status(
+            plan.id,
+            "approved".to_string(),
+            Some("张工".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(approved.status.as_deref(), Some("approved"));
+    }
+}
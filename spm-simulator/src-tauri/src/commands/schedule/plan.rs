@@ -174,6 +174,7 @@ pub async fn get_plans(
 pub async fn save_plan(id: i32) -> Result<crate::models::schedule_plan::Model, AppError> {
     use crate::db::get_db;
     use crate::models::schedule_plan::Entity as Plan;
+    use crate::services::plan_workflow_service as workflow;
     use sea_orm::*;
 
     let db = get_db();
@@ -182,11 +183,16 @@ pub async fn save_plan(id: i32) -> Result<crate::models::schedule_plan::Model, A
         .await?
         .ok_or(AppError::PlanNotFound(id))?;
 
+    let from = workflow::status_of(&plan).to_string();
+    workflow::check_transition(&from, workflow::PLAN_SAVED, &Default::default())?;
     let mut active: crate::models::schedule_plan::ActiveModel = plan.into();
-    active.status = Set(Some("saved".to_string()));
+    active.status = Set(Some(workflow::PLAN_SAVED.to_string()));
     active.updated_at = Set(Some(chrono::Utc::now()));
 
     let result = active.update(db).await?;
+    if from != workflow::PLAN_SAVED {
+        workflow::record_transition(db, result.id, &from, workflow::PLAN_SAVED, None, None).await?;
+    }
     write_operation_log(
         "plan",
         "save",
@@ -221,7 +227,11 @@ pub async fn delete_plan(id: i32) -> Result<(), AppError> {
         )));
     }
 
-    Plan::delete_by_id(id).exec(db).await?;
+    let txn = db.begin().await?;
+    Plan::delete_by_id(id).exec(&txn).await?;
+    // 释放方案锁定的材料
+    crate::services::material_lifecycle_service::release_plan_reservations(&txn, id).await?;
+    txn.commit().await?;
     write_operation_log(
         "plan",
         "delete",
@@ -233,29 +243,74 @@ pub async fn delete_plan(id: i32) -> Result<(), AppError> {
     Ok(())
 }
 
+/// 变更方案状态：按审批流程校验流转与守卫条件，写入审批记录并同步材料锁定
+///
+/// 审批通过/驳回须填写审批人，驳回须填写意见；存在未忽略的高风险时不能审批通过。
 #[tauri::command]
 pub async fn update_plan_status(
     id: i32,
     status: String,
+    approver: Option<String>,
+    comment: Option<String>,
 ) -> Result<crate::models::schedule_plan::Model, AppError> {
     use crate::db::get_db;
+    use crate::models::schedule_item;
     use crate::models::schedule_plan::Entity as Plan;
+    use crate::services::plan_workflow_service::{self as workflow, TransitionContext};
     use sea_orm::*;
 
-    let db = get_db();
+    // 守卫读取（方案、排程项数、未忽略高风险）与状态变更、审批记录、材料锁定在同一事务内
+    let txn = get_db().begin().await?;
     let plan = Plan::find_by_id(id)
-        .one(db)
+        .one(&txn)
         .await?
         .ok_or(AppError::PlanNotFound(id))?;
 
+    let from = workflow::status_of(&plan).to_string();
+    let item_count = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(id))
+        .count(&txn)
+        .await?;
+    let unignored_high_risks = if status == workflow::PLAN_APPROVED {
+        super::risk::analyze_plan_risks(&txn, id).await?.risk_high
+    } else {
+        0
+    };
+    workflow::check_transition(
+        &from,
+        &status,
+        &TransitionContext {
+            item_count,
+            unignored_high_risks,
+            approver: approver.as_deref(),
+            comment: comment.as_deref(),
+        },
+    )?;
+
+    // 生效时锁定方案材料，离开生效状态时释放
     let mut active: crate::models::schedule_plan::ActiveModel = plan.into();
-    let status_clone = status.clone();
-    active.status = Set(Some(status));
+    active.status = Set(Some(status.clone()));
     active.updated_at = Set(Some(chrono::Utc::now()));
-
-    let result = active.update(db).await?;
-    let action = match status_clone.as_str() {
+    let result = active.update(&txn).await?;
+    workflow::record_transition(
+        &txn,
+        result.id,
+        &from,
+        &status,
+        approver.as_deref(),
+        comment.as_deref(),
+    )
+    .await?;
+    let reservations =
+        crate::services::material_lifecycle_service::sync_plan_reservations(&txn, result.id)
+            .await?;
+    txn.commit().await?;
+    let action = match status.as_str() {
+        "submitted" => "submit",
+        "approved" => "approve",
+        "rejected" => "reject",
         "confirmed" => "confirm",
+        "executing" => "execute",
         "archived" => "archive",
         "saved" => "save",
         _ => "update",
@@ -265,8 +320,29 @@ pub async fn update_plan_status(
         action,
         Some("plan"),
         Some(result.id),
-        Some(format!("更新方案状态为 {}", status_clone)),
+        Some(format!(
+            "方案状态 {} → {}{}，锁定材料 {} 个，释放材料 {} 个",
+            from,
+            status,
+            approver
+                .as_deref()
+                .map(|a| format!("（审批人 {}）", a))
+                .unwrap_or_default(),
+            reservations.reserved,
+            reservations.released
+        )),
     )
     .await;
+    if status == workflow::PLAN_CONFIRMED && from != workflow::PLAN_CONFIRMED {
+        crate::services::handoff_service::dispatch_confirmed_plan(result.id).await;
+    }
     Ok(result)
 }
+
+/// 查询方案的审批/状态流转记录
+#[tauri::command]
+pub async fn get_plan_approvals(
+    plan_id: i32,
+) -> Result<Vec<crate::models::plan_approval::Model>, AppError> {
+    crate::services::plan_workflow_service::list_approvals(crate::db::get_db(), plan_id).await
+}
@@ -0,0 +1,149 @@
+use crate::services::report_service::{
+    PlanReportData, ReportCoil, ReportForecast, ReportMaintenance, ReportPlanMeta, ReportRisk,
+    ReportShiftConfig,
+};
+use crate::AppError;
+
+/// 装配方案 HTML 报告所需数据：方案信息、评估指标、排程项、班次与检修窗口、风险及待温预测
+pub(crate) async fn load_plan_report_data(
+    plan_id: i32,
+    forecast_days: Option<i32>,
+) -> Result<PlanReportData, AppError> {
+    use crate::db::get_db;
+    use crate::models::{maintenance_plan, material, schedule_item, schedule_plan, system_config};
+    use chrono::{Duration, NaiveDate, NaiveTime};
+    use sea_orm::*;
+
+    let db = get_db();
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let strategy_name = crate::services::strategy_revision_service::load_plan_strategy(db, &plan)
+        .await?
+        .map(|s| s.name)
+        .unwrap_or_default();
+
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(db)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|it| it.material_id).collect();
+    let mat_map: std::collections::HashMap<i32, material::Model> = if mat_ids.is_empty() {
+        std::collections::HashMap::new()
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect()
+    };
+    let coils: Vec<ReportCoil> = items
+        .iter()
+        .filter_map(|it| {
+            let m = mat_map.get(&it.material_id)?;
+            Some(ReportCoil {
+                sequence: it.sequence,
+                coil_id: m.coil_id.clone(),
+                steel_grade: m.steel_grade.clone(),
+                width: m.width,
+                thickness: m.thickness,
+                weight: m.weight,
+                shift_date: it.shift_date.clone(),
+                shift_type: it.shift_type.clone(),
+                planned_start: it.planned_start.clone(),
+                planned_end: it.planned_end.clone(),
+                is_roll_change: it.is_roll_change.unwrap_or(false),
+            })
+        })
+        .collect();
+
+    let shift_cfg: std::collections::HashMap<String, String> = system_config::Entity::find()
+        .filter(system_config::Column::ConfigGroup.eq("shift"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.config_key, c.config_value))
+        .collect();
+    let parse_time = |key: &str, default: &str| -> NaiveTime {
+        shift_cfg
+            .get(key)
+            .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M").ok())
+            .unwrap_or_else(|| NaiveTime::parse_from_str(default, "%H:%M").unwrap_or_default())
+    };
+    let shift_config = ReportShiftConfig {
+        day_start: parse_time("day_start", "08:00"),
+        day_end: parse_time("day_end", "20:00"),
+        night_start: parse_time("night_start", "20:00"),
+        night_end: parse_time("night_end", "08:00"),
+    };
+
+    // 检修计划：取与方案周期（含末日夜班跨零点）有交集的启用计划，转换为本地时间
+    let plan_start = NaiveDate::parse_from_str(&plan.start_date, "%Y-%m-%d")
+        .unwrap_or_else(|_| chrono::Local::now().date_naive());
+    let plan_end = NaiveDate::parse_from_str(&plan.end_date, "%Y-%m-%d").unwrap_or(plan_start);
+    let window_start = plan_start.and_time(NaiveTime::MIN);
+    let window_end = (plan_end + Duration::days(2)).and_time(NaiveTime::MIN);
+    let maintenance: Vec<ReportMaintenance> = maintenance_plan::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|m| m.is_active.unwrap_or(true))
+        .map(|m| ReportMaintenance {
+            title: m.title,
+            maintenance_type: m.maintenance_type,
+            start: m.start_time.with_timezone(&chrono::Local).naive_local(),
+            end: m.end_time.with_timezone(&chrono::Local).naive_local(),
+        })
+        .filter(|m| m.start < window_end && m.end > window_start)
+        .collect();
+
+    let eval = super::risk::evaluate_plan_kpis(plan_id).await?;
+    let risks = super::risk::get_risk_analysis(plan_id)
+        .await?
+        .violations
+        .into_iter()
+        .filter(|v| !v.ignored)
+        .map(|v| ReportRisk {
+            severity: v.severity,
+            constraint_type: v.constraint_type,
+            sequence: v.sequence,
+            coil_id: v.coil_id,
+            message: v.message,
+            due_date: v.due_date,
+        })
+        .collect();
+    let forecast = super::risk::get_waiting_forecast(forecast_days)
+        .await?
+        .into_iter()
+        .map(|f| ReportForecast {
+            ready_date: f.ready_date,
+            count: f.count,
+            total_weight: f.total_weight,
+        })
+        .collect();
+
+    Ok(PlanReportData {
+        meta: ReportPlanMeta {
+            plan_no: plan.plan_no,
+            name: plan.name,
+            period_type: plan.period_type,
+            start_date: plan.start_date,
+            end_date: plan.end_date,
+            status: plan.status.unwrap_or_default(),
+            version: plan.version.unwrap_or(1),
+            strategy_name,
+            remarks: plan.remarks.unwrap_or_default(),
+        },
+        eval,
+        coils,
+        shift_config,
+        maintenance,
+        risks,
+        forecast,
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
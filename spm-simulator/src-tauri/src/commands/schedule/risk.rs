@@ -133,11 +133,16 @@ pub struct ApplyRiskSuggestionResult {
 
 #[tauri::command]
 pub async fn get_risk_analysis(plan_id: i32) -> Result<RiskAnalysis, AppError> {
-    use crate::db::get_db;
-    use crate::models::{material, schedule_item, schedule_plan, strategy_template};
-    use sea_orm::*;
+    analyze_plan_risks(crate::db::get_db(), plan_id).await
+}
 
-    let db = get_db();
+/// 在给定连接上生成风险分析（可在事务内调用）
+pub(crate) async fn analyze_plan_risks<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    plan_id: i32,
+) -> Result<RiskAnalysis, AppError> {
+    use crate::models::{material, schedule_item, schedule_plan};
+    use sea_orm::*;
 
     // 加载方案
     let plan = schedule_plan::Entity::find_by_id(plan_id)
@@ -173,38 +178,36 @@ pub async fn get_risk_analysis(plan_id: i32) -> Result<RiskAnalysis, AppError> {
         .and_then(|s| serde_json::from_str(s).ok())
         .unwrap_or_default();
 
-    let (width_jump_threshold, thickness_jump_threshold) =
-        if let Some(strategy_id) = plan.strategy_id {
-            let strategy = strategy_template::Entity::find_by_id(strategy_id)
-                .one(db)
-                .await?;
+    let (width_jump_threshold, thickness_jump_threshold) = if plan.strategy_id.is_some() {
+        let strategy =
+            crate::services::strategy_revision_service::load_plan_strategy(db, &plan).await?;
 
-            let width = strategy
-                .as_ref()
-                .and_then(|s| validator::parse_hard_constraints(&s.constraints).ok())
-                .and_then(|cfg| {
-                    cfg.constraints
-                        .into_iter()
-                        .find(|c| c.constraint_type == "width_jump" && c.enabled)
-                        .and_then(|c| c.max_value)
-                })
-                .unwrap_or(100.0);
-
-            let thickness = strategy
-                .and_then(|s| s.soft_constraints)
-                .and_then(|soft| validator::parse_soft_constraints(&soft).ok())
-                .and_then(|cfg| {
-                    cfg.constraints
-                        .into_iter()
-                        .find(|c| c.constraint_type == "thickness_jump" && c.enabled)
-                        .and_then(|c| c.threshold)
-                })
-                .unwrap_or(1.0);
-
-            (width, thickness)
-        } else {
-            (100.0, 1.0)
-        };
+        let width = strategy
+            .as_ref()
+            .and_then(|s| validator::parse_hard_constraints(&s.constraints).ok())
+            .and_then(|cfg| {
+                cfg.constraints
+                    .into_iter()
+                    .find(|c| c.constraint_type == "width_jump" && c.enabled)
+                    .and_then(|c| c.max_value)
+            })
+            .unwrap_or(100.0);
+
+        let thickness = strategy
+            .and_then(|s| s.soft_constraints)
+            .and_then(|soft| validator::parse_soft_constraints(&soft).ok())
+            .and_then(|cfg| {
+                cfg.constraints
+                    .into_iter()
+                    .find(|c| c.constraint_type == "thickness_jump" && c.enabled)
+                    .and_then(|c| c.threshold)
+            })
+            .unwrap_or(1.0);
+
+        (width, thickness)
+    } else {
+        (100.0, 1.0)
+    };
 
     // 解析风险标记
     let mut violations = Vec::new();
@@ -454,34 +457,21 @@ pub async fn ignore_risk(
     constraint_type: String,
     material_id: i32,
 ) -> Result<Vec<IgnoredRiskEntry>, AppError> {
-    use crate::db::get_db;
-    use crate::models::schedule_plan;
-    use sea_orm::*;
-
-    let db = get_db();
-    let plan = schedule_plan::Entity::find_by_id(plan_id)
-        .one(db)
-        .await?
-        .ok_or(AppError::PlanNotFound(plan_id))?;
-
-    let mut ignored: Vec<IgnoredRiskEntry> = plan
-        .ignored_risks
-        .as_deref()
-        .and_then(|s| serde_json::from_str(s).ok())
-        .unwrap_or_default();
-
     let entry = IgnoredRiskEntry {
         constraint_type: constraint_type.clone(),
         material_id,
     };
-
-    if !ignored.contains(&entry) {
-        ignored.push(entry);
-    }
-
-    let mut plan_active: schedule_plan::ActiveModel = plan.into();
-    plan_active.ignored_risks = Set(Some(serde_json::to_string(&ignored).unwrap_or_default()));
-    plan_active.update(db).await?;
+    let ignored = update_ignored_risks(
+        plan_id,
+        "ignore_risk",
+        format!("忽略风险 {} (material_id={})", constraint_type, material_id),
+        |ignored| {
+            if !ignored.contains(&entry) {
+                ignored.push(entry);
+            }
+        },
+    )
+    .await?;
 
     write_operation_log(
         "schedule",
@@ -503,40 +493,64 @@ pub async fn unignore_risk(
     plan_id: i32,
     constraint_type: String,
     material_id: i32,
+) -> Result<Vec<IgnoredRiskEntry>, AppError> {
+    let ignored = update_ignored_risks(
+        plan_id,
+        "unignore_risk",
+        format!(
+            "取消忽略风险 {} (material_id={})",
+            constraint_type, material_id
+        ),
+        |ignored| {
+            ignored
+                .retain(|e| !(e.constraint_type == constraint_type && e.material_id == material_id))
+        },
+    )
+    .await?;
+
+    write_operation_log(
+        "schedule",
+        "unignore_risk",
+        Some("plan"),
+        Some(plan_id),
+        Some(format!(
+            "取消忽略风险: constraint={}, material_id={}",
+            constraint_type, material_id
+        )),
+    )
+    .await;
+
+    Ok(ignored)
+}
+
+/// 在事务内修改方案的风险忽略清单并记录撤销项，返回修改后的清单
+async fn update_ignored_risks(
+    plan_id: i32,
+    action_type: &str,
+    description: String,
+    mutate: impl FnOnce(&mut Vec<IgnoredRiskEntry>),
 ) -> Result<Vec<IgnoredRiskEntry>, AppError> {
     use crate::db::get_db;
     use crate::models::schedule_plan;
     use sea_orm::*;
 
-    let db = get_db();
-    let plan = schedule_plan::Entity::find_by_id(plan_id)
-        .one(db)
-        .await?
-        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let tx = get_db().begin().await?;
+    // 审批中及之后的方案，风险忽略清单随方案一起冻结
+    let plan = crate::services::plan_workflow_service::ensure_plan_editable(&tx, plan_id).await?;
+    let before = crate::services::undo_service::capture(&tx, plan_id).await?;
 
     let mut ignored: Vec<IgnoredRiskEntry> = plan
         .ignored_risks
         .as_deref()
         .and_then(|s| serde_json::from_str(s).ok())
         .unwrap_or_default();
-
-    ignored.retain(|e| !(e.constraint_type == constraint_type && e.material_id == material_id));
+    mutate(&mut ignored);
 
     let mut plan_active: schedule_plan::ActiveModel = plan.into();
     plan_active.ignored_risks = Set(Some(serde_json::to_string(&ignored).unwrap_or_default()));
-    plan_active.update(db).await?;
-
-    write_operation_log(
-        "schedule",
-        "unignore_risk",
-        Some("plan"),
-        Some(plan_id),
-        Some(format!(
-            "取消忽略风险: constraint={}, material_id={}",
-            constraint_type, material_id
-        )),
-    )
-    .await;
+    plan_active.update(&tx).await?;
+    crate::services::undo_service::record(&tx, plan_id, action_type, description, &before).await?;
+    tx.commit().await?;
 
     Ok(ignored)
 }
@@ -570,12 +584,38 @@ fn classify_ready_date_in_plan(
     }
 }
 
-async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
-    use crate::db::get_db;
-    use crate::models::{material, schedule_item, schedule_plan, strategy_template};
-    use sea_orm::*;
+pub(super) async fn recalculate_risk_flags<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+) -> Result<(), AppError> {
+    reevaluate_plan(conn, plan_id, true, None).await.map(|_| ())
+}
 
-    let db = get_db();
+/// 按给定换辊点重新评估方案并回写（换辊点优化后使用，不再按贪心重算换辊）
+pub(super) async fn recalculate_with_roll_changes<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    roll_changes: &[roll_change::RollChangePoint],
+) -> Result<evaluator::EvalResult, AppError> {
+    reevaluate_plan(conn, plan_id, true, Some(roll_changes)).await
+}
+
+/// 按当前排程重新评估方案，不写回数据库（用于报告等只读场景）
+pub(crate) async fn evaluate_plan_kpis(plan_id: i32) -> Result<evaluator::EvalResult, AppError> {
+    reevaluate_plan(crate::db::get_db(), plan_id, false, None).await
+}
+
+/// 重新校验并评估方案；persist 为 true 时回写排程项 risk_flags 与方案评分
+///
+/// roll_override 为 None 时按贪心规则重算换辊点，否则直接采用给定换辊点。
+async fn reevaluate_plan<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    plan_id: i32,
+    persist: bool,
+    roll_override: Option<&[roll_change::RollChangePoint]>,
+) -> Result<evaluator::EvalResult, AppError> {
+    use crate::models::{material, schedule_item, schedule_plan};
+    use sea_orm::*;
 
     // 1. 加载方案及关联策略
     let plan = schedule_plan::Entity::find_by_id(plan_id)
@@ -583,12 +623,13 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
         .await?
         .ok_or(AppError::PlanNotFound(plan_id))?;
 
-    let strategy_id = plan
-        .strategy_id
-        .ok_or_else(|| AppError::Internal("方案未关联策略模板，无法重算风险".into()))?;
-
-    let strategy = strategy_template::Entity::find_by_id(strategy_id)
-        .one(db)
+    if plan.strategy_id.is_none() {
+        return Err(AppError::Internal(
+            "方案未关联策略模板，无法重算风险".into(),
+        ));
+    }
+    // 按方案记录的策略修订快照评估，旧方案未记录修订时取模板当前内容
+    let strategy = crate::services::strategy_revision_service::load_plan_strategy(db, &plan)
         .await?
         .ok_or_else(|| AppError::Internal("策略模板不存在".into()))?;
 
@@ -613,7 +654,7 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
         .unwrap_or_else(|_| chrono::Utc::now().date_naive());
     let plan_end =
         chrono::NaiveDate::parse_from_str(&plan.end_date, "%Y-%m-%d").unwrap_or(plan_start);
-    let temper_config = crate::services::temp_service::load_temper_config().await?;
+    let temper_config = crate::services::temp_service::load_temper_config(db).await?;
 
     // 2. 按新序位加载排程项和材料
     let items = schedule_item::Entity::find()
@@ -642,7 +683,7 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
         let mut earliest_schedule_date = None;
         if m.temp_status.as_deref() != Some("ready") {
             if let Some(ready_date) =
-                crate::services::temp_service::calculate_ready_date(&m.coiling_time, &temper_config)
+                crate::services::temp_service::calculate_ready_date(m, &temper_config)
             {
                 match classify_ready_date_in_plan(&ready_date, plan_start, plan_end) {
                     ReadyDatePlacement::AvailableAtStart(date) => {
@@ -723,57 +764,74 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
         violation_by_mat.entry(v.material_id).or_default().push(v);
     }
 
-    for it in &items {
-        let flags = violation_by_mat.get(&it.material_id);
-        let mut flag_vec: Vec<serde_json::Value> = match flags {
-            Some(vs) => vs
-                .iter()
-                .filter_map(|v| serde_json::to_value(v).ok())
-                .collect(),
-            _ => vec![],
-        };
-
-        // 追加 shift_capacity 违规
-        for (mat_id, msg) in &shift_capacity_violations {
-            if *mat_id == it.material_id {
+    if persist {
+        for it in &items {
+            let flags = violation_by_mat.get(&it.material_id);
+            let mut flag_vec: Vec<serde_json::Value> = match flags {
+                Some(vs) => vs
+                    .iter()
+                    .filter_map(|v| serde_json::to_value(v).ok())
+                    .collect(),
+                _ => vec![],
+            };
+
+            // 追加 shift_capacity 违规
+            for (mat_id, msg) in &shift_capacity_violations {
+                if *mat_id == it.material_id {
+                    flag_vec.push(serde_json::json!({
+                        "constraint_type": "shift_capacity",
+                        "severity": "medium",
+                        "message": msg,
+                        "material_index": 0,
+                        "material_id": mat_id,
+                    }));
+                }
+            }
+            if let Some(ready_date) = rolling_ready_date_by_material.get(&it.material_id) {
                 flag_vec.push(serde_json::json!({
-                    "constraint_type": "shift_capacity",
-                    "severity": "medium",
-                    "message": msg,
-                    "material_index": 0,
-                    "material_id": mat_id,
+                    "constraint_type": "rolling_temp",
+                    "severity": "info",
+                    "message": format!("滚动适温: 预计{}适温", ready_date),
+                    "material_id": it.material_id,
+                    "ready_date": ready_date,
                 }));
             }
-        }
-        if let Some(ready_date) = rolling_ready_date_by_material.get(&it.material_id) {
-            flag_vec.push(serde_json::json!({
-                "constraint_type": "rolling_temp",
-                "severity": "info",
-                "message": format!("滚动适温: 预计{}适温", ready_date),
-                "material_id": it.material_id,
-                "ready_date": ready_date,
-            }));
-        }
 
-        let risk_json = if flag_vec.is_empty() {
-            None
-        } else {
-            Some(serde_json::to_string(&flag_vec).unwrap_or_default())
-        };
-        schedule_item::Entity::update_many()
-            .col_expr(schedule_item::Column::RiskFlags, Expr::value(risk_json))
-            .filter(schedule_item::Column::Id.eq(it.id))
-            .exec(db)
-            .await?;
+            let risk_json = if flag_vec.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_string(&flag_vec).unwrap_or_default())
+            };
+            schedule_item::Entity::update_many()
+                .col_expr(schedule_item::Column::RiskFlags, Expr::value(risk_json))
+                .filter(schedule_item::Column::Id.eq(it.id))
+                .exec(db)
+                .await?;
+        }
     }
 
     // 6. 重新评估方案分数并更新 plan 表
     let roll_config = roll_change::extract_roll_config(&hard_config);
-    let roll_changes = roll_change::calculate_roll_changes(&sorted, &roll_config);
+    let roll_changes = match roll_override {
+        Some(points) => points.to_vec(),
+        None => roll_change::calculate_roll_changes(&sorted, &roll_config),
+    };
     let rc_indices = roll_change::roll_change_indices(&roll_changes);
 
-    let (soft_adjust, soft_details) =
+    let (mut soft_adjust, mut soft_details) =
         validator::evaluate_soft_constraints(&sorted, &soft_config, &rc_indices);
+    if crate::engine::yard::find_yard_constraint(&soft_config.constraints).is_some() {
+        let (yard_materials, yard_cost_config) = super::yard::load_yard_inputs(db).await?;
+        if let Some(detail) = crate::engine::yard::evaluate_sequence_yard_cost(
+            &sorted,
+            &yard_materials,
+            &soft_config,
+            &yard_cost_config,
+        ) {
+            soft_adjust += detail.adjust;
+            soft_details.push(detail);
+        }
+    }
 
     let plan_days = {
         let start = chrono::NaiveDate::parse_from_str(&plan.start_date, "%Y-%m-%d").ok();
@@ -799,6 +857,10 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
     let total_weight: f64 = sorted.iter().map(|s| s.material.weight).sum();
     let roll_change_count = roll_changes.len() as i32;
 
+    if !persist {
+        return Ok(eval);
+    }
+
     let mut plan_active: schedule_plan::ActiveModel = plan.into();
     plan_active.total_count = Set(Some(total_count));
     plan_active.total_weight = Set(Some(total_weight));
@@ -810,7 +872,7 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
     plan_active.risk_count_high = Set(Some(eval.risk_high));
     plan_active.risk_count_medium = Set(Some(eval.risk_medium));
     plan_active.risk_count_low = Set(Some(eval.risk_low));
-    plan_active.risk_summary = Set(Some(eval.risk_summary));
+    plan_active.risk_summary = Set(Some(eval.risk_summary.clone()));
     plan_active.updated_at = Set(Some(chrono::Utc::now()));
     plan_active.update(db).await?;
 
@@ -821,7 +883,7 @@ async fn recalculate_risk_flags(plan_id: i32) -> Result<(), AppError> {
         eval.score_overall,
     );
 
-    Ok(())
+    Ok(eval)
 }
 
 #[tauri::command]
@@ -830,9 +892,10 @@ pub async fn apply_risk_suggestion(
     risk_id: String,
 ) -> Result<ApplyRiskSuggestionResult, AppError> {
     use crate::db::get_db;
-    use crate::models::{material, schedule_item, schedule_plan, strategy_template};
+    use crate::models::{material, schedule_item, schedule_plan};
     use sea_orm::*;
 
+    crate::services::plan_workflow_service::ensure_plan_editable(get_db(), plan_id).await?;
     let idx: usize = risk_id
         .parse()
         .map_err(|_| AppError::DataConversionError(format!("无效风险ID: {}", risk_id)))?;
@@ -881,8 +944,10 @@ pub async fn apply_risk_suggestion(
         .one(db)
         .await?
         .ok_or(AppError::PlanNotFound(plan_id))?;
-    let width_jump_threshold = if let Some(sid) = plan_record.strategy_id {
-        let strategy = strategy_template::Entity::find_by_id(sid).one(db).await?;
+    let width_jump_threshold = if plan_record.strategy_id.is_some() {
+        let strategy =
+            crate::services::strategy_revision_service::load_plan_strategy(db, &plan_record)
+                .await?;
         strategy
             .and_then(|s| validator::parse_hard_constraints(&s.constraints).ok())
             .and_then(|hc| {
@@ -1210,13 +1275,15 @@ pub async fn apply_risk_suggestion(
     };
 
     if changed {
+        let tx = db.begin().await?;
+        let before = crate::services::undo_service::capture(&tx, plan_id).await?;
         // Phase 1: set sequences to negative temporary values
         for (i, it) in items.iter().enumerate() {
             let temp_seq = -(i as i32 + 1);
             schedule_item::Entity::update_many()
                 .col_expr(schedule_item::Column::Sequence, Expr::value(temp_seq))
                 .filter(schedule_item::Column::Id.eq(it.id))
-                .exec(db)
+                .exec(&tx)
                 .await?;
         }
         // Phase 2: set sequences to correct positive values + sync shift_date/shift_type
@@ -1233,12 +1300,21 @@ pub async fn apply_risk_suggestion(
                     Expr::value(it.shift_type.clone()),
                 )
                 .filter(schedule_item::Column::Id.eq(it.id))
-                .exec(db)
+                .exec(&tx)
                 .await?;
         }
 
         // Phase 3: recalculate risk_flags and plan scores based on new sequence
-        recalculate_risk_flags(plan_id).await?;
+        recalculate_risk_flags(&tx, plan_id).await?;
+        crate::services::undo_service::record(
+            &tx,
+            plan_id,
+            "apply_risk_suggestion",
+            action_note.clone(),
+            &before,
+        )
+        .await?;
+        tx.commit().await?;
     }
 
     write_operation_log(
@@ -1271,27 +1347,16 @@ pub async fn get_waiting_forecast(
 ) -> Result<Vec<WaitingForecastItem>, AppError> {
     use crate::db::get_db;
     use crate::models::material;
-    use chrono::Datelike;
     use sea_orm::*;
     use std::collections::BTreeMap;
 
     let db = get_db();
-    let config = crate::services::temp_service::load_temper_config().await?;
+    let config = crate::services::temp_service::load_temper_config(db).await?;
     let days = forecast_days.unwrap_or(7).max(1);
     let now = chrono::Utc::now();
-    let current_month = now.month();
-
-    let threshold = if config.spring_months.contains(&current_month) {
-        config.spring_days
-    } else if config.summer_months.contains(&current_month) {
-        config.summer_days
-    } else if config.autumn_months.contains(&current_month) {
-        config.autumn_days
-    } else if config.winter_months.contains(&current_month) {
-        config.winter_days
-    } else {
-        config.spring_days
-    };
+    let horizon = (now + chrono::Duration::days(days as i64))
+        .format("%Y-%m-%d")
+        .to_string();
 
     let waiting_materials = material::Entity::find()
         .filter(material::Column::TempStatus.eq("waiting"))
@@ -1301,14 +1366,10 @@ pub async fn get_waiting_forecast(
 
     let mut map: BTreeMap<String, (i32, f64)> = BTreeMap::new();
     for m in waiting_materials {
-        let wait_days = now.signed_duration_since(m.coiling_time).num_days() as i32;
-        let remain_days = (threshold - wait_days).max(1);
-        if remain_days > days {
+        let ready_date = waiting_ready_date(&m, &config, now);
+        if ready_date > horizon {
             continue;
         }
-        let ready_date = (now + chrono::Duration::days(remain_days as i64))
-            .format("%Y-%m-%d")
-            .to_string();
         let entry = map.entry(ready_date).or_insert((0, 0.0));
         entry.0 += 1;
         entry.1 += m.weight;
@@ -1332,29 +1393,15 @@ pub async fn get_waiting_forecast_details(
 ) -> Result<Vec<WaitingForecastDetailItem>, AppError> {
     use crate::db::get_db;
     use crate::models::material;
-    use chrono::Datelike;
     use sea_orm::*;
 
     let db = get_db();
-    let config = crate::services::temp_service::load_temper_config().await?;
+    let config = crate::services::temp_service::load_temper_config(db).await?;
     let now = chrono::Utc::now();
-    let current_month = now.month();
 
     let _target_date = chrono::NaiveDate::parse_from_str(&ready_date, "%Y-%m-%d")
         .map_err(|_| AppError::DataConversionError(format!("无效日期格式: {}", ready_date)))?;
 
-    let threshold = if config.spring_months.contains(&current_month) {
-        config.spring_days
-    } else if config.summer_months.contains(&current_month) {
-        config.summer_days
-    } else if config.autumn_months.contains(&current_month) {
-        config.autumn_days
-    } else if config.winter_months.contains(&current_month) {
-        config.winter_days
-    } else {
-        config.spring_days
-    };
-
     let waiting_materials = material::Entity::find()
         .filter(material::Column::TempStatus.eq("waiting"))
         .filter(material::Column::Status.eq("pending"))
@@ -1364,10 +1411,7 @@ pub async fn get_waiting_forecast_details(
     let mut result = Vec::new();
     for m in waiting_materials {
         let wait_days = now.signed_duration_since(m.coiling_time).num_days() as i32;
-        let remain_days = (threshold - wait_days).max(1);
-        let material_ready_date = (now + chrono::Duration::days(remain_days as i64))
-            .format("%Y-%m-%d")
-            .to_string();
+        let material_ready_date = waiting_ready_date(&m, &config, now);
         if material_ready_date != ready_date {
             continue;
         }
@@ -1397,6 +1441,16 @@ pub async fn get_waiting_forecast_details(
     Ok(result)
 }
 
+/// 待温材料的预计适温日期，与适温状态同一判定规则；按当前模型已适温但状态未刷新的，计为当天
+fn waiting_ready_date(
+    m: &crate::models::material::Model,
+    config: &crate::services::temp_service::TemperConfig,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    crate::services::temp_service::calculate_ready_date(m, config)
+        .unwrap_or_else(|| now.format("%Y-%m-%d").to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{classify_ready_date_in_plan, ReadyDatePlacement};
@@ -1431,4 +1485,57 @@ mod tests {
         let placement = classify_ready_date_in_plan("2026-03-01", start, end);
         assert_eq!(placement, ReadyDatePlacement::Excluded);
     }
+
+    #[tokio::test]
+    async fn ignore_and_unignore_risk_are_undoable() {
+        use crate::models::schedule_plan;
+        use sea_orm::*;
+
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_risk_ignore_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = crate::db::get_db();
+        let plan = schedule_plan::ActiveModel {
+            plan_no: Set(format!("IG-{}", seed)),
+            name: Set("IG".to_string()),
+            period_type: Set("daily".to_string()),
+            start_date: Set("2026-02-13".to_string()),
+            end_date: Set("2026-02-13".to_string()),
+            status: Set(Some("draft".to_string())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        let ignored_of = || async {
+            let plan = schedule_plan::Entity::find_by_id(plan.id)
+                .one(db)
+                .await
+                .unwrap()
+                .unwrap();
+            plan.ignored_risks
+                .as_deref()
+                .and_then(|s| serde_json::from_str::<Vec<super::IgnoredRiskEntry>>(s).ok())
+                .unwrap_or_default()
+                .len()
+        };
+
+        let ignored = super::ignore_risk(plan.id, "width_jump".to_string(), 7)
+            .await
+            .unwrap();
+        assert_eq!(ignored.len(), 1);
+        super::unignore_risk(plan.id, "width_jump".to_string(), 7)
+            .await
+            .unwrap();
+        assert_eq!(ignored_of().await, 0);
+
+        super::super::undo_action(plan.id).await.unwrap();
+        assert_eq!(ignored_of().await, 1);
+        super::super::undo_action(plan.id).await.unwrap();
+        assert_eq!(ignored_of().await, 0);
+        super::super::redo_action(plan.id).await.unwrap();
+        assert_eq!(ignored_of().await, 1);
+    }
 }
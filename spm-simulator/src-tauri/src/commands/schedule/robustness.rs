@@ -0,0 +1,129 @@
+use crate::engine::robustness::{self, RobustnessConfig, RobustnessReport, SimCoil};
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanRobustnessResult {
+    pub plan_id: i32,
+    /// 报告中分钟数的基准时刻（方案首个班次日期 00:00）
+    pub base_time: String,
+    pub report: RobustnessReport,
+}
+
+/// Monte Carlo 重放方案，评估班次按时率、单卷完工分布与交期延误概率
+#[tauri::command]
+pub async fn simulate_plan_robustness(
+    plan_id: i32,
+    config: Option<RobustnessConfig>,
+) -> Result<PlanRobustnessResult, AppError> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+
+    let (base_time, coils) = load_plan_sim_coils(plan_id).await?;
+    Ok(PlanRobustnessResult {
+        plan_id,
+        base_time,
+        report: robustness::simulate_robustness(&coils, &config),
+    })
+}
+
+/// 将方案排程项转换为重放输入，返回（基准时刻, 钢卷序列）
+///
+/// 基准时刻为方案首个班次日期 00:00；方案为空时基准时刻为空串。
+pub(crate) async fn load_plan_sim_coils(plan_id: i32) -> Result<(String, Vec<SimCoil>), AppError> {
+    use crate::db::get_db;
+    use crate::engine::scheduler;
+    use crate::models::{material, schedule_item, schedule_plan};
+    use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+    use sea_orm::*;
+
+    let db = get_db();
+    schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(db)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|it| it.material_id).collect();
+    let mat_map: std::collections::HashMap<i32, material::Model> = if mat_ids.is_empty() {
+        std::collections::HashMap::new()
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect()
+    };
+
+    // 班次时间窗（分钟，夜班结束为次日白班开始）与换辊时长
+    let [(_, day_start, day_end), (_, _, night_end)] =
+        scheduler::shift_windows(&scheduler::load_config_map(db).await?);
+    let roll_change_min = super::roll_change::plan_roll_config(plan_id)
+        .await?
+        .change_duration_min;
+
+    let Some(base_date) = items
+        .iter()
+        .filter_map(|it| NaiveDate::parse_from_str(&it.shift_date, "%Y-%m-%d").ok())
+        .min()
+    else {
+        return Ok((String::new(), vec![]));
+    };
+    let base = base_date.and_time(NaiveTime::MIN);
+    let minute_of_day = |t: NaiveTime| (t.hour() * 60 + t.minute()) as f64;
+    let to_min = |dt: NaiveDateTime| (dt - base).num_seconds() as f64 / 60.0;
+
+    let mut coils = Vec::with_capacity(items.len());
+    for it in &items {
+        let (Some(mat), Ok(date)) = (
+            mat_map.get(&it.material_id),
+            NaiveDate::parse_from_str(&it.shift_date, "%Y-%m-%d"),
+        ) else {
+            continue;
+        };
+        let is_night = it.shift_type == "night";
+        let (Some(start), Some(end)) = (
+            it.planned_start
+                .as_deref()
+                .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M").ok()),
+            it.planned_end
+                .as_deref()
+                .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M").ok()),
+        ) else {
+            continue;
+        };
+        // 夜班跨零点后的时刻归属次日
+        let start_date = if is_night && minute_of_day(start) < day_start {
+            date + Duration::days(1)
+        } else {
+            date
+        };
+        let start_at = start_date.and_time(start);
+        let mut duration = (end - start).num_minutes();
+        if duration < 0 {
+            duration += 24 * 60;
+        }
+        let shift_end_at = date.and_time(NaiveTime::MIN)
+            + Duration::minutes(if is_night { night_end } else { day_end } as i64);
+
+        coils.push(SimCoil {
+            material_id: mat.id,
+            coil_id: mat.coil_id.clone(),
+            shift_key: format!("{} {}", it.shift_date, it.shift_type),
+            shift_end_min: to_min(shift_end_at),
+            planned_start_min: to_min(start_at),
+            planned_duration_min: duration as f64,
+            is_roll_change: it.is_roll_change.unwrap_or(false),
+            roll_change_min,
+            due_min: mat.due_date.map(|d| to_min(d.naive_utc())),
+        });
+    }
+
+    Ok((base.format("%Y-%m-%d %H:%M").to_string(), coils))
+}
@@ -0,0 +1,94 @@
+use crate::engine::{roll_change, scheduler, validator};
+use crate::utils::log::write_operation_log;
+use crate::AppError;
+
+/// 读取方案关联策略的换辊配置（未关联策略时取默认值）
+pub(crate) async fn plan_roll_config(
+    plan_id: i32,
+) -> Result<roll_change::RollChangeConfig, AppError> {
+    use crate::db::get_db;
+    use crate::models::schedule_plan;
+    use sea_orm::*;
+
+    let db = get_db();
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let Some(strategy) =
+        crate::services::strategy_revision_service::load_plan_strategy(db, &plan).await?
+    else {
+        return Ok(roll_change::RollChangeConfig::default());
+    };
+    let hard_config = validator::parse_hard_constraints(&strategy.constraints)?;
+    Ok(roll_change::extract_roll_config(&hard_config))
+}
+
+/// 对已编辑的方案重新优化换辊点（动态规划，按日独立求解）
+#[tauri::command]
+pub async fn reoptimize_roll_changes(
+    plan_id: i32,
+) -> Result<scheduler::RollChangeReoptimizeOutput, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    let tx = db.begin().await?;
+    let plan = crate::services::plan_workflow_service::ensure_plan_editable(&tx, plan_id).await?;
+    if plan.strategy_id.is_none() {
+        return Err(AppError::Internal(
+            "方案未关联策略模板，无法优化换辊点".into(),
+        ));
+    }
+    let strategy = crate::services::strategy_revision_service::load_plan_strategy(&tx, &plan)
+        .await?
+        .ok_or_else(|| AppError::Internal("策略模板不存在".into()))?;
+
+    let hard_config = validator::parse_hard_constraints(&strategy.constraints)?;
+    let soft_config = strategy
+        .soft_constraints
+        .as_deref()
+        .map(validator::parse_soft_constraints)
+        .transpose()?
+        .unwrap_or(validator::SoftConstraintsConfig {
+            constraints: vec![],
+        });
+    let roll_config = roll_change::extract_roll_config(&hard_config);
+    let weights = roll_change::RollChangeCostWeights::from_soft_constraints(&soft_config);
+
+    let before = crate::services::undo_service::capture(&tx, plan_id).await?;
+    let output =
+        scheduler::apply_optimal_roll_changes(&tx, plan_id, &roll_config, &weights).await?;
+    // 评分、风险计数与 risk_summary 按优化后的换辊点重算
+    super::risk::recalculate_with_roll_changes(&tx, plan_id, &output.roll_changes).await?;
+    crate::services::undo_service::record(
+        &tx,
+        plan_id,
+        "reoptimize_roll_changes",
+        format!(
+            "换辊点优化: {} 次 → {} 次",
+            output.before_count, output.after_count
+        ),
+        &before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    write_operation_log(
+        "schedule",
+        "reoptimize_roll_changes",
+        Some("plan"),
+        Some(plan_id),
+        Some(format!(
+            "换辊点优化: {} 次 → {} 次, 代价 {:.1} → {:.1}, 调整 {} 项",
+            output.before_count,
+            output.after_count,
+            output.before_cost,
+            output.after_cost,
+            output.changed_items
+        )),
+    )
+    .await;
+
+    Ok(output)
+}
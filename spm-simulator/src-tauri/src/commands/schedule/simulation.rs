@@ -0,0 +1,285 @@
+use crate::engine::robustness::SimCoil;
+use crate::engine::simulation::{self, SimulationEvent, SimulationScore, SimulationTimeline};
+use crate::models::{simulation_event, simulation_session};
+use crate::utils::log::write_operation_log;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+
+/// 默认快照间隔（分钟）
+const DEFAULT_SNAPSHOT_EVERY: f64 = 5.0;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationReplay {
+    pub session: simulation_session::Model,
+    pub events: Vec<simulation_event::Model>,
+    pub timeline: SimulationTimeline,
+    pub score: SimulationScore,
+}
+
+async fn load_session(
+    session_id: i32,
+) -> Result<
+    (
+        simulation_session::Model,
+        Vec<SimCoil>,
+        Vec<simulation_event::Model>,
+        Vec<SimulationEvent>,
+    ),
+    AppError,
+> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    let session = simulation_session::Entity::find_by_id(session_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::InvalidInput(format!("模拟会话 {} 不存在", session_id)))?;
+    let coils: Vec<SimCoil> = serde_json::from_str(&session.plan_snapshot)
+        .map_err(|e| AppError::DataConversionError(format!("方案快照解析失败: {}", e)))?;
+    let rows = simulation_event::Entity::find()
+        .filter(simulation_event::Column::SessionId.eq(session_id))
+        .order_by_asc(simulation_event::Column::AtMinute)
+        .order_by_asc(simulation_event::Column::Id)
+        .all(db)
+        .await?;
+    let events = rows
+        .iter()
+        .map(|r| {
+            serde_json::from_str::<SimulationEvent>(&r.payload)
+                .map_err(|e| AppError::DataConversionError(format!("模拟事件解析失败: {}", e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((session, coils, rows, events))
+}
+
+/// 为已确认方案创建执行模拟会话（保存方案快照）
+#[tauri::command]
+pub async fn create_simulation_session(
+    plan_id: i32,
+    name: Option<String>,
+) -> Result<simulation_session::Model, AppError> {
+    use crate::db::get_db;
+    use crate::models::schedule_plan;
+    use sea_orm::*;
+
+    let db = get_db();
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    if !crate::services::plan_workflow_service::is_effective(
+        crate::services::plan_workflow_service::status_of(&plan),
+    ) {
+        return Err(AppError::ConstraintViolation(
+            "仅已确认或执行中的方案可进行执行模拟".into(),
+        ));
+    }
+
+    let (base_time, coils) = super::robustness::load_plan_sim_coils(plan_id).await?;
+    if coils.is_empty() {
+        return Err(AppError::ConstraintViolation("方案无可模拟的排程项".into()));
+    }
+    let start_minute = simulation::start_minute(&coils);
+    let snapshot =
+        serde_json::to_string(&coils).map_err(|e| AppError::DataConversionError(e.to_string()))?;
+
+    let now = chrono::Utc::now();
+    let session = simulation_session::ActiveModel {
+        plan_id: Set(plan_id),
+        name: Set(name.unwrap_or_else(|| format!("{} 执行模拟", plan.name))),
+        status: Set("running".into()),
+        base_time: Set(base_time),
+        plan_snapshot: Set(snapshot),
+        current_minute: Set(start_minute),
+        created_at: Set(Some(now)),
+        updated_at: Set(Some(now)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    write_operation_log(
+        "schedule",
+        "create_simulation",
+        Some("plan"),
+        Some(plan_id),
+        Some(format!(
+            "创建执行模拟会话 #{}: {}",
+            session.id, session.name
+        )),
+    )
+    .await;
+
+    Ok(session)
+}
+
+#[tauri::command]
+pub async fn get_simulation_sessions(
+    plan_id: Option<i32>,
+) -> Result<Vec<simulation_session::Model>, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    let mut query = simulation_session::Entity::find();
+    if let Some(pid) = plan_id {
+        query = query.filter(simulation_session::Column::PlanId.eq(pid));
+    }
+    Ok(query
+        .order_by_desc(simulation_session::Column::CreatedAt)
+        .all(db)
+        .await?)
+}
+
+/// 向运行中的会话注入事件（不早于当前模拟时刻）
+#[tauri::command]
+pub async fn inject_simulation_event(
+    session_id: i32,
+    event: SimulationEvent,
+) -> Result<simulation_event::Model, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    let (session, coils, _, _) = load_session(session_id).await?;
+    if session.status != "running" {
+        return Err(AppError::ConstraintViolation(
+            "模拟会话已结束，不能注入事件".into(),
+        ));
+    }
+    event.validate(simulation::plan_horizon(&coils))?;
+    if event.at_minute() < session.current_minute {
+        return Err(AppError::InvalidInput(format!(
+            "事件时刻 {} 早于当前模拟时刻 {}",
+            event.at_minute(),
+            session.current_minute
+        )));
+    }
+    let payload =
+        serde_json::to_string(&event).map_err(|e| AppError::DataConversionError(e.to_string()))?;
+
+    let row = simulation_event::ActiveModel {
+        session_id: Set(session_id),
+        at_minute: Set(event.at_minute()),
+        event_type: Set(event.type_name().to_string()),
+        payload: Set(payload),
+        created_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(row)
+}
+
+/// 推进模拟到指定时刻，返回从开始到该时刻的状态时间线
+#[tauri::command]
+pub async fn step_simulation(
+    session_id: i32,
+    to_minute: f64,
+    snapshot_every: Option<f64>,
+) -> Result<SimulationTimeline, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let (session, coils, _, events) = load_session(session_id).await?;
+    if session.status != "running" {
+        return Err(AppError::ConstraintViolation("模拟会话已结束".into()));
+    }
+    let (start, end) = simulation::plan_horizon(&coils);
+    if !to_minute.is_finite() || to_minute < start || to_minute > end {
+        return Err(AppError::InvalidInput(format!(
+            "推进时刻 {} 超出方案时间范围 {}~{}",
+            to_minute, start, end
+        )));
+    }
+    let to_minute = to_minute.max(session.current_minute);
+    let timeline = simulation::run_simulation(
+        &coils,
+        &events,
+        Some(to_minute),
+        snapshot_every.unwrap_or(DEFAULT_SNAPSHOT_EVERY),
+    );
+
+    let mut active: simulation_session::ActiveModel = session.into();
+    active.current_minute = Set(timeline.end_minute);
+    if timeline.finished {
+        let score = simulation::score_simulation(&timeline);
+        active.status = Set("finished".into());
+        active.score = Set(Some(score.score));
+        active.score_detail = Set(serde_json::to_string(&score).ok());
+    }
+    active.updated_at = Set(Some(chrono::Utc::now()));
+    active.update(get_db()).await?;
+
+    Ok(timeline)
+}
+
+/// 按事件日志完整重放会话并评分（会话随之结束）
+#[tauri::command]
+pub async fn replay_simulation_session(
+    session_id: i32,
+    snapshot_every: Option<f64>,
+) -> Result<SimulationReplay, AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let (session, coils, rows, events) = load_session(session_id).await?;
+    let timeline = simulation::run_simulation(
+        &coils,
+        &events,
+        None,
+        snapshot_every.unwrap_or(DEFAULT_SNAPSHOT_EVERY),
+    );
+    let score = simulation::score_simulation(&timeline);
+
+    let mut active: simulation_session::ActiveModel = session.into();
+    active.status = Set("finished".into());
+    active.current_minute = Set(timeline.end_minute);
+    active.score = Set(Some(score.score));
+    active.score_detail = Set(serde_json::to_string(&score).ok());
+    active.updated_at = Set(Some(chrono::Utc::now()));
+    let session = active.update(get_db()).await?;
+
+    Ok(SimulationReplay {
+        session,
+        events: rows,
+        timeline,
+        score,
+    })
+}
+
+#[tauri::command]
+pub async fn delete_simulation_session(session_id: i32) -> Result<(), AppError> {
+    use crate::db::get_db;
+    use sea_orm::*;
+
+    let db = get_db();
+    simulation_event::Entity::delete_many()
+        .filter(simulation_event::Column::SessionId.eq(session_id))
+        .exec(db)
+        .await?;
+    simulation_session::Entity::delete_by_id(session_id)
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn missing_session_is_reported_as_invalid_input() {
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_simulation_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+
+        let err = step_simulation(i32::MAX, 0.0, None).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+        let err = replay_simulation_session(i32::MAX, None).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    }
+}
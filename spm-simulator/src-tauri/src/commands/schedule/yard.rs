@@ -0,0 +1,69 @@
+use crate::engine::yard::{self, YardCostConfig, YardRetrievalReport};
+use crate::models::material;
+use crate::AppError;
+
+/// 加载库区吊运计算输入：在库材料（未完成）与 "yard" 分组代价参数
+pub(crate) async fn load_yard_inputs<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+) -> Result<(Vec<material::Model>, YardCostConfig), AppError> {
+    use crate::models::system_config;
+    use sea_orm::*;
+
+    let yard_materials = material::Entity::find()
+        .filter(material::Column::Status.ne("completed"))
+        .all(conn)
+        .await?;
+    let group: std::collections::HashMap<String, String> = system_config::Entity::find()
+        .filter(system_config::Column::ConfigGroup.eq("yard"))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|c| (c.config_key, c.config_value))
+        .collect();
+    Ok((
+        yard_materials,
+        YardCostConfig::from_config_group(Some(&group)),
+    ))
+}
+
+/// 按方案上线顺序计算库区吊运代价（跨库区次数、倒垛数、预计吊运时间）
+#[tauri::command]
+pub async fn get_plan_yard_cost(plan_id: i32) -> Result<YardRetrievalReport, AppError> {
+    use crate::db::get_db;
+    use crate::models::{schedule_item, schedule_plan};
+    use sea_orm::*;
+
+    let db = get_db();
+    schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(db)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|it| it.material_id).collect();
+    let mats = if mat_ids.is_empty() {
+        vec![]
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(db)
+            .await?
+    };
+    let mat_map: std::collections::HashMap<i32, material::Model> =
+        mats.into_iter().map(|m| (m.id, m)).collect();
+    let sequence: Vec<&material::Model> = items
+        .iter()
+        .filter_map(|it| mat_map.get(&it.material_id))
+        .collect();
+
+    let (yard_materials, cost_config) = load_yard_inputs(crate::db::get_db()).await?;
+    Ok(yard::calculate_retrieval_cost(
+        &sequence,
+        &yard_materials,
+        &cost_config,
+    ))
+}
@@ -72,3 +72,43 @@ pub const OVERDUE_SUPER_THRESHOLD_DAYS: i64 = 30;
 
 /// D+7 紧急天数阈值
 pub const DELIVERY_D7_THRESHOLD_DAYS: i64 = 7;
+
+// ─── 钢卷冷却模型 ───
+
+/// 钢密度（kg/m³）
+pub const STEEL_DENSITY: f64 = 7850.0;
+
+/// 钢比热容（J/kg·K，取 20~600℃ 平均值）
+pub const STEEL_SPECIFIC_HEAT: f64 = 500.0;
+
+/// 钢导热系数（W/m·K）
+pub const STEEL_CONDUCTIVITY: f64 = 45.0;
+
+/// 钢卷层间接触热阻（m²·K/W）
+pub const STEEL_INTERLAYER_RESISTANCE: f64 = 5.0e-4;
+
+/// 钢卷标准内径（m）
+pub const COIL_INNER_DIAMETER_M: f64 = 0.762;
+
+/// 冷却模拟步长（分钟）
+pub const COOLING_STEP_MINUTES: i64 = 30;
+
+// ─── 库区吊运 ───
+
+/// 单次吊取耗时（分钟）
+pub const DEFAULT_YARD_PICK_MINUTES: f64 = 2.0;
+
+/// 每倒垛一卷耗时（分钟）
+pub const DEFAULT_YARD_RELOCATE_MINUTES: f64 = 3.0;
+
+/// 跨库区行车移动耗时（分钟）
+pub const DEFAULT_YARD_BAY_TRAVEL_MINUTES: f64 = 4.0;
+
+/// 同库区每跨一排移动耗时（分钟）
+pub const DEFAULT_YARD_ROW_TRAVEL_MINUTES: f64 = 0.5;
+
+/// 排程候选打分：跨库区惩罚
+pub const YARD_BAY_SWITCH_SCORE_PENALTY: f64 = 0.05;
+
+/// 排程候选打分：每个压卷的倒垛惩罚
+pub const YARD_DIG_SCORE_PENALTY: f64 = 0.03;
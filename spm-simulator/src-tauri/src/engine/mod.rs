@@ -1,10 +1,15 @@
 pub mod constants;
 pub mod evaluator;
+pub mod plan_compare;
+pub mod plan_merge;
 pub mod priority;
+pub mod robustness;
 pub mod roll_change;
 pub mod scheduler;
+pub mod simulation;
 pub mod sorter;
 pub mod temp_calc;
 #[cfg(test)]
 pub mod test_helpers;
 pub mod validator;
+pub mod yard;
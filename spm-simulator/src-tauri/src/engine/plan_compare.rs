@@ -0,0 +1,535 @@
+//! 方案两两对比指标
+//!
+//! 以卷号标识钢卷，比较两个方案的上线序列：
+//!   - 序列相似度：Kendall tau（公共钢卷的成对顺序一致性）与最长公共子序列
+//!   - 班次差异：各班次的卷数/吨位及仅在一侧出现于该班次的钢卷
+//!   - 换辊点对齐：按序号位置对齐两侧换辊点，报告偏移与未对齐的换辊点
+//!   - 交期结果差异：公共钢卷中一侧按期、另一侧延误的钢卷
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 换辊点允许对齐的最大序号偏移，超过则视为两侧各自独立的换辊点
+pub const ROLL_ALIGN_MAX_OFFSET: usize = 20;
+
+/// 参与对比的排程项（按上线顺序排列）
+#[derive(Debug, Clone)]
+pub struct CompareCoil {
+    pub coil_id: String,
+    pub weight: f64,
+    /// 班次标识，如 "2026-03-01 day"
+    pub shift_key: String,
+    pub is_roll_change: bool,
+    pub planned_end: Option<NaiveDateTime>,
+    pub due_date: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SequenceSimilarity {
+    pub common_count: usize,
+    /// 公共钢卷的 Kendall tau，范围 [-1, 1]；公共钢卷不足 2 个时为 None
+    pub kendall_tau: Option<f64>,
+    /// 顺序相反的钢卷对数
+    pub discordant_pairs: u64,
+    pub lcs_length: usize,
+    /// LCS 长度 / 较长方案的卷数
+    pub lcs_similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShiftDiffItem {
+    pub shift_key: String,
+    pub count_a: usize,
+    pub count_b: usize,
+    pub count_delta: i64,
+    pub weight_a: f64,
+    pub weight_b: f64,
+    pub weight_delta: f64,
+    /// 方案A在该班次、方案B不在该班次的钢卷
+    pub only_a_coils: Vec<String>,
+    pub only_b_coils: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollChangePoint {
+    /// 换辊卷的序号（从 1 开始）
+    pub position: usize,
+    pub coil_id: String,
+    /// 换辊前累计轧制吨位（方案起点起算）
+    pub cumulative_weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollChangeAlignItem {
+    pub point_a: Option<RollChangePoint>,
+    pub point_b: Option<RollChangePoint>,
+    /// B 序号 - A 序号，仅对齐项有值
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollChangeAlignment {
+    pub count_a: usize,
+    pub count_b: usize,
+    pub aligned_count: usize,
+    pub mean_abs_offset: f64,
+    pub items: Vec<RollChangeAlignItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueOutcomeItem {
+    pub coil_id: String,
+    pub due_date: String,
+    pub end_a: String,
+    pub end_b: String,
+    /// 完工晚于交期的分钟数，按期为 0
+    pub late_minutes_a: i64,
+    pub late_minutes_b: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DueOutcomeDiff {
+    pub late_count_a: usize,
+    pub late_count_b: usize,
+    pub total_late_minutes_a: i64,
+    pub total_late_minutes_b: i64,
+    /// A 按期、B 延误
+    pub became_late: usize,
+    /// A 延误、B 按期
+    pub became_on_time: usize,
+    /// 交期结果不同的公共钢卷
+    pub items: Vec<DueOutcomeItem>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanPairAnalysis {
+    pub similarity: SequenceSimilarity,
+    pub shift_diffs: Vec<ShiftDiffItem>,
+    pub roll_change_alignment: RollChangeAlignment,
+    pub due_outcome: DueOutcomeDiff,
+}
+
+/// 归并排序统计逆序对
+fn count_inversions(values: &mut [usize]) -> u64 {
+    let n = values.len();
+    if n < 2 {
+        return 0;
+    }
+    let mid = n / 2;
+    let mut inversions =
+        count_inversions(&mut values[..mid]) + count_inversions(&mut values[mid..]);
+    let mut merged = Vec::with_capacity(n);
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < n {
+        if values[i] <= values[j] {
+            merged.push(values[i]);
+            i += 1;
+        } else {
+            inversions += (mid - i) as u64;
+            merged.push(values[j]);
+            j += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..mid]);
+    merged.extend_from_slice(&values[j..n]);
+    values.copy_from_slice(&merged);
+    inversions
+}
+
+/// 最长递增子序列长度
+fn lis_length(values: &[usize]) -> usize {
+    let mut tails: Vec<usize> = Vec::new();
+    for &v in values {
+        let idx = tails.partition_point(|&t| t < v);
+        if idx == tails.len() {
+            tails.push(v);
+        } else {
+            tails[idx] = v;
+        }
+    }
+    tails.len()
+}
+
+/// 序列相似度
+///
+/// 卷号在单个方案内唯一，两序列的公共子序列只能由公共钢卷构成，
+/// 因此 LCS 等价于「公共钢卷按 A 顺序排列后，其 B 序号的最长递增子序列」。
+pub fn sequence_similarity(a: &[String], b: &[String]) -> SequenceSimilarity {
+    let pos_b: HashMap<&str, usize> = b.iter().enumerate().map(|(i, c)| (c.as_str(), i)).collect();
+    let ranks: Vec<usize> = a
+        .iter()
+        .filter_map(|c| pos_b.get(c.as_str()).copied())
+        .collect();
+    let n = ranks.len();
+    let lcs_length = lis_length(&ranks);
+    let discordant_pairs = count_inversions(&mut ranks.clone());
+    let kendall_tau = if n < 2 {
+        None
+    } else {
+        let total = (n as u64 * (n as u64 - 1) / 2) as f64;
+        Some((total - 2.0 * discordant_pairs as f64) / total)
+    };
+    let longest = a.len().max(b.len());
+    SequenceSimilarity {
+        common_count: n,
+        kendall_tau,
+        discordant_pairs,
+        lcs_length,
+        lcs_similarity: if longest == 0 {
+            1.0
+        } else {
+            lcs_length as f64 / longest as f64
+        },
+    }
+}
+
+/// 班次卷数/吨位差异，按班次标识排序
+pub fn shift_diffs(a: &[CompareCoil], b: &[CompareCoil]) -> Vec<ShiftDiffItem> {
+    let shift_of_a: HashMap<&str, &str> = a
+        .iter()
+        .map(|c| (c.coil_id.as_str(), c.shift_key.as_str()))
+        .collect();
+    let shift_of_b: HashMap<&str, &str> = b
+        .iter()
+        .map(|c| (c.coil_id.as_str(), c.shift_key.as_str()))
+        .collect();
+
+    let mut map: BTreeMap<&str, ShiftDiffItem> = BTreeMap::new();
+    let entry = |key: &str| ShiftDiffItem {
+        shift_key: key.to_string(),
+        count_a: 0,
+        count_b: 0,
+        count_delta: 0,
+        weight_a: 0.0,
+        weight_b: 0.0,
+        weight_delta: 0.0,
+        only_a_coils: vec![],
+        only_b_coils: vec![],
+    };
+    for c in a {
+        let item = map
+            .entry(c.shift_key.as_str())
+            .or_insert_with(|| entry(&c.shift_key));
+        item.count_a += 1;
+        item.weight_a += c.weight;
+        if shift_of_b.get(c.coil_id.as_str()) != Some(&c.shift_key.as_str()) {
+            item.only_a_coils.push(c.coil_id.clone());
+        }
+    }
+    for c in b {
+        let item = map
+            .entry(c.shift_key.as_str())
+            .or_insert_with(|| entry(&c.shift_key));
+        item.count_b += 1;
+        item.weight_b += c.weight;
+        if shift_of_a.get(c.coil_id.as_str()) != Some(&c.shift_key.as_str()) {
+            item.only_b_coils.push(c.coil_id.clone());
+        }
+    }
+
+    map.into_values()
+        .map(|mut item| {
+            item.count_delta = item.count_b as i64 - item.count_a as i64;
+            item.weight_delta = item.weight_b - item.weight_a;
+            item
+        })
+        .collect()
+}
+
+fn roll_change_points(coils: &[CompareCoil]) -> Vec<RollChangePoint> {
+    let mut cumulative = 0.0;
+    let mut points = Vec::new();
+    for (idx, c) in coils.iter().enumerate() {
+        if c.is_roll_change {
+            points.push(RollChangePoint {
+                position: idx + 1,
+                coil_id: c.coil_id.clone(),
+                cumulative_weight: cumulative,
+            });
+        }
+        cumulative += c.weight;
+    }
+    points
+}
+
+/// 换辊点对齐
+///
+/// 单调对齐（不交叉），对齐代价为序号偏移绝对值，未对齐代价为 ROLL_ALIGN_MAX_OFFSET / 2，
+/// 因此只有偏移小于 ROLL_ALIGN_MAX_OFFSET 的换辊点才会被对齐。
+pub fn align_roll_changes(a: &[CompareCoil], b: &[CompareCoil]) -> RollChangeAlignment {
+    let pa = roll_change_points(a);
+    let pb = roll_change_points(b);
+    let (n, m) = (pa.len(), pb.len());
+    let gap = ROLL_ALIGN_MAX_OFFSET as f64 / 2.0;
+
+    // cost[i][j]: 对齐 pa[i..] 与 pb[j..] 的最小代价
+    let mut cost = vec![vec![0.0f64; m + 1]; n + 1];
+    for i in (0..=n).rev() {
+        for j in (0..=m).rev() {
+            if i == n && j == m {
+                continue;
+            }
+            let mut best = f64::INFINITY;
+            if i < n {
+                best = best.min(cost[i + 1][j] + gap);
+            }
+            if j < m {
+                best = best.min(cost[i][j + 1] + gap);
+            }
+            if i < n && j < m {
+                let offset = pa[i].position.abs_diff(pb[j].position);
+                if offset < ROLL_ALIGN_MAX_OFFSET {
+                    best = best.min(cost[i + 1][j + 1] + offset as f64);
+                }
+            }
+            cost[i][j] = best;
+        }
+    }
+
+    let mut items = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    let mut offset_sum = 0usize;
+    while i < n || j < m {
+        if i < n && j < m {
+            let offset = pa[i].position.abs_diff(pb[j].position);
+            if offset < ROLL_ALIGN_MAX_OFFSET && cost[i][j] == cost[i + 1][j + 1] + offset as f64 {
+                offset_sum += offset;
+                items.push(RollChangeAlignItem {
+                    point_a: Some(pa[i].clone()),
+                    point_b: Some(pb[j].clone()),
+                    offset: Some(pb[j].position as i64 - pa[i].position as i64),
+                });
+                i += 1;
+                j += 1;
+                continue;
+            }
+        }
+        // 两侧均可跳过时先输出序号靠前的一侧，使结果按位置交错排列
+        let skip_a = i < n && cost[i][j] == cost[i + 1][j] + gap;
+        let skip_b = j < m && cost[i][j] == cost[i][j + 1] + gap;
+        let take_a = skip_a && (!skip_b || pa[i].position <= pb[j].position);
+        if take_a {
+            items.push(RollChangeAlignItem {
+                point_a: Some(pa[i].clone()),
+                point_b: None,
+                offset: None,
+            });
+            i += 1;
+        } else {
+            items.push(RollChangeAlignItem {
+                point_a: None,
+                point_b: Some(pb[j].clone()),
+                offset: None,
+            });
+            j += 1;
+        }
+    }
+
+    let aligned_count = items.iter().filter(|it| it.offset.is_some()).count();
+    RollChangeAlignment {
+        count_a: n,
+        count_b: m,
+        aligned_count,
+        mean_abs_offset: if aligned_count > 0 {
+            offset_sum as f64 / aligned_count as f64
+        } else {
+            0.0
+        },
+        items,
+    }
+}
+
+fn late_minutes(coil: &CompareCoil) -> Option<i64> {
+    let end = coil.planned_end?;
+    let due = coil.due_date?;
+    Some((end - due).num_minutes().max(0))
+}
+
+/// 交期结果差异
+pub fn due_outcome_diff(a: &[CompareCoil], b: &[CompareCoil]) -> DueOutcomeDiff {
+    let mut diff = DueOutcomeDiff::default();
+    for c in a {
+        if let Some(late) = late_minutes(c).filter(|m| *m > 0) {
+            diff.late_count_a += 1;
+            diff.total_late_minutes_a += late;
+        }
+    }
+    for c in b {
+        if let Some(late) = late_minutes(c).filter(|m| *m > 0) {
+            diff.late_count_b += 1;
+            diff.total_late_minutes_b += late;
+        }
+    }
+
+    let map_b: HashMap<&str, &CompareCoil> = b.iter().map(|c| (c.coil_id.as_str(), c)).collect();
+    let fmt = |dt: Option<NaiveDateTime>| {
+        dt.map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    };
+    for ca in a {
+        let Some(cb) = map_b.get(ca.coil_id.as_str()) else {
+            continue;
+        };
+        let (Some(late_a), Some(late_b)) = (late_minutes(ca), late_minutes(cb)) else {
+            continue;
+        };
+        if (late_a > 0) == (late_b > 0) {
+            continue;
+        }
+        if late_b > 0 {
+            diff.became_late += 1;
+        } else {
+            diff.became_on_time += 1;
+        }
+        diff.items.push(DueOutcomeItem {
+            coil_id: ca.coil_id.clone(),
+            due_date: fmt(ca.due_date),
+            end_a: fmt(ca.planned_end),
+            end_b: fmt(cb.planned_end),
+            late_minutes_a: late_a,
+            late_minutes_b: late_b,
+        });
+    }
+    diff
+}
+
+/// 汇总两个方案的全部对比指标
+pub fn analyze_pair(a: &[CompareCoil], b: &[CompareCoil]) -> PlanPairAnalysis {
+    let seq_a: Vec<String> = a.iter().map(|c| c.coil_id.clone()).collect();
+    let seq_b: Vec<String> = b.iter().map(|c| c.coil_id.clone()).collect();
+    PlanPairAnalysis {
+        similarity: sequence_similarity(&seq_a, &seq_b),
+        shift_diffs: shift_diffs(a, b),
+        roll_change_alignment: align_roll_changes(a, b),
+        due_outcome: due_outcome_diff(a, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn seq(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn coil(id: &str, shift: &str, weight: f64, roll: bool) -> CompareCoil {
+        CompareCoil {
+            coil_id: id.to_string(),
+            weight,
+            shift_key: shift.to_string(),
+            is_roll_change: roll,
+            planned_end: None,
+            due_date: None,
+        }
+    }
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sequence_similarity_metrics() {
+        let same = sequence_similarity(&seq(&["A", "B", "C", "D"]), &seq(&["A", "B", "C", "D"]));
+        assert_eq!(same.kendall_tau, Some(1.0));
+        assert_eq!(same.lcs_length, 4);
+        assert!((same.lcs_similarity - 1.0).abs() < 1e-9);
+
+        let reversed =
+            sequence_similarity(&seq(&["A", "B", "C", "D"]), &seq(&["D", "C", "B", "A"]));
+        assert_eq!(reversed.kendall_tau, Some(-1.0));
+        assert_eq!(reversed.discordant_pairs, 6);
+        assert_eq!(reversed.lcs_length, 1);
+
+        // 公共钢卷 A,B,C,D 中仅 (B,C) 逆序：tau = (6 - 2) / 6
+        let partial = sequence_similarity(
+            &seq(&["A", "B", "C", "D", "X"]),
+            &seq(&["A", "C", "B", "Y", "D"]),
+        );
+        assert_eq!(partial.common_count, 4);
+        assert_eq!(partial.discordant_pairs, 1);
+        assert!((partial.kendall_tau.unwrap() - 4.0 / 6.0).abs() < 1e-9);
+        assert_eq!(partial.lcs_length, 3);
+        assert!((partial.lcs_similarity - 0.6).abs() < 1e-9);
+
+        assert_eq!(
+            sequence_similarity(&seq(&["A"]), &seq(&["A"])).kendall_tau,
+            None
+        );
+    }
+
+    #[test]
+    fn test_shift_diffs_and_roll_alignment() {
+        let a = vec![
+            coil("A", "d1 day", 10.0, false),
+            coil("B", "d1 day", 20.0, false),
+            coil("C", "d1 night", 30.0, true),
+        ];
+        let b = vec![
+            coil("A", "d1 day", 10.0, false),
+            coil("C", "d1 day", 30.0, true),
+            coil("B", "d1 night", 20.0, false),
+            coil("E", "d1 night", 5.0, true),
+        ];
+        let diffs = shift_diffs(&a, &b);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].shift_key, "d1 day");
+        assert!((diffs[0].weight_delta - 10.0).abs() < 1e-9);
+        assert_eq!(diffs[0].only_a_coils, vec!["B".to_string()]);
+        assert_eq!(diffs[0].only_b_coils, vec!["C".to_string()]);
+        assert_eq!(diffs[1].count_delta, 1);
+
+        // A 的换辊点(3) 与 B 的换辊点(2) 对齐，B 的换辊点(4) 独立
+        let align = align_roll_changes(&a, &b);
+        assert_eq!(
+            (align.count_a, align.count_b, align.aligned_count),
+            (1, 2, 1)
+        );
+        assert_eq!(align.items.len(), 2);
+        assert_eq!(align.items[0].offset, Some(-1));
+        assert!(align.items[1].point_a.is_none());
+        assert_eq!(align.items[1].point_b.as_ref().unwrap().position, 4);
+    }
+
+    #[test]
+    fn test_roll_alignment_skips_far_points() {
+        let mut a: Vec<CompareCoil> = (0..60)
+            .map(|i| coil(&format!("C{}", i), "s", 1.0, false))
+            .collect();
+        let mut b = a.clone();
+        a[4].is_roll_change = true;
+        b[50].is_roll_change = true;
+        let align = align_roll_changes(&a, &b);
+        assert_eq!(align.aligned_count, 0);
+        assert_eq!(align.items.len(), 2);
+        assert_eq!(align.items[0].point_a.as_ref().unwrap().position, 5);
+        assert_eq!(align.items[1].point_b.as_ref().unwrap().position, 51);
+    }
+
+    #[test]
+    fn test_due_outcome_diff() {
+        let mut a1 = coil("A", "s", 1.0, false);
+        a1.due_date = Some(at(10));
+        a1.planned_end = Some(at(9));
+        let mut b1 = a1.clone();
+        b1.planned_end = Some(at(12));
+
+        let mut a2 = coil("B", "s", 1.0, false);
+        a2.due_date = Some(at(10));
+        a2.planned_end = Some(at(11));
+        let mut b2 = a2.clone();
+        b2.planned_end = Some(at(8));
+
+        let diff = due_outcome_diff(&[a1, a2], &[b1, b2]);
+        assert_eq!((diff.late_count_a, diff.late_count_b), (1, 1));
+        assert_eq!((diff.became_late, diff.became_on_time), (1, 1));
+        assert_eq!(diff.total_late_minutes_b, 120);
+        assert_eq!(diff.items.len(), 2);
+        assert_eq!(diff.items[0].late_minutes_b, 120);
+    }
+}
@@ -0,0 +1,451 @@
+//! 方案版本差异与三方合并
+//!
+//! 以材料 ID 标识排程项，比较两个版本的上线顺序：
+//!   - inserted     新版本新增的钢卷
+//!   - removed      新版本移除的钢卷
+//!   - moved        双方都有但相对顺序改变的钢卷（不在最长公共子序列中的钢卷）
+//!   - lock_changes 锁定状态变化
+//!
+//! 三方合并以 ours（当前分支）为底，按 theirs 的顺序逐项应用 theirs 相对共同祖先的改动，
+//! 新增/移动的钢卷插在其 theirs 中最近的、已存在于合并结果中的前驱之后。
+//! 冲突时保留钢卷（不丢材料）并以 ours 为准，冲突项写入报告。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 版本中的一个排程项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanEntry {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub is_locked: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffInsert {
+    pub material_id: i32,
+    pub coil_id: String,
+    /// 在新版本中的位置（从 0 开始）
+    pub position: usize,
+    /// 新版本中的前驱钢卷
+    pub after_material_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffRemove {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub old_position: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffMove {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub from_position: usize,
+    pub to_position: usize,
+    pub after_material_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLockChange {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub from_locked: bool,
+    pub to_locked: bool,
+}
+
+/// 两个版本之间的结构化差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanDiff {
+    pub inserted: Vec<DiffInsert>,
+    pub removed: Vec<DiffRemove>,
+    pub moved: Vec<DiffMove>,
+    pub lock_changes: Vec<DiffLockChange>,
+}
+
+impl PlanDiff {
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.lock_changes.is_empty()
+    }
+}
+
+/// 合并冲突
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+    pub material_id: i32,
+    pub coil_id: String,
+    /// remove_move / remove_lock / move_move / insert_insert / lock_lock
+    pub conflict_type: String,
+    pub ours: String,
+    pub theirs: String,
+    pub resolution: String,
+}
+
+/// 三方合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeOutcome {
+    pub merged: Vec<PlanEntry>,
+    pub ours_diff: PlanDiff,
+    pub theirs_diff: PlanDiff,
+    pub conflicts: Vec<MergeConflict>,
+    /// 成功应用的 theirs 改动数
+    pub applied_theirs: usize,
+}
+
+/// 最长公共子序列（按材料 ID），返回属于 LCS 的材料集合
+fn lcs_members(a: &[i32], b: &[i32]) -> HashSet<i32> {
+    let n = a.len();
+    let m = b.len();
+    let mut dp = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            dp[i][j] = if a[i] == b[j] {
+                dp[i + 1][j + 1] + 1
+            } else {
+                dp[i + 1][j].max(dp[i][j + 1])
+            };
+        }
+    }
+    let mut members = HashSet::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            members.insert(a[i]);
+            i += 1;
+            j += 1;
+        } else if dp[i + 1][j] > dp[i][j + 1] {
+            // 等长时优先跳过新版本元素，使被挪动的钢卷（而非其邻居）记为移动
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    members
+}
+
+/// 计算 base → target 的结构化差异
+pub fn diff_plans(base: &[PlanEntry], target: &[PlanEntry]) -> PlanDiff {
+    let base_pos: HashMap<i32, usize> = base
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.material_id, i))
+        .collect();
+    let target_pos: HashMap<i32, usize> = target
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.material_id, i))
+        .collect();
+
+    let common_base: Vec<i32> = base
+        .iter()
+        .map(|e| e.material_id)
+        .filter(|id| target_pos.contains_key(id))
+        .collect();
+    let common_target: Vec<i32> = target
+        .iter()
+        .map(|e| e.material_id)
+        .filter(|id| base_pos.contains_key(id))
+        .collect();
+    let stable = lcs_members(&common_base, &common_target);
+
+    let mut diff = PlanDiff::default();
+    for (pos, entry) in target.iter().enumerate() {
+        let after_material_id = pos.checked_sub(1).map(|p| target[p].material_id);
+        match base_pos.get(&entry.material_id) {
+            None => diff.inserted.push(DiffInsert {
+                material_id: entry.material_id,
+                coil_id: entry.coil_id.clone(),
+                position: pos,
+                after_material_id,
+            }),
+            Some(&from) => {
+                if !stable.contains(&entry.material_id) {
+                    diff.moved.push(DiffMove {
+                        material_id: entry.material_id,
+                        coil_id: entry.coil_id.clone(),
+                        from_position: from,
+                        to_position: pos,
+                        after_material_id,
+                    });
+                }
+                let before = &base[from];
+                if before.is_locked != entry.is_locked {
+                    diff.lock_changes.push(DiffLockChange {
+                        material_id: entry.material_id,
+                        coil_id: entry.coil_id.clone(),
+                        from_locked: before.is_locked,
+                        to_locked: entry.is_locked,
+                    });
+                }
+            }
+        }
+    }
+    for (pos, entry) in base.iter().enumerate() {
+        if !target_pos.contains_key(&entry.material_id) {
+            diff.removed.push(DiffRemove {
+                material_id: entry.material_id,
+                coil_id: entry.coil_id.clone(),
+                old_position: pos,
+            });
+        }
+    }
+    diff
+}
+
+/// theirs 中 material_id 之前最近的、已在合并结果中的钢卷
+fn anchor_in(merged: &[PlanEntry], theirs: &[PlanEntry], material_id: i32) -> Option<i32> {
+    let pos = theirs.iter().position(|e| e.material_id == material_id)?;
+    theirs[..pos]
+        .iter()
+        .rev()
+        .map(|e| e.material_id)
+        .find(|id| merged.iter().any(|m| m.material_id == *id))
+}
+
+fn place_after(merged: &mut Vec<PlanEntry>, entry: PlanEntry, anchor: Option<i32>) {
+    merged.retain(|e| e.material_id != entry.material_id);
+    let idx = anchor
+        .and_then(|a| merged.iter().position(|e| e.material_id == a))
+        .map(|p| p + 1)
+        .unwrap_or(0);
+    merged.insert(idx, entry);
+}
+
+/// 三方合并：在 ours 上应用 theirs 相对 base 的改动
+pub fn merge_plans(base: &[PlanEntry], ours: &[PlanEntry], theirs: &[PlanEntry]) -> MergeOutcome {
+    let ours_diff = diff_plans(base, ours);
+    let theirs_diff = diff_plans(base, theirs);
+
+    let ours_removed: HashSet<i32> = ours_diff.removed.iter().map(|r| r.material_id).collect();
+    let ours_inserted: HashMap<i32, &DiffInsert> = ours_diff
+        .inserted
+        .iter()
+        .map(|d| (d.material_id, d))
+        .collect();
+    let ours_moved: HashMap<i32, &DiffMove> =
+        ours_diff.moved.iter().map(|d| (d.material_id, d)).collect();
+    let ours_locks: HashMap<i32, &DiffLockChange> = ours_diff
+        .lock_changes
+        .iter()
+        .map(|d| (d.material_id, d))
+        .collect();
+    let theirs_inserted: HashSet<i32> =
+        theirs_diff.inserted.iter().map(|d| d.material_id).collect();
+    let theirs_moved: HashMap<i32, &DiffMove> = theirs_diff
+        .moved
+        .iter()
+        .map(|d| (d.material_id, d))
+        .collect();
+
+    let mut merged: Vec<PlanEntry> = ours.to_vec();
+    let mut conflicts = Vec::new();
+    let mut applied_theirs = 0usize;
+    let describe_pos = |after: Option<i32>| match after {
+        Some(id) => format!("排在材料 {} 之后", id),
+        None => "排在首位".to_string(),
+    };
+
+    // 1. theirs 移除
+    for removed in &theirs_diff.removed {
+        let id = removed.material_id;
+        if let Some(mv) = ours_moved.get(&id) {
+            conflicts.push(MergeConflict {
+                material_id: id,
+                coil_id: removed.coil_id.clone(),
+                conflict_type: "remove_move".into(),
+                ours: format!("移动到第 {} 位", mv.to_position + 1),
+                theirs: "移除".into(),
+                resolution: "保留钢卷（按 ours 位置）".into(),
+            });
+        } else if let Some(lock) = ours_locks.get(&id) {
+            conflicts.push(MergeConflict {
+                material_id: id,
+                coil_id: removed.coil_id.clone(),
+                conflict_type: "remove_lock".into(),
+                ours: if lock.to_locked { "锁定" } else { "解锁" }.into(),
+                theirs: "移除".into(),
+                resolution: "保留钢卷（按 ours 位置）".into(),
+            });
+        } else if merged.iter().any(|e| e.material_id == id) {
+            merged.retain(|e| e.material_id != id);
+            applied_theirs += 1;
+        }
+    }
+
+    // 2. 按 theirs 顺序应用新增与移动
+    for entry in theirs {
+        let id = entry.material_id;
+        if theirs_inserted.contains(&id) {
+            match ours_inserted.get(&id) {
+                Some(ours_ins) => {
+                    let theirs_anchor = anchor_in(&merged, theirs, id);
+                    let ours_anchor = anchor_in(&merged, ours, id);
+                    if theirs_anchor != ours_anchor {
+                        conflicts.push(MergeConflict {
+                            material_id: id,
+                            coil_id: entry.coil_id.clone(),
+                            conflict_type: "insert_insert".into(),
+                            ours: describe_pos(ours_ins.after_material_id),
+                            theirs: describe_pos(theirs_anchor),
+                            resolution: "采用 ours 位置".into(),
+                        });
+                    }
+                }
+                None => {
+                    let anchor = anchor_in(&merged, theirs, id);
+                    place_after(&mut merged, entry.clone(), anchor);
+                    applied_theirs += 1;
+                }
+            }
+        } else if let Some(their_move) = theirs_moved.get(&id) {
+            let anchor = anchor_in(&merged, theirs, id);
+            if ours_removed.contains(&id) {
+                conflicts.push(MergeConflict {
+                    material_id: id,
+                    coil_id: entry.coil_id.clone(),
+                    conflict_type: "remove_move".into(),
+                    ours: "移除".into(),
+                    theirs: format!("移动到第 {} 位", their_move.to_position + 1),
+                    resolution: "保留钢卷（按 theirs 位置恢复）".into(),
+                });
+                place_after(&mut merged, entry.clone(), anchor);
+            } else if ours_moved.contains_key(&id) {
+                if anchor_in(&merged, ours, id) != anchor {
+                    conflicts.push(MergeConflict {
+                        material_id: id,
+                        coil_id: entry.coil_id.clone(),
+                        conflict_type: "move_move".into(),
+                        ours: describe_pos(ours_moved[&id].after_material_id),
+                        theirs: describe_pos(their_move.after_material_id),
+                        resolution: "采用 ours 位置".into(),
+                    });
+                }
+            } else {
+                let current = merged
+                    .iter()
+                    .find(|e| e.material_id == id)
+                    .cloned()
+                    .unwrap_or_else(|| entry.clone());
+                place_after(&mut merged, current, anchor);
+                applied_theirs += 1;
+            }
+        }
+    }
+
+    // 3. theirs 锁定变化
+    for lock in &theirs_diff.lock_changes {
+        let id = lock.material_id;
+        if ours_removed.contains(&id) {
+            if !merged.iter().any(|e| e.material_id == id) {
+                conflicts.push(MergeConflict {
+                    material_id: id,
+                    coil_id: lock.coil_id.clone(),
+                    conflict_type: "remove_lock".into(),
+                    ours: "移除".into(),
+                    theirs: if lock.to_locked { "锁定" } else { "解锁" }.into(),
+                    resolution: "保留钢卷（按 theirs 位置恢复）".into(),
+                });
+                if let Some(entry) = theirs.iter().find(|e| e.material_id == id) {
+                    let anchor = anchor_in(&merged, theirs, id);
+                    place_after(&mut merged, entry.clone(), anchor);
+                }
+            }
+            continue;
+        }
+        if let Some(ours_lock) = ours_locks.get(&id) {
+            if ours_lock.to_locked != lock.to_locked {
+                conflicts.push(MergeConflict {
+                    material_id: id,
+                    coil_id: lock.coil_id.clone(),
+                    conflict_type: "lock_lock".into(),
+                    ours: ours_lock.to_locked.to_string(),
+                    theirs: lock.to_locked.to_string(),
+                    resolution: "采用 ours 锁定状态".into(),
+                });
+            }
+            continue;
+        }
+        if let Some(e) = merged.iter_mut().find(|e| e.material_id == id) {
+            e.is_locked = lock.to_locked;
+            applied_theirs += 1;
+        }
+    }
+
+    MergeOutcome {
+        merged,
+        ours_diff,
+        theirs_diff,
+        conflicts,
+        applied_theirs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(ids: &[i32]) -> Vec<PlanEntry> {
+        ids.iter()
+            .map(|id| PlanEntry {
+                material_id: *id,
+                coil_id: format!("C{:03}", id),
+                is_locked: false,
+            })
+            .collect()
+    }
+
+    fn ids(entries: &[PlanEntry]) -> Vec<i32> {
+        entries.iter().map(|e| e.material_id).collect()
+    }
+
+    #[test]
+    fn test_diff_detects_insert_remove_move_lock() {
+        let base = plan(&[1, 2, 3, 4, 5]);
+        let mut target = plan(&[1, 4, 2, 3, 6]);
+        target[0].is_locked = true;
+        let diff = diff_plans(&base, &target);
+        assert_eq!(diff.inserted.len(), 1);
+        assert_eq!(diff.inserted[0].material_id, 6);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].material_id, 5);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].material_id, 4);
+        assert_eq!(diff.lock_changes.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_applies_both_sides() {
+        let base = plan(&[1, 2, 3, 4, 5]);
+        let ours = plan(&[1, 2, 3, 4, 5, 7]); // 新增 7
+        let theirs = plan(&[1, 3, 2, 4]); // 移动 3，移除 5
+        let outcome = merge_plans(&base, &ours, &theirs);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(ids(&outcome.merged), vec![1, 3, 2, 4, 7]);
+    }
+
+    #[test]
+    fn test_merge_remove_vs_move_conflict_keeps_coil() {
+        let base = plan(&[1, 2, 3, 4]);
+        let ours = plan(&[1, 2, 4]); // 移除 3
+        let theirs = plan(&[3, 1, 2, 4]); // 移动 3 到首位
+        let outcome = merge_plans(&base, &ours, &theirs);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].conflict_type, "remove_move");
+        assert_eq!(ids(&outcome.merged), vec![3, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_merge_conflicting_moves_prefer_ours() {
+        let base = plan(&[1, 2, 3, 4]);
+        let ours = plan(&[4, 1, 2, 3]);
+        let theirs = plan(&[1, 2, 4, 3]);
+        let outcome = merge_plans(&base, &ours, &theirs);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].conflict_type, "move_move");
+        assert_eq!(ids(&outcome.merged), vec![4, 1, 2, 3]);
+    }
+}
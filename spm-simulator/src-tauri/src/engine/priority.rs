@@ -287,17 +287,17 @@ fn calculate_product_type(
 }
 
 /// 从数据库加载完整的优先级计算上下文
-pub async fn load_priority_context() -> Result<PriorityContext, crate::AppError> {
-    use crate::db::get_db;
+pub async fn load_priority_context<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+) -> Result<PriorityContext, crate::AppError> {
     use sea_orm::*;
 
-    let db = get_db();
     let mut ctx = PriorityContext::default();
 
     // 加载权重
     let weights = crate::models::priority_weight_config::Entity::find()
         .filter(crate::models::priority_weight_config::Column::Enabled.eq(true))
-        .all(db)
+        .all(conn)
         .await?;
     for w in &weights {
         match w.dimension_type.as_str() {
@@ -314,7 +314,7 @@ pub async fn load_priority_context() -> Result<PriorityContext, crate::AppError>
     // 加载交期维度
     let dims = crate::models::priority_dimension_config::Entity::find()
         .filter(crate::models::priority_dimension_config::Column::Enabled.eq(true))
-        .all(db)
+        .all(conn)
         .await?;
     for d in &dims {
         match d.dimension_type.as_str() {
@@ -333,7 +333,7 @@ pub async fn load_priority_context() -> Result<PriorityContext, crate::AppError>
     // 加载客户优先级
     let customers = crate::models::customer_priority_config::Entity::find()
         .filter(crate::models::customer_priority_config::Column::Enabled.eq(true))
-        .all(db)
+        .all(conn)
         .await?;
     for c in &customers {
         ctx.customer_scores
@@ -343,7 +343,7 @@ pub async fn load_priority_context() -> Result<PriorityContext, crate::AppError>
     // 加载集批优先级
     let batches = crate::models::batch_priority_config::Entity::find()
         .filter(crate::models::batch_priority_config::Column::Enabled.eq(true))
-        .all(db)
+        .all(conn)
         .await?;
     for b in &batches {
         ctx.batch_scores
@@ -353,7 +353,7 @@ pub async fn load_priority_context() -> Result<PriorityContext, crate::AppError>
     // 加载产品大类优先级
     let products = crate::models::product_type_priority_config::Entity::find()
         .filter(crate::models::product_type_priority_config::Column::Enabled.eq(true))
-        .all(db)
+        .all(conn)
         .await?;
     for p in &products {
         ctx.product_type_scores
@@ -364,19 +364,31 @@ pub async fn load_priority_context() -> Result<PriorityContext, crate::AppError>
 }
 
 /// 批量计算所有材料的优先级并更新 DB
-pub async fn batch_calculate_priorities(
+pub async fn batch_calculate_priorities<C: sea_orm::ConnectionTrait>(
+    conn: &C,
     materials: &[material::Model],
 ) -> Result<Vec<(i32, PriorityDetail)>, crate::AppError> {
-    use crate::db::get_db;
+    use crate::services::material_history_service;
     use sea_orm::prelude::Expr;
     use sea_orm::*;
 
-    let ctx = load_priority_context().await?;
-    let db = get_db();
+    let ctx = load_priority_context(conn).await?;
     let mut results = Vec::with_capacity(materials.len());
+    let mut history = Vec::new();
 
     for mat in materials {
         let detail = calculate_priority(mat, &ctx);
+        let mut updated = mat.clone();
+        updated.priority_auto = Some(detail.final_score);
+        updated.priority_final = Some(detail.final_score);
+        history.extend(material_history_service::diff_models(
+            mat,
+            &updated,
+            &[
+                material::Column::PriorityAuto,
+                material::Column::PriorityFinal,
+            ],
+        ));
 
         // 更新 DB
         crate::models::material::Entity::update_many()
@@ -401,15 +413,242 @@ pub async fn batch_calculate_priorities(
                 Expr::current_timestamp().into(),
             )
             .filter(material::Column::Id.eq(mat.id))
-            .exec(db)
+            .exec(conn)
             .await?;
 
         results.push((mat.id, detail));
     }
 
+    material_history_service::record_changes(
+        conn,
+        &history,
+        material_history_service::SOURCE_PRIORITY_RECOMPUTE,
+        None,
+    )
+    .await?;
+
     Ok(results)
 }
 
+// ---------- 权重调整预演（What-if） ----------
+
+/// 单卷排名变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityRankChange {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub weight: f64,
+    pub baseline_score: i32,
+    pub proposed_score: i32,
+    pub baseline_rank: usize,
+    pub proposed_rank: usize,
+    /// 正数表示排名上升（名次变小）
+    pub rank_delta: i64,
+}
+
+/// final_score 分布区间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreBucket {
+    pub lower: i32,
+    pub upper: i32,
+    pub baseline_count: usize,
+    pub proposed_count: usize,
+}
+
+/// final_score 分布统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreStats {
+    pub min: i32,
+    pub max: i32,
+    pub mean: f64,
+    pub median: i32,
+    pub p90: i32,
+}
+
+/// 权重预演结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityWhatIfReport {
+    pub total: usize,
+    pub changed_count: usize,
+    pub baseline_stats: ScoreStats,
+    pub proposed_stats: ScoreStats,
+    pub distribution: Vec<ScoreBucket>,
+    /// 按排名变化幅度排序的前 N 个变动卷
+    pub top_movers: Vec<PriorityRankChange>,
+    /// 前 N 吨窗口（吨）
+    pub tonnage_window: f64,
+    /// 新进入前 N 吨窗口的卷
+    pub entered: Vec<PriorityRankChange>,
+    /// 被挤出前 N 吨窗口的卷
+    pub exited: Vec<PriorityRankChange>,
+}
+
+/// 在内存中对比当前配置与拟定配置下的优先级排名（不写库）
+pub fn simulate_priority_change(
+    materials: &[material::Model],
+    baseline: &PriorityContext,
+    proposed: &PriorityContext,
+    top_n: usize,
+    tonnage_window: f64,
+    bucket_size: i32,
+) -> PriorityWhatIfReport {
+    let baseline_scores: Vec<i32> = materials
+        .iter()
+        .map(|m| calculate_priority(m, baseline).final_score)
+        .collect();
+    let proposed_scores: Vec<i32> = materials
+        .iter()
+        .map(|m| calculate_priority(m, proposed).final_score)
+        .collect();
+
+    let baseline_ranks = rank_by_score(materials, &baseline_scores);
+    let proposed_ranks = rank_by_score(materials, &proposed_scores);
+    let baseline_window = tonnage_window_members(materials, &baseline_ranks, tonnage_window);
+    let proposed_window = tonnage_window_members(materials, &proposed_ranks, tonnage_window);
+
+    let changes: Vec<PriorityRankChange> = materials
+        .iter()
+        .enumerate()
+        .map(|(i, m)| PriorityRankChange {
+            material_id: m.id,
+            coil_id: m.coil_id.clone(),
+            weight: m.weight,
+            baseline_score: baseline_scores[i],
+            proposed_score: proposed_scores[i],
+            baseline_rank: baseline_ranks[i],
+            proposed_rank: proposed_ranks[i],
+            rank_delta: baseline_ranks[i] as i64 - proposed_ranks[i] as i64,
+        })
+        .collect();
+
+    let changed_count = changes
+        .iter()
+        .filter(|c| c.rank_delta != 0 || c.baseline_score != c.proposed_score)
+        .count();
+
+    let mut top_movers: Vec<PriorityRankChange> = changes
+        .iter()
+        .filter(|c| c.rank_delta != 0)
+        .cloned()
+        .collect();
+    top_movers.sort_by(|a, b| {
+        b.rank_delta
+            .abs()
+            .cmp(&a.rank_delta.abs())
+            .then(a.proposed_rank.cmp(&b.proposed_rank))
+    });
+    top_movers.truncate(top_n);
+
+    let mut entered: Vec<PriorityRankChange> = changes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| proposed_window[*i] && !baseline_window[*i])
+        .map(|(_, c)| c.clone())
+        .collect();
+    entered.sort_by_key(|c| c.proposed_rank);
+    let mut exited: Vec<PriorityRankChange> = changes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| baseline_window[*i] && !proposed_window[*i])
+        .map(|(_, c)| c.clone())
+        .collect();
+    exited.sort_by_key(|c| c.baseline_rank);
+
+    PriorityWhatIfReport {
+        total: materials.len(),
+        changed_count,
+        baseline_stats: score_stats(&baseline_scores),
+        proposed_stats: score_stats(&proposed_scores),
+        distribution: score_buckets(&baseline_scores, &proposed_scores, bucket_size),
+        top_movers,
+        tonnage_window,
+        entered,
+        exited,
+    }
+}
+
+/// 按 final_score 降序计算名次（1 起），同分按 coil_id 升序
+fn rank_by_score(materials: &[material::Model], scores: &[i32]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..materials.len()).collect();
+    order.sort_by(|&a, &b| {
+        scores[b]
+            .cmp(&scores[a])
+            .then_with(|| materials[a].coil_id.cmp(&materials[b].coil_id))
+    });
+    let mut ranks = vec![0usize; materials.len()];
+    for (pos, &idx) in order.iter().enumerate() {
+        ranks[idx] = pos + 1;
+    }
+    ranks
+}
+
+/// 标记按名次累计重量落在前 N 吨内的材料
+fn tonnage_window_members(
+    materials: &[material::Model],
+    ranks: &[usize],
+    tonnage_window: f64,
+) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..materials.len()).collect();
+    order.sort_by_key(|&i| ranks[i]);
+    let mut members = vec![false; materials.len()];
+    let mut cumulative = 0.0;
+    for idx in order {
+        if cumulative >= tonnage_window {
+            break;
+        }
+        cumulative += materials[idx].weight;
+        members[idx] = true;
+    }
+    members
+}
+
+fn score_stats(scores: &[i32]) -> ScoreStats {
+    if scores.is_empty() {
+        return ScoreStats::default();
+    }
+    let mut sorted = scores.to_vec();
+    sorted.sort_unstable();
+    let percentile = |p: f64| -> i32 {
+        let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+        sorted[idx.min(sorted.len() - 1)]
+    };
+    let sum: i64 = sorted.iter().map(|&s| s as i64).sum();
+    ScoreStats {
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        mean: (sum as f64 / sorted.len() as f64 * 100.0).round() / 100.0,
+        median: percentile(0.5),
+        p90: percentile(0.9),
+    }
+}
+
+fn score_buckets(baseline: &[i32], proposed: &[i32], bucket_size: i32) -> Vec<ScoreBucket> {
+    let bucket_size = bucket_size.max(1);
+    let (Some(&min), Some(&max)) = (
+        baseline.iter().chain(proposed.iter()).min(),
+        baseline.iter().chain(proposed.iter()).max(),
+    ) else {
+        return vec![];
+    };
+    let first = min.div_euclid(bucket_size);
+    let last = max.div_euclid(bucket_size);
+    let mut buckets: Vec<ScoreBucket> = (first..=last)
+        .map(|b| ScoreBucket {
+            lower: b * bucket_size,
+            upper: (b + 1) * bucket_size - 1,
+            baseline_count: 0,
+            proposed_count: 0,
+        })
+        .collect();
+    for &s in baseline {
+        buckets[(s.div_euclid(bucket_size) - first) as usize].baseline_count += 1;
+    }
+    for &s in proposed {
+        buckets[(s.div_euclid(bucket_size) - first) as usize].proposed_count += 1;
+    }
+    buckets
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -515,4 +754,51 @@ mod tests {
                 .round() as i32;
         assert_eq!(detail.final_score, expected);
     }
+
+    #[test]
+    fn test_simulate_priority_change_detects_movers_and_window() {
+        let mut a = make_material(1, "A", 1000.0, 400.0);
+        a.export_flag = Some(true); // contract=100
+        let mut b = make_material(2, "B", 1000.0, 400.0);
+        b.product_type = Some("P1".to_string());
+        let c = make_material(3, "C", 1000.0, 400.0);
+
+        let mut baseline = default_ctx();
+        baseline.product_type_scores.insert("P1".into(), 60);
+        let mut proposed = baseline.clone();
+        proposed.weights.contract = 0.0;
+        proposed.weights.product_type = 1.0;
+
+        let report = simulate_priority_change(&[a, b, c], &baseline, &proposed, 10, 800.0, 100);
+        assert_eq!(report.total, 3);
+        // baseline: A(80) > B(60) > C(30); proposed: B(90) > A(30) = C(30)
+        let b_change = report.top_movers.iter().find(|c| c.coil_id == "B").unwrap();
+        assert_eq!(b_change.baseline_rank, 2);
+        assert_eq!(b_change.proposed_rank, 1);
+        assert!(report.entered.is_empty());
+        assert!(report.exited.is_empty());
+        assert_eq!(report.baseline_stats.max, 80);
+        assert_eq!(report.proposed_stats.max, 90);
+    }
+
+    #[test]
+    fn test_simulate_priority_change_window_crossing() {
+        let mut a = make_material(1, "A", 1000.0, 500.0);
+        a.export_flag = Some(true);
+        let mut b = make_material(2, "B", 1000.0, 500.0);
+        b.product_type = Some("P1".to_string());
+
+        let mut baseline = default_ctx();
+        baseline.product_type_scores.insert("P1".into(), 60);
+        let mut proposed = baseline.clone();
+        proposed.weights.contract = 0.0;
+
+        let report = simulate_priority_change(&[a, b], &baseline, &proposed, 10, 500.0, 50);
+        assert_eq!(report.entered.len(), 1);
+        assert_eq!(report.entered[0].coil_id, "B");
+        assert_eq!(report.exited.len(), 1);
+        assert_eq!(report.exited[0].coil_id, "A");
+        let baseline_total: usize = report.distribution.iter().map(|b| b.baseline_count).sum();
+        assert_eq!(baseline_total, 2);
+    }
 }
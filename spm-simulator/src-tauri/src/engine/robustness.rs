@@ -0,0 +1,488 @@
+//! 方案鲁棒性分析 — Monte Carlo 重放
+//!
+//! 按方案既定顺序逐卷重放，每次迭代随机扰动：
+//!   1. 加工时长 = 计划时长 × 节奏系数（可配置分布）
+//!   2. 突发短停：按每小时发生率的泊松过程在加工期间发生，停机时长服从可配置分布
+//!   3. 换辊超时：计划换辊耗时之外的额外分钟数（可配置分布）
+//!
+//! 钢卷不早于计划开始时间上线；时间统一以"距方案基准时刻的分钟数"表示。
+//! 随机数采用内置 SplitMix64，固定种子可完全复现结果。
+
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 可复现的伪随机数生成器（SplitMix64）
+#[derive(Debug, Clone)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 均匀分布
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 标准正态分布（Box-Muller）
+    pub fn next_standard_normal(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// 随机分布定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Distribution {
+    Fixed { value: f64 },
+    Uniform { min: f64, max: f64 },
+    Normal { mean: f64, std_dev: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+    Exponential { mean: f64 },
+}
+
+impl Distribution {
+    /// 校验分布参数：均为有限值，且区间/标准差/均值合法
+    pub fn validate(&self, name: &str) -> Result<(), AppError> {
+        let invalid = |msg: &str| Err(AppError::InvalidInput(format!("{}: {}", name, msg)));
+        let params: Vec<f64> = match *self {
+            Distribution::Fixed { value } => vec![value],
+            Distribution::Uniform { min, max } => vec![min, max],
+            Distribution::Normal { mean, std_dev } => vec![mean, std_dev],
+            Distribution::Triangular { min, mode, max } => vec![min, mode, max],
+            Distribution::Exponential { mean } => vec![mean],
+        };
+        if params.iter().any(|v| !v.is_finite()) {
+            return invalid("参数必须是有限数值");
+        }
+        match *self {
+            Distribution::Uniform { min, max } if min > max => invalid("min 不能大于 max"),
+            Distribution::Normal { std_dev, .. } if std_dev < 0.0 => invalid("std_dev 不能为负"),
+            Distribution::Triangular { min, mode, max } if !(min <= mode && mode <= max) => {
+                invalid("需满足 min <= mode <= max")
+            }
+            Distribution::Exponential { mean } if mean <= 0.0 => invalid("mean 必须大于 0"),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut SimRng) -> f64 {
+        match *self {
+            Distribution::Fixed { value } => value,
+            Distribution::Uniform { min, max } => min + (max - min) * rng.next_f64(),
+            Distribution::Normal { mean, std_dev } => mean + std_dev * rng.next_standard_normal(),
+            Distribution::Triangular { min, mode, max } => {
+                if max <= min {
+                    return min;
+                }
+                let u = rng.next_f64();
+                let split = (mode - min) / (max - min);
+                if u < split {
+                    min + ((max - min) * (mode - min) * u).sqrt()
+                } else {
+                    max - ((max - min) * (max - mode) * (1.0 - u)).sqrt()
+                }
+            }
+            Distribution::Exponential { mean } => -mean * (1.0 - rng.next_f64()).ln(),
+        }
+    }
+}
+
+/// 鲁棒性模拟参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustnessConfig {
+    /// 重放次数
+    pub iterations: usize,
+    /// 随机种子
+    pub seed: u64,
+    /// 加工节奏系数（乘在计划加工时长上，下限 0.1）
+    pub rhythm_factor: Distribution,
+    /// 突发短停发生率（次/小时）
+    pub breakdown_rate_per_hour: f64,
+    /// 单次短停时长（分钟）
+    pub breakdown_minutes: Distribution,
+    /// 换辊超时（分钟，负值按 0 计）
+    pub roll_change_overrun_minutes: Distribution,
+}
+
+/// 模拟次数上限
+pub const MAX_ITERATIONS: usize = 100_000;
+/// 短停发生率上限（次/小时）
+pub const MAX_BREAKDOWN_RATE_PER_HOUR: f64 = 60.0;
+
+impl RobustnessConfig {
+    /// 校验全部参数，非法时返回 InvalidInput
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.iterations == 0 || self.iterations > MAX_ITERATIONS {
+            return Err(AppError::InvalidInput(format!(
+                "模拟次数需在 1~{} 之间",
+                MAX_ITERATIONS
+            )));
+        }
+        let rate = self.breakdown_rate_per_hour;
+        if !rate.is_finite() || !(0.0..=MAX_BREAKDOWN_RATE_PER_HOUR).contains(&rate) {
+            return Err(AppError::InvalidInput(format!(
+                "短停发生率需在 0~{} 次/小时之间",
+                MAX_BREAKDOWN_RATE_PER_HOUR
+            )));
+        }
+        self.rhythm_factor.validate("加工节奏系数")?;
+        self.breakdown_minutes.validate("短停时长")?;
+        self.roll_change_overrun_minutes.validate("换辊超时")?;
+        Ok(())
+    }
+}
+
+impl Default for RobustnessConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 500,
+            seed: 20240601,
+            rhythm_factor: Distribution::Normal {
+                mean: 1.0,
+                std_dev: 0.08,
+            },
+            breakdown_rate_per_hour: 0.05,
+            breakdown_minutes: Distribution::Exponential { mean: 15.0 },
+            roll_change_overrun_minutes: Distribution::Triangular {
+                min: 0.0,
+                mode: 5.0,
+                max: 20.0,
+            },
+        }
+    }
+}
+
+/// 重放输入：单卷计划（时间为距基准时刻的分钟数）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimCoil {
+    pub material_id: i32,
+    pub coil_id: String,
+    /// 班次标识（如 "2024-06-01 day"）
+    pub shift_key: String,
+    pub shift_end_min: f64,
+    pub planned_start_min: f64,
+    pub planned_duration_min: f64,
+    /// 上线前是否换辊
+    pub is_roll_change: bool,
+    pub roll_change_min: f64,
+    pub due_min: Option<f64>,
+}
+
+/// 单卷完工时间分布
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoilCompletionStats {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub planned_end_min: f64,
+    pub mean_min: f64,
+    pub p50_min: f64,
+    pub p90_min: f64,
+    pub p95_min: f64,
+    pub max_min: f64,
+    /// 交期延误概率（无交期时为 None）
+    pub due_miss_probability: Option<f64>,
+}
+
+/// 班次按时完成概率
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShiftOnTimeStats {
+    pub shift_key: String,
+    pub shift_end_min: f64,
+    pub coil_count: usize,
+    pub on_time_probability: f64,
+    /// 平均超时（分钟，仅统计超时的迭代）
+    pub mean_overrun_min: f64,
+    pub p95_finish_min: f64,
+}
+
+/// 鲁棒性分析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustnessReport {
+    pub iterations: usize,
+    pub seed: u64,
+    pub shifts: Vec<ShiftOnTimeStats>,
+    pub coils: Vec<CoilCompletionStats>,
+    /// 方案整体按时完成概率（全部班次均按时）
+    pub all_shifts_on_time_probability: f64,
+}
+
+/// 单次重放，返回各卷完工时刻
+fn replay_once(coils: &[SimCoil], config: &RobustnessConfig, rng: &mut SimRng) -> Vec<f64> {
+    let mut finishes = Vec::with_capacity(coils.len());
+    let mut clock = f64::NEG_INFINITY;
+    for coil in coils {
+        let mut start = clock;
+        if coil.is_roll_change {
+            let overrun = config.roll_change_overrun_minutes.sample(rng).max(0.0);
+            start += coil.roll_change_min + overrun;
+        }
+        // 不早于计划开始时间上线（计划开始已含计划换辊时间）
+        start = start.max(coil.planned_start_min);
+
+        let factor = config.rhythm_factor.sample(rng).max(0.1);
+        let mut duration = coil.planned_duration_min * factor;
+
+        // 加工期间的泊松短停：只在名义加工时长内抽取到达（复合泊松），
+        // 短停时长累加到总时长但不延长到达窗口，否则高发生率下循环不收敛
+        if config.breakdown_rate_per_hour > 0.0 {
+            let processing = duration;
+            let mean_gap = 60.0 / config.breakdown_rate_per_hour;
+            let mut elapsed = -mean_gap * (1.0 - rng.next_f64()).ln();
+            while elapsed < processing {
+                duration += config.breakdown_minutes.sample(rng).max(0.0);
+                elapsed += -mean_gap * (1.0 - rng.next_f64()).ln();
+            }
+        }
+
+        clock = start + duration;
+        finishes.push(clock);
+    }
+    finishes
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Monte Carlo 重放方案，统计班次按时率、单卷完工分布与交期延误概率
+pub fn simulate_robustness(coils: &[SimCoil], config: &RobustnessConfig) -> RobustnessReport {
+    let iterations = config.iterations.max(1);
+    let mut rng = SimRng::new(config.seed);
+
+    // 班次按出现顺序编号，记录末卷索引
+    let mut shift_order: Vec<(String, f64, usize, usize)> = Vec::new();
+    let mut shift_pos: HashMap<&str, usize> = HashMap::new();
+    for (idx, coil) in coils.iter().enumerate() {
+        match shift_pos.get(coil.shift_key.as_str()) {
+            Some(&pos) => {
+                shift_order[pos].2 = idx;
+                shift_order[pos].3 += 1;
+            }
+            None => {
+                shift_pos.insert(coil.shift_key.as_str(), shift_order.len());
+                shift_order.push((coil.shift_key.clone(), coil.shift_end_min, idx, 1));
+            }
+        }
+    }
+
+    let mut samples: Vec<Vec<f64>> = vec![Vec::with_capacity(iterations); coils.len()];
+    let mut due_misses = vec![0usize; coils.len()];
+    let mut shift_late = vec![0usize; shift_order.len()];
+    let mut shift_overrun_sum = vec![0.0f64; shift_order.len()];
+    let mut all_on_time = 0usize;
+
+    for _ in 0..iterations {
+        let finishes = replay_once(coils, config, &mut rng);
+        for (idx, finish) in finishes.iter().enumerate() {
+            samples[idx].push(*finish);
+            if coils[idx].due_min.is_some_and(|due| *finish > due) {
+                due_misses[idx] += 1;
+            }
+        }
+        let mut iteration_on_time = true;
+        for (pos, (_, shift_end, last_idx, _)) in shift_order.iter().enumerate() {
+            let overrun = finishes[*last_idx] - shift_end;
+            if overrun > 0.0 {
+                shift_late[pos] += 1;
+                shift_overrun_sum[pos] += overrun;
+                iteration_on_time = false;
+            }
+        }
+        if iteration_on_time {
+            all_on_time += 1;
+        }
+    }
+
+    let coil_stats = coils
+        .iter()
+        .zip(samples.iter_mut())
+        .zip(due_misses.iter())
+        .map(|((coil, finishes), misses)| {
+            finishes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            CoilCompletionStats {
+                material_id: coil.material_id,
+                coil_id: coil.coil_id.clone(),
+                planned_end_min: coil.planned_start_min + coil.planned_duration_min,
+                mean_min: finishes.iter().sum::<f64>() / iterations as f64,
+                p50_min: percentile(finishes, 0.5),
+                p90_min: percentile(finishes, 0.9),
+                p95_min: percentile(finishes, 0.95),
+                max_min: finishes.last().copied().unwrap_or(0.0),
+                due_miss_probability: coil.due_min.map(|_| *misses as f64 / iterations as f64),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let shifts = shift_order
+        .iter()
+        .enumerate()
+        .map(
+            |(pos, (key, shift_end, last_idx, count))| ShiftOnTimeStats {
+                shift_key: key.clone(),
+                shift_end_min: *shift_end,
+                coil_count: *count,
+                on_time_probability: 1.0 - shift_late[pos] as f64 / iterations as f64,
+                mean_overrun_min: if shift_late[pos] > 0 {
+                    shift_overrun_sum[pos] / shift_late[pos] as f64
+                } else {
+                    0.0
+                },
+                p95_finish_min: coil_stats[*last_idx].p95_min,
+            },
+        )
+        .collect();
+
+    RobustnessReport {
+        iterations,
+        seed: config.seed,
+        shifts,
+        coils: coil_stats,
+        all_shifts_on_time_probability: all_on_time as f64 / iterations as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shift_coils(count: usize, duration: f64, shift_end: f64) -> Vec<SimCoil> {
+        (0..count)
+            .map(|i| SimCoil {
+                material_id: i as i32 + 1,
+                coil_id: format!("C{:03}", i + 1),
+                shift_key: "2024-06-01 day".into(),
+                shift_end_min: shift_end,
+                planned_start_min: i as f64 * duration,
+                planned_duration_min: duration,
+                is_roll_change: false,
+                roll_change_min: 0.0,
+                due_min: Some(shift_end),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_is_reproducible() {
+        let coils = shift_coils(20, 30.0, 660.0);
+        let config = RobustnessConfig {
+            iterations: 200,
+            ..RobustnessConfig::default()
+        };
+        let a = simulate_robustness(&coils, &config);
+        let b = simulate_robustness(&coils, &config);
+        assert_eq!(
+            a.shifts[0].on_time_probability,
+            b.shifts[0].on_time_probability
+        );
+        assert_eq!(a.coils[19].p95_min, b.coils[19].p95_min);
+    }
+
+    #[test]
+    fn test_deterministic_config_matches_plan() {
+        let coils = shift_coils(10, 30.0, 300.0);
+        let config = RobustnessConfig {
+            iterations: 10,
+            seed: 1,
+            rhythm_factor: Distribution::Fixed { value: 1.0 },
+            breakdown_rate_per_hour: 0.0,
+            breakdown_minutes: Distribution::Fixed { value: 0.0 },
+            roll_change_overrun_minutes: Distribution::Fixed { value: 0.0 },
+        };
+        let report = simulate_robustness(&coils, &config);
+        assert_eq!(report.shifts[0].on_time_probability, 1.0);
+        assert_eq!(report.coils[9].p95_min, 300.0);
+        assert_eq!(report.coils[9].due_miss_probability, Some(0.0));
+    }
+
+    #[test]
+    fn test_breakdown_load_above_sixty_minutes_per_hour_terminates() {
+        // 6 次/小时 × 均值 15 分钟 = 90 分钟/小时 ≥ 60，旧实现在此处不收敛
+        let coils = shift_coils(10, 30.0, 300.0);
+        let config = RobustnessConfig {
+            iterations: 50,
+            breakdown_rate_per_hour: 6.0,
+            breakdown_minutes: Distribution::Exponential { mean: 15.0 },
+            ..RobustnessConfig::default()
+        };
+        config.validate().unwrap();
+        let report = simulate_robustness(&coils, &config);
+        assert!(report.coils[9].mean_min.is_finite());
+        assert!(report.coils[9].mean_min > 300.0);
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_parameters() {
+        let base = RobustnessConfig::default();
+        for config in [
+            RobustnessConfig {
+                breakdown_rate_per_hour: f64::INFINITY,
+                ..base.clone()
+            },
+            RobustnessConfig {
+                breakdown_rate_per_hour: -1.0,
+                ..base.clone()
+            },
+            RobustnessConfig {
+                iterations: 0,
+                ..base.clone()
+            },
+            RobustnessConfig {
+                breakdown_minutes: Distribution::Exponential { mean: 0.0 },
+                ..base.clone()
+            },
+            RobustnessConfig {
+                rhythm_factor: Distribution::Normal {
+                    mean: f64::NAN,
+                    std_dev: 0.1,
+                },
+                ..base.clone()
+            },
+            RobustnessConfig {
+                roll_change_overrun_minutes: Distribution::Uniform { min: 5.0, max: 1.0 },
+                ..base.clone()
+            },
+        ] {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+        assert!(base.validate().is_ok());
+    }
+
+    #[test]
+    fn test_frequent_breakdowns_reduce_on_time_probability() {
+        let coils = shift_coils(20, 30.0, 630.0);
+        let calm = RobustnessConfig {
+            iterations: 300,
+            breakdown_rate_per_hour: 0.01,
+            ..RobustnessConfig::default()
+        };
+        let stormy = RobustnessConfig {
+            breakdown_rate_per_hour: 1.0,
+            ..calm.clone()
+        };
+        let calm_report = simulate_robustness(&coils, &calm);
+        let stormy_report = simulate_robustness(&coils, &stormy);
+        assert!(
+            stormy_report.shifts[0].on_time_probability < calm_report.shifts[0].on_time_probability
+        );
+        assert!(stormy_report.coils[19].due_miss_probability.unwrap() > 0.5);
+    }
+}
@@ -7,8 +7,12 @@
 //!   2. finish_last_coil: 达到阈值后完成当前卷再换辊
 //!   3. 换辊消耗 30 分钟
 //!   4. 换辊后累计吨位重置
+//!
+//! 除逐卷贪心判断外，`optimize_roll_changes` 对固定序列用动态规划求最优换辊点：
+//! 代价 = 换辊次数 + 未被换辊覆盖的宽度跳跃 + 未紧随换辊的高表面卷，约束每个轧制单位吨位不超阈值。
 
 use crate::engine::sorter::SortedMaterial;
+use crate::engine::validator::SoftConstraintsConfig;
 use serde::{Deserialize, Serialize};
 
 /// 换辊配置
@@ -135,6 +139,178 @@ fn find_best_change_point(
     best_idx
 }
 
+/// 换辊点优化代价权重
+#[derive(Debug, Clone)]
+pub struct RollChangeCostWeights {
+    /// 每次换辊代价
+    pub change_cost: f64,
+    /// 轧制单位内部（未被换辊覆盖）的宽度跳跃代价
+    pub uncovered_width_jump_cost: f64,
+    /// 高表面卷未排在换辊后 surface_window 卷内的代价
+    pub late_high_surface_cost: f64,
+    /// 高表面等级
+    pub high_surface_levels: Vec<String>,
+    /// 换辊后视为"新辊"的卷数
+    pub surface_window: usize,
+}
+
+impl Default for RollChangeCostWeights {
+    fn default() -> Self {
+        Self {
+            change_cost: 10.0,
+            uncovered_width_jump_cost: 5.0,
+            late_high_surface_cost: 2.0,
+            high_surface_levels: vec!["FA".into(), "FB".into()],
+            surface_window: 5,
+        }
+    }
+}
+
+impl RollChangeCostWeights {
+    /// 高表面等级与窗口沿用策略软约束 surface_after_roll_change 的配置
+    pub fn from_soft_constraints(soft: &SoftConstraintsConfig) -> Self {
+        let mut weights = Self::default();
+        if let Some(c) = soft
+            .constraints
+            .iter()
+            .find(|c| c.enabled && c.constraint_type == "surface_after_roll_change")
+        {
+            if let Some(levels) = &c.target_levels {
+                weights.high_surface_levels = levels.clone();
+            }
+            if let Some(within) = c.within_coils {
+                weights.surface_window = within.max(1) as usize;
+            }
+        }
+        weights
+    }
+
+    fn is_high_surface(&self, item: &SortedMaterial) -> bool {
+        let level = item.material.surface_level.as_deref().unwrap_or("");
+        self.high_surface_levels.iter().any(|t| t == level)
+    }
+}
+
+/// 计算给定换辊点方案的代价（序列起点视为新辊）
+pub fn roll_change_plan_cost(
+    sequence: &[SortedMaterial],
+    change_indices: &[usize],
+    config: &RollChangeConfig,
+    weights: &RollChangeCostWeights,
+) -> f64 {
+    let mut cost = weights.change_cost * change_indices.len() as f64;
+    let mut since_change = 0usize;
+    for i in 0..sequence.len() {
+        if i > 0 && change_indices.contains(&(i - 1)) {
+            since_change = 0;
+        } else if i > 0 {
+            let jump = (sequence[i - 1].material.width - sequence[i].material.width).abs();
+            if jump >= config.width_jump_threshold {
+                cost += weights.uncovered_width_jump_cost;
+            }
+        }
+        if since_change >= weights.surface_window && weights.is_high_surface(&sequence[i]) {
+            cost += weights.late_high_surface_cost;
+        }
+        since_change += 1;
+    }
+    cost
+}
+
+/// 动态规划求固定序列的最优换辊点（序列起点视为新辊）
+///
+/// 吨位可行性：finish_last_coil 时轧制单位在最后一卷上线前未达阈值即可，
+/// 否则整个轧制单位吨位不超阈值；单卷即超阈值时独立成一个轧制单位。
+/// 贪心换辊会在触发点前后 3 卷内向宽度跳跃点挪动（最晚到触发点后第 2 卷），可能超出阈值，
+/// 故贪心方案不一定在可行域内；最优解仅保证在满足吨位约束的方案中代价最小。
+pub fn optimize_roll_changes(
+    sequence: &[SortedMaterial],
+    config: &RollChangeConfig,
+    weights: &RollChangeCostWeights,
+) -> Vec<RollChangePoint> {
+    let n = sequence.len();
+    if n == 0 {
+        return vec![];
+    }
+
+    // 前缀和：吨位 / 相邻宽度跳跃 / 高表面卷
+    let mut tons = vec![0.0f64; n + 1];
+    let mut jumps = vec![0usize; n + 1];
+    let mut surfaces = vec![0usize; n + 1];
+    for i in 0..n {
+        tons[i + 1] = tons[i] + sequence[i].material.weight;
+        let is_jump = i > 0
+            && (sequence[i - 1].material.width - sequence[i].material.width).abs()
+                >= config.width_jump_threshold;
+        jumps[i + 1] = jumps[i] + usize::from(is_jump);
+        surfaces[i + 1] = surfaces[i] + usize::from(weights.is_high_surface(&sequence[i]));
+    }
+
+    // 轧制单位 [s, e] 的代价（不含 s 之前的状态）
+    let segment_cost = |s: usize, e: usize| -> f64 {
+        let mut cost = if s > 0 { weights.change_cost } else { 0.0 };
+        // s 与 s-1 之间的跳跃由换辊覆盖，内部跳跃位于 (s, e]
+        cost += weights.uncovered_width_jump_cost * (jumps[e + 1] - jumps[s + 1]) as f64;
+        let late_start = s + weights.surface_window;
+        if late_start <= e {
+            cost +=
+                weights.late_high_surface_cost * (surfaces[e + 1] - surfaces[late_start]) as f64;
+        }
+        cost
+    };
+
+    let mut dp = vec![f64::INFINITY; n + 1];
+    let mut prev_start = vec![0usize; n + 1];
+    dp[0] = 0.0;
+    for e in 0..n {
+        for s in (0..=e).rev() {
+            let load = if config.finish_last_coil {
+                tons[e] - tons[s]
+            } else {
+                tons[e + 1] - tons[s]
+            };
+            let within = if config.finish_last_coil {
+                load < config.tonnage_threshold
+            } else {
+                load <= config.tonnage_threshold
+            };
+            if s < e && !within {
+                break;
+            }
+            let candidate = dp[s] + segment_cost(s, e);
+            if candidate < dp[e + 1] {
+                dp[e + 1] = candidate;
+                prev_start[e + 1] = s;
+            }
+        }
+    }
+
+    // 回溯各轧制单位起点
+    let mut starts = Vec::new();
+    let mut end = n;
+    while end > 0 {
+        let s = prev_start[end];
+        starts.push(s);
+        end = s;
+    }
+    starts.reverse();
+
+    starts
+        .windows(2)
+        .map(|w| {
+            let (s, next) = (w[0], w[1]);
+            RollChangePoint {
+                after_index: next - 1,
+                cumulative_weight: tons[next] - tons[s],
+                at_width_jump: (sequence[next - 1].material.width - sequence[next].material.width)
+                    .abs()
+                    >= config.width_jump_threshold,
+                duration_min: config.change_duration_min,
+            }
+        })
+        .collect()
+}
+
 /// 从策略模板硬约束中提取换辊配置
 pub fn extract_roll_config(
     hard_constraints: &crate::engine::validator::HardConstraintsConfig,
@@ -168,6 +344,30 @@ pub fn extract_roll_config(
     config
 }
 
+/// 按给定换辊索引构建换辊点（序列起点视为新辊）
+pub fn roll_change_points_at(
+    sequence: &[SortedMaterial],
+    change_indices: &[usize],
+    config: &RollChangeConfig,
+) -> Vec<RollChangePoint> {
+    let mut points = Vec::new();
+    let mut cumulative = 0.0f64;
+    for (i, sm) in sequence.iter().enumerate() {
+        cumulative += sm.material.weight;
+        if change_indices.contains(&i) && i + 1 < sequence.len() {
+            points.push(RollChangePoint {
+                after_index: i,
+                cumulative_weight: cumulative,
+                at_width_jump: (sm.material.width - sequence[i + 1].material.width).abs()
+                    >= config.width_jump_threshold,
+                duration_min: config.change_duration_min,
+            });
+            cumulative = 0.0;
+        }
+    }
+    points
+}
+
 /// 获取换辊点的索引列表
 pub fn roll_change_indices(points: &[RollChangePoint]) -> Vec<usize> {
     points.iter().map(|p| p.after_index).collect()
@@ -295,6 +495,81 @@ mod tests {
         assert_eq!(config.width_jump_threshold, 50.0);
     }
 
+    #[test]
+    fn test_optimize_prefers_width_jump_within_capacity() {
+        // 150t × 6，阈值 600t：必须至少换辊一次，宽度跳跃在第 2 卷之后
+        let widths = [1000.0, 1000.0, 1300.0, 1300.0, 1300.0, 1300.0];
+        let seq: Vec<_> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                wrap(make_material(
+                    i as i32 + 1,
+                    &format!("C{:03}", i + 1),
+                    *w,
+                    150.0,
+                ))
+            })
+            .collect();
+        let config = RollChangeConfig {
+            tonnage_threshold: 600.0,
+            finish_last_coil: false,
+            ..RollChangeConfig::default()
+        };
+        let weights = RollChangeCostWeights::default();
+        let result = optimize_roll_changes(&seq, &config, &weights);
+        assert_eq!(roll_change_indices(&result), vec![1]);
+        assert!(result[0].at_width_jump);
+        assert!(result.iter().all(|rc| rc.cumulative_weight <= 600.0));
+    }
+
+    #[test]
+    fn test_optimize_matches_brute_force() {
+        let widths = [
+            1000.0, 1100.0, 1100.0, 1250.0, 1250.0, 1000.0, 1000.0, 1200.0,
+        ];
+        let mut seq: Vec<_> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                wrap(make_material(
+                    i as i32 + 1,
+                    &format!("C{:03}", i + 1),
+                    *w,
+                    250.0,
+                ))
+            })
+            .collect();
+        seq[7].material.surface_level = Some("FA".into());
+        let config = RollChangeConfig::default();
+        let weights = RollChangeCostWeights::default();
+
+        // 枚举全部换辊点组合，取满足吨位约束的最小代价
+        let n = seq.len();
+        let mut best = f64::INFINITY;
+        for mask in 0u32..(1 << (n - 1)) {
+            let indices: Vec<usize> = (0..n - 1).filter(|i| mask & (1 << i) != 0).collect();
+            let mut load = 0.0;
+            let mut feasible = true;
+            for (i, item) in seq.iter().enumerate() {
+                if i > 0 && indices.contains(&(i - 1)) {
+                    load = 0.0;
+                }
+                if load >= config.tonnage_threshold {
+                    feasible = false;
+                }
+                load += item.material.weight;
+            }
+            if feasible {
+                best = best.min(roll_change_plan_cost(&seq, &indices, &config, &weights));
+            }
+        }
+
+        let optimal = roll_change_indices(&optimize_roll_changes(&seq, &config, &weights));
+        let optimal_cost = roll_change_plan_cost(&seq, &optimal, &config, &weights);
+        assert_eq!(optimal_cost, best);
+    }
+
     #[test]
     fn test_roll_change_indices_conversion() {
         let points = vec![
@@ -6,7 +6,7 @@
 //! Step 4: 优先级计算 + 多因子排序
 //! Step 5: 硬约束校验
 //! Step 6: 换辊配置提取（动态换辊在 Step 7 中执行）
-//! Step 7: 按日期分批排程 + 动态换辊
+//! Step 7: 按日期分批排程 + 动态换辊（可选：按日以动态规划重排换辊点）
 //! Step 8: 方案评估
 
 use crate::utils::datetime::DEFAULT_SHIFT_START;
@@ -15,16 +15,17 @@ use sea_orm::*;
 use std::collections::HashMap;
 use std::time::Instant;
 
-use crate::db::get_db;
+use crate::engine::constants::{YARD_BAY_SWITCH_SCORE_PENALTY, YARD_DIG_SCORE_PENALTY};
 use crate::engine::{
     evaluator::{self, EvalResult},
     priority,
     roll_change::{self, RollChangeConfig, RollChangePoint},
     sorter::{self, SortedMaterial},
     validator::{self, SoftConstraintsConfig},
+    yard::{self, YardState},
 };
 use crate::models::{material, schedule_item, schedule_plan, strategy_template};
-use crate::services::temp_service;
+use crate::services::{material_lifecycle_service, temp_service};
 use crate::AppError;
 
 /// 排程结果
@@ -61,6 +62,8 @@ struct HybridSchedulerConfig {
     time_budget_ms: u128,
     max_nodes: usize,
     fallback_enabled: bool,
+    /// Step 7 完成后以动态规划重排换辊点（scheduler.roll_change_mode = optimal）
+    optimal_roll_change: bool,
 }
 
 impl Default for HybridSchedulerConfig {
@@ -73,6 +76,7 @@ impl Default for HybridSchedulerConfig {
             time_budget_ms: 120_000,
             max_nodes: 200_000,
             fallback_enabled: true,
+            optimal_roll_change: false,
         }
     }
 }
@@ -98,6 +102,9 @@ impl HybridSchedulerConfig {
             parse_u128_cfg(group, "time_budget_ms", cfg.time_budget_ms, 1_000, 900_000);
         cfg.max_nodes = parse_usize_cfg(group, "max_nodes", cfg.max_nodes, 1_000, 5_000_000);
         cfg.fallback_enabled = parse_bool_cfg(group, "fallback_enabled", cfg.fallback_enabled);
+        cfg.optimal_roll_change = group
+            .and_then(|g| g.get("roll_change_mode"))
+            .is_some_and(|v| v.eq_ignore_ascii_case("optimal"));
         cfg
     }
 
@@ -145,10 +152,12 @@ struct BeamState {
     score: f64,
 }
 
-/// 自动排程主入口
-pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOutput, AppError> {
-    let db = get_db();
-
+/// 自动排程主入口；适温刷新、优先级与排程结果均经 conn 写入，可在调用方事务内执行
+pub async fn auto_schedule<C: ConnectionTrait>(
+    db: &C,
+    plan_id: i32,
+    strategy_id: i32,
+) -> Result<ScheduleOutput, AppError> {
     // ═══ Step 1: 数据准备 ═══
     log::info!(
         "[排程] Step 1: 数据准备 plan={}, strategy={}",
@@ -167,6 +176,9 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
         .one(db)
         .await?
         .ok_or(AppError::Internal("策略模板不存在".into()))?;
+    // 记录本次排程所用的策略修订
+    let revision =
+        crate::services::strategy_revision_service::ensure_current_revision(db, &strategy).await?;
 
     // 解析策略配置
     let sort_config = sorter::parse_sort_config(&strategy.sort_weights)?;
@@ -190,16 +202,7 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
         .unwrap_or(1200.0);
 
     // 加载系统配置
-    let sys_configs = crate::models::system_config::Entity::find().all(db).await?;
-    let config_map: HashMap<String, HashMap<String, String>> = {
-        let mut m: HashMap<String, HashMap<String, String>> = HashMap::new();
-        for c in &sys_configs {
-            m.entry(c.config_group.clone())
-                .or_default()
-                .insert(c.config_key.clone(), c.config_value.clone());
-        }
-        m
-    };
+    let config_map = load_config_map(db).await?;
 
     let avg_rhythm: f64 = config_map
         .get("capacity")
@@ -220,10 +223,10 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
 
     // ═══ Step 2: 适温计算 + 滚动适温预测 ═══
     log::info!("[排程] Step 2: 适温状态刷新 + 滚动适温预测");
-    temp_service::refresh_all_temper_status().await?;
+    temp_service::refresh_all_temper_status(db).await?;
 
     // 加载适温配置（用于滚动适温计算）
-    let temper_config = temp_service::load_temper_config().await?;
+    let temper_config = temp_service::load_temper_config(db).await?;
     let plan_start = NaiveDate::parse_from_str(&plan.start_date, "%Y-%m-%d")
         .unwrap_or_else(|_| Utc::now().date_naive());
     let plan_end = NaiveDate::parse_from_str(&plan.end_date, "%Y-%m-%d").unwrap_or(plan_start);
@@ -234,10 +237,24 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
     // ═══ Step 3: 预处理 ═══
     log::info!("[排程] Step 3: 预处理 (total={})", all_materials.len());
 
-    // 排除冻结材料
+    // 库区占用：启用 yard_retrieval 软约束时参与候选打分与评估（冻结材料仍占垛位）
+    let yard_materials: Vec<material::Model> =
+        if yard::find_yard_constraint(&soft_config.constraints).is_some() {
+            all_materials
+                .iter()
+                .filter(|m| m.status.as_deref() != Some("completed"))
+                .cloned()
+                .collect()
+        } else {
+            vec![]
+        };
+    let mut yard_state = yard::find_yard_constraint(&soft_config.constraints)
+        .map(|_| YardState::from_materials(&yard_materials));
+
+    // 仅保留待排材料与本方案已锁定的材料（排除冻结、生产中、已完工及其他方案锁定的材料）
     let active_materials: Vec<material::Model> = all_materials
         .into_iter()
-        .filter(|m| m.status.as_deref() != Some("frozen"))
+        .filter(|m| material_lifecycle_service::is_available_for_plan(m, plan_id))
         .collect();
 
     // 已适温材料
@@ -258,7 +275,7 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
         .iter()
         .filter(|m| m.temp_status.as_deref() != Some("ready"))
     {
-        if let Some(ready_date) = temp_service::calculate_ready_date(&m.coiling_time, &temper_config) {
+        if let Some(ready_date) = temp_service::calculate_ready_date(m, &temper_config) {
             match classify_ready_date_in_plan(&ready_date, plan_start, plan_end) {
                 ReadyDatePlacement::AvailableAtStart => {
                     all_candidate_materials.push(m.clone());
@@ -324,7 +341,7 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
     log::info!("[排程] Step 4: 优先级计算 + 多因子排序");
 
     // 批量计算优先级
-    priority::batch_calculate_priorities(&all_candidate_materials).await?;
+    priority::batch_calculate_priorities(db, &all_candidate_materials).await?;
 
     // 重新加载（优先级已更新）
     let material_ids: Vec<i32> = all_candidate_materials.iter().map(|m| m.id).collect();
@@ -362,32 +379,7 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
         .await?;
 
     // 获取班次时间
-    let day_start_str = config_map
-        .get("shift")
-        .and_then(|g| g.get("day_start"))
-        .map(|s| s.as_str())
-        .unwrap_or("08:00");
-    let day_end_str = config_map
-        .get("shift")
-        .and_then(|g| g.get("day_end"))
-        .map(|s| s.as_str())
-        .unwrap_or("20:00");
-    let night_start_str = config_map
-        .get("shift")
-        .and_then(|g| g.get("night_start"))
-        .map(|s| s.as_str())
-        .unwrap_or("20:00");
-
-    let parse_time_minutes = |s: &str| -> f64 {
-        let t = chrono::NaiveTime::parse_from_str(s, "%H:%M").unwrap_or(DEFAULT_SHIFT_START);
-        t.hour() as f64 * 60.0 + t.minute() as f64
-    };
-
-    let day_start_min = parse_time_minutes(day_start_str);
-    let day_end_min = parse_time_minutes(day_end_str);
-    let night_start_min = parse_time_minutes(night_start_str);
-    // 夜班结束 = 次日白班开始，用 24h 偏移
-    let night_end_min = day_start_min + 24.0 * 60.0;
+    let shifts = shift_windows(&config_map);
 
     let rhythm_minutes = avg_rhythm;
 
@@ -437,10 +429,7 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
         let mut prev_sorted_idx: Option<usize> = None;
         let mut day_scheduled: Vec<bool> = vec![false; available_pool.len()];
 
-        for &(shift_type_str, shift_start, shift_end) in &[
-            ("day", day_start_min, day_end_min),
-            ("night", night_start_min, night_end_min),
-        ] {
+        for &(shift_type_str, shift_start, shift_end) in &shifts {
             let mut shift_cumulative = 0.0f64;
 
             // 双时间轨策略：
@@ -494,6 +483,7 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
                     shift_capacity,
                     rhythm_minutes,
                     &roll_config,
+                    yard_state.as_ref(),
                 ) else {
                     break;
                 };
@@ -597,6 +587,9 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
                 };
 
                 item.insert(db).await?;
+                if let Some(state) = yard_state.as_mut() {
+                    state.retrieve(sm.material.id);
+                }
                 scheduled_indices.push(sorted_idx);
                 sequence_no += 1;
 
@@ -625,6 +618,20 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
         );
     }
 
+    // 换辊点全局优化：按日对已排序列求最优换辊点并回写排程项
+    if scheduler_cfg.optimal_roll_change && !scheduled_indices.is_empty() {
+        let weights = roll_change::RollChangeCostWeights::from_soft_constraints(&soft_config);
+        let greedy_count = all_roll_changes.len();
+        all_roll_changes = apply_optimal_roll_changes(db, plan_id, &roll_config, &weights)
+            .await?
+            .roll_changes;
+        log::info!(
+            "[排程] 换辊点优化: 贪心 {} 次 → 优化 {} 次",
+            greedy_count,
+            all_roll_changes.len()
+        );
+    }
+
     // 未能排入的材料警告
     if !available_pool.is_empty() || !future_pool.is_empty() {
         let unscheduled = available_pool.len() + future_pool.len();
@@ -647,8 +654,17 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
 
     let rc_indices = roll_change::roll_change_indices(&all_roll_changes);
 
-    let (soft_adjust, soft_details) =
+    let (mut soft_adjust, mut soft_details) =
         validator::evaluate_soft_constraints(&scheduled_sorted, &soft_config, &rc_indices);
+    if let Some(detail) = yard::evaluate_sequence_yard_cost(
+        &scheduled_sorted,
+        &yard_materials,
+        &soft_config,
+        &yard::YardCostConfig::from_config_group(config_map.get("yard")),
+    ) {
+        soft_adjust += detail.adjust;
+        soft_details.push(detail);
+    }
 
     let plan_days = {
         let start = NaiveDate::parse_from_str(&plan.start_date, "%Y-%m-%d").ok();
@@ -696,6 +712,8 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
 
     // 更新方案统计
     let mut plan_active: schedule_plan::ActiveModel = plan.into();
+    plan_active.strategy_id = Set(Some(strategy_id));
+    plan_active.strategy_revision_id = Set(Some(revision.id));
     plan_active.total_count = Set(Some(total_count));
     plan_active.total_weight = Set(Some(total_weight));
     plan_active.roll_change_count = Set(Some(roll_change_count));
@@ -732,6 +750,46 @@ pub async fn auto_schedule(plan_id: i32, strategy_id: i32) -> Result<ScheduleOut
     })
 }
 
+/// 加载系统配置，按 config_group → config_key 分组
+pub(crate) async fn load_config_map<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<HashMap<String, HashMap<String, String>>, AppError> {
+    let sys_configs = crate::models::system_config::Entity::find()
+        .all(conn)
+        .await?;
+    let mut m: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for c in sys_configs {
+        m.entry(c.config_group)
+            .or_default()
+            .insert(c.config_key, c.config_value);
+    }
+    Ok(m)
+}
+
+/// 白班 / 夜班时间窗（分钟）；夜班结束 = 次日白班开始，用 24h 偏移
+pub(crate) fn shift_windows(
+    config_map: &HashMap<String, HashMap<String, String>>,
+) -> [(&'static str, f64, f64); 2] {
+    let shift_minutes = |key: &str, default: &str| -> f64 {
+        let value = config_map
+            .get("shift")
+            .and_then(|g| g.get(key))
+            .map(|s| s.as_str())
+            .unwrap_or(default);
+        let t = chrono::NaiveTime::parse_from_str(value, "%H:%M").unwrap_or(DEFAULT_SHIFT_START);
+        t.hour() as f64 * 60.0 + t.minute() as f64
+    };
+    let day_start = shift_minutes("day_start", "08:00");
+    [
+        ("day", day_start, shift_minutes("day_end", "20:00")),
+        (
+            "night",
+            shift_minutes("night_start", "20:00"),
+            day_start + 24.0 * 60.0,
+        ),
+    ]
+}
+
 fn parse_bool_cfg(group: Option<&HashMap<String, String>>, key: &str, default_value: bool) -> bool {
     group
         .and_then(|g| g.get(key))
@@ -828,6 +886,27 @@ fn candidate_increment_score(
     priority_ratio * 0.62 + fill_ratio * 0.38 + tail_bonus - roll_penalty
 }
 
+/// 候选材料的库区吊运惩罚（未启用 yard_retrieval 时为 0）
+fn yard_candidate_penalty(
+    yard: Option<&YardState>,
+    current: &material::Model,
+    prev: Option<&material::Model>,
+) -> f64 {
+    let Some(state) = yard else {
+        return 0.0;
+    };
+    let mut penalty = state.blockers(current.id).len() as f64 * YARD_DIG_SCORE_PENALTY;
+    if let (Some(p), Some(c)) = (
+        prev.and_then(|m| state.location(m.id)),
+        state.location(current.id),
+    ) {
+        if p.bay != c.bay {
+            penalty += YARD_BAY_SWITCH_SCORE_PENALTY;
+        }
+    }
+    penalty
+}
+
 fn greedy_pick_next_position(
     sorted: &[SortedMaterial],
     available_pool: &[usize],
@@ -840,6 +919,7 @@ fn greedy_pick_next_position(
     shift_capacity: f64,
     rhythm_minutes: f64,
     roll_config: &RollChangeConfig,
+    yard: Option<&YardState>,
 ) -> Option<usize> {
     let mut best: Option<(usize, f64)> = None;
     for (rank, &pool_idx) in unscheduled_positions.iter().enumerate() {
@@ -866,7 +946,7 @@ fn greedy_pick_next_position(
             &eval,
             shift_capacity,
             shift_cumulative,
-        );
+        ) - yard_candidate_penalty(yard, &sm.material, prev_material);
         if best
             .map(|(_, best_score)| score > best_score)
             .unwrap_or(true)
@@ -891,6 +971,7 @@ fn beam_pick_next_position(
     shift_capacity: f64,
     rhythm_minutes: f64,
     roll_config: &RollChangeConfig,
+    yard: Option<&YardState>,
 ) -> Option<usize> {
     if unscheduled_positions.is_empty() || cfg.beam_width < 2 || cfg.beam_lookahead < 2 {
         return None;
@@ -959,7 +1040,7 @@ fn beam_pick_next_position(
                     &eval,
                     shift_capacity,
                     state.shift_cumulative,
-                );
+                ) - yard_candidate_penalty(yard, &sm.material, prev_material);
                 next_beam.push(BeamState {
                     selected_positions,
                     check_time: eval.next_check_time,
@@ -1001,6 +1082,7 @@ fn pick_next_pool_position(
     shift_capacity: f64,
     rhythm_minutes: f64,
     roll_config: &RollChangeConfig,
+    yard: Option<&YardState>,
 ) -> Option<(usize, PickMode)> {
     let beam_allowed = cfg.mode != SchedulerMode::GreedyOnly
         && elapsed_ms <= cfg.time_budget_ms
@@ -1021,6 +1103,7 @@ fn pick_next_pool_position(
             shift_capacity,
             rhythm_minutes,
             roll_config,
+            yard,
         ) {
             return Some((pool_idx, PickMode::Beam));
         }
@@ -1042,6 +1125,7 @@ fn pick_next_pool_position(
         shift_capacity,
         rhythm_minutes,
         roll_config,
+        yard,
     )?;
     let mode = if cfg.mode == SchedulerMode::GreedyOnly {
         PickMode::GreedyOnly
@@ -1072,6 +1156,245 @@ fn should_roll_change(
     true
 }
 
+/// 换辊点重排结果
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RollChangeReoptimizeOutput {
+    pub plan_id: i32,
+    pub before_count: usize,
+    pub after_count: usize,
+    pub before_cost: f64,
+    pub after_cost: f64,
+    /// 换辊标记或计划时间发生变化的排程项数
+    pub changed_items: usize,
+    /// 优化后的换辊点（after_index 为方案全序列索引）
+    pub roll_changes: Vec<RollChangePoint>,
+}
+
+/// 对方案现有序列按日求最优换辊点，回写换辊标记并顺延班次内计划时间
+///
+/// 与 Step 7 一致，每日首卷视为新辊；排程项的班次归属与加工时长保持不变，
+/// 仅按新的换辊位置重新累加换辊耗时。若顺延后某班次的结束时间超出班次结束
+/// （且晚于原换辊方案的结束时间），该日保留原换辊点不做调整。
+pub async fn apply_optimal_roll_changes<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    roll_config: &RollChangeConfig,
+    weights: &roll_change::RollChangeCostWeights,
+) -> Result<RollChangeReoptimizeOutput, AppError> {
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(conn)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|it| it.material_id).collect();
+    let mat_map: HashMap<i32, material::Model> = if mat_ids.is_empty() {
+        HashMap::new()
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect()
+    };
+    // 材料缺失的排程项不参与换辊计算
+    let items: Vec<schedule_item::Model> = items
+        .into_iter()
+        .filter(|it| mat_map.contains_key(&it.material_id))
+        .collect();
+    let shifts = shift_windows(&load_config_map(conn).await?);
+
+    let mut roll_changes = Vec::new();
+    let mut before_count = 0usize;
+    let mut before_cost = 0.0f64;
+    let mut after_cost = 0.0f64;
+    let mut changed_items = 0usize;
+
+    let mut day_start = 0usize;
+    while day_start < items.len() {
+        let mut day_end = day_start;
+        while day_end < items.len() && items[day_end].shift_date == items[day_start].shift_date {
+            day_end += 1;
+        }
+        let day_items = &items[day_start..day_end];
+        let day_seq: Vec<SortedMaterial> = day_items
+            .iter()
+            .filter_map(|it| mat_map.get(&it.material_id))
+            .map(|m| SortedMaterial {
+                material: m.clone(),
+                sort_keys: vec![],
+                earliest_schedule_date: None,
+            })
+            .collect();
+
+        let old_indices: Vec<usize> = day_items
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, it)| it.is_roll_change == Some(true))
+            .map(|(i, _)| i - 1)
+            .collect();
+        let old_cost =
+            roll_change::roll_change_plan_cost(&day_seq, &old_indices, roll_config, weights);
+        before_count += old_indices.len();
+        before_cost += old_cost;
+
+        let day_changes = roll_change::optimize_roll_changes(&day_seq, roll_config, weights);
+        let new_indices = roll_change::roll_change_indices(&day_changes);
+        let mut new_flags = vec![false; day_items.len()];
+        for &i in &new_indices {
+            new_flags[i + 1] = true;
+        }
+
+        // 逐班次按新换辊位置顺延计划时间
+        let mut updates = Vec::new();
+        let mut overflow = None;
+        let mut shift_start = 0usize;
+        while shift_start < day_items.len() {
+            let mut shift_end = shift_start;
+            while shift_end < day_items.len()
+                && day_items[shift_end].shift_type == day_items[shift_start].shift_type
+            {
+                shift_end += 1;
+            }
+            let shift_items = &day_items[shift_start..shift_end];
+            let shift_flags = &new_flags[shift_start..shift_end];
+            let window = shifts
+                .iter()
+                .find(|(shift_type, _, _)| *shift_type == shift_items[0].shift_type)
+                .map(|&(_, start, end)| (start, end));
+            let new_times = retime_shift(shift_items, shift_flags, roll_config, window);
+
+            if let (Some((_, window_end)), Some(new_times)) = (window, &new_times) {
+                let old_flags: Vec<bool> = shift_items
+                    .iter()
+                    .map(|it| it.is_roll_change == Some(true))
+                    .collect();
+                let old_end = retime_shift(shift_items, &old_flags, roll_config, window)
+                    .and_then(|times| times.last().map(|&(_, end)| end))
+                    .unwrap_or(window_end);
+                let new_end = new_times.last().map(|&(_, end)| end).unwrap_or(window_end);
+                if new_end > window_end.max(old_end) + 0.5 {
+                    overflow = Some((shift_items[0].shift_type.clone(), new_end - window_end));
+                    break;
+                }
+            }
+
+            for (offset, item) in shift_items.iter().enumerate() {
+                let new_flag = shift_flags[offset];
+                let (planned_start, planned_end) = match &new_times {
+                    Some(times) => {
+                        let (start, end) = times[offset];
+                        (Some(format_time(start)), Some(format_time(end)))
+                    }
+                    None => (item.planned_start.clone(), item.planned_end.clone()),
+                };
+                if item.is_roll_change.unwrap_or(false) == new_flag
+                    && item.planned_start == planned_start
+                    && item.planned_end == planned_end
+                {
+                    continue;
+                }
+                updates.push((item.id, new_flag, planned_start, planned_end));
+            }
+            shift_start = shift_end;
+        }
+
+        if let Some((shift_type, excess)) = overflow {
+            log::warn!(
+                "[换辊] {} {} 班优化后超出班次结束 {:.0} 分钟，保留原换辊点",
+                day_items[0].shift_date,
+                shift_type,
+                excess
+            );
+            after_cost += old_cost;
+            for mut rc in roll_change::roll_change_points_at(&day_seq, &old_indices, roll_config) {
+                rc.after_index += day_start;
+                roll_changes.push(rc);
+            }
+            day_start = day_end;
+            continue;
+        }
+
+        after_cost +=
+            roll_change::roll_change_plan_cost(&day_seq, &new_indices, roll_config, weights);
+        for mut rc in day_changes {
+            rc.after_index += day_start;
+            roll_changes.push(rc);
+        }
+        for (id, new_flag, planned_start, planned_end) in updates {
+            schedule_item::Entity::update_many()
+                .col_expr(
+                    schedule_item::Column::IsRollChange,
+                    sea_query::Expr::value(new_flag),
+                )
+                .col_expr(
+                    schedule_item::Column::PlannedStart,
+                    sea_query::Expr::value(planned_start),
+                )
+                .col_expr(
+                    schedule_item::Column::PlannedEnd,
+                    sea_query::Expr::value(planned_end),
+                )
+                .filter(schedule_item::Column::Id.eq(id))
+                .exec(conn)
+                .await?;
+            changed_items += 1;
+        }
+        day_start = day_end;
+    }
+
+    Ok(RollChangeReoptimizeOutput {
+        plan_id,
+        before_count,
+        after_count: roll_changes.len(),
+        before_cost,
+        after_cost,
+        changed_items,
+        roll_changes,
+    })
+}
+
+/// 按换辊标记重新累加同一班次内排程项的计划时间（分钟，可跨 24h）
+///
+/// 起点取首项原计划开始（首项原为换辊时扣除换辊耗时），各项加工时长保持不变；
+/// 夜班跨零点的时间按班次时间窗平移到同一时间轴。任一项缺少计划时间时返回 None。
+fn retime_shift(
+    items: &[schedule_item::Model],
+    flags: &[bool],
+    roll_config: &RollChangeConfig,
+    window: Option<(f64, f64)>,
+) -> Option<Vec<(f64, f64)>> {
+    let parse_minutes = |v: &Option<String>| -> Option<f64> {
+        let t = chrono::NaiveTime::parse_from_str(v.as_deref()?, "%H:%M").ok()?;
+        Some(t.hour() as f64 * 60.0 + t.minute() as f64)
+    };
+    let first = items.first()?;
+    let mut cursor = parse_minutes(&first.planned_start)?;
+    if first.is_roll_change == Some(true) {
+        cursor -= roll_config.change_duration_min;
+    }
+    if let Some((window_start, _)) = window {
+        while cursor < window_start - 0.5 {
+            cursor += 24.0 * 60.0;
+        }
+    }
+
+    let mut times = Vec::with_capacity(items.len());
+    for (item, &flag) in items.iter().zip(flags) {
+        let start = parse_minutes(&item.planned_start)?;
+        let end = parse_minutes(&item.planned_end)?;
+        let duration = (end - start).rem_euclid(24.0 * 60.0);
+        if flag {
+            cursor += roll_config.change_duration_min;
+        }
+        times.push((cursor, cursor + duration));
+        cursor += duration;
+    }
+    Some(times)
+}
+
 /// 格式化分钟数为 HH:MM
 fn format_time(minutes: f64) -> String {
     let total_min = minutes.round() as u32;
@@ -1112,8 +1435,8 @@ fn classify_ready_date_in_plan(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::NaiveDate;
     use crate::engine::test_helpers::helpers::{make_material, wrap};
+    use chrono::NaiveDate;
 
     #[test]
     fn pick_next_pool_position_should_skip_infeasible_head_candidate() {
@@ -1145,6 +1468,7 @@ mod tests {
             100.0,
             3.5,
             &roll_config,
+            None,
         )
         .expect("应命中后续可排候选");
 
@@ -1181,6 +1505,7 @@ mod tests {
             100.0,
             3.5,
             &roll_config,
+            None,
         );
 
         assert!(pick.is_none());
@@ -1215,6 +1540,7 @@ mod tests {
             100.0,
             3.5,
             &roll_config,
+            None,
         )
         .expect("应触发贪心兜底");
 
@@ -1251,4 +1577,45 @@ mod tests {
             ReadyDatePlacement::Excluded
         );
     }
+
+    fn night_item(id: i32, start: &str, end: &str, roll: bool) -> schedule_item::Model {
+        schedule_item::Model {
+            id,
+            plan_id: 1,
+            material_id: id,
+            sequence: id,
+            shift_date: "2026-03-01".into(),
+            shift_no: 2,
+            shift_type: "night".into(),
+            planned_start: Some(start.into()),
+            planned_end: Some(end.into()),
+            cumulative_weight: None,
+            is_roll_change: Some(roll),
+            is_locked: Some(false),
+            lock_reason: None,
+            risk_flags: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn retime_shift_keeps_night_shift_on_one_timeline_across_midnight() {
+        let roll_config = RollChangeConfig {
+            change_duration_min: 30.0,
+            ..Default::default()
+        };
+        let items = vec![
+            night_item(1, "07:00", "07:40", false),
+            night_item(2, "07:40", "08:00", false),
+        ];
+        let window = Some((20.0 * 60.0, 32.0 * 60.0));
+
+        let times = retime_shift(&items, &[false, true], &roll_config, window).unwrap();
+        // 次日 07:00 平移为 31:00；换辊后第二卷顺延 30 分钟，结束超出 08:00 班次结束
+        assert_eq!(times[0], (31.0 * 60.0, 31.0 * 60.0 + 40.0));
+        assert_eq!(times[1].0, 31.0 * 60.0 + 70.0);
+        assert!(times[1].1 > 32.0 * 60.0);
+        assert_eq!(format_time(times[1].1), "08:30");
+    }
 }
@@ -0,0 +1,710 @@
+//! 离散事件执行模拟 — 逐分钟推进已确认方案
+//!
+//! 产线状态：空闲 / 换辊 / 轧制 / 停机。每分钟先处理到期的注入事件，再推进产线：
+//!   - line_stop          产线停机若干分钟（暂停当前换辊或轧制）
+//!   - coil_rejected      钢卷判废：在队列中则移除，正在轧制则中止
+//!   - rush_coil          急单插入：默认插到队首，下一卷即上线
+//!   - roll_change_overrun 换辊超时：正在换辊则延长，否则计入下一次换辊
+//!
+//! 计划钢卷不早于计划开始时间上线。同一组（方案快照 + 事件日志）重放结果完全一致。
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::engine::robustness::SimCoil;
+use crate::AppError;
+
+/// 注入事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub enum SimulationEvent {
+    LineStop {
+        at_minute: f64,
+        duration_min: f64,
+        reason: Option<String>,
+    },
+    CoilRejected {
+        at_minute: f64,
+        material_id: i32,
+        reason: Option<String>,
+    },
+    RushCoil {
+        at_minute: f64,
+        coil_id: String,
+        duration_min: f64,
+        /// true=插到队首，false=排到队尾
+        #[serde(default = "default_true")]
+        insert_next: bool,
+    },
+    RollChangeOverrun {
+        at_minute: f64,
+        extra_min: f64,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SimulationEvent {
+    pub fn at_minute(&self) -> f64 {
+        match self {
+            SimulationEvent::LineStop { at_minute, .. }
+            | SimulationEvent::CoilRejected { at_minute, .. }
+            | SimulationEvent::RushCoil { at_minute, .. }
+            | SimulationEvent::RollChangeOverrun { at_minute, .. } => *at_minute,
+        }
+    }
+
+    /// 校验事件参数：时刻须落在方案时间范围内，时长须为非负有限值且不超过方案跨度
+    pub fn validate(&self, horizon: (f64, f64)) -> Result<(), AppError> {
+        let (start, end) = horizon;
+        let at = self.at_minute();
+        if !at.is_finite() || at < start || at > end {
+            return Err(AppError::InvalidInput(format!(
+                "事件时刻 {} 超出方案时间范围 {}~{}",
+                at, start, end
+            )));
+        }
+        let duration = match self {
+            SimulationEvent::LineStop { duration_min, .. }
+            | SimulationEvent::RushCoil { duration_min, .. } => {
+                Some(("duration_min", *duration_min))
+            }
+            SimulationEvent::RollChangeOverrun { extra_min, .. } => Some(("extra_min", *extra_min)),
+            SimulationEvent::CoilRejected { .. } => None,
+        };
+        if let Some((name, value)) = duration {
+            if !value.is_finite() || value < 0.0 || value > end - start {
+                return Err(AppError::InvalidInput(format!(
+                    "{} 须在 0~{} 分钟之间",
+                    name,
+                    end - start
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            SimulationEvent::LineStop { .. } => "line_stop",
+            SimulationEvent::CoilRejected { .. } => "coil_rejected",
+            SimulationEvent::RushCoil { .. } => "rush_coil",
+            SimulationEvent::RollChangeOverrun { .. } => "roll_change_overrun",
+        }
+    }
+}
+
+/// 模拟中的作业（计划卷或急单）
+#[derive(Debug, Clone)]
+struct Job {
+    /// 计划卷在方案快照中的索引；急单为 None
+    plan_index: Option<usize>,
+    /// 在执行结果列表中的索引
+    outcome_index: usize,
+    material_id: Option<i32>,
+    coil_id: String,
+    duration_min: f64,
+    planned_start_min: Option<f64>,
+    is_roll_change: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineState {
+    Idle,
+    RollChange { remaining: f64 },
+    Rolling { remaining: f64 },
+}
+
+/// 时间线快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationSnapshot {
+    pub minute: f64,
+    /// idle / roll_change / rolling / stopped / finished
+    pub line_state: String,
+    pub current_coil: Option<String>,
+    /// 当前卷已完成比例（0~1）
+    pub current_progress: f64,
+    pub queue_len: usize,
+    /// 队首若干卷
+    pub queue_head: Vec<String>,
+    /// 相对计划的延误（分钟，提前为负）
+    pub delay_vs_plan_min: f64,
+}
+
+/// 单卷执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoilOutcome {
+    pub material_id: Option<i32>,
+    pub coil_id: String,
+    pub is_rush: bool,
+    /// completed / rejected / pending
+    pub status: String,
+    pub actual_start_min: Option<f64>,
+    pub actual_end_min: Option<f64>,
+    pub planned_end_min: Option<f64>,
+    pub delay_min: Option<f64>,
+    pub due_missed: bool,
+}
+
+/// 模拟结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationTimeline {
+    pub start_minute: f64,
+    pub end_minute: f64,
+    pub finished: bool,
+    pub snapshots: Vec<SimulationSnapshot>,
+    pub outcomes: Vec<CoilOutcome>,
+}
+
+/// 会话评分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationScore {
+    pub score: i32,
+    pub completed_count: usize,
+    pub rejected_count: usize,
+    pub rush_completed_count: usize,
+    pub on_time_rate: f64,
+    pub avg_delay_min: f64,
+    pub max_delay_min: f64,
+    pub due_miss_count: usize,
+}
+
+const QUEUE_HEAD_SIZE: usize = 5;
+
+/// 模拟起始时刻：首卷（含计划换辊）最早开始的整分钟
+pub fn start_minute(coils: &[SimCoil]) -> f64 {
+    let earliest = coils
+        .iter()
+        .map(|c| {
+            c.planned_start_min
+                - if c.is_roll_change {
+                    c.roll_change_min
+                } else {
+                    0.0
+                }
+        })
+        .fold(f64::INFINITY, f64::min);
+    if earliest.is_finite() {
+        earliest.floor()
+    } else {
+        0.0
+    }
+}
+
+/// 方案时间范围：模拟起始时刻 ~ 最晚计划结束时刻
+pub fn plan_horizon(coils: &[SimCoil]) -> (f64, f64) {
+    let start = start_minute(coils);
+    let end = coils
+        .iter()
+        .map(|c| c.planned_start_min + c.planned_duration_min)
+        .fold(start, f64::max);
+    (start, end)
+}
+
+/// 单次模拟最多推进的分钟数（31 天），防止事件异常时无限推进
+pub const MAX_SIMULATED_MINUTES: f64 = 31.0 * 24.0 * 60.0;
+
+/// 逐分钟推进模拟，直到 until_minute、全部作业结束或达到 MAX_SIMULATED_MINUTES
+///
+/// snapshot_every 为快照间隔（分钟，≥1）；状态变化的分钟也会输出快照。
+pub fn run_simulation(
+    coils: &[SimCoil],
+    events: &[SimulationEvent],
+    until_minute: Option<f64>,
+    snapshot_every: f64,
+) -> SimulationTimeline {
+    let start_minute = start_minute(coils);
+    let snapshot_every = snapshot_every.max(1.0);
+
+    let mut pending_events: Vec<&SimulationEvent> = events.iter().collect();
+    pending_events.sort_by(|a, b| {
+        a.at_minute()
+            .partial_cmp(&b.at_minute())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut pending_events: VecDeque<&SimulationEvent> = pending_events.into();
+
+    let mut queue: VecDeque<Job> = coils
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| Job {
+            plan_index: Some(idx),
+            outcome_index: idx,
+            material_id: Some(c.material_id),
+            coil_id: c.coil_id.clone(),
+            duration_min: c.planned_duration_min.max(1.0),
+            planned_start_min: Some(c.planned_start_min),
+            is_roll_change: c.is_roll_change,
+        })
+        .collect();
+
+    let mut outcomes: Vec<CoilOutcome> = coils
+        .iter()
+        .map(|c| CoilOutcome {
+            material_id: Some(c.material_id),
+            coil_id: c.coil_id.clone(),
+            is_rush: false,
+            status: "pending".into(),
+            actual_start_min: None,
+            actual_end_min: None,
+            planned_end_min: Some(c.planned_start_min + c.planned_duration_min),
+            delay_min: None,
+            due_missed: false,
+        })
+        .collect();
+
+    let mut state = LineState::Idle;
+    let mut current: Option<(Job, usize)> = None; // (作业, outcomes 索引)
+    let mut stop_remaining = 0.0f64;
+    let mut pending_overrun = 0.0f64;
+    let mut snapshots = Vec::new();
+    let mut last_signature = String::new();
+    let mut minute = start_minute;
+    let mut next_snapshot = start_minute;
+
+    let roll_change_min = |job: &Job| -> f64 {
+        job.plan_index
+            .map(|idx| coils[idx].roll_change_min)
+            .unwrap_or(0.0)
+    };
+
+    loop {
+        // 1. 处理到期事件
+        while pending_events
+            .front()
+            .is_some_and(|e| e.at_minute() <= minute)
+        {
+            let Some(event) = pending_events.pop_front() else {
+                break;
+            };
+            match event {
+                SimulationEvent::LineStop { duration_min, .. } => {
+                    stop_remaining += duration_min.max(0.0);
+                }
+                SimulationEvent::CoilRejected { material_id, .. } => {
+                    if let Some(pos) = queue
+                        .iter()
+                        .position(|j| j.material_id == Some(*material_id))
+                    {
+                        if let Some(job) = queue.remove(pos) {
+                            outcomes[job.outcome_index].status = "rejected".into();
+                        }
+                    } else if current
+                        .as_ref()
+                        .is_some_and(|(j, _)| j.material_id == Some(*material_id))
+                    {
+                        if let Some((_, out_idx)) = current.take() {
+                            outcomes[out_idx].status = "rejected".into();
+                        }
+                        state = LineState::Idle;
+                    }
+                }
+                SimulationEvent::RushCoil {
+                    coil_id,
+                    duration_min,
+                    insert_next,
+                    ..
+                } => {
+                    outcomes.push(CoilOutcome {
+                        material_id: None,
+                        coil_id: coil_id.clone(),
+                        is_rush: true,
+                        status: "pending".into(),
+                        actual_start_min: None,
+                        actual_end_min: None,
+                        planned_end_min: None,
+                        delay_min: None,
+                        due_missed: false,
+                    });
+                    let job = Job {
+                        plan_index: None,
+                        outcome_index: outcomes.len() - 1,
+                        material_id: None,
+                        coil_id: coil_id.clone(),
+                        duration_min: duration_min.max(1.0),
+                        planned_start_min: None,
+                        is_roll_change: false,
+                    };
+                    if *insert_next {
+                        queue.push_front(job);
+                    } else {
+                        queue.push_back(job);
+                    }
+                }
+                SimulationEvent::RollChangeOverrun { extra_min, .. } => {
+                    if let LineState::RollChange { remaining } = state {
+                        state = LineState::RollChange {
+                            remaining: remaining + extra_min.max(0.0),
+                        };
+                    } else {
+                        pending_overrun += extra_min.max(0.0);
+                    }
+                }
+            }
+        }
+
+        // 2. 空闲时取下一作业（计划卷不早于计划开始时间）
+        if state == LineState::Idle && stop_remaining <= 0.0 {
+            let ready = queue.front().is_some_and(|job| {
+                let earliest = job
+                    .planned_start_min
+                    .map(|s| {
+                        s - if job.is_roll_change {
+                            roll_change_min(job)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .unwrap_or(f64::NEG_INFINITY);
+                earliest <= minute
+            });
+            if ready {
+                if let Some(job) = queue.pop_front() {
+                    let out_idx = job.outcome_index;
+                    state = if job.is_roll_change {
+                        let remaining = roll_change_min(&job) + pending_overrun;
+                        pending_overrun = 0.0;
+                        LineState::RollChange { remaining }
+                    } else {
+                        outcomes[out_idx].actual_start_min = Some(minute);
+                        LineState::Rolling {
+                            remaining: job.duration_min,
+                        }
+                    };
+                    current = Some((job, out_idx));
+                }
+            }
+        }
+
+        // 末卷结束后仍有待触发事件（如插单）时继续推进
+        let finished = current.is_none() && queue.is_empty() && pending_events.is_empty();
+
+        // 3. 输出快照（按间隔或状态变化）
+        let snapshot = build_snapshot(
+            minute,
+            &state,
+            stop_remaining,
+            current.as_ref(),
+            &queue,
+            &outcomes,
+            finished,
+        );
+        let signature = format!(
+            "{}|{:?}|{}",
+            snapshot.line_state, snapshot.current_coil, snapshot.queue_len
+        );
+        if minute >= next_snapshot || signature != last_signature || finished {
+            snapshots.push(snapshot);
+            last_signature = signature;
+            while next_snapshot <= minute {
+                next_snapshot += snapshot_every;
+            }
+        }
+
+        if finished
+            || until_minute.is_some_and(|u| minute >= u)
+            || minute - start_minute >= MAX_SIMULATED_MINUTES
+        {
+            return SimulationTimeline {
+                start_minute,
+                end_minute: minute,
+                finished,
+                snapshots,
+                outcomes,
+            };
+        }
+
+        // 4. 推进一分钟
+        if stop_remaining > 0.0 {
+            stop_remaining = (stop_remaining - 1.0).max(0.0);
+        } else {
+            match state {
+                LineState::Idle => {}
+                LineState::RollChange { remaining } => {
+                    let left = remaining - 1.0;
+                    state = if left > 0.0 {
+                        LineState::RollChange { remaining: left }
+                    } else if let Some((job, out_idx)) = current.as_ref() {
+                        outcomes[*out_idx].actual_start_min = Some(minute + 1.0);
+                        LineState::Rolling {
+                            remaining: job.duration_min,
+                        }
+                    } else {
+                        LineState::Idle
+                    };
+                }
+                LineState::Rolling { remaining } => {
+                    let left = remaining - 1.0;
+                    if left > 0.0 {
+                        state = LineState::Rolling { remaining: left };
+                    } else {
+                        if let Some((job, out_idx)) = current.take() {
+                            let end = minute + 1.0;
+                            let outcome = &mut outcomes[out_idx];
+                            outcome.status = "completed".into();
+                            outcome.actual_end_min = Some(end);
+                            outcome.delay_min = outcome.planned_end_min.map(|p| end - p);
+                            outcome.due_missed = job
+                                .plan_index
+                                .and_then(|idx| coils[idx].due_min)
+                                .is_some_and(|due| end > due);
+                        }
+                        state = LineState::Idle;
+                    }
+                }
+            }
+        }
+        minute += 1.0;
+    }
+}
+
+fn build_snapshot(
+    minute: f64,
+    state: &LineState,
+    stop_remaining: f64,
+    current: Option<&(Job, usize)>,
+    queue: &VecDeque<Job>,
+    outcomes: &[CoilOutcome],
+    finished: bool,
+) -> SimulationSnapshot {
+    let line_state = if finished {
+        "finished"
+    } else if stop_remaining > 0.0 {
+        "stopped"
+    } else {
+        match state {
+            LineState::Idle => "idle",
+            LineState::RollChange { .. } => "roll_change",
+            LineState::Rolling { .. } => "rolling",
+        }
+    };
+    let current_progress = match (state, current) {
+        (LineState::Rolling { remaining }, Some((job, _))) => {
+            (1.0 - remaining / job.duration_min).clamp(0.0, 1.0)
+        }
+        _ => 0.0,
+    };
+
+    // 延误：当前/下一计划卷的（实际或最早可能）开始时间与计划开始之差；
+    // 全部完成时取最后一卷完工延误
+    let head_delay = current
+        .map(|(job, idx)| (job, outcomes[*idx].actual_start_min.unwrap_or(minute)))
+        .into_iter()
+        .chain(queue.iter().map(|job| (job, minute)))
+        .find_map(|(job, start)| job.planned_start_min.map(|p| start - p));
+    let delay_vs_plan_min = head_delay.unwrap_or_else(|| {
+        outcomes
+            .iter()
+            .rev()
+            .find_map(|o| o.delay_min)
+            .unwrap_or(0.0)
+    });
+
+    SimulationSnapshot {
+        minute,
+        line_state: line_state.into(),
+        current_coil: current.map(|(job, _)| job.coil_id.clone()),
+        current_progress,
+        queue_len: queue.len(),
+        queue_head: queue
+            .iter()
+            .take(QUEUE_HEAD_SIZE)
+            .map(|j| j.coil_id.clone())
+            .collect(),
+        delay_vs_plan_min,
+    }
+}
+
+/// 按执行结果评分（满分 100）
+///
+/// 扣分项：计划卷平均延误每 10 分钟 -5、交期延误每卷 -5、判废每卷 -2、未完成计划卷每卷 -3；
+/// 加分项：完成急单每卷 +2。
+pub fn score_simulation(timeline: &SimulationTimeline) -> SimulationScore {
+    let planned: Vec<&CoilOutcome> = timeline.outcomes.iter().filter(|o| !o.is_rush).collect();
+    let completed: Vec<&&CoilOutcome> =
+        planned.iter().filter(|o| o.status == "completed").collect();
+    let rejected_count = planned.iter().filter(|o| o.status == "rejected").count();
+    let unfinished = planned.iter().filter(|o| o.status == "pending").count();
+    let rush_completed_count = timeline
+        .outcomes
+        .iter()
+        .filter(|o| o.is_rush && o.status == "completed")
+        .count();
+
+    let delays: Vec<f64> = completed.iter().filter_map(|o| o.delay_min).collect();
+    let on_time = delays.iter().filter(|d| **d <= 0.0).count();
+    let avg_delay_min = if delays.is_empty() {
+        0.0
+    } else {
+        delays.iter().map(|d| d.max(0.0)).sum::<f64>() / delays.len() as f64
+    };
+    let max_delay_min = delays.iter().cloned().fold(0.0f64, f64::max);
+    let due_miss_count = completed.iter().filter(|o| o.due_missed).count();
+
+    let raw = 100.0
+        - avg_delay_min / 10.0 * 5.0
+        - due_miss_count as f64 * 5.0
+        - rejected_count as f64 * 2.0
+        - unfinished as f64 * 3.0
+        + rush_completed_count as f64 * 2.0;
+
+    SimulationScore {
+        score: raw.round().clamp(0.0, 100.0) as i32,
+        completed_count: completed.len(),
+        rejected_count,
+        rush_completed_count,
+        on_time_rate: if delays.is_empty() {
+            0.0
+        } else {
+            on_time as f64 / delays.len() as f64
+        },
+        avg_delay_min,
+        max_delay_min,
+        due_miss_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(count: usize) -> Vec<SimCoil> {
+        (0..count)
+            .map(|i| SimCoil {
+                material_id: i as i32 + 1,
+                coil_id: format!("C{:03}", i + 1),
+                shift_key: "2024-06-01 day".into(),
+                shift_end_min: 1200.0,
+                planned_start_min: 480.0 + i as f64 * 20.0,
+                planned_duration_min: 20.0,
+                is_roll_change: false,
+                roll_change_min: 30.0,
+                due_min: Some(600.0),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_undisturbed_run_matches_plan() {
+        let coils = plan(5);
+        let timeline = run_simulation(&coils, &[], None, 10.0);
+        assert!(timeline.finished);
+        assert_eq!(timeline.end_minute, 580.0);
+        assert!(timeline.outcomes.iter().all(|o| o.delay_min == Some(0.0)));
+        assert_eq!(score_simulation(&timeline).score, 100);
+    }
+
+    #[test]
+    fn test_line_stop_delays_following_coils() {
+        let coils = plan(5);
+        let events = vec![SimulationEvent::LineStop {
+            at_minute: 490.0,
+            duration_min: 15.0,
+            reason: None,
+        }];
+        let timeline = run_simulation(&coils, &events, None, 10.0);
+        assert_eq!(timeline.outcomes[0].delay_min, Some(15.0));
+        assert_eq!(timeline.outcomes[4].delay_min, Some(15.0));
+        assert!(timeline.snapshots.iter().any(|s| s.line_state == "stopped"));
+    }
+
+    #[test]
+    fn test_rejected_and_rush_coils() {
+        let coils = plan(4);
+        let events = vec![
+            SimulationEvent::CoilRejected {
+                at_minute: 485.0,
+                material_id: 3,
+                reason: Some("表面缺陷".into()),
+            },
+            SimulationEvent::RushCoil {
+                at_minute: 485.0,
+                coil_id: "RUSH01".into(),
+                duration_min: 10.0,
+                insert_next: true,
+            },
+        ];
+        let timeline = run_simulation(&coils, &events, None, 5.0);
+        assert_eq!(timeline.outcomes[2].status, "rejected");
+        let rush = timeline.outcomes.iter().find(|o| o.is_rush).unwrap();
+        assert_eq!(rush.status, "completed");
+        assert_eq!(rush.actual_start_min, Some(500.0));
+        let score = score_simulation(&timeline);
+        assert_eq!(score.rejected_count, 1);
+        assert_eq!(score.rush_completed_count, 1);
+    }
+
+    #[test]
+    fn test_event_after_last_coil_is_still_applied() {
+        // 末卷被剔除后队列提前清空，之后的插单仍须生效
+        let coils = plan(2);
+        let events = vec![
+            SimulationEvent::CoilRejected {
+                at_minute: 485.0,
+                material_id: 2,
+                reason: None,
+            },
+            SimulationEvent::RushCoil {
+                at_minute: 510.0,
+                coil_id: "RUSH01".into(),
+                duration_min: 10.0,
+                insert_next: false,
+            },
+        ];
+        assert!(events
+            .iter()
+            .all(|e| e.validate(plan_horizon(&coils)).is_ok()));
+        let timeline = run_simulation(&coils, &events, None, 10.0);
+        assert!(timeline.finished);
+        assert_eq!(timeline.end_minute, 520.0);
+        let rush = timeline.outcomes.iter().find(|o| o.is_rush).unwrap();
+        assert_eq!(rush.status, "completed");
+        assert_eq!(rush.actual_start_min, Some(510.0));
+    }
+
+    #[test]
+    fn test_long_line_stop_hits_simulation_cap() {
+        let coils = plan(2);
+        let events = vec![SimulationEvent::LineStop {
+            at_minute: 480.0,
+            duration_min: 1.0e9,
+            reason: None,
+        }];
+        let timeline = run_simulation(&coils, &events, None, 1440.0);
+        assert!(!timeline.finished);
+        assert_eq!(timeline.end_minute, 480.0 + MAX_SIMULATED_MINUTES);
+    }
+
+    #[test]
+    fn test_event_validation_against_plan_horizon() {
+        let horizon = plan_horizon(&plan(5));
+        assert_eq!(horizon, (480.0, 580.0));
+        let stop = |at_minute: f64, duration_min: f64| SimulationEvent::LineStop {
+            at_minute,
+            duration_min,
+            reason: None,
+        };
+        assert!(stop(500.0, 15.0).validate(horizon).is_ok());
+        assert!(stop(f64::NAN, 15.0).validate(horizon).is_err());
+        assert!(stop(600.0, 15.0).validate(horizon).is_err());
+        assert!(stop(500.0, -1.0).validate(horizon).is_err());
+        assert!(stop(500.0, f64::INFINITY).validate(horizon).is_err());
+        let overrun = SimulationEvent::RollChangeOverrun {
+            at_minute: 500.0,
+            extra_min: 1000.0,
+        };
+        assert!(overrun.validate(horizon).is_err());
+    }
+
+    #[test]
+    fn test_until_minute_stops_early() {
+        let coils = plan(5);
+        let timeline = run_simulation(&coils, &[], Some(530.0), 10.0);
+        assert!(!timeline.finished);
+        assert_eq!(timeline.end_minute, 530.0);
+        let last = timeline.snapshots.last().unwrap();
+        assert_eq!(last.current_coil.as_deref(), Some("C003"));
+    }
+}
@@ -1 +1,231 @@
-// Temperature calculation engine (Phase 2)
+//! 钢卷冷却模型 — 估算卷芯温度随时间的变化
+//!
+//! 采用集总参数（Newton 冷却）+ 径向导热修正：
+//!   T(t+Δt) = T_amb + (T(t) − T_amb) × exp(−Δt / τ)
+//!   τ = m·c / (h·A) × (1 + Bi / 3)
+//!
+//! - m、A 由卷重、宽度和标准内径（762 mm）反推外径后得到；
+//! - 层间接触热阻使径向等效导热系数随带钢厚度变化，薄规格卷芯冷却更慢；
+//! - 环境温度逐日取值（日气温表优先，缺省回落到月均温度表）。
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::f64::consts::PI;
+
+use super::constants::{
+    COIL_INNER_DIAMETER_M, COOLING_STEP_MINUTES, STEEL_CONDUCTIVITY, STEEL_DENSITY,
+    STEEL_INTERLAYER_RESISTANCE, STEEL_SPECIFIC_HEAT,
+};
+
+/// 冷却模型参数
+#[derive(Debug, Clone)]
+pub struct CoolingParams {
+    /// 适温目标温度（℃），卷芯温度低于该值视为适温
+    pub target_temp: f64,
+    /// 材料未提供卷取温度时使用的默认值（℃）
+    pub default_coiling_temp: f64,
+    /// 表面综合换热系数（W/m²·K，含对流与辐射）
+    pub heat_transfer_coeff: f64,
+    /// 最长模拟天数，超出仍未适温则视为不可达（按 [`effective_ready_time`] 于模拟截止时刻放行）
+    pub max_days: i64,
+}
+
+impl Default for CoolingParams {
+    fn default() -> Self {
+        Self {
+            target_temp: 50.0,
+            default_coiling_temp: 600.0,
+            heat_transfer_coeff: 10.0,
+            max_days: 15,
+        }
+    }
+}
+
+/// 冷却计算所需的钢卷物理参数
+#[derive(Debug, Clone, Copy)]
+pub struct CoilThermalInput {
+    /// 卷取温度（℃）
+    pub coiling_temp: f64,
+    /// 卷重（吨）
+    pub weight: f64,
+    /// 带钢厚度（mm）
+    pub thickness: f64,
+    /// 带钢宽度（mm）
+    pub width: f64,
+}
+
+/// 计算钢卷冷却时间常数（小时）
+pub fn cooling_time_constant_hours(coil: &CoilThermalInput, heat_transfer_coeff: f64) -> f64 {
+    let mass = coil.weight.max(0.1) * 1000.0;
+    let width = (coil.width / 1000.0).max(0.1);
+    let thickness = (coil.thickness / 1000.0).max(0.0001);
+    let h = heat_transfer_coeff.max(0.1);
+    let inner = COIL_INNER_DIAMETER_M;
+
+    // 由体积反推外径：V = π/4 × (D² − d²) × w
+    let volume = mass / STEEL_DENSITY;
+    let outer = (4.0 * volume / (PI * width) + inner * inner).sqrt();
+
+    // 外圆柱面 + 内孔面 + 两端面
+    let area =
+        PI * outer * width + PI * inner * width + 2.0 * PI / 4.0 * (outer * outer - inner * inner);
+
+    // 径向等效导热系数（每层钢板导热 + 层间接触热阻串联）
+    let radial_k = thickness / (thickness / STEEL_CONDUCTIVITY + STEEL_INTERLAYER_RESISTANCE);
+    let half_wall = (outer - inner) / 4.0;
+    let biot = h * half_wall / radial_k;
+
+    let lumped_seconds = mass * STEEL_SPECIFIC_HEAT / (h * area);
+    lumped_seconds * (1.0 + biot / 3.0) / 3600.0
+}
+
+/// 模拟卷芯温度，返回降到目标温度以下的时刻；在 max_days 内不可达时返回 None
+pub fn estimate_ready_time<F>(
+    coiling_time: &DateTime<Utc>,
+    coil: &CoilThermalInput,
+    params: &CoolingParams,
+    ambient_at: F,
+) -> Option<DateTime<Utc>>
+where
+    F: Fn(NaiveDate) -> f64,
+{
+    if coil.coiling_temp <= params.target_temp {
+        return Some(*coiling_time);
+    }
+
+    let tau_hours = cooling_time_constant_hours(coil, params.heat_transfer_coeff);
+    let step_hours = COOLING_STEP_MINUTES as f64 / 60.0;
+    let decay = (-step_hours / tau_hours).exp();
+    let max_steps = params.max_days.max(1) * 24 * 60 / COOLING_STEP_MINUTES;
+
+    let mut temp = coil.coiling_temp;
+    let mut at = *coiling_time;
+    for _ in 0..max_steps {
+        let ambient = ambient_at(at.date_naive());
+        let next = ambient + (temp - ambient) * decay;
+        let next_at = at + Duration::minutes(COOLING_STEP_MINUTES);
+        if next <= params.target_temp {
+            // 步内线性插值，避免结果被步长量化
+            let ratio = ((temp - params.target_temp) / (temp - next)).clamp(0.0, 1.0);
+            let minutes = (COOLING_STEP_MINUTES as f64 * ratio).round() as i64;
+            return Some(at + Duration::minutes(minutes));
+        }
+        temp = next;
+        at = next_at;
+    }
+    None
+}
+
+/// 统一的适温时刻：模拟可达时取估算时刻，不可达时取模拟截止时刻（卷取后 max_days）
+///
+/// 适温状态、预计适温日期与待温预测均按此规则判定，避免不可达钢卷在各处结论不一致。
+pub fn effective_ready_time(
+    coiling_time: &DateTime<Utc>,
+    estimated: Option<DateTime<Utc>>,
+    params: &CoolingParams,
+) -> DateTime<Utc> {
+    estimated.unwrap_or_else(|| *coiling_time + Duration::days(params.max_days.max(1)))
+}
+
+/// 计算指定时刻的卷芯温度（℃）
+pub fn core_temperature_at<F>(
+    coiling_time: &DateTime<Utc>,
+    at: &DateTime<Utc>,
+    coil: &CoilThermalInput,
+    params: &CoolingParams,
+    ambient_at: F,
+) -> f64
+where
+    F: Fn(NaiveDate) -> f64,
+{
+    let tau_hours = cooling_time_constant_hours(coil, params.heat_transfer_coeff);
+    let mut temp = coil.coiling_temp;
+    let mut cursor = *coiling_time;
+    while cursor < *at {
+        let step_end = (cursor + Duration::minutes(COOLING_STEP_MINUTES)).min(*at);
+        let hours = (step_end - cursor).num_seconds() as f64 / 3600.0;
+        let ambient = ambient_at(cursor.date_naive());
+        temp = ambient + (temp - ambient) * (-hours / tau_hours).exp();
+        cursor = step_end;
+    }
+    temp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coil(thickness: f64) -> CoilThermalInput {
+        CoilThermalInput {
+            coiling_temp: 600.0,
+            weight: 20.0,
+            thickness,
+            width: 1250.0,
+        }
+    }
+
+    #[test]
+    fn test_thin_coil_cools_slower_than_thick() {
+        let params = CoolingParams::default();
+        let start = Utc::now();
+        let thin = estimate_ready_time(&start, &coil(2.0), &params, |_| 20.0).unwrap();
+        let thick = estimate_ready_time(&start, &coil(12.0), &params, |_| 20.0).unwrap();
+        assert!(thin > thick);
+        // 常规规格应在数天内适温
+        assert!(thin - start < Duration::days(6));
+        assert!(thick - start > Duration::days(1));
+    }
+
+    #[test]
+    fn test_warm_ambient_delays_readiness() {
+        let params = CoolingParams::default();
+        let start = Utc::now();
+        let winter = estimate_ready_time(&start, &coil(4.0), &params, |_| 0.0).unwrap();
+        let summer = estimate_ready_time(&start, &coil(4.0), &params, |_| 30.0).unwrap();
+        assert!(summer > winter);
+    }
+
+    #[test]
+    fn test_unreachable_target_returns_none() {
+        let params = CoolingParams {
+            target_temp: 25.0,
+            ..CoolingParams::default()
+        };
+        let start = Utc::now();
+        assert!(estimate_ready_time(&start, &coil(4.0), &params, |_| 30.0).is_none());
+    }
+
+    #[test]
+    fn test_unreachable_target_is_released_at_simulation_end() {
+        let params = CoolingParams {
+            target_temp: 25.0,
+            max_days: 10,
+            ..CoolingParams::default()
+        };
+        let start = Utc::now();
+        let estimated = estimate_ready_time(&start, &coil(4.0), &params, |_| 30.0);
+        assert_eq!(
+            effective_ready_time(&start, estimated, &params),
+            start + Duration::days(10)
+        );
+        let reachable =
+            estimate_ready_time(&start, &coil(4.0), &CoolingParams::default(), |_| 20.0);
+        assert_eq!(
+            effective_ready_time(&start, reachable, &params),
+            reachable.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_core_temperature_matches_ready_time() {
+        let params = CoolingParams::default();
+        let start = Utc::now();
+        let c = coil(4.0);
+        let ready = estimate_ready_time(&start, &c, &params, |_| 20.0).unwrap();
+        let before =
+            core_temperature_at(&start, &(ready - Duration::hours(2)), &c, &params, |_| 20.0);
+        let after =
+            core_temperature_at(&start, &(ready + Duration::hours(2)), &c, &params, |_| 20.0);
+        assert!(before > params.target_temp);
+        assert!(after < params.target_temp);
+    }
+}
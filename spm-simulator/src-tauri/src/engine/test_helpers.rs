@@ -46,6 +46,9 @@ pub mod helpers {
             created_at: None,
             updated_at: None,
             import_batch_id: None,
+            coiling_temp: None,
+            reserved_plan_id: None,
+            status_reason: None,
         }
     }
 
@@ -13,6 +13,7 @@
 //!   2. thickness_jump — 厚度跳跃惩罚 -5
 //!   3. surface_after_roll_change — 换辊后高表面奖励 +20
 //!   4. contract_grouping — 合同集中奖励 +10
+//!   5. yard_retrieval — 跨库区/倒垛惩罚（需库区占用信息，在 yard 模块中处理）
 
 use crate::engine::sorter::SortedMaterial;
 use serde::{Deserialize, Serialize};
@@ -71,6 +72,9 @@ pub struct SoftConstraint {
     pub target_levels: Option<Vec<String>>,
     #[serde(default)]
     pub within_coils: Option<i32>,
+    /// 每倒垛一卷的惩罚分（yard_retrieval 使用）
+    #[serde(default)]
+    pub dig_penalty: Option<i32>,
 }
 
 // ─── 校验结果 ───
@@ -121,8 +125,11 @@ pub fn validate_hard_constraints(
             "overdue_priority" => {
                 check_overdue_priority(sequence, &mut violations);
             }
-            // roll_change_tonnage 和 roll_change_duration 在换辊模块中处理
-            _ => {}
+            // 换辊吨位/时长在换辊模块处理，班次产能在排程器中处理
+            "roll_change_tonnage" | "roll_change_duration" | "shift_capacity" => {}
+            other => {
+                log::warn!("未知硬约束类型 {}，已忽略", other);
+            }
         }
     }
 
@@ -200,7 +207,11 @@ pub fn evaluate_soft_constraints(
                     description: format!("合同集中{}组, 每组+{}分", count, bonus),
                 });
             }
-            _ => {}
+            // yard_retrieval 需库区占用信息，在 yard 模块中处理
+            "yard_retrieval" => {}
+            other => {
+                log::warn!("未知软约束类型 {}，已忽略", other);
+            }
         }
     }
 
@@ -408,6 +419,9 @@ mod tests {
             created_at: None,
             updated_at: None,
             import_batch_id: None,
+            coiling_temp: None,
+            reserved_plan_id: None,
+            status_reason: None,
         }
     }
 
@@ -527,6 +541,7 @@ mod tests {
             unit: None,
             target_levels: None,
             within_coils: None,
+            dig_penalty: None,
         }
     }
 
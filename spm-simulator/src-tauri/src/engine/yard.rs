@@ -0,0 +1,374 @@
+//! 库区模型 — 库位解析、压垛关系与吊运代价
+//!
+//! 库位编码 `storage_loc` 形如 "A03-12-2"：库区(bay)-排(row)-层(level)，
+//! 层号自下而上从 1 开始。同一库区同一排视为一个垛位，层号更高的钢卷压在下层钢卷之上，
+//! 取下层卷前需先把上层卷倒垛移开。
+//!
+//! 吊运代价（分钟）= 吊取 + 倒垛 × 压卷数 + 跨库区行车移动 + 同库区跨排移动
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::engine::constants::{
+    DEFAULT_YARD_BAY_TRAVEL_MINUTES, DEFAULT_YARD_PICK_MINUTES, DEFAULT_YARD_RELOCATE_MINUTES,
+    DEFAULT_YARD_ROW_TRAVEL_MINUTES,
+};
+use crate::engine::sorter::SortedMaterial;
+use crate::engine::validator::{SoftConstraint, SoftConstraintsConfig, SoftScoreDetail};
+use crate::models::material;
+
+/// 解析后的库位
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct YardLocation {
+    pub bay: String,
+    pub row: i32,
+    pub level: i32,
+}
+
+/// 吊运代价参数（system_config 分组 "yard"）
+#[derive(Debug, Clone)]
+pub struct YardCostConfig {
+    /// 单次吊取耗时（分钟）
+    pub pick_minutes: f64,
+    /// 每倒垛一卷耗时（分钟）
+    pub relocate_minutes: f64,
+    /// 跨库区行车移动耗时（分钟）
+    pub bay_travel_minutes: f64,
+    /// 同库区每跨一排移动耗时（分钟）
+    pub row_travel_minutes: f64,
+}
+
+impl Default for YardCostConfig {
+    fn default() -> Self {
+        Self {
+            pick_minutes: DEFAULT_YARD_PICK_MINUTES,
+            relocate_minutes: DEFAULT_YARD_RELOCATE_MINUTES,
+            bay_travel_minutes: DEFAULT_YARD_BAY_TRAVEL_MINUTES,
+            row_travel_minutes: DEFAULT_YARD_ROW_TRAVEL_MINUTES,
+        }
+    }
+}
+
+impl YardCostConfig {
+    /// 从 system_config "yard" 分组解析
+    pub fn from_config_group(group: Option<&HashMap<String, String>>) -> Self {
+        let mut cfg = Self::default();
+        let read = |key: &str, default: f64| -> f64 {
+            group
+                .and_then(|g| g.get(key))
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default)
+        };
+        cfg.pick_minutes = read("pick_minutes", cfg.pick_minutes);
+        cfg.relocate_minutes = read("relocate_minutes", cfg.relocate_minutes);
+        cfg.bay_travel_minutes = read("bay_travel_minutes", cfg.bay_travel_minutes);
+        cfg.row_travel_minutes = read("row_travel_minutes", cfg.row_travel_minutes);
+        cfg
+    }
+}
+
+/// 单卷吊运明细
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoilRetrieval {
+    pub sequence_index: usize,
+    pub material_id: i32,
+    pub coil_id: String,
+    pub storage_loc: Option<String>,
+    pub location: Option<YardLocation>,
+    /// 需先倒垛移开的压卷
+    pub blockers: Vec<String>,
+    pub bay_switch: bool,
+    pub cost_minutes: f64,
+}
+
+/// 序列吊运代价汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct YardRetrievalReport {
+    pub total_cost_minutes: f64,
+    pub bay_switch_count: usize,
+    pub dig_count: usize,
+    pub unknown_location_count: usize,
+    pub items: Vec<CoilRetrieval>,
+}
+
+/// 解析库位编码："A03-12-2" / "A03_12_2" / "A03 12" / "S3"
+pub fn parse_storage_loc(raw: &str) -> Option<YardLocation> {
+    let parts: Vec<&str> = raw
+        .split(['-', '_', '/', ' ', '.'])
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let bay = parts.first()?.to_uppercase();
+    let row = match parts.get(1) {
+        Some(v) => v.parse::<i32>().ok()?,
+        None => 0,
+    };
+    let level = match parts.get(2) {
+        Some(v) => v.parse::<i32>().ok()?.max(1),
+        None => 1,
+    };
+    Some(YardLocation { bay, row, level })
+}
+
+/// 库区占用状态：按垛位记录在库钢卷，随吊运逐步移除
+#[derive(Debug, Clone, Default)]
+pub struct YardState {
+    locations: HashMap<i32, YardLocation>,
+    coil_ids: HashMap<i32, String>,
+    /// (bay, row) → [(level, material_id)]
+    stacks: HashMap<(String, i32), Vec<(i32, i32)>>,
+    /// 已倒垛移开的钢卷（视为已在垛顶，无压卷）
+    relocated: HashSet<i32>,
+}
+
+impl YardState {
+    /// 以在库材料构建库区状态（无法解析库位的材料忽略）
+    pub fn from_materials<'a, I>(materials: I) -> Self
+    where
+        I: IntoIterator<Item = &'a material::Model>,
+    {
+        let mut state = Self::default();
+        for m in materials {
+            let Some(loc) = m.storage_loc.as_deref().and_then(parse_storage_loc) else {
+                continue;
+            };
+            state
+                .stacks
+                .entry((loc.bay.clone(), loc.row))
+                .or_default()
+                .push((loc.level, m.id));
+            state.coil_ids.insert(m.id, m.coil_id.clone());
+            state.locations.insert(m.id, loc);
+        }
+        state
+    }
+
+    pub fn location(&self, material_id: i32) -> Option<&YardLocation> {
+        self.locations.get(&material_id)
+    }
+
+    /// 当前压在指定钢卷之上的钢卷 id
+    pub fn blockers(&self, material_id: i32) -> Vec<i32> {
+        if self.relocated.contains(&material_id) {
+            return vec![];
+        }
+        let Some(loc) = self.locations.get(&material_id) else {
+            return vec![];
+        };
+        self.stacks
+            .get(&(loc.bay.clone(), loc.row))
+            .map(|stack| {
+                stack
+                    .iter()
+                    .filter(|(level, id)| *level > loc.level && *id != material_id)
+                    .map(|(_, id)| *id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 吊取指定钢卷：先倒垛移开压卷，再将其移出库区；返回被倒垛的钢卷 id
+    pub fn retrieve(&mut self, material_id: i32) -> Vec<i32> {
+        let blockers = self.blockers(material_id);
+        if let Some(loc) = self.locations.get(&material_id) {
+            if let Some(stack) = self.stacks.get_mut(&(loc.bay.clone(), loc.row)) {
+                stack.retain(|(_, id)| *id != material_id && !blockers.contains(id));
+            }
+        }
+        for id in &blockers {
+            self.relocated.insert(*id);
+        }
+        self.relocated.remove(&material_id);
+        blockers
+    }
+
+    fn coil_id(&self, material_id: i32) -> String {
+        self.coil_ids
+            .get(&material_id)
+            .cloned()
+            .unwrap_or_else(|| material_id.to_string())
+    }
+}
+
+/// 单次吊运代价（分钟）及是否跨库区
+pub fn move_cost(
+    prev: Option<&YardLocation>,
+    current: Option<&YardLocation>,
+    blockers: usize,
+    config: &YardCostConfig,
+) -> (f64, bool) {
+    let mut cost = config.pick_minutes + config.relocate_minutes * blockers as f64;
+    let mut bay_switch = false;
+    if let (Some(p), Some(c)) = (prev, current) {
+        if p.bay != c.bay {
+            bay_switch = true;
+            cost += config.bay_travel_minutes;
+        } else {
+            cost += config.row_travel_minutes * (p.row - c.row).abs() as f64;
+        }
+    }
+    (cost, bay_switch)
+}
+
+/// 按上线顺序模拟吊运，计算整体吊运代价
+///
+/// `yard` 为当前在库的全部材料（含未排入序列的压卷），`sequence` 为上线顺序。
+pub fn calculate_retrieval_cost(
+    sequence: &[&material::Model],
+    yard: &[material::Model],
+    config: &YardCostConfig,
+) -> YardRetrievalReport {
+    let mut state = YardState::from_materials(yard.iter().chain(sequence.iter().copied()));
+    let mut report = YardRetrievalReport::default();
+    let mut prev_loc: Option<YardLocation> = None;
+
+    for (idx, m) in sequence.iter().enumerate() {
+        let loc = state.location(m.id).cloned();
+        if loc.is_none() {
+            report.unknown_location_count += 1;
+        }
+        let blockers = state.retrieve(m.id);
+        let (cost, bay_switch) = move_cost(prev_loc.as_ref(), loc.as_ref(), blockers.len(), config);
+
+        report.total_cost_minutes += cost;
+        report.dig_count += blockers.len();
+        if bay_switch {
+            report.bay_switch_count += 1;
+        }
+        report.items.push(CoilRetrieval {
+            sequence_index: idx,
+            material_id: m.id,
+            coil_id: m.coil_id.clone(),
+            storage_loc: m.storage_loc.clone(),
+            location: loc.clone(),
+            blockers: blockers.iter().map(|id| state.coil_id(*id)).collect(),
+            bay_switch,
+            cost_minutes: cost,
+        });
+        if loc.is_some() {
+            prev_loc = loc;
+        }
+    }
+
+    report.total_cost_minutes = (report.total_cost_minutes * 10.0).round() / 10.0;
+    report
+}
+
+/// 软约束 yard_retrieval：跨库区每次扣 penalty 分，每倒垛一卷扣 dig_penalty 分
+pub fn evaluate_yard_soft_constraint(
+    report: &YardRetrievalReport,
+    constraint: &SoftConstraint,
+) -> SoftScoreDetail {
+    let penalty = constraint.penalty.unwrap_or(5);
+    let dig_penalty = constraint.dig_penalty.unwrap_or(3);
+    let adjust =
+        -(penalty * report.bay_switch_count as i32 + dig_penalty * report.dig_count as i32);
+    SoftScoreDetail {
+        constraint_type: "yard_retrieval".into(),
+        adjust,
+        count: report.bay_switch_count + report.dig_count,
+        description: format!(
+            "跨库区{}次(每次-{}分), 倒垛{}卷(每卷-{}分), 预计吊运{:.0}分钟",
+            report.bay_switch_count,
+            penalty,
+            report.dig_count,
+            dig_penalty,
+            report.total_cost_minutes
+        ),
+    }
+}
+
+/// 对排程序列计算 yard_retrieval 软约束评分；策略未启用该约束时返回 None
+pub fn evaluate_sequence_yard_cost(
+    sequence: &[SortedMaterial],
+    yard: &[material::Model],
+    soft_config: &SoftConstraintsConfig,
+    cost_config: &YardCostConfig,
+) -> Option<SoftScoreDetail> {
+    let constraint = find_yard_constraint(&soft_config.constraints)?;
+    let seq: Vec<&material::Model> = sequence.iter().map(|s| &s.material).collect();
+    let report = calculate_retrieval_cost(&seq, yard, cost_config);
+    Some(evaluate_yard_soft_constraint(&report, constraint))
+}
+
+/// 取策略中启用的 yard_retrieval 软约束
+pub fn find_yard_constraint(constraints: &[SoftConstraint]) -> Option<&SoftConstraint> {
+    constraints
+        .iter()
+        .find(|c| c.enabled && c.constraint_type == "yard_retrieval")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_helpers::helpers::make_material;
+
+    fn located(id: i32, loc: &str) -> material::Model {
+        let mut m = make_material(id, &format!("C{:03}", id), 1000.0, 20.0);
+        m.storage_loc = Some(loc.to_string());
+        m
+    }
+
+    #[test]
+    fn test_parse_storage_loc_formats() {
+        assert_eq!(
+            parse_storage_loc("a03-12-2"),
+            Some(YardLocation {
+                bay: "A03".into(),
+                row: 12,
+                level: 2
+            })
+        );
+        assert_eq!(
+            parse_storage_loc("S3").map(|l| (l.row, l.level)),
+            Some((0, 1))
+        );
+        assert_eq!(parse_storage_loc("A03-x-1"), None);
+        assert_eq!(parse_storage_loc("  "), None);
+    }
+
+    #[test]
+    fn test_retrieval_counts_buried_coils_outside_sequence() {
+        let bottom = located(1, "A01-01-1");
+        let top = located(2, "A01-01-2");
+        let yard = vec![bottom.clone(), top.clone()];
+        let config = YardCostConfig::default();
+
+        let report = calculate_retrieval_cost(&[&bottom], &yard, &config);
+        assert_eq!(report.dig_count, 1);
+        assert_eq!(report.items[0].blockers, vec!["C002".to_string()]);
+
+        // 先取上层卷再取下层卷，无需倒垛
+        let report = calculate_retrieval_cost(&[&top, &bottom], &yard, &config);
+        assert_eq!(report.dig_count, 0);
+    }
+
+    #[test]
+    fn test_retrieval_counts_bay_switches() {
+        let a1 = located(1, "A01-01-1");
+        let b1 = located(2, "B01-01-1");
+        let a2 = located(3, "A01-03-1");
+        let yard = vec![a1.clone(), b1.clone(), a2.clone()];
+        let config = YardCostConfig::default();
+
+        let hopping = calculate_retrieval_cost(&[&a1, &b1, &a2], &yard, &config);
+        let grouped = calculate_retrieval_cost(&[&a1, &a2, &b1], &yard, &config);
+        assert_eq!(hopping.bay_switch_count, 2);
+        assert_eq!(grouped.bay_switch_count, 1);
+        assert!(grouped.total_cost_minutes < hopping.total_cost_minutes);
+    }
+
+    #[test]
+    fn test_relocated_coil_has_no_blockers() {
+        let bottom = located(1, "A01-01-1");
+        let middle = located(2, "A01-01-2");
+        let top = located(3, "A01-01-3");
+        let mut state = YardState::from_materials([&bottom, &middle, &top]);
+        assert_eq!(state.blockers(1).len(), 2);
+        let moved = state.retrieve(1);
+        assert_eq!(moved.len(), 2);
+        assert!(state.blockers(2).is_empty());
+        assert!(state.blockers(3).is_empty());
+    }
+}
@@ -36,19 +36,27 @@ pub fn run() {
                     log::warn!("启动自动备份失败: {}", err);
                 }
             });
+            services::handoff_service::spawn_inbox_watcher();
+            services::import_watch_service::spawn_import_watcher();
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::material::import_materials,
             commands::material::test_import_materials,
+            commands::material::preview_material_import,
+            commands::material::export_import_error_workbook,
             commands::material::get_materials,
             commands::material::update_material_status,
+            commands::material::report_production_feedback,
             commands::material::update_material_priority,
+            commands::material::get_material_history,
+            commands::material::restore_material_fields,
             commands::material::refresh_temper_status,
             commands::material::delete_materials,
             commands::material::get_import_batches,
             commands::material::delete_import_batch,
             commands::material::replace_all_materials,
+            commands::material::run_material_import_watch,
             commands::schedule::create_plan,
             commands::schedule::get_plan,
             commands::schedule::get_plans,
@@ -62,11 +70,14 @@ pub fn run() {
             commands::schedule::lock_schedule_items,
             commands::schedule::get_schedule_items,
             commands::schedule::update_plan_status,
-            commands::schedule::push_undo,
+            commands::schedule::get_plan_approvals,
             commands::schedule::undo_action,
             commands::schedule::redo_action,
             commands::schedule::get_undo_redo_count,
             commands::schedule::clear_undo_stack,
+            commands::schedule::get_undo_history,
+            commands::schedule::preview_undo_entry,
+            commands::schedule::jump_to_undo_entry,
             commands::schedule::get_risk_analysis,
             commands::schedule::evaluate_risks,
             commands::schedule::apply_risk_suggestion,
@@ -80,6 +91,15 @@ pub fn run() {
             commands::schedule::compare_plans_multi,
             commands::schedule::get_plan_versions,
             commands::schedule::rollback_plan_version,
+            commands::schedule::fork_plan_from_undo_entry,
+            commands::schedule::diff_plan_versions,
+            commands::schedule::preview_plan_merge,
+            commands::schedule::merge_plan_versions,
+            commands::schedule::export_plan_json,
+            commands::schedule::import_plan_json,
+            commands::schedule::get_plan_dispatches,
+            commands::schedule::dispatch_plan_to_outbox,
+            commands::schedule::poll_plan_inbox,
             commands::schedule::get_operation_logs,
             commands::schedule::get_operation_log_estimate,
             commands::schedule::export_logs,
@@ -89,12 +109,24 @@ pub fn run() {
             commands::schedule::clear_logs,
             commands::schedule::clean_history_plans,
             commands::schedule::clean_materials,
+            commands::schedule::get_plan_yard_cost,
+            commands::schedule::reoptimize_roll_changes,
+            commands::schedule::simulate_plan_robustness,
+            commands::schedule::create_simulation_session,
+            commands::schedule::get_simulation_sessions,
+            commands::schedule::inject_simulation_event,
+            commands::schedule::step_simulation,
+            commands::schedule::replay_simulation_session,
+            commands::schedule::delete_simulation_session,
             commands::config::get_system_config,
             commands::config::update_system_config,
             commands::config::get_shift_config,
             commands::config::update_shift_config,
+            commands::config::get_ambient_temperatures,
+            commands::config::import_ambient_temperature_csv,
             commands::config::get_priority_weight_configs,
             commands::config::upsert_priority_weight_configs,
+            commands::config::preview_priority_weights,
             commands::config::get_priority_dimension_configs,
             commands::config::upsert_priority_dimension_config,
             commands::config::delete_priority_dimension_config,
@@ -119,6 +151,13 @@ pub fn run() {
             commands::config::set_default_strategy,
             commands::config::export_strategy_template,
             commands::config::import_strategy_template,
+            commands::config::get_strategy_revisions,
+            commands::config::diff_strategy_revisions,
+            commands::config::export_strategy_revision_diff,
+            commands::config::restore_strategy_revision,
+            commands::config::validate_strategy_config,
+            commands::config::export_config_bundle,
+            commands::config::import_config_bundle,
             commands::config::get_maintenance_plans,
             commands::config::create_maintenance_plan,
             commands::config::update_maintenance_plan,
@@ -129,8 +168,12 @@ pub fn run() {
             commands::field_mapping::update_field_mapping,
             commands::field_mapping::delete_field_mapping,
             commands::field_mapping::preview_file_headers,
+            commands::field_mapping::suggest_field_mappings,
+            commands::field_mapping::create_field_mapping_from_suggestions,
             commands::export::export_plan_excel,
             commands::export::export_plan_csv,
+            commands::export::export_crane_pick_list,
+            commands::export::export_plan_html_report,
             commands::export::export_materials_excel,
             commands::export::get_material_stats,
             commands::export::get_export_templates,
@@ -9,6 +9,12 @@ INSERT OR IGNORE INTO system_config (config_group, config_key, config_value, val
 ('temp', 'summer_days', '4', 'number', '夏季适温天数'),
 ('temp', 'autumn_days', '4', 'number', '秋季适温天数'),
 ('temp', 'winter_days', '3', 'number', '冬季适温天数'),
+('temp', 'model', 'days', 'string', '适温模型: days(按季节天数)/cooling(冷却模型)'),
+('temp', 'target_temp', '50', 'number', '冷却模型适温目标温度(℃)'),
+('temp', 'default_coiling_temp', '600', 'number', '缺省卷取温度(℃)'),
+('temp', 'heat_transfer_coeff', '10', 'number', '钢卷表面综合换热系数(W/m²·K)'),
+('temp', 'max_cooling_days', '15', 'number', '冷却模型最长模拟天数'),
+('temp', 'ambient_monthly', '0,3,9,16,22,26,29,28,23,16,8,2', 'string', '1~12月平均环境温度(℃)'),
 ('capacity', 'shift_capacity', '1200', 'number', '单班产能上限(吨)'),
 ('capacity', 'daily_target', '2400', 'number', '日产能目标(吨)'),
 ('capacity', 'avg_rhythm', '3.5', 'number', '平均轧制节奏(分钟/卷)'),
@@ -23,12 +29,17 @@ INSERT OR IGNORE INTO system_config (config_group, config_key, config_value, val
 ('scheduler', 'time_budget_ms', '120000', 'number', 'Beam求解时间预算(ms)'),
 ('scheduler', 'max_nodes', '200000', 'number', 'Beam最大扩展节点数'),
 ('scheduler', 'fallback_enabled', 'true', 'boolean', 'Beam超限后启用贪心兜底'),
+('scheduler', 'roll_change_mode', 'greedy', 'string', '换辊点决策: greedy(逐卷判断)/optimal(动态规划重排)'),
 ('warning', 'capacity_yellow', '85', 'number', '产能利用率黄灯(%)'),
 ('warning', 'capacity_red', '70', 'number', '产能利用率红灯(%)'),
 ('warning', 'due_warn_days', '3', 'number', '交期预警天数'),
 ('warning', 'storage_warn_days', '7', 'number', '库龄预警天数'),
 ('warning', 'storage_critical_days', '14', 'number', '库龄严重预警天数'),
-('undo', 'max_steps', '50', 'number', '最大撤销步数');
+('undo', 'max_steps', '50', 'number', '最大撤销步数'),
+('yard', 'pick_minutes', '2', 'number', '单卷吊取耗时(分钟)'),
+('yard', 'relocate_minutes', '3', 'number', '每倒垛一卷耗时(分钟)'),
+('yard', 'bay_travel_minutes', '4', 'number', '跨库区行车移动耗时(分钟)'),
+('yard', 'row_travel_minutes', '0.5', 'number', '同库区每跨一排移动耗时(分钟)');
 
 -- 清理已归并到策略配置的历史系统参数（幂等）
 DELETE FROM system_config
@@ -42,6 +53,25 @@ INSERT OR IGNORE INTO system_config (config_group, config_key, config_value, val
 ('backup', 'path', '', 'string', '备份目录路径（留空使用默认路径）'),
 ('backup', 'keep_days', '30', 'number', '备份保留天数（<=0 表示不自动清理）');
 
+-- MES 下发/回执配置
+INSERT OR IGNORE INTO system_config (config_group, config_key, config_value, value_type, description) VALUES
+('handoff', 'outbox_enabled', 'false', 'boolean', '方案确认后是否自动写入下发目录'),
+('handoff', 'outbox_dir', '', 'string', '下发目录（outbox）'),
+('handoff', 'outbox_format', 'csv', 'string', '下发格式: csv/json/xml'),
+('handoff', 'csv_template_id', '', 'number', 'CSV 下发使用的导出模板ID（留空使用默认列）'),
+('handoff', 'inbox_dir', '', 'string', '回执目录（inbox，留空不轮询）'),
+('handoff', 'inbox_poll_seconds', '30', 'number', '回执目录轮询间隔(秒)');
+
+-- 材料目录监控自动导入配置
+INSERT OR IGNORE INTO system_config (config_group, config_key, config_value, value_type, description) VALUES
+('import_watch', 'enabled', 'false', 'boolean', '是否启用目录监控自动导入'),
+('import_watch', 'watch_dir', '', 'string', '监控目录（成功文件归档到 archive/，失败文件隔离到 failed/）'),
+('import_watch', 'mapping_id', '', 'number', '绑定的字段映射模板ID（留空使用默认映射）'),
+('import_watch', 'conflict_mode', 'skip', 'string', '冲突模式: skip/overwrite/merge'),
+('import_watch', 'merge_policy', '', 'json', 'merge 模式字段策略，如 {"default":"source","fields":{"remarks":"keep_local"}}'),
+('import_watch', 'all_or_nothing', 'false', 'boolean', '整批导入（任一行失败则整体回滚）'),
+('import_watch', 'poll_seconds', '60', 'number', '目录轮询间隔(秒)');
+
 -- 默认优先级权重配置
 INSERT OR IGNORE INTO priority_weight_config (dimension_type, dimension_name, weight, sort_order, description) VALUES
 ('assessment', '合同考核', 1.0, 1, '考核/非考核'),
@@ -345,3 +345,154 @@ ALTER TABLE schedule_plan ADD COLUMN ignored_risks TEXT;
 
 -- 数据迁移：清理遗留的 scheduled 状态（scheduled 不再是合法的材料全局状态）
 UPDATE material SET status = 'pending' WHERE status = 'scheduled';
+
+-- material 表增量迁移：添加 coiling_temp 列（卷取温度 ℃，冷却模型使用）
+ALTER TABLE material ADD COLUMN coiling_temp REAL;
+
+-- 环境日气温表（冷却模型使用，缺失日期回落到月均温度）
+CREATE TABLE IF NOT EXISTS ambient_temperature (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    record_date TEXT NOT NULL UNIQUE,
+    avg_temp    REAL NOT NULL,
+    source      TEXT,
+    created_at  DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 执行模拟会话表（plan_snapshot 保存创建时的方案快照，保证事件日志可重放）
+CREATE TABLE IF NOT EXISTS simulation_session (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    plan_id         INTEGER NOT NULL,
+    name            TEXT NOT NULL,
+    status          TEXT NOT NULL DEFAULT 'running',
+    base_time       TEXT NOT NULL,
+    plan_snapshot   TEXT NOT NULL,
+    current_minute  REAL NOT NULL DEFAULT 0,
+    score           INTEGER,
+    score_detail    TEXT,
+    created_at      DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at      DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (plan_id) REFERENCES schedule_plan(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_simulation_session_plan ON simulation_session(plan_id);
+
+-- 执行模拟事件日志
+CREATE TABLE IF NOT EXISTS simulation_event (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id      INTEGER NOT NULL,
+    at_minute       REAL NOT NULL,
+    event_type      TEXT NOT NULL,
+    payload         TEXT NOT NULL,
+    created_at      DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (session_id) REFERENCES simulation_session(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_simulation_event_session ON simulation_event(session_id, at_minute);
+
+-- 方案下发记录（确认后写入 outbox，inbox 回执回写 status：sent/acknowledged/rejected/failed）
+CREATE TABLE IF NOT EXISTS plan_dispatch (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    plan_id          INTEGER NOT NULL,
+    plan_no          TEXT NOT NULL,
+    format           TEXT NOT NULL,
+    file_name        TEXT NOT NULL,
+    checksum         TEXT,
+    status           TEXT NOT NULL DEFAULT 'sent',
+    error_message    TEXT,
+    response_file    TEXT,
+    response_message TEXT,
+    responded_at     DATETIME,
+    created_at       DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (plan_id) REFERENCES schedule_plan(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_plan_dispatch_plan ON plan_dispatch(plan_id);
+CREATE INDEX IF NOT EXISTS idx_plan_dispatch_file ON plan_dispatch(file_name);
+
+-- 映射模板导入校验规则（JSON 数组：range/allowed_values/pattern/required_if/compare）
+ALTER TABLE field_mapping ADD COLUMN validation_rules TEXT;
+
+-- 映射模板源文件读取配置（工作表、表头行、数据起始行、CSV 分隔符与编码）
+ALTER TABLE field_mapping ADD COLUMN sheet TEXT;
+ALTER TABLE field_mapping ADD COLUMN header_row INTEGER;
+ALTER TABLE field_mapping ADD COLUMN data_start_row INTEGER;
+ALTER TABLE field_mapping ADD COLUMN csv_delimiter TEXT;
+ALTER TABLE field_mapping ADD COLUMN csv_encoding TEXT;
+
+-- 材料字段变更历史（导入覆盖、人工调整、优先级重算、字段恢复）
+CREATE TABLE IF NOT EXISTS material_change_log (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    material_id INTEGER NOT NULL,
+    coil_id     TEXT NOT NULL,
+    field_name  TEXT NOT NULL,
+    old_value   TEXT,
+    new_value   TEXT,
+    source      TEXT NOT NULL,
+    source_ref  TEXT,
+    changed_at  DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_material_change_material ON material_change_log(material_id, changed_at);
+CREATE INDEX IF NOT EXISTS idx_material_change_coil ON material_change_log(coil_id);
+
+-- 材料生命周期：锁定方案与状态原因（冻结/解冻/中止说明）
+ALTER TABLE material ADD COLUMN reserved_plan_id INTEGER;
+ALTER TABLE material ADD COLUMN status_reason TEXT;
+CREATE INDEX IF NOT EXISTS idx_material_reserved_plan ON material(reserved_plan_id);
+
+-- 方案审批/状态流转记录（提交、审批通过、驳回、确认、执行、归档）
+CREATE TABLE IF NOT EXISTS plan_approval (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    plan_id     INTEGER NOT NULL,
+    from_status TEXT,
+    to_status   TEXT NOT NULL,
+    approver    TEXT,
+    comment     TEXT,
+    created_at  DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (plan_id) REFERENCES schedule_plan(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_plan_approval_plan ON plan_approval(plan_id, created_at);
+
+-- 撤销栈：服务端记录差异操作
+ALTER TABLE undo_stack ADD COLUMN description TEXT;
+-- 清理旧版前端推送的整表快照（格式不兼容）
+DELETE FROM undo_stack WHERE description IS NULL;
+
+-- 策略模板修订：每次修改生成不可变快照，方案记录所用修订
+CREATE TABLE IF NOT EXISTS strategy_revision (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    strategy_id      INTEGER NOT NULL,
+    revision_no      INTEGER NOT NULL,
+    name             TEXT NOT NULL,
+    description      TEXT,
+    sort_weights     TEXT NOT NULL,
+    constraints      TEXT NOT NULL,
+    soft_constraints TEXT,
+    eval_weights     TEXT NOT NULL,
+    temper_rules     TEXT NOT NULL,
+    change_note      TEXT,
+    created_at       DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(strategy_id, revision_no),
+    FOREIGN KEY (strategy_id) REFERENCES strategy_template(id) ON DELETE CASCADE
+);
+ALTER TABLE schedule_plan ADD COLUMN strategy_revision_id INTEGER;
+
+-- 数据迁移：旧版方案状态映射到审批流程状态（空值视为草稿，completed 视为已归档）
+UPDATE schedule_plan SET status = 'draft' WHERE status IS NULL OR TRIM(status) = '';
+UPDATE schedule_plan SET status = 'archived' WHERE status = 'completed';
+
+-- 数据迁移：为已确认/执行中方案补记材料锁定（仅处理未锁定的待排材料，满足不变量后不再命中任何行）；
+-- 同一材料出现在多个生效方案中时归属 id 最大的方案
+UPDATE material SET status = 'planned', reserved_plan_id = (
+    SELECT MAX(si.plan_id) FROM schedule_item si
+    JOIN schedule_plan sp ON sp.id = si.plan_id
+    WHERE si.material_id = material.id AND sp.status IN ('confirmed', 'executing')
+)
+WHERE (status IS NULL OR status = 'pending')
+  AND reserved_plan_id IS NULL
+  AND id IN (
+    SELECT si.material_id FROM schedule_item si
+    JOIN schedule_plan sp ON sp.id = si.plan_id
+    WHERE sp.status IN ('confirmed', 'executing')
+  );
@@ -11,6 +11,17 @@ pub struct Model {
     pub source_type: String,
     pub mappings: String,
     pub value_transforms: Option<String>,
+    pub validation_rules: Option<String>,
+    /// 工作表名称或序号（1 起始）
+    pub sheet: Option<String>,
+    /// 表头行（1 起始）
+    pub header_row: Option<i32>,
+    /// 数据起始行（1 起始）
+    pub data_start_row: Option<i32>,
+    /// CSV 分隔符（空/auto 自动识别）
+    pub csv_delimiter: Option<String>,
+    /// CSV 编码（空/auto 自动识别）
+    pub csv_encoding: Option<String>,
     pub created_at: Option<ChronoDateTimeUtc>,
     pub updated_at: Option<ChronoDateTimeUtc>,
 }
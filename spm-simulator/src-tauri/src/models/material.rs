@@ -43,6 +43,11 @@ pub struct Model {
     pub created_at: Option<ChronoDateTimeUtc>,
     pub updated_at: Option<ChronoDateTimeUtc>,
     pub import_batch_id: Option<i32>,
+    pub coiling_temp: Option<f64>,
+    /// 锁定该材料的已确认方案
+    pub reserved_plan_id: Option<i32>,
+    /// 最近一次状态变更原因（冻结、解冻、生产中止）
+    pub status_reason: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
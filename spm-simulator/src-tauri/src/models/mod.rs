@@ -1,3 +1,4 @@
+pub mod ambient_temperature;
 pub mod batch_priority_config;
 pub mod customer_priority_config;
 pub mod error_log;
@@ -6,13 +7,19 @@ pub mod field_mapping;
 pub mod import_batch;
 pub mod maintenance_plan;
 pub mod material;
+pub mod material_change_log;
 pub mod operation_log;
 pub mod performance_metric;
+pub mod plan_approval;
+pub mod plan_dispatch;
 pub mod priority_dimension_config;
 pub mod priority_weight_config;
 pub mod product_type_priority_config;
 pub mod schedule_item;
 pub mod schedule_plan;
+pub mod simulation_event;
+pub mod simulation_session;
+pub mod strategy_revision;
 pub mod strategy_template;
 pub mod system_config;
 pub mod undo_stack;
@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "plan_dispatch")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub plan_id: i32,
+    pub plan_no: String,
+    pub format: String,
+    pub file_name: String,
+    pub checksum: Option<String>,
+    pub status: String,
+    pub error_message: Option<String>,
+    pub response_file: Option<String>,
+    pub response_message: Option<String>,
+    pub responded_at: Option<ChronoDateTimeUtc>,
+    pub created_at: Option<ChronoDateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
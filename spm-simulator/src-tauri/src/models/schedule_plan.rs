@@ -31,6 +31,8 @@ pub struct Model {
     pub updated_at: Option<ChronoDateTimeUtc>,
     pub remarks: Option<String>,
     pub ignored_risks: Option<String>,
+    /// 排程所用的策略修订
+    pub strategy_revision_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
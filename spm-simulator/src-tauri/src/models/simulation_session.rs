@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "simulation_session")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub plan_id: i32,
+    pub name: String,
+    pub status: String,
+    pub base_time: String,
+    pub plan_snapshot: String,
+    pub current_minute: f64,
+    pub score: Option<i32>,
+    pub score_detail: Option<String>,
+    pub created_at: Option<ChronoDateTimeUtc>,
+    pub updated_at: Option<ChronoDateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
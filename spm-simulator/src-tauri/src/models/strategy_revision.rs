@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "strategy_revision")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub strategy_id: i32,
+    pub revision_no: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub sort_weights: String,
+    pub constraints: String,
+    pub soft_constraints: Option<String>,
+    pub eval_weights: String,
+    pub temper_rules: String,
+    pub change_note: Option<String>,
+    pub created_at: Option<ChronoDateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
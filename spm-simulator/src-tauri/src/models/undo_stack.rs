@@ -11,6 +11,7 @@ pub struct Model {
     pub before_state: String,
     pub after_state: String,
     pub is_undone: Option<bool>,
+    pub description: Option<String>,
     pub created_at: Option<ChronoDateTimeUtc>,
 }
 
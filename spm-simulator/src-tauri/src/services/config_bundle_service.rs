@@ -0,0 +1,1053 @@
+//! 配置包导出/导入
+//!
+//! 把系统配置、策略模板、优先级各表、字段映射、导出模板、检修计划打包为一个带版本号的 JSON 文件，
+//! 供多个厂区共享基线配置。导入时按各分区的业务键与本地记录比对，先给出新增/修改/删除差异（试运行），
+//! 再按分区选择合并（merge，只增改）或替换（replace，另删除配置包中没有的本地记录）。
+
+use crate::models::{
+    batch_priority_config, customer_priority_config, export_template, field_mapping,
+    maintenance_plan, priority_dimension_config, priority_weight_config,
+    product_type_priority_config, schedule_plan, strategy_template, system_config,
+};
+use crate::services::import_validation_service::CompiledRules;
+use crate::services::{source_reader_service, strategy_revision_service, strategy_schema_service};
+use crate::AppError;
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IdenStatic,
+    IntoActiveModel, Iterable, PrimaryKeyToColumn, QueryFilter, TransactionTrait,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub const BUNDLE_FORMAT: &str = "spm-config-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+pub const MODE_SKIP: &str = "skip";
+pub const MODE_MERGE: &str = "merge";
+pub const MODE_REPLACE: &str = "replace";
+
+pub const ACTION_ADD: &str = "add";
+pub const ACTION_UPDATE: &str = "update";
+pub const ACTION_REMOVE: &str = "remove";
+
+/// 与本机路径相关的系统配置分组，不随配置包迁移
+pub const LOCAL_CONFIG_GROUPS: &[&str] = &["backup", "handoff", "import_watch"];
+
+/// 所有分区都不导出的字段（自增主键与时间戳）
+const COMMON_OMIT_FIELDS: &[&str] = &["id", "created_at", "updated_at"];
+
+/// 配置包分区定义
+#[derive(Debug)]
+pub struct BundleSection {
+    pub name: &'static str,
+    pub label: &'static str,
+    /// 业务键，用于与本地记录对应
+    key_fields: &'static [&'static str],
+    /// 保留本地取值、不随配置包覆盖的字段（如默认标记）
+    local_fields: &'static [&'static str],
+}
+
+pub const SECTIONS: &[BundleSection] = &[
+    BundleSection {
+        name: "system_config",
+        label: "系统配置",
+        key_fields: &["config_group", "config_key"],
+        local_fields: &[],
+    },
+    BundleSection {
+        name: "strategies",
+        label: "策略模板",
+        key_fields: &["name"],
+        local_fields: &["is_default", "is_system"],
+    },
+    BundleSection {
+        name: "priority_weights",
+        label: "优先级维度权重",
+        key_fields: &["dimension_type"],
+        local_fields: &[],
+    },
+    BundleSection {
+        name: "priority_dimensions",
+        label: "优先级维度配置",
+        key_fields: &["dimension_type", "dimension_code"],
+        local_fields: &[],
+    },
+    BundleSection {
+        name: "customer_priorities",
+        label: "客户优先级",
+        key_fields: &["customer_code"],
+        local_fields: &[],
+    },
+    BundleSection {
+        name: "batch_priorities",
+        label: "集批优先级",
+        key_fields: &["batch_code"],
+        local_fields: &[],
+    },
+    BundleSection {
+        name: "product_type_priorities",
+        label: "产品大类优先级",
+        key_fields: &["product_type"],
+        local_fields: &[],
+    },
+    BundleSection {
+        name: "field_mappings",
+        label: "字段映射",
+        key_fields: &["template_name"],
+        local_fields: &["is_default"],
+    },
+    BundleSection {
+        name: "export_templates",
+        label: "导出模板",
+        key_fields: &["name"],
+        local_fields: &["is_default"],
+    },
+    BundleSection {
+        name: "maintenance_plans",
+        label: "检修计划",
+        key_fields: &["title", "start_time"],
+        local_fields: &[],
+    },
+];
+
+/// 按分区名取对应实体类型执行表达式
+macro_rules! with_section_entity {
+    ($section:expr, $entity:ident => $body:expr) => {
+        match $section {
+            "system_config" => {
+                type $entity = system_config::Entity;
+                $body
+            }
+            "strategies" => {
+                type $entity = strategy_template::Entity;
+                $body
+            }
+            "priority_weights" => {
+                type $entity = priority_weight_config::Entity;
+                $body
+            }
+            "priority_dimensions" => {
+                type $entity = priority_dimension_config::Entity;
+                $body
+            }
+            "customer_priorities" => {
+                type $entity = customer_priority_config::Entity;
+                $body
+            }
+            "batch_priorities" => {
+                type $entity = batch_priority_config::Entity;
+                $body
+            }
+            "product_type_priorities" => {
+                type $entity = product_type_priority_config::Entity;
+                $body
+            }
+            "field_mappings" => {
+                type $entity = field_mapping::Entity;
+                $body
+            }
+            "export_templates" => {
+                type $entity = export_template::Entity;
+                $body
+            }
+            "maintenance_plans" => {
+                type $entity = maintenance_plan::Entity;
+                $body
+            }
+            other => unreachable!("未定义的配置包分区 {}", other),
+        }
+    };
+}
+
+/// 配置包文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    /// 分区名 -> 记录（不含 id/时间戳）
+    pub sections: BTreeMap<String, Vec<Map<String, Value>>>,
+}
+
+/// 单条记录差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleRecordChange {
+    /// 业务键，多字段以 / 连接
+    pub key: String,
+    /// add / update / remove
+    pub action: String,
+    /// update 时变化的字段
+    pub fields: Vec<String>,
+}
+
+/// 单个分区差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSectionDiff {
+    pub section: String,
+    pub label: String,
+    /// skip / merge / replace
+    pub mode: String,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    pub changes: Vec<BundleRecordChange>,
+    /// 无法按导入方式处理而保留本地记录的原因（如替换时仍被方案引用的策略模板）
+    pub conflicts: Vec<String>,
+}
+
+/// 配置包导入结果（试运行时为差异预览）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundleReport {
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    pub dry_run: bool,
+    pub applied: bool,
+    pub sections: Vec<BundleSectionDiff>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// 分区导入计划：差异 + 待执行的完整记录 JSON
+#[derive(Debug, Default)]
+struct SectionPlan {
+    inserts: Vec<Value>,
+    updates: Vec<Value>,
+    deletes: Vec<Value>,
+    changes: Vec<BundleRecordChange>,
+    unchanged: usize,
+    conflicts: Vec<String>,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+pub fn find_section(name: &str) -> Option<&'static BundleSection> {
+    SECTIONS.iter().find(|s| s.name == name)
+}
+
+fn column_names<E: EntityTrait>() -> Vec<String> {
+    E::Column::iter().map(|c| c.as_str().to_string()).collect()
+}
+
+fn record_key(section: &BundleSection, record: &Map<String, Value>) -> Option<String> {
+    let mut parts = Vec::with_capacity(section.key_fields.len());
+    for field in section.key_fields {
+        match record.get(*field) {
+            Some(Value::String(s)) if !s.trim().is_empty() => parts.push(s.clone()),
+            Some(Value::Null) | Some(Value::String(_)) | None => return None,
+            Some(other) => parts.push(other.to_string()),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// 数值按大小比较（避免 1 与 1.0 被视为不同）
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+/// 本地模型转为配置包记录：去掉主键、时间戳和本地字段
+fn to_record(section: &BundleSection, full: &Map<String, Value>) -> Map<String, Value> {
+    full.iter()
+        .filter(|(k, _)| {
+            !COMMON_OMIT_FIELDS.contains(&k.as_str()) && !section.local_fields.contains(&k.as_str())
+        })
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// 系统配置中不参与迁移的本地分组
+fn is_local_config(section: &BundleSection, record: &Map<String, Value>) -> bool {
+    section.name == "system_config"
+        && record
+            .get("config_group")
+            .and_then(Value::as_str)
+            .is_some_and(|g| LOCAL_CONFIG_GROUPS.contains(&g))
+}
+
+/// 比对本地记录与配置包记录，生成导入计划（不访问数据库）
+fn plan_section(
+    section: &BundleSection,
+    mode: &str,
+    columns: &[String],
+    local: &[Map<String, Value>],
+    incoming: &[Map<String, Value>],
+    now: &str,
+) -> SectionPlan {
+    let mut plan = SectionPlan::default();
+    let local_by_key: HashMap<String, &Map<String, Value>> = local
+        .iter()
+        .filter(|row| !is_local_config(section, row))
+        .filter_map(|row| record_key(section, row).map(|k| (k, row)))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut ignored_fields = HashSet::new();
+    let mut skipped_local = 0;
+    for (i, raw) in incoming.iter().enumerate() {
+        if is_local_config(section, raw) {
+            skipped_local += 1;
+            continue;
+        }
+        let Some(key) = record_key(section, raw) else {
+            plan.errors.push(format!(
+                "{} 第 {} 条缺少业务键 {}",
+                section.label,
+                i + 1,
+                section.key_fields.join("+")
+            ));
+            continue;
+        };
+        if !seen.insert(key.clone()) {
+            plan.warnings
+                .push(format!("{} {} 重复，仅导入第一条", section.label, key));
+            continue;
+        }
+
+        let mut record = Map::new();
+        for (k, v) in raw {
+            if !columns.contains(k) {
+                ignored_fields.insert(k.clone());
+            } else if !COMMON_OMIT_FIELDS.contains(&k.as_str())
+                && !section.local_fields.contains(&k.as_str())
+            {
+                record.insert(k.clone(), v.clone());
+            }
+        }
+
+        match local_by_key.get(&key) {
+            Some(existing) => {
+                let mut fields: Vec<String> = record
+                    .iter()
+                    .filter(|(k, v)| !existing.get(*k).is_some_and(|old| values_equal(old, v)))
+                    .map(|(k, _)| k.clone())
+                    .collect();
+                if fields.is_empty() {
+                    plan.unchanged += 1;
+                    continue;
+                }
+                fields.sort();
+                let mut merged = (*existing).clone();
+                merged.extend(record);
+                if merged.contains_key("updated_at") {
+                    merged.insert("updated_at".to_string(), Value::String(now.to_string()));
+                }
+                plan.updates.push(Value::Object(merged));
+                plan.changes.push(BundleRecordChange {
+                    key,
+                    action: ACTION_UPDATE.to_string(),
+                    fields,
+                });
+            }
+            None => {
+                // 主键占位，插入前置为 NotSet
+                record.insert("id".to_string(), Value::from(0));
+                plan.inserts.push(Value::Object(record));
+                plan.changes.push(BundleRecordChange {
+                    key,
+                    action: ACTION_ADD.to_string(),
+                    fields: Vec::new(),
+                });
+            }
+        }
+    }
+
+    if mode == MODE_REPLACE {
+        // 系统配置只替换配置包中出现的分组；系统策略模板不删除
+        let groups: HashSet<&str> = incoming
+            .iter()
+            .filter_map(|r| r.get("config_group").and_then(Value::as_str))
+            .collect();
+        let mut removed: Vec<(&String, &&Map<String, Value>)> = local_by_key
+            .iter()
+            .filter(|(key, _)| !seen.contains(*key))
+            .filter(|(_, row)| match section.name {
+                "system_config" => row
+                    .get("config_group")
+                    .and_then(Value::as_str)
+                    .is_some_and(|g| groups.contains(g)),
+                "strategies" => row.get("is_system").and_then(Value::as_bool) != Some(true),
+                _ => true,
+            })
+            .collect();
+        removed.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, row) in removed {
+            plan.deletes.push(Value::Object((*row).clone()));
+            plan.changes.push(BundleRecordChange {
+                key: key.clone(),
+                action: ACTION_REMOVE.to_string(),
+                fields: Vec::new(),
+            });
+        }
+    }
+
+    if skipped_local > 0 {
+        plan.warnings.push(format!(
+            "已跳过 {} 条本机路径相关配置（{}）",
+            skipped_local,
+            LOCAL_CONFIG_GROUPS.join(", ")
+        ));
+    }
+    if !ignored_fields.is_empty() {
+        let mut names: Vec<String> = ignored_fields.into_iter().collect();
+        names.sort();
+        plan.warnings.push(format!(
+            "{} 忽略未知字段: {}",
+            section.label,
+            names.join(", ")
+        ));
+    }
+    plan
+}
+
+async fn load_local<E, C>(conn: &C) -> Result<Vec<Map<String, Value>>, AppError>
+where
+    C: ConnectionTrait,
+    E: EntityTrait,
+    E::Model: Serialize,
+{
+    E::find()
+        .all(conn)
+        .await?
+        .iter()
+        .map(|model| match serde_json::to_value(model) {
+            Ok(Value::Object(map)) => Ok(map),
+            Ok(_) => Err(AppError::DataConversionError(
+                "记录序列化结果不是对象".into(),
+            )),
+            Err(e) => Err(AppError::DataConversionError(format!(
+                "记录序列化失败: {}",
+                e
+            ))),
+        })
+        .collect()
+}
+
+/// 待写入记录能否还原为模型（缺少必填字段、类型不符时报错）
+fn check_models<E>(section: &BundleSection, plan: &mut SectionPlan)
+where
+    E: EntityTrait,
+    E::Model: DeserializeOwned,
+{
+    let mut errors = Vec::new();
+    for value in plan.inserts.iter().chain(plan.updates.iter()) {
+        if let Err(e) = serde_json::from_value::<E::Model>(value.clone()) {
+            let key = value
+                .as_object()
+                .and_then(|m| record_key(section, m))
+                .unwrap_or_default();
+            errors.push(format!("{} {} 字段格式错误: {}", section.label, key, e));
+        }
+    }
+    plan.errors.extend(errors);
+}
+
+async fn apply_plan<E, C>(conn: &C, plan: &SectionPlan) -> Result<(), AppError>
+where
+    C: ConnectionTrait,
+    E: EntityTrait,
+    E::Model: DeserializeOwned + IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelBehavior + Send,
+{
+    let to_active = |value: &Value| {
+        <E::ActiveModel as ActiveModelTrait>::from_json(value.clone())
+            .map_err(|e| AppError::DataConversionError(format!("记录转换失败: {}", e)))
+    };
+    for value in &plan.deletes {
+        to_active(value)?.delete(conn).await?;
+    }
+    for value in &plan.updates {
+        to_active(value)?.update(conn).await?;
+    }
+    for value in &plan.inserts {
+        let mut active = to_active(value)?;
+        for pk in E::PrimaryKey::iter() {
+            active.not_set(pk.into_column());
+        }
+        active.insert(conn).await?;
+    }
+    Ok(())
+}
+
+/// 导出配置包；sections 为空时导出全部分区
+pub async fn build_bundle<C: ConnectionTrait>(
+    conn: &C,
+    sections: Option<&[String]>,
+) -> Result<ConfigBundle, AppError> {
+    let selected: Vec<&BundleSection> = match sections {
+        Some(names) if !names.is_empty() => names
+            .iter()
+            .map(|name| {
+                find_section(name)
+                    .ok_or_else(|| AppError::InvalidInput(format!("未知配置包分区: {}", name)))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => SECTIONS.iter().collect(),
+    };
+
+    let mut out = BTreeMap::new();
+    for section in selected {
+        let local = with_section_entity!(section.name, E => load_local::<E, _>(conn).await?);
+        let mut records: Vec<(String, Map<String, Value>)> = local
+            .iter()
+            .filter(|row| !is_local_config(section, row))
+            .map(|row| {
+                let key = record_key(section, row).unwrap_or_default();
+                (key, to_record(section, row))
+            })
+            .collect();
+        records.sort_by(|a, b| a.0.cmp(&b.0));
+        out.insert(
+            section.name.to_string(),
+            records.into_iter().map(|(_, r)| r).collect(),
+        );
+    }
+
+    Ok(ConfigBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        sections: out,
+    })
+}
+
+/// 解析配置包文件内容并检查格式与版本
+pub fn parse_bundle(content: &str) -> Result<ConfigBundle, AppError> {
+    let bundle: ConfigBundle = serde_json::from_str(content)
+        .map_err(|e| AppError::DataConversionError(format!("配置包格式错误: {}", e)))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(AppError::DataConversionError(format!(
+            "不是配置包文件: format={}",
+            bundle.format
+        )));
+    }
+    if bundle.version == 0 || bundle.version > BUNDLE_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "配置包版本 {} 不受支持（当前支持 1~{}）",
+            bundle.version, BUNDLE_VERSION
+        )));
+    }
+    Ok(bundle)
+}
+
+/// 策略模板额外做结构校验
+fn check_strategies(plan: &mut SectionPlan) {
+    let mut errors = Vec::new();
+    for value in plan.inserts.iter().chain(plan.updates.iter()) {
+        let Ok(tpl) = serde_json::from_value::<strategy_template::Model>(value.clone()) else {
+            continue;
+        };
+        let report = strategy_schema_service::validate_strategy(
+            &tpl.sort_weights,
+            &tpl.constraints,
+            tpl.soft_constraints.as_deref(),
+            &tpl.eval_weights,
+            &tpl.temper_rules,
+        );
+        if !report.valid {
+            errors.push(format!(
+                "策略模板 {} 校验失败: {}",
+                tpl.name,
+                report.error_summary()
+            ));
+        }
+    }
+    plan.errors.extend(errors);
+}
+
+/// 替换时仍被排程方案引用的策略模板保留不删除，记为冲突
+async fn keep_referenced_strategies<C: ConnectionTrait>(
+    conn: &C,
+    plan: &mut SectionPlan,
+) -> Result<(), AppError> {
+    let delete_id = |value: &Value| value.get("id").and_then(Value::as_i64).map(|id| id as i32);
+    let ids: Vec<i32> = plan.deletes.iter().filter_map(delete_id).collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let mut usage: HashMap<i32, usize> = HashMap::new();
+    for p in schedule_plan::Entity::find()
+        .filter(schedule_plan::Column::StrategyId.is_in(ids))
+        .all(conn)
+        .await?
+    {
+        if let Some(id) = p.strategy_id {
+            *usage.entry(id).or_default() += 1;
+        }
+    }
+    if usage.is_empty() {
+        return Ok(());
+    }
+
+    let mut kept = HashSet::new();
+    let conflicts = &mut plan.conflicts;
+    plan.deletes.retain(|value| {
+        let Some(count) = delete_id(value).and_then(|id| usage.get(&id)) else {
+            return true;
+        };
+        let name = value
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        conflicts.push(format!(
+            "策略模板 {} 仍被 {} 个排程方案引用，保留不删除",
+            name, count
+        ));
+        kept.insert(name);
+        false
+    });
+    plan.changes
+        .retain(|c| !(c.action == ACTION_REMOVE && kept.contains(&c.key)));
+    Ok(())
+}
+
+/// 字段映射额外按保存映射模板时的规则校验（校验规则、表头行/数据起始行）
+fn check_field_mappings(plan: &mut SectionPlan) {
+    let mut errors = Vec::new();
+    for value in plan.inserts.iter().chain(plan.updates.iter()) {
+        let Ok(mapping) = serde_json::from_value::<field_mapping::Model>(value.clone()) else {
+            continue;
+        };
+        let checked = CompiledRules::parse(mapping.validation_rules.as_deref()).and_then(|_| {
+            source_reader_service::validate_read_rows(mapping.header_row, mapping.data_start_row)
+        });
+        if let Err(e) = checked {
+            errors.push(format!(
+                "字段映射 {} 校验失败: {}",
+                mapping.template_name, e
+            ));
+        }
+    }
+    plan.errors.extend(errors);
+}
+
+/// 导入配置包。modes 为分区 -> skip/merge/replace，未指定的分区默认 merge；
+/// dry_run 或存在错误时只返回差异，不写入
+pub async fn import_bundle<C>(
+    db: &C,
+    bundle: &ConfigBundle,
+    modes: &HashMap<String, String>,
+    dry_run: bool,
+) -> Result<ConfigBundleReport, AppError>
+where
+    C: ConnectionTrait + TransactionTrait,
+{
+    let mut report = ConfigBundleReport {
+        version: bundle.version,
+        app_version: bundle.app_version.clone(),
+        exported_at: bundle.exported_at.clone(),
+        dry_run,
+        applied: false,
+        sections: Vec::new(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for name in bundle.sections.keys() {
+        if find_section(name).is_none() {
+            report.warnings.push(format!("忽略未知分区 {}", name));
+        }
+    }
+    for (name, mode) in modes {
+        if find_section(name).is_none() {
+            return Err(AppError::InvalidInput(format!("未知配置包分区: {}", name)));
+        }
+        if ![MODE_SKIP, MODE_MERGE, MODE_REPLACE].contains(&mode.as_str()) {
+            return Err(AppError::InvalidInput(format!(
+                "分区 {} 的导入方式 {} 无效，可选 skip/merge/replace",
+                name, mode
+            )));
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = db.begin().await?;
+    let mut plans = Vec::new();
+    for section in SECTIONS {
+        let Some(incoming) = bundle.sections.get(section.name) else {
+            continue;
+        };
+        let mode = modes
+            .get(section.name)
+            .map(String::as_str)
+            .unwrap_or(MODE_MERGE);
+        let mut plan = if mode == MODE_SKIP {
+            SectionPlan::default()
+        } else {
+            with_section_entity!(section.name, E => {
+                let local = load_local::<E, _>(&tx).await?;
+                let mut plan =
+                    plan_section(section, mode, &column_names::<E>(), &local, incoming, &now);
+                check_models::<E>(section, &mut plan);
+                plan
+            })
+        };
+        match section.name {
+            "strategies" => {
+                check_strategies(&mut plan);
+                keep_referenced_strategies(&tx, &mut plan).await?;
+            }
+            "field_mappings" => check_field_mappings(&mut plan),
+            _ => {}
+        }
+
+        let count = |action: &str| plan.changes.iter().filter(|c| c.action == action).count();
+        report.sections.push(BundleSectionDiff {
+            section: section.name.to_string(),
+            label: section.label.to_string(),
+            mode: mode.to_string(),
+            added: count(ACTION_ADD),
+            updated: count(ACTION_UPDATE),
+            removed: count(ACTION_REMOVE),
+            unchanged: plan.unchanged,
+            changes: plan.changes.clone(),
+            conflicts: plan.conflicts.clone(),
+        });
+        report.errors.append(&mut plan.errors);
+        report.warnings.append(&mut plan.warnings);
+        plans.push((section, plan));
+    }
+
+    if dry_run || !report.errors.is_empty() {
+        return Ok(report);
+    }
+
+    for (section, plan) in &plans {
+        if section.name == "strategies" {
+            apply_strategies(&tx, plan).await?;
+        } else {
+            with_section_entity!(section.name, E => apply_plan::<E, _>(&tx, plan).await?);
+        }
+    }
+    tx.commit().await?;
+    report.applied = true;
+    Ok(report)
+}
+
+/// 策略模板写入前后补记修订，与界面编辑一致
+async fn apply_strategies<C: ConnectionTrait>(
+    conn: &C,
+    plan: &SectionPlan,
+) -> Result<(), AppError> {
+    let names = |values: &[Value]| -> Vec<String> {
+        values
+            .iter()
+            .filter_map(|v| v.get("name").and_then(Value::as_str).map(str::to_string))
+            .collect()
+    };
+    let updated = names(&plan.updates);
+    for tpl in strategy_template::Entity::find()
+        .filter(strategy_template::Column::Name.is_in(updated.clone()))
+        .all(conn)
+        .await?
+    {
+        strategy_revision_service::ensure_current_revision(conn, &tpl).await?;
+    }
+
+    apply_plan::<strategy_template::Entity, _>(conn, plan).await?;
+
+    let mut touched = updated;
+    touched.extend(names(&plan.inserts));
+    for tpl in strategy_template::Entity::find()
+        .filter(strategy_template::Column::Name.is_in(touched))
+        .all(conn)
+        .await?
+    {
+        strategy_revision_service::revise_if_changed(conn, &tpl, Some("导入配置包".to_string()))
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(json: &str) -> Vec<Map<String, Value>> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn plan_merge_and_replace_by_business_key() {
+        let section = find_section("customer_priorities").unwrap();
+        let cols = columns(&[
+            "id",
+            "customer_code",
+            "customer_name",
+            "priority_score",
+            "enabled",
+            "updated_at",
+        ]);
+        let local = rows(
+            r#"[{"id":1,"customer_code":"C1","customer_name":"甲","priority_score":50,"enabled":true,"updated_at":null},
+                {"id":2,"customer_code":"C2","customer_name":"乙","priority_score":60.0,"enabled":true,"updated_at":null},
+                {"id":3,"customer_code":"C3","customer_name":"丙","priority_score":70,"enabled":true,"updated_at":null}]"#,
+        );
+        let incoming = rows(
+            r#"[{"customer_code":"C1","customer_name":"甲","priority_score":80,"enabled":true,"site":"A"},
+                {"customer_code":"C2","customer_name":"乙","priority_score":60,"enabled":true},
+                {"customer_code":"C4","customer_name":"丁","priority_score":90,"enabled":true},
+                {"customer_code":"C4","customer_name":"丁2","priority_score":90,"enabled":true},
+                {"customer_name":"无键","priority_score":1,"enabled":true}]"#,
+        );
+
+        let merge = plan_section(section, MODE_MERGE, &cols, &local, &incoming, "now");
+        assert_eq!(merge.unchanged, 1);
+        assert_eq!(
+            merge.changes,
+            vec![
+                BundleRecordChange {
+                    key: "C1".into(),
+                    action: ACTION_UPDATE.into(),
+                    fields: vec!["priority_score".into()],
+                },
+                BundleRecordChange {
+                    key: "C4".into(),
+                    action: ACTION_ADD.into(),
+                    fields: vec![],
+                },
+            ]
+        );
+        assert_eq!(merge.updates[0]["id"], 1);
+        assert_eq!(merge.updates[0]["updated_at"], "now");
+        assert!(merge.deletes.is_empty());
+        assert_eq!(merge.errors.len(), 1);
+        assert_eq!(merge.warnings.len(), 2, "{:?}", merge.warnings);
+
+        let replace = plan_section(section, MODE_REPLACE, &cols, &local, &incoming, "now");
+        assert_eq!(replace.deletes.len(), 1);
+        assert_eq!(replace.deletes[0]["customer_code"], "C3");
+    }
+
+    #[test]
+    fn plan_keeps_local_groups_and_system_strategies() {
+        let config = find_section("system_config").unwrap();
+        let cols = columns(&["id", "config_group", "config_key", "config_value"]);
+        let local = rows(
+            r#"[{"id":1,"config_group":"shift","config_key":"day_start","config_value":"08:00"},
+                {"id":2,"config_group":"shift","config_key":"legacy","config_value":"x"},
+                {"id":3,"config_group":"temp","config_key":"enabled","config_value":"true"},
+                {"id":4,"config_group":"backup","config_key":"path","config_value":"D:/bak"}]"#,
+        );
+        let incoming = rows(
+            r#"[{"config_group":"shift","config_key":"day_start","config_value":"07:30"},
+                {"config_group":"backup","config_key":"path","config_value":"/other"}]"#,
+        );
+        let plan = plan_section(config, MODE_REPLACE, &cols, &local, &incoming, "now");
+        let actions: Vec<(&str, &str)> = plan
+            .changes
+            .iter()
+            .map(|c| (c.key.as_str(), c.action.as_str()))
+            .collect();
+        // 只替换配置包中出现的 shift 分组；backup 属于本机配置
+        assert_eq!(
+            actions,
+            vec![
+                ("shift/day_start", ACTION_UPDATE),
+                ("shift/legacy", ACTION_REMOVE)
+            ]
+        );
+
+        let strategies = find_section("strategies").unwrap();
+        let cols = columns(&["id", "name", "is_system", "is_default"]);
+        let local = rows(
+            r#"[{"id":1,"name":"标准排序策略","is_system":true,"is_default":true},
+                {"id":2,"name":"旧策略","is_system":false,"is_default":false}]"#,
+        );
+        let incoming = rows(r#"[{"name":"新策略","is_default":true}]"#);
+        let plan = plan_section(strategies, MODE_REPLACE, &cols, &local, &incoming, "now");
+        assert_eq!(plan.deletes.len(), 1);
+        assert_eq!(plan.deletes[0]["name"], "旧策略");
+        // 默认标记保留本地取值，不随配置包写入
+        assert!(plan.inserts[0].get("is_default").is_none());
+    }
+
+    #[test]
+    fn field_mapping_rules_and_rows_are_checked() {
+        let mapping = |name: &str, rules: Value, header: Value, data: Value| {
+            serde_json::json!({
+                "id": 0, "template_name": name, "is_default": false, "source_type": "excel",
+                "mappings": "[]", "value_transforms": null, "validation_rules": rules,
+                "sheet": null, "header_row": header, "data_start_row": data,
+                "csv_delimiter": null, "csv_encoding": null,
+                "created_at": null, "updated_at": null
+            })
+        };
+        let mut plan = SectionPlan {
+            inserts: vec![
+                mapping("正常", Value::Null, Value::from(2), Value::from(3)),
+                mapping("坏规则", Value::from("not json"), Value::Null, Value::Null),
+            ],
+            updates: vec![mapping(
+                "坏行号",
+                Value::Null,
+                Value::from(3),
+                Value::from(3),
+            )],
+            ..Default::default()
+        };
+        check_field_mappings(&mut plan);
+        assert_eq!(plan.errors.len(), 2, "{:?}", plan.errors);
+        assert!(plan.errors[0].contains("坏规则"));
+        assert!(plan.errors[1].contains("坏行号"));
+    }
+
+    #[tokio::test]
+    async fn export_then_import_round_trip() {
+        use crate::db::get_db;
+        use sea_orm::{ActiveModelTrait, Set};
+
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_config_bundle_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let code = format!("BUNDLE-{}", seed);
+        customer_priority_config::ActiveModel {
+            customer_code: Set(code.clone()),
+            customer_name: Set("配置包客户".to_string()),
+            priority_level: Set("vip".to_string()),
+            priority_score: Set(90),
+            enabled: Set(true),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        let mut bundle = build_bundle(db, Some(&["customer_priorities".to_string()]))
+            .await
+            .unwrap();
+        assert_eq!(bundle.sections.len(), 1);
+        let rows = bundle.sections.get_mut("customer_priorities").unwrap();
+        let row = rows
+            .iter_mut()
+            .find(|r| r["customer_code"] == code.as_str())
+            .expect("导出缺少测试客户");
+        assert!(row.get("id").is_none());
+        row.insert("priority_score".into(), Value::from(95));
+        let new_code = format!("{}-NEW", code);
+        let mut added = row.clone();
+        added.insert("customer_code".into(), Value::from(new_code.clone()));
+        rows.push(added);
+
+        let content = serde_json::to_string(&bundle).unwrap();
+        let bundle = parse_bundle(&content).unwrap();
+        let preview = import_bundle(db, &bundle, &HashMap::new(), true)
+            .await
+            .unwrap();
+        assert!(!preview.applied);
+        let diff = &preview.sections[0];
+        assert!(diff
+            .changes
+            .iter()
+            .any(|c| c.key == code && c.action == ACTION_UPDATE));
+        assert!(diff
+            .changes
+            .iter()
+            .any(|c| c.key == new_code && c.action == ACTION_ADD));
+
+        let applied = import_bundle(db, &bundle, &HashMap::new(), false)
+            .await
+            .unwrap();
+        assert!(applied.applied, "{:?}", applied.errors);
+        let stored = customer_priority_config::Entity::find()
+            .filter(customer_priority_config::Column::CustomerCode.is_in([code, new_code]))
+            .all(db)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(stored.iter().all(|c| c.priority_score == 95));
+
+        // 完整配置包原样回导不应报错
+        let full = build_bundle(db, None).await.unwrap();
+        assert_eq!(full.sections.len(), SECTIONS.len());
+        let report = import_bundle(db, &full, &HashMap::new(), true)
+            .await
+            .unwrap();
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+        let mut newer = bundle.clone();
+        newer.version = BUNDLE_VERSION + 1;
+        assert!(parse_bundle(&serde_json::to_string(&newer).unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn replace_keeps_strategies_referenced_by_plans() {
+        use crate::db::get_db;
+        use sea_orm::{ActiveModelTrait, Set};
+
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_config_bundle_ref_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let template = |name: String| strategy_template::ActiveModel {
+            name: Set(name),
+            sort_weights: Set(r#"{"priorities":[]}"#.to_string()),
+            constraints: Set(r#"{"constraints":[]}"#.to_string()),
+            eval_weights: Set(r#"{"weights":{}}"#.to_string()),
+            temper_rules: Set("{}".to_string()),
+            ..Default::default()
+        };
+        let used = template(format!("引用策略-{}", seed))
+            .insert(db)
+            .await
+            .unwrap();
+        let unused = template(format!("闲置策略-{}", seed))
+            .insert(db)
+            .await
+            .unwrap();
+        schedule_plan::ActiveModel {
+            plan_no: Set(format!("BUNDLE-REF-{}", seed)),
+            name: Set("引用方案".to_string()),
+            period_type: Set("daily".to_string()),
+            start_date: Set("2026-02-13".to_string()),
+            end_date: Set("2026-02-13".to_string()),
+            strategy_id: Set(Some(used.id)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        // 仅试运行：共享测试库中不真正删除其他用例的策略
+        let bundle = ConfigBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            app_version: "test".to_string(),
+            exported_at: "now".to_string(),
+            sections: BTreeMap::from([("strategies".to_string(), Vec::new())]),
+        };
+        let modes = HashMap::from([("strategies".to_string(), MODE_REPLACE.to_string())]);
+        let report = import_bundle(db, &bundle, &modes, true).await.unwrap();
+        let diff = &report.sections[0];
+        assert!(diff
+            .changes
+            .iter()
+            .any(|c| c.key == unused.name && c.action == ACTION_REMOVE));
+        assert!(!diff.changes.iter().any(|c| c.key == used.name));
+        assert!(
+            diff.conflicts.iter().any(|c| c.contains(&used.name)),
+            "{:?}",
+            diff.conflicts
+        );
+        assert_eq!(diff.removed, diff.changes.len());
+    }
+}
@@ -434,6 +434,200 @@ pub async fn export_plan_to_excel(
     Ok(row_count)
 }
 
+/// 导出行车吊运清单（按班次分 Sheet，库区状态跨班次延续）
+pub async fn export_crane_pick_list(plan_id: i32, file_path: &str) -> Result<usize, AppError> {
+    use crate::engine::yard::{move_cost, YardCostConfig, YardLocation, YardState};
+    use crate::models::system_config;
+
+    let db = get_db();
+
+    schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(db)
+        .await?;
+
+    let yard_materials = material::Entity::find()
+        .filter(material::Column::Status.ne("completed"))
+        .all(db)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|i| i.material_id).collect();
+    let plan_mats = if mat_ids.is_empty() {
+        vec![]
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(db)
+            .await?
+    };
+    let mat_map: HashMap<i32, &material::Model> = plan_mats.iter().map(|m| (m.id, m)).collect();
+    let coil_by_id: HashMap<i32, &str> = yard_materials
+        .iter()
+        .chain(plan_mats.iter())
+        .map(|m| (m.id, m.coil_id.as_str()))
+        .collect();
+
+    let yard_group: HashMap<String, String> = system_config::Entity::find()
+        .filter(system_config::Column::ConfigGroup.eq("yard"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.config_key, c.config_value))
+        .collect();
+    let cost_config = YardCostConfig::from_config_group(Some(&yard_group));
+    let mut state = YardState::from_materials(yard_materials.iter().chain(plan_mats.iter()));
+
+    let mut workbook = Workbook::new();
+    let header_fmt = Format::new()
+        .set_bold()
+        .set_align(FormatAlign::Center)
+        .set_background_color(Color::RGB(0x1677FF))
+        .set_font_color(Color::White)
+        .set_font_size(11.0);
+    let data_fmt = Format::new().set_font_size(10.0);
+    let center_fmt = Format::new()
+        .set_align(FormatAlign::Center)
+        .set_font_size(10.0);
+
+    let headers: [(&str, f64); 12] = [
+        ("顺序", 8.0),
+        ("计划开始", 18.0),
+        ("钢卷号", 16.0),
+        ("库位", 14.0),
+        ("库区", 8.0),
+        ("排", 6.0),
+        ("层", 6.0),
+        ("需倒垛卷", 24.0),
+        ("倒垛数", 8.0),
+        ("预计吊运(分钟)", 14.0),
+        ("重量(t)", 10.0),
+        ("宽度(mm)", 10.0),
+    ];
+
+    // 按班次分组（items 已按顺序排列，同一班次连续）
+    let mut groups: Vec<(String, Vec<&schedule_item::Model>)> = Vec::new();
+    for item in &items {
+        let shift_label = match item.shift_type.as_str() {
+            "day" => "白班",
+            "night" => "夜班",
+            other => other,
+        };
+        let key = format!("{}{}", item.shift_date, shift_label);
+        match groups.last_mut() {
+            Some((k, list)) if *k == key => list.push(item),
+            _ => groups.push((key, vec![item])),
+        }
+    }
+
+    let mut prev_loc: Option<YardLocation> = None;
+    for (key, group) in &groups {
+        let sheet = workbook.add_worksheet();
+        sheet
+            .set_name(key)
+            .map_err(|e| AppError::FileError(e.to_string()))?;
+        for (col, (name, width)) in headers.iter().enumerate() {
+            write_cell_str(sheet, 0, col as u16, name, &header_fmt)?;
+            sheet
+                .set_column_width(col as u16, *width)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+        }
+
+        for (idx, item) in group.iter().enumerate() {
+            let row = (idx + 1) as u32;
+            let mat = mat_map.get(&item.material_id);
+            let loc = state.location(item.material_id).cloned();
+            let blockers = state.retrieve(item.material_id);
+
+            let (cost, _) = move_cost(
+                prev_loc.as_ref(),
+                loc.as_ref(),
+                blockers.len(),
+                &cost_config,
+            );
+            if loc.is_some() {
+                prev_loc = loc.clone();
+            }
+            let blocker_coils: Vec<&str> = blockers
+                .iter()
+                .map(|id| coil_by_id.get(id).copied().unwrap_or("-"))
+                .collect();
+
+            write_cell_num(sheet, row, 0, item.sequence as f64, &center_fmt)?;
+            write_cell_str(
+                sheet,
+                row,
+                1,
+                item.planned_start.as_deref().unwrap_or("-"),
+                &data_fmt,
+            )?;
+            write_cell_str(
+                sheet,
+                row,
+                2,
+                mat.map(|m| m.coil_id.as_str()).unwrap_or("-"),
+                &data_fmt,
+            )?;
+            write_cell_str(
+                sheet,
+                row,
+                3,
+                mat.and_then(|m| m.storage_loc.as_deref()).unwrap_or("-"),
+                &data_fmt,
+            )?;
+            match loc.as_ref() {
+                Some(l) => {
+                    write_cell_str(sheet, row, 4, &l.bay, &center_fmt)?;
+                    write_cell_num(sheet, row, 5, l.row as f64, &center_fmt)?;
+                    write_cell_num(sheet, row, 6, l.level as f64, &center_fmt)?;
+                }
+                None => {
+                    for col in 4..=6 {
+                        write_cell_str(sheet, row, col, "-", &center_fmt)?;
+                    }
+                }
+            }
+            write_cell_str(sheet, row, 7, &blocker_coils.join(","), &data_fmt)?;
+            write_cell_num(sheet, row, 8, blockers.len() as f64, &center_fmt)?;
+            write_cell_num(sheet, row, 9, (cost * 10.0).round() / 10.0, &center_fmt)?;
+            write_cell_num(
+                sheet,
+                row,
+                10,
+                mat.map(|m| m.weight).unwrap_or(0.0),
+                &data_fmt,
+            )?;
+            write_cell_num(
+                sheet,
+                row,
+                11,
+                mat.map(|m| m.width).unwrap_or(0.0),
+                &data_fmt,
+            )?;
+        }
+    }
+
+    if groups.is_empty() {
+        let sheet = workbook.add_worksheet();
+        for (col, (name, width)) in headers.iter().enumerate() {
+            write_cell_str(sheet, 0, col as u16, name, &header_fmt)?;
+            sheet
+                .set_column_width(col as u16, *width)
+                .map_err(|e| AppError::FileError(e.to_string()))?;
+        }
+    }
+
+    workbook
+        .save(file_path)
+        .map_err(|e| AppError::FileError(format!("保存Excel失败: {}", e)))?;
+
+    Ok(items.len())
+}
+
 /// 导出材料列表到 Excel
 pub async fn export_materials_to_excel(
     file_path: &str,
@@ -0,0 +1,702 @@
+//! MES 下发与回执服务
+//!
+//! 方案确认后按配置格式（CSV 模板 / JSON / XML）写入 outbox 目录：先写临时文件再
+//! rename，保证下游只会看到完整文件；数据文件落盘后再写 `{file}.manifest.json`
+//! （含 sha256 校验和）。后台按间隔轮询 inbox 目录，识别 ack/reject 回执并回写下发记录。
+
+use crate::models::{plan_dispatch, schedule_plan};
+use crate::services::plan_json_service::{self, PlanDocument};
+use crate::utils::log::write_operation_log;
+use crate::utils::watch_dir::{load_config_group, move_into, parse_bool};
+use crate::AppError;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const MIN_POLL_SECONDS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoffFormat {
+    Csv,
+    Json,
+    Xml,
+}
+
+impl HandoffFormat {
+    fn from_str(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "json" => HandoffFormat::Json,
+            "xml" => HandoffFormat::Xml,
+            _ => HandoffFormat::Csv,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HandoffFormat::Csv => "csv",
+            HandoffFormat::Json => "json",
+            HandoffFormat::Xml => "xml",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HandoffConfig {
+    pub outbox_enabled: bool,
+    pub outbox_dir: Option<PathBuf>,
+    pub format: HandoffFormat,
+    pub csv_template_id: Option<i32>,
+    pub inbox_dir: Option<PathBuf>,
+    pub poll_seconds: u64,
+}
+
+impl Default for HandoffConfig {
+    fn default() -> Self {
+        Self {
+            outbox_enabled: false,
+            outbox_dir: None,
+            format: HandoffFormat::Csv,
+            csv_template_id: None,
+            inbox_dir: None,
+            poll_seconds: 30,
+        }
+    }
+}
+
+/// 下发清单（manifest），与数据文件同目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispatchManifest {
+    pub format: String,
+    pub plan_id: i32,
+    pub plan_no: String,
+    pub plan_name: String,
+    pub version: i32,
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub item_count: usize,
+    pub dispatched_at: String,
+}
+
+/// 回执解析结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboxReceipt {
+    pub file_name: Option<String>,
+    pub plan_no: Option<String>,
+    pub accepted: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonReceipt {
+    #[serde(default)]
+    file_name: Option<String>,
+    #[serde(default)]
+    plan_no: Option<String>,
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InboxPollResult {
+    pub processed: usize,
+    pub acknowledged: usize,
+    pub rejected: usize,
+    /// 无法解析或无法匹配下发记录的回执文件
+    pub unmatched: Vec<String>,
+}
+
+fn non_empty_path(value: &str) -> Option<PathBuf> {
+    let raw = value.trim();
+    (!raw.is_empty()).then(|| PathBuf::from(raw))
+}
+
+pub async fn load_handoff_config() -> Result<HandoffConfig, AppError> {
+    let mut config = HandoffConfig::default();
+    for (key, value) in load_config_group("handoff").await? {
+        let value = value.as_str();
+        match key.as_str() {
+            "outbox_enabled" => config.outbox_enabled = parse_bool(value),
+            "outbox_dir" => config.outbox_dir = non_empty_path(value),
+            "outbox_format" => config.format = HandoffFormat::from_str(value),
+            "csv_template_id" => config.csv_template_id = value.trim().parse::<i32>().ok(),
+            "inbox_dir" => config.inbox_dir = non_empty_path(value),
+            "inbox_poll_seconds" => {
+                if let Ok(secs) = value.trim().parse::<u64>() {
+                    config.poll_seconds = secs;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(config)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+fn temp_path_for(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{}.tmp", name))
+}
+
+/// 原子写入：先写同目录下的隐藏临时文件并 fsync，再 rename 为目标文件
+pub fn write_atomic(target: &Path, bytes: &[u8]) -> Result<(), AppError> {
+    let tmp = temp_path_for(target);
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, target)?;
+    Ok(())
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn xml_opt<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map(|v| escape_xml(&v.to_string()))
+        .unwrap_or_default()
+}
+
+/// 渲染方案 XML（结构与 JSON 文档一致，排程项按顺序号输出）
+pub fn render_plan_xml(doc: &PlanDocument) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<plan format=\"{}\" schema_version=\"{}\" exported_at=\"{}\">\n",
+        escape_xml(&doc.format),
+        doc.schema_version,
+        escape_xml(&doc.exported_at)
+    ));
+    let meta = &doc.plan;
+    xml.push_str(&format!(
+        "  <meta plan_no=\"{}\" name=\"{}\" period_type=\"{}\" start_date=\"{}\" end_date=\"{}\" status=\"{}\" version=\"{}\"/>\n",
+        escape_xml(&meta.plan_no),
+        escape_xml(&meta.name),
+        escape_xml(&meta.period_type),
+        escape_xml(&meta.start_date),
+        escape_xml(&meta.end_date),
+        xml_opt(&meta.status),
+        xml_opt(&meta.version),
+    ));
+    xml.push_str(&format!("  <items count=\"{}\">\n", doc.items.len()));
+    for item in &doc.items {
+        xml.push_str(&format!(
+            "    <item sequence=\"{}\" coil_id=\"{}\" shift_date=\"{}\" shift_no=\"{}\" shift_type=\"{}\" planned_start=\"{}\" planned_end=\"{}\" is_roll_change=\"{}\" steel_grade=\"{}\" thickness=\"{}\" width=\"{}\" weight=\"{}\" storage_loc=\"{}\"/>\n",
+            item.sequence,
+            escape_xml(&item.coil_id),
+            escape_xml(&item.shift_date),
+            item.shift_no,
+            escape_xml(&item.shift_type),
+            xml_opt(&item.planned_start),
+            xml_opt(&item.planned_end),
+            item.is_roll_change,
+            escape_xml(&item.coil.steel_grade),
+            item.coil.thickness,
+            item.coil.width,
+            item.coil.weight,
+            xml_opt(&item.coil.storage_loc),
+        ));
+    }
+    xml.push_str("  </items>\n");
+    xml.push_str("  <roll_changes>\n");
+    for rc in &doc.roll_changes {
+        xml.push_str(&format!(
+            "    <roll_change sequence=\"{}\" coil_id=\"{}\"/>\n",
+            rc.sequence,
+            escape_xml(&rc.coil_id)
+        ));
+    }
+    xml.push_str("  </roll_changes>\n");
+    xml.push_str("</plan>\n");
+    xml
+}
+
+/// 解析回执文件
+///
+/// - `{下发文件名}.ack` / `{下发文件名}.rej`：纯文本，内容作为回执说明
+/// - `*.json`：`{"file_name"?, "plan_no"?, "status": "ack|acknowledged|reject|rejected", "message"?}`
+pub fn parse_inbox_file(file_name: &str, content: &str) -> Result<InboxReceipt, AppError> {
+    let lower = file_name.to_lowercase();
+    let message = Some(content.trim().to_string()).filter(|s| !s.is_empty());
+    for (suffix, accepted) in [(".ack", true), (".rej", false)] {
+        if lower.ends_with(suffix) {
+            let data_file = &file_name[..file_name.len() - suffix.len()];
+            if data_file.is_empty() {
+                break;
+            }
+            return Ok(InboxReceipt {
+                file_name: Some(data_file.to_string()),
+                plan_no: None,
+                accepted,
+                message,
+            });
+        }
+    }
+    if lower.ends_with(".json") {
+        let parsed: JsonReceipt = serde_json::from_str(content)
+            .map_err(|e| AppError::FileFormatError(format!("回执JSON解析失败: {}", e)))?;
+        let accepted = match parsed.status.trim().to_lowercase().as_str() {
+            "ack" | "acknowledged" | "ok" => true,
+            "reject" | "rejected" | "rej" => false,
+            other => {
+                return Err(AppError::FileFormatError(format!(
+                    "未知回执状态: {}",
+                    other
+                )))
+            }
+        };
+        if parsed.file_name.is_none() && parsed.plan_no.is_none() {
+            return Err(AppError::FileFormatError(
+                "回执缺少 file_name 或 plan_no".to_string(),
+            ));
+        }
+        return Ok(InboxReceipt {
+            file_name: parsed.file_name,
+            plan_no: parsed.plan_no,
+            accepted,
+            message: parsed.message,
+        });
+    }
+    Err(AppError::FileFormatError(format!(
+        "无法识别的回执文件: {}",
+        file_name
+    )))
+}
+
+/// 将方案写入 outbox 并登记下发记录；写入失败时登记为 failed 后返回错误
+pub async fn dispatch_plan(
+    plan_id: i32,
+    config: &HandoffConfig,
+) -> Result<plan_dispatch::Model, AppError> {
+    let db = crate::db::get_db();
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let format = config.format;
+    let file_name = format!(
+        "{}_v{}_{}.{}",
+        plan.plan_no,
+        plan.version.unwrap_or(1),
+        chrono::Local::now().format("%Y%m%d%H%M%S"),
+        format.as_str()
+    );
+
+    let written = write_outbox_files(&plan, &file_name, config).await;
+    let (status, checksum, error_message) = match &written {
+        Ok(manifest) => ("sent", Some(manifest.sha256.clone()), None),
+        Err(e) => ("failed", None, Some(e.to_string())),
+    };
+    let record = plan_dispatch::ActiveModel {
+        plan_id: Set(plan.id),
+        plan_no: Set(plan.plan_no.clone()),
+        format: Set(format.as_str().to_string()),
+        file_name: Set(file_name.clone()),
+        checksum: Set(checksum),
+        status: Set(status.to_string()),
+        error_message: Set(error_message),
+        created_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    match written {
+        Ok(manifest) => {
+            write_operation_log(
+                "plan",
+                "dispatch",
+                Some("plan"),
+                Some(plan.id),
+                Some(format!(
+                    "方案下发: {} -> {} ({} 项, sha256={})",
+                    plan.plan_no, file_name, manifest.item_count, manifest.sha256
+                )),
+            )
+            .await;
+            Ok(record)
+        }
+        Err(e) => {
+            write_operation_log(
+                "plan",
+                "dispatch_failed",
+                Some("plan"),
+                Some(plan.id),
+                Some(format!(
+                    "方案下发失败: {} -> {}: {}",
+                    plan.plan_no, file_name, e
+                )),
+            )
+            .await;
+            Err(e)
+        }
+    }
+}
+
+async fn write_outbox_files(
+    plan: &schedule_plan::Model,
+    file_name: &str,
+    config: &HandoffConfig,
+) -> Result<DispatchManifest, AppError> {
+    let dir = config
+        .outbox_dir
+        .as_ref()
+        .ok_or_else(|| AppError::InvalidInput("未配置下发目录 outbox_dir".to_string()))?;
+    std::fs::create_dir_all(dir)?;
+    let target = dir.join(file_name);
+
+    let item_count = match config.format {
+        HandoffFormat::Csv => {
+            // CSV 复用导出模板逻辑：先导出到临时文件再 rename
+            let tmp = temp_path_for(&target);
+            let tmp_str = tmp.to_string_lossy().to_string();
+            let count = crate::services::export_service::export_plan_to_csv(
+                plan.id,
+                &tmp_str,
+                config.csv_template_id,
+            )
+            .await;
+            let count = match count {
+                Ok(c) => c,
+                Err(e) => {
+                    let _ = std::fs::remove_file(&tmp);
+                    return Err(e);
+                }
+            };
+            std::fs::File::open(&tmp)?.sync_all()?;
+            std::fs::rename(&tmp, &target)?;
+            count
+        }
+        HandoffFormat::Json => {
+            let doc = plan_json_service::build_plan_document(plan.id).await?;
+            let json = serde_json::to_string_pretty(&doc)
+                .map_err(|e| AppError::DataConversionError(format!("序列化失败: {}", e)))?;
+            write_atomic(&target, json.as_bytes())?;
+            doc.items.len()
+        }
+        HandoffFormat::Xml => {
+            let doc = plan_json_service::build_plan_document(plan.id).await?;
+            write_atomic(&target, render_plan_xml(&doc).as_bytes())?;
+            doc.items.len()
+        }
+    };
+
+    let bytes = std::fs::read(&target)?;
+    let manifest = DispatchManifest {
+        format: config.format.as_str().to_string(),
+        plan_id: plan.id,
+        plan_no: plan.plan_no.clone(),
+        plan_name: plan.name.clone(),
+        version: plan.version.unwrap_or(1),
+        file_name: file_name.to_string(),
+        size_bytes: bytes.len() as u64,
+        sha256: sha256_hex(&bytes),
+        item_count,
+        dispatched_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| AppError::DataConversionError(format!("序列化失败: {}", e)))?;
+    write_atomic(
+        &dir.join(format!("{}.manifest.json", file_name)),
+        manifest_json.as_bytes(),
+    )?;
+    Ok(manifest)
+}
+
+/// 方案确认后的自动下发钩子：未启用时跳过，失败只记录不影响状态变更
+pub async fn dispatch_confirmed_plan(plan_id: i32) {
+    let config = match load_handoff_config().await {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            log::warn!("读取下发配置失败: {}", err);
+            return;
+        }
+    };
+    if !config.outbox_enabled {
+        return;
+    }
+    if let Err(err) = dispatch_plan(plan_id, &config).await {
+        log::warn!("方案 {} 自动下发失败: {}", plan_id, err);
+    }
+}
+
+async fn find_dispatch(receipt: &InboxReceipt) -> Result<Option<plan_dispatch::Model>, AppError> {
+    let db = crate::db::get_db();
+    if let Some(file_name) = &receipt.file_name {
+        let found = plan_dispatch::Entity::find()
+            .filter(plan_dispatch::Column::FileName.eq(file_name.clone()))
+            .order_by_desc(plan_dispatch::Column::Id)
+            .one(db)
+            .await?;
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+    if let Some(plan_no) = &receipt.plan_no {
+        return Ok(plan_dispatch::Entity::find()
+            .filter(plan_dispatch::Column::PlanNo.eq(plan_no.clone()))
+            .filter(plan_dispatch::Column::Status.ne("failed"))
+            .order_by_desc(plan_dispatch::Column::Id)
+            .one(db)
+            .await?);
+    }
+    Ok(None)
+}
+
+/// 扫描 inbox 目录，处理回执并回写下发记录；已处理文件移入 processed/，无法识别或匹配的移入 error/
+pub async fn poll_inbox(inbox_dir: &Path) -> Result<InboxPollResult, AppError> {
+    let mut result = InboxPollResult::default();
+    if !inbox_dir.is_dir() {
+        return Ok(result);
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(inbox_dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            // 跳过写入中的隐藏临时文件
+            !p.file_name()
+                .map(|n| n.to_string_lossy().starts_with('.'))
+                .unwrap_or(true)
+        })
+        .collect();
+    paths.sort();
+
+    let processed_dir = inbox_dir.join("processed");
+    let error_dir = inbox_dir.join("error");
+    let db = crate::db::get_db();
+    for path in paths {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                log::warn!("回执文件 {} 读取失败: {}", file_name, err);
+                result.unmatched.push(file_name);
+                move_into(&path, &error_dir)?;
+                continue;
+            }
+        };
+        let receipt = match parse_inbox_file(&file_name, &content) {
+            Ok(r) => r,
+            Err(err) => {
+                log::warn!("回执文件 {} 解析失败: {}", file_name, err);
+                result.unmatched.push(file_name);
+                move_into(&path, &error_dir)?;
+                continue;
+            }
+        };
+        let Some(dispatch) = find_dispatch(&receipt).await? else {
+            result.unmatched.push(file_name);
+            move_into(&path, &error_dir)?;
+            continue;
+        };
+
+        let status = if receipt.accepted {
+            "acknowledged"
+        } else {
+            "rejected"
+        };
+        let mut active: plan_dispatch::ActiveModel = dispatch.clone().into();
+        active.status = Set(status.to_string());
+        active.response_file = Set(Some(file_name.clone()));
+        active.response_message = Set(receipt.message.clone());
+        active.responded_at = Set(Some(chrono::Utc::now()));
+        active.update(db).await?;
+
+        write_operation_log(
+            "plan",
+            if receipt.accepted {
+                "dispatch_ack"
+            } else {
+                "dispatch_reject"
+            },
+            Some("plan"),
+            Some(dispatch.plan_id),
+            Some(format!(
+                "MES 回执 {}: {} {}",
+                dispatch.file_name,
+                status,
+                receipt.message.clone().unwrap_or_default()
+            )),
+        )
+        .await;
+
+        result.processed += 1;
+        if receipt.accepted {
+            result.acknowledged += 1;
+        } else {
+            result.rejected += 1;
+        }
+        move_into(&path, &processed_dir)?;
+    }
+    Ok(result)
+}
+
+/// 启动回执目录后台轮询；每轮重新读取配置，未配置 inbox 时仅等待
+pub fn spawn_inbox_watcher() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            let config = load_handoff_config().await.unwrap_or_default();
+            if let Some(dir) = &config.inbox_dir {
+                if let Err(err) = poll_inbox(dir).await {
+                    log::warn!("轮询回执目录失败: {}", err);
+                }
+            }
+            let secs = config.poll_seconds.max(MIN_POLL_SECONDS);
+            tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::plan_json_service::{
+        PlanDocCoil, PlanDocItem, PlanDocMeta, PlanDocScores, PLAN_JSON_FORMAT,
+    };
+
+    #[test]
+    fn parse_inbox_file_supports_suffix_and_json_receipts() {
+        let ack = parse_inbox_file("SP-1_v1_20260101.csv.ack", " ok \n").unwrap();
+        assert_eq!(ack.file_name.as_deref(), Some("SP-1_v1_20260101.csv"));
+        assert!(ack.accepted);
+        assert_eq!(ack.message.as_deref(), Some("ok"));
+
+        let rej = parse_inbox_file("SP-1_v1_20260101.xml.REJ", "").unwrap();
+        assert!(!rej.accepted);
+        assert_eq!(rej.message, None);
+
+        let json = parse_inbox_file(
+            "receipt.json",
+            r#"{"plan_no":"SP-1","status":"Rejected","message":"宽度超限"}"#,
+        )
+        .unwrap();
+        assert_eq!(json.plan_no.as_deref(), Some("SP-1"));
+        assert!(!json.accepted);
+        assert_eq!(json.message.as_deref(), Some("宽度超限"));
+
+        assert!(parse_inbox_file("receipt.json", r#"{"status":"ack"}"#).is_err());
+        assert!(parse_inbox_file("r.json", r#"{"plan_no":"A","status":"maybe"}"#).is_err());
+        assert!(parse_inbox_file("notes.txt", "ack").is_err());
+    }
+
+    #[test]
+    fn render_plan_xml_escapes_values() {
+        let doc = PlanDocument {
+            format: PLAN_JSON_FORMAT.to_string(),
+            schema_version: 1,
+            exported_at: "2026-01-01 08:00:00".to_string(),
+            plan: PlanDocMeta {
+                plan_no: "SP-1".to_string(),
+                name: "A&B <周计划>".to_string(),
+                period_type: "weekly".to_string(),
+                start_date: "2026-01-01".to_string(),
+                end_date: "2026-01-07".to_string(),
+                status: Some("confirmed".to_string()),
+                version: Some(2),
+                remarks: None,
+            },
+            strategy: None,
+            items: vec![PlanDocItem {
+                sequence: 1,
+                coil_id: "C\"1".to_string(),
+                shift_date: "2026-01-01".to_string(),
+                shift_no: 1,
+                shift_type: "day".to_string(),
+                planned_start: None,
+                planned_end: None,
+                cumulative_weight: None,
+                is_roll_change: false,
+                is_locked: false,
+                lock_reason: None,
+                coil: PlanDocCoil {
+                    steel_grade: "Q235".to_string(),
+                    thickness: 2.5,
+                    width: 1250.0,
+                    weight: 20.0,
+                    hardness_level: None,
+                    surface_level: None,
+                    product_type: None,
+                    contract_no: None,
+                    customer_name: None,
+                    due_date: None,
+                    storage_loc: None,
+                    temp_status: None,
+                },
+            }],
+            roll_changes: vec![],
+            scores: PlanDocScores::default(),
+        };
+        let xml = render_plan_xml(&doc);
+        assert!(xml.contains("name=\"A&amp;B &lt;周计划&gt;\""));
+        assert!(xml.contains("coil_id=\"C&quot;1\""));
+        assert!(xml.contains("<items count=\"1\">"));
+        assert!(xml.contains("version=\"2\""));
+    }
+
+    #[test]
+    fn write_atomic_replaces_target_without_leaving_temp_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "spm_handoff_test_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("plan.json");
+        write_atomic(&target, b"first").unwrap();
+        write_atomic(&target, b"second").unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"second");
+        assert!(!temp_path_for(&target).exists());
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn unreadable_receipt_is_moved_to_error_dir() {
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_handoff_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let dir = std::env::temp_dir().join(format!("spm_handoff_inbox_{}", seed));
+        std::fs::create_dir_all(&dir).unwrap();
+        // 非 UTF-8 内容无法按文本读取
+        std::fs::write(dir.join("SP-1_v1.csv.ack"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let result = poll_inbox(&dir).await.unwrap();
+        assert_eq!(result.processed, 0);
+        assert_eq!(result.unmatched, vec!["SP-1_v1.csv.ack".to_string()]);
+        assert!(!dir.join("SP-1_v1.csv.ack").exists());
+        assert!(dir.join("error").join("SP-1_v1.csv.ack").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,5 +1,4 @@
 use crate::utils::datetime::MIDNIGHT;
-use calamine::{open_workbook, Data, Reader, Xlsx};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
 use serde::{Deserialize, Serialize};
@@ -8,6 +7,9 @@ use std::path::Path;
 
 use crate::db::get_db;
 use crate::models::{field_mapping, import_batch, material};
+use crate::services::import_validation_service::CompiledRules;
+use crate::services::material_history_service::{self, FieldChangeRecord};
+use crate::services::source_reader_service::{read_source, SourceReadOptions, SourceRows};
 use crate::utils::temperature::calculate_temp_status;
 use crate::AppError;
 
@@ -43,6 +45,22 @@ pub struct ImportContext {
     pub mappings: Vec<FieldMappingItem>,
     pub transforms: Vec<ValueTransformRule>,
     pub header_index: HashMap<String, usize>,
+    /// 模板上的导入校验规则
+    pub rules: CompiledRules,
+    /// 首个数据行在源文件中的行号（用于错误提示）
+    pub first_line_no: usize,
+    /// 各数据行在源文件中的物理行号；为空时按首行行号顺延
+    pub line_numbers: Vec<usize>,
+}
+
+impl ImportContext {
+    /// 第 row_idx 个数据行在源文件中的行号
+    pub fn line_no(&self, row_idx: usize) -> usize {
+        self.line_numbers
+            .get(row_idx)
+            .copied()
+            .unwrap_or(self.first_line_no + row_idx)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +79,14 @@ pub struct ImportTestResult {
     pub rows: Vec<ImportTestRow>,
 }
 
+/// 行级错误工作簿导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportErrorWorkbook {
+    pub total: usize,
+    pub error_rows: usize,
+    pub file_path: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportSummary {
     pub batch_id: i32,
@@ -69,9 +95,148 @@ pub struct ImportSummary {
     pub failed: usize,
     pub skipped: usize,
     pub overwritten: usize,
+    /// 预分类：本地不存在的行
+    pub new_count: usize,
+    /// 预分类：本地已存在且导入字段未变化的行（含文件内重复行）
+    pub duplicate_count: usize,
+    /// 预分类：本地已存在且导入字段有变化的行
+    pub changed_count: usize,
     pub errors: Vec<String>,
 }
 
+/// merge 冲突模式下单个字段的取值策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldMergePolicy {
+    /// 以导入文件为准
+    #[default]
+    Source,
+    /// 保留本地值
+    KeepLocal,
+    /// 仅当本地值为空时填充
+    FillEmpty,
+}
+
+/// merge 冲突模式的字段策略，未单独配置的字段使用 default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergePolicy {
+    #[serde(default)]
+    pub default: FieldMergePolicy,
+    #[serde(default)]
+    pub fields: HashMap<String, FieldMergePolicy>,
+}
+
+impl Default for MergePolicy {
+    /// 默认以文件为准，备注只在本地为空时填充（保留计划员填写的备注）
+    fn default() -> Self {
+        Self {
+            default: FieldMergePolicy::Source,
+            fields: HashMap::from([("remarks".to_string(), FieldMergePolicy::FillEmpty)]),
+        }
+    }
+}
+
+impl MergePolicy {
+    /// 全部字段以文件为准（overwrite 模式）
+    fn source_wins() -> Self {
+        Self {
+            default: FieldMergePolicy::Source,
+            fields: HashMap::new(),
+        }
+    }
+
+    fn policy_for(&self, field: &str) -> FieldMergePolicy {
+        self.fields.get(field).copied().unwrap_or(self.default)
+    }
+
+    /// 解析 JSON 策略（缺省使用默认策略），校验字段名必须是可导入字段
+    pub fn parse(raw: Option<&str>) -> Result<Self, AppError> {
+        let Some(raw) = raw.map(str::trim).filter(|r| !r.is_empty()) else {
+            return Ok(Self::default());
+        };
+        let policy: MergePolicy = serde_json::from_str(raw)
+            .map_err(|e| AppError::InvalidInput(format!("合并策略解析失败: {}", e)))?;
+        if let Some(unknown) = policy.fields.keys().find(|f| {
+            !IMPORTED_DATA_COLUMNS
+                .iter()
+                .any(|col| sea_orm::IdenStatic::as_str(col) == f.as_str())
+        }) {
+            return Err(AppError::InvalidInput(format!(
+                "合并策略包含不可导入的字段: {}",
+                unknown
+            )));
+        }
+        Ok(policy)
+    }
+}
+
+/// 导入选项
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// skip / overwrite / merge
+    pub conflict_mode: String,
+    /// 整批在一个事务内写入，任一行失败即全部回滚
+    pub all_or_nothing: bool,
+    /// merge 模式的字段策略
+    pub merge_policy: MergePolicy,
+    /// 源文件读取选项（未设置的项取映射模板配置）
+    pub read_options: SourceReadOptions,
+}
+
+impl ImportOptions {
+    pub fn new(conflict_mode: &str) -> Self {
+        Self {
+            conflict_mode: conflict_mode.to_string(),
+            all_or_nothing: false,
+            merge_policy: MergePolicy::default(),
+            read_options: SourceReadOptions::default(),
+        }
+    }
+
+    /// 已存在钢卷的取值策略：overwrite 全部以文件为准，merge 按字段策略
+    fn effective_policy(&self) -> MergePolicy {
+        if self.conflict_mode == "merge" {
+            self.merge_policy.clone()
+        } else {
+            MergePolicy::source_wins()
+        }
+    }
+
+    fn writes_existing(&self) -> bool {
+        matches!(self.conflict_mode.as_str(), "overwrite" | "merge")
+    }
+}
+
+/// 单字段变化（from → to）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// 已存在钢卷的变化预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoilChangePreview {
+    pub line_no: usize,
+    pub coil_id: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 导入变化预览（不写库）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportChangePreview {
+    pub conflict_mode: String,
+    pub total: usize,
+    pub new_count: usize,
+    pub duplicate_count: usize,
+    pub changed_count: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+    /// 按当前冲突模式会被更新的已存在钢卷及字段变化
+    pub changed: Vec<CoilChangePreview>,
+}
+
 /// 导入文件大小上限（50 MB）
 const MAX_IMPORT_FILE_SIZE: u64 = 50 * 1024 * 1024;
 /// 导入数据行数上限（不含表头）
@@ -108,8 +273,34 @@ pub async fn import_from_excel(
     mapping_id: Option<i32>,
     conflict_mode: &str,
 ) -> Result<ImportSummary, AppError> {
-    check_file_size(file_path)?;
+    import_file_with_source(
+        file_path,
+        mapping_id,
+        &ImportOptions::new(conflict_mode),
+        None,
+    )
+    .await
+}
+
+/// 带来源标记的导入：source 非空时写入批次备注，且失败批次保留为 failed（含错误信息）便于追溯
+pub async fn import_file_with_source(
+    file_path: &str,
+    mapping_id: Option<i32>,
+    options: &ImportOptions,
+    source: Option<&str>,
+) -> Result<ImportSummary, AppError> {
+    import_file_tracked(file_path, mapping_id, options, source)
+        .await?
+        .1
+}
 
+/// 同 import_file_with_source，但无论导入成败都返回所建批次的 id；仅批次创建失败时返回外层错误
+pub async fn import_file_tracked(
+    file_path: &str,
+    mapping_id: Option<i32>,
+    options: &ImportOptions,
+    source: Option<&str>,
+) -> Result<(i32, Result<ImportSummary, AppError>), AppError> {
     // 提取文件名
     let file_name = Path::new(file_path)
         .file_name()
@@ -118,37 +309,37 @@ pub async fn import_from_excel(
         .to_string();
 
     // 生成批次号并创建批次记录
-    let batch_id = create_import_batch(&file_name, conflict_mode).await?;
-
-    let path = Path::new(file_path);
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
-    let result = match ext.as_str() {
-        "xlsx" | "xls" => {
-            import_excel_with_batch(file_path, mapping_id, conflict_mode, batch_id).await
-        }
-        "csv" => import_csv_with_batch(file_path, mapping_id, conflict_mode, batch_id).await,
-        _ => Err(AppError::FileFormatError(format!(
-            "不支持的文件格式: .{}，请使用 .xlsx、.xls 或 .csv 文件",
-            ext
-        ))),
-    };
+    let batch_id = create_import_batch(&file_name, &options.conflict_mode).await?;
+
+    let result = import_with_batch(file_path, mapping_id, batch_id, options).await;
 
     // 更新批次统计
+    let db = get_db();
+    use sea_orm::prelude::Expr;
     match &result {
         Ok(summary) => {
             update_import_batch(batch_id, summary).await.ok();
+            if let Some(source) = source {
+                import_batch::Entity::update_many()
+                    .col_expr(import_batch::Column::Remarks, Expr::value(source))
+                    .filter(import_batch::Column::Id.eq(batch_id))
+                    .exec(db)
+                    .await
+                    .ok();
+            }
         }
-        Err(_) => {
-            // 导入失败，标记批次为 deleted
-            let db = get_db();
-            use sea_orm::prelude::Expr;
-            import_batch::Entity::update_many()
-                .col_expr(import_batch::Column::Status, Expr::value("deleted"))
+        Err(err) => {
+            // 导入失败：有来源标记时保留为 failed 供追溯，否则标记为 deleted
+            let (status, remarks) = match source {
+                Some(source) => ("failed", Some(format!("{}: {}", source, err))),
+                None => ("deleted", None),
+            };
+            let mut update = import_batch::Entity::update_many()
+                .col_expr(import_batch::Column::Status, Expr::value(status));
+            if let Some(remarks) = remarks {
+                update = update.col_expr(import_batch::Column::Remarks, Expr::value(remarks));
+            }
+            update
                 .filter(import_batch::Column::Id.eq(batch_id))
                 .exec(db)
                 .await
@@ -156,7 +347,7 @@ pub async fn import_from_excel(
         }
     }
 
-    result
+    Ok((batch_id, result))
 }
 
 pub async fn test_import_from_file(
@@ -164,155 +355,63 @@ pub async fn test_import_from_file(
     mapping_id: Option<i32>,
     mappings_override: Option<&str>,
     transforms_override: Option<&str>,
+    rules_override: Option<&str>,
+    read_options: Option<&SourceReadOptions>,
     sample_limit: Option<usize>,
 ) -> Result<ImportTestResult, AppError> {
-    check_file_size(file_path)?;
-    let path = Path::new(file_path);
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
-    let (headers, data_rows) = match ext.as_str() {
-        "xlsx" | "xls" => read_excel_rows(file_path)?,
-        "csv" => read_csv_rows(file_path)?,
-        _ => {
-            return Err(AppError::FileFormatError(format!(
-                "不支持的文件格式: .{}，请使用 .xlsx、.xls 或 .csv 文件",
-                ext
-            )))
-        }
-    };
-
-    let context =
-        build_import_context(&headers, mapping_id, mappings_override, transforms_override).await?;
+    let source = read_import_source(file_path, mapping_id, read_options).await?;
+    let mut context = build_import_context(
+        &source.headers,
+        mapping_id,
+        mappings_override,
+        transforms_override,
+        rules_override,
+    )
+    .await?;
+    context.first_line_no = source.first_line_no;
+    context.line_numbers = source.line_numbers.clone();
 
     let limit = sample_limit.unwrap_or(20).clamp(1, 200);
-    Ok(process_rows_dry_run(&data_rows, &context, limit))
+    Ok(process_rows_dry_run(&source.rows, &context, limit))
 }
 
-fn read_excel_rows(file_path: &str) -> Result<(Vec<String>, Vec<Vec<String>>), AppError> {
-    let mut workbook: Xlsx<_> = open_workbook(file_path)
-        .map_err(|e| AppError::FileFormatError(format!("无法打开Excel文件: {}", e)))?;
-
-    let sheet_name = workbook
-        .sheet_names()
-        .first()
-        .cloned()
-        .ok_or_else(|| AppError::FileFormatError("Excel文件中没有工作表".to_string()))?;
-
-    let range = workbook
-        .worksheet_range(&sheet_name)
-        .map_err(|e| AppError::FileFormatError(format!("无法读取工作表: {}", e)))?;
-
-    let rows: Vec<Vec<String>> = range
-        .rows()
-        .map(|row| row.iter().map(cell_to_string).collect())
-        .collect();
-
-    if rows.is_empty() {
-        return Err(AppError::FileFormatError("Excel文件为空".to_string()));
-    }
-
-    let headers = rows[0].clone();
-    let data_rows = rows[1..].to_vec();
-    check_row_count(data_rows.len())?;
-    Ok((headers, data_rows))
-}
-
-fn read_csv_rows(file_path: &str) -> Result<(Vec<String>, Vec<Vec<String>>), AppError> {
-    let mut reader = csv::Reader::from_path(file_path)
-        .map_err(|e| AppError::FileFormatError(format!("无法打开CSV文件: {}", e)))?;
-
-    let headers: Vec<String> = reader
-        .headers()
-        .map_err(|e| AppError::FileFormatError(format!("无法读取CSV表头: {}", e)))?
-        .iter()
-        .map(|h| h.to_string())
-        .collect();
-
-    if headers.is_empty() {
-        return Err(AppError::FileFormatError("CSV文件为空".to_string()));
-    }
-
-    let data_rows: Vec<Vec<String>> = reader
-        .records()
-        .filter_map(|r| r.ok())
-        .map(|record| record.iter().map(|f| f.to_string()).collect())
-        .collect();
-
-    check_row_count(data_rows.len())?;
-    Ok((headers, data_rows))
-}
-
-/// Excel 文件导入
-async fn import_excel_with_batch(
+/// 读取导入源文件：显式传入的读取选项优先，未设置的项取映射模板上的配置
+async fn read_import_source(
     file_path: &str,
     mapping_id: Option<i32>,
-    conflict_mode: &str,
-    batch_id: i32,
-) -> Result<ImportSummary, AppError> {
-    let mut workbook: Xlsx<_> = open_workbook(file_path)
-        .map_err(|e| AppError::FileFormatError(format!("无法打开Excel文件: {}", e)))?;
-
-    let sheet_name = workbook
-        .sheet_names()
-        .first()
+    read_options: Option<&SourceReadOptions>,
+) -> Result<SourceRows, AppError> {
+    check_file_size(file_path)?;
+    let template_options = match mapping_id {
+        Some(id) => field_mapping::Entity::find_by_id(id)
+            .one(get_db())
+            .await?
+            .map(|m| SourceReadOptions::from(&m))
+            .unwrap_or_default(),
+        None => SourceReadOptions::default(),
+    };
+    let options = read_options
         .cloned()
-        .ok_or_else(|| AppError::FileFormatError("Excel文件中没有工作表".to_string()))?;
-
-    let range = workbook
-        .worksheet_range(&sheet_name)
-        .map_err(|e| AppError::FileFormatError(format!("无法读取工作表: {}", e)))?;
-
-    let rows: Vec<Vec<String>> = range
-        .rows()
-        .map(|row| row.iter().map(cell_to_string).collect())
-        .collect();
-
-    if rows.is_empty() {
-        return Err(AppError::FileFormatError("Excel文件为空".to_string()));
-    }
-
-    let headers = &rows[0];
-    let data_rows = &rows[1..];
-
-    let context = build_import_context(headers, mapping_id, None, None).await?;
-
-    process_rows(data_rows, &context, conflict_mode, batch_id).await
+        .unwrap_or_default()
+        .or(template_options);
+    let source = read_source(file_path, &options)?;
+    check_row_count(source.rows.len())?;
+    Ok(source)
 }
 
-/// CSV 文件导入
-async fn import_csv_with_batch(
+/// 读取源文件并按批次导入
+async fn import_with_batch(
     file_path: &str,
     mapping_id: Option<i32>,
-    conflict_mode: &str,
     batch_id: i32,
+    options: &ImportOptions,
 ) -> Result<ImportSummary, AppError> {
-    let mut reader = csv::Reader::from_path(file_path)
-        .map_err(|e| AppError::FileFormatError(format!("无法打开CSV文件: {}", e)))?;
-
-    let headers: Vec<String> = reader
-        .headers()
-        .map_err(|e| AppError::FileFormatError(format!("无法读取CSV表头: {}", e)))?
-        .iter()
-        .map(|h| h.to_string())
-        .collect();
-
-    let data_rows: Vec<Vec<String>> = reader
-        .records()
-        .filter_map(|r| r.ok())
-        .map(|record| record.iter().map(|f| f.to_string()).collect())
-        .collect();
-
-    if headers.is_empty() {
-        return Err(AppError::FileFormatError("CSV文件为空".to_string()));
-    }
+    let source = read_import_source(file_path, mapping_id, Some(&options.read_options)).await?;
+    let mut context = build_import_context(&source.headers, mapping_id, None, None, None).await?;
+    context.first_line_no = source.first_line_no;
+    context.line_numbers = source.line_numbers.clone();
 
-    let context = build_import_context(&headers, mapping_id, None, None).await?;
-
-    process_rows(&data_rows, &context, conflict_mode, batch_id).await
+    process_rows(&source.rows, &context, batch_id, options).await
 }
 
 /// 构建导入上下文（解析映射配置）
@@ -321,10 +420,12 @@ async fn build_import_context(
     mapping_id: Option<i32>,
     mappings_override: Option<&str>,
     transforms_override: Option<&str>,
+    rules_override: Option<&str>,
 ) -> Result<ImportContext, AppError> {
     let db = get_db();
 
-    // 获取映射配置
+    // 获取映射配置（校验规则：显式传入优先，其次取模板上的规则）
+    let mut template_rules: Option<String> = None;
     let (mappings, mut transforms) = if let Some(raw_mappings) = mappings_override {
         let m: Vec<FieldMappingItem> = serde_json::from_str(raw_mappings)
             .map_err(|e| AppError::DataConversionError(format!("映射配置解析失败: {}", e)))?;
@@ -352,6 +453,7 @@ async fn build_import_context(
         } else {
             vec![]
         };
+        template_rules = mapping.validation_rules;
 
         (m, t)
     } else {
@@ -366,11 +468,15 @@ async fn build_import_context(
     }
 
     merge_inline_transform_rules(&mappings, &mut transforms);
+    let rules = CompiledRules::parse(rules_override.or(template_rules.as_deref()))?;
 
     Ok(ImportContext {
         mappings,
         transforms,
         header_index,
+        rules,
+        first_line_no: 2,
+        line_numbers: Vec::new(),
     })
 }
 
@@ -398,6 +504,7 @@ fn build_default_mappings(headers: &[String]) -> Vec<FieldMappingItem> {
         ("周交期", "weekly_delivery"),
         ("集批代码", "batch_code"),
         ("卷取时间", "coiling_time"),
+        ("卷取温度", "coiling_temp"),
         ("库龄", "storage_days"),
         ("库位", "storage_loc"),
         ("交期", "due_date"),
@@ -422,6 +529,7 @@ fn build_default_mappings(headers: &[String]) -> Vec<FieldMappingItem> {
         ("weekly_delivery", "weekly_delivery"),
         ("batch_code", "batch_code"),
         ("coiling_time", "coiling_time"),
+        ("coiling_temp", "coiling_temp"),
         ("storage_days", "storage_days"),
         ("storage_loc", "storage_loc"),
         ("due_date", "due_date"),
@@ -444,84 +552,254 @@ fn build_default_mappings(headers: &[String]) -> Vec<FieldMappingItem> {
         .collect()
 }
 
-/// 处理数据行，批量插入数据库
-async fn process_rows(
+/// 单次批量写入的行数（material 约 40 列，控制在 SQLite 绑定参数上限内）
+const WRITE_CHUNK_SIZE: usize = 200;
+/// 预分类时按 coil_id 批量查询已有记录的分块大小
+const LOOKUP_CHUNK_SIZE: usize = 500;
+
+/// 预分类比较、覆盖/合并时更新的导入字段（不含 status、priority_* 等排程/人工字段）
+pub(crate) const IMPORTED_DATA_COLUMNS: [material::Column; 23] = [
+    material::Column::SteelGrade,
+    material::Column::Thickness,
+    material::Column::Width,
+    material::Column::Weight,
+    material::Column::CoilingTime,
+    material::Column::CoilingTemp,
+    material::Column::HardnessLevel,
+    material::Column::SurfaceLevel,
+    material::Column::RoughnessReq,
+    material::Column::ElongationReq,
+    material::Column::ProductType,
+    material::Column::ContractNo,
+    material::Column::CustomerName,
+    material::Column::CustomerCode,
+    material::Column::ContractAttr,
+    material::Column::ContractNature,
+    material::Column::ExportFlag,
+    material::Column::WeeklyDelivery,
+    material::Column::BatchCode,
+    material::Column::DueDate,
+    material::Column::StorageDays,
+    material::Column::StorageLoc,
+    material::Column::Remarks,
+];
+
+/// 预分类结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowClass {
+    /// 本地不存在
+    New,
+    /// 本地已存在且导入字段完全一致，或文件内重复
+    Duplicate,
+    /// 本地已存在但导入字段有变化
+    Changed,
+}
+
+/// 已通过转换、待写入的行
+struct PreparedRow {
+    line_no: usize,
+    coil_id: String,
+    model: material::ActiveModel,
+    class: RowClass,
+    /// 已存在钢卷相对本地的字段变化
+    changes: Vec<FieldChange>,
+    /// 已存在钢卷的本地 id
+    material_id: Option<i32>,
+}
+
+/// 字段值转为展示字符串（空值为 None）
+pub(crate) fn value_to_display(value: &sea_orm::Value) -> Option<String> {
+    use sea_orm::Value;
+    match value {
+        Value::String(v) => v.as_ref().map(|s| s.to_string()),
+        Value::Double(v) => v.map(|n| n.to_string()),
+        Value::Float(v) => v.map(|n| n.to_string()),
+        Value::Int(v) => v.map(|n| n.to_string()),
+        Value::BigInt(v) => v.map(|n| n.to_string()),
+        Value::Bool(v) => v.map(|b| b.to_string()),
+        Value::ChronoDateTimeUtc(v) => v
+            .as_ref()
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+        other => Some(format!("{:?}", other)),
+    }
+}
+
+fn is_empty_value(value: &sea_orm::Value) -> bool {
+    value_to_display(value)
+        .map(|s| s.trim().is_empty())
+        .unwrap_or(true)
+}
+
+/// 按字段策略将导入值与本地记录合并：incoming 被改写为合并后的值，返回相对本地的字段变化
+fn resolve_against_existing(
+    existing: &material::Model,
+    incoming: &mut material::ActiveModel,
+    policy: &MergePolicy,
+) -> Vec<FieldChange> {
+    use sea_orm::{ActiveValue, ModelTrait};
+
+    let mut changes = Vec::new();
+    for col in IMPORTED_DATA_COLUMNS {
+        let local = existing.get(col);
+        let imported = match incoming.get(col) {
+            ActiveValue::Set(v) | ActiveValue::Unchanged(v) => v,
+            ActiveValue::NotSet => continue,
+        };
+        let field = sea_orm::IdenStatic::as_str(&col);
+        let chosen = match policy.policy_for(field) {
+            FieldMergePolicy::Source => imported,
+            FieldMergePolicy::KeepLocal => local.clone(),
+            FieldMergePolicy::FillEmpty if is_empty_value(&local) => imported,
+            FieldMergePolicy::FillEmpty => local.clone(),
+        };
+        if chosen != local {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                from: value_to_display(&local),
+                to: value_to_display(&chosen),
+            });
+        }
+        incoming.set(col, chosen);
+    }
+    // 卷取时间未变化时保留本地适温状态（由适温刷新维护）
+    if !changes.iter().any(|c| c.field == "coiling_time") {
+        incoming.temp_status = Set(existing.temp_status.clone());
+        incoming.temp_wait_days = Set(existing.temp_wait_days);
+        incoming.is_tempered = Set(existing.is_tempered);
+    }
+    changes
+}
+
+/// 按 coil_id 分块查询已有材料
+async fn load_existing_materials<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+    coil_ids: &[String],
+) -> Result<HashMap<String, material::Model>, AppError> {
+    let mut existing = HashMap::new();
+    for chunk in coil_ids.chunks(LOOKUP_CHUNK_SIZE) {
+        let found = material::Entity::find()
+            .filter(material::Column::CoilId.is_in(chunk.to_vec()))
+            .all(conn)
+            .await?;
+        existing.extend(found.into_iter().map(|m| (m.coil_id.clone(), m)));
+    }
+    Ok(existing)
+}
+
+/// 多行 INSERT；coil_id 冲突时 overwrite 为 upsert（只更新导入字段），否则忽略。返回实际影响行数
+async fn write_rows<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+    models: Vec<material::ActiveModel>,
+    upsert: bool,
+) -> Result<u64, sea_orm::DbErr> {
+    use sea_orm::sea_query::OnConflict;
+
+    if models.is_empty() {
+        return Ok(0);
+    }
+    let on_conflict = if upsert {
+        let mut columns = IMPORTED_DATA_COLUMNS.to_vec();
+        columns.extend([
+            material::Column::TempStatus,
+            material::Column::TempWaitDays,
+            material::Column::IsTempered,
+            material::Column::ImportBatchId,
+            material::Column::UpdatedAt,
+        ]);
+        OnConflict::column(material::Column::CoilId)
+            .update_columns(columns)
+            .to_owned()
+    } else {
+        OnConflict::column(material::Column::CoilId)
+            .do_nothing()
+            .to_owned()
+    };
+    material::Entity::insert_many(models)
+        .on_conflict(on_conflict)
+        .exec_without_returning(conn)
+        .await
+}
+
+/// 分块写入；非整批模式下某块失败时逐行重试以定位错误行，返回 (写入行数, 失败行号)
+async fn write_prepared<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+    rows: &[&PreparedRow],
+    upsert: bool,
+    all_or_nothing: bool,
+    errors: &mut Vec<String>,
+) -> Result<(usize, Vec<usize>), AppError> {
+    let mut written = 0usize;
+    let mut failed_lines = Vec::new();
+    for chunk in rows.chunks(WRITE_CHUNK_SIZE) {
+        let models: Vec<material::ActiveModel> = chunk.iter().map(|r| r.model.clone()).collect();
+        match write_rows(conn, models, upsert).await {
+            Ok(n) => written += n as usize,
+            Err(e) if all_or_nothing => {
+                return Err(AppError::ConstraintViolation(format!(
+                    "第{}-{}行批量写入失败，已整体回滚: {}",
+                    chunk.first().map(|r| r.line_no).unwrap_or(0),
+                    chunk.last().map(|r| r.line_no).unwrap_or(0),
+                    e
+                )));
+            }
+            Err(_) => {
+                for row in chunk {
+                    match write_rows(conn, vec![row.model.clone()], upsert).await {
+                        Ok(n) => written += n as usize,
+                        Err(e) => {
+                            let msg = format!("第{}行写入失败: {}", row.line_no, e);
+                            log::warn!("{}", msg);
+                            errors.push(msg);
+                            failed_lines.push(row.line_no);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok((written, failed_lines))
+}
+
+/// 转换数据行并按 coil_id 去重；文件内重复行：覆盖类模式以后出现的行为准，skip 保留首行
+fn prepare_rows(
     rows: &[Vec<String>],
     context: &ImportContext,
-    conflict_mode: &str,
     batch_id: i32,
-) -> Result<ImportSummary, AppError> {
-    let db = get_db();
-    let total = rows.len();
-    let mut success = 0usize;
-    let mut skipped = 0usize;
-    let mut overwritten = 0usize;
+    keep_last: bool,
+) -> (Vec<PreparedRow>, usize, Vec<String>) {
+    let mut prepared: Vec<PreparedRow> = Vec::new();
+    let mut index_by_coil: HashMap<String, usize> = HashMap::new();
+    let mut in_file_duplicates = 0usize;
     let mut errors = Vec::new();
-
     for (row_idx, row) in rows.iter().enumerate() {
-        let line_no = row_idx + 2; // 数据从第2行开始（第1行是表头）
-
+        let line_no = context.line_no(row_idx);
         match build_material_model(row, context, line_no) {
-            Ok(mut active_model) => {
-                // 设置批次 ID
-                active_model.import_batch_id = Set(Some(batch_id));
-
-                match active_model.insert(db).await {
-                    Ok(_) => {
-                        success += 1;
-                    }
-                    Err(e) => {
-                        let err_str = e.to_string();
-                        let is_unique_conflict = err_str.contains("UNIQUE")
-                            || err_str.contains("unique")
-                            || err_str.contains("duplicate");
-
-                        if is_unique_conflict {
-                            match conflict_mode {
-                                "skip" => {
-                                    skipped += 1;
-                                }
-                                "overwrite" => {
-                                    // 提取 coil_id 值
-                                    let coil_id = extract_coil_id(row, context);
-                                    match coil_id {
-                                        Some(cid) => {
-                                            match overwrite_material(
-                                                db, &cid, row, context, line_no, batch_id,
-                                            )
-                                            .await
-                                            {
-                                                Ok(_) => {
-                                                    overwritten += 1;
-                                                }
-                                                Err(oe) => {
-                                                    let msg = format!(
-                                                        "第{}行覆盖更新失败: {}",
-                                                        line_no, oe
-                                                    );
-                                                    log::warn!("{}", msg);
-                                                    errors.push(msg);
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            let msg =
-                                                format!("第{}行覆盖失败: 无法提取coil_id", line_no);
-                                            log::warn!("{}", msg);
-                                            errors.push(msg);
-                                        }
-                                    }
-                                }
-                                _ => {
-                                    skipped += 1;
-                                }
-                            }
-                        } else {
-                            let msg = format!("第{}行插入失败: {}", line_no, e);
-                            log::warn!("{}", msg);
-                            errors.push(msg);
+            Ok(mut model) => {
+                model.import_batch_id = Set(Some(batch_id));
+                model.updated_at = Set(Some(Utc::now()));
+                let coil_id = match &model.coil_id {
+                    sea_orm::ActiveValue::Set(v) => v.clone(),
+                    _ => String::new(),
+                };
+                match index_by_coil.get(&coil_id) {
+                    Some(&idx) => {
+                        in_file_duplicates += 1;
+                        if keep_last {
+                            prepared[idx].line_no = line_no;
+                            prepared[idx].model = model;
                         }
                     }
+                    None => {
+                        index_by_coil.insert(coil_id.clone(), prepared.len());
+                        prepared.push(PreparedRow {
+                            line_no,
+                            coil_id,
+                            model,
+                            class: RowClass::New,
+                            changes: Vec::new(),
+                            material_id: None,
+                        });
+                    }
                 }
             }
             Err(e) => {
@@ -531,11 +809,127 @@ async fn process_rows(
             }
         }
     }
+    (prepared, in_file_duplicates, errors)
+}
+
+/// 预分类：查询本地已有记录，按冲突模式的取值策略计算字段变化
+///
+/// 写入路径须传入写事务连接，保证分类结果与随后的写入看到同一份数据。
+async fn classify_rows<C: sea_orm::ConnectionTrait>(
+    conn: &C,
+    prepared: &mut [PreparedRow],
+    options: &ImportOptions,
+) -> Result<(), AppError> {
+    let coil_ids: Vec<String> = prepared.iter().map(|r| r.coil_id.clone()).collect();
+    let existing = load_existing_materials(conn, &coil_ids).await?;
+    let policy = options.effective_policy();
+    for row in prepared.iter_mut() {
+        match existing.get(&row.coil_id) {
+            None => row.class = RowClass::New,
+            Some(m) => {
+                row.material_id = Some(m.id);
+                row.changes = resolve_against_existing(m, &mut row.model, &policy);
+                row.class = if row.changes.is_empty() {
+                    RowClass::Duplicate
+                } else {
+                    RowClass::Changed
+                };
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 处理数据行：先转换并按 coil_id 预分类（新增/重复/变化），再分块批量写入
+///
+/// all_or_nothing 为 true 时任一行转换或写入失败即整体回滚并返回错误；
+/// 否则失败行记入 errors，其余行照常写入。
+async fn process_rows(
+    rows: &[Vec<String>],
+    context: &ImportContext,
+    batch_id: i32,
+    options: &ImportOptions,
+) -> Result<ImportSummary, AppError> {
+    use sea_orm::TransactionTrait;
+
+    let db = get_db();
+    let total = rows.len();
+    let all_or_nothing = options.all_or_nothing;
+
+    // 1. 转换与文件内去重
+    let (mut prepared, in_file_duplicates, mut errors) =
+        prepare_rows(rows, context, batch_id, options.writes_existing());
+    if all_or_nothing && !errors.is_empty() {
+        return Err(AppError::DataConversionError(format!(
+            "整批导入中止，{} 行转换失败: {}",
+            errors.len(),
+            errors
+                .iter()
+                .take(5)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("; ")
+        )));
+    }
 
-    let failed = total - success - skipped - overwritten;
+    // 2. 在写事务内预分类
+    let tx = db.begin().await?;
+    classify_rows(&tx, &mut prepared, options).await?;
+    let new_rows: Vec<&PreparedRow> = prepared
+        .iter()
+        .filter(|r| r.class == RowClass::New)
+        .collect();
+    let changed_rows: Vec<&PreparedRow> = prepared
+        .iter()
+        .filter(|r| r.class == RowClass::Changed)
+        .collect();
+    let new_count = new_rows.len();
+    let changed_count = changed_rows.len();
+    let duplicate_count = prepared.len() - new_count - changed_count + in_file_duplicates;
+
+    // 3. 同一事务写入：整批模式任一失败即回滚；否则失败块逐行重试（SQLite 下失败语句不会中止事务）
+    let (success, _) = write_prepared(&tx, &new_rows, false, all_or_nothing, &mut errors).await?;
+    let overwritten = if options.writes_existing() {
+        let (written, failed_lines) =
+            write_prepared(&tx, &changed_rows, true, all_or_nothing, &mut errors).await?;
+        // 已写入的变化行按字段记入变更历史
+        let records: Vec<FieldChangeRecord> = changed_rows
+            .iter()
+            .filter(|r| !failed_lines.contains(&r.line_no))
+            .filter_map(|r| r.material_id.map(|id| (id, *r)))
+            .flat_map(|(id, r)| {
+                r.changes.iter().map(move |c| FieldChangeRecord {
+                    material_id: id,
+                    coil_id: r.coil_id.clone(),
+                    field: c.field.clone(),
+                    old_value: c.from.clone(),
+                    new_value: c.to.clone(),
+                })
+            })
+            .collect();
+        material_history_service::record_changes(
+            &tx,
+            &records,
+            material_history_service::SOURCE_IMPORT,
+            Some(&format!("batch:{}", batch_id)),
+        )
+        .await?;
+        written
+    } else {
+        0
+    };
+    tx.commit().await?;
+
+    // 跳过 = 未变化/文件内重复/skip 模式下的变化行，以及写入时因并发已存在而被忽略的新增行
+    let failed = errors.len();
+    let skipped = total - success - overwritten - failed;
     log::info!(
-        "导入完成: 总计={}, 成功={}, 跳过={}, 覆盖={}, 失败={}",
+        "导入完成: 模式={}, 总计={}, 新增={}, 变化={}, 重复={}, 成功={}, 跳过={}, 覆盖={}, 失败={}",
+        options.conflict_mode,
         total,
+        new_count,
+        changed_count,
+        duplicate_count,
         success,
         skipped,
         overwritten,
@@ -549,10 +943,58 @@ async fn process_rows(
         failed,
         skipped,
         overwritten,
+        new_count,
+        duplicate_count,
+        changed_count,
         errors,
     })
 }
 
+/// 预览导入对已存在钢卷的字段变化（from → to），不写库
+pub async fn preview_import_changes(
+    file_path: &str,
+    mapping_id: Option<i32>,
+    options: &ImportOptions,
+) -> Result<ImportChangePreview, AppError> {
+    let source = read_import_source(file_path, mapping_id, Some(&options.read_options)).await?;
+    let data_rows = &source.rows;
+    let mut context = build_import_context(&source.headers, mapping_id, None, None, None).await?;
+    context.first_line_no = source.first_line_no;
+    context.line_numbers = source.line_numbers.clone();
+
+    let (mut prepared, _, errors) = prepare_rows(data_rows, &context, 0, options.writes_existing());
+    classify_rows(get_db(), &mut prepared, options).await?;
+    let new_count = prepared.iter().filter(|r| r.class == RowClass::New).count();
+    let changed: Vec<CoilChangePreview> = prepared
+        .into_iter()
+        .filter(|r| r.class == RowClass::Changed)
+        .map(|r| CoilChangePreview {
+            line_no: r.line_no,
+            coil_id: r.coil_id,
+            changes: r.changes,
+        })
+        .collect();
+    let changed_count = changed.len();
+    let failed = errors.len();
+    let duplicate_count = data_rows.len() - new_count - changed_count - failed;
+
+    Ok(ImportChangePreview {
+        conflict_mode: options.conflict_mode.clone(),
+        total: data_rows.len(),
+        new_count,
+        duplicate_count,
+        changed_count,
+        failed,
+        errors,
+        // skip 模式不会更新已存在钢卷，仅给出计数
+        changed: if options.writes_existing() {
+            changed
+        } else {
+            Vec::new()
+        },
+    })
+}
+
 fn process_rows_dry_run(
     rows: &[Vec<String>],
     context: &ImportContext,
@@ -564,7 +1006,7 @@ fn process_rows_dry_run(
     let mut preview_rows = Vec::new();
 
     for (row_idx, row) in rows.iter().enumerate() {
-        let line_no = row_idx + 2;
+        let line_no = context.line_no(row_idx);
         match build_material_model(row, context, line_no) {
             Ok(_) => {
                 success += 1;
@@ -585,7 +1027,7 @@ fn process_rows_dry_run(
                     preview_rows.push(ImportTestRow {
                         line_no,
                         status: "error".to_string(),
-                        message: err,
+                        message: err.message,
                     });
                 }
             }
@@ -602,6 +1044,126 @@ fn process_rows_dry_run(
     }
 }
 
+/// 定位一行的错误及涉及的目标字段：先映射/转换错误，再校验规则，最后建模错误
+fn diagnose_row(
+    row: &[String],
+    context: &ImportContext,
+    line_no: usize,
+) -> Option<(String, Vec<String>)> {
+    build_material_model(row, context, line_no)
+        .err()
+        .map(|e| (e.message, e.fields))
+}
+
+/// 目标字段对应的源文件列下标
+fn source_columns(targets: &[String], context: &ImportContext) -> Vec<usize> {
+    let mut cols: Vec<usize> = context
+        .mappings
+        .iter()
+        .filter(|m| targets.contains(&m.target_field))
+        .filter_map(|m| context.header_index.get(m.source_field.trim()).copied())
+        .collect();
+    cols.sort_unstable();
+    cols.dedup();
+    cols
+}
+
+/// 导出行级错误工作簿：仅包含未通过的行（行号 + 原始列 + 错误信息），涉及的单元格标红
+pub async fn export_import_error_workbook(
+    file_path: &str,
+    mapping_id: Option<i32>,
+    mappings_override: Option<&str>,
+    transforms_override: Option<&str>,
+    rules_override: Option<&str>,
+    read_options: Option<&SourceReadOptions>,
+    output_path: &str,
+) -> Result<ImportErrorWorkbook, AppError> {
+    use rust_xlsxwriter::{Color, Format, Workbook};
+
+    let source = read_import_source(file_path, mapping_id, read_options).await?;
+    let (headers, data_rows) = (&source.headers, &source.rows);
+    let mut context = build_import_context(
+        headers,
+        mapping_id,
+        mappings_override,
+        transforms_override,
+        rules_override,
+    )
+    .await?;
+    context.first_line_no = source.first_line_no;
+    context.line_numbers = source.line_numbers.clone();
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet
+        .set_name("错误明细")
+        .map_err(|e| AppError::FileError(format!("创建工作表失败: {}", e)))?;
+
+    let header_fmt = Format::new()
+        .set_bold()
+        .set_font_size(11.0)
+        .set_background_color(Color::RGB(0x1677FF))
+        .set_font_color(Color::White);
+    let data_fmt = Format::new().set_font_size(10.0);
+    let error_cell_fmt = Format::new()
+        .set_font_size(10.0)
+        .set_background_color(Color::RGB(0xFFC7CE))
+        .set_font_color(Color::RGB(0x9C0006));
+    let xlsx_err =
+        |e: rust_xlsxwriter::XlsxError| AppError::FileError(format!("写入Excel失败: {}", e));
+
+    let message_col = (headers.len() + 1) as u16;
+    sheet
+        .write_string_with_format(0, 0, "行号", &header_fmt)
+        .map_err(xlsx_err)?;
+    for (i, h) in headers.iter().enumerate() {
+        sheet
+            .write_string_with_format(0, (i + 1) as u16, h, &header_fmt)
+            .map_err(xlsx_err)?;
+    }
+    sheet
+        .write_string_with_format(0, message_col, "错误信息", &header_fmt)
+        .map_err(xlsx_err)?;
+
+    let mut out_row = 0u32;
+    for (row_idx, row) in data_rows.iter().enumerate() {
+        let line_no = context.line_no(row_idx);
+        let Some((message, targets)) = diagnose_row(row, &context, line_no) else {
+            continue;
+        };
+        out_row += 1;
+        let bad_cols = source_columns(&targets, &context);
+        sheet
+            .write_number_with_format(out_row, 0, line_no as f64, &data_fmt)
+            .map_err(xlsx_err)?;
+        for col in 0..headers.len() {
+            let value = row.get(col).map(String::as_str).unwrap_or("");
+            let fmt = if bad_cols.contains(&col) {
+                &error_cell_fmt
+            } else {
+                &data_fmt
+            };
+            sheet
+                .write_string_with_format(out_row, (col + 1) as u16, value, fmt)
+                .map_err(xlsx_err)?;
+        }
+        sheet
+            .write_string_with_format(out_row, message_col, &message, &data_fmt)
+            .map_err(xlsx_err)?;
+    }
+    sheet.set_column_width(message_col, 60).map_err(xlsx_err)?;
+
+    workbook
+        .save(output_path)
+        .map_err(|e| AppError::FileError(format!("保存Excel失败: {}", e)))?;
+
+    Ok(ImportErrorWorkbook {
+        total: data_rows.len(),
+        error_rows: out_row as usize,
+        file_path: output_path.to_string(),
+    })
+}
+
 // ─── 批次管理辅助函数 ───
 
 /// 创建导入批次记录，返回 batch_id
@@ -676,106 +1238,34 @@ async fn update_import_batch(batch_id: i32, summary: &ImportSummary) -> Result<(
     Ok(())
 }
 
-/// 从行数据中提取 coil_id 值
-fn extract_coil_id(row: &[String], context: &ImportContext) -> Option<String> {
-    for mapping in &context.mappings {
-        if mapping.target_field == "coil_id" {
-            if let Some(&col_idx) = context.header_index.get(&mapping.source_field) {
-                if col_idx < row.len() {
-                    let val = row[col_idx].trim().to_string();
-                    if !val.is_empty() {
-                        return Some(val);
-                    }
-                }
-            }
+/// 行级转换/校验错误，携带涉及的目标字段（用于错误工作簿定位源单元格）
+#[derive(Debug, Clone, PartialEq)]
+struct RowError {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl RowError {
+    fn at(message: String, field: &str) -> Self {
+        Self {
+            message,
+            fields: vec![field.to_string()],
         }
     }
-    None
 }
 
-/// 覆盖更新已存在的材料记录（保留 id、status、priority 等字段）
-async fn overwrite_material(
-    db: &sea_orm::DatabaseConnection,
-    coil_id: &str,
-    row: &[String],
-    context: &ImportContext,
-    line_no: usize,
-    batch_id: i32,
-) -> Result<(), String> {
-    use sea_orm::*;
-
-    // 查找已有记录
-    let existing = material::Entity::find()
-        .filter(material::Column::CoilId.eq(coil_id))
-        .one(db)
-        .await
-        .map_err(|e| format!("查询已有记录失败: {}", e))?
-        .ok_or_else(|| format!("未找到 coil_id={} 的记录", coil_id))?;
-
-    // 构建新的字段值
-    let new_model = build_material_model(row, context, line_no)?;
-
-    // 使用已有记录的 id 构建更新模型，只更新物理属性和客户信息
-    let mut update_model = material::ActiveModel {
-        id: Set(existing.id),
-        ..Default::default()
-    };
-
-    // 更新物理属性
-    update_model.steel_grade = new_model.steel_grade.clone();
-    update_model.thickness = new_model.thickness.clone();
-    update_model.width = new_model.width.clone();
-    update_model.weight = new_model.weight.clone();
-    update_model.coil_id = Set(coil_id.to_string());
-    update_model.coiling_time = new_model.coiling_time.clone();
-
-    // 更新可选物理属性
-    update_model.hardness_level = new_model.hardness_level.clone();
-    update_model.surface_level = new_model.surface_level.clone();
-    update_model.roughness_req = new_model.roughness_req.clone();
-    update_model.elongation_req = new_model.elongation_req.clone();
-    update_model.product_type = new_model.product_type.clone();
-
-    // 更新客户/合同信息
-    update_model.contract_no = new_model.contract_no.clone();
-    update_model.customer_name = new_model.customer_name.clone();
-    update_model.customer_code = new_model.customer_code.clone();
-    update_model.contract_attr = new_model.contract_attr.clone();
-    update_model.contract_nature = new_model.contract_nature.clone();
-    update_model.export_flag = new_model.export_flag.clone();
-    update_model.weekly_delivery = new_model.weekly_delivery.clone();
-    update_model.batch_code = new_model.batch_code.clone();
-
-    // 更新时间和元数据
-    update_model.due_date = new_model.due_date.clone();
-    update_model.storage_days = new_model.storage_days.clone();
-    update_model.storage_loc = new_model.storage_loc.clone();
-    update_model.temp_status = new_model.temp_status.clone();
-    update_model.temp_wait_days = new_model.temp_wait_days.clone();
-    update_model.is_tempered = new_model.is_tempered.clone();
-    update_model.tempered_at = new_model.tempered_at.clone();
-    update_model.remarks = new_model.remarks.clone();
-
-    // 更新批次 ID 和时间戳
-    update_model.import_batch_id = Set(Some(batch_id));
-    update_model.updated_at = Set(Some(Utc::now()));
-
-    // 注意：不更新 status, priority_*, 保留原有排程状态和手工优先级
-
-    update_model
-        .update(db)
-        .await
-        .map_err(|e| format!("更新记录失败: {}", e))?;
-
-    Ok(())
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
-/// 根据映射规则，将一行数据构建为 Material ActiveModel
-fn build_material_model(
+/// 根据映射规则，将一行数据映射为 目标字段 → 值（空值不收录）
+fn map_row_fields(
     row: &[String],
     context: &ImportContext,
     line_no: usize,
-) -> Result<material::ActiveModel, String> {
+) -> Result<HashMap<String, String>, RowError> {
     let mut fields: HashMap<String, String> = HashMap::new();
 
     for mapping in &context.mappings {
@@ -820,37 +1310,94 @@ fn build_material_model(
                     raw
                 }
             }),
-        }?;
+        }
+        .map_err(|e| RowError::at(e, &mapping.target_field))?;
 
         if !value.is_empty() {
             fields.insert(mapping.target_field.clone(), value);
         }
     }
 
+    Ok(fields)
+}
+
+/// 执行模板校验规则，多条违反合并为一条错误信息，涉及字段按出现顺序去重
+fn check_validation_rules(
+    fields: &HashMap<String, String>,
+    context: &ImportContext,
+    line_no: usize,
+) -> Result<(), RowError> {
+    let violations = context.rules.validate(fields);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let message = violations
+        .iter()
+        .map(|v| v.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut targets: Vec<String> = Vec::new();
+    for field in violations.into_iter().flat_map(|v| v.fields) {
+        if !targets.contains(&field) {
+            targets.push(field);
+        }
+    }
+    Err(RowError {
+        message: format!("第{}行: 校验未通过: {}", line_no, message),
+        fields: targets,
+    })
+}
+
+/// 根据映射规则，将一行数据构建为 Material ActiveModel
+fn build_material_model(
+    row: &[String],
+    context: &ImportContext,
+    line_no: usize,
+) -> Result<material::ActiveModel, RowError> {
+    let fields = map_row_fields(row, context, line_no)?;
+    check_validation_rules(&fields, context, line_no)?;
+
     // 必填字段校验
     let coil_id = fields
         .get("coil_id")
         .filter(|v| !v.is_empty())
-        .ok_or_else(|| format!("第{}行: 缺少钢卷号(coil_id)", line_no))?
+        .ok_or_else(|| RowError::at(format!("第{}行: 缺少钢卷号(coil_id)", line_no), "coil_id"))?
         .clone();
 
     let steel_grade = fields
         .get("steel_grade")
         .filter(|v| !v.is_empty())
-        .ok_or_else(|| format!("第{}行: 缺少钢种(steel_grade)", line_no))?
+        .ok_or_else(|| {
+            RowError::at(
+                format!("第{}行: 缺少钢种(steel_grade)", line_no),
+                "steel_grade",
+            )
+        })?
         .clone();
 
-    let thickness = parse_f64(fields.get("thickness"), "thickness", line_no)?;
-    let width = parse_f64(fields.get("width"), "width", line_no)?;
-    let weight = parse_f64(fields.get("weight"), "weight", line_no)?;
+    let number = |field: &str| {
+        parse_f64(fields.get(field), field, line_no).map_err(|e| RowError::at(e, field))
+    };
+    let thickness = number("thickness")?;
+    let width = number("width")?;
+    let weight = number("weight")?;
 
     let coiling_time_str = fields
         .get("coiling_time")
         .filter(|v| !v.is_empty())
-        .ok_or_else(|| format!("第{}行: 缺少卷取时间(coiling_time)", line_no))?;
+        .ok_or_else(|| {
+            RowError::at(
+                format!("第{}行: 缺少卷取时间(coiling_time)", line_no),
+                "coiling_time",
+            )
+        })?;
 
-    let coiling_time = parse_datetime_to_utc(coiling_time_str)
-        .ok_or_else(|| format!("第{}行: 卷取时间格式无效: {}", line_no, coiling_time_str))?;
+    let coiling_time = parse_datetime_to_utc(coiling_time_str).ok_or_else(|| {
+        RowError::at(
+            format!("第{}行: 卷取时间格式无效: {}", line_no, coiling_time_str),
+            "coiling_time",
+        )
+    })?;
 
     // 计算适温状态
     let (temp_status, temp_wait_days) = calculate_temp_status(&coiling_time);
@@ -865,6 +1412,10 @@ fn build_material_model(
         .get("elongation_req")
         .and_then(|v| v.parse::<f64>().ok());
 
+    let coiling_temp = fields
+        .get("coiling_temp")
+        .and_then(|v| v.parse::<f64>().ok());
+
     let storage_days = fields
         .get("storage_days")
         .and_then(|v| v.parse::<i32>().ok());
@@ -893,6 +1444,7 @@ fn build_material_model(
         weekly_delivery: Set(weekly_delivery),
         batch_code: Set(fields.get("batch_code").cloned()),
         coiling_time: Set(coiling_time),
+        coiling_temp: Set(coiling_temp),
         temp_status: Set(Some(temp_status)),
         temp_wait_days: Set(Some(temp_wait_days)),
         is_tempered: Set(Some(is_tempered)),
@@ -1352,7 +1904,7 @@ fn apply_date_format(
 }
 
 /// 解析日期时间字符串为 UTC DateTime
-fn parse_datetime_to_utc(s: &str) -> Option<chrono::DateTime<Utc>> {
+pub(crate) fn parse_datetime_to_utc(s: &str) -> Option<chrono::DateTime<Utc>> {
     let trimmed = s.trim();
     if trimmed.is_empty() {
         return None;
@@ -1392,7 +1944,7 @@ fn parse_datetime_to_utc(s: &str) -> Option<chrono::DateTime<Utc>> {
 }
 
 /// Excel 序列号转 DateTime
-fn excel_serial_to_datetime(serial: f64) -> Option<chrono::DateTime<Utc>> {
+pub(crate) fn excel_serial_to_datetime(serial: f64) -> Option<chrono::DateTime<Utc>> {
     // Excel 序列号基准日期: 1899-12-30
     let base = NaiveDate::from_ymd_opt(1899, 12, 30)?;
     let days = serial.trunc() as i64;
@@ -1408,36 +1960,6 @@ fn excel_serial_to_datetime(serial: f64) -> Option<chrono::DateTime<Utc>> {
     Some(Utc.from_utc_datetime(&dt))
 }
 
-/// 将 calamine 单元格值转为字符串
-fn cell_to_string(cell: &Data) -> String {
-    match cell {
-        Data::Int(v) => v.to_string(),
-        Data::Float(v) => {
-            // 避免浮点数无意义小数
-            if *v == v.trunc() && v.abs() < 1e15 {
-                format!("{:.0}", v)
-            } else {
-                v.to_string()
-            }
-        }
-        Data::String(v) => v.clone(),
-        Data::Bool(v) => v.to_string(),
-        Data::DateTime(v) => {
-            // calamine 0.26: ExcelDateTime → 转为 f64 序列号再转日期
-            let serial = v.as_f64();
-            if let Some(dt) = excel_serial_to_datetime(serial) {
-                dt.format("%Y-%m-%d %H:%M:%S").to_string()
-            } else {
-                format!("{:?}", v)
-            }
-        }
-        Data::DateTimeIso(v) => v.clone(),
-        Data::DurationIso(v) => v.clone(),
-        Data::Error(e) => format!("ERROR:{:?}", e),
-        Data::Empty => String::new(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1452,6 +1974,9 @@ mod tests {
             mappings: vec![],
             transforms: vec![],
             header_index,
+            rules: Default::default(),
+            first_line_no: 2,
+            line_numbers: Vec::new(),
         }
     }
 
@@ -1549,4 +2074,283 @@ mod tests {
             .expect_err("date format should fail");
         assert!(err.contains("日期格式不匹配"));
     }
+
+    #[tokio::test]
+    async fn bulk_import_classifies_rows_and_rolls_back_all_or_nothing() {
+        let atomic = |mode: &str| ImportOptions {
+            all_or_nothing: true,
+            ..ImportOptions::new(mode)
+        };
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let work_dir = std::env::temp_dir().join(format!("spm_bulk_import_{}", seed));
+        std::fs::create_dir_all(&work_dir).expect("创建临时目录失败");
+        let db_url = format!("sqlite:{}?mode=rwc", work_dir.join("t.db").display());
+        crate::db::init_database_for_test(&db_url)
+            .await
+            .expect("初始化测试数据库失败");
+
+        let header = "coil_id,steel_grade,thickness,width,weight,coiling_time\n";
+        let first = work_dir.join("first.csv");
+        std::fs::write(
+            &first,
+            format!(
+                "{h}B{s}-1,Q235,2.5,1250,20,2026-01-01 08:00:00\n\
+                 B{s}-2,Q235,2.5,1250,21,2026-01-01 08:00:00\n\
+                 B{s}-2,Q235,2.5,1250,22,2026-01-01 08:00:00\n",
+                h = header,
+                s = seed
+            ),
+        )
+        .unwrap();
+        let summary = import_file_with_source(first.to_str().unwrap(), None, &atomic("skip"), None)
+            .await
+            .expect("首次导入失败");
+        assert_eq!(summary.new_count, 2);
+        assert_eq!(summary.duplicate_count, 1);
+        assert_eq!(
+            (summary.success, summary.skipped, summary.failed),
+            (2, 1, 0)
+        );
+
+        // 第二次：1 行未变化、1 行变化、1 行新增；overwrite 模式只更新变化行
+        let second = work_dir.join("second.csv");
+        std::fs::write(
+            &second,
+            format!(
+                "{h}B{s}-1,Q235,2.5,1250,20,2026-01-01 08:00:00\n\
+                 B{s}-2,Q345,2.5,1250,21,2026-01-01 08:00:00\n\
+                 B{s}-3,Q235,3.0,1000,18,2026-01-01 08:00:00\n",
+                h = header,
+                s = seed
+            ),
+        )
+        .unwrap();
+        let summary = import_file_with_source(
+            second.to_str().unwrap(),
+            None,
+            &ImportOptions::new("overwrite"),
+            None,
+        )
+        .await
+        .expect("二次导入失败");
+        assert_eq!(
+            (
+                summary.new_count,
+                summary.changed_count,
+                summary.duplicate_count
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            (summary.success, summary.overwritten, summary.skipped),
+            (1, 1, 1)
+        );
+        let updated = material::Entity::find()
+            .filter(material::Column::CoilId.eq(format!("B{}-2", seed)))
+            .one(get_db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.steel_grade, "Q345");
+        assert_eq!(updated.status.as_deref(), Some("pending"));
+
+        // 整批模式：任一行无效即不写入任何行
+        let bad = work_dir.join("bad.csv");
+        std::fs::write(
+            &bad,
+            format!(
+                "{h}B{s}-4,Q235,2.5,1250,20,2026-01-01 08:00:00\n\
+                 B{s}-5,Q235,abc,1250,20,2026-01-01 08:00:00\n",
+                h = header,
+                s = seed
+            ),
+        )
+        .unwrap();
+        assert!(
+            import_file_with_source(bad.to_str().unwrap(), None, &atomic("skip"), None)
+                .await
+                .is_err()
+        );
+        let inserted = material::Entity::find()
+            .filter(material::Column::CoilId.eq(format!("B{}-4", seed)))
+            .one(get_db())
+            .await
+            .unwrap();
+        assert!(inserted.is_none());
+
+        // merge 模式：本地备注保留，钢种按文件更新；预览与实际写入一致
+        use sea_orm::IntoActiveModel;
+        let mut local = updated.into_active_model();
+        local.remarks = Set(Some("计划员备注".to_string()));
+        local.update(get_db()).await.unwrap();
+        let third = work_dir.join("third.csv");
+        std::fs::write(
+            &third,
+            format!(
+                "coil_id,steel_grade,thickness,width,weight,coiling_time,remarks\n\
+                 B{s}-2,SPHC,2.5,1250,21,2026-01-01 08:00:00,源系统备注\n",
+                s = seed
+            ),
+        )
+        .unwrap();
+        let merge = ImportOptions::new("merge");
+        let preview = preview_import_changes(third.to_str().unwrap(), None, &merge)
+            .await
+            .expect("预览失败");
+        assert_eq!(preview.changed.len(), 1);
+        assert_eq!(
+            preview.changed[0].changes,
+            vec![FieldChange {
+                field: "steel_grade".to_string(),
+                from: Some("Q345".to_string()),
+                to: Some("SPHC".to_string()),
+            }]
+        );
+        let summary = import_file_with_source(third.to_str().unwrap(), None, &merge, None)
+            .await
+            .expect("合并导入失败");
+        assert_eq!(summary.overwritten, 1);
+        let merged = material::Entity::find()
+            .filter(material::Column::CoilId.eq(format!("B{}-2", seed)))
+            .one(get_db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.steel_grade, "SPHC");
+        assert_eq!(merged.remarks.as_deref(), Some("计划员备注"));
+        let _ = std::fs::remove_dir_all(&work_dir);
+    }
+
+    #[test]
+    fn merge_policy_resolves_fields_per_policy() {
+        let existing: material::Model = serde_json::from_value(serde_json::json!({
+            "id": 1, "coil_id": "C1", "contract_no": null, "customer_name": "老客户",
+            "customer_code": null, "steel_grade": "Q235", "thickness": 2.5, "width": 1250.0,
+            "weight": 20.0, "hardness_level": null, "surface_level": null, "roughness_req": null,
+            "elongation_req": null, "product_type": null, "contract_attr": null,
+            "contract_nature": null, "export_flag": null, "weekly_delivery": null,
+            "batch_code": null, "coiling_time": "2026-01-01T00:00:00Z", "temp_status": "ready",
+            "temp_wait_days": 0, "is_tempered": true, "tempered_at": null, "storage_days": null,
+            "storage_loc": null, "due_date": null, "status": "pending", "priority_auto": 0,
+            "priority_manual_adjust": 5, "priority_final": 0, "priority_detail": null,
+            "priority_reason": null, "remarks": "", "created_at": null, "updated_at": null,
+            "import_batch_id": null, "coiling_temp": null
+        }))
+        .expect("构造材料失败");
+        let incoming = || material::ActiveModel {
+            steel_grade: Set("Q345".to_string()),
+            customer_name: Set(Some("新客户".to_string())),
+            contract_no: Set(Some("HT-1".to_string())),
+            remarks: Set(Some("源备注".to_string())),
+            coiling_time: Set(existing.coiling_time),
+            temp_status: Set(Some("waiting".to_string())),
+            ..Default::default()
+        };
+
+        let policy = MergePolicy::parse(Some(
+            r#"{"default":"source","fields":{"customer_name":"keep_local","contract_no":"fill_empty","remarks":"fill_empty"}}"#,
+        ))
+        .expect("策略解析失败");
+        let mut model = incoming();
+        let changes = resolve_against_existing(&existing, &mut model, &policy);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["steel_grade", "contract_no", "remarks"]);
+        assert_eq!(model.customer_name, Set(Some("老客户".to_string())));
+        // 卷取时间未变，适温状态保留本地值
+        assert_eq!(model.temp_status, Set(Some("ready".to_string())));
+
+        let mut model = incoming();
+        let changes = resolve_against_existing(&existing, &mut model, &MergePolicy::source_wins());
+        assert_eq!(changes.len(), 4);
+
+        assert!(MergePolicy::parse(Some(r#"{"fields":{"status":"source"}}"#)).is_err());
+        assert!(MergePolicy::parse(Some(r#"{"default":"whatever"}"#)).is_err());
+        assert_eq!(
+            MergePolicy::parse(None).unwrap().policy_for("remarks"),
+            FieldMergePolicy::FillEmpty
+        );
+    }
+
+    #[test]
+    fn validation_rules_fail_row_and_locate_source_cells() {
+        let headers = [
+            "卷号",
+            "钢种",
+            "厚度",
+            "宽度",
+            "重量",
+            "卷取时间",
+            "交期",
+            "硬度",
+        ];
+        let targets = [
+            "coil_id",
+            "steel_grade",
+            "thickness",
+            "width",
+            "weight",
+            "coiling_time",
+            "due_date",
+            "hardness_level",
+        ];
+        let context = ImportContext {
+            mappings: headers
+                .iter()
+                .zip(targets)
+                .map(|(h, t)| FieldMappingItem {
+                    source_field: h.to_string(),
+                    target_field: t.to_string(),
+                    mapping_type: "direct".to_string(),
+                    default_value: None,
+                    transform_rule: None,
+                    source_format: None,
+                })
+                .collect(),
+            transforms: vec![],
+            header_index: headers
+                .iter()
+                .enumerate()
+                .map(|(i, h)| (h.to_string(), i))
+                .collect(),
+            rules: CompiledRules::parse(Some(
+                r#"[{"type":"allowed_values","field":"hardness_level","values":["软","中","硬"]},
+                    {"type":"compare","field":"due_date","op":">=","other":"coiling_time"}]"#,
+            ))
+            .unwrap(),
+            first_line_no: 2,
+            line_numbers: Vec::new(),
+        };
+        let row = |due: &str, hardness: &str| -> Vec<String> {
+            [
+                "C001",
+                "Q235",
+                "2.5",
+                "1250",
+                "20",
+                "2026-01-05 08:00:00",
+                due,
+                hardness,
+            ]
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+        };
+
+        assert!(build_material_model(&row("2026-01-10", "中"), &context, 2).is_ok());
+        assert!(diagnose_row(&row("2026-01-10", "中"), &context, 2).is_none());
+
+        let bad = row("2026-01-01", "超硬");
+        let err = build_material_model(&bad, &context, 3).expect_err("should fail");
+        assert!(err.message.contains("校验未通过"));
+        let (message, fields) = diagnose_row(&bad, &context, 3).expect("should fail");
+        assert!(message.contains("hardness_level"));
+        assert_eq!(fields, vec!["hardness_level", "due_date", "coiling_time"]);
+        assert_eq!(source_columns(&fields, &context), vec![5, 6, 7]);
+
+        // 转换错误按出错的目标字段定位
+        let mut missing = row("2026-01-10", "中");
+        missing[2] = "abc".to_string();
+        let (_, fields) = diagnose_row(&missing, &context, 4).expect("should fail");
+        assert_eq!(source_columns(&fields, &context), vec![2]);
+    }
 }
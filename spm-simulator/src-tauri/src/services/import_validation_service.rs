@@ -0,0 +1,385 @@
+//! 导入校验规则
+//!
+//! 规则以 JSON 数组挂在字段映射模板上（field_mapping.validation_rules），在字段映射与值转换
+//! 完成后对目标字段值执行：数值范围、允许值集合、正则、条件必填、跨字段比较。
+//! 空值只由必填类规则（required_if）处理，其余规则遇空值跳过。
+
+use crate::services::import_service::parse_datetime_to_utc;
+use crate::AppError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+}
+
+impl CompareOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+        }
+    }
+
+    fn holds(self, ord: Ordering) -> bool {
+        match self {
+            CompareOp::Gt => ord == Ordering::Greater,
+            CompareOp::Ge => ord != Ordering::Less,
+            CompareOp::Lt => ord == Ordering::Less,
+            CompareOp::Le => ord != Ordering::Greater,
+            CompareOp::Eq => ord == Ordering::Equal,
+            CompareOp::Ne => ord != Ordering::Equal,
+        }
+    }
+}
+
+/// 校验规则定义（message 为空时使用默认提示）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValidationRule {
+    /// 数值范围（闭区间，min/max 可只填一个）
+    Range {
+        field: String,
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// 允许值集合
+    AllowedValues {
+        field: String,
+        values: Vec<String>,
+        #[serde(default)]
+        ignore_case: bool,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// 正则匹配（整值需匹配时请使用 ^...$）
+    Pattern {
+        field: String,
+        pattern: String,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// 条件必填：when_field 非空（且 when_values 非空时取值在其中）时 field 必填
+    RequiredIf {
+        field: String,
+        when_field: String,
+        #[serde(default)]
+        when_values: Vec<String>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// 跨字段比较：field op other（两侧均为数字按数值比较，均为日期按时间比较，否则按文本比较）
+    Compare {
+        field: String,
+        op: CompareOp,
+        other: String,
+        #[serde(default)]
+        message: Option<String>,
+    },
+}
+
+impl ValidationRule {
+    fn type_name(&self) -> &'static str {
+        match self {
+            ValidationRule::Range { .. } => "range",
+            ValidationRule::AllowedValues { .. } => "allowed_values",
+            ValidationRule::Pattern { .. } => "pattern",
+            ValidationRule::RequiredIf { .. } => "required_if",
+            ValidationRule::Compare { .. } => "compare",
+        }
+    }
+
+    fn custom_message(&self) -> Option<&str> {
+        match self {
+            ValidationRule::Range { message, .. }
+            | ValidationRule::AllowedValues { message, .. }
+            | ValidationRule::Pattern { message, .. }
+            | ValidationRule::RequiredIf { message, .. }
+            | ValidationRule::Compare { message, .. } => {
+                message.as_deref().filter(|m| !m.trim().is_empty())
+            }
+        }
+    }
+}
+
+/// 单条规则违反
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleViolation {
+    pub rule_type: String,
+    /// 涉及的目标字段（用于定位并高亮源单元格）
+    pub fields: Vec<String>,
+    pub message: String,
+}
+
+/// 解析并预编译后的规则集
+#[derive(Debug, Clone, Default)]
+pub struct CompiledRules {
+    rules: Vec<(ValidationRule, Option<Regex>)>,
+}
+
+fn value_of<'a>(fields: &'a HashMap<String, String>, field: &str) -> Option<&'a str> {
+    fields
+        .get(field)
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+/// 比较两个字段值：数字 > 日期 > 文本
+fn compare_values(left: &str, right: &str) -> Ordering {
+    if let (Ok(a), Ok(b)) = (left.parse::<f64>(), right.parse::<f64>()) {
+        return a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    }
+    if let (Some(a), Some(b)) = (parse_datetime_to_utc(left), parse_datetime_to_utc(right)) {
+        return a.cmp(&b);
+    }
+    left.cmp(right)
+}
+
+impl CompiledRules {
+    /// 解析规则 JSON（空值表示无规则）；正则无法编译或范围上下限颠倒时报错
+    pub fn parse(raw: Option<&str>) -> Result<Self, AppError> {
+        let Some(raw) = raw.map(str::trim).filter(|r| !r.is_empty()) else {
+            return Ok(Self::default());
+        };
+        let rules: Vec<ValidationRule> = serde_json::from_str(raw)
+            .map_err(|e| AppError::InvalidInput(format!("校验规则解析失败: {}", e)))?;
+        let mut compiled = Vec::with_capacity(rules.len());
+        for (idx, rule) in rules.into_iter().enumerate() {
+            let regex = match &rule {
+                ValidationRule::Pattern { field, pattern, .. } => {
+                    Some(Regex::new(pattern).map_err(|e| {
+                        AppError::InvalidInput(format!(
+                            "第{}条规则（{}）正则无效: {}",
+                            idx + 1,
+                            field,
+                            e
+                        ))
+                    })?)
+                }
+                ValidationRule::Range {
+                    field,
+                    min: Some(min),
+                    max: Some(max),
+                    ..
+                } if min > max => {
+                    return Err(AppError::InvalidInput(format!(
+                        "第{}条规则（{}）范围下限大于上限",
+                        idx + 1,
+                        field
+                    )));
+                }
+                _ => None,
+            };
+            compiled.push((rule, regex));
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 对一行的目标字段值执行全部规则
+    pub fn validate(&self, fields: &HashMap<String, String>) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        for (rule, regex) in &self.rules {
+            let failure: Option<(Vec<String>, String)> = match rule {
+                ValidationRule::Range {
+                    field, min, max, ..
+                } => value_of(fields, field).and_then(|raw| match raw.parse::<f64>() {
+                    Err(_) => Some((
+                        vec![field.clone()],
+                        format!("{} 值 {} 不是有效数字", field, raw),
+                    )),
+                    Ok(v) if min.is_some_and(|m| v < m) || max.is_some_and(|m| v > m) => Some((
+                        vec![field.clone()],
+                        format!(
+                            "{} 值 {} 超出范围 [{}, {}]",
+                            field,
+                            raw,
+                            min.map(|m| m.to_string())
+                                .unwrap_or_else(|| "-∞".to_string()),
+                            max.map(|m| m.to_string())
+                                .unwrap_or_else(|| "+∞".to_string())
+                        ),
+                    )),
+                    Ok(_) => None,
+                }),
+                ValidationRule::AllowedValues {
+                    field,
+                    values,
+                    ignore_case,
+                    ..
+                } => value_of(fields, field).and_then(|raw| {
+                    let allowed = values.iter().any(|v| {
+                        if *ignore_case {
+                            v.trim().eq_ignore_ascii_case(raw)
+                        } else {
+                            v.trim() == raw
+                        }
+                    });
+                    (!allowed).then(|| {
+                        (
+                            vec![field.clone()],
+                            format!("{} 值 {} 不在允许值 [{}] 中", field, raw, values.join(",")),
+                        )
+                    })
+                }),
+                ValidationRule::Pattern { field, pattern, .. } => {
+                    value_of(fields, field).and_then(|raw| {
+                        let matched = regex.as_ref().map(|r| r.is_match(raw)).unwrap_or(true);
+                        (!matched).then(|| {
+                            (
+                                vec![field.clone()],
+                                format!("{} 值 {} 不匹配格式 {}", field, raw, pattern),
+                            )
+                        })
+                    })
+                }
+                ValidationRule::RequiredIf {
+                    field,
+                    when_field,
+                    when_values,
+                    ..
+                } => value_of(fields, when_field).and_then(|cond| {
+                    let triggered =
+                        when_values.is_empty() || when_values.iter().any(|v| v.trim() == cond);
+                    (triggered && value_of(fields, field).is_none()).then(|| {
+                        (
+                            vec![field.clone(), when_field.clone()],
+                            format!("{} 为 {} 时 {} 必填", when_field, cond, field),
+                        )
+                    })
+                }),
+                ValidationRule::Compare {
+                    field, op, other, ..
+                } => match (value_of(fields, field), value_of(fields, other)) {
+                    (Some(left), Some(right)) if !op.holds(compare_values(left, right)) => Some((
+                        vec![field.clone(), other.clone()],
+                        format!(
+                            "{}({}) 应 {} {}({})",
+                            field,
+                            left,
+                            op.symbol(),
+                            other,
+                            right
+                        ),
+                    )),
+                    _ => None,
+                },
+            };
+            if let Some((fields, default_message)) = failure {
+                violations.push(RuleViolation {
+                    rule_type: rule.type_name().to_string(),
+                    fields,
+                    message: rule
+                        .custom_message()
+                        .map(str::to_string)
+                        .unwrap_or(default_message),
+                });
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn rules_report_range_allowed_pattern_required_and_compare_violations() {
+        let rules = CompiledRules::parse(Some(
+            r#"[
+                {"type":"range","field":"width","min":600,"max":2200},
+                {"type":"range","field":"thickness","max":25,"message":"厚度超限"},
+                {"type":"allowed_values","field":"hardness_level","values":["软","中","硬"]},
+                {"type":"pattern","field":"coil_id","pattern":"^[A-Z]\\d{6}$"},
+                {"type":"required_if","field":"contract_no","when_field":"export_flag","when_values":["1","是"]},
+                {"type":"compare","field":"due_date","op":">=","other":"coiling_time"}
+            ]"#,
+        ))
+        .expect("规则解析失败");
+
+        let ok = row(&[
+            ("coil_id", "A123456"),
+            ("width", "1250"),
+            ("thickness", "2.5"),
+            ("hardness_level", "中"),
+            ("export_flag", "0"),
+            ("coiling_time", "2026-01-01 08:00:00"),
+            ("due_date", "2026-01-05"),
+        ]);
+        assert!(rules.validate(&ok).is_empty());
+
+        let bad = row(&[
+            ("coil_id", "a-1"),
+            ("width", "25"),
+            ("thickness", "300"),
+            ("hardness_level", "超硬"),
+            ("export_flag", "是"),
+            ("coiling_time", "2026-01-05 08:00:00"),
+            ("due_date", "2026-01-01"),
+        ]);
+        let violations = rules.validate(&bad);
+        let types: Vec<&str> = violations.iter().map(|v| v.rule_type.as_str()).collect();
+        assert_eq!(
+            types,
+            vec![
+                "range",
+                "range",
+                "allowed_values",
+                "pattern",
+                "required_if",
+                "compare"
+            ]
+        );
+        assert_eq!(violations[1].message, "厚度超限");
+        assert_eq!(violations[4].fields, vec!["contract_no", "export_flag"]);
+        assert_eq!(violations[5].fields, vec!["due_date", "coiling_time"]);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_regex_and_inverted_range() {
+        assert!(CompiledRules::parse(None).unwrap().is_empty());
+        assert!(CompiledRules::parse(Some("  ")).unwrap().is_empty());
+        assert!(CompiledRules::parse(Some(
+            r#"[{"type":"pattern","field":"coil_id","pattern":"("}]"#
+        ))
+        .is_err());
+        assert!(CompiledRules::parse(Some(
+            r#"[{"type":"range","field":"width","min":2000,"max":600}]"#
+        ))
+        .is_err());
+        assert!(CompiledRules::parse(Some(r#"[{"type":"unknown","field":"x"}]"#)).is_err());
+    }
+}
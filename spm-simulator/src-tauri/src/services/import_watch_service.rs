@@ -0,0 +1,468 @@
+//! 材料目录监控自动导入
+//!
+//! 按配置轮询监控目录，发现新的表格文件（.xlsx/.xls/.ods 等）或 .csv 文件后使用绑定的映射模板与冲突模式
+//! 调用导入；成功文件移入 archive/，失败文件移入 failed/ 并生成 `{file}.error.txt`
+//! 错误报告。每个文件对应一个 import_batch（备注标记为目录监控导入）。
+//! 文件移动失败时在原位置留下 `{file}.move_pending` 标记（内容为目标子目录），
+//! 下一轮只重试移动而不再重复导入。
+
+use crate::services::import_service::{self, ImportSummary, MergePolicy};
+use crate::services::source_reader_service::SPREADSHEET_EXTENSIONS;
+use crate::utils::log::write_operation_log;
+use crate::utils::watch_dir::{load_config_group, move_into, parse_bool};
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const MIN_POLL_SECONDS: u64 = 10;
+/// 文件最后修改后至少静置的秒数，避免读取仍在写入的文件
+const MIN_FILE_AGE_SECS: u64 = 5;
+const ARCHIVE_DIR: &str = "archive";
+const FAILED_DIR: &str = "failed";
+const SOURCE_REMARK: &str = "目录监控自动导入";
+/// 已处理但未能移走的文件标记后缀
+const MOVE_PENDING_SUFFIX: &str = ".move_pending";
+
+#[derive(Debug, Clone)]
+pub struct ImportWatchConfig {
+    pub enabled: bool,
+    pub watch_dir: Option<PathBuf>,
+    pub mapping_id: Option<i32>,
+    pub conflict_mode: String,
+    /// 整批导入：任一行失败即整体回滚并隔离文件
+    pub all_or_nothing: bool,
+    /// merge 模式的字段策略
+    pub merge_policy: MergePolicy,
+    pub poll_seconds: u64,
+}
+
+impl Default for ImportWatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            watch_dir: None,
+            mapping_id: None,
+            conflict_mode: "skip".to_string(),
+            all_or_nothing: false,
+            merge_policy: MergePolicy::default(),
+            poll_seconds: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedFileResult {
+    pub file_name: String,
+    pub success: bool,
+    pub batch_id: Option<i32>,
+    pub imported: usize,
+    pub failed_rows: usize,
+    pub error: Option<String>,
+    /// 文件处理后的存放路径（archive/ 或 failed/；移动失败时为原路径）
+    pub moved_to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportWatchRunResult {
+    pub scanned: usize,
+    pub files: Vec<WatchedFileResult>,
+}
+
+pub async fn load_import_watch_config() -> Result<ImportWatchConfig, AppError> {
+    let mut config = ImportWatchConfig::default();
+    for (key, value) in load_config_group("import_watch").await? {
+        let value = value.as_str();
+        match key.as_str() {
+            "enabled" => config.enabled = parse_bool(value),
+            "watch_dir" => {
+                config.watch_dir = (!value.is_empty()).then(|| PathBuf::from(value));
+            }
+            "mapping_id" => config.mapping_id = value.parse::<i32>().ok(),
+            "conflict_mode" => {
+                if matches!(value, "skip" | "overwrite" | "merge") {
+                    config.conflict_mode = value.to_string();
+                }
+            }
+            "all_or_nothing" => config.all_or_nothing = parse_bool(value),
+            "merge_policy" => match MergePolicy::parse(Some(value)) {
+                Ok(policy) => config.merge_policy = policy,
+                Err(err) => log::warn!("目录监控合并策略无效，使用默认策略: {}", err),
+            },
+            "poll_seconds" => {
+                if let Ok(secs) = value.parse::<u64>() {
+                    config.poll_seconds = secs;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(config)
+}
+
+/// 是否为待导入文件：表格或 CSV 扩展名（不含 .txt，避免误收错误报告），且非隐藏/临时/Office 锁文件
+fn is_importable_file_name(name: &str) -> bool {
+    if name.starts_with('.') || name.starts_with("~$") {
+        return false;
+    }
+    match name.rsplit_once('.') {
+        Some((_, ext)) => {
+            let ext = ext.to_lowercase();
+            ext == "csv" || SPREADSHEET_EXTENSIONS.contains(&ext.as_str())
+        }
+        None => false,
+    }
+}
+
+fn is_settled(path: &Path, now: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| now.duration_since(modified).ok())
+        .map(|age| age >= Duration::from_secs(MIN_FILE_AGE_SECS))
+        .unwrap_or(false)
+}
+
+fn list_pending_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let now = SystemTime::now();
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| is_importable_file_name(&n.to_string_lossy()))
+                .unwrap_or(false)
+        })
+        .filter(|p| is_settled(p, now))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// 生成错误报告文本
+fn render_error_report(
+    file_name: &str,
+    config: &ImportWatchConfig,
+    error: Option<&str>,
+    summary: Option<&ImportSummary>,
+) -> String {
+    let mut lines = vec![
+        format!("文件: {}", file_name),
+        format!("时间: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")),
+        format!(
+            "映射模板: {}",
+            config
+                .mapping_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "默认".to_string())
+        ),
+        format!("冲突模式: {}", config.conflict_mode),
+    ];
+    if let Some(err) = error {
+        lines.push(format!("导入失败: {}", err));
+    }
+    if let Some(s) = summary {
+        lines.push(format!(
+            "批次ID: {} 总数={} 成功={} 跳过={} 覆盖={} 失败={}",
+            s.batch_id, s.total, s.success, s.skipped, s.overwritten, s.failed
+        ));
+        if !s.errors.is_empty() {
+            lines.push("行错误:".to_string());
+            lines.extend(s.errors.iter().map(|e| format!("  {}", e)));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+fn write_report_next_to(target: &Path, report: &str) -> Result<(), AppError> {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    std::fs::write(target.with_file_name(format!("{}.error.txt", name)), report)?;
+    Ok(())
+}
+
+fn move_pending_marker(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}{}", name, MOVE_PENDING_SUFFIX))
+}
+
+/// 将已处理的文件移入 archive/ 或 failed/；失败时留下标记并返回错误信息
+fn settle_file(path: &Path, dir: &Path, sub_dir: &str) -> Result<PathBuf, String> {
+    let marker = move_pending_marker(path);
+    match move_into(path, &dir.join(sub_dir)) {
+        Ok(moved) => {
+            if marker.exists() {
+                let _ = std::fs::remove_file(&marker);
+            }
+            Ok(moved)
+        }
+        Err(err) => {
+            if let Err(mark_err) = std::fs::write(&marker, sub_dir) {
+                log::error!(
+                    "目录监控写入移动标记失败: {}: {}",
+                    marker.display(),
+                    mark_err
+                );
+            }
+            Err(format!("文件移入 {}/ 失败: {}", sub_dir, err))
+        }
+    }
+}
+
+fn write_report_or_warn(target: &Path, report: &str) {
+    if let Err(err) = write_report_next_to(target, report) {
+        log::warn!("目录监控写入错误报告失败: {}: {}", target.display(), err);
+    }
+}
+
+async fn process_file(path: &Path, dir: &Path, config: &ImportWatchConfig) -> WatchedFileResult {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_path = path.to_string_lossy().to_string();
+    let options = import_service::ImportOptions {
+        all_or_nothing: config.all_or_nothing,
+        merge_policy: config.merge_policy.clone(),
+        ..import_service::ImportOptions::new(&config.conflict_mode)
+    };
+    let (batch_id, result) = match import_service::import_file_tracked(
+        &file_path,
+        config.mapping_id,
+        &options,
+        Some(SOURCE_REMARK),
+    )
+    .await
+    {
+        Ok((batch_id, result)) => (Some(batch_id), result),
+        Err(err) => (None, Err(err)),
+    };
+
+    match result {
+        Ok(summary) => {
+            let settled = settle_file(path, dir, ARCHIVE_DIR);
+            if let (Ok(moved), true) = (&settled, summary.failed > 0) {
+                // 部分行失败：文件归档，同时保留行错误报告
+                write_report_or_warn(
+                    moved,
+                    &render_error_report(&file_name, config, None, Some(&summary)),
+                );
+            }
+            write_operation_log(
+                "material",
+                "auto_import",
+                Some("material"),
+                None,
+                Some(format!(
+                    "目录监控导入: 文件={}, mode={}, 成功={}, 跳过={}, 覆盖={}, 失败={}",
+                    file_name,
+                    config.conflict_mode,
+                    summary.success,
+                    summary.skipped,
+                    summary.overwritten,
+                    summary.failed
+                )),
+            )
+            .await;
+            WatchedFileResult {
+                file_name,
+                success: true,
+                batch_id,
+                imported: summary.success + summary.overwritten,
+                failed_rows: summary.failed,
+                error: settled.as_ref().err().cloned(),
+                moved_to: settled
+                    .unwrap_or_else(|_| path.to_path_buf())
+                    .to_string_lossy()
+                    .to_string(),
+            }
+        }
+        Err(err) => {
+            let mut message = err.to_string();
+            let settled = settle_file(path, dir, FAILED_DIR);
+            match &settled {
+                Ok(moved) => write_report_or_warn(
+                    moved,
+                    &render_error_report(&file_name, config, Some(&message), None),
+                ),
+                Err(move_err) => message = format!("{}；{}", message, move_err),
+            }
+            write_operation_log(
+                "material",
+                "auto_import_failed",
+                Some("material"),
+                None,
+                Some(format!(
+                    "目录监控导入失败: 文件={}, 错误={}",
+                    file_name, message
+                )),
+            )
+            .await;
+            WatchedFileResult {
+                file_name,
+                success: false,
+                batch_id,
+                imported: 0,
+                failed_rows: 0,
+                error: Some(message),
+                moved_to: settled
+                    .unwrap_or_else(|_| path.to_path_buf())
+                    .to_string_lossy()
+                    .to_string(),
+            }
+        }
+    }
+}
+
+/// 扫描一次监控目录并导入所有就绪文件（按文件名顺序逐个导入）
+pub async fn run_import_watch_once(
+    config: &ImportWatchConfig,
+) -> Result<ImportWatchRunResult, AppError> {
+    let dir = config
+        .watch_dir
+        .as_ref()
+        .ok_or_else(|| AppError::InvalidInput("未配置监控目录 watch_dir".to_string()))?;
+    if !dir.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "监控目录不存在: {}",
+            dir.display()
+        )));
+    }
+    let files = list_pending_files(dir)?;
+    let mut run = ImportWatchRunResult {
+        scanned: files.len(),
+        files: Vec::with_capacity(files.len()),
+    };
+    for path in files {
+        // 上一轮已导入但未能移走的文件：只重试移动
+        if let Ok(sub_dir) = std::fs::read_to_string(move_pending_marker(&path)) {
+            let sub_dir = if sub_dir.trim() == FAILED_DIR {
+                FAILED_DIR
+            } else {
+                ARCHIVE_DIR
+            };
+            if let Err(err) = settle_file(&path, dir, sub_dir) {
+                log::warn!("目录监控重试移动失败: {}: {}", path.display(), err);
+            }
+            continue;
+        }
+        run.files.push(process_file(&path, dir, config).await);
+    }
+    Ok(run)
+}
+
+/// 启动目录监控后台任务；每轮重新读取配置，未启用时仅等待
+pub fn spawn_import_watcher() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            let config = load_import_watch_config().await.unwrap_or_default();
+            if config.enabled && config.watch_dir.is_some() {
+                if let Err(err) = run_import_watch_once(&config).await {
+                    log::warn!("目录监控导入失败: {}", err);
+                }
+            }
+            let secs = config.poll_seconds.max(MIN_POLL_SECONDS);
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn importable_file_name_filters_temp_and_unsupported_files() {
+        assert!(is_importable_file_name("materials_20260101.xlsx"));
+        assert!(is_importable_file_name("MAT.CSV"));
+        assert!(is_importable_file_name("old.xls"));
+        assert!(is_importable_file_name("mill.ods"));
+        assert!(!is_importable_file_name("~$materials.xlsx"));
+        assert!(!is_importable_file_name(".materials.csv"));
+        assert!(!is_importable_file_name("materials.csv.part"));
+        assert!(!is_importable_file_name("materials.csv.error.txt"));
+    }
+
+    #[test]
+    fn error_report_lists_summary_and_row_errors() {
+        let config = ImportWatchConfig {
+            mapping_id: Some(3),
+            conflict_mode: "overwrite".to_string(),
+            ..Default::default()
+        };
+        let summary = ImportSummary {
+            batch_id: 12,
+            total: 3,
+            success: 1,
+            failed: 2,
+            skipped: 0,
+            overwritten: 0,
+            new_count: 3,
+            duplicate_count: 0,
+            changed_count: 0,
+            errors: vec![
+                "第2行: 缺少钢卷号".to_string(),
+                "第3行: 宽度无效".to_string(),
+            ],
+        };
+        let report = render_error_report("m.xlsx", &config, None, Some(&summary));
+        assert!(report.contains("映射模板: 3"));
+        assert!(report.contains("冲突模式: overwrite"));
+        assert!(report.contains("批次ID: 12"));
+        assert!(report.contains("  第3行: 宽度无效"));
+
+        let failed = render_error_report(
+            "m.xlsx",
+            &ImportWatchConfig::default(),
+            Some("坏文件"),
+            None,
+        );
+        assert!(failed.contains("映射模板: 默认"));
+        assert!(failed.contains("导入失败: 坏文件"));
+    }
+
+    #[tokio::test]
+    async fn processed_file_is_not_reimported_while_move_is_pending() {
+        let dir = std::env::temp_dir().join(format!(
+            "spm_import_watch_pending_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("m.csv");
+        std::fs::write(&src, "coil_id\n").unwrap();
+        // archive 被同名文件占用，移动失败
+        std::fs::write(dir.join(ARCHIVE_DIR), "blocker").unwrap();
+
+        let err = settle_file(&src, &dir, ARCHIVE_DIR).unwrap_err();
+        assert!(err.contains("archive"));
+        assert!(src.exists());
+        let marker = move_pending_marker(&src);
+        assert_eq!(std::fs::read_to_string(&marker).unwrap(), ARCHIVE_DIR);
+
+        // 下一轮只重试移动，不产生导入结果
+        std::fs::remove_file(dir.join(ARCHIVE_DIR)).unwrap();
+        let config = ImportWatchConfig {
+            watch_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        let old = SystemTime::now() - Duration::from_secs(MIN_FILE_AGE_SECS * 2);
+        std::fs::File::options()
+            .write(true)
+            .open(&src)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let run = run_import_watch_once(&config).await.unwrap();
+        assert_eq!(run.scanned, 1);
+        assert!(run.files.is_empty());
+        assert!(!src.exists());
+        assert!(!marker.exists());
+        assert!(dir.join(ARCHIVE_DIR).join("m.csv").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
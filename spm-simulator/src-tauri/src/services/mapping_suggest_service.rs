@@ -0,0 +1,848 @@
+//! 字段映射建议
+//!
+//! 根据源文件表头与样例值为每个目标字段推荐源列：表头按别名精确/包含/模糊（编辑距离、
+//! 二元组相似度）与拼音（全拼、首字母）匹配，列值按类型特征（日期、数值范围、布尔、
+//! 卷号类编码）嗅探，两者加权得到置信度后按全局最优贪心分配（每列、每个目标字段至多一次）。
+
+use crate::services::import_service::{parse_datetime_to_utc, FieldMappingItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 默认最低置信度
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.45;
+/// 值嗅探采样行数
+const VALUE_SAMPLE_ROWS: usize = 200;
+/// 表头得分达到该值时视为表头命中（否则仅凭列值特征推荐）
+const HEADER_HIT_SCORE: f64 = 0.6;
+
+/// 目标字段的列值类型特征
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueKind {
+    /// 卷号/合同号类：字母数字编码，基本唯一
+    Identifier,
+    /// 短编码（钢种、等级、库位等）
+    Code,
+    /// 自由文本
+    Text,
+    /// 数值（合理范围）
+    Number { min: f64, max: f64 },
+    /// 整数（合理范围）
+    Integer { min: i64, max: i64 },
+    /// 日期时间
+    DateTime,
+    /// 布尔标志
+    Flag,
+}
+
+struct TargetSpec {
+    field: &'static str,
+    required: bool,
+    aliases: &'static [&'static str],
+    kind: ValueKind,
+}
+
+const TARGETS: &[TargetSpec] = &[
+    TargetSpec {
+        field: "coil_id",
+        required: true,
+        aliases: &[
+            "钢卷号",
+            "卷号",
+            "钢卷编号",
+            "材料号",
+            "卷号码",
+            "原料卷号",
+            "物料号",
+            "coil",
+            "coil_no",
+            "coil_id",
+        ],
+        kind: ValueKind::Identifier,
+    },
+    TargetSpec {
+        field: "contract_no",
+        required: false,
+        aliases: &[
+            "合同号",
+            "合同编号",
+            "订单号",
+            "销售订单",
+            "contract",
+            "contract_no",
+            "order_no",
+        ],
+        kind: ValueKind::Identifier,
+    },
+    TargetSpec {
+        field: "customer_name",
+        required: false,
+        aliases: &[
+            "客户名称",
+            "客户",
+            "用户名称",
+            "用户",
+            "customer",
+            "customer_name",
+        ],
+        kind: ValueKind::Text,
+    },
+    TargetSpec {
+        field: "customer_code",
+        required: false,
+        aliases: &[
+            "客户代码",
+            "客户编码",
+            "用户代码",
+            "客户编号",
+            "customer_code",
+            "customer_id",
+        ],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "steel_grade",
+        required: true,
+        aliases: &[
+            "钢种",
+            "牌号",
+            "钢级",
+            "钢种牌号",
+            "钢号",
+            "grade",
+            "steel_grade",
+        ],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "thickness",
+        required: true,
+        aliases: &["厚度", "成品厚度", "厚", "规格厚度", "thickness", "thick"],
+        kind: ValueKind::Number {
+            min: 0.1,
+            max: 30.0,
+        },
+    },
+    TargetSpec {
+        field: "width",
+        required: true,
+        aliases: &["宽度", "成品宽度", "宽", "规格宽度", "width"],
+        kind: ValueKind::Number {
+            min: 300.0,
+            max: 2500.0,
+        },
+    },
+    TargetSpec {
+        field: "weight",
+        required: true,
+        aliases: &[
+            "重量",
+            "净重",
+            "卷重",
+            "毛重",
+            "实际重量",
+            "weight",
+            "net_weight",
+        ],
+        kind: ValueKind::Number {
+            min: 0.1,
+            max: 60.0,
+        },
+    },
+    TargetSpec {
+        field: "hardness_level",
+        required: false,
+        aliases: &["硬度等级", "硬度", "硬度级别", "hardness", "hardness_level"],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "surface_level",
+        required: false,
+        aliases: &[
+            "表面等级",
+            "表面",
+            "表面质量",
+            "表面级别",
+            "surface",
+            "surface_level",
+        ],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "roughness_req",
+        required: false,
+        aliases: &["粗糙度要求", "粗糙度", "roughness", "roughness_req"],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "elongation_req",
+        required: false,
+        aliases: &["延伸率要求", "延伸率", "elongation", "elongation_req"],
+        kind: ValueKind::Number {
+            min: 0.0,
+            max: 100.0,
+        },
+    },
+    TargetSpec {
+        field: "product_type",
+        required: false,
+        aliases: &[
+            "产品大类",
+            "产品类型",
+            "品种",
+            "产品",
+            "product_type",
+            "product",
+        ],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "contract_attr",
+        required: false,
+        aliases: &["合同属性", "订单属性", "contract_attr"],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "contract_nature",
+        required: false,
+        aliases: &["合同性质", "订单性质", "contract_nature"],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "export_flag",
+        required: false,
+        aliases: &[
+            "出口标志",
+            "出口",
+            "是否出口",
+            "内外贸",
+            "export",
+            "export_flag",
+        ],
+        kind: ValueKind::Flag,
+    },
+    TargetSpec {
+        field: "weekly_delivery",
+        required: false,
+        aliases: &["周交期", "周交货", "本周交货", "weekly_delivery"],
+        kind: ValueKind::Flag,
+    },
+    TargetSpec {
+        field: "batch_code",
+        required: false,
+        aliases: &[
+            "集批代码",
+            "集批",
+            "批次代码",
+            "批号",
+            "batch",
+            "batch_code",
+        ],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "coiling_time",
+        required: true,
+        aliases: &[
+            "卷取时间",
+            "生产时间",
+            "下线时间",
+            "卷取日期",
+            "生产日期",
+            "coiling_time",
+            "produce_time",
+        ],
+        kind: ValueKind::DateTime,
+    },
+    TargetSpec {
+        field: "coiling_temp",
+        required: false,
+        aliases: &["卷取温度", "终轧温度", "coiling_temp"],
+        kind: ValueKind::Number {
+            min: 300.0,
+            max: 950.0,
+        },
+    },
+    TargetSpec {
+        field: "storage_days",
+        required: false,
+        aliases: &["库龄", "在库天数", "存放天数", "storage_days"],
+        kind: ValueKind::Integer { min: 0, max: 3650 },
+    },
+    TargetSpec {
+        field: "storage_loc",
+        required: false,
+        aliases: &[
+            "库位",
+            "库区",
+            "存放位置",
+            "仓位",
+            "location",
+            "storage_loc",
+        ],
+        kind: ValueKind::Code,
+    },
+    TargetSpec {
+        field: "due_date",
+        required: false,
+        aliases: &[
+            "交期",
+            "交货期",
+            "交货日期",
+            "合同交期",
+            "due_date",
+            "delivery_date",
+        ],
+        kind: ValueKind::DateTime,
+    },
+    TargetSpec {
+        field: "remarks",
+        required: false,
+        aliases: &["备注", "说明", "remark", "remarks", "note"],
+        kind: ValueKind::Text,
+    },
+];
+
+/// 别名用字的拼音（仅覆盖 TARGETS 中出现的汉字）
+const PINYIN: &[(char, &str)] = &[
+    ('下', "xia"),
+    ('交', "jiao"),
+    ('产', "chan"),
+    ('仓', "cang"),
+    ('代', "dai"),
+    ('伸', "shen"),
+    ('位', "wei"),
+    ('内', "nei"),
+    ('净', "jing"),
+    ('出', "chu"),
+    ('别', "bie"),
+    ('区', "qu"),
+    ('单', "dan"),
+    ('卷', "juan"),
+    ('厚', "hou"),
+    ('原', "yuan"),
+    ('取', "qu"),
+    ('口', "kou"),
+    ('号', "hao"),
+    ('合', "he"),
+    ('同', "tong"),
+    ('名', "ming"),
+    ('否', "fou"),
+    ('周', "zhou"),
+    ('品', "pin"),
+    ('售', "shou"),
+    ('在', "zai"),
+    ('型', "xing"),
+    ('备', "bei"),
+    ('外', "wai"),
+    ('大', "da"),
+    ('天', "tian"),
+    ('存', "cun"),
+    ('实', "shi"),
+    ('客', "ke"),
+    ('宽', "kuan"),
+    ('属', "shu"),
+    ('库', "ku"),
+    ('度', "du"),
+    ('延', "yan"),
+    ('志', "zhi"),
+    ('性', "xing"),
+    ('成', "cheng"),
+    ('户', "hu"),
+    ('批', "pi"),
+    ('放', "fang"),
+    ('数', "shu"),
+    ('料', "liao"),
+    ('日', "ri"),
+    ('时', "shi"),
+    ('明', "ming"),
+    ('是', "shi"),
+    ('期', "qi"),
+    ('本', "ben"),
+    ('材', "cai"),
+    ('标', "biao"),
+    ('格', "ge"),
+    ('次', "ci"),
+    ('毛', "mao"),
+    ('求', "qiu"),
+    ('注', "zhu"),
+    ('温', "wen"),
+    ('牌', "pai"),
+    ('物', "wu"),
+    ('率', "lv"),
+    ('生', "sheng"),
+    ('用', "yong"),
+    ('码', "ma"),
+    ('硬', "ying"),
+    ('种', "zhong"),
+    ('称', "cheng"),
+    ('等', "deng"),
+    ('类', "lei"),
+    ('粗', "cu"),
+    ('糙', "cao"),
+    ('级', "ji"),
+    ('线', "xian"),
+    ('终', "zhong"),
+    ('编', "bian"),
+    ('置', "zhi"),
+    ('表', "biao"),
+    ('要', "yao"),
+    ('规', "gui"),
+    ('订', "ding"),
+    ('说', "shuo"),
+    ('货', "huo"),
+    ('质', "zhi"),
+    ('贸', "mao"),
+    ('轧', "zha"),
+    ('重', "zhong"),
+    ('量', "liang"),
+    ('钢', "gang"),
+    ('销', "xiao"),
+    ('间', "jian"),
+    ('际', "ji"),
+    ('集', "ji"),
+    ('面', "mian"),
+    ('龄', "ling"),
+];
+
+/// 单列的映射建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingSuggestion {
+    pub source_field: String,
+    pub column_index: usize,
+    pub target_field: String,
+    pub mapping_type: String,
+    pub source_format: Option<String>,
+    /// 综合置信度 0~1
+    pub confidence: f64,
+    pub header_score: f64,
+    pub value_score: f64,
+    /// 推荐依据：header_exact / header_pinyin / header_similar / value_pattern
+    pub reason: String,
+    /// 列值统一形态（如 ^[A-Z]\d{8}$），可直接用作校验规则
+    pub detected_pattern: Option<String>,
+}
+
+/// 映射建议结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingSuggestionResult {
+    pub suggestions: Vec<MappingSuggestion>,
+    /// 可直接保存为模板的映射配置
+    pub mappings: Vec<FieldMappingItem>,
+    /// 未分配的源列
+    pub unmapped_headers: Vec<String>,
+    /// 未找到来源的必填目标字段
+    pub missing_required: Vec<String>,
+}
+
+/// 归一化表头：小写、去除括号内单位/说明与分隔符号
+fn normalize_header(raw: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0usize;
+    for c in raw.chars() {
+        match c {
+            '(' | '（' | '[' | '【' => depth += 1,
+            ')' | '）' | ']' | '】' => depth = depth.saturating_sub(1),
+            _ if depth > 0 => {}
+            _ if c.is_alphanumeric() => out.extend(c.to_lowercase()),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// 汉字别名的全拼与首字母（含未收录汉字时返回 None）
+fn pinyin_forms(alias: &str) -> Option<(String, String)> {
+    let mut full = String::new();
+    let mut initials = String::new();
+    for c in alias.chars() {
+        let py = PINYIN.iter().find(|(ch, _)| *ch == c)?.1;
+        full.push_str(py);
+        initials.push_str(&py[..1]);
+    }
+    Some((full, initials))
+}
+
+fn is_cjk(c: char) -> bool {
+    ('\u{4e00}'..='\u{9fff}').contains(&c)
+}
+
+/// 表头与目标字段的相似度及依据
+fn header_score(header: &str, spec: &TargetSpec) -> (f64, &'static str) {
+    let h = normalize_header(header);
+    if h.is_empty() {
+        return (0.0, "header_similar");
+    }
+    let ascii = h.chars().all(|c| c.is_ascii_alphanumeric());
+    let mut best = (0.0f64, "header_similar");
+    let mut consider = |score: f64, reason: &'static str| {
+        if score > best.0 {
+            best = (score, reason);
+        }
+    };
+
+    for alias in spec.aliases.iter().copied().chain([spec.field]) {
+        let a = normalize_header(alias);
+        if a.is_empty() {
+            continue;
+        }
+        if h == a {
+            consider(1.0, "header_exact");
+            continue;
+        }
+        let (hl, al) = (h.chars().count(), a.chars().count());
+        if hl >= 2 && al >= 2 && (h.contains(&a) || a.contains(&h)) {
+            let ratio = hl.min(al) as f64 / hl.max(al) as f64;
+            consider(0.7 + 0.2 * ratio, "header_similar");
+        }
+        consider(
+            strsim::normalized_levenshtein(&h, &a).max(strsim::sorensen_dice(&h, &a)) * 0.85,
+            "header_similar",
+        );
+
+        // 拼音：ASCII 表头与汉字别名的全拼/首字母比较
+        if ascii && alias.chars().all(is_cjk) {
+            if let Some((full, initials)) = pinyin_forms(alias) {
+                if h == full {
+                    consider(0.95, "header_pinyin");
+                } else if h == initials && initials.len() >= 2 {
+                    consider(0.85, "header_pinyin");
+                } else {
+                    consider(
+                        strsim::normalized_levenshtein(&h, &full) * 0.8,
+                        "header_pinyin",
+                    );
+                }
+            }
+        }
+    }
+    best
+}
+
+fn is_datetime_like(v: &str) -> bool {
+    let has_sep = v.contains('-') || v.contains('/') || v.contains(':');
+    let compact = v.len() == 8 && v.chars().all(|c| c.is_ascii_digit()) && v.starts_with('2');
+    (has_sep || compact) && parse_datetime_to_utc(v).is_some()
+}
+
+fn is_flag_like(v: &str) -> bool {
+    matches!(
+        v.to_lowercase().as_str(),
+        "是" | "否" | "y" | "n" | "yes" | "no" | "true" | "false" | "1" | "0"
+    )
+}
+
+fn is_identifier_like(v: &str) -> bool {
+    v.len() >= 6
+        && v.chars().any(|c| c.is_ascii_digit())
+        && v.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !is_datetime_like(v)
+}
+
+/// 列值与类型特征的吻合度（非空样本中符合的比例）
+fn value_score(values: &[&str], kind: ValueKind) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let matches = |v: &str| -> bool {
+        match kind {
+            ValueKind::Identifier => is_identifier_like(v),
+            ValueKind::Code => {
+                v.chars().count() <= 16 && !v.contains(' ') && parse_datetime_to_utc(v).is_none()
+            }
+            ValueKind::Text => v.parse::<f64>().is_err() && !is_datetime_like(v),
+            ValueKind::Number { min, max } => v
+                .parse::<f64>()
+                .map(|n| n >= min && n <= max)
+                .unwrap_or(false),
+            ValueKind::Integer { min, max } => v
+                .parse::<i64>()
+                .map(|n| n >= min && n <= max)
+                .unwrap_or(false),
+            ValueKind::DateTime => is_datetime_like(v),
+            ValueKind::Flag => is_flag_like(v),
+        }
+    };
+    let ratio = values.iter().filter(|v| matches(v)).count() as f64 / values.len() as f64;
+    match kind {
+        // 编号类要求基本唯一
+        ValueKind::Identifier => {
+            let distinct = values.iter().collect::<HashSet<_>>().len() as f64;
+            ratio * (distinct / values.len() as f64)
+        }
+        _ => ratio,
+    }
+}
+
+/// 推断列值的统一形态：全部样本逐字符类别一致时返回正则（大写 [A-Z]、小写 [a-z]、数字 \d）
+fn detect_pattern(values: &[&str]) -> Option<String> {
+    fn shape(v: &str) -> Vec<String> {
+        v.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    "[A-Z]".to_string()
+                } else if c.is_ascii_lowercase() {
+                    "[a-z]".to_string()
+                } else if c.is_ascii_digit() {
+                    "\\d".to_string()
+                } else {
+                    regex::escape(&c.to_string())
+                }
+            })
+            .collect()
+    }
+    let first = shape(values.first()?);
+    if values.len() < 2 || first.len() < 4 || values.iter().any(|v| shape(v) != first) {
+        return None;
+    }
+    // 压缩连续相同的字符类：\d\d\d → \d{3}
+    let mut out = String::from("^");
+    let mut i = 0;
+    while i < first.len() {
+        let mut j = i;
+        while j < first.len() && first[j] == first[i] {
+            j += 1;
+        }
+        out.push_str(&first[i]);
+        if j - i > 1 {
+            out.push_str(&format!("{{{}}}", j - i));
+        }
+        i = j;
+    }
+    out.push('$');
+    Some(out)
+}
+
+/// 日期列的映射方式：8 位紧凑日期使用 date 映射并指定格式，其余直接映射
+fn date_mapping(values: &[&str]) -> (String, Option<String>) {
+    let compact = !values.is_empty()
+        && values
+            .iter()
+            .all(|v| v.len() == 8 && v.chars().all(|c| c.is_ascii_digit()));
+    if compact {
+        ("date".to_string(), Some("YYYYMMDD".to_string()))
+    } else {
+        ("direct".to_string(), None)
+    }
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// 生成映射建议
+pub fn suggest_mappings(
+    headers: &[String],
+    rows: &[Vec<String>],
+    min_confidence: f64,
+) -> MappingSuggestionResult {
+    let columns: Vec<Vec<&str>> = (0..headers.len())
+        .map(|col| {
+            rows.iter()
+                .take(VALUE_SAMPLE_ROWS)
+                .filter_map(|r| r.get(col).map(|v| v.trim()))
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .collect();
+
+    // 所有 (列, 目标) 候选
+    let mut candidates: Vec<MappingSuggestion> = Vec::new();
+    for (col, header) in headers.iter().enumerate() {
+        if header.trim().is_empty() {
+            continue;
+        }
+        let values = &columns[col];
+        for spec in TARGETS {
+            let (h_score, h_reason) = header_score(header, spec);
+            let v_score = value_score(values, spec.kind);
+            let (confidence, reason) = if h_score >= HEADER_HIT_SCORE {
+                // 无样本值时仅依据表头
+                let blended = if values.is_empty() {
+                    h_score
+                } else {
+                    0.75 * h_score + 0.25 * v_score
+                };
+                (blended, h_reason)
+            } else {
+                // 仅凭列值：只对区分度高的类型给出有限置信度
+                let weight = match spec.kind {
+                    ValueKind::DateTime | ValueKind::Identifier => 0.5,
+                    ValueKind::Number { .. } | ValueKind::Integer { .. } | ValueKind::Flag => 0.35,
+                    ValueKind::Code | ValueKind::Text => 0.0,
+                };
+                (0.3 * h_score + weight * v_score, "value_pattern")
+            };
+            if confidence < min_confidence {
+                continue;
+            }
+            let (mapping_type, source_format) = if spec.kind == ValueKind::DateTime {
+                date_mapping(values)
+            } else {
+                ("direct".to_string(), None)
+            };
+            candidates.push(MappingSuggestion {
+                source_field: header.trim().to_string(),
+                column_index: col,
+                target_field: spec.field.to_string(),
+                mapping_type,
+                source_format,
+                confidence: round2(confidence.min(1.0)),
+                header_score: round2(h_score),
+                value_score: round2(v_score),
+                reason: reason.to_string(),
+                detected_pattern: match spec.kind {
+                    ValueKind::Identifier | ValueKind::Code => detect_pattern(values),
+                    _ => None,
+                },
+            });
+        }
+    }
+
+    // 全局贪心：置信度高者优先，每列、每个目标字段只分配一次
+    candidates.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.column_index.cmp(&b.column_index))
+    });
+    let mut used_cols = HashSet::new();
+    let mut used_targets = HashSet::new();
+    let mut suggestions: Vec<MappingSuggestion> = Vec::new();
+    for c in candidates {
+        if used_cols.contains(&c.column_index) || used_targets.contains(&c.target_field) {
+            continue;
+        }
+        used_cols.insert(c.column_index);
+        used_targets.insert(c.target_field.clone());
+        suggestions.push(c);
+    }
+    suggestions.sort_by_key(|s| s.column_index);
+
+    let mappings = suggestions
+        .iter()
+        .map(|s| FieldMappingItem {
+            source_field: s.source_field.clone(),
+            target_field: s.target_field.clone(),
+            mapping_type: s.mapping_type.clone(),
+            default_value: None,
+            transform_rule: None,
+            source_format: s.source_format.clone(),
+        })
+        .collect();
+    let unmapped_headers = headers
+        .iter()
+        .enumerate()
+        .filter(|(i, h)| !h.trim().is_empty() && !used_cols.contains(i))
+        .map(|(_, h)| h.trim().to_string())
+        .collect();
+    let missing_required = TARGETS
+        .iter()
+        .filter(|t| t.required && !used_targets.contains(t.field))
+        .map(|t| t.field.to_string())
+        .collect();
+
+    MappingSuggestionResult {
+        suggestions,
+        mappings,
+        unmapped_headers,
+        missing_required,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn pinyin_table_covers_all_aliases() {
+        for spec in TARGETS {
+            for alias in spec.aliases.iter().filter(|a| a.chars().all(is_cjk)) {
+                assert!(pinyin_forms(alias).is_some(), "缺少拼音: {}", alias);
+            }
+        }
+        assert_eq!(
+            pinyin_forms("钢卷号"),
+            Some(("gangjuanhao".to_string(), "gjh".to_string()))
+        );
+    }
+
+    #[test]
+    fn suggests_by_fuzzy_pinyin_and_value_patterns() {
+        let headers = strings(&[
+            "卷号(主)",
+            "GZ",
+            "成品厚度(mm)",
+            "kuandu",
+            "净重/t",
+            "下线日期",
+            "列7",
+            "客户",
+        ]);
+        let rows: Vec<Vec<String>> = (1..=5)
+            .map(|i| {
+                strings(&[
+                    &format!("H2601000{}", i),
+                    "Q235B",
+                    "2.75",
+                    "1250",
+                    "21.5",
+                    &format!("2026-01-0{} 08:00:00", i),
+                    &format!("2026020{}", i),
+                    "某钢贸公司",
+                ])
+            })
+            .collect();
+
+        let result = suggest_mappings(&headers, &rows, DEFAULT_MIN_CONFIDENCE);
+        let target_of = |header: &str| {
+            result
+                .suggestions
+                .iter()
+                .find(|s| s.source_field == header)
+                .map(|s| s.target_field.as_str())
+        };
+        assert_eq!(target_of("卷号(主)"), Some("coil_id"));
+        assert_eq!(target_of("成品厚度(mm)"), Some("thickness"));
+        assert_eq!(target_of("kuandu"), Some("width"));
+        assert_eq!(target_of("净重/t"), Some("weight"));
+        assert_eq!(target_of("下线日期"), Some("coiling_time"));
+        assert_eq!(target_of("客户"), Some("customer_name"));
+        // 表头无意义的日期列仅凭列值推荐为交期，且识别为紧凑日期格式
+        let due = result
+            .suggestions
+            .iter()
+            .find(|s| s.source_field == "列7")
+            .expect("应按列值推荐");
+        assert_eq!(due.target_field, "due_date");
+        assert_eq!(due.reason, "value_pattern");
+        assert_eq!(due.source_format.as_deref(), Some("YYYYMMDD"));
+
+        let coil = result
+            .suggestions
+            .iter()
+            .find(|s| s.target_field == "coil_id")
+            .unwrap();
+        assert_eq!(coil.detected_pattern.as_deref(), Some("^[A-Z]\\d{8}$"));
+        assert_eq!(result.mappings.len(), result.suggestions.len());
+    }
+
+    #[test]
+    fn pinyin_initials_and_missing_required_are_reported() {
+        let headers = strings(&["GJH", "GZ", "备注"]);
+        let result = suggest_mappings(&headers, &[], DEFAULT_MIN_CONFIDENCE);
+        let coil = result
+            .suggestions
+            .iter()
+            .find(|s| s.source_field == "GJH")
+            .expect("应按拼音首字母匹配");
+        assert_eq!(coil.target_field, "coil_id");
+        assert_eq!(coil.reason, "header_pinyin");
+        assert!(result.missing_required.contains(&"thickness".to_string()));
+        assert!(!result.missing_required.contains(&"coil_id".to_string()));
+    }
+}
@@ -0,0 +1,384 @@
+//! 材料字段变更历史
+//!
+//! 导入覆盖/合并、人工调整状态与优先级、优先级重算时按字段记录 旧值 → 新值 及来源，
+//! 支持按钢卷查询时间线，并可将选中的变更恢复为旧值（恢复本身也记入历史）。
+
+use crate::db::get_db;
+use crate::models::{material, material_change_log};
+use crate::services::import_service::{value_to_display, IMPORTED_DATA_COLUMNS};
+use crate::AppError;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, Value,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub const SOURCE_IMPORT: &str = "import";
+pub const SOURCE_MANUAL: &str = "manual";
+pub const SOURCE_PRIORITY_RECOMPUTE: &str = "priority_recompute";
+pub const SOURCE_RESTORE: &str = "restore";
+pub const SOURCE_PLAN: &str = "plan";
+pub const SOURCE_PRODUCTION: &str = "production";
+
+/// 单次写入历史的分块大小
+const INSERT_CHUNK_SIZE: usize = 300;
+
+/// 待记录的字段变化
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChangeRecord {
+    pub material_id: i32,
+    pub coil_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// 字段恢复结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreFieldsResult {
+    pub restored: usize,
+    /// 当前值已不等于该变更的新值（之后又被修改），未强制时跳过
+    pub conflicts: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// 可恢复字段：导入数据字段与人工优先级调整（状态由生命周期驱动，不可直接恢复）
+fn is_restorable(col: material::Column) -> bool {
+    let name = sea_orm::IdenStatic::as_str(&col);
+    IMPORTED_DATA_COLUMNS
+        .iter()
+        .any(|c| sea_orm::IdenStatic::as_str(c) == name)
+        || matches!(col, material::Column::PriorityManualAdjust)
+}
+
+/// 比较两个版本的指定字段，返回有变化的字段记录
+pub fn diff_models(
+    old: &material::Model,
+    new: &material::Model,
+    columns: &[material::Column],
+) -> Vec<FieldChangeRecord> {
+    columns
+        .iter()
+        .filter_map(|col| {
+            let (before, after) = (old.get(*col), new.get(*col));
+            (before != after).then(|| FieldChangeRecord {
+                material_id: old.id,
+                coil_id: old.coil_id.clone(),
+                field: sea_orm::IdenStatic::as_str(col).to_string(),
+                old_value: value_to_display(&before),
+                new_value: value_to_display(&after),
+            })
+        })
+        .collect()
+}
+
+/// 批量写入变更历史（可在事务内调用）
+pub async fn record_changes<C: ConnectionTrait>(
+    conn: &C,
+    records: &[FieldChangeRecord],
+    source: &str,
+    source_ref: Option<&str>,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+    for chunk in records.chunks(INSERT_CHUNK_SIZE) {
+        let models = chunk.iter().map(|r| material_change_log::ActiveModel {
+            material_id: Set(r.material_id),
+            coil_id: Set(r.coil_id.clone()),
+            field_name: Set(r.field.clone()),
+            old_value: Set(r.old_value.clone()),
+            new_value: Set(r.new_value.clone()),
+            source: Set(source.to_string()),
+            source_ref: Set(source_ref.map(str::to_string)),
+            changed_at: Set(Some(now)),
+            ..Default::default()
+        });
+        material_change_log::Entity::insert_many(models)
+            .exec_without_returning(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// 查询钢卷的变更时间线（新的在前）；material_id 与 coil_id 至少提供一个
+pub async fn get_timeline(
+    material_id: Option<i32>,
+    coil_id: Option<&str>,
+    field: Option<&str>,
+    limit: Option<u64>,
+) -> Result<Vec<material_change_log::Model>, AppError> {
+    let mut query = material_change_log::Entity::find();
+    query = match (
+        material_id,
+        coil_id.map(str::trim).filter(|c| !c.is_empty()),
+    ) {
+        (Some(id), _) => query.filter(material_change_log::Column::MaterialId.eq(id)),
+        (None, Some(coil)) => query.filter(material_change_log::Column::CoilId.eq(coil)),
+        (None, None) => {
+            return Err(AppError::InvalidInput(
+                "查询变更历史需指定 material_id 或 coil_id".to_string(),
+            ))
+        }
+    };
+    if let Some(field) = field.filter(|f| !f.is_empty()) {
+        query = query.filter(material_change_log::Column::FieldName.eq(field));
+    }
+    Ok(query
+        .order_by_desc(material_change_log::Column::ChangedAt)
+        .order_by_desc(material_change_log::Column::Id)
+        .limit(limit.unwrap_or(500).min(5000))
+        .all(get_db())
+        .await?)
+}
+
+/// 将历史中的展示字符串解析回字段当前值的类型
+fn parse_field_value(template: &Value, raw: Option<&str>) -> Result<Value, String> {
+    let parse_err = |kind: &str| format!("无法将 {:?} 解析为{}", raw, kind);
+    Ok(match template {
+        Value::String(_) => Value::String(raw.map(|s| Box::new(s.to_string()))),
+        Value::Double(_) => Value::Double(
+            raw.map(|s| s.parse::<f64>().map_err(|_| parse_err("数值")))
+                .transpose()?,
+        ),
+        Value::Int(_) => Value::Int(
+            raw.map(|s| s.parse::<i32>().map_err(|_| parse_err("整数")))
+                .transpose()?,
+        ),
+        Value::Bool(_) => Value::Bool(
+            raw.map(|s| s.parse::<bool>().map_err(|_| parse_err("布尔值")))
+                .transpose()?,
+        ),
+        Value::ChronoDateTimeUtc(_) => Value::ChronoDateTimeUtc(
+            raw.map(|s| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                    .map(|dt| Box::new(Utc.from_utc_datetime(&dt)))
+                    .map_err(|_| parse_err("日期时间"))
+            })
+            .transpose()?,
+        ),
+        other => return Err(format!("不支持恢复的字段类型: {:?}", other)),
+    })
+}
+
+/// 将选中的变更恢复为旧值；同一字段选中多条时以最早的一条为准（恢复到该变更之前）
+///
+/// 当前值与变更的新值不一致说明之后又被修改，force 为 false 时记为冲突并跳过。
+/// 恢复后在同一事务内重算相关材料的适温状态与优先级。
+pub async fn restore_field_values(
+    change_ids: &[i32],
+    force: bool,
+) -> Result<RestoreFieldsResult, AppError> {
+    use sea_orm::TransactionTrait;
+
+    let db = get_db();
+    let mut changes = material_change_log::Entity::find()
+        .filter(material_change_log::Column::Id.is_in(change_ids.to_vec()))
+        .order_by_asc(material_change_log::Column::Id)
+        .all(db)
+        .await?;
+    let mut result = RestoreFieldsResult::default();
+    if changes.is_empty() {
+        return Ok(result);
+    }
+    // (material_id, field) 去重，保留最早的变更
+    let mut seen = std::collections::HashSet::new();
+    changes.retain(|c| seen.insert((c.material_id, c.field_name.clone())));
+
+    let mut by_material: HashMap<i32, Vec<material_change_log::Model>> = HashMap::new();
+    for change in changes {
+        by_material
+            .entry(change.material_id)
+            .or_default()
+            .push(change);
+    }
+
+    let tx = db.begin().await?;
+    let mut restored_ids = Vec::new();
+    let mut ids: Vec<i32> = by_material.keys().copied().collect();
+    ids.sort_unstable();
+    for material_id in ids {
+        let Some(current) = material::Entity::find_by_id(material_id).one(&tx).await? else {
+            result
+                .skipped
+                .push(format!("材料 id={} 已不存在", material_id));
+            continue;
+        };
+        let mut active: material::ActiveModel = current.clone().into();
+        let mut records = Vec::new();
+        for change in &by_material[&material_id] {
+            let label = format!("{}.{}(#{})", current.coil_id, change.field_name, change.id);
+            let Some(col) = material::Column::from_str(&change.field_name)
+                .ok()
+                .filter(|c| is_restorable(*c))
+            else {
+                result.skipped.push(format!("{}: 字段不可恢复", label));
+                continue;
+            };
+            let now_value = current.get(col);
+            let now_display = value_to_display(&now_value);
+            if now_display == change.old_value {
+                result.skipped.push(format!("{}: 已是旧值", label));
+                continue;
+            }
+            if now_display != change.new_value && !force {
+                result.conflicts.push(format!(
+                    "{}: 当前值 {:?} 与变更后的值 {:?} 不一致",
+                    label, now_display, change.new_value
+                ));
+                continue;
+            }
+            match parse_field_value(&now_value, change.old_value.as_deref()) {
+                Ok(value) => {
+                    active.set(col, value);
+                    records.push(FieldChangeRecord {
+                        material_id,
+                        coil_id: current.coil_id.clone(),
+                        field: change.field_name.clone(),
+                        old_value: now_display,
+                        new_value: change.old_value.clone(),
+                    });
+                }
+                Err(e) => result.skipped.push(format!("{}: {}", label, e)),
+            }
+        }
+        if records.is_empty() {
+            continue;
+        }
+        active.updated_at = Set(Some(Utc::now()));
+        active.update(&tx).await?;
+        let source_ref = format!(
+            "change:{}",
+            by_material[&material_id]
+                .iter()
+                .map(|c| c.id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        record_changes(&tx, &records, SOURCE_RESTORE, Some(&source_ref)).await?;
+        result.restored += records.len();
+        restored_ids.push(material_id);
+    }
+
+    // 恢复的字段可能影响适温与优先级，按恢复后的值重算
+    if !restored_ids.is_empty() {
+        let restored = material::Entity::find()
+            .filter(material::Column::Id.is_in(restored_ids.clone()))
+            .all(&tx)
+            .await?;
+        crate::services::temp_service::refresh_temper_status_of(&tx, &restored).await?;
+        let restored = material::Entity::find()
+            .filter(material::Column::Id.is_in(restored_ids))
+            .all(&tx)
+            .await?;
+        crate::engine::priority::batch_calculate_priorities(&tx, &restored).await?;
+    }
+    tx.commit().await?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_field_value_round_trips_display_strings() {
+        let dt = Utc.with_ymd_and_hms(2026, 1, 2, 8, 30, 0).unwrap();
+        let value = Value::ChronoDateTimeUtc(Some(Box::new(dt)));
+        let shown = value_to_display(&value);
+        assert_eq!(parse_field_value(&value, shown.as_deref()).unwrap(), value);
+        assert_eq!(
+            parse_field_value(&Value::Double(None), Some("2.5")).unwrap(),
+            Value::Double(Some(2.5))
+        );
+        assert_eq!(
+            parse_field_value(&Value::Bool(Some(true)), None).unwrap(),
+            Value::Bool(None)
+        );
+        assert!(parse_field_value(&Value::Int(Some(1)), Some("abc")).is_err());
+    }
+
+    #[tokio::test]
+    async fn timeline_and_restore_follow_recorded_changes() {
+        let seed = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_history_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let original = material::ActiveModel {
+            coil_id: Set(format!("H{}", seed)),
+            steel_grade: Set("Q235".to_string()),
+            thickness: Set(2.5),
+            width: Set(1250.0),
+            weight: Set(20.0),
+            coiling_time: Set(Utc::now()),
+            status: Set(Some("pending".to_string())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("写入测试材料失败");
+
+        // 人工修改钢种与重量
+        let mut active: material::ActiveModel = original.clone().into();
+        active.steel_grade = Set("Q345".to_string());
+        active.weight = Set(22.5);
+        let changed = active.update(db).await.unwrap();
+        let records = diff_models(
+            &original,
+            &changed,
+            &[
+                material::Column::SteelGrade,
+                material::Column::Weight,
+                material::Column::Width,
+            ],
+        );
+        assert_eq!(records.len(), 2);
+        record_changes(db, &records, SOURCE_MANUAL, Some("test"))
+            .await
+            .unwrap();
+
+        let timeline = get_timeline(None, Some(&original.coil_id), None, None)
+            .await
+            .unwrap();
+        assert_eq!(timeline.len(), 2);
+        let grade = timeline
+            .iter()
+            .find(|c| c.field_name == "steel_grade")
+            .unwrap();
+        let weight = timeline.iter().find(|c| c.field_name == "weight").unwrap();
+        assert_eq!(grade.old_value.as_deref(), Some("Q235"));
+
+        // 重量在记录之后又被改动：未强制时报告冲突，钢种正常恢复
+        let mut active: material::ActiveModel = changed.into();
+        active.weight = Set(30.0);
+        active.update(db).await.unwrap();
+        let result = restore_field_values(&[grade.id, weight.id], false)
+            .await
+            .unwrap();
+        assert_eq!(result.restored, 1);
+        assert_eq!(result.conflicts.len(), 1);
+
+        let current = material::Entity::find_by_id(original.id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(current.steel_grade, "Q235");
+        assert_eq!(current.weight, 30.0);
+        // 恢复后按新值重算适温与优先级
+        assert!(current.temp_status.is_some());
+        assert!(current.priority_final.is_some());
+
+        let result = restore_field_values(&[weight.id], true).await.unwrap();
+        assert_eq!(result.restored, 1);
+        let restored = get_timeline(Some(original.id), None, Some("weight"), None)
+            .await
+            .unwrap();
+        assert_eq!(restored[0].source, SOURCE_RESTORE);
+        assert_eq!(restored[0].new_value.as_deref(), Some("20"));
+    }
+}
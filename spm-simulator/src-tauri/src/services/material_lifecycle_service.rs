@@ -0,0 +1,594 @@
+//! 材料生命周期
+//!
+//! pending → planned（被已确认方案锁定）→ in_production → completed；未完工材料可冻结（需原因），
+//! 解冻时若锁定方案仍处于生效状态（确认或执行中）则回到 planned，否则回到 pending。
+//! 方案确认/取消确认由 update_plan_status 驱动，开工/完工/中止由生产实绩反馈驱动。
+
+use crate::db::get_db;
+use crate::models::{material, schedule_item, schedule_plan};
+use crate::services::material_history_service::{self, FieldChangeRecord};
+use crate::services::plan_workflow_service;
+use crate::AppError;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_PLANNED: &str = "planned";
+pub const STATUS_IN_PRODUCTION: &str = "in_production";
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_FROZEN: &str = "frozen";
+
+/// 生产实绩反馈
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductionFeedback {
+    pub coil_id: String,
+    /// start 开工 / finish 完工 / abort 中止（退回 planned）
+    pub event: String,
+    /// 实绩所属方案；提供时校验材料确由该方案锁定
+    pub plan_id: Option<i32>,
+    pub reason: Option<String>,
+}
+
+/// 生产实绩反馈处理结果（逐条处理，失败条目不影响其他条目）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedbackResult {
+    pub applied: usize,
+    pub errors: Vec<String>,
+}
+
+/// 方案锁定同步结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReservationSummary {
+    pub reserved: usize,
+    pub released: usize,
+}
+
+/// 生命周期允许的状态迁移
+pub fn can_transition(from: &str, to: &str) -> bool {
+    matches!(
+        (from, to),
+        (STATUS_PENDING, STATUS_PLANNED)
+            | (STATUS_PENDING, STATUS_FROZEN)
+            | (STATUS_PLANNED, STATUS_PENDING)
+            | (STATUS_PLANNED, STATUS_IN_PRODUCTION)
+            | (STATUS_PLANNED, STATUS_COMPLETED)
+            | (STATUS_PLANNED, STATUS_FROZEN)
+            | (STATUS_IN_PRODUCTION, STATUS_PLANNED)
+            | (STATUS_IN_PRODUCTION, STATUS_COMPLETED)
+            | (STATUS_FROZEN, STATUS_PENDING)
+            | (STATUS_FROZEN, STATUS_PLANNED)
+    )
+}
+
+fn status_of(m: &material::Model) -> &str {
+    m.status.as_deref().unwrap_or(STATUS_PENDING)
+}
+
+/// 材料能否排入指定方案：待排材料，或已被该方案锁定的材料
+pub fn is_available_for_plan(m: &material::Model, plan_id: i32) -> bool {
+    match status_of(m) {
+        STATUS_PENDING => true,
+        STATUS_PLANNED => m.reserved_plan_id.map_or(true, |id| id == plan_id),
+        _ => false,
+    }
+}
+
+/// 说明材料不能排入指定方案的原因
+fn unavailable_reason(m: &material::Model, plan_id: i32) -> Option<String> {
+    if is_available_for_plan(m, plan_id) {
+        return None;
+    }
+    Some(match (status_of(m), m.reserved_plan_id) {
+        (STATUS_PLANNED, Some(other)) => format!("{} 已被方案 id={} 锁定", m.coil_id, other),
+        (STATUS_FROZEN, _) => format!("{} 已冻结", m.coil_id),
+        (STATUS_IN_PRODUCTION, _) => format!("{} 正在生产", m.coil_id),
+        (STATUS_COMPLETED, _) => format!("{} 已完工", m.coil_id),
+        (other, _) => format!("{} 状态为 {}", m.coil_id, other),
+    })
+}
+
+/// 校验材料均可排入指定方案，否则返回 ConstraintViolation
+pub fn ensure_available(materials: &[material::Model], plan_id: i32) -> Result<(), AppError> {
+    let reasons: Vec<String> = materials
+        .iter()
+        .filter_map(|m| unavailable_reason(m, plan_id))
+        .collect();
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    let preview = reasons
+        .iter()
+        .take(10)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("; ");
+    let suffix = if reasons.len() > 10 { " ..." } else { "" };
+    Err(AppError::ConstraintViolation(format!(
+        "{} 个材料不可排入当前方案: {}{}",
+        reasons.len(),
+        preview,
+        suffix
+    )))
+}
+
+/// 按 id 在给定连接上加载材料并校验均可排入指定方案（写入排程项前在同一事务内调用）
+pub async fn ensure_ids_available<C: ConnectionTrait>(
+    conn: &C,
+    material_ids: &[i32],
+    plan_id: i32,
+) -> Result<(), AppError> {
+    if material_ids.is_empty() {
+        return Ok(());
+    }
+    let materials = material::Entity::find()
+        .filter(material::Column::Id.is_in(material_ids.iter().copied()))
+        .all(conn)
+        .await?;
+    ensure_available(&materials, plan_id)
+}
+
+/// 写入状态迁移并返回历史记录；reserved_plan_id 为 None 时保留原值
+async fn apply_status<C: ConnectionTrait>(
+    conn: &C,
+    m: &material::Model,
+    to: &str,
+    reserved_plan_id: Option<Option<i32>>,
+    reason: Option<&str>,
+) -> Result<FieldChangeRecord, AppError> {
+    let mut active: material::ActiveModel = m.clone().into();
+    active.status = Set(Some(to.to_string()));
+    if let Some(plan) = reserved_plan_id {
+        active.reserved_plan_id = Set(plan);
+    }
+    active.status_reason = Set(reason.map(str::to_string));
+    active.updated_at = Set(Some(chrono::Utc::now()));
+    active.update(conn).await?;
+    Ok(FieldChangeRecord {
+        material_id: m.id,
+        coil_id: m.coil_id.clone(),
+        field: "status".to_string(),
+        old_value: Some(status_of(m).to_string()),
+        new_value: Some(to.to_string()),
+    })
+}
+
+/// 按方案当前状态同步材料锁定：生效状态（确认/执行中）锁定方案内全部待排材料并释放已移出的材料，
+/// 其他状态释放该方案锁定的全部材料。方案内存在不可锁定的材料时返回错误。
+pub async fn sync_plan_reservations<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+) -> Result<ReservationSummary, AppError> {
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let reserving = plan_workflow_service::is_effective(plan_workflow_service::status_of(&plan));
+
+    let item_ids: HashSet<i32> = if reserving {
+        schedule_item::Entity::find()
+            .filter(schedule_item::Column::PlanId.eq(plan_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|item| item.material_id)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let in_plan = material::Entity::find()
+        .filter(material::Column::Id.is_in(item_ids.iter().copied()))
+        .all(conn)
+        .await?;
+    // 已由本方案锁定的材料（含生产中、已完工、锁定后冻结）无需再校验
+    let newcomers: Vec<material::Model> = in_plan
+        .iter()
+        .filter(|m| m.reserved_plan_id != Some(plan_id))
+        .cloned()
+        .collect();
+    ensure_available(&newcomers, plan_id)?;
+
+    let mut summary = ReservationSummary::default();
+    let mut records = Vec::new();
+    for m in in_plan.iter().filter(|m| status_of(m) == STATUS_PENDING) {
+        records.push(apply_status(conn, m, STATUS_PLANNED, Some(Some(plan_id)), None).await?);
+        summary.reserved += 1;
+    }
+
+    summary.released = release_held(conn, plan_id, &item_ids, &mut records).await?;
+
+    material_history_service::record_changes(
+        conn,
+        &records,
+        material_history_service::SOURCE_PLAN,
+        Some(&format!("plan:{}", plan_id)),
+    )
+    .await?;
+    Ok(summary)
+}
+
+/// 释放方案锁定的全部材料（方案删除时调用）
+pub async fn release_plan_reservations<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+) -> Result<usize, AppError> {
+    let mut records = Vec::new();
+    let released = release_held(conn, plan_id, &HashSet::new(), &mut records).await?;
+    material_history_service::record_changes(
+        conn,
+        &records,
+        material_history_service::SOURCE_PLAN,
+        Some(&format!("plan:{}", plan_id)),
+    )
+    .await?;
+    Ok(released)
+}
+
+/// 释放方案锁定但不在 keep 中的材料，返回释放数量
+async fn release_held<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    keep: &HashSet<i32>,
+    records: &mut Vec<FieldChangeRecord>,
+) -> Result<usize, AppError> {
+    let held = material::Entity::find()
+        .filter(material::Column::ReservedPlanId.eq(plan_id))
+        .all(conn)
+        .await?;
+    let mut released = 0;
+    for m in held.iter().filter(|m| !keep.contains(&m.id)) {
+        match status_of(m) {
+            STATUS_PLANNED => {
+                records.push(apply_status(conn, m, STATUS_PENDING, Some(None), None).await?);
+                released += 1;
+            }
+            // 冻结材料解除锁定，解冻后回到 pending
+            STATUS_FROZEN => {
+                let mut active: material::ActiveModel = m.clone().into();
+                active.reserved_plan_id = Set(None);
+                active.update(conn).await?;
+                released += 1;
+            }
+            // 生产中/已完工材料保留方案关联作为生产记录
+            _ => {}
+        }
+    }
+    Ok(released)
+}
+
+/// 人工变更材料状态：仅允许冻结、解冻（目标 pending）与生产中材料的人工完工
+///
+/// 冻结与解冻须填写原因；任一材料迁移不合法时整批拒绝。
+pub async fn apply_manual_status(
+    ids: &[i32],
+    target: &str,
+    reason: Option<&str>,
+) -> Result<u64, AppError> {
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+    if !matches!(target, STATUS_PENDING | STATUS_FROZEN | STATUS_COMPLETED) {
+        return Err(AppError::ConstraintViolation(format!(
+            "材料状态 {} 由方案确认或生产实绩驱动，不能手工设置；允许值: pending(解冻)/frozen/completed",
+            target
+        )));
+    }
+    if target != STATUS_COMPLETED && reason.is_none() {
+        return Err(AppError::InvalidInput(
+            "冻结/解冻材料须填写原因".to_string(),
+        ));
+    }
+
+    let db = get_db();
+    let tx = db.begin().await?;
+    let materials = material::Entity::find()
+        .filter(material::Column::Id.is_in(ids.to_vec()))
+        .all(&tx)
+        .await?;
+    let reserving_plans = confirmed_plan_ids(&tx, &materials).await?;
+
+    let mut moves = Vec::new();
+    let mut invalid = Vec::new();
+    for m in &materials {
+        let from = status_of(m);
+        if from == target {
+            continue;
+        }
+        // 解冻：锁定方案仍生效时回到 planned
+        let to = match (from, target) {
+            (STATUS_FROZEN, STATUS_PENDING)
+                if m.reserved_plan_id
+                    .is_some_and(|id| reserving_plans.contains(&id)) =>
+            {
+                STATUS_PLANNED
+            }
+            _ => target,
+        };
+        let manual = match to {
+            STATUS_COMPLETED => from == STATUS_IN_PRODUCTION,
+            STATUS_PENDING | STATUS_PLANNED => from == STATUS_FROZEN,
+            _ => true,
+        };
+        if manual && can_transition(from, to) {
+            moves.push((m, to));
+        } else {
+            invalid.push(format!("{}({} → {})", m.coil_id, from, target));
+        }
+    }
+    if !invalid.is_empty() {
+        return Err(AppError::ConstraintViolation(format!(
+            "不允许的材料状态迁移: {}",
+            invalid.join(", ")
+        )));
+    }
+
+    let mut records = Vec::with_capacity(moves.len());
+    for (m, to) in &moves {
+        let reserved = (*to == STATUS_PENDING).then_some(None);
+        records.push(apply_status(&tx, m, to, reserved, reason).await?);
+    }
+    material_history_service::record_changes(
+        &tx,
+        &records,
+        material_history_service::SOURCE_MANUAL,
+        Some("update_material_status"),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(moves.len() as u64)
+}
+
+/// 材料锁定方案中仍生效的方案 id
+async fn confirmed_plan_ids<C: ConnectionTrait>(
+    conn: &C,
+    materials: &[material::Model],
+) -> Result<HashSet<i32>, AppError> {
+    let plan_ids: HashSet<i32> = materials
+        .iter()
+        .filter_map(|m| m.reserved_plan_id)
+        .collect();
+    if plan_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+    Ok(schedule_plan::Entity::find()
+        .filter(schedule_plan::Column::Id.is_in(plan_ids))
+        .filter(
+            schedule_plan::Column::Status
+                .is_in(plan_workflow_service::EFFECTIVE_STATUSES.iter().copied()),
+        )
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect())
+}
+
+/// 处理生产实绩反馈：开工 planned → in_production，完工 → completed，中止 in_production → planned
+///
+/// 逐条校验的失败记入 errors 并跳过，数据库写入失败时整批回滚。
+pub async fn apply_production_feedback(
+    entries: &[ProductionFeedback],
+) -> Result<FeedbackResult, AppError> {
+    let db = get_db();
+    let tx = db.begin().await?;
+    let coil_ids: Vec<String> = entries
+        .iter()
+        .map(|e| e.coil_id.trim().to_string())
+        .collect();
+    let mut materials: HashMap<String, material::Model> = material::Entity::find()
+        .filter(material::Column::CoilId.is_in(coil_ids))
+        .all(&tx)
+        .await?
+        .into_iter()
+        .map(|m| (m.coil_id.clone(), m))
+        .collect();
+
+    let mut result = FeedbackResult::default();
+    for entry in entries {
+        let coil_id = entry.coil_id.trim();
+        let Some(m) = materials.get(coil_id) else {
+            result.errors.push(format!("{}: 材料不存在", coil_id));
+            continue;
+        };
+        let from = status_of(m);
+        let to = match entry.event.trim().to_lowercase().as_str() {
+            "start" => STATUS_IN_PRODUCTION,
+            "finish" => STATUS_COMPLETED,
+            "abort" => STATUS_PLANNED,
+            other => {
+                result
+                    .errors
+                    .push(format!("{}: 未知实绩事件 {}", coil_id, other));
+                continue;
+            }
+        };
+        if let Some(plan_id) = entry.plan_id {
+            if m.reserved_plan_id != Some(plan_id) {
+                result.errors.push(format!(
+                    "{}: 未被方案 id={} 锁定（当前锁定方案 {:?}）",
+                    coil_id, plan_id, m.reserved_plan_id
+                ));
+                continue;
+            }
+        }
+        let valid = match to {
+            STATUS_PLANNED => from == STATUS_IN_PRODUCTION,
+            _ => can_transition(from, to),
+        };
+        if !valid {
+            result
+                .errors
+                .push(format!("{}: 状态 {} 不能迁移到 {}", coil_id, from, to));
+            continue;
+        }
+
+        let record = apply_status(&tx, m, to, None, entry.reason.as_deref()).await?;
+        material_history_service::record_changes(
+            &tx,
+            &[record],
+            material_history_service::SOURCE_PRODUCTION,
+            Some(&entry.event),
+        )
+        .await?;
+        if let Some(m) = materials.get_mut(coil_id) {
+            m.status = Some(to.to_string());
+            m.status_reason = entry.reason.clone();
+        }
+        result.applied += 1;
+    }
+    tx.commit().await?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_transitions_follow_state_machine() {
+        assert!(can_transition(STATUS_PENDING, STATUS_PLANNED));
+        assert!(can_transition(STATUS_PLANNED, STATUS_IN_PRODUCTION));
+        assert!(can_transition(STATUS_IN_PRODUCTION, STATUS_COMPLETED));
+        assert!(can_transition(STATUS_FROZEN, STATUS_PLANNED));
+        assert!(!can_transition(STATUS_PENDING, STATUS_COMPLETED));
+        assert!(!can_transition(STATUS_PENDING, STATUS_IN_PRODUCTION));
+        assert!(!can_transition(STATUS_COMPLETED, STATUS_PENDING));
+        assert!(!can_transition(STATUS_IN_PRODUCTION, STATUS_FROZEN));
+    }
+
+    #[test]
+    fn availability_respects_reservation_owner() {
+        let mut m = crate::engine::test_helpers::helpers::make_material(1, "C1", 1000.0, 20.0);
+        m.status = Some(STATUS_PLANNED.to_string());
+        m.reserved_plan_id = Some(7);
+        assert!(is_available_for_plan(&m, 7));
+        assert!(!is_available_for_plan(&m, 8));
+        let err = ensure_available(&[m.clone()], 8).unwrap_err().to_string();
+        assert!(err.contains("方案 id=7"));
+
+        m.status = Some(STATUS_PENDING.to_string());
+        m.reserved_plan_id = None;
+        assert!(is_available_for_plan(&m, 8));
+        m.status = Some(STATUS_FROZEN.to_string());
+        assert!(!is_available_for_plan(&m, 8));
+    }
+
+    #[tokio::test]
+    async fn plan_confirmation_and_feedback_drive_material_status() {
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_lifecycle_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let coil = material::ActiveModel {
+            coil_id: Set(format!("L{}", seed)),
+            steel_grade: Set("Q235".to_string()),
+            thickness: Set(2.5),
+            width: Set(1250.0),
+            weight: Set(20.0),
+            coiling_time: Set(chrono::Utc::now()),
+            status: Set(Some(STATUS_PENDING.to_string())),
+            temp_status: Set(Some("ready".to_string())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("写入测试材料失败");
+        let new_plan = |no: &str| schedule_plan::ActiveModel {
+            plan_no: Set(format!("{}-{}", no, seed)),
+            name: Set(no.to_string()),
+            period_type: Set("daily".to_string()),
+            start_date: Set("2026-02-13".to_string()),
+            end_date: Set("2026-02-13".to_string()),
+            status: Set(Some("draft".to_string())),
+            ..Default::default()
+        };
+        let plan_a = new_plan("LA").insert(db).await.unwrap();
+        let plan_b = new_plan("LB").insert(db).await.unwrap();
+        for plan in [&plan_a, &plan_b] {
+            schedule_item::ActiveModel {
+                plan_id: Set(plan.id),
+                material_id: Set(coil.id),
+                sequence: Set(1),
+                shift_date: Set("2026-02-13".to_string()),
+                shift_no: Set(1),
+                shift_type: Set("day".to_string()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .unwrap();
+        }
+        let set_status = |plan: &schedule_plan::Model, status: &str| {
+            let mut active: schedule_plan::ActiveModel = plan.clone().into();
+            active.status = Set(Some(status.to_string()));
+            active
+        };
+        let reload = || async {
+            material::Entity::find_by_id(coil.id)
+                .one(db)
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        // 确认方案 A 锁定材料，方案 B 再确认被拒绝
+        set_status(&plan_a, "confirmed").update(db).await.unwrap();
+        let summary = sync_plan_reservations(db, plan_a.id).await.unwrap();
+        assert_eq!(summary.reserved, 1);
+        let m = reload().await;
+        assert_eq!(m.status.as_deref(), Some(STATUS_PLANNED));
+        assert_eq!(m.reserved_plan_id, Some(plan_a.id));
+        set_status(&plan_b, "confirmed").update(db).await.unwrap();
+        assert!(sync_plan_reservations(db, plan_b.id).await.is_err());
+        set_status(&plan_b, "draft").update(db).await.unwrap();
+        // 合并/导入/派生/回滚写入排程项前同样按锁定校验
+        assert!(ensure_ids_available(db, &[coil.id], plan_a.id)
+            .await
+            .is_ok());
+        assert!(ensure_ids_available(db, &[coil.id], plan_b.id)
+            .await
+            .is_err());
+
+        // 实绩：非法事件与方案不符的条目报错，其余条目生效
+        let feedback = |event: &str, plan_id: Option<i32>| ProductionFeedback {
+            coil_id: coil.coil_id.clone(),
+            event: event.to_string(),
+            plan_id,
+            reason: None,
+        };
+        let result = apply_production_feedback(&[
+            feedback("start", Some(plan_b.id)),
+            feedback("start", Some(plan_a.id)),
+            feedback("bogus", None),
+        ])
+        .await
+        .unwrap();
+        assert_eq!((result.applied, result.errors.len()), (1, 2));
+        assert_eq!(reload().await.status.as_deref(), Some(STATUS_IN_PRODUCTION));
+
+        // 生产中材料不能冻结；方案取消确认后仍保留生产关联
+        assert!(
+            apply_manual_status(&[coil.id], STATUS_FROZEN, Some("质量异议"))
+                .await
+                .is_err()
+        );
+        set_status(&plan_a, "saved").update(db).await.unwrap();
+        assert_eq!(
+            sync_plan_reservations(db, plan_a.id)
+                .await
+                .unwrap()
+                .released,
+            0
+        );
+        let result = apply_production_feedback(&[feedback("finish", None)])
+            .await
+            .unwrap();
+        assert_eq!(result.applied, 1);
+        let m = reload().await;
+        assert_eq!(m.status.as_deref(), Some(STATUS_COMPLETED));
+        assert_eq!(m.reserved_plan_id, Some(plan_a.id));
+    }
+}
@@ -1,7 +1,20 @@
 pub mod backup_service;
+pub mod config_bundle_service;
 pub mod error_tracking_service;
 pub mod export_service;
+pub mod handoff_service;
 pub mod import_service;
+pub mod import_validation_service;
+pub mod import_watch_service;
+pub mod mapping_suggest_service;
+pub mod material_history_service;
+pub mod material_lifecycle_service;
 pub mod performance_service;
+pub mod plan_json_service;
+pub mod plan_workflow_service;
+pub mod report_service;
+pub mod source_reader_service;
+pub mod strategy_revision_service;
+pub mod strategy_schema_service;
 pub mod temp_service;
 pub mod undo_service;
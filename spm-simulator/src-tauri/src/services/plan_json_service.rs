@@ -0,0 +1,395 @@
+//! 方案 JSON 交换格式
+//!
+//! 顶层结构（schema_version = 1）：
+//!   - format / schema_version / exported_at 文件标识与版本
+//!   - plan       方案元信息
+//!   - strategy   策略快照（与策略模板导出文件字段一致，配置为 JSON 字符串）
+//!   - items      排程项（按序），含钢卷属性快照
+//!   - roll_changes 换辊点
+//!   - scores     评分与风险统计
+//!
+//! 导入方以 coil_id 匹配本地材料；钢卷属性快照仅用于核对，不回写材料表。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::AppError;
+
+pub const PLAN_JSON_FORMAT: &str = "spm-plan";
+pub const PLAN_JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDocument {
+    pub format: String,
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub plan: PlanDocMeta,
+    #[serde(default)]
+    pub strategy: Option<PlanDocStrategy>,
+    pub items: Vec<PlanDocItem>,
+    #[serde(default)]
+    pub roll_changes: Vec<PlanDocRollChange>,
+    #[serde(default)]
+    pub scores: PlanDocScores,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDocMeta {
+    pub plan_no: String,
+    pub name: String,
+    pub period_type: String,
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub version: Option<i32>,
+    #[serde(default)]
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanDocStrategy {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub sort_weights: String,
+    pub constraints: String,
+    #[serde(default)]
+    pub soft_constraints: Option<String>,
+    pub eval_weights: String,
+    pub temper_rules: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDocItem {
+    pub sequence: i32,
+    pub coil_id: String,
+    pub shift_date: String,
+    pub shift_no: i32,
+    pub shift_type: String,
+    #[serde(default)]
+    pub planned_start: Option<String>,
+    #[serde(default)]
+    pub planned_end: Option<String>,
+    #[serde(default)]
+    pub cumulative_weight: Option<f64>,
+    #[serde(default)]
+    pub is_roll_change: bool,
+    #[serde(default)]
+    pub is_locked: bool,
+    #[serde(default)]
+    pub lock_reason: Option<String>,
+    pub coil: PlanDocCoil,
+}
+
+/// 钢卷属性快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDocCoil {
+    pub steel_grade: String,
+    pub thickness: f64,
+    pub width: f64,
+    pub weight: f64,
+    #[serde(default)]
+    pub hardness_level: Option<String>,
+    #[serde(default)]
+    pub surface_level: Option<String>,
+    #[serde(default)]
+    pub product_type: Option<String>,
+    #[serde(default)]
+    pub contract_no: Option<String>,
+    #[serde(default)]
+    pub customer_name: Option<String>,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    #[serde(default)]
+    pub storage_loc: Option<String>,
+    #[serde(default)]
+    pub temp_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDocRollChange {
+    pub sequence: i32,
+    pub coil_id: String,
+    #[serde(default)]
+    pub cumulative_weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanDocScores {
+    pub total_count: Option<i32>,
+    pub total_weight: Option<f64>,
+    pub roll_change_count: Option<i32>,
+    pub score_overall: Option<i32>,
+    pub score_sequence: Option<i32>,
+    pub score_delivery: Option<i32>,
+    pub score_efficiency: Option<i32>,
+    pub risk_high: Option<i32>,
+    pub risk_medium: Option<i32>,
+    pub risk_low: Option<i32>,
+}
+
+/// 与本地材料属性不一致的钢卷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoilAttributeMismatch {
+    pub coil_id: String,
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// 解析并校验方案 JSON：文件标识、版本、排程项序号与卷号唯一性
+pub fn parse_plan_document(content: &str) -> Result<PlanDocument, AppError> {
+    let doc: PlanDocument = serde_json::from_str(content)
+        .map_err(|e| AppError::FileFormatError(format!("方案JSON解析失败: {}", e)))?;
+    if doc.format != PLAN_JSON_FORMAT {
+        return Err(AppError::FileFormatError(format!(
+            "不是方案交换文件: format={}",
+            doc.format
+        )));
+    }
+    if doc.schema_version == 0 || doc.schema_version > PLAN_JSON_SCHEMA_VERSION {
+        return Err(AppError::FileFormatError(format!(
+            "不支持的方案文件版本 {}（当前支持 ≤ {}）",
+            doc.schema_version, PLAN_JSON_SCHEMA_VERSION
+        )));
+    }
+
+    let mut coils = HashSet::new();
+    let mut sequences = HashSet::new();
+    for item in &doc.items {
+        if item.coil_id.trim().is_empty() {
+            return Err(AppError::FileFormatError(format!(
+                "第 {} 项缺少卷号",
+                item.sequence
+            )));
+        }
+        if !coils.insert(item.coil_id.as_str()) {
+            return Err(AppError::FileFormatError(format!(
+                "卷号重复: {}",
+                item.coil_id
+            )));
+        }
+        if !sequences.insert(item.sequence) {
+            return Err(AppError::FileFormatError(format!(
+                "排程序号重复: {}",
+                item.sequence
+            )));
+        }
+    }
+    Ok(doc)
+}
+
+/// 从数据库装配方案交换文档
+pub async fn build_plan_document(plan_id: i32) -> Result<PlanDocument, AppError> {
+    use crate::db::get_db;
+    use crate::models::{material, schedule_item, schedule_plan};
+    use sea_orm::*;
+
+    let db = get_db();
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let strategy =
+        crate::services::strategy_revision_service::load_plan_strategy(db, &plan).await?;
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(db)
+        .await?;
+    let mat_ids: Vec<i32> = items.iter().map(|it| it.material_id).collect();
+    let mat_map: std::collections::HashMap<i32, material::Model> = if mat_ids.is_empty() {
+        std::collections::HashMap::new()
+    } else {
+        material::Entity::find()
+            .filter(material::Column::Id.is_in(mat_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect()
+    };
+
+    let doc_items: Vec<PlanDocItem> = items
+        .iter()
+        .filter_map(|it| {
+            let m = mat_map.get(&it.material_id)?;
+            Some(PlanDocItem {
+                sequence: it.sequence,
+                coil_id: m.coil_id.clone(),
+                shift_date: it.shift_date.clone(),
+                shift_no: it.shift_no,
+                shift_type: it.shift_type.clone(),
+                planned_start: it.planned_start.clone(),
+                planned_end: it.planned_end.clone(),
+                cumulative_weight: it.cumulative_weight,
+                is_roll_change: it.is_roll_change.unwrap_or(false),
+                is_locked: it.is_locked.unwrap_or(false),
+                lock_reason: it.lock_reason.clone(),
+                coil: PlanDocCoil {
+                    steel_grade: m.steel_grade.clone(),
+                    thickness: m.thickness,
+                    width: m.width,
+                    weight: m.weight,
+                    hardness_level: m.hardness_level.clone(),
+                    surface_level: m.surface_level.clone(),
+                    product_type: m.product_type.clone(),
+                    contract_no: m.contract_no.clone(),
+                    customer_name: m.customer_name.clone(),
+                    due_date: m.due_date.map(|d| d.to_rfc3339()),
+                    storage_loc: m.storage_loc.clone(),
+                    temp_status: m.temp_status.clone(),
+                },
+            })
+        })
+        .collect();
+    let roll_changes = doc_items
+        .iter()
+        .filter(|it| it.is_roll_change)
+        .map(|it| PlanDocRollChange {
+            sequence: it.sequence,
+            coil_id: it.coil_id.clone(),
+            cumulative_weight: it.cumulative_weight,
+        })
+        .collect();
+
+    let doc = PlanDocument {
+        format: PLAN_JSON_FORMAT.to_string(),
+        schema_version: PLAN_JSON_SCHEMA_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        plan: PlanDocMeta {
+            plan_no: plan.plan_no.clone(),
+            name: plan.name.clone(),
+            period_type: plan.period_type.clone(),
+            start_date: plan.start_date.clone(),
+            end_date: plan.end_date.clone(),
+            status: plan.status.clone(),
+            version: plan.version,
+            remarks: plan.remarks.clone(),
+        },
+        strategy: strategy.map(|s| PlanDocStrategy {
+            name: s.name,
+            description: s.description,
+            sort_weights: s.sort_weights,
+            constraints: s.constraints,
+            soft_constraints: s.soft_constraints,
+            eval_weights: s.eval_weights,
+            temper_rules: s.temper_rules,
+        }),
+        items: doc_items,
+        roll_changes,
+        scores: PlanDocScores {
+            total_count: plan.total_count,
+            total_weight: plan.total_weight,
+            roll_change_count: plan.roll_change_count,
+            score_overall: plan.score_overall,
+            score_sequence: plan.score_sequence,
+            score_delivery: plan.score_delivery,
+            score_efficiency: plan.score_efficiency,
+            risk_high: plan.risk_count_high,
+            risk_medium: plan.risk_count_medium,
+            risk_low: plan.risk_count_low,
+        },
+    };
+
+    Ok(doc)
+}
+
+/// 核对钢卷快照与本地材料的关键属性（钢种、规格、重量）
+pub fn compare_coil_attributes(
+    coil_id: &str,
+    snapshot: &PlanDocCoil,
+    local: &crate::models::material::Model,
+) -> Vec<CoilAttributeMismatch> {
+    let mut out = Vec::new();
+    let mut push = |field: &str, expected: String, actual: String| {
+        out.push(CoilAttributeMismatch {
+            coil_id: coil_id.to_string(),
+            field: field.to_string(),
+            expected,
+            actual,
+        });
+    };
+    if snapshot.steel_grade != local.steel_grade {
+        push(
+            "steel_grade",
+            snapshot.steel_grade.clone(),
+            local.steel_grade.clone(),
+        );
+    }
+    for (field, expected, actual) in [
+        ("thickness", snapshot.thickness, local.thickness),
+        ("width", snapshot.width, local.width),
+        ("weight", snapshot.weight, local.weight),
+    ] {
+        if (expected - actual).abs() > 1e-6 {
+            push(field, expected.to_string(), actual.to_string());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_json(format: &str, version: u32, coils: &[(&str, i32)]) -> String {
+        let items: Vec<serde_json::Value> = coils
+            .iter()
+            .map(|(coil_id, seq)| {
+                serde_json::json!({
+                    "sequence": seq,
+                    "coil_id": coil_id,
+                    "shift_date": "2026-03-01",
+                    "shift_no": 1,
+                    "shift_type": "day",
+                    "coil": {"steel_grade": "Q235", "thickness": 2.0, "width": 1250.0, "weight": 20.0}
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "format": format,
+            "schema_version": version,
+            "exported_at": "2026-03-01 08:00:00",
+            "plan": {
+                "plan_no": "SP-1",
+                "name": "测试方案",
+                "period_type": "daily",
+                "start_date": "2026-03-01",
+                "end_date": "2026-03-01"
+            },
+            "items": items
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_parse_plan_document_round_trip() {
+        let doc = parse_plan_document(&sample_json(PLAN_JSON_FORMAT, 1, &[("C1", 1), ("C2", 2)]))
+            .unwrap();
+        assert_eq!(doc.items.len(), 2);
+        assert!(doc.strategy.is_none());
+        assert!(doc.roll_changes.is_empty());
+
+        let text = serde_json::to_string(&doc).unwrap();
+        let again = parse_plan_document(&text).unwrap();
+        assert_eq!(again.items[1].coil_id, "C2");
+        assert_eq!(again.plan.name, "测试方案");
+    }
+
+    #[test]
+    fn test_parse_plan_document_rejects_invalid() {
+        let wrong_format = parse_plan_document(&sample_json("other", 1, &[("C1", 1)]));
+        assert!(matches!(wrong_format, Err(AppError::FileFormatError(_))));
+        let future = parse_plan_document(&sample_json(PLAN_JSON_FORMAT, 99, &[("C1", 1)]));
+        assert!(matches!(future, Err(AppError::FileFormatError(_))));
+        let dup_coil =
+            parse_plan_document(&sample_json(PLAN_JSON_FORMAT, 1, &[("C1", 1), ("C1", 2)]));
+        assert!(matches!(dup_coil, Err(AppError::FileFormatError(_))));
+        let dup_seq =
+            parse_plan_document(&sample_json(PLAN_JSON_FORMAT, 1, &[("C1", 1), ("C2", 1)]));
+        assert!(matches!(dup_seq, Err(AppError::FileFormatError(_))));
+    }
+}
@@ -0,0 +1,224 @@
+//! 方案状态流转与审批
+//!
+//! draft/saved → submitted → approved / rejected → confirmed → executing → archived。
+//! 每次流转校验守卫条件并写入审批记录；仅 draft/saved/rejected 状态允许修改排程项。
+
+use crate::models::{plan_approval, schedule_plan};
+use crate::AppError;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+pub const PLAN_DRAFT: &str = "draft";
+pub const PLAN_SAVED: &str = "saved";
+pub const PLAN_SUBMITTED: &str = "submitted";
+pub const PLAN_APPROVED: &str = "approved";
+pub const PLAN_REJECTED: &str = "rejected";
+pub const PLAN_CONFIRMED: &str = "confirmed";
+pub const PLAN_EXECUTING: &str = "executing";
+pub const PLAN_ARCHIVED: &str = "archived";
+
+/// 已生效（下发、锁定材料、可仿真）的方案状态
+pub const EFFECTIVE_STATUSES: &[&str] = &[PLAN_CONFIRMED, PLAN_EXECUTING];
+
+/// 流转守卫所需的上下文
+#[derive(Debug, Clone, Default)]
+pub struct TransitionContext<'a> {
+    pub item_count: u64,
+    /// 未忽略的高风险数量
+    pub unignored_high_risks: i32,
+    pub approver: Option<&'a str>,
+    pub comment: Option<&'a str>,
+}
+
+/// 方案状态（空视为草稿）
+pub fn status_of(plan: &schedule_plan::Model) -> &str {
+    plan.status.as_deref().unwrap_or(PLAN_DRAFT)
+}
+
+/// 是否允许修改排程项
+pub fn is_editable(status: &str) -> bool {
+    matches!(status, PLAN_DRAFT | PLAN_SAVED | PLAN_REJECTED)
+}
+
+/// 是否为已生效状态
+pub fn is_effective(status: &str) -> bool {
+    EFFECTIVE_STATUSES.contains(&status)
+}
+
+/// 当前状态允许流转到的状态
+pub fn next_statuses(from: &str) -> &'static [&'static str] {
+    match from {
+        PLAN_DRAFT | PLAN_SAVED => &[PLAN_DRAFT, PLAN_SAVED, PLAN_SUBMITTED, PLAN_ARCHIVED],
+        PLAN_REJECTED => &[PLAN_DRAFT, PLAN_SAVED, PLAN_SUBMITTED, PLAN_ARCHIVED],
+        PLAN_SUBMITTED => &[PLAN_APPROVED, PLAN_REJECTED, PLAN_DRAFT],
+        PLAN_APPROVED => &[PLAN_CONFIRMED, PLAN_DRAFT],
+        PLAN_CONFIRMED => &[PLAN_EXECUTING, PLAN_ARCHIVED],
+        PLAN_EXECUTING => &[PLAN_ARCHIVED],
+        PLAN_ARCHIVED => &[],
+        // 迁移未覆盖的遗留状态：至少允许归档，避免方案卡死
+        _ => &[PLAN_ARCHIVED],
+    }
+}
+
+fn non_blank(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// 校验状态流转及其守卫条件
+pub fn check_transition(from: &str, to: &str, ctx: &TransitionContext) -> Result<(), AppError> {
+    if !next_statuses(from).contains(&to) {
+        return Err(AppError::ConstraintViolation(format!(
+            "方案状态不能从 {} 变更为 {}，允许: {:?}",
+            from,
+            to,
+            next_statuses(from)
+        )));
+    }
+    match to {
+        PLAN_SUBMITTED if ctx.item_count == 0 => Err(AppError::ConstraintViolation(
+            "方案没有排程项，不能提交审批".to_string(),
+        )),
+        PLAN_APPROVED | PLAN_REJECTED if non_blank(ctx.approver).is_none() => Err(
+            AppError::InvalidInput("审批通过/驳回须填写审批人".to_string()),
+        ),
+        PLAN_APPROVED if ctx.unignored_high_risks > 0 => {
+            Err(AppError::ConstraintViolation(format!(
+                "方案存在 {} 个未忽略的高风险，不能审批通过",
+                ctx.unignored_high_risks
+            )))
+        }
+        PLAN_REJECTED if non_blank(ctx.comment).is_none() => {
+            Err(AppError::InvalidInput("驳回方案须填写审批意见".to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 加载方案并确认其状态允许修改排程项
+pub async fn ensure_plan_editable<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+) -> Result<schedule_plan::Model, AppError> {
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let status = status_of(&plan);
+    if !is_editable(status) {
+        return Err(AppError::ConstraintViolation(format!(
+            "方案 {} 当前状态为 {}，不允许修改排程",
+            plan.plan_no, status
+        )));
+    }
+    Ok(plan)
+}
+
+/// 写入状态流转/审批记录
+pub async fn record_transition<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    from: &str,
+    to: &str,
+    approver: Option<&str>,
+    comment: Option<&str>,
+) -> Result<plan_approval::Model, AppError> {
+    Ok(plan_approval::ActiveModel {
+        plan_id: Set(plan_id),
+        from_status: Set(Some(from.to_string())),
+        to_status: Set(to.to_string()),
+        approver: Set(non_blank(approver).map(str::to_string)),
+        comment: Set(non_blank(comment).map(str::to_string)),
+        created_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?)
+}
+
+/// 方案审批记录（按时间先后）
+pub async fn list_approvals<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+) -> Result<Vec<plan_approval::Model>, AppError> {
+    Ok(plan_approval::Entity::find()
+        .filter(plan_approval::Column::PlanId.eq(plan_id))
+        .order_by_asc(plan_approval::Column::CreatedAt)
+        .order_by_asc(plan_approval::Column::Id)
+        .all(conn)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workflow_follows_defined_path() {
+        let ok = TransitionContext {
+            item_count: 3,
+            approver: Some("张工"),
+            comment: Some("同意"),
+            ..Default::default()
+        };
+        let path = [
+            PLAN_DRAFT,
+            PLAN_SUBMITTED,
+            PLAN_APPROVED,
+            PLAN_CONFIRMED,
+            PLAN_EXECUTING,
+            PLAN_ARCHIVED,
+        ];
+        for pair in path.windows(2) {
+            assert!(
+                check_transition(pair[0], pair[1], &ok).is_ok(),
+                "{:?}",
+                pair
+            );
+        }
+        assert!(check_transition(PLAN_DRAFT, PLAN_CONFIRMED, &ok).is_err());
+        assert!(check_transition(PLAN_ARCHIVED, PLAN_DRAFT, &ok).is_err());
+        assert!(check_transition(PLAN_CONFIRMED, PLAN_DRAFT, &ok).is_err());
+        assert!(is_editable(PLAN_REJECTED));
+        assert!(!is_editable(PLAN_SUBMITTED));
+    }
+
+    #[test]
+    fn legacy_status_can_only_be_archived() {
+        let ok = TransitionContext {
+            item_count: 1,
+            ..Default::default()
+        };
+        assert!(check_transition("completed", PLAN_ARCHIVED, &ok).is_ok());
+        assert!(check_transition("completed", PLAN_DRAFT, &ok).is_err());
+        assert!(next_statuses(PLAN_ARCHIVED).is_empty());
+    }
+
+    #[test]
+    fn guards_block_unsafe_transitions() {
+        let empty = TransitionContext::default();
+        assert!(check_transition(PLAN_DRAFT, PLAN_SUBMITTED, &empty).is_err());
+
+        let risky = TransitionContext {
+            item_count: 1,
+            unignored_high_risks: 2,
+            approver: Some("张工"),
+            comment: None,
+        };
+        let err = check_transition(PLAN_SUBMITTED, PLAN_APPROVED, &risky).unwrap_err();
+        assert!(err.to_string().contains("高风险"));
+        // 驳回不受风险限制，但须填写意见
+        assert!(check_transition(PLAN_SUBMITTED, PLAN_REJECTED, &risky).is_err());
+        let reject = TransitionContext {
+            comment: Some("宽度跳跃过大"),
+            ..risky
+        };
+        assert!(check_transition(PLAN_SUBMITTED, PLAN_REJECTED, &reject).is_ok());
+
+        let anonymous = TransitionContext {
+            item_count: 1,
+            ..Default::default()
+        };
+        assert!(check_transition(PLAN_SUBMITTED, PLAN_APPROVED, &anonymous).is_err());
+    }
+}
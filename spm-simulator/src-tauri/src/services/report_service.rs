@@ -0,0 +1,695 @@
+//! 方案 HTML 报告
+//!
+//! 生成单文件 HTML（内联 CSS 与 SVG，无外部脚本/字体依赖），便于邮件分发与离线查看。
+//! 数据由调用方装配为 PlanReportData，本模块只负责渲染。
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::fmt::Write;
+
+use crate::engine::evaluator::EvalResult;
+
+/// 甘特图绘图区宽度(px)
+const GANTT_PLOT_WIDTH: f64 = 960.0;
+const GANTT_LABEL_WIDTH: f64 = 150.0;
+const GANTT_ROW_HEIGHT: f64 = 34.0;
+const PROFILE_PLOT_WIDTH: f64 = 1040.0;
+const PROFILE_PLOT_HEIGHT: f64 = 240.0;
+
+#[derive(Debug, Clone)]
+pub struct ReportPlanMeta {
+    pub plan_no: String,
+    pub name: String,
+    pub period_type: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub status: String,
+    pub version: i32,
+    pub strategy_name: String,
+    pub remarks: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportCoil {
+    pub sequence: i32,
+    pub coil_id: String,
+    pub steel_grade: String,
+    pub width: f64,
+    pub thickness: f64,
+    pub weight: f64,
+    pub shift_date: String,
+    pub shift_type: String,
+    pub planned_start: Option<String>,
+    pub planned_end: Option<String>,
+    pub is_roll_change: bool,
+}
+
+/// 班次时间配置（HH:MM，本地时间）
+#[derive(Debug, Clone)]
+pub struct ReportShiftConfig {
+    pub day_start: NaiveTime,
+    pub day_end: NaiveTime,
+    pub night_start: NaiveTime,
+    pub night_end: NaiveTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportMaintenance {
+    pub title: String,
+    pub maintenance_type: String,
+    /// 本地时间
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportRisk {
+    pub severity: String,
+    pub constraint_type: String,
+    pub sequence: i32,
+    pub coil_id: String,
+    pub message: String,
+    pub due_date: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportForecast {
+    pub ready_date: String,
+    pub count: i32,
+    pub total_weight: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlanReportData {
+    pub meta: ReportPlanMeta,
+    pub eval: EvalResult,
+    pub coils: Vec<ReportCoil>,
+    pub shift_config: ReportShiftConfig,
+    pub maintenance: Vec<ReportMaintenance>,
+    pub risks: Vec<ReportRisk>,
+    pub forecast: Vec<ReportForecast>,
+    pub generated_at: String,
+}
+
+/// HTML 转义
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn shift_type_label(shift_type: &str) -> &'static str {
+    if shift_type == "night" {
+        "夜班"
+    } else {
+        "白班"
+    }
+}
+
+fn severity_label(severity: &str) -> &'static str {
+    match severity {
+        "high" => "高",
+        "medium" => "中",
+        "low" => "低",
+        _ => "提示",
+    }
+}
+
+/// 班次起止时刻；夜班结束时刻早于开始时刻时归属次日
+fn shift_window(
+    date: NaiveDate,
+    shift_type: &str,
+    cfg: &ReportShiftConfig,
+) -> (NaiveDateTime, NaiveDateTime) {
+    let (start, end) = if shift_type == "night" {
+        (cfg.night_start, cfg.night_end)
+    } else {
+        (cfg.day_start, cfg.day_end)
+    };
+    let start_at = date.and_time(start);
+    let mut end_at = date.and_time(end);
+    if end_at <= start_at {
+        end_at += Duration::days(1);
+    }
+    (start_at, end_at)
+}
+
+/// 把 HH:MM 放到班次窗口内（跨零点的时刻归属次日）
+fn time_in_shift(time: &str, shift_start: NaiveDateTime) -> Option<NaiveDateTime> {
+    let t = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    let mut at = shift_start.date().and_time(t);
+    if at < shift_start {
+        at += Duration::days(1);
+    }
+    Some(at)
+}
+
+fn render_kpi_cards(out: &mut String, eval: &EvalResult) {
+    let m = &eval.metrics;
+    let cards: [(&str, String, &str); 14] = [
+        ("综合评分", eval.score_overall.to_string(), "score"),
+        ("序列合理性", eval.score_sequence.to_string(), "score"),
+        ("交期满足度", eval.score_delivery.to_string(), "score"),
+        ("效率", eval.score_efficiency.to_string(), "score"),
+        ("排程卷数", m.total_count.to_string(), ""),
+        ("总吨位", format!("{:.1} t", m.total_weight), ""),
+        ("换辊次数", m.roll_change_count.to_string(), ""),
+        ("宽度跳跃", m.width_jump_count.to_string(), ""),
+        ("钢种切换", m.steel_grade_switch_count.to_string(), ""),
+        ("产能利用率", format!("{:.1}%", m.capacity_utilization), ""),
+        ("适温比例", format!("{:.1}%", m.tempered_ratio), ""),
+        (
+            "紧急完成率",
+            format!("{:.1}%", m.urgent_completion_rate),
+            "",
+        ),
+        ("逾期卷数", m.overdue_count.to_string(), ""),
+        (
+            "风险 高/中/低",
+            format!(
+                "{} / {} / {}",
+                eval.risk_high, eval.risk_medium, eval.risk_low
+            ),
+            if eval.risk_high > 0 { "danger" } else { "" },
+        ),
+    ];
+    out.push_str("<div class=\"kpis\">");
+    for (label, value, class) in cards {
+        let _ = write!(
+            out,
+            "<div class=\"kpi {}\"><div class=\"kpi-label\">{}</div><div class=\"kpi-value\">{}</div></div>",
+            class,
+            escape_html(label),
+            escape_html(&value)
+        );
+    }
+    out.push_str("</div>");
+}
+
+/// 按班次分行的甘特图：钢卷为矩形，换辊为红色竖线，检修为灰色斜纹块
+fn render_gantt(out: &mut String, data: &PlanReportData) {
+    // 按上线顺序收集班次行
+    let mut rows: Vec<(String, String)> = Vec::new();
+    for c in &data.coils {
+        let key = (c.shift_date.clone(), c.shift_type.clone());
+        if !rows.contains(&key) {
+            rows.push(key);
+        }
+    }
+    if rows.is_empty() {
+        out.push_str("<p class=\"empty\">方案暂无排程项</p>");
+        return;
+    }
+
+    let windows: Vec<Option<(NaiveDateTime, NaiveDateTime)>> = rows
+        .iter()
+        .map(|(date, shift_type)| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .map(|d| shift_window(d, shift_type, &data.shift_config))
+        })
+        .collect();
+    let max_minutes = windows
+        .iter()
+        .flatten()
+        .map(|(s, e)| (*e - *s).num_minutes())
+        .max()
+        .unwrap_or(720)
+        .max(60) as f64;
+    let scale = GANTT_PLOT_WIDTH / max_minutes;
+    let top = 24.0;
+    let width = GANTT_LABEL_WIDTH + GANTT_PLOT_WIDTH + 10.0;
+    let height = top + rows.len() as f64 * GANTT_ROW_HEIGHT + 10.0;
+
+    let _ = write!(
+        out,
+        "<svg class=\"chart\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" xmlns=\"http://www.w3.org/2000/svg\">\
+         <defs><pattern id=\"hatch\" width=\"6\" height=\"6\" patternUnits=\"userSpaceOnUse\" patternTransform=\"rotate(45)\">\
+         <rect width=\"6\" height=\"6\" fill=\"#d9d9d9\"/><line x1=\"0\" y1=\"0\" x2=\"0\" y2=\"6\" stroke=\"#8c8c8c\" stroke-width=\"2\"/></pattern></defs>",
+        w = width,
+        h = height
+    );
+
+    // 时间刻度（相对班次开始的小时数）
+    let hours = (max_minutes / 60.0).ceil() as i64;
+    for hour in 0..=hours {
+        let x = GANTT_LABEL_WIDTH + hour as f64 * 60.0 * scale;
+        if x > GANTT_LABEL_WIDTH + GANTT_PLOT_WIDTH + 0.5 {
+            break;
+        }
+        let _ = write!(
+            out,
+            "<line x1=\"{x:.1}\" y1=\"{y1}\" x2=\"{x:.1}\" y2=\"{y2}\" class=\"grid\"/>\
+             <text x=\"{x:.1}\" y=\"14\" class=\"axis\" text-anchor=\"middle\">+{hour}h</text>",
+            x = x,
+            y1 = top,
+            y2 = height - 10.0,
+            hour = hour
+        );
+    }
+
+    for (row_idx, ((date, shift_type), window)) in rows.iter().zip(&windows).enumerate() {
+        let y = top + row_idx as f64 * GANTT_ROW_HEIGHT;
+        let _ = write!(
+            out,
+            "<text x=\"4\" y=\"{:.1}\" class=\"label\">{} {}</text>",
+            y + GANTT_ROW_HEIGHT / 2.0 + 4.0,
+            escape_html(date),
+            shift_type_label(shift_type)
+        );
+        let Some((shift_start, shift_end)) = window else {
+            continue;
+        };
+
+        for m in &data.maintenance {
+            let start = m.start.max(*shift_start);
+            let end = m.end.min(*shift_end);
+            if start >= end {
+                continue;
+            }
+            let x = GANTT_LABEL_WIDTH + (start - *shift_start).num_minutes() as f64 * scale;
+            let w = (end - start).num_minutes() as f64 * scale;
+            let _ = write!(
+                out,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"url(#hatch)\" stroke=\"#8c8c8c\">\
+                 <title>检修: {} ({}) {} ~ {}</title></rect>",
+                x,
+                y + 2.0,
+                w.max(1.0),
+                GANTT_ROW_HEIGHT - 4.0,
+                escape_html(&m.title),
+                escape_html(&m.maintenance_type),
+                m.start.format("%Y-%m-%d %H:%M"),
+                m.end.format("%Y-%m-%d %H:%M")
+            );
+        }
+
+        let row_coils = data
+            .coils
+            .iter()
+            .filter(|c| &c.shift_date == date && &c.shift_type == shift_type);
+        for (idx, c) in row_coils.enumerate() {
+            let (Some(start), Some(end)) = (
+                c.planned_start
+                    .as_deref()
+                    .and_then(|t| time_in_shift(t, *shift_start)),
+                c.planned_end
+                    .as_deref()
+                    .and_then(|t| time_in_shift(t, *shift_start)),
+            ) else {
+                continue;
+            };
+            let end = if end < start {
+                end + Duration::days(1)
+            } else {
+                end
+            };
+            let x = GANTT_LABEL_WIDTH + (start - *shift_start).num_minutes() as f64 * scale;
+            let w = ((end - start).num_minutes() as f64 * scale).max(1.0);
+            let fill = if idx % 2 == 0 { "#4096ff" } else { "#91caff" };
+            let _ = write!(
+                out,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+                 <title>#{} {} {} {}×{} {:.1}t {}~{}</title></rect>",
+                x,
+                y + 6.0,
+                w,
+                GANTT_ROW_HEIGHT - 12.0,
+                fill,
+                c.sequence,
+                escape_html(&c.coil_id),
+                escape_html(&c.steel_grade),
+                c.thickness,
+                c.width,
+                c.weight,
+                start.format("%H:%M"),
+                end.format("%H:%M")
+            );
+            if c.is_roll_change {
+                let _ = write!(
+                    out,
+                    "<line x1=\"{x:.1}\" y1=\"{y1:.1}\" x2=\"{x:.1}\" y2=\"{y2:.1}\" class=\"roll\">\
+                     <title>换辊: #{seq} {coil}</title></line>",
+                    x = x,
+                    y1 = y + 1.0,
+                    y2 = y + GANTT_ROW_HEIGHT - 1.0,
+                    seq = c.sequence,
+                    coil = escape_html(&c.coil_id)
+                );
+            }
+        }
+    }
+    out.push_str("</svg>");
+    out.push_str(
+        "<div class=\"legend\"><span class=\"sw coil\"></span>钢卷 \
+         <span class=\"sw roll\"></span>换辊 <span class=\"sw maint\"></span>检修</div>",
+    );
+}
+
+fn polyline(values: &[f64], min: f64, max: f64, left: f64, top: f64) -> String {
+    let n = values.len().max(2) - 1;
+    let span = if max - min > f64::EPSILON {
+        max - min
+    } else {
+        1.0
+    };
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let x = left + i as f64 / n as f64 * PROFILE_PLOT_WIDTH;
+            let y = top + (1.0 - (v - min) / span) * PROFILE_PLOT_HEIGHT;
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 宽度（左轴）/厚度（右轴）轮廓折线，换辊位置为虚线
+fn render_profile(out: &mut String, coils: &[ReportCoil]) {
+    if coils.is_empty() {
+        out.push_str("<p class=\"empty\">方案暂无排程项</p>");
+        return;
+    }
+    let widths: Vec<f64> = coils.iter().map(|c| c.width).collect();
+    let thicknesses: Vec<f64> = coils.iter().map(|c| c.thickness).collect();
+    let bounds = |vals: &[f64]| {
+        let min = vals.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = vals.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        (min, max)
+    };
+    let (w_min, w_max) = bounds(&widths);
+    let (t_min, t_max) = bounds(&thicknesses);
+    let left = 60.0;
+    let top = 16.0;
+    let width = left + PROFILE_PLOT_WIDTH + 60.0;
+    let height = top + PROFILE_PLOT_HEIGHT + 30.0;
+    let n = coils.len().max(2) - 1;
+
+    let _ = write!(
+        out,
+        "<svg class=\"chart\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" xmlns=\"http://www.w3.org/2000/svg\">\
+         <rect x=\"{l}\" y=\"{t}\" width=\"{pw}\" height=\"{ph}\" class=\"frame\"/>",
+        w = width,
+        h = height,
+        l = left,
+        t = top,
+        pw = PROFILE_PLOT_WIDTH,
+        ph = PROFILE_PLOT_HEIGHT
+    );
+    for (i, c) in coils.iter().enumerate() {
+        if !c.is_roll_change {
+            continue;
+        }
+        let x = left + i as f64 / n as f64 * PROFILE_PLOT_WIDTH;
+        let _ = write!(
+            out,
+            "<line x1=\"{x:.1}\" y1=\"{t}\" x2=\"{x:.1}\" y2=\"{b}\" class=\"roll dashed\"><title>换辊: #{s}</title></line>",
+            x = x,
+            t = top,
+            b = top + PROFILE_PLOT_HEIGHT,
+            s = c.sequence
+        );
+    }
+    let _ = write!(
+        out,
+        "<polyline points=\"{}\" class=\"series width\"/><polyline points=\"{}\" class=\"series thickness\"/>",
+        polyline(&widths, w_min, w_max, left, top),
+        polyline(&thicknesses, t_min, t_max, left, top)
+    );
+    let _ = write!(
+        out,
+        "<text x=\"{l0}\" y=\"{t0}\" class=\"axis width\" text-anchor=\"end\">{w_max:.0}</text>\
+         <text x=\"{l0}\" y=\"{b0}\" class=\"axis width\" text-anchor=\"end\">{w_min:.0}</text>\
+         <text x=\"{r0}\" y=\"{t0}\" class=\"axis thickness\">{t_max:.2}</text>\
+         <text x=\"{r0}\" y=\"{b0}\" class=\"axis thickness\">{t_min:.2}</text>\
+         <text x=\"{l}\" y=\"{bottom}\" class=\"axis\">#1</text>\
+         <text x=\"{r}\" y=\"{bottom}\" class=\"axis\" text-anchor=\"end\">#{last}</text></svg>",
+        l0 = left - 6.0,
+        r0 = left + PROFILE_PLOT_WIDTH + 6.0,
+        t0 = top + 10.0,
+        b0 = top + PROFILE_PLOT_HEIGHT,
+        l = left,
+        r = left + PROFILE_PLOT_WIDTH,
+        bottom = top + PROFILE_PLOT_HEIGHT + 18.0,
+        last = coils.len(),
+        w_max = w_max,
+        w_min = w_min,
+        t_max = t_max,
+        t_min = t_min
+    );
+    out.push_str(
+        "<div class=\"legend\"><span class=\"sw width\"></span>宽度(mm) \
+         <span class=\"sw thickness\"></span>厚度(mm) <span class=\"sw roll\"></span>换辊</div>",
+    );
+}
+
+fn render_risk_table(out: &mut String, risks: &[ReportRisk]) {
+    if risks.is_empty() {
+        out.push_str("<p class=\"empty\">无风险项</p>");
+        return;
+    }
+    out.push_str(
+        "<table><thead><tr><th>级别</th><th>类型</th><th>序号</th><th>卷号</th><th>交期</th><th>说明</th></tr></thead><tbody>",
+    );
+    for r in risks {
+        let _ = write!(
+            out,
+            "<tr class=\"sev-{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&r.severity),
+            severity_label(&r.severity),
+            escape_html(&r.constraint_type),
+            r.sequence,
+            escape_html(&r.coil_id),
+            escape_html(r.due_date.as_deref().unwrap_or("-")),
+            escape_html(&r.message)
+        );
+    }
+    out.push_str("</tbody></table>");
+}
+
+fn render_forecast(out: &mut String, forecast: &[ReportForecast]) {
+    if forecast.is_empty() {
+        out.push_str("<p class=\"empty\">预测期内无待温材料转为适温</p>");
+        return;
+    }
+    let max_weight = forecast
+        .iter()
+        .map(|f| f.total_weight)
+        .fold(0.0f64, f64::max)
+        .max(1.0);
+    out.push_str(
+        "<table><thead><tr><th>适温日期</th><th>卷数</th><th>吨位</th><th></th></tr></thead><tbody>",
+    );
+    for f in forecast {
+        let _ = write!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td class=\"bar-cell\"><div class=\"bar\" style=\"width:{:.1}%\"></div></td></tr>",
+            escape_html(&f.ready_date),
+            f.count,
+            f.total_weight,
+            f.total_weight / max_weight * 100.0
+        );
+    }
+    out.push_str("</tbody></table>");
+}
+
+const REPORT_CSS: &str = "body{font-family:-apple-system,'PingFang SC','Microsoft YaHei',sans-serif;margin:24px;color:#1f1f1f;background:#fafafa}\
+h1{font-size:22px;margin:0 0 4px}h2{font-size:17px;margin:28px 0 10px;border-left:4px solid #1677ff;padding-left:8px}\
+.meta{color:#595959;font-size:13px}.meta span{margin-right:18px}\
+.kpis{display:flex;flex-wrap:wrap;gap:10px}.kpi{background:#fff;border:1px solid #f0f0f0;border-radius:6px;padding:10px 14px;min-width:120px}\
+.kpi-label{font-size:12px;color:#8c8c8c}.kpi-value{font-size:20px;font-weight:600}.kpi.score .kpi-value{color:#1677ff}.kpi.danger .kpi-value{color:#cf1322}\
+.chart{background:#fff;border:1px solid #f0f0f0;max-width:100%;height:auto}\
+.grid{stroke:#f0f0f0}.frame{fill:none;stroke:#d9d9d9}.axis{font-size:11px;fill:#8c8c8c}.label{font-size:12px;fill:#262626}\
+.roll{stroke:#cf1322;stroke-width:2}.dashed{stroke-dasharray:4 3;stroke-width:1}\
+.series{fill:none;stroke-width:1.5}.series.width,.axis.width{stroke:#1677ff}.series.thickness{stroke:#fa8c16}.axis.width{fill:#1677ff;stroke:none}.axis.thickness{fill:#fa8c16}\
+.legend{font-size:12px;color:#595959;margin:6px 0}.sw{display:inline-block;width:12px;height:12px;margin:0 4px 0 12px;vertical-align:middle}\
+.sw.coil{background:#4096ff}.sw.roll{background:#cf1322}.sw.maint{background:#bfbfbf}.sw.width{background:#1677ff}.sw.thickness{background:#fa8c16}\
+table{border-collapse:collapse;background:#fff;font-size:12px;width:100%}th,td{border:1px solid #f0f0f0;padding:5px 8px;text-align:left}\
+th{background:#fafafa}.sev-high td:first-child{color:#cf1322;font-weight:600}.sev-medium td:first-child{color:#d48806}\
+.bar-cell{width:40%}.bar{height:10px;background:#52c41a;border-radius:2px}.empty{color:#8c8c8c;font-size:13px}\
+footer{margin-top:32px;font-size:12px;color:#bfbfbf}";
+
+/// 渲染方案 HTML 报告
+pub fn render_plan_report(data: &PlanReportData) -> String {
+    let meta = &data.meta;
+    let mut out = String::with_capacity(64 * 1024 + data.coils.len() * 400);
+    let _ = write!(
+        out,
+        "<!DOCTYPE html><html lang=\"zh-CN\"><head><meta charset=\"utf-8\">\
+         <title>排程方案报告 - {}</title><style>{}</style></head><body>",
+        escape_html(&meta.name),
+        REPORT_CSS
+    );
+    let _ = write!(
+        out,
+        "<h1>{}</h1><div class=\"meta\"><span>方案编号: {}</span><span>周期: {} ~ {} ({})</span>\
+         <span>状态: {}</span><span>版本: v{}</span><span>策略: {}</span></div>",
+        escape_html(&meta.name),
+        escape_html(&meta.plan_no),
+        escape_html(&meta.start_date),
+        escape_html(&meta.end_date),
+        escape_html(&meta.period_type),
+        escape_html(&meta.status),
+        meta.version,
+        escape_html(&meta.strategy_name)
+    );
+    if !meta.remarks.is_empty() {
+        let _ = write!(
+            out,
+            "<div class=\"meta\">备注: {}</div>",
+            escape_html(&meta.remarks)
+        );
+    }
+
+    out.push_str("<h2>关键指标</h2>");
+    render_kpi_cards(&mut out, &data.eval);
+    out.push_str("<h2>班次甘特图</h2>");
+    render_gantt(&mut out, data);
+    out.push_str("<h2>宽度/厚度轮廓</h2>");
+    render_profile(&mut out, &data.coils);
+    let _ = write!(out, "<h2>风险清单 ({})</h2>", data.risks.len());
+    render_risk_table(&mut out, &data.risks);
+    out.push_str("<h2>待温材料适温预测</h2>");
+    render_forecast(&mut out, &data.forecast);
+
+    let _ = write!(
+        out,
+        "<footer>生成时间: {}</footer></body></html>",
+        escape_html(&data.generated_at)
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::evaluator::EvalMetrics;
+
+    fn sample_data() -> PlanReportData {
+        let t = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").unwrap();
+        let coil = |seq: i32, shift_type: &str, start: &str, end: &str, roll: bool| ReportCoil {
+            sequence: seq,
+            coil_id: format!("C<{}>", seq),
+            steel_grade: "Q235".into(),
+            width: 1000.0 + seq as f64 * 10.0,
+            thickness: 2.0,
+            weight: 20.0,
+            shift_date: "2026-03-01".into(),
+            shift_type: shift_type.into(),
+            planned_start: Some(start.into()),
+            planned_end: Some(end.into()),
+            is_roll_change: roll,
+        };
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        PlanReportData {
+            meta: ReportPlanMeta {
+                plan_no: "P-001".into(),
+                name: "三月 & 计划".into(),
+                period_type: "daily".into(),
+                start_date: "2026-03-01".into(),
+                end_date: "2026-03-01".into(),
+                status: "draft".into(),
+                version: 1,
+                strategy_name: "标准".into(),
+                remarks: String::new(),
+            },
+            eval: EvalResult {
+                score_overall: 90,
+                score_sequence: 88,
+                score_delivery: 92,
+                score_efficiency: 85,
+                metrics: EvalMetrics {
+                    total_count: 3,
+                    total_weight: 60.0,
+                    roll_change_count: 1,
+                    width_jump_count: 0,
+                    steel_grade_switch_count: 0,
+                    capacity_utilization: 50.0,
+                    tempered_ratio: 100.0,
+                    urgent_completion_rate: 100.0,
+                    overdue_count: 0,
+                    soft_score_adjust: 0,
+                },
+                risk_high: 1,
+                risk_medium: 0,
+                risk_low: 0,
+                risk_summary: String::new(),
+            },
+            coils: vec![
+                coil(1, "day", "08:00", "08:30", false),
+                coil(2, "day", "09:00", "09:30", true),
+                coil(3, "night", "23:50", "00:20", false),
+            ],
+            shift_config: ReportShiftConfig {
+                day_start: t("08:00"),
+                day_end: t("20:00"),
+                night_start: t("20:00"),
+                night_end: t("08:00"),
+            },
+            maintenance: vec![ReportMaintenance {
+                title: "定修".into(),
+                maintenance_type: "planned".into(),
+                start: at("2026-03-01 12:00"),
+                end: at("2026-03-01 14:00"),
+            }],
+            risks: vec![ReportRisk {
+                severity: "high".into(),
+                constraint_type: "width_jump".into(),
+                sequence: 2,
+                coil_id: "C<2>".into(),
+                message: "宽度跳跃 <150mm>".into(),
+                due_date: None,
+            }],
+            forecast: vec![ReportForecast {
+                ready_date: "2026-03-02".into(),
+                count: 2,
+                total_weight: 40.0,
+            }],
+            generated_at: "2026-03-01 07:00:00".into(),
+        }
+    }
+
+    #[test]
+    fn test_render_plan_report_sections() {
+        let html = render_plan_report(&sample_data());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("三月 &amp; 计划"));
+        assert!(html.contains("C&lt;2&gt;"));
+        assert!(!html.contains("C<2>"));
+        // 班次两行 + 轮廓图
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("2026-03-01 夜班"));
+        assert!(html.contains("换辊: #2 C&lt;2&gt;"));
+        assert!(html.contains("检修: 定修"));
+        assert!(html.contains("风险清单 (1)"));
+        assert!(html.contains("2026-03-02"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_gantt_places_cross_midnight_coil_in_night_row() {
+        let data = sample_data();
+        let shift_start = NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap();
+        let end = time_in_shift("00:20", shift_start).unwrap();
+        assert_eq!((end - shift_start).num_minutes(), 260);
+
+        let (start, stop) = shift_window(
+            NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            "night",
+            &data.shift_config,
+        );
+        assert_eq!((stop - start).num_minutes(), 720);
+    }
+}
@@ -0,0 +1,580 @@
+//! 导入源文件读取
+//!
+//! 表格文件（.xlsx/.xlsm/.xlsb/.xls/.ods）由 calamine 自动识别格式，可按名称或序号选择工作表；
+//! 文本文件（.csv/.txt）自动识别编码（UTF-8/GBK）与分隔符（, ; Tab |）。
+//! 表头行与数据起始行均为 1 起始的物理行号（空行同样计数），用于跳过标题横幅等非数据行。
+
+use crate::models::field_mapping;
+use crate::services::import_service::excel_serial_to_datetime;
+use crate::AppError;
+use calamine::{open_workbook_auto, Data, Reader};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 表格文件扩展名
+pub const SPREADSHEET_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+/// 文本文件扩展名
+pub const TEXT_EXTENSIONS: [&str; 2] = ["csv", "txt"];
+
+/// 自动识别时的候选分隔符
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+/// 分隔符识别采样行数（自表头行起）
+const DELIMITER_SAMPLE_LINES: usize = 20;
+
+/// 读取选项（均可选，缺省为首个工作表、第1行表头、表头下一行开始数据、自动识别编码与分隔符）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceReadOptions {
+    /// 工作表名称或序号（1 起始）；名称优先匹配
+    #[serde(default)]
+    pub sheet: Option<String>,
+    /// 表头所在行（1 起始）
+    #[serde(default)]
+    pub header_row: Option<u32>,
+    /// 数据起始行（1 起始，须大于表头行）
+    #[serde(default)]
+    pub data_start_row: Option<u32>,
+    /// CSV 分隔符：auto / , / ; / \t / |
+    #[serde(default)]
+    pub csv_delimiter: Option<String>,
+    /// CSV 编码：auto / utf-8 / gbk
+    #[serde(default)]
+    pub csv_encoding: Option<String>,
+}
+
+impl SourceReadOptions {
+    /// 逐项合并：self 中已设置的项优先，其余取 fallback
+    pub fn or(self, fallback: SourceReadOptions) -> SourceReadOptions {
+        SourceReadOptions {
+            sheet: self.sheet.or(fallback.sheet),
+            header_row: self.header_row.or(fallback.header_row),
+            data_start_row: self.data_start_row.or(fallback.data_start_row),
+            csv_delimiter: self.csv_delimiter.or(fallback.csv_delimiter),
+            csv_encoding: self.csv_encoding.or(fallback.csv_encoding),
+        }
+    }
+
+    fn header_row(&self) -> usize {
+        self.header_row.unwrap_or(1).max(1) as usize
+    }
+
+    fn data_start_row(&self) -> usize {
+        self.data_start_row
+            .map(|r| r as usize)
+            .unwrap_or(self.header_row() + 1)
+    }
+}
+
+impl From<&field_mapping::Model> for SourceReadOptions {
+    fn from(m: &field_mapping::Model) -> Self {
+        let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.trim().is_empty());
+        SourceReadOptions {
+            sheet: non_empty(&m.sheet),
+            header_row: m.header_row.and_then(|r| u32::try_from(r).ok()),
+            data_start_row: m.data_start_row.and_then(|r| u32::try_from(r).ok()),
+            csv_delimiter: non_empty(&m.csv_delimiter),
+            csv_encoding: non_empty(&m.csv_encoding),
+        }
+    }
+}
+
+/// 读取结果
+#[derive(Debug, Clone)]
+pub struct SourceRows {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// rows[0] 在源文件中的行号（1 起始）
+    pub first_line_no: usize,
+    /// 每个数据行在源文件中的物理行号（与 rows 一一对应）
+    pub line_numbers: Vec<usize>,
+    /// 实际读取的工作表（文本文件为 None）
+    pub sheet_name: Option<String>,
+    /// 文件内全部工作表
+    pub sheet_names: Vec<String>,
+    /// 实际使用的编码（仅文本文件）
+    pub encoding: Option<String>,
+    /// 实际使用的分隔符（仅文本文件）
+    pub delimiter: Option<char>,
+}
+
+fn file_extension(file_path: &str) -> String {
+    Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// 是否为支持导入的文件扩展名
+pub fn is_supported_file(file_path: &str) -> bool {
+    let ext = file_extension(file_path);
+    SPREADSHEET_EXTENSIONS.contains(&ext.as_str()) || TEXT_EXTENSIONS.contains(&ext.as_str())
+}
+
+fn unsupported_format(ext: &str) -> AppError {
+    AppError::FileFormatError(format!(
+        "不支持的文件格式: .{}，请使用 .xlsx、.xls、.ods 或 .csv 文件",
+        ext
+    ))
+}
+
+/// 校验读取配置：行号须为正数，数据起始行须在表头行之后
+pub fn validate_read_rows(
+    header_row: Option<i32>,
+    data_start_row: Option<i32>,
+) -> Result<(), AppError> {
+    if header_row.is_some_and(|r| r < 1) || data_start_row.is_some_and(|r| r < 1) {
+        return Err(AppError::InvalidInput(
+            "表头行与数据起始行须为正整数".to_string(),
+        ));
+    }
+    if let Some(data) = data_start_row {
+        let header = header_row.unwrap_or(1);
+        if data <= header {
+            return Err(AppError::InvalidInput(format!(
+                "数据起始行({})必须大于表头行({})",
+                data, header
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 按选项读取源文件，返回表头与数据行
+pub fn read_source(file_path: &str, options: &SourceReadOptions) -> Result<SourceRows, AppError> {
+    let header_row = options.header_row();
+    let data_start_row = options.data_start_row();
+    if data_start_row <= header_row {
+        return Err(AppError::InvalidInput(format!(
+            "数据起始行({})必须大于表头行({})",
+            data_start_row, header_row
+        )));
+    }
+
+    let ext = file_extension(file_path);
+    let (raw_rows, mut result) = if SPREADSHEET_EXTENSIONS.contains(&ext.as_str()) {
+        read_spreadsheet(file_path, options.sheet.as_deref())?
+    } else if TEXT_EXTENSIONS.contains(&ext.as_str()) {
+        read_text(file_path, options, header_row)?
+    } else {
+        return Err(unsupported_format(&ext));
+    };
+
+    // raw_rows 中每行附带其物理行号
+    let header = raw_rows
+        .iter()
+        .find(|(line, _)| *line == header_row)
+        .ok_or_else(|| {
+            AppError::FileFormatError(if raw_rows.is_empty() {
+                "文件为空".to_string()
+            } else {
+                format!("表头行(第{}行)为空或超出文件范围", header_row)
+            })
+        })?;
+    result.headers = header.1.iter().map(|h| h.trim().to_string()).collect();
+    if result.headers.iter().all(|h| h.is_empty()) {
+        return Err(AppError::FileFormatError(format!(
+            "表头行(第{}行)为空",
+            header_row
+        )));
+    }
+    let (line_numbers, rows): (Vec<usize>, Vec<Vec<String>>) = raw_rows
+        .into_iter()
+        .filter(|(line, _)| *line >= data_start_row)
+        .unzip();
+    result.first_line_no = line_numbers.first().copied().unwrap_or(data_start_row);
+    result.line_numbers = line_numbers;
+    result.rows = rows;
+    Ok(result)
+}
+
+/// 列出表格文件的工作表（文本文件返回空列表）
+pub fn list_sheets(file_path: &str) -> Result<Vec<String>, AppError> {
+    let ext = file_extension(file_path);
+    if TEXT_EXTENSIONS.contains(&ext.as_str()) {
+        return Ok(Vec::new());
+    }
+    if !SPREADSHEET_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(unsupported_format(&ext));
+    }
+    let workbook = open_workbook_auto(file_path)
+        .map_err(|e| AppError::FileFormatError(format!("无法打开表格文件: {}", e)))?;
+    Ok(workbook.sheet_names().to_vec())
+}
+
+/// 按名称或序号（1 起始）解析工作表，缺省为首个
+fn resolve_sheet(sheet_names: &[String], sheet: Option<&str>) -> Result<String, AppError> {
+    let first = || {
+        sheet_names
+            .first()
+            .cloned()
+            .ok_or_else(|| AppError::FileFormatError("表格文件中没有工作表".to_string()))
+    };
+    let Some(wanted) = sheet.map(str::trim).filter(|s| !s.is_empty()) else {
+        return first();
+    };
+    if let Some(name) = sheet_names.iter().find(|n| n.as_str() == wanted) {
+        return Ok(name.clone());
+    }
+    if let Some(name) = wanted
+        .parse::<usize>()
+        .ok()
+        .and_then(|idx| idx.checked_sub(1))
+        .and_then(|idx| sheet_names.get(idx))
+    {
+        return Ok(name.clone());
+    }
+    Err(AppError::FileFormatError(format!(
+        "工作表不存在: {}（可选: {}）",
+        wanted,
+        sheet_names.join(", ")
+    )))
+}
+
+/// 带物理行号（1 起始）的原始行
+type NumberedRows = Vec<(usize, Vec<String>)>;
+
+/// 读取表格文件，返回 (带行号的行, 结果骨架)
+fn read_spreadsheet(
+    file_path: &str,
+    sheet: Option<&str>,
+) -> Result<(NumberedRows, SourceRows), AppError> {
+    let mut workbook = open_workbook_auto(file_path)
+        .map_err(|e| AppError::FileFormatError(format!("无法打开表格文件: {}", e)))?;
+    let sheet_names = workbook.sheet_names().to_vec();
+    let sheet_name = resolve_sheet(&sheet_names, sheet)?;
+
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|e| AppError::FileFormatError(format!("无法读取工作表: {}", e)))?;
+    // calamine 的 range 从首个非空单元格开始，需补回前导空行/空列以保持行号与列位置
+    let (row_offset, col_offset) = range
+        .start()
+        .map(|(r, c)| (r as usize, c as usize))
+        .unwrap_or((0, 0));
+    let rows: NumberedRows = range
+        .rows()
+        .enumerate()
+        .map(|(idx, row)| {
+            let cells = std::iter::repeat(String::new())
+                .take(col_offset)
+                .chain(row.iter().map(cell_to_string))
+                .collect();
+            (row_offset + idx + 1, cells)
+        })
+        .collect();
+
+    Ok((
+        rows,
+        SourceRows {
+            headers: Vec::new(),
+            rows: Vec::new(),
+            first_line_no: 0,
+            line_numbers: Vec::new(),
+            sheet_name: Some(sheet_name),
+            sheet_names,
+            encoding: None,
+            delimiter: None,
+        },
+    ))
+}
+
+/// 读取文本文件（编码与分隔符可自动识别）；空行不产生记录但计入行号
+fn read_text(
+    file_path: &str,
+    options: &SourceReadOptions,
+    header_row: usize,
+) -> Result<(NumberedRows, SourceRows), AppError> {
+    let bytes = std::fs::read(file_path)
+        .map_err(|e| AppError::FileFormatError(format!("无法打开CSV文件: {}", e)))?;
+    let (text, encoding) = decode_text(&bytes, options.csv_encoding.as_deref())?;
+    let delimiter = match options
+        .csv_delimiter
+        .as_deref()
+        .filter(|d| !d.is_empty() && !d.eq_ignore_ascii_case("auto"))
+    {
+        Some("\\t") | Some("tab") => b'\t',
+        Some(d) if d.len() == 1 => d.as_bytes()[0],
+        Some(d) => {
+            return Err(AppError::InvalidInput(format!(
+                "CSV分隔符无效: {}（须为单个字符或 auto）",
+                d
+            )))
+        }
+        None => detect_delimiter(&text, header_row),
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+    // csv 的记录位置指向跳过前导空行之前，需按字节偏移自行换算物理行号
+    let bytes = text.as_bytes();
+    let (mut scanned, mut line) = (0usize, 1usize);
+    let mut rows: NumberedRows = Vec::new();
+    for r in reader.records() {
+        let record = r.map_err(|e| {
+            AppError::FileFormatError(match e.position() {
+                Some(pos) => format!("CSV第{}行附近解析失败: {}", pos.line(), e),
+                None => format!("CSV解析失败: {}", e),
+            })
+        })?;
+        let mut start = record
+            .position()
+            .map(|p| p.byte() as usize)
+            .unwrap_or(scanned);
+        while matches!(bytes.get(start), Some(b'\r') | Some(b'\n')) {
+            start += 1;
+        }
+        line += bytes[scanned..start]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count();
+        scanned = start;
+        rows.push((line, record.iter().map(|f| f.to_string()).collect()));
+    }
+
+    Ok((
+        rows,
+        SourceRows {
+            headers: Vec::new(),
+            rows: Vec::new(),
+            first_line_no: 0,
+            line_numbers: Vec::new(),
+            sheet_name: None,
+            sheet_names: Vec::new(),
+            encoding: Some(encoding.to_string()),
+            delimiter: Some(delimiter as char),
+        },
+    ))
+}
+
+/// 解码文本：auto 时合法 UTF-8（含 BOM）按 UTF-8，否则按 GBK（GB18030 超集）
+fn decode_text(bytes: &[u8], encoding: Option<&str>) -> Result<(String, &'static str), AppError> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let wanted = encoding
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| !e.is_empty() && e != "auto");
+    let use_gbk = match wanted.as_deref() {
+        None => std::str::from_utf8(bytes).is_err(),
+        Some("utf-8") | Some("utf8") => false,
+        Some("gbk") | Some("gb2312") | Some("gb18030") => true,
+        Some(other) => {
+            return Err(AppError::InvalidInput(format!(
+                "不支持的CSV编码: {}（可选 auto、utf-8、gbk）",
+                other
+            )))
+        }
+    };
+    if use_gbk {
+        let (text, _, had_errors) = encoding_rs::GB18030.decode(bytes);
+        if had_errors {
+            log::warn!("CSV按GBK解码存在无法识别的字符");
+        }
+        Ok((text.into_owned(), "gbk"))
+    } else {
+        Ok((String::from_utf8_lossy(bytes).into_owned(), "utf-8"))
+    }
+}
+
+/// 统计一行中引号外的分隔符数量
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    line.bytes()
+        .filter(|b| {
+            if *b == b'"' {
+                in_quotes = !in_quotes;
+            }
+            !in_quotes && *b == delimiter
+        })
+        .count()
+}
+
+/// 识别分隔符：自表头行起采样，取每行数量一致（且非零）的行数最多者，
+/// 平局时取表头数量较多者，默认逗号
+fn detect_delimiter(text: &str, header_row: usize) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .skip(header_row.saturating_sub(1))
+        .filter(|l| !l.trim().is_empty())
+        .take(DELIMITER_SAMPLE_LINES)
+        .collect();
+    let Some(first) = lines.first() else {
+        return b',';
+    };
+    DELIMITER_CANDIDATES
+        .iter()
+        .filter_map(|&d| {
+            let header_count = count_unquoted(first, d);
+            if header_count == 0 {
+                return None;
+            }
+            let consistent = lines
+                .iter()
+                .filter(|l| count_unquoted(l, d) == header_count)
+                .count();
+            Some((consistent, header_count, d))
+        })
+        .max_by_key(|(consistent, header_count, _)| (*consistent, *header_count))
+        .map(|(_, _, d)| d)
+        .unwrap_or(b',')
+}
+
+/// 单元格转字符串（整数值浮点去除小数，日期转为 YYYY-MM-DD HH:MM:SS）
+pub fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Int(v) => v.to_string(),
+        Data::Float(v) => {
+            // 避免浮点数无意义小数
+            if *v == v.trunc() && v.abs() < 1e15 {
+                format!("{:.0}", v)
+            } else {
+                v.to_string()
+            }
+        }
+        Data::String(v) => v.clone(),
+        Data::Bool(v) => v.to_string(),
+        Data::DateTime(v) => {
+            // calamine 0.26: ExcelDateTime → 转为 f64 序列号再转日期
+            let serial = v.as_f64();
+            if let Some(dt) = excel_serial_to_datetime(serial) {
+                dt.format("%Y-%m-%d %H:%M:%S").to_string()
+            } else {
+                format!("{:?}", v)
+            }
+        }
+        Data::DateTimeIso(v) => v.clone(),
+        Data::DurationIso(v) => v.clone(),
+        Data::Error(e) => format!("ERROR:{:?}", e),
+        Data::Empty => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("spm_source_reader_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn detects_gbk_semicolon_csv_with_banner_rows() {
+        let content = "钢卷导出报表;;\n;;\n钢卷号;钢种;厚度\nC001;\"Q235;B\";2.5\nC002;SPHC;3.0\n";
+        let (gbk, _, _) = encoding_rs::GBK.encode(content);
+        let path = temp_path("gbk.csv");
+        std::fs::write(&path, &gbk).unwrap();
+
+        let options = SourceReadOptions {
+            header_row: Some(3),
+            ..Default::default()
+        };
+        let source = read_source(path.to_str().unwrap(), &options).expect("读取失败");
+        assert_eq!(source.encoding.as_deref(), Some("gbk"));
+        assert_eq!(source.delimiter, Some(';'));
+        assert_eq!(source.headers, vec!["钢卷号", "钢种", "厚度"]);
+        assert_eq!(source.rows.len(), 2);
+        assert_eq!(source.rows[0][1], "Q235;B");
+        assert_eq!(source.first_line_no, 4);
+
+        // 数据起始行不得早于表头
+        let bad = SourceReadOptions {
+            header_row: Some(3),
+            data_start_row: Some(3),
+            ..Default::default()
+        };
+        assert!(read_source(path.to_str().unwrap(), &bad).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn selects_sheet_by_name_or_index_and_keeps_absolute_rows() {
+        use rust_xlsxwriter::Workbook;
+
+        let path = temp_path("multi.xlsx");
+        let mut workbook = Workbook::new();
+        let summary = workbook.add_worksheet();
+        summary.set_name("汇总").unwrap();
+        summary.write_string(0, 0, "汇总表").unwrap();
+        let detail = workbook.add_worksheet();
+        detail.set_name("明细").unwrap();
+        // 第1行空白、第2行标题横幅、第3行表头、数据从第4行起，且首列空白
+        detail.write_string(1, 1, "2026年1月钢卷明细").unwrap();
+        detail.write_string(2, 1, "钢卷号").unwrap();
+        detail.write_string(2, 2, "宽度").unwrap();
+        detail.write_string(3, 1, "C001").unwrap();
+        detail.write_number(3, 2, 1250.0).unwrap();
+        workbook.save(&path).unwrap();
+        let file = path.to_str().unwrap();
+
+        assert_eq!(list_sheets(file).unwrap(), vec!["汇总", "明细"]);
+        for sheet in ["明细", "2"] {
+            let source = read_source(
+                file,
+                &SourceReadOptions {
+                    sheet: Some(sheet.to_string()),
+                    header_row: Some(3),
+                    ..Default::default()
+                },
+            )
+            .expect("读取失败");
+            assert_eq!(source.sheet_name.as_deref(), Some("明细"));
+            assert_eq!(source.headers, vec!["", "钢卷号", "宽度"]);
+            assert_eq!(source.rows, vec![vec!["", "C001", "1250"]]);
+            assert_eq!(source.first_line_no, 4);
+        }
+
+        let default_sheet = read_source(file, &SourceReadOptions::default()).unwrap();
+        assert_eq!(default_sheet.sheet_name.as_deref(), Some("汇总"));
+        assert!(read_source(
+            file,
+            &SourceReadOptions {
+                sheet: Some("不存在".to_string()),
+                ..Default::default()
+            }
+        )
+        .is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn blank_lines_keep_physical_line_numbers() {
+        let content = "导出报表\n\n钢卷号,钢种\nC001,Q235\n\nC002,SPHC\n";
+        let path = temp_path("blank.csv");
+        std::fs::write(&path, content).unwrap();
+
+        let options = SourceReadOptions {
+            header_row: Some(3),
+            ..Default::default()
+        };
+        let source = read_source(path.to_str().unwrap(), &options).expect("读取失败");
+        assert_eq!(source.headers, vec!["钢卷号", "钢种"]);
+        assert_eq!(
+            source.rows,
+            vec![vec!["C001", "Q235"], vec!["C002", "SPHC"]]
+        );
+        assert_eq!(source.line_numbers, vec![4, 6]);
+        assert_eq!(source.first_line_no, 4);
+
+        // 表头行指向空行时报错而不是错位读取
+        let blank_header = SourceReadOptions {
+            header_row: Some(2),
+            ..Default::default()
+        };
+        assert!(read_source(path.to_str().unwrap(), &blank_header).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn delimiter_detection_prefers_consistent_counts() {
+        assert_eq!(detect_delimiter("a,b,c\n1,2,3\n", 1), b',');
+        assert_eq!(detect_delimiter("a\tb\n1\t2\n", 1), b'\t');
+        assert_eq!(detect_delimiter("a;b;c\n\"1,5\";2;3\n", 1), b';');
+        assert_eq!(detect_delimiter("single\nvalue\n", 1), b',');
+        // 横幅行含逗号，表头与数据用分号
+        assert_eq!(
+            detect_delimiter("报表,2026年1月,第1版\n\n钢卷号;钢种\nC001;Q235\n", 3),
+            b';'
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! 策略模板修订
+//!
+//! 策略模板每次修改都生成一条不可变修订（内容快照），排程时方案记录所用修订；
+//! 修订之间按排序因子、硬约束、软约束、评分权重、适温规则做语义对比，也可把旧修订恢复为当前内容。
+
+use crate::models::{schedule_plan, strategy_revision, strategy_template};
+use crate::AppError;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const CHANGE_ADDED: &str = "added";
+pub const CHANGE_REMOVED: &str = "removed";
+pub const CHANGE_CHANGED: &str = "changed";
+pub const CHANGE_REORDERED: &str = "reordered";
+
+/// 单项差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyChange {
+    /// sort_weights / constraints / soft_constraints / eval_weights / temper_rules
+    pub section: String,
+    /// 如 priorities[field=width].weight、constraints[type=width_jump].max_value
+    pub path: String,
+    pub kind: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// 两个修订间的语义差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyRevisionDiff {
+    pub strategy_id: i32,
+    pub from_revision_no: i32,
+    pub to_revision_no: i32,
+    pub changes: Vec<StrategyChange>,
+}
+
+/// 策略内容各部分（名称, 原始 JSON）
+fn sections(
+    sort_weights: &str,
+    constraints: &str,
+    soft_constraints: Option<&str>,
+    eval_weights: &str,
+    temper_rules: &str,
+) -> [(&'static str, Option<String>); 5] {
+    [
+        ("sort_weights", Some(sort_weights.to_string())),
+        ("constraints", Some(constraints.to_string())),
+        ("soft_constraints", soft_constraints.map(str::to_string)),
+        ("eval_weights", Some(eval_weights.to_string())),
+        ("temper_rules", Some(temper_rules.to_string())),
+    ]
+}
+
+fn revision_sections(rev: &strategy_revision::Model) -> [(&'static str, Option<String>); 5] {
+    sections(
+        &rev.sort_weights,
+        &rev.constraints,
+        rev.soft_constraints.as_deref(),
+        &rev.eval_weights,
+        &rev.temper_rules,
+    )
+}
+
+fn template_sections(tpl: &strategy_template::Model) -> [(&'static str, Option<String>); 5] {
+    sections(
+        &tpl.sort_weights,
+        &tpl.constraints,
+        tpl.soft_constraints.as_deref(),
+        &tpl.eval_weights,
+        &tpl.temper_rules,
+    )
+}
+
+/// 解析为 JSON，无法解析时按原始字符串比较
+fn parse_section(raw: &Option<String>) -> Option<Value> {
+    raw.as_ref().map(|text| {
+        serde_json::from_str::<Value>(text).unwrap_or_else(|_| Value::String(text.clone()))
+    })
+}
+
+/// 数组元素的标识（约束按 type、排序因子按 field）
+fn identity_key(value: &Value) -> Option<String> {
+    ["type", "field"].iter().find_map(|key| {
+        value
+            .get(*key)
+            .and_then(Value::as_str)
+            .map(|id| format!("{}={}", key, id))
+    })
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn diff_value(
+    section: &str,
+    path: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    out: &mut Vec<StrategyChange>,
+) {
+    let mut push = |kind: &str, before: Option<&Value>, after: Option<&Value>| {
+        out.push(StrategyChange {
+            section: section.to_string(),
+            path: path.to_string(),
+            kind: kind.to_string(),
+            before: before.cloned(),
+            after: after.cloned(),
+        })
+    };
+    match (before, after) {
+        (None, None) => {}
+        (Some(b), None) => push(CHANGE_REMOVED, Some(b), None),
+        (None, Some(a)) => push(CHANGE_ADDED, None, Some(a)),
+        (Some(Value::Object(b)), Some(Value::Object(a))) => {
+            let keys = b.keys().chain(a.keys().filter(|k| !b.contains_key(*k)));
+            for key in keys {
+                diff_value(section, &join_path(path, key), b.get(key), a.get(key), out);
+            }
+        }
+        (Some(Value::Array(b)), Some(Value::Array(a)))
+            if b.iter().chain(a.iter()).all(|v| identity_key(v).is_some()) =>
+        {
+            let keyed = |items: &[Value]| -> Vec<(String, Value)> {
+                items
+                    .iter()
+                    .map(|v| (identity_key(v).unwrap_or_default(), v.clone()))
+                    .collect()
+            };
+            let (b, a) = (keyed(b), keyed(a));
+            let find = |list: &[(String, Value)], key: &str| {
+                list.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+            };
+            for (key, value) in &b {
+                let element_path = format!("{}[{}]", path, key);
+                diff_value(
+                    section,
+                    &element_path,
+                    Some(value),
+                    find(&a, key).as_ref(),
+                    out,
+                );
+            }
+            for (key, value) in &a {
+                if find(&b, key).is_none() {
+                    let element_path = format!("{}[{}]", path, key);
+                    diff_value(section, &element_path, None, Some(value), out);
+                }
+            }
+            // 共有元素的先后顺序变化（影响排序因子优先级）
+            let order = |list: &[(String, Value)], other: &[(String, Value)]| -> Vec<Value> {
+                list.iter()
+                    .filter(|(k, _)| other.iter().any(|(o, _)| o == k))
+                    .map(|(k, _)| Value::String(k.clone()))
+                    .collect()
+            };
+            let (before_order, after_order) = (order(&b, &a), order(&a, &b));
+            if before_order != after_order {
+                out.push(StrategyChange {
+                    section: section.to_string(),
+                    path: path.to_string(),
+                    kind: CHANGE_REORDERED.to_string(),
+                    before: Some(Value::Array(before_order)),
+                    after: Some(Value::Array(after_order)),
+                });
+            }
+        }
+        (Some(b), Some(a)) if b != a => push(CHANGE_CHANGED, Some(b), Some(a)),
+        _ => {}
+    }
+}
+
+/// 语义对比两个修订
+pub fn diff_revisions(
+    from: &strategy_revision::Model,
+    to: &strategy_revision::Model,
+) -> StrategyRevisionDiff {
+    let mut changes = Vec::new();
+    for ((section, before), (_, after)) in revision_sections(from)
+        .iter()
+        .zip(revision_sections(to).iter())
+    {
+        diff_value(
+            section,
+            "",
+            parse_section(before).as_ref(),
+            parse_section(after).as_ref(),
+            &mut changes,
+        );
+    }
+    StrategyRevisionDiff {
+        strategy_id: to.strategy_id,
+        from_revision_no: from.revision_no,
+        to_revision_no: to.revision_no,
+        changes,
+    }
+}
+
+/// 策略的最新修订
+pub async fn latest_revision<C: ConnectionTrait>(
+    conn: &C,
+    strategy_id: i32,
+) -> Result<Option<strategy_revision::Model>, AppError> {
+    Ok(strategy_revision::Entity::find()
+        .filter(strategy_revision::Column::StrategyId.eq(strategy_id))
+        .order_by_desc(strategy_revision::Column::RevisionNo)
+        .one(conn)
+        .await?)
+}
+
+/// 策略全部修订（新的在前）
+pub async fn list_revisions<C: ConnectionTrait>(
+    conn: &C,
+    strategy_id: i32,
+) -> Result<Vec<strategy_revision::Model>, AppError> {
+    Ok(strategy_revision::Entity::find()
+        .filter(strategy_revision::Column::StrategyId.eq(strategy_id))
+        .order_by_desc(strategy_revision::Column::RevisionNo)
+        .all(conn)
+        .await?)
+}
+
+/// 以模板当前内容生成一条新修订
+pub async fn record_revision<C: ConnectionTrait>(
+    conn: &C,
+    template: &strategy_template::Model,
+    change_note: Option<String>,
+) -> Result<strategy_revision::Model, AppError> {
+    let next_no = latest_revision(conn, template.id)
+        .await?
+        .map(|rev| rev.revision_no + 1)
+        .unwrap_or(1);
+    Ok(strategy_revision::ActiveModel {
+        strategy_id: Set(template.id),
+        revision_no: Set(next_no),
+        name: Set(template.name.clone()),
+        description: Set(template.description.clone()),
+        sort_weights: Set(template.sort_weights.clone()),
+        constraints: Set(template.constraints.clone()),
+        soft_constraints: Set(template.soft_constraints.clone()),
+        eval_weights: Set(template.eval_weights.clone()),
+        temper_rules: Set(template.temper_rules.clone()),
+        change_note: Set(change_note),
+        created_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?)
+}
+
+/// 模板内容与最新修订不同（或尚无修订）时生成新修订，否则返回最新修订
+pub async fn revise_if_changed<C: ConnectionTrait>(
+    conn: &C,
+    template: &strategy_template::Model,
+    change_note: Option<String>,
+) -> Result<strategy_revision::Model, AppError> {
+    match latest_revision(conn, template.id).await? {
+        Some(rev) if revision_sections(&rev) == template_sections(template) => Ok(rev),
+        Some(_) => {
+            let note = change_note.unwrap_or_else(|| "同步模板当前内容".to_string());
+            record_revision(conn, template, Some(note)).await
+        }
+        None => {
+            let note = change_note.unwrap_or_else(|| "初始版本".to_string());
+            record_revision(conn, template, Some(note)).await
+        }
+    }
+}
+
+/// 取与模板当前内容一致的修订；旧数据没有修订或内容被绕过修订修改时补记一条
+pub async fn ensure_current_revision<C: ConnectionTrait>(
+    conn: &C,
+    template: &strategy_template::Model,
+) -> Result<strategy_revision::Model, AppError> {
+    revise_if_changed(conn, template, None).await
+}
+
+/// 查找属于指定策略的修订
+pub async fn find_revision<C: ConnectionTrait>(
+    conn: &C,
+    strategy_id: i32,
+    revision_id: i32,
+) -> Result<strategy_revision::Model, AppError> {
+    strategy_revision::Entity::find_by_id(revision_id)
+        .one(conn)
+        .await?
+        .filter(|rev| rev.strategy_id == strategy_id)
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "策略 {} 不存在修订 id={}",
+                strategy_id, revision_id
+            ))
+        })
+}
+
+/// 方案评估所用策略：优先取方案记录的修订快照，未记录修订（旧方案）时回退到模板当前内容
+///
+/// 快照以模板模型返回（id 为所属模板），调用方无需区分来源。
+pub async fn load_plan_strategy<C: ConnectionTrait>(
+    conn: &C,
+    plan: &schedule_plan::Model,
+) -> Result<Option<strategy_template::Model>, AppError> {
+    if let Some(revision_id) = plan.strategy_revision_id {
+        let revision = strategy_revision::Entity::find_by_id(revision_id)
+            .one(conn)
+            .await?
+            .filter(|rev| plan.strategy_id.map_or(true, |sid| sid == rev.strategy_id));
+        if let Some(rev) = revision {
+            return Ok(Some(revision_as_template(rev)));
+        }
+    }
+    match plan.strategy_id {
+        Some(sid) => Ok(strategy_template::Entity::find_by_id(sid).one(conn).await?),
+        None => Ok(None),
+    }
+}
+
+fn revision_as_template(rev: strategy_revision::Model) -> strategy_template::Model {
+    strategy_template::Model {
+        id: rev.strategy_id,
+        name: rev.name,
+        description: rev.description,
+        is_default: None,
+        is_system: None,
+        sort_weights: rev.sort_weights,
+        constraints: rev.constraints,
+        soft_constraints: rev.soft_constraints,
+        eval_weights: rev.eval_weights,
+        temper_rules: rev.temper_rules,
+        created_at: rev.created_at,
+        updated_at: rev.created_at,
+    }
+}
+
+/// 将旧修订恢复为模板当前内容（名称保持不变），并生成一条新修订
+pub async fn restore_revision<C: ConnectionTrait>(
+    conn: &C,
+    strategy_id: i32,
+    revision_id: i32,
+) -> Result<(strategy_template::Model, strategy_revision::Model), AppError> {
+    let template = strategy_template::Entity::find_by_id(strategy_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::Internal("策略模板不存在".to_string()))?;
+    let source = find_revision(conn, strategy_id, revision_id).await?;
+    // 恢复前确保当前内容也有修订留存
+    ensure_current_revision(conn, &template).await?;
+
+    let mut active: strategy_template::ActiveModel = template.into();
+    active.description = Set(source.description.clone());
+    active.sort_weights = Set(source.sort_weights.clone());
+    active.constraints = Set(source.constraints.clone());
+    active.soft_constraints = Set(source.soft_constraints.clone());
+    active.eval_weights = Set(source.eval_weights.clone());
+    active.temper_rules = Set(source.temper_rules.clone());
+    active.updated_at = Set(Some(chrono::Utc::now()));
+    let restored = active.update(conn).await?;
+    let revision = record_revision(
+        conn,
+        &restored,
+        Some(format!("恢复自修订 r{}", source.revision_no)),
+    )
+    .await?;
+    Ok((restored, revision))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revision(no: i32, sort_weights: &str, constraints: &str) -> strategy_revision::Model {
+        strategy_revision::Model {
+            id: no,
+            strategy_id: 1,
+            revision_no: no,
+            name: "默认策略".to_string(),
+            description: None,
+            sort_weights: sort_weights.to_string(),
+            constraints: constraints.to_string(),
+            soft_constraints: None,
+            eval_weights: r#"{"weights":{"width_jump_count":{"weight":30}}}"#.to_string(),
+            temper_rules: r#"{"enabled":true}"#.to_string(),
+            change_note: None,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn diff_matches_elements_by_identity() {
+        let from = revision(
+            1,
+            r#"{"priorities":[{"field":"temp_status","weight":100},{"field":"width","weight":50}]}"#,
+            r#"{"constraints":[{"type":"width_jump","max_value":100}]}"#,
+        );
+        let to = revision(
+            2,
+            r#"{"priorities":[{"field":"width","weight":60},{"field":"temp_status","weight":100}]}"#,
+            r#"{"constraints":[{"type":"width_jump","max_value":80},{"type":"shift_capacity","max_value":1200}]}"#,
+        );
+
+        let diff = diff_revisions(&from, &to);
+        let paths: Vec<(&str, &str)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("priorities[field=width].weight", CHANGE_CHANGED),
+                ("priorities", CHANGE_REORDERED),
+                ("constraints[type=width_jump].max_value", CHANGE_CHANGED),
+                ("constraints[type=shift_capacity]", CHANGE_ADDED),
+            ]
+        );
+        assert_eq!(diff.changes[0].before, Some(serde_json::json!(50)));
+        assert!(diff_revisions(&from, &from).changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn revisions_are_recorded_and_restorable() {
+        use crate::db::get_db;
+
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_strategy_rev_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let template = strategy_template::ActiveModel {
+            name: Set(format!("修订测试-{}", seed)),
+            sort_weights: Set(r#"{"priorities":[]}"#.to_string()),
+            constraints: Set(r#"{"constraints":[]}"#.to_string()),
+            eval_weights: Set(r#"{"weights":{}}"#.to_string()),
+            temper_rules: Set("{}".to_string()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        let first = ensure_current_revision(db, &template).await.unwrap();
+        assert_eq!(first.revision_no, 1);
+        // 内容未变时复用同一修订
+        assert_eq!(
+            ensure_current_revision(db, &template).await.unwrap().id,
+            first.id
+        );
+
+        let mut active: strategy_template::ActiveModel = template.into();
+        active.constraints =
+            Set(r#"{"constraints":[{"type":"width_jump","max_value":80}]}"#.to_string());
+        let edited = active.update(db).await.unwrap();
+        let second = record_revision(db, &edited, None).await.unwrap();
+        assert_eq!(second.revision_no, 2);
+
+        // 方案按记录的修订快照评估，未记录修订时取模板当前内容
+        let plan = schedule_plan::ActiveModel {
+            plan_no: Set(format!("REV-{}", seed)),
+            name: Set("REV".to_string()),
+            period_type: Set("daily".to_string()),
+            start_date: Set("2026-02-13".to_string()),
+            end_date: Set("2026-02-13".to_string()),
+            strategy_id: Set(Some(edited.id)),
+            strategy_revision_id: Set(Some(first.id)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        let pinned = load_plan_strategy(db, &plan).await.unwrap().unwrap();
+        assert_eq!(
+            (pinned.id, pinned.constraints.as_str()),
+            (edited.id, r#"{"constraints":[]}"#)
+        );
+        let legacy = schedule_plan::Model {
+            strategy_revision_id: None,
+            ..plan
+        };
+        let live = load_plan_strategy(db, &legacy).await.unwrap().unwrap();
+        assert_eq!(live.constraints, edited.constraints);
+
+        let (restored, third) = restore_revision(db, edited.id, first.id).await.unwrap();
+        assert_eq!(restored.constraints, r#"{"constraints":[]}"#);
+        assert_eq!(third.revision_no, 3);
+        assert!(diff_revisions(&first, &third).changes.is_empty());
+        assert_eq!(list_revisions(db, edited.id).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,776 @@
+//! 策略配置结构校验
+//!
+//! 引擎的 parse_* 只要 JSON 能反序列化就接受，未知约束类型、拼错的字段会被静默忽略。
+//! 这里按已知排序字段、约束类型及其参数逐项检查取值范围和相互冲突的设置，
+//! 返回带路径（如 constraints.constraints[1].max_value）的错误与警告；
+//! 新建、更新、导入模板时存在错误则拒绝保存，警告不阻止保存。
+
+use crate::engine::{evaluator, sorter, validator};
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+pub const LEVEL_ERROR: &str = "error";
+pub const LEVEL_WARNING: &str = "warning";
+
+/// 已知排序字段（与 sorter::extract_field_value 一致）
+pub const SORT_FIELDS: &[&str] = &[
+    "temp_status",
+    "width",
+    "priority",
+    "hardness_level",
+    "thickness",
+    "surface_level",
+    "product_type",
+    "storage_days",
+    "steel_grade",
+];
+
+/// 数值型排序字段，sort_map 对其不生效
+const NUMERIC_SORT_FIELDS: &[&str] = &["width", "priority", "thickness", "storage_days"];
+
+const SORT_KEYS: &[&str] = &[
+    "field",
+    "order",
+    "weight",
+    "enabled",
+    "group",
+    "description",
+    "sort_map",
+    "is_prerequisite",
+];
+
+/// 硬约束类型及其使用的参数
+pub const HARD_CONSTRAINT_TYPES: &[(&str, &[&str])] = &[
+    ("temp_status_filter", &[]),
+    ("width_jump", &["max_value"]),
+    ("roll_change_tonnage", &["max_value", "finish_last_coil"]),
+    ("shift_capacity", &["max_value"]),
+    ("roll_change_duration", &["value"]),
+    ("overdue_priority", &["max_days"]),
+];
+const HARD_COMMON_KEYS: &[&str] = &[
+    "type",
+    "name",
+    "enabled",
+    "unit",
+    "description",
+    "error_message",
+];
+const HARD_PARAM_KEYS: &[&str] = &["max_value", "value", "max_days", "finish_last_coil"];
+
+/// 软约束类型及其使用的参数
+pub const SOFT_CONSTRAINT_TYPES: &[(&str, &[&str])] = &[
+    ("steel_grade_switch", &["penalty"]),
+    ("thickness_jump", &["threshold", "penalty"]),
+    (
+        "surface_after_roll_change",
+        &["bonus", "target_levels", "within_coils"],
+    ),
+    ("contract_grouping", &["bonus"]),
+    ("yard_retrieval", &["penalty", "dig_penalty"]),
+];
+const SOFT_COMMON_KEYS: &[&str] = &["type", "name", "enabled", "unit", "description"];
+const SOFT_PARAM_KEYS: &[&str] = &[
+    "penalty",
+    "bonus",
+    "threshold",
+    "target_levels",
+    "within_coils",
+    "dig_penalty",
+];
+
+/// 已知评分指标（与 evaluator::evaluate_plan 一致）
+pub const EVAL_WEIGHT_KEYS: &[&str] = &[
+    "width_jump_count",
+    "roll_change_count",
+    "capacity_utilization",
+    "tempered_ratio",
+    "urgent_completion",
+];
+
+/// 单条校验问题
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaIssue {
+    /// 如 sort_weights.priorities[2].order
+    pub path: String,
+    /// error / warning
+    pub level: String,
+    pub message: String,
+}
+
+/// 策略配置校验结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategyValidationReport {
+    pub valid: bool,
+    pub errors: Vec<SchemaIssue>,
+    pub warnings: Vec<SchemaIssue>,
+}
+
+impl StrategyValidationReport {
+    /// 前几条错误拼成一行，用于拒绝保存时的提示
+    pub fn error_summary(&self) -> String {
+        let mut parts: Vec<String> = self
+            .errors
+            .iter()
+            .take(3)
+            .map(|e| format!("{}: {}", e.path, e.message))
+            .collect();
+        if self.errors.len() > 3 {
+            parts.push(format!("共 {} 项错误", self.errors.len()));
+        }
+        parts.join("；")
+    }
+}
+
+/// 单个部分的结构检查函数
+type SectionCheck = fn(&mut Checker, &Value);
+
+#[derive(Default)]
+struct Checker {
+    errors: Vec<SchemaIssue>,
+    warnings: Vec<SchemaIssue>,
+}
+
+impl Checker {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(SchemaIssue {
+            path: path.to_string(),
+            level: LEVEL_ERROR.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn warn(&mut self, path: &str, message: impl Into<String>) {
+        self.warnings.push(SchemaIssue {
+            path: path.to_string(),
+            level: LEVEL_WARNING.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn object<'a>(&mut self, path: &str, value: &'a Value) -> Option<&'a Map<String, Value>> {
+        let obj = value.as_object();
+        if obj.is_none() {
+            self.error(path, "必须是对象");
+        }
+        obj
+    }
+
+    /// 未知字段只告警：引擎反序列化时会忽略它们
+    fn unknown_keys(&mut self, path: &str, obj: &Map<String, Value>, known: &[&[&str]]) {
+        for key in obj.keys() {
+            if !known.iter().any(|set| set.contains(&key.as_str())) {
+                self.warn(&format!("{}.{}", path, key), "未知字段，将被忽略");
+            }
+        }
+    }
+
+    /// 取字段（null 视为未设置），返回 (字段路径, 值)
+    fn field<'a>(
+        &mut self,
+        path: &str,
+        obj: &'a Map<String, Value>,
+        key: &str,
+        required: bool,
+    ) -> Option<(String, &'a Value)> {
+        let field_path = format!("{}.{}", path, key);
+        match obj.get(key) {
+            None | Some(Value::Null) => {
+                if required {
+                    self.error(&field_path, "缺少必填字段");
+                }
+                None
+            }
+            Some(v) => Some((field_path, v)),
+        }
+    }
+
+    fn string<'a>(
+        &mut self,
+        path: &str,
+        obj: &'a Map<String, Value>,
+        key: &str,
+        required: bool,
+    ) -> Option<(String, &'a str)> {
+        let (field_path, value) = self.field(path, obj, key, required)?;
+        match value.as_str() {
+            Some(s) => Some((field_path, s)),
+            None => {
+                self.error(&field_path, "必须是字符串");
+                None
+            }
+        }
+    }
+
+    fn boolean(
+        &mut self,
+        path: &str,
+        obj: &Map<String, Value>,
+        key: &str,
+        required: bool,
+    ) -> Option<bool> {
+        let (field_path, value) = self.field(path, obj, key, required)?;
+        let b = value.as_bool();
+        if b.is_none() {
+            self.error(&field_path, "必须是布尔值");
+        }
+        b
+    }
+
+    /// 整数字段，范围为闭区间 [min, max]
+    fn integer(
+        &mut self,
+        path: &str,
+        obj: &Map<String, Value>,
+        key: &str,
+        required: bool,
+        min: i64,
+        max: i64,
+    ) -> Option<i64> {
+        let (field_path, value) = self.field(path, obj, key, required)?;
+        let Some(n) = value.as_i64() else {
+            self.error(&field_path, "必须是整数");
+            return None;
+        };
+        if n < min || n > max {
+            if max == i32::MAX as i64 {
+                self.error(&field_path, format!("不能小于 {}", min));
+            } else {
+                self.error(&field_path, format!("取值需在 {}~{} 之间", min, max));
+            }
+            return None;
+        }
+        Some(n)
+    }
+
+    fn number(
+        &mut self,
+        path: &str,
+        obj: &Map<String, Value>,
+        key: &str,
+        required: bool,
+    ) -> Option<f64> {
+        let (field_path, value) = self.field(path, obj, key, required)?;
+        let n = value.as_f64();
+        if n.is_none() {
+            self.error(&field_path, "必须是数值");
+        }
+        n
+    }
+
+    fn array<'a>(
+        &mut self,
+        path: &str,
+        obj: &'a Map<String, Value>,
+        key: &str,
+    ) -> Option<&'a Vec<Value>> {
+        let (field_path, value) = self.field(path, obj, key, true)?;
+        let list = value.as_array();
+        if list.is_none() {
+            self.error(&field_path, "必须是数组");
+        }
+        list
+    }
+
+    /// 设置了当前类型不使用的参数时告警
+    fn inapplicable_params(
+        &mut self,
+        path: &str,
+        obj: &Map<String, Value>,
+        param_keys: &[&str],
+        used: &[&str],
+        kind: &str,
+    ) {
+        for key in param_keys {
+            if !used.contains(key) && obj.get(*key).is_some_and(|v| !v.is_null()) {
+                self.warn(
+                    &format!("{}.{}", path, key),
+                    format!("{} 不使用该参数", kind),
+                );
+            }
+        }
+    }
+}
+
+fn lookup<'a>(table: &'a [(&str, &'a [&'a str])], kind: &str) -> Option<&'a [&'a str]> {
+    table.iter().find(|(k, _)| *k == kind).map(|(_, p)| *p)
+}
+
+fn type_names(table: &[(&str, &[&str])]) -> String {
+    table.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(", ")
+}
+
+fn parse_section(c: &mut Checker, section: &str, raw: &str) -> Option<Value> {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            c.error(section, format!("JSON 格式错误: {}", e));
+            None
+        }
+    }
+}
+
+fn check_sort_weights(c: &mut Checker, value: &Value) {
+    let root = "sort_weights";
+    let Some(obj) = c.object(root, value) else {
+        return;
+    };
+    c.unknown_keys(root, obj, &[&["priorities"]]);
+    let Some(list) = c.array(root, obj, "priorities") else {
+        return;
+    };
+
+    let mut seen = HashSet::new();
+    let mut enabled_count = 0;
+    for (i, item) in list.iter().enumerate() {
+        let path = format!("{}.priorities[{}]", root, i);
+        let Some(p) = c.object(&path, item) else {
+            continue;
+        };
+        c.unknown_keys(&path, p, &[SORT_KEYS]);
+
+        if let Some((field_path, field)) = c.string(&path, p, "field", true) {
+            if !SORT_FIELDS.contains(&field) {
+                c.error(
+                    &field_path,
+                    format!("未知排序字段 {}，可选: {}", field, SORT_FIELDS.join(", ")),
+                );
+            } else if !seen.insert(field) {
+                c.error(&field_path, format!("排序字段 {} 重复", field));
+            }
+            let has_sort_map = p
+                .get("sort_map")
+                .and_then(Value::as_object)
+                .is_some_and(|m| !m.is_empty());
+            if has_sort_map && NUMERIC_SORT_FIELDS.contains(&field) {
+                c.warn(
+                    &format!("{}.sort_map", path),
+                    format!("{} 为数值字段，sort_map 不生效", field),
+                );
+            }
+        }
+        if let Some((order_path, order)) = c.string(&path, p, "order", true) {
+            if order != "asc" && order != "desc" {
+                c.error(&order_path, "排序方向必须为 asc 或 desc");
+            }
+        }
+        c.integer(&path, p, "weight", true, 0, 100);
+        if c.boolean(&path, p, "enabled", true) == Some(true) {
+            enabled_count += 1;
+        }
+        c.boolean(&path, p, "group", false);
+        c.boolean(&path, p, "is_prerequisite", false);
+        c.string(&path, p, "description", false);
+        if let Some((map_path, map)) = c.field(&path, p, "sort_map", false) {
+            match map.as_object() {
+                Some(entries) => {
+                    for (k, v) in entries {
+                        if v.as_i64().and_then(|n| i32::try_from(n).ok()).is_none() {
+                            c.error(&format!("{}.{}", map_path, k), "映射值必须是整数");
+                        }
+                    }
+                }
+                None => c.error(&map_path, "必须是对象"),
+            }
+        }
+    }
+
+    if enabled_count == 0 {
+        c.error(&format!("{}.priorities", root), "至少需要启用一个排序因子");
+    }
+}
+
+fn check_hard_constraints(c: &mut Checker, value: &Value) {
+    let root = "constraints";
+    let Some(obj) = c.object(root, value) else {
+        return;
+    };
+    c.unknown_keys(root, obj, &[&["constraints"]]);
+    let Some(list) = c.array(root, obj, "constraints") else {
+        return;
+    };
+
+    let mut seen = HashSet::new();
+    // 启用约束的 max_value: 类型 -> (下标, 值)
+    let mut enabled_max: HashMap<&str, (usize, f64)> = HashMap::new();
+    for (i, item) in list.iter().enumerate() {
+        let path = format!("{}.constraints[{}]", root, i);
+        let Some(p) = c.object(&path, item) else {
+            continue;
+        };
+        c.unknown_keys(&path, p, &[HARD_COMMON_KEYS, HARD_PARAM_KEYS]);
+        c.string(&path, p, "name", true);
+        let enabled = c.boolean(&path, p, "enabled", true).unwrap_or(false);
+        for key in ["unit", "description", "error_message"] {
+            c.string(&path, p, key, false);
+        }
+        let max_value = c.number(&path, p, "max_value", false);
+        let value = c.number(&path, p, "value", false);
+        c.integer(&path, p, "max_days", false, 0, i32::MAX as i64);
+        c.boolean(&path, p, "finish_last_coil", false);
+
+        let Some((type_path, kind)) = c.string(&path, p, "type", true) else {
+            continue;
+        };
+        let Some(params) = lookup(HARD_CONSTRAINT_TYPES, kind) else {
+            c.error(
+                &type_path,
+                format!(
+                    "未知硬约束类型 {}，可选: {}",
+                    kind,
+                    type_names(HARD_CONSTRAINT_TYPES)
+                ),
+            );
+            continue;
+        };
+        if !seen.insert(kind) {
+            c.error(&type_path, format!("硬约束类型 {} 重复", kind));
+        }
+        c.inapplicable_params(&path, p, HARD_PARAM_KEYS, params, kind);
+
+        let (param, param_value) = match kind {
+            "width_jump" | "roll_change_tonnage" | "shift_capacity" => ("max_value", max_value),
+            "roll_change_duration" => ("value", value),
+            _ => continue,
+        };
+        match param_value {
+            Some(v) if v <= 0.0 => c.error(&format!("{}.{}", path, param), "必须大于 0"),
+            Some(v) => {
+                if enabled && param == "max_value" {
+                    enabled_max.insert(kind, (i, v));
+                }
+            }
+            None => c.warn(&format!("{}.{}", path, param), "未设置，将使用默认值"),
+        }
+    }
+
+    if let (Some((i, tonnage)), Some((_, capacity))) = (
+        enabled_max.get("roll_change_tonnage"),
+        enabled_max.get("shift_capacity"),
+    ) {
+        if tonnage > capacity {
+            c.warn(
+                &format!("{}.constraints[{}].max_value", root, i),
+                format!(
+                    "换辊吨位阈值 {} 大于班次产能 {}，单班内不会触发换辊",
+                    tonnage, capacity
+                ),
+            );
+        }
+    }
+}
+
+fn check_soft_constraints(c: &mut Checker, value: &Value) {
+    let root = "soft_constraints";
+    let Some(obj) = c.object(root, value) else {
+        return;
+    };
+    c.unknown_keys(root, obj, &[&["constraints"]]);
+    let Some(list) = c.array(root, obj, "constraints") else {
+        return;
+    };
+
+    let mut seen = HashSet::new();
+    for (i, item) in list.iter().enumerate() {
+        let path = format!("{}.constraints[{}]", root, i);
+        let Some(p) = c.object(&path, item) else {
+            continue;
+        };
+        c.unknown_keys(&path, p, &[SOFT_COMMON_KEYS, SOFT_PARAM_KEYS]);
+        c.string(&path, p, "name", true);
+        c.boolean(&path, p, "enabled", true);
+        for key in ["unit", "description"] {
+            c.string(&path, p, key, false);
+        }
+        for key in ["penalty", "bonus", "dig_penalty"] {
+            c.integer(&path, p, key, false, 0, i32::MAX as i64);
+        }
+        if let Some(threshold) = c.number(&path, p, "threshold", false) {
+            if threshold < 0.0 {
+                c.error(&format!("{}.threshold", path), "不能为负数");
+            }
+        }
+        c.integer(&path, p, "within_coils", false, 1, i32::MAX as i64);
+        if let Some((levels_path, levels)) = c.field(&path, p, "target_levels", false) {
+            match levels.as_array() {
+                Some(items) if items.is_empty() => c.warn(&levels_path, "目标等级为空，约束不生效"),
+                Some(items) if items.iter().all(Value::is_string) => {}
+                _ => c.error(&levels_path, "必须是字符串数组"),
+            }
+        }
+
+        let Some((type_path, kind)) = c.string(&path, p, "type", true) else {
+            continue;
+        };
+        let Some(params) = lookup(SOFT_CONSTRAINT_TYPES, kind) else {
+            c.error(
+                &type_path,
+                format!(
+                    "未知软约束类型 {}，可选: {}",
+                    kind,
+                    type_names(SOFT_CONSTRAINT_TYPES)
+                ),
+            );
+            continue;
+        };
+        if !seen.insert(kind) {
+            c.error(&type_path, format!("软约束类型 {} 重复", kind));
+        }
+        c.inapplicable_params(&path, p, SOFT_PARAM_KEYS, params, kind);
+    }
+}
+
+fn check_eval_weights(c: &mut Checker, value: &Value) {
+    let root = "eval_weights";
+    let Some(obj) = c.object(root, value) else {
+        return;
+    };
+    c.unknown_keys(root, obj, &[&["weights"]]);
+    let Some((weights_path, weights)) = c.field(root, obj, "weights", true) else {
+        return;
+    };
+    let Some(weights) = c.object(&weights_path, weights) else {
+        return;
+    };
+
+    let mut total = 0;
+    for (key, item) in weights {
+        let path = format!("{}.{}", weights_path, key);
+        if !EVAL_WEIGHT_KEYS.contains(&key.as_str()) {
+            c.error(
+                &path,
+                format!(
+                    "未知评分指标 {}，可选: {}",
+                    key,
+                    EVAL_WEIGHT_KEYS.join(", ")
+                ),
+            );
+        }
+        let Some(w) = c.object(&path, item) else {
+            continue;
+        };
+        c.unknown_keys(&path, w, &[&["weight", "description"]]);
+        c.string(&path, w, "description", false);
+        if let Some(weight) = c.integer(&path, w, "weight", true, 0, 100) {
+            if EVAL_WEIGHT_KEYS.contains(&key.as_str()) {
+                total += weight;
+            }
+        }
+    }
+
+    if total == 0 {
+        c.error(&weights_path, "评分权重合计为 0，无法计算综合评分");
+    } else if total != 100 {
+        c.warn(
+            &weights_path,
+            format!("评分权重合计为 {}，评估时按比例折算", total),
+        );
+    }
+}
+
+fn check_temper_rules(c: &mut Checker, value: &Value) {
+    let root = "temper_rules";
+    let Some(obj) = c.object(root, value) else {
+        return;
+    };
+    c.unknown_keys(root, obj, &[&["enabled", "description", "seasons"]]);
+    c.boolean(root, obj, "enabled", false);
+    c.string(root, obj, "description", false);
+    let Some((seasons_path, seasons)) = c.field(root, obj, "seasons", false) else {
+        return;
+    };
+    let Some(seasons) = c.object(&seasons_path, seasons) else {
+        return;
+    };
+
+    // 月份 -> 首个占用的季节
+    let mut month_owner: HashMap<i64, &str> = HashMap::new();
+    for (name, season) in seasons {
+        let path = format!("{}.{}", seasons_path, name);
+        let Some(s) = c.object(&path, season) else {
+            continue;
+        };
+        c.unknown_keys(&path, s, &[&["months", "min_days", "description"]]);
+        c.string(&path, s, "description", false);
+        c.integer(&path, s, "min_days", true, 0, 365);
+        let Some(months) = c.array(&path, s, "months") else {
+            continue;
+        };
+        for (j, month) in months.iter().enumerate() {
+            let month_path = format!("{}.months[{}]", path, j);
+            match month.as_i64() {
+                Some(m) if (1..=12).contains(&m) => {
+                    if let Some(owner) = month_owner.insert(m, name.as_str()) {
+                        c.warn(
+                            &month_path,
+                            format!("{} 月同时属于 {} 和 {}", m, owner, name),
+                        );
+                    }
+                }
+                _ => c.error(&month_path, "月份必须是 1~12 的整数"),
+            }
+        }
+    }
+}
+
+/// 校验策略模板五个 JSON 部分
+pub fn validate_strategy(
+    sort_weights: &str,
+    constraints: &str,
+    soft_constraints: Option<&str>,
+    eval_weights: &str,
+    temper_rules: &str,
+) -> StrategyValidationReport {
+    let mut c = Checker::default();
+    let soft_constraints = soft_constraints.filter(|s| !s.trim().is_empty());
+
+    let sections: [(&str, Option<&str>, SectionCheck); 5] = [
+        ("sort_weights", Some(sort_weights), check_sort_weights),
+        ("constraints", Some(constraints), check_hard_constraints),
+        ("soft_constraints", soft_constraints, check_soft_constraints),
+        ("eval_weights", Some(eval_weights), check_eval_weights),
+        ("temper_rules", Some(temper_rules), check_temper_rules),
+    ];
+    for (section, raw, check) in sections {
+        let Some(raw) = raw else {
+            continue;
+        };
+        let before = c.errors.len();
+        let Some(value) = parse_section(&mut c, section, raw) else {
+            continue;
+        };
+        check(&mut c, &value);
+
+        // 结构检查通过后再用引擎解析兜底，防止两边规则不一致
+        if c.errors.len() == before {
+            let parsed = match section {
+                "sort_weights" => sorter::parse_sort_config(raw).map(|_| ()),
+                "constraints" => validator::parse_hard_constraints(raw).map(|_| ()),
+                "soft_constraints" => validator::parse_soft_constraints(raw).map(|_| ()),
+                "eval_weights" => evaluator::parse_eval_weights(raw).map(|_| ()),
+                _ => Ok(()),
+            };
+            if let Err(e) = parsed {
+                c.error(section, e.to_string());
+            }
+        }
+    }
+
+    StrategyValidationReport {
+        valid: c.errors.is_empty(),
+        errors: c.errors,
+        warnings: c.warnings,
+    }
+}
+
+/// 存在错误时拒绝保存
+pub fn ensure_valid(
+    sort_weights: &str,
+    constraints: &str,
+    soft_constraints: Option<&str>,
+    eval_weights: &str,
+    temper_rules: &str,
+) -> Result<StrategyValidationReport, AppError> {
+    let report = validate_strategy(
+        sort_weights,
+        constraints,
+        soft_constraints,
+        eval_weights,
+        temper_rules,
+    );
+    if !report.valid {
+        return Err(AppError::InvalidInput(format!(
+            "策略配置校验失败: {}",
+            report.error_summary()
+        )));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 与 defaults.sql 中系统默认模板一致
+    const DEFAULT_SORT: &str = r#"{"priorities":[{"field":"temp_status","order":"desc","weight":100,"enabled":true,"group":false,"description":"适温状态优先","sort_map":{"ready":1,"waiting":0},"is_prerequisite":true},{"field":"width","order":"desc","weight":95,"enabled":true,"group":true,"description":"宽度优先，宽→窄"},{"field":"hardness_level","order":"asc","weight":85,"enabled":true,"group":true,"description":"硬度等级，软→硬","sort_map":{"软":1,"中":2,"硬":3}}]}"#;
+    const DEFAULT_HARD: &str = r#"{"constraints":[{"type":"temp_status_filter","name":"适温材料筛选","enabled":true,"description":"只有适温材料才可进入排程队列"},{"type":"width_jump","name":"宽度跳跃限制","max_value":100,"unit":"mm","enabled":true,"error_message":"相邻材料宽度差超过{max_value}mm限制"},{"type":"roll_change_tonnage","name":"换辊吨位阈值","max_value":800,"unit":"吨","enabled":true,"finish_last_coil":true},{"type":"shift_capacity","name":"班次产能上限","max_value":1200,"unit":"吨","enabled":true},{"type":"roll_change_duration","name":"换辊时长","value":30,"unit":"分钟","enabled":true},{"type":"overdue_priority","name":"超期材料强制优先","max_days":0,"enabled":true}]}"#;
+    const DEFAULT_SOFT: &str = r#"{"constraints":[{"type":"steel_grade_switch","name":"钢种切换惩罚","penalty":10,"enabled":true},{"type":"thickness_jump","name":"厚度跳跃惩罚","threshold":1.0,"penalty":5,"unit":"mm","enabled":true},{"type":"surface_after_roll_change","name":"高表面等级换辊后优先","target_levels":["FA","FB"],"within_coils":5,"bonus":20,"enabled":true},{"type":"contract_grouping","name":"合同材料集中","bonus":10,"enabled":true}]}"#;
+    const DEFAULT_EVAL: &str = r#"{"weights":{"width_jump_count":{"weight":30,"description":"宽度跳跃次数"},"roll_change_count":{"weight":25},"capacity_utilization":{"weight":20},"tempered_ratio":{"weight":15},"urgent_completion":{"weight":10}}}"#;
+    const DEFAULT_TEMPER: &str = r#"{"enabled":true,"description":"适温材料判定规则","seasons":{"spring":{"months":[3,4,5],"min_days":3},"summer":{"months":[6,7,8],"min_days":4},"autumn":{"months":[9,10,11],"min_days":4},"winter":{"months":[12,1,2],"min_days":3}}}"#;
+
+    fn paths(issues: &[SchemaIssue]) -> Vec<&str> {
+        issues.iter().map(|i| i.path.as_str()).collect()
+    }
+
+    #[test]
+    fn default_template_is_clean() {
+        let report = validate_strategy(
+            DEFAULT_SORT,
+            DEFAULT_HARD,
+            Some(DEFAULT_SOFT),
+            DEFAULT_EVAL,
+            DEFAULT_TEMPER,
+        );
+        assert!(report.valid, "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn reports_path_addressed_errors_and_warnings() {
+        let sort = r#"{"priorities":[{"field":"widht","order":"down","weight":120,"enabled":true},{"field":"thickness","order":"asc","weight":50,"enabled":true,"sort_map":{"a":1}}]}"#;
+        let hard = r#"{"constraints":[{"type":"width_jump","name":"宽度","enabled":true,"max_value":-5,"max_vaule":100},{"type":"max_speed","name":"速度","enabled":true},{"type":"roll_change_tonnage","name":"吨位","enabled":true,"max_value":2000},{"type":"shift_capacity","name":"产能","enabled":true,"max_value":1200}]}"#;
+        let soft = r#"{"constraints":[{"type":"steel_grade_switch","name":"钢种","enabled":true,"penalty":-1,"bonus":5}]}"#;
+        let eval = r#"{"weights":{"width_jump_count":{"weight":30},"speed":{"weight":10}}}"#;
+        let temper = r#"{"seasons":{"spring":{"months":[3,13],"min_days":3},"summer":{"months":[3,6],"min_days":4}}}"#;
+
+        let report = validate_strategy(sort, hard, Some(soft), eval, temper);
+        assert!(!report.valid);
+        let errors = paths(&report.errors);
+        for expected in [
+            "sort_weights.priorities[0].field",
+            "sort_weights.priorities[0].order",
+            "sort_weights.priorities[0].weight",
+            "constraints.constraints[0].max_value",
+            "constraints.constraints[1].type",
+            "soft_constraints.constraints[0].penalty",
+            "eval_weights.weights.speed",
+            "temper_rules.seasons.spring.months[1]",
+        ] {
+            assert!(
+                errors.contains(&expected),
+                "缺少错误 {}: {:?}",
+                expected,
+                errors
+            );
+        }
+        let warnings = paths(&report.warnings);
+        for expected in [
+            "sort_weights.priorities[1].sort_map",
+            "constraints.constraints[0].max_vaule",
+            "constraints.constraints[2].max_value",
+            "soft_constraints.constraints[0].bonus",
+            "eval_weights.weights",
+            "temper_rules.seasons.summer.months[0]",
+        ] {
+            assert!(
+                warnings.contains(&expected),
+                "缺少警告 {}: {:?}",
+                expected,
+                warnings
+            );
+        }
+        assert!(ensure_valid(sort, hard, Some(soft), eval, temper).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_json_and_missing_sections() {
+        let report = validate_strategy("{bad", r#"{"items":[]}"#, None, DEFAULT_EVAL, "[]");
+        assert_eq!(
+            paths(&report.errors),
+            vec!["sort_weights", "constraints.constraints", "temper_rules"]
+        );
+        assert_eq!(paths(&report.warnings), vec!["constraints.items"]);
+    }
+}
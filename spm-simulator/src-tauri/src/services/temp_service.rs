@@ -1,11 +1,16 @@
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use sea_orm::*;
 use std::collections::HashMap;
 
-use crate::db::get_db;
+use crate::engine::temp_calc::{self, CoilThermalInput, CoolingParams};
+use crate::models::material;
 use crate::models::system_config::Entity as Config;
 use crate::AppError;
 
+/// 适温模型：按季节天数 / 冷却模型
+pub const TEMPER_MODEL_DAYS: &str = "days";
+pub const TEMPER_MODEL_COOLING: &str = "cooling";
+
 /// 适温配置（从数据库加载）
 #[derive(Debug, Clone)]
 pub struct TemperConfig {
@@ -18,6 +23,13 @@ pub struct TemperConfig {
     pub summer_months: Vec<u32>,
     pub autumn_months: Vec<u32>,
     pub winter_months: Vec<u32>,
+    /// 适温模型: days / cooling
+    pub model: String,
+    pub cooling: CoolingParams,
+    /// 1~12 月平均环境温度（℃）
+    pub ambient_monthly: Vec<f64>,
+    /// 导入的日气温（优先于月均温度）
+    pub ambient_daily: HashMap<NaiveDate, f64>,
 }
 
 impl Default for TemperConfig {
@@ -32,17 +44,40 @@ impl Default for TemperConfig {
             summer_months: vec![6, 7, 8],
             autumn_months: vec![9, 10, 11],
             winter_months: vec![12, 1, 2],
+            model: TEMPER_MODEL_DAYS.to_string(),
+            cooling: CoolingParams::default(),
+            ambient_monthly: vec![
+                0.0, 3.0, 9.0, 16.0, 22.0, 26.0, 29.0, 28.0, 23.0, 16.0, 8.0, 2.0,
+            ],
+            ambient_daily: HashMap::new(),
         }
     }
 }
 
-/// 从数据库加载适温配置
-pub async fn load_temper_config() -> Result<TemperConfig, AppError> {
-    let db = get_db();
+impl TemperConfig {
+    /// 是否使用冷却模型
+    pub fn uses_cooling_model(&self) -> bool {
+        self.model == TEMPER_MODEL_COOLING
+    }
+
+    /// 取某日环境温度：日气温表优先，否则取月均温度
+    pub fn ambient_temp(&self, date: NaiveDate) -> f64 {
+        if let Some(v) = self.ambient_daily.get(&date) {
+            return *v;
+        }
+        let idx = date.month0() as usize;
+        self.ambient_monthly
+            .get(idx)
+            .copied()
+            .unwrap_or(TemperConfig::default().ambient_monthly[idx])
+    }
+}
 
+/// 从数据库加载适温配置
+pub async fn load_temper_config<C: ConnectionTrait>(conn: &C) -> Result<TemperConfig, AppError> {
     let configs = Config::find()
         .filter(crate::models::system_config::Column::ConfigGroup.eq("temp"))
-        .all(db)
+        .all(conn)
         .await?;
 
     let mut config_map: HashMap<String, String> = HashMap::new();
@@ -79,6 +114,51 @@ pub async fn load_temper_config() -> Result<TemperConfig, AppError> {
     if let Some(v) = config_map.get("winter_months") {
         tc.winter_months = parse_month_list(v);
     }
+    if let Some(v) = config_map.get("model") {
+        if v == TEMPER_MODEL_COOLING {
+            tc.model = TEMPER_MODEL_COOLING.to_string();
+        }
+    }
+    if let Some(v) = config_map.get("target_temp").and_then(|v| v.parse().ok()) {
+        tc.cooling.target_temp = v;
+    }
+    if let Some(v) = config_map
+        .get("default_coiling_temp")
+        .and_then(|v| v.parse().ok())
+    {
+        tc.cooling.default_coiling_temp = v;
+    }
+    if let Some(v) = config_map
+        .get("heat_transfer_coeff")
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v > 0.0)
+    {
+        tc.cooling.heat_transfer_coeff = v;
+    }
+    if let Some(v) = config_map
+        .get("max_cooling_days")
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+    {
+        tc.cooling.max_days = v;
+    }
+    if let Some(v) = config_map.get("ambient_monthly") {
+        let monthly = parse_ambient_monthly(v);
+        if monthly.len() == 12 {
+            tc.ambient_monthly = monthly;
+        }
+    }
+
+    if tc.uses_cooling_model() {
+        let rows = crate::models::ambient_temperature::Entity::find()
+            .all(conn)
+            .await?;
+        for row in rows {
+            if let Ok(date) = NaiveDate::parse_from_str(&row.record_date, "%Y-%m-%d") {
+                tc.ambient_daily.insert(date, row.avg_temp);
+            }
+        }
+    }
 
     Ok(tc)
 }
@@ -107,6 +187,51 @@ pub fn calculate_temp_status_with_config(
     (status.to_string(), wait_days)
 }
 
+/// 按配置的适温模型计算单个材料的适温状态
+///
+/// days 模型仅依赖卷取时间；cooling 模型结合卷取温度、卷重、厚度、宽度和环境温度。
+pub fn calculate_temp_status_for_material(
+    mat: &material::Model,
+    config: &TemperConfig,
+) -> (String, i32) {
+    if !config.enabled || !config.uses_cooling_model() {
+        return calculate_temp_status_with_config(&mat.coiling_time, config);
+    }
+
+    let now = Utc::now();
+    let wait_days = now.signed_duration_since(mat.coiling_time).num_days() as i32;
+    let ready = cooling_ready_time(mat, config) <= now;
+    let status = if ready { "ready" } else { "waiting" };
+    (status.to_string(), wait_days)
+}
+
+/// 冷却模型下的适温时刻（不可达时按 temp_calc::effective_ready_time 取模拟截止时刻）
+pub fn cooling_ready_time(mat: &material::Model, config: &TemperConfig) -> DateTime<Utc> {
+    temp_calc::effective_ready_time(
+        &mat.coiling_time,
+        estimate_cooling_ready_time(mat, config),
+        &config.cooling,
+    )
+}
+
+/// 冷却模型估算的适温时刻；超出模拟天数仍未适温返回 None
+pub fn estimate_cooling_ready_time(
+    mat: &material::Model,
+    config: &TemperConfig,
+) -> Option<DateTime<Utc>> {
+    let coil = CoilThermalInput {
+        coiling_temp: mat
+            .coiling_temp
+            .unwrap_or(config.cooling.default_coiling_temp),
+        weight: mat.weight,
+        thickness: mat.thickness,
+        width: mat.width,
+    };
+    temp_calc::estimate_ready_time(&mat.coiling_time, &coil, &config.cooling, |d| {
+        config.ambient_temp(d)
+    })
+}
+
 /// 根据配置获取当前月份的适温阈值天数
 pub(crate) fn get_threshold_from_config(month: u32, config: &TemperConfig) -> i32 {
     if config.spring_months.contains(&month) {
@@ -124,21 +249,28 @@ pub(crate) fn get_threshold_from_config(month: u32, config: &TemperConfig) -> i3
 }
 
 /// 批量刷新所有材料的适温状态
-pub async fn refresh_all_temper_status() -> Result<(usize, usize, usize), AppError> {
+pub async fn refresh_all_temper_status<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<(usize, usize, usize), AppError> {
+    let materials = material::Entity::find().all(conn).await?;
+    let (tempered, waiting) = refresh_temper_status_of(conn, &materials).await?;
+    Ok((materials.len(), tempered, waiting))
+}
+
+/// 刷新指定材料的适温状态，返回 (已适温, 待温) 数量
+pub async fn refresh_temper_status_of<C: ConnectionTrait>(
+    conn: &C,
+    materials: &[material::Model],
+) -> Result<(usize, usize), AppError> {
     use crate::models::material::Entity as Material;
     use sea_orm::prelude::Expr;
 
-    let db = get_db();
-    let config = load_temper_config().await?;
-    let materials = Material::find().all(db).await?;
-
-    let total = materials.len();
+    let config = load_temper_config(conn).await?;
     let mut tempered = 0usize;
     let mut waiting = 0usize;
 
-    for material in &materials {
-        let (status, wait_days) =
-            calculate_temp_status_with_config(&material.coiling_time, &config);
+    for material in materials {
+        let (status, wait_days) = calculate_temp_status_for_material(material, &config);
         let is_temp = status == "ready";
 
         if is_temp {
@@ -165,21 +297,28 @@ pub async fn refresh_all_temper_status() -> Result<(usize, usize, usize), AppErr
                 Expr::current_timestamp().into(),
             )
             .filter(crate::models::material::Column::Id.eq(material.id))
-            .exec(db)
+            .exec(conn)
             .await?;
     }
 
-    Ok((total, tempered, waiting))
+    Ok((tempered, waiting))
 }
 
 /// 计算材料的预计适温日期（YYYY-MM-DD）。
-/// 如材料已适温，返回 None。
-pub fn calculate_ready_date(coiling_time: &DateTime<Utc>, config: &TemperConfig) -> Option<String> {
+/// 如材料已适温，返回 None；冷却模型不可达时按 cooling_ready_time 取模拟截止日期。
+pub fn calculate_ready_date(mat: &material::Model, config: &TemperConfig) -> Option<String> {
     if !config.enabled {
         return None; // 适温功能关闭，视为已适温
     }
     let now = Utc::now();
-    let wait_days = now.signed_duration_since(*coiling_time).num_days() as i32;
+    if config.uses_cooling_model() {
+        let ready_at = cooling_ready_time(mat, config);
+        if ready_at <= now {
+            return None;
+        }
+        return Some(ready_at.format("%Y-%m-%d").to_string());
+    }
+    let wait_days = now.signed_duration_since(mat.coiling_time).num_days() as i32;
     let current_month = now.month();
     let threshold = get_threshold_from_config(current_month, config);
     if wait_days >= threshold {
@@ -200,3 +339,11 @@ fn parse_month_list(s: &str) -> Vec<u32> {
         .filter(|m| *m >= 1 && *m <= 12)
         .collect()
 }
+
+/// 解析月均温度字符串 "0,3,9,..." → 12 个月的温度
+fn parse_ambient_monthly(s: &str) -> Vec<f64> {
+    s.split(',')
+        .filter_map(|m| m.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .collect()
+}
@@ -1 +1,787 @@
-// Undo service - Undo/Redo operations (Phase 4)
+//! 服务端撤销/重做
+//!
+//! 排程变更命令执行前后各取一次方案快照（排程项 + 方案元数据），只保存差异：
+//! before_state 存逆操作、after_state 存正操作，撤销/重做在单个事务内按固定阶段回放。
+//! 命令应在同一事务内完成 capture、变更与 record，多步变更因此只记录一条撤销项。
+
+use crate::models::{material, schedule_item, schedule_plan, undo_stack};
+use crate::AppError;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// 参与撤销的方案元数据字段（状态由审批流程管理，不随撤销回退）
+const PLAN_META_FIELDS: &[&str] = &[
+    "total_count",
+    "total_weight",
+    "roll_change_count",
+    "score_overall",
+    "score_sequence",
+    "score_delivery",
+    "score_efficiency",
+    "risk_count_high",
+    "risk_count_medium",
+    "risk_count_low",
+    "risk_summary",
+    "ignored_risks",
+    "strategy_id",
+    "strategy_revision_id",
+];
+
+/// 比较排程项时忽略的字段
+const ITEM_IGNORED_FIELDS: &[&str] = &["created_at", "updated_at"];
+
+/// 方案快照：排程项与方案元数据
+#[derive(Debug, Clone)]
+pub struct PlanSnapshot {
+    pub plan: schedule_plan::Model,
+    pub items: Vec<schedule_item::Model>,
+}
+
+/// 单行字段补丁（仅含变化的字段）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowPatch {
+    pub id: i32,
+    pub fields: Map<String, Value>,
+}
+
+/// 撤销/重做操作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum UndoOp {
+    /// 按原 id 重建排程项
+    InsertItems {
+        items: Vec<schedule_item::Model>,
+    },
+    DeleteItems {
+        ids: Vec<i32>,
+    },
+    PatchItems {
+        patches: Vec<RowPatch>,
+    },
+    PatchPlan {
+        fields: Map<String, Value>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoRedoResult {
+    pub action_type: String,
+    pub remaining: usize,
+}
+
+/// 读取方案快照
+pub async fn capture<C: ConnectionTrait>(conn: &C, plan_id: i32) -> Result<PlanSnapshot, AppError> {
+    let plan = schedule_plan::Entity::find_by_id(plan_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::PlanNotFound(plan_id))?;
+    let items = schedule_item::Entity::find()
+        .filter(schedule_item::Column::PlanId.eq(plan_id))
+        .order_by_asc(schedule_item::Column::Sequence)
+        .all(conn)
+        .await?;
+    Ok(PlanSnapshot { plan, items })
+}
+
+fn to_object<T: Serialize>(value: &T) -> Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// 两个 JSON 对象中取值不同的字段（取 to 的值）
+fn changed_fields(
+    from: &Map<String, Value>,
+    to: &Map<String, Value>,
+    keep: impl Fn(&str) -> bool,
+) -> Map<String, Value> {
+    to.iter()
+        .filter(|(k, v)| keep(k) && from.get(k.as_str()) != Some(*v))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// 计算把 from 变为 to 的操作序列
+pub fn diff_ops(from: &PlanSnapshot, to: &PlanSnapshot) -> Vec<UndoOp> {
+    let from_items: HashMap<i32, &schedule_item::Model> =
+        from.items.iter().map(|it| (it.id, it)).collect();
+    let to_ids: std::collections::HashSet<i32> = to.items.iter().map(|it| it.id).collect();
+
+    let deleted: Vec<i32> = from
+        .items
+        .iter()
+        .filter(|it| !to_ids.contains(&it.id))
+        .map(|it| it.id)
+        .collect();
+    let mut inserted = Vec::new();
+    let mut patches = Vec::new();
+    for item in &to.items {
+        match from_items.get(&item.id) {
+            None => inserted.push(item.clone()),
+            Some(old) => {
+                let fields = changed_fields(&to_object(*old), &to_object(item), |k| {
+                    !ITEM_IGNORED_FIELDS.contains(&k)
+                });
+                if !fields.is_empty() {
+                    patches.push(RowPatch {
+                        id: item.id,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    let plan_fields = changed_fields(&to_object(&from.plan), &to_object(&to.plan), |k| {
+        PLAN_META_FIELDS.contains(&k)
+    });
+
+    let mut ops = Vec::new();
+    if !deleted.is_empty() {
+        ops.push(UndoOp::DeleteItems { ids: deleted });
+    }
+    if !patches.is_empty() {
+        ops.push(UndoOp::PatchItems { patches });
+    }
+    if !inserted.is_empty() {
+        ops.push(UndoOp::InsertItems { items: inserted });
+    }
+    if !plan_fields.is_empty() {
+        ops.push(UndoOp::PatchPlan {
+            fields: plan_fields,
+        });
+    }
+    ops
+}
+
+/// 对比操作前快照与当前状态，记录一条撤销项；无变化时不记录
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    action_type: &str,
+    description: String,
+    before: &PlanSnapshot,
+) -> Result<Option<i32>, AppError> {
+    let after = capture(conn, plan_id).await?;
+    let forward = diff_ops(before, &after);
+    if forward.is_empty() {
+        return Ok(None);
+    }
+    let inverse = diff_ops(&after, before);
+    let to_json = |ops: &Vec<UndoOp>| {
+        serde_json::to_string(ops)
+            .map_err(|e| AppError::Internal(format!("撤销操作序列化失败: {}", e)))
+    };
+
+    // 新操作使已撤销的记录失效
+    undo_stack::Entity::delete_many()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .filter(undo_stack::Column::IsUndone.eq(true))
+        .exec(conn)
+        .await?;
+    let entry = undo_stack::ActiveModel {
+        plan_id: Set(plan_id),
+        action_type: Set(action_type.to_string()),
+        before_state: Set(to_json(&inverse)?),
+        after_state: Set(to_json(&forward)?),
+        is_undone: Set(Some(false)),
+        description: Set(Some(description)),
+        created_at: Set(Some(chrono::Utc::now())),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    trim_stack(conn, plan_id).await?;
+    Ok(Some(entry.id))
+}
+
+/// 依据系统配置限制每个方案的撤销栈长度（默认 50）
+async fn trim_stack<C: ConnectionTrait>(conn: &C, plan_id: i32) -> Result<(), AppError> {
+    let max_steps = crate::models::system_config::Entity::find()
+        .filter(crate::models::system_config::Column::ConfigGroup.eq("undo"))
+        .filter(crate::models::system_config::Column::ConfigKey.eq("max_steps"))
+        .one(conn)
+        .await?
+        .and_then(|row| row.config_value.parse::<usize>().ok())
+        .unwrap_or(50)
+        .clamp(1, 500);
+    let stale_ids: Vec<i32> = undo_stack::Entity::find()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .order_by_desc(undo_stack::Column::Id)
+        .all(conn)
+        .await?
+        .into_iter()
+        .skip(max_steps)
+        .map(|row| row.id)
+        .collect();
+    if !stale_ids.is_empty() {
+        undo_stack::Entity::delete_many()
+            .filter(undo_stack::Column::Id.is_in(stale_ids))
+            .exec(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+fn parse_ops(raw: &str) -> Result<Vec<UndoOp>, AppError> {
+    serde_json::from_str(raw).map_err(|e| AppError::Internal(format!("撤销记录解析失败: {}", e)))
+}
+
+/// 把补丁字段合并到记录上
+fn merge_fields<T>(value: &T, fields: &Map<String, Value>) -> Result<T, AppError>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    let mut merged = to_object(value);
+    for (k, v) in fields {
+        merged.insert(k.clone(), v.clone());
+    }
+    serde_json::from_value(Value::Object(merged))
+        .map_err(|e| AppError::Internal(format!("撤销补丁应用失败: {}", e)))
+}
+
+/// 按固定阶段回放操作：删除 → 补丁行序号暂置为负 → 插入 → 写回补丁 → 方案元数据
+///
+/// 排程项有 (plan_id, sequence) 唯一约束，先把待改行的序号移开，避免中间状态冲突。
+pub async fn apply_ops<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    ops: &[UndoOp],
+) -> Result<(), AppError> {
+    use sea_orm::prelude::Expr;
+
+    for op in ops {
+        if let UndoOp::DeleteItems { ids } = op {
+            schedule_item::Entity::delete_many()
+                .filter(schedule_item::Column::PlanId.eq(plan_id))
+                .filter(schedule_item::Column::Id.is_in(ids.clone()))
+                .exec(conn)
+                .await?;
+        }
+    }
+    let patches: Vec<&RowPatch> = ops
+        .iter()
+        .filter_map(|op| match op {
+            UndoOp::PatchItems { patches } => Some(patches.iter()),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    for patch in &patches {
+        if patch.fields.contains_key("sequence") {
+            schedule_item::Entity::update_many()
+                .col_expr(schedule_item::Column::Sequence, Expr::value(-patch.id))
+                .filter(schedule_item::Column::Id.eq(patch.id))
+                .exec(conn)
+                .await?;
+        }
+    }
+    for op in ops {
+        if let UndoOp::InsertItems { items } = op {
+            for item in items {
+                let mut active = item.clone().into_active_model().reset_all();
+                active.plan_id = Set(plan_id);
+                active.insert(conn).await?;
+            }
+        }
+    }
+    for patch in &patches {
+        let current = schedule_item::Entity::find_by_id(patch.id)
+            .one(conn)
+            .await?
+            .ok_or_else(|| AppError::Internal(format!("排程项 {} 不存在，无法回放", patch.id)))?;
+        merge_fields(&current, &patch.fields)?
+            .into_active_model()
+            .reset_all()
+            .update(conn)
+            .await?;
+    }
+    for op in ops {
+        if let UndoOp::PatchPlan { fields } = op {
+            let plan = schedule_plan::Entity::find_by_id(plan_id)
+                .one(conn)
+                .await?
+                .ok_or(AppError::PlanNotFound(plan_id))?;
+            merge_fields(&plan, fields)?
+                .into_active_model()
+                .reset_all()
+                .update(conn)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn count_entries<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    undone: bool,
+) -> Result<usize, AppError> {
+    Ok(undo_stack::Entity::find()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .filter(undo_stack::Column::IsUndone.eq(undone))
+        .count(conn)
+        .await? as usize)
+}
+
+/// 撤销最近一条操作
+pub async fn undo(db: &DatabaseConnection, plan_id: i32) -> Result<UndoRedoResult, AppError> {
+    let latest = undo_stack::Entity::find()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .filter(undo_stack::Column::IsUndone.eq(false))
+        .order_by_desc(undo_stack::Column::Id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NothingToUndo)?;
+    let action_type = latest.action_type.clone();
+
+    let tx = db.begin().await?;
+    apply_ops(&tx, plan_id, &parse_ops(&latest.before_state)?).await?;
+    let mut active: undo_stack::ActiveModel = latest.into();
+    active.is_undone = Set(Some(true));
+    active.update(&tx).await?;
+    tx.commit().await?;
+
+    Ok(UndoRedoResult {
+        action_type,
+        remaining: count_entries(db, plan_id, false).await?,
+    })
+}
+
+/// 重做最早一条已撤销操作
+pub async fn redo(db: &DatabaseConnection, plan_id: i32) -> Result<UndoRedoResult, AppError> {
+    let earliest = undo_stack::Entity::find()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .filter(undo_stack::Column::IsUndone.eq(true))
+        .order_by_asc(undo_stack::Column::Id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NothingToRedo)?;
+    let action_type = earliest.action_type.clone();
+
+    let tx = db.begin().await?;
+    apply_ops(&tx, plan_id, &parse_ops(&earliest.after_state)?).await?;
+    let mut active: undo_stack::ActiveModel = earliest.into();
+    active.is_undone = Set(Some(false));
+    active.update(&tx).await?;
+    tx.commit().await?;
+
+    Ok(UndoRedoResult {
+        action_type,
+        remaining: count_entries(db, plan_id, true).await?,
+    })
+}
+
+/// 单条撤销记录的变更摘要
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UndoDiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    /// 序号发生变化的排程项
+    pub moved: usize,
+    /// 序号以外字段变化的排程项（锁定、班次等）
+    pub updated: usize,
+    pub plan_changed: bool,
+}
+
+/// 撤销历史条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoHistoryEntry {
+    pub id: i32,
+    pub action_type: String,
+    pub description: String,
+    pub is_undone: bool,
+    pub created_at: Option<String>,
+    pub summary: UndoDiffSummary,
+}
+
+/// 某条撤销记录处的方案预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanStatePreview {
+    /// 实际定位到的记录（None 表示全部撤销后的初始状态）
+    pub entry_id: Option<i32>,
+    pub plan: schedule_plan::Model,
+    pub items: Vec<schedule_item::Model>,
+    /// 材料 id -> 卷号，供预览展示
+    pub coil_ids: HashMap<i32, String>,
+}
+
+/// 统计正向操作的变更规模
+pub fn summarize(ops: &[UndoOp]) -> UndoDiffSummary {
+    let mut summary = UndoDiffSummary::default();
+    for op in ops {
+        match op {
+            UndoOp::InsertItems { items } => summary.added += items.len(),
+            UndoOp::DeleteItems { ids } => summary.removed += ids.len(),
+            UndoOp::PatchItems { patches } => {
+                for patch in patches {
+                    if patch.fields.contains_key("sequence") {
+                        summary.moved += 1;
+                    } else {
+                        summary.updated += 1;
+                    }
+                }
+            }
+            UndoOp::PatchPlan { .. } => summary.plan_changed = true,
+        }
+    }
+    summary
+}
+
+/// 在内存中对快照回放操作
+pub fn apply_ops_to_snapshot(snapshot: &mut PlanSnapshot, ops: &[UndoOp]) -> Result<(), AppError> {
+    for op in ops {
+        match op {
+            UndoOp::DeleteItems { ids } => snapshot.items.retain(|it| !ids.contains(&it.id)),
+            UndoOp::InsertItems { items } => snapshot.items.extend(items.iter().cloned()),
+            UndoOp::PatchItems { patches } => {
+                for patch in patches {
+                    if let Some(item) = snapshot.items.iter_mut().find(|it| it.id == patch.id) {
+                        *item = merge_fields(item, &patch.fields)?;
+                    }
+                }
+            }
+            UndoOp::PatchPlan { fields } => snapshot.plan = merge_fields(&snapshot.plan, fields)?,
+        }
+    }
+    snapshot.items.sort_by_key(|it| it.sequence);
+    Ok(())
+}
+
+/// 方案撤销历史（按记录先后）
+pub async fn list_history<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+) -> Result<Vec<UndoHistoryEntry>, AppError> {
+    let entries = undo_stack::Entity::find()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .order_by_asc(undo_stack::Column::Id)
+        .all(conn)
+        .await?;
+    entries
+        .into_iter()
+        .map(|entry| {
+            Ok(UndoHistoryEntry {
+                summary: summarize(&parse_ops(&entry.after_state)?),
+                id: entry.id,
+                description: entry
+                    .description
+                    .unwrap_or_else(|| entry.action_type.clone()),
+                action_type: entry.action_type,
+                is_undone: entry.is_undone.unwrap_or(false),
+                created_at: entry.created_at.map(|d| d.to_rfc3339()),
+            })
+        })
+        .collect()
+}
+
+/// 回放步骤：记录及方向（true 为重做）
+type Step = (undo_stack::Model, bool);
+
+/// 计算从当前状态到达目标记录所需的步骤
+async fn steps_to<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    target: Option<i32>,
+) -> Result<(Option<i32>, Vec<Step>), AppError> {
+    let entries = undo_stack::Entity::find()
+        .filter(undo_stack::Column::PlanId.eq(plan_id))
+        .order_by_asc(undo_stack::Column::Id)
+        .all(conn)
+        .await?;
+    let applied_until = match target {
+        None => 0,
+        Some(id) => {
+            let end = entries.iter().position(|e| e.id == id).ok_or_else(|| {
+                AppError::InvalidInput(format!("撤销记录 {} 不属于方案 {}", id, plan_id))
+            })?;
+            end + 1
+        }
+    };
+    let resolved = applied_until.checked_sub(1).map(|idx| entries[idx].id);
+
+    let mut steps: Vec<Step> = Vec::new();
+    for entry in entries[applied_until..].iter().rev() {
+        if entry.is_undone != Some(true) {
+            steps.push((entry.clone(), false));
+        }
+    }
+    for entry in &entries[..applied_until] {
+        if entry.is_undone == Some(true) {
+            steps.push((entry.clone(), true));
+        }
+    }
+    Ok((resolved, steps))
+}
+
+fn step_ops(step: &Step) -> Result<Vec<UndoOp>, AppError> {
+    let (entry, redo) = step;
+    parse_ops(if *redo {
+        &entry.after_state
+    } else {
+        &entry.before_state
+    })
+}
+
+/// 预览方案在某条记录执行后的状态（不落库）
+pub async fn preview_at<C: ConnectionTrait>(
+    conn: &C,
+    plan_id: i32,
+    target: Option<i32>,
+) -> Result<PlanStatePreview, AppError> {
+    let (entry_id, steps) = steps_to(conn, plan_id, target).await?;
+    let mut snapshot = capture(conn, plan_id).await?;
+    for step in &steps {
+        apply_ops_to_snapshot(&mut snapshot, &step_ops(step)?)?;
+    }
+    let material_ids: Vec<i32> = snapshot.items.iter().map(|it| it.material_id).collect();
+    let coil_ids = material::Entity::find()
+        .filter(material::Column::Id.is_in(material_ids))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|m| (m.id, m.coil_id))
+        .collect();
+    Ok(PlanStatePreview {
+        entry_id,
+        plan: snapshot.plan,
+        items: snapshot.items,
+        coil_ids,
+    })
+}
+
+/// 直接跳转到某条记录执行后的状态，返回回放的记录数
+pub async fn jump_to(
+    db: &DatabaseConnection,
+    plan_id: i32,
+    target: Option<i32>,
+) -> Result<usize, AppError> {
+    let tx = db.begin().await?;
+    let (_, steps) = steps_to(&tx, plan_id, target).await?;
+    for step in &steps {
+        apply_ops(&tx, plan_id, &step_ops(step)?).await?;
+        let (entry, redo) = step;
+        let mut active: undo_stack::ActiveModel = entry.clone().into();
+        active.is_undone = Set(Some(!*redo));
+        active.update(&tx).await?;
+    }
+    tx.commit().await?;
+    Ok(steps.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i32, material_id: i32, sequence: i32) -> schedule_item::Model {
+        schedule_item::Model {
+            id,
+            plan_id: 1,
+            material_id,
+            sequence,
+            shift_date: "2026-02-13".to_string(),
+            shift_no: 1,
+            shift_type: "day".to_string(),
+            planned_start: None,
+            planned_end: None,
+            cumulative_weight: None,
+            is_roll_change: Some(false),
+            is_locked: Some(false),
+            lock_reason: None,
+            risk_flags: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn diff_ops_only_keeps_changed_rows_and_fields() {
+        let plan: schedule_plan::Model = serde_json::from_value(serde_json::json!({
+            "id": 1, "plan_no": "P1", "name": "P1", "period_type": "daily",
+            "start_date": "2026-02-13", "end_date": "2026-02-13", "total_count": 3
+        }))
+        .unwrap();
+        let before = PlanSnapshot {
+            plan: plan.clone(),
+            items: vec![item(1, 11, 1), item(2, 12, 2), item(3, 13, 3)],
+        };
+        let mut moved = item(3, 13, 2);
+        moved.updated_at = Some(chrono::Utc::now());
+        let after = PlanSnapshot {
+            plan: schedule_plan::Model {
+                total_count: Some(3),
+                status: Some("saved".to_string()),
+                ..plan
+            },
+            items: vec![item(1, 11, 1), moved, item(4, 14, 3)],
+        };
+
+        let forward = diff_ops(&before, &after);
+        assert_eq!(forward.len(), 3);
+        assert_eq!(forward[0], UndoOp::DeleteItems { ids: vec![2] });
+        let UndoOp::PatchItems { patches } = &forward[1] else {
+            panic!("应生成补丁操作");
+        };
+        assert_eq!(patches.len(), 1);
+        assert_eq!(
+            patches[0].fields.keys().collect::<Vec<_>>(),
+            vec!["sequence"]
+        );
+        assert!(matches!(&forward[2], UndoOp::InsertItems { items } if items[0].id == 4));
+
+        // 逆操作恢复被删除的行；方案状态不参与撤销
+        let inverse = diff_ops(&after, &before);
+        assert!(inverse
+            .iter()
+            .any(|op| matches!(op, UndoOp::InsertItems { items } if items[0].id == 2)));
+        assert!(!inverse
+            .iter()
+            .any(|op| matches!(op, UndoOp::PatchPlan { .. })));
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_replay_recorded_changes() {
+        use crate::db::get_db;
+        use sea_orm::prelude::Expr;
+
+        let seed = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let db_path = std::env::temp_dir().join(format!("spm_undo_{}.db", seed));
+        crate::db::init_database_for_test(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .expect("初始化测试数据库失败");
+        let db = get_db();
+
+        let plan = schedule_plan::ActiveModel {
+            plan_no: Set(format!("U-{}", seed)),
+            name: Set("撤销测试".to_string()),
+            period_type: Set("daily".to_string()),
+            start_date: Set("2026-02-13".to_string()),
+            end_date: Set("2026-02-13".to_string()),
+            status: Set(Some("draft".to_string())),
+            total_count: Set(Some(3)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        let mut item_ids = Vec::new();
+        for seq in 1..=3 {
+            let coil = material::ActiveModel {
+                coil_id: Set(format!("U{}-{}", seed, seq)),
+                steel_grade: Set("Q235".to_string()),
+                thickness: Set(2.5),
+                width: Set(1250.0),
+                weight: Set(20.0),
+                coiling_time: Set(chrono::Utc::now()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .unwrap();
+            let row = schedule_item::ActiveModel {
+                plan_id: Set(plan.id),
+                material_id: Set(coil.id),
+                sequence: Set(seq),
+                shift_date: Set("2026-02-13".to_string()),
+                shift_no: Set(1),
+                shift_type: Set("day".to_string()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .unwrap();
+            item_ids.push(row.id);
+        }
+        let order = |snapshot: &PlanSnapshot| {
+            snapshot
+                .items
+                .iter()
+                .map(|it| (it.id, it.sequence))
+                .collect::<Vec<_>>()
+        };
+        let original = capture(db, plan.id).await.unwrap();
+
+        // 两次操作：移除第 2 项并更新方案统计，再把第 3 项前移
+        let before = capture(db, plan.id).await.unwrap();
+        schedule_item::Entity::delete_by_id(item_ids[1])
+            .exec(db)
+            .await
+            .unwrap();
+        schedule_plan::Entity::update_many()
+            .col_expr(schedule_plan::Column::TotalCount, Expr::value(2))
+            .filter(schedule_plan::Column::Id.eq(plan.id))
+            .exec(db)
+            .await
+            .unwrap();
+        record(db, plan.id, "remove_material", "移除".to_string(), &before)
+            .await
+            .unwrap()
+            .expect("应记录移除操作");
+        let before = capture(db, plan.id).await.unwrap();
+        schedule_item::Entity::update_many()
+            .col_expr(schedule_item::Column::Sequence, Expr::value(2))
+            .filter(schedule_item::Column::Id.eq(item_ids[2]))
+            .exec(db)
+            .await
+            .unwrap();
+        record(db, plan.id, "move_item", "移动".to_string(), &before)
+            .await
+            .unwrap()
+            .expect("应记录移动操作");
+        let edited = capture(db, plan.id).await.unwrap();
+        assert_eq!(order(&edited), vec![(item_ids[0], 1), (item_ids[2], 2)]);
+
+        assert_eq!(undo(db, plan.id).await.unwrap().remaining, 1);
+        let undone = undo(db, plan.id).await.unwrap();
+        assert_eq!(undone.action_type, "remove_material");
+        assert_eq!(undone.remaining, 0);
+        let restored = capture(db, plan.id).await.unwrap();
+        assert_eq!(order(&restored), order(&original));
+        assert_eq!(restored.plan.total_count, Some(3));
+        assert!(matches!(
+            undo(db, plan.id).await,
+            Err(AppError::NothingToUndo)
+        ));
+
+        assert_eq!(redo(db, plan.id).await.unwrap().remaining, 1);
+        let redone = redo(db, plan.id).await.unwrap();
+        assert_eq!(redone.remaining, 0);
+        let replayed = capture(db, plan.id).await.unwrap();
+        assert_eq!(order(&replayed), order(&edited));
+        assert_eq!(replayed.plan.total_count, Some(2));
+
+        // 历史摘要、预览与跳转
+        let history = list_history(db, plan.id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].summary.removed, 1);
+        assert!(history[0].summary.plan_changed);
+        assert_eq!(history[1].summary.moved, 1);
+        let preview = preview_at(db, plan.id, Some(history[0].id)).await.unwrap();
+        assert_eq!(preview.entry_id, Some(history[0].id));
+        assert_eq!(
+            preview.items.iter().map(|it| it.id).collect::<Vec<_>>(),
+            vec![item_ids[0], item_ids[2]]
+        );
+        let initial = preview_at(db, plan.id, None).await.unwrap();
+        assert_eq!(
+            initial.items.iter().map(|it| it.id).collect::<Vec<_>>(),
+            item_ids
+        );
+        assert_eq!(
+            initial.coil_ids.get(&initial.items[1].material_id),
+            Some(&format!("U{}-2", seed))
+        );
+        assert_eq!(jump_to(db, plan.id, None).await.unwrap(), 2);
+        assert_eq!(
+            order(&capture(db, plan.id).await.unwrap()),
+            order(&original)
+        );
+        assert_eq!(jump_to(db, plan.id, Some(history[1].id)).await.unwrap(), 2);
+        assert_eq!(order(&capture(db, plan.id).await.unwrap()), order(&edited));
+    }
+}
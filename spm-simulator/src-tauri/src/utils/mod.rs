@@ -2,3 +2,4 @@ pub mod datetime;
 pub mod log;
 pub mod season;
 pub mod temperature;
+pub mod watch_dir;
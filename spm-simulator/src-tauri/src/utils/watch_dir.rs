@@ -0,0 +1,86 @@
+//! 目录轮询共用工具 — 导入目录监控与 MES 回执收件箱共用的配置读取与文件归档
+
+use crate::models::system_config;
+use crate::AppError;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use std::path::{Path, PathBuf};
+
+/// 解析布尔型配置值（1/true/yes/on，不区分大小写）
+pub fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// 读取 system_config 指定分组的全部 (config_key, 去首尾空白的 config_value)
+pub async fn load_config_group(group: &str) -> Result<Vec<(String, String)>, AppError> {
+    let rows = system_config::Entity::find()
+        .filter(system_config::Column::ConfigGroup.eq(group))
+        .all(crate::db::get_db())
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.config_key, row.config_value.trim().to_string()))
+        .collect())
+}
+
+/// 移动文件到子目录（不存在时创建），重名时在文件名前追加毫秒时间戳
+pub fn move_into(path: &Path, dir: &Path) -> Result<PathBuf, AppError> {
+    std::fs::create_dir_all(dir)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut target = dir.join(&name);
+    let mut seq = 0u32;
+    while target.exists() {
+        let stamp = chrono::Local::now().format("%Y%m%d%H%M%S%3f");
+        target = if seq == 0 {
+            dir.join(format!("{}_{}", stamp, name))
+        } else {
+            dir.join(format!("{}_{}_{}", stamp, seq, name))
+        };
+        seq += 1;
+    }
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_into_avoids_overwriting_existing_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "spm_watch_dir_test_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let archive = dir.join("archive");
+        std::fs::create_dir_all(&archive).unwrap();
+        std::fs::write(archive.join("a.csv"), "old").unwrap();
+        for content in ["new", "newer"] {
+            let src = dir.join("a.csv");
+            std::fs::write(&src, content).unwrap();
+            let moved = move_into(&src, &archive).unwrap();
+            assert!(!src.exists());
+            assert_ne!(moved, archive.join("a.csv"));
+            assert!(moved.to_string_lossy().ends_with("_a.csv"));
+            assert_eq!(std::fs::read_to_string(&moved).unwrap(), content);
+        }
+        assert_eq!(
+            std::fs::read_to_string(archive.join("a.csv")).unwrap(),
+            "old"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parse_bool_accepts_common_truthy_values() {
+        assert!(parse_bool(" TRUE "));
+        assert!(parse_bool("on"));
+        assert!(!parse_bool("0"));
+        assert!(!parse_bool(""));
+    }
+}
@@ -5,14 +5,18 @@
  * - 排程状态标签/颜色映射（方案级，派生）
  */
 
-// ─── 材料状态 (pending / completed / frozen) ───
+// ─── 材料状态 (pending / planned / in_production / completed / frozen) ───
 export const materialStatusLabelMap: Record<string, string> = {
   pending: '待排',
+  planned: '已锁定',
+  in_production: '生产中',
   completed: '完成',
   frozen: '冻结',
 };
 export const materialStatusColorMap: Record<string, string> = {
   pending: 'default',
+  planned: 'processing',
+  in_production: 'blue',
   completed: 'success',
   frozen: 'warning',
 };
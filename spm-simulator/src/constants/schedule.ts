@@ -8,20 +8,32 @@
  * - 交期属性代码 / 合同属性代码
  */
 
-// ─── 方案状态 (draft / saved / confirmed / archived) ───
+// ─── 方案状态 (draft / saved → submitted → approved / rejected → confirmed → executing → archived) ───
 export const planStatusLabelMap: Record<string, string> = {
   draft: '草稿',
   saved: '已保存',
+  submitted: '待审批',
+  approved: '已审批',
+  rejected: '已驳回',
   confirmed: '已确认',
+  executing: '执行中',
   archived: '已归档',
 };
 export const planStatusColorMap: Record<string, string> = {
   draft: 'default',
   saved: 'processing',
+  submitted: 'gold',
+  approved: 'cyan',
+  rejected: 'error',
   confirmed: 'success',
+  executing: 'blue',
   archived: 'warning',
 };
 
+/** 允许修改排程项的方案状态 */
+export const isPlanEditable = (status?: string) =>
+  !status || status === 'draft' || status === 'saved' || status === 'rejected';
+
 // ─── 约束类型标签 ───
 export const constraintLabelMap: Record<string, string> = {
   // 硬约束
@@ -5,12 +5,12 @@ import { SwapOutlined } from '@ant-design/icons';
 export interface ComparisonSelectorProps {
   planAId: number | null;
   planBId: number | null;
-  planCId: number | null;
+  extraPlanIds: number[];
   planOptions: { value: number; label: string }[];
   loading: boolean;
   onPlanAChange: (v: number) => void;
   onPlanBChange: (v: number) => void;
-  onPlanCChange: (v: number | null) => void;
+  onExtraPlansChange: (v: number[]) => void;
   onSwapAB: () => void;
   onCompare: () => void;
 }
@@ -18,17 +18,18 @@ export interface ComparisonSelectorProps {
 export default memo(function ComparisonSelector({
   planAId,
   planBId,
-  planCId,
+  extraPlanIds,
   planOptions,
   loading,
   onPlanAChange,
   onPlanBChange,
-  onPlanCChange,
+  onExtraPlansChange,
   onSwapAB,
   onCompare,
 }: ComparisonSelectorProps) {
   const filterOption = (input: string, option?: { label: string }) =>
     (option?.label as string)?.toLowerCase().includes(input.toLowerCase());
+  const extraOptions = planOptions.filter((o) => o.value !== planAId && o.value !== planBId);
 
   return (
     <Card size="small" style={{ marginBottom: 12 }}>
@@ -60,14 +61,16 @@ export default memo(function ComparisonSelector({
           showSearch
           filterOption={filterOption}
         />
-        <span style={{ fontWeight: 500 }}>方案 C(可选):</span>
+        <span style={{ fontWeight: 500 }}>更多方案(可选):</span>
         <Select
+          mode="multiple"
           allowClear
-          style={{ width: 280 }}
-          value={planCId ?? undefined}
-          onChange={(v) => onPlanCChange(v ?? null)}
-          placeholder="选择方案 C（可选）"
-          options={planOptions}
+          maxTagCount="responsive"
+          style={{ minWidth: 280, maxWidth: 560 }}
+          value={extraPlanIds.filter((id) => id !== planAId && id !== planBId)}
+          onChange={onExtraPlansChange}
+          placeholder="追加方案进行多方案对比"
+          options={extraOptions}
           showSearch
           filterOption={filterOption}
         />
@@ -4,16 +4,17 @@ import type { TableColumnsType } from 'antd';
 import { DownloadOutlined } from '@ant-design/icons';
 import type * as EChartsCore from 'echarts/core';
 import type { MultiPlanComparisonResult, SequenceChangeItem } from '../../types/schedule';
-import type { ThreeModeSequencePair } from './types';
+import type { MultiModeSequencePair } from './types';
 import DeferredEChart from '../../components/DeferredEChart';
 import { ScoreSideCard } from './constants';
+import PairAnalysisPanel from './PairAnalysisPanel';
 
-export interface ThreeModeComparisonProps {
+export interface MultiModeComparisonProps {
   echarts: typeof EChartsCore;
   multiResult: MultiPlanComparisonResult;
   radarOptionMulti: unknown;
   metricsBarOptionMulti: unknown;
-  threeModeRecommendation: { recommendedId: number; reason: string } | null;
+  multiModeRecommendation: { recommendedId: number; reason: string } | null;
   multiMetricRows: Record<string, string | number>[];
   multiMetricColumns: TableColumnsType<Record<string, string | number>>;
   overlapData: {
@@ -30,54 +31,59 @@ export interface ThreeModeComparisonProps {
     only_a_count: number;
     only_b_count: number;
   }>;
-  threeModePairs: ThreeModeSequencePair[];
-  selectedThreeModePair: ThreeModeSequencePair | null;
-  selectedThreeModeAvgMove: string;
-  threeModeSequenceColumns: TableColumnsType<SequenceChangeItem>;
-  onThreeModePairKeyChange: (key: string) => void;
+  multiModePairs: MultiModeSequencePair[];
+  selectedMultiModePair: MultiModeSequencePair | null;
+  selectedMultiModeAvgMove: string;
+  multiModeSequenceColumns: TableColumnsType<SequenceChangeItem>;
+  onMultiModePairKeyChange: (key: string) => void;
   exportingSequence: boolean;
   onExportSequence: (planA: number, planB: number, label: string, format: 'excel' | 'csv') => void;
   onConfirmPlan?: (planId: number) => void;
+  onExecutePlan?: (planId: number) => void;
   onArchivePlan?: (planId: number) => void;
 }
 
-export default memo(function ThreeModeComparison({
+export default memo(function MultiModeComparison({
   echarts,
   multiResult,
   radarOptionMulti,
   metricsBarOptionMulti,
-  threeModeRecommendation,
+  multiModeRecommendation,
   multiMetricRows,
   multiMetricColumns,
   overlapData,
   overlapColumns,
-  threeModePairs,
-  selectedThreeModePair,
-  selectedThreeModeAvgMove,
-  threeModeSequenceColumns,
-  onThreeModePairKeyChange,
+  multiModePairs,
+  selectedMultiModePair,
+  selectedMultiModeAvgMove,
+  multiModeSequenceColumns,
+  onMultiModePairKeyChange,
   exportingSequence,
   onExportSequence,
   onConfirmPlan,
+  onExecutePlan,
   onArchivePlan,
-}: ThreeModeComparisonProps) {
-  const enableThreeModeSequenceVirtual = (selectedThreeModePair?.changes.length ?? 0) >= 200;
+}: MultiModeComparisonProps) {
+  // 每行最多四张方案卡片，更多方案自动换行
+  const sideSpan = Math.max(24 / multiResult.plans.length, 6);
+  const enableMultiModeSequenceVirtual = (selectedMultiModePair?.changes.length ?? 0) >= 200;
 
   return (
     <>
-      <Row gutter={12} style={{ marginBottom: 12 }}>
+      <Row gutter={12}>
         {multiResult.plans.map((side) => (
-          <Col span={8} key={side.plan_id}>
+          <Col span={sideSpan} key={side.plan_id} style={{ marginBottom: 12 }}>
             <Card size="small" styles={{ body: { padding: 12 } }}>
               <ScoreSideCard
                 side={side}
-                recommended={threeModeRecommendation?.recommendedId === side.plan_id}
+                recommended={multiModeRecommendation?.recommendedId === side.plan_id}
                 reason={
-                  threeModeRecommendation?.recommendedId === side.plan_id
-                    ? threeModeRecommendation.reason
+                  multiModeRecommendation?.recommendedId === side.plan_id
+                    ? multiModeRecommendation.reason
                     : undefined
                 }
                 onConfirm={onConfirmPlan}
+                onExecute={onExecutePlan}
                 onArchive={onArchivePlan}
               />
             </Card>
@@ -88,7 +94,7 @@ export default memo(function ThreeModeComparison({
       <Row gutter={12} style={{ marginBottom: 12 }}>
         <Col span={12}>
           <Card
-            title="评分雷达对比(三方案)"
+            title="评分雷达对比(多方案)"
             size="small"
             styles={{ body: { height: 280, padding: 4 } }}
           >
@@ -101,7 +107,7 @@ export default memo(function ThreeModeComparison({
         </Col>
         <Col span={12}>
           <Card
-            title="关键指标柱状对比(三方案)"
+            title="关键指标柱状对比(多方案)"
             size="small"
             styles={{ body: { height: 280, padding: 4 } }}
           >
@@ -149,27 +155,27 @@ export default memo(function ThreeModeComparison({
                 <Select
                   size="small"
                   style={{ width: 360 }}
-                  value={selectedThreeModePair?.key}
-                  options={threeModePairs.map((item) => ({
+                  value={selectedMultiModePair?.key}
+                  options={multiModePairs.map((item) => ({
                     value: item.key,
                     label: `${item.label} (${item.changes.length}卷)`,
                   }))}
-                  onChange={(value) => onThreeModePairKeyChange(value)}
+                  onChange={(value) => onMultiModePairKeyChange(value)}
                 />
                 <span style={{ color: '#999', fontSize: 12 }}>
-                  平均位移 {selectedThreeModeAvgMove}
+                  平均位移 {selectedMultiModeAvgMove}
                 </span>
                 <Button
                   size="small"
                   icon={<DownloadOutlined />}
                   loading={exportingSequence}
-                  disabled={!selectedThreeModePair}
+                  disabled={!selectedMultiModePair}
                   onClick={() => {
-                    if (!selectedThreeModePair) return;
+                    if (!selectedMultiModePair) return;
                     void onExportSequence(
-                      selectedThreeModePair.plan_a_id,
-                      selectedThreeModePair.plan_b_id,
-                      selectedThreeModePair.key.replace('-', '_vs_'),
+                      selectedMultiModePair.plan_a_id,
+                      selectedMultiModePair.plan_b_id,
+                      selectedMultiModePair.key.replace('-', '_vs_'),
                       'excel'
                     );
                   }}
@@ -180,13 +186,13 @@ export default memo(function ThreeModeComparison({
                   size="small"
                   icon={<DownloadOutlined />}
                   loading={exportingSequence}
-                  disabled={!selectedThreeModePair}
+                  disabled={!selectedMultiModePair}
                   onClick={() => {
-                    if (!selectedThreeModePair) return;
+                    if (!selectedMultiModePair) return;
                     void onExportSequence(
-                      selectedThreeModePair.plan_a_id,
-                      selectedThreeModePair.plan_b_id,
-                      selectedThreeModePair.key.replace('-', '_vs_'),
+                      selectedMultiModePair.plan_a_id,
+                      selectedMultiModePair.plan_b_id,
+                      selectedMultiModePair.key.replace('-', '_vs_'),
                       'csv'
                     );
                   }}
@@ -201,16 +207,27 @@ export default memo(function ThreeModeComparison({
             <Table
               size="small"
               rowKey="coil_id"
-              dataSource={selectedThreeModePair?.changes ?? []}
-              columns={threeModeSequenceColumns}
+              dataSource={selectedMultiModePair?.changes ?? []}
+              columns={multiModeSequenceColumns}
               pagination={{ pageSize: 8, size: 'small' }}
-              virtual={enableThreeModeSequenceVirtual}
+              virtual={enableMultiModeSequenceVirtual}
               scroll={{ x: 620, y: 260 }}
               locale={{ emptyText: '该成对方案共同材料顺序无变化' }}
             />
           </Card>
         </Col>
       </Row>
+      {selectedMultiModePair && (
+        <Row gutter={12} style={{ marginTop: 12 }}>
+          <Col span={24}>
+            <PairAnalysisPanel
+              analysis={selectedMultiModePair.analysis}
+              labelA={selectedMultiModePair.plan_a_label}
+              labelB={selectedMultiModePair.plan_b_label}
+            />
+          </Col>
+        </Row>
+      )}
     </>
   );
 });
@@ -0,0 +1,217 @@
+import { memo } from 'react';
+import { Card, Row, Col, Statistic, Table, Tabs, Tag } from 'antd';
+import type { TableColumnsType } from 'antd';
+import type {
+  DueOutcomeItem,
+  PlanPairAnalysis,
+  RollChangeAlignItem,
+  RollChangePoint,
+  ShiftDiffItem,
+} from '../../types/schedule';
+
+export interface PairAnalysisPanelProps {
+  analysis: PlanPairAnalysis;
+  labelA: string;
+  labelB: string;
+}
+
+function signed(value: number, precision = 0) {
+  const text = value.toFixed(precision);
+  return value > 0 ? `+${text}` : text;
+}
+
+function renderRollPoint(point: RollChangePoint | null) {
+  if (!point) return <span style={{ color: '#999' }}>无对应</span>;
+  return `#${point.position} ${point.coil_id} (${point.cumulative_weight.toFixed(1)}t)`;
+}
+
+function rollRowKey(row: RollChangeAlignItem) {
+  return `${row.point_a?.position ?? '-'}-${row.point_b?.position ?? '-'}`;
+}
+
+function renderLate(minutes: number) {
+  return minutes > 0 ? <Tag color="red">延误 {minutes} 分钟</Tag> : <Tag color="green">按期</Tag>;
+}
+
+export default memo(function PairAnalysisPanel({
+  analysis,
+  labelA,
+  labelB,
+}: PairAnalysisPanelProps) {
+  const { similarity, shift_diffs, roll_change_alignment, due_outcome } = analysis;
+
+  const shiftColumns: TableColumnsType<ShiftDiffItem> = [
+    { title: '班次', dataIndex: 'shift_key', width: 160 },
+    { title: `${labelA}卷数`, dataIndex: 'count_a', width: 110, align: 'right' },
+    { title: `${labelB}卷数`, dataIndex: 'count_b', width: 110, align: 'right' },
+    {
+      title: '卷数差',
+      dataIndex: 'count_delta',
+      width: 90,
+      align: 'right',
+      render: (v: number) => signed(v),
+    },
+    {
+      title: '重量差(t)',
+      dataIndex: 'weight_delta',
+      width: 100,
+      align: 'right',
+      render: (v: number) => signed(v, 1),
+    },
+    {
+      title: '换班材料',
+      key: 'moved',
+      ellipsis: true,
+      render: (_: unknown, row: ShiftDiffItem) =>
+        row.only_a_coils.length + row.only_b_coils.length > 0
+          ? `移出 ${row.only_a_coils.length} / 移入 ${row.only_b_coils.length}`
+          : '-',
+    },
+  ];
+
+  const rollColumns: TableColumnsType<RollChangeAlignItem> = [
+    {
+      title: `${labelA}换辊点`,
+      key: 'point_a',
+      render: (_: unknown, row: RollChangeAlignItem) => renderRollPoint(row.point_a),
+    },
+    {
+      title: `${labelB}换辊点`,
+      key: 'point_b',
+      render: (_: unknown, row: RollChangeAlignItem) => renderRollPoint(row.point_b),
+    },
+    {
+      title: '位置偏移',
+      dataIndex: 'offset',
+      width: 100,
+      align: 'right',
+      render: (v: number | null) => (v == null ? '-' : signed(v)),
+    },
+  ];
+
+  const dueColumns: TableColumnsType<DueOutcomeItem> = [
+    { title: '卷号', dataIndex: 'coil_id', width: 140, ellipsis: true },
+    { title: '交期', dataIndex: 'due_date', width: 120 },
+    { title: `${labelA}完工`, dataIndex: 'end_a', width: 160 },
+    {
+      title: `${labelA}结果`,
+      dataIndex: 'late_minutes_a',
+      width: 130,
+      render: (v: number) => renderLate(v),
+    },
+    { title: `${labelB}完工`, dataIndex: 'end_b', width: 160 },
+    {
+      title: `${labelB}结果`,
+      dataIndex: 'late_minutes_b',
+      width: 130,
+      render: (v: number) => renderLate(v),
+    },
+  ];
+
+  return (
+    <Card title={`序列差异分析 (${labelA} vs ${labelB})`} size="small">
+      <Row gutter={8} style={{ marginBottom: 12 }}>
+        <Col span={5}>
+          <Statistic
+            title="Kendall τ"
+            value={similarity.kendall_tau == null ? '-' : similarity.kendall_tau.toFixed(3)}
+            styles={{ content: { fontSize: 18 } }}
+          />
+        </Col>
+        <Col span={5}>
+          <Statistic
+            title="逆序对"
+            value={similarity.discordant_pairs}
+            styles={{ content: { fontSize: 18 } }}
+          />
+        </Col>
+        <Col span={5}>
+          <Statistic
+            title="LCS 长度"
+            value={similarity.lcs_length}
+            suffix={`/ ${similarity.common_count}`}
+            styles={{ content: { fontSize: 18 } }}
+          />
+        </Col>
+        <Col span={4}>
+          <Statistic
+            title="LCS 相似度"
+            value={(similarity.lcs_similarity * 100).toFixed(1)}
+            suffix="%"
+            styles={{ content: { fontSize: 18 } }}
+          />
+        </Col>
+        <Col span={5}>
+          <Statistic
+            title="换辊对齐"
+            value={roll_change_alignment.aligned_count}
+            suffix={`/ ${Math.max(roll_change_alignment.count_a, roll_change_alignment.count_b)}`}
+            styles={{ content: { fontSize: 18 } }}
+          />
+        </Col>
+      </Row>
+      <Tabs
+        size="small"
+        items={[
+          {
+            key: 'shift',
+            label: `班次差异 (${shift_diffs.length})`,
+            children: (
+              <Table
+                size="small"
+                rowKey="shift_key"
+                dataSource={shift_diffs}
+                columns={shiftColumns}
+                pagination={false}
+                scroll={{ y: 240 }}
+                locale={{ emptyText: '两方案班次分布一致' }}
+              />
+            ),
+          },
+          {
+            key: 'roll',
+            label: `换辊对齐 (${roll_change_alignment.count_a} / ${roll_change_alignment.count_b})`,
+            children: (
+              <>
+                <div style={{ fontSize: 12, color: '#999', marginBottom: 8 }}>
+                  平均位置偏移 {roll_change_alignment.mean_abs_offset.toFixed(1)}
+                </div>
+                <Table
+                  size="small"
+                  rowKey={rollRowKey}
+                  dataSource={roll_change_alignment.items}
+                  columns={rollColumns}
+                  pagination={false}
+                  scroll={{ y: 240 }}
+                  locale={{ emptyText: '两方案均无换辊' }}
+                />
+              </>
+            ),
+          },
+          {
+            key: 'due',
+            label: `交期结果 (${due_outcome.items.length})`,
+            children: (
+              <>
+                <div style={{ fontSize: 12, color: '#999', marginBottom: 8 }}>
+                  延误卷数 {due_outcome.late_count_a} → {due_outcome.late_count_b}，延误总时长{' '}
+                  {due_outcome.total_late_minutes_a} → {due_outcome.total_late_minutes_b}{' '}
+                  分钟，新增延误 {due_outcome.became_late}，转为按期 {due_outcome.became_on_time}
+                </div>
+                <Table
+                  size="small"
+                  rowKey="coil_id"
+                  dataSource={due_outcome.items}
+                  columns={dueColumns}
+                  pagination={{ pageSize: 8, size: 'small' }}
+                  scroll={{ x: 840, y: 240 }}
+                  locale={{ emptyText: '共同材料交期结果无变化' }}
+                />
+              </>
+            ),
+          },
+        ]}
+      />
+    </Card>
+  );
+});
@@ -6,6 +6,7 @@ import type * as EChartsCore from 'echarts/core';
 import type { PlanComparisonResult, SequenceChangeItem } from '../../types/schedule';
 import DeferredEChart from '../../components/DeferredEChart';
 import { DiffValue, ScoreSideCard } from './constants';
+import PairAnalysisPanel from './PairAnalysisPanel';
 
 export interface TwoModeComparisonProps {
   echarts: typeof EChartsCore;
@@ -23,6 +24,7 @@ export interface TwoModeComparisonProps {
   exportingSequence: boolean;
   onExportSequence: (planA: number, planB: number, label: string, format: 'excel' | 'csv') => void;
   onConfirmPlan?: (planId: number) => void;
+  onExecutePlan?: (planId: number) => void;
   onArchivePlan?: (planId: number) => void;
 }
 
@@ -42,6 +44,7 @@ export default memo(function TwoModeComparison({
   exportingSequence,
   onExportSequence,
   onConfirmPlan,
+  onExecutePlan,
   onArchivePlan,
 }: TwoModeComparisonProps) {
   const enableCoilVirtual = coilData.length >= 200;
@@ -62,6 +65,7 @@ export default memo(function TwoModeComparison({
                   : undefined
               }
               onConfirm={onConfirmPlan}
+              onExecute={onExecutePlan}
               onArchive={onArchivePlan}
             />
           </Card>
@@ -114,6 +118,7 @@ export default memo(function TwoModeComparison({
                   : undefined
               }
               onConfirm={onConfirmPlan}
+              onExecute={onExecutePlan}
               onArchive={onArchivePlan}
             />
           </Card>
@@ -309,6 +314,15 @@ export default memo(function TwoModeComparison({
           </Card>
         </Col>
       </Row>
+      <Row gutter={12} style={{ marginTop: 12 }}>
+        <Col span={24}>
+          <PairAnalysisPanel
+            analysis={result.analysis}
+            labelA={result.plan_a.plan_name}
+            labelB={result.plan_b.plan_name}
+          />
+        </Col>
+      </Row>
     </>
   );
 });
@@ -1,5 +1,12 @@
 import { Row, Col, Statistic, Tag, Progress, Button, Popconfirm, Space } from 'antd';
-import { ArrowUpOutlined, ArrowDownOutlined, MinusOutlined, CheckCircleOutlined, InboxOutlined } from '@ant-design/icons';
+import {
+  ArrowUpOutlined,
+  ArrowDownOutlined,
+  MinusOutlined,
+  CheckCircleOutlined,
+  InboxOutlined,
+  PlayCircleOutlined,
+} from '@ant-design/icons';
 import type { PlanComparisonSide } from '../../types/schedule';
 import {
   planStatusLabelMap as statusLabelMap,
@@ -48,16 +55,19 @@ export function ScoreSideCard({
   recommended,
   reason,
   onConfirm,
+  onExecute,
   onArchive,
 }: {
   side: PlanComparisonSide;
   recommended?: boolean;
   reason?: string;
   onConfirm?: (planId: number) => void;
+  onExecute?: (planId: number) => void;
   onArchive?: (planId: number) => void;
 }) {
-  const canConfirm = onConfirm && side.status !== 'confirmed' && side.status !== 'archived';
-  const canArchive = onArchive && side.status === 'confirmed';
+  const canConfirm = onConfirm && side.status === 'approved';
+  const canExecute = onExecute && side.status === 'confirmed';
+  const canArchive = onArchive && (side.status === 'confirmed' || side.status === 'executing');
 
   return (
     <div style={{ textAlign: 'center' }}>
@@ -107,7 +117,7 @@ export function ScoreSideCard({
           </Col>
         </Row>
       </div>
-      {(canConfirm || canArchive) && (
+      {(canConfirm || canExecute || canArchive) && (
         <div style={{ marginTop: 10 }}>
           <Space size="small">
             {canConfirm && (
@@ -123,6 +133,19 @@ export function ScoreSideCard({
                 </Button>
               </Popconfirm>
             )}
+            {canExecute && (
+              <Popconfirm
+                title="开始执行"
+                description="方案将下发执行，执行中的方案只能归档，是否继续？"
+                onConfirm={() => onExecute(side.plan_id)}
+                okText="执行"
+                cancelText="取消"
+              >
+                <Button size="small" icon={<PlayCircleOutlined />}>
+                  开始执行
+                </Button>
+              </Popconfirm>
+            )}
             {canArchive && (
               <Popconfirm
                 title="归档方案"
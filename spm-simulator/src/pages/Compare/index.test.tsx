@@ -1,4 +1,4 @@
-import { render, screen, waitFor } from '@testing-library/react';
+import { fireEvent, render, screen, waitFor } from '@testing-library/react';
 import { MemoryRouter, Route, Routes } from 'react-router-dom';
 import { beforeEach, describe, expect, it, vi } from 'vitest';
 
@@ -6,6 +6,7 @@ import { save } from '@tauri-apps/plugin-dialog';
 import type {
   MultiPlanComparisonResult,
   PlanComparisonResult,
+  PlanPairAnalysis,
   SchedulePlan,
 } from '../../types/schedule';
 import { scheduleApi } from '../../services/scheduleApi';
@@ -63,8 +64,45 @@ const plans: SchedulePlan[] = [
     status: 'confirmed',
     score_overall: 88,
   },
+  {
+    id: 4,
+    plan_no: 'P-004',
+    name: '方案D',
+    period_type: 'daily',
+    start_date: '2026-02-01',
+    end_date: '2026-02-01',
+    status: 'draft',
+    score_overall: 75,
+  },
 ];
 
+const pairAnalysis: PlanPairAnalysis = {
+  similarity: {
+    common_count: 0,
+    kendall_tau: null,
+    discordant_pairs: 0,
+    lcs_length: 0,
+    lcs_similarity: 0,
+  },
+  shift_diffs: [],
+  roll_change_alignment: {
+    count_a: 0,
+    count_b: 0,
+    aligned_count: 0,
+    mean_abs_offset: 0,
+    items: [],
+  },
+  due_outcome: {
+    late_count_a: 0,
+    late_count_b: 0,
+    total_late_minutes_a: 0,
+    total_late_minutes_b: 0,
+    became_late: 0,
+    became_on_time: 0,
+    items: [],
+  },
+};
+
 const twoPlanResult: PlanComparisonResult = {
   plan_a: {
     plan_id: 1,
@@ -112,6 +150,7 @@ const twoPlanResult: PlanComparisonResult = {
     { coil_id: 'C-001', sequence_a: 1, sequence_b: 3, delta: 2 },
     { coil_id: 'C-002', sequence_a: 2, sequence_b: 1, delta: -1 },
   ],
+  analysis: pairAnalysis,
 };
 
 const multiPlanResult: MultiPlanComparisonResult = {
@@ -144,10 +183,65 @@ const multiPlanResult: MultiPlanComparisonResult = {
       common_count: 2,
       only_a_count: 1,
       only_b_count: 1,
+      analysis: pairAnalysis,
     },
   ],
 };
 
+const detailedAnalysis: PlanPairAnalysis = {
+  similarity: {
+    common_count: 4,
+    kendall_tau: 0.6667,
+    discordant_pairs: 1,
+    lcs_length: 3,
+    lcs_similarity: 0.75,
+  },
+  shift_diffs: [
+    {
+      shift_key: '2026-02-01 白班',
+      count_a: 3,
+      count_b: 2,
+      count_delta: -1,
+      weight_a: 30,
+      weight_b: 20,
+      weight_delta: -10,
+      only_a_coils: ['C-003'],
+      only_b_coils: [],
+    },
+  ],
+  roll_change_alignment: {
+    count_a: 1,
+    count_b: 1,
+    aligned_count: 1,
+    mean_abs_offset: 2,
+    items: [
+      {
+        point_a: { position: 5, coil_id: 'C-005', cumulative_weight: 50 },
+        point_b: { position: 7, coil_id: 'C-007', cumulative_weight: 70 },
+        offset: 2,
+      },
+    ],
+  },
+  due_outcome: {
+    late_count_a: 0,
+    late_count_b: 1,
+    total_late_minutes_a: 0,
+    total_late_minutes_b: 45,
+    became_late: 1,
+    became_on_time: 0,
+    items: [
+      {
+        coil_id: 'C-009',
+        due_date: '2026-02-01 12:00',
+        end_a: '2026-02-01 11:00',
+        end_b: '2026-02-01 12:45',
+        late_minutes_a: -60,
+        late_minutes_b: 45,
+      },
+    ],
+  },
+};
+
 function renderCompare(url = '/compare?planA=1&planB=2') {
   render(
     <MemoryRouter initialEntries={[url]}>
@@ -185,7 +279,7 @@ describe('Compare 对比视图', () => {
     await waitFor(() => {
       expect(mockedScheduleApi.comparePlansMulti).toHaveBeenCalledWith([1, 2, 3]);
     });
-    expect(await screen.findByText('评分雷达对比(三方案)')).toBeInTheDocument();
+    expect(await screen.findByText('评分雷达对比(多方案)')).toBeInTheDocument();
     expect(screen.getByText('多方案指标明细')).toBeInTheDocument();
   });
 
@@ -246,4 +340,41 @@ describe('Compare 对比视图', () => {
     });
     expect(await screen.findByText('方案成对重叠统计')).toBeInTheDocument();
   });
+
+  it('happy path: URL 传入四方案时按 N 方案对比并逐对计算顺序差异', async () => {
+    renderCompare('/compare?plans=1,2,3,4');
+
+    await waitFor(() => {
+      expect(mockedScheduleApi.comparePlansMulti).toHaveBeenCalledWith([1, 2, 3, 4]);
+    });
+    await waitFor(() => {
+      expect(mockedScheduleApi.comparePlans).toHaveBeenCalledTimes(6);
+    });
+    expect(mockedScheduleApi.comparePlans).toHaveBeenCalledWith(3, 4);
+    expect(await screen.findByText('评分雷达对比(多方案)')).toBeInTheDocument();
+    expect(screen.getByText('序列差异分析 (方案A vs 方案B)')).toBeInTheDocument();
+  });
+
+  it('happy path: 两方案对比展示 Kendall tau、LCS、班次、换辊与交期差异', async () => {
+    mockedScheduleApi.comparePlans.mockResolvedValue({
+      ...twoPlanResult,
+      analysis: detailedAnalysis,
+    });
+    renderCompare('/compare?planA=1&planB=2');
+
+    expect(await screen.findByText('序列差异分析 (方案A vs 方案B)')).toBeInTheDocument();
+    expect(screen.getByText('Kendall τ').closest('.ant-statistic')).toHaveTextContent('0.667');
+    expect(screen.getByText('LCS 长度').closest('.ant-statistic')).toHaveTextContent('3/ 4');
+    expect(screen.getByText('LCS 相似度').closest('.ant-statistic')).toHaveTextContent('75.0%');
+    expect(screen.getByText('2026-02-01 白班')).toBeInTheDocument();
+    expect(screen.getByText('移出 1 / 移入 0')).toBeInTheDocument();
+
+    fireEvent.click(screen.getByText('换辊对齐 (1 / 1)'));
+    expect(await screen.findByText('#5 C-005 (50.0t)')).toBeInTheDocument();
+    expect(screen.getByText('#7 C-007 (70.0t)')).toBeInTheDocument();
+
+    fireEvent.click(screen.getByText('交期结果 (1)'));
+    expect(await screen.findByText('C-009')).toBeInTheDocument();
+    expect(screen.getByText('延误 45 分钟')).toBeInTheDocument();
+  });
 });
@@ -15,7 +15,7 @@ import { useCompareData } from './useCompareData';
 import { useCompareCharts } from './useCompareCharts';
 import ComparisonSelector from './ComparisonSelector';
 import TwoModeComparison from './TwoModeComparison';
-import ThreeModeComparison from './ThreeModeComparison';
+import MultiModeComparison from './MultiModeComparison';
 
 echarts.use([
   RadarChart,
@@ -39,12 +39,12 @@ export default function Compare() {
       <ComparisonSelector
         planAId={d.planAId}
         planBId={d.planBId}
-        planCId={d.planCId}
+        extraPlanIds={d.extraPlanIds}
         planOptions={d.planOptions}
         loading={d.loading}
         onPlanAChange={d.setPlanAId}
         onPlanBChange={d.setPlanBId}
-        onPlanCChange={d.setPlanCId}
+        onExtraPlansChange={d.setExtraPlanIds}
         onSwapAB={() => {
           const tmp = d.planAId;
           d.setPlanAId(d.planBId);
@@ -54,30 +54,31 @@ export default function Compare() {
       />
 
       <Spin spinning={d.loading}>
-        {d.isThreeMode ? (
+        {d.isMultiMode ? (
           !d.multiResult ? (
             <Card>
-              <Empty description="请选择三个不同方案进行对比" style={{ padding: 40 }} />
+              <Empty description="请选择至少三个不同方案进行对比" style={{ padding: 40 }} />
             </Card>
           ) : (
-            <ThreeModeComparison
+            <MultiModeComparison
               echarts={echarts}
               multiResult={d.multiResult}
               radarOptionMulti={charts.radarOptionMulti}
               metricsBarOptionMulti={charts.metricsBarOptionMulti}
-              threeModeRecommendation={d.threeModeRecommendation}
+              multiModeRecommendation={d.multiModeRecommendation}
               multiMetricRows={d.multiMetricRows}
               multiMetricColumns={d.multiMetricColumns}
               overlapData={d.overlapData}
               overlapColumns={d.overlapColumns}
-              threeModePairs={d.threeModePairs}
-              selectedThreeModePair={d.selectedThreeModePair}
-              selectedThreeModeAvgMove={d.selectedThreeModeAvgMove}
-              threeModeSequenceColumns={d.threeModeSequenceColumns}
-              onThreeModePairKeyChange={d.setThreeModePairKey}
+              multiModePairs={d.multiModePairs}
+              selectedMultiModePair={d.selectedMultiModePair}
+              selectedMultiModeAvgMove={d.selectedMultiModeAvgMove}
+              multiModeSequenceColumns={d.multiModeSequenceColumns}
+              onMultiModePairKeyChange={d.setMultiModePairKey}
               exportingSequence={d.exportingSequence}
               onExportSequence={d.handleExportSequence}
               onConfirmPlan={d.handleConfirmPlan}
+              onExecutePlan={d.handleExecutePlan}
               onArchivePlan={d.handleArchivePlan}
             />
           )
@@ -102,6 +103,7 @@ export default function Compare() {
             exportingSequence={d.exportingSequence}
             onExportSequence={d.handleExportSequence}
             onConfirmPlan={d.handleConfirmPlan}
+            onExecutePlan={d.handleExecutePlan}
             onArchivePlan={d.handleArchivePlan}
           />
         )}
@@ -1,6 +1,6 @@
-import type { SequenceChangeItem } from '../../types/schedule';
+import type { PlanPairAnalysis, SequenceChangeItem } from '../../types/schedule';
 
-export interface ThreeModeSequencePair {
+export interface MultiModeSequencePair {
   key: string;
   label: string;
   plan_a_id: number;
@@ -8,4 +8,5 @@ export interface ThreeModeSequencePair {
   plan_a_label: string;
   plan_b_label: string;
   changes: SequenceChangeItem[];
+  analysis: PlanPairAnalysis;
 }
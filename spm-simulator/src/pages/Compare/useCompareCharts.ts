@@ -1,6 +1,9 @@
 import { useMemo } from 'react';
 import type { PlanComparisonResult, MultiPlanComparisonResult } from '../../types/schedule';
 
+/** 多方案配色，方案数超过色板时循环使用 */
+const MULTI_PALETTE = ['#1677ff', '#ff4d4f', '#52c41a', '#faad14', '#722ed1', '#13c2c2'];
+
 export interface UseCompareChartsParams {
   result: PlanComparisonResult | null;
   multiResult: MultiPlanComparisonResult | null;
@@ -77,8 +80,7 @@ export function useCompareCharts({ result, multiResult }: UseCompareChartsParams
               {
                 type: 'radar',
                 data: multiResult.plans.map((side, idx) => {
-                  const palette = ['#1677ff', '#ff4d4f', '#52c41a'];
-                  const color = palette[idx % palette.length];
+                  const color = MULTI_PALETTE[idx % MULTI_PALETTE.length];
                   return {
                     value: [
                       side.score_overall,
@@ -162,24 +164,24 @@ export function useCompareCharts({ result, multiResult }: UseCompareChartsParams
               axisLabel: { fontSize: 10 },
             },
             yAxis: { type: 'value' as const },
-            series: multiResult.plans.map((side, idx) => {
-              const palette = ['#1677ff', '#ff4d4f', '#52c41a'];
-              return {
-                name: side.plan_name,
-                type: 'bar' as const,
-                data: [
-                  side.total_count,
-                  side.total_weight,
-                  side.roll_change_count,
-                  side.steel_grade_switches,
-                  side.risk_high,
-                  side.risk_medium,
-                  side.risk_low,
-                ],
-                itemStyle: { color: palette[idx % palette.length], borderRadius: [3, 3, 0, 0] },
-                barMaxWidth: 20,
-              };
-            }),
+            series: multiResult.plans.map((side, idx) => ({
+              name: side.plan_name,
+              type: 'bar' as const,
+              data: [
+                side.total_count,
+                side.total_weight,
+                side.roll_change_count,
+                side.steel_grade_switches,
+                side.risk_high,
+                side.risk_medium,
+                side.risk_low,
+              ],
+              itemStyle: {
+                color: MULTI_PALETTE[idx % MULTI_PALETTE.length],
+                borderRadius: [3, 3, 0, 0],
+              },
+              barMaxWidth: 20,
+            })),
           }
         : {},
     [multiResult]
@@ -12,10 +12,11 @@ import type {
   PlanComparisonResult,
   PlanComparisonSide,
   MultiPlanComparisonResult,
+  PlanStatus,
   SequenceChangeItem,
 } from '../../types/schedule';
 import { getErrorMessage } from '../../utils/error';
-import type { ThreeModeSequencePair } from './types';
+import type { MultiModeSequencePair } from './types';
 import { comparePlanQuality, buildRecommendReason } from './utils';
 
 export interface UseCompareDataParams {
@@ -23,19 +24,34 @@ export interface UseCompareDataParams {
   searchParams: URLSearchParams;
 }
 
+/** 解析 URL 中的对比方案：优先 plans=1,2,3，兼容旧的 planA/planB/planC */
+function parseQueryPlanIds(searchParams: URLSearchParams): number[] {
+  const raw = searchParams.get('plans');
+  const values = raw
+    ? raw.split(',')
+    : [searchParams.get('planA'), searchParams.get('planB'), searchParams.get('planC')];
+  const ids = values.map((v) => Number(v)).filter((v) => Number.isInteger(v) && v > 0);
+  return Array.from(new Set(ids));
+}
+
 export function useCompareData({ navigate, searchParams }: UseCompareDataParams) {
   const [plans, setPlans] = useState<SchedulePlan[]>([]);
   const [planAId, setPlanAId] = useState<number | null>(null);
   const [planBId, setPlanBId] = useState<number | null>(null);
-  const [planCId, setPlanCId] = useState<number | null>(null);
+  const [extraPlanIds, setExtraPlanIds] = useState<number[]>([]);
   const [result, setResult] = useState<PlanComparisonResult | null>(null);
   const [multiResult, setMultiResult] = useState<MultiPlanComparisonResult | null>(null);
-  const [threeModePairs, setThreeModePairs] = useState<ThreeModeSequencePair[]>([]);
-  const [threeModePairKey, setThreeModePairKey] = useState<string>('');
+  const [multiModePairs, setMultiModePairs] = useState<MultiModeSequencePair[]>([]);
+  const [multiModePairKey, setMultiModePairKey] = useState<string>('');
   const [loading, setLoading] = useState(false);
   const [exportingSequence, setExportingSequence] = useState(false);
   const [queryAutoCompared, setQueryAutoCompared] = useState(false);
-  const isThreeMode = !!planCId && planCId !== planAId && planCId !== planBId;
+  const selectedPlanIds = useMemo(
+    () =>
+      Array.from(new Set([planAId, planBId, ...extraPlanIds].filter((id): id is number => !!id))),
+    [planAId, planBId, extraPlanIds]
+  );
+  const isMultiMode = !!planAId && !!planBId && planAId !== planBId && selectedPlanIds.length > 2;
 
   const loadPlans = useCallback(async () => {
     try {
@@ -52,17 +68,11 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
 
   useEffect(() => {
     if (plans.length === 0) return;
-    const planA = Number(searchParams.get('planA'));
-    const planB = Number(searchParams.get('planB'));
-    const planC = Number(searchParams.get('planC'));
-    if (!Number.isFinite(planA) || !Number.isFinite(planB)) return;
-    if (planA <= 0 || planB <= 0) return;
-    if (!plans.some((p) => p.id === planA) || !plans.some((p) => p.id === planB)) return;
-    setPlanAId(planA);
-    setPlanBId(planB);
-    if (Number.isFinite(planC) && planC > 0 && plans.some((p) => p.id === planC)) {
-      setPlanCId(planC);
-    }
+    const ids = parseQueryPlanIds(searchParams).filter((id) => plans.some((p) => p.id === id));
+    if (ids.length < 2) return;
+    setPlanAId(ids[0]);
+    setPlanBId(ids[1]);
+    setExtraPlanIds(ids.slice(2));
   }, [plans, searchParams]);
 
   const handleCompare = useCallback(async () => {
@@ -76,18 +86,12 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
     }
     try {
       setLoading(true);
-      if (isThreeMode && planCId) {
-        if (planCId === planAId || planCId === planBId) {
-          message.warning('第三个方案必须与 A/B 不同');
-          return;
-        }
-        const pairConfigs = [
-          { a: planAId, b: planBId, label: 'A vs B' },
-          { a: planAId, b: planCId, label: 'A vs C' },
-          { a: planBId, b: planCId, label: 'B vs C' },
-        ];
+      if (isMultiMode) {
+        const pairConfigs = selectedPlanIds.flatMap((a, idx) =>
+          selectedPlanIds.slice(idx + 1).map((b) => ({ a, b }))
+        );
         const [multiData, pairResults] = await Promise.all([
-          scheduleApi.comparePlansMulti([planAId, planBId, planCId]),
+          scheduleApi.comparePlansMulti(selectedPlanIds),
           Promise.all(
             pairConfigs.map(async (pair) => {
               const detail = await scheduleApi.comparePlans(pair.a, pair.b);
@@ -108,38 +112,35 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
             plan_a_label: planALabel,
             plan_b_label: planBLabel,
             changes: item.detail.sequence_changes,
+            analysis: item.detail.analysis,
           };
         });
-        setThreeModePairs(pairData);
-        setThreeModePairKey((prev) =>
+        setMultiModePairs(pairData);
+        setMultiModePairKey((prev) =>
           pairData.some((item) => item.key === prev) ? prev : (pairData[0]?.key ?? '')
         );
       } else {
         const data = await scheduleApi.comparePlans(planAId, planBId);
         setResult(data);
         setMultiResult(null);
-        setThreeModePairs([]);
-        setThreeModePairKey('');
+        setMultiModePairs([]);
+        setMultiModePairKey('');
       }
     } catch (error: unknown) {
       message.error(`对比失败: ${getErrorMessage(error)}`);
     } finally {
       setLoading(false);
     }
-  }, [planAId, planBId, planCId, isThreeMode, plans]);
+  }, [planAId, planBId, selectedPlanIds, isMultiMode, plans]);
 
   useEffect(() => {
-    const planA = Number(searchParams.get('planA'));
-    const planB = Number(searchParams.get('planB'));
-    const planC = Number(searchParams.get('planC'));
-    const hasQueryPair = Number.isFinite(planA) && Number.isFinite(planB) && planA > 0 && planB > 0;
-    if (!hasQueryPair || queryAutoCompared) return;
-    if (!planAId || !planBId) return;
-    if (planAId !== planA || planBId !== planB) return;
-    if (Number.isFinite(planC) && planC > 0 && (!planCId || planCId !== planC)) return;
+    if (queryAutoCompared) return;
+    const queryIds = parseQueryPlanIds(searchParams);
+    if (queryIds.length < 2) return;
+    if (queryIds.join(',') !== selectedPlanIds.join(',')) return;
     setQueryAutoCompared(true);
     void handleCompare();
-  }, [searchParams, queryAutoCompared, planAId, planBId, planCId, handleCompare]);
+  }, [searchParams, queryAutoCompared, selectedPlanIds, handleCompare]);
 
   // ─── Derived data ───
 
@@ -166,7 +167,7 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
     };
   }, [result]);
 
-  const threeModeRecommendation = useMemo(() => {
+  const multiModeRecommendation = useMemo(() => {
     if (!multiResult || multiResult.plans.length === 0) return null;
     const ranked = [...multiResult.plans].sort(comparePlanQuality);
     return {
@@ -296,32 +297,32 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
 
   // ─── Tables: Sequence changes (three-mode) ───
 
-  const selectedThreeModePair =
-    threeModePairs.find((item) => item.key === threeModePairKey) ?? threeModePairs[0] ?? null;
+  const selectedMultiModePair =
+    multiModePairs.find((item) => item.key === multiModePairKey) ?? multiModePairs[0] ?? null;
 
-  const selectedThreeModeAvgMove = useMemo(
+  const selectedMultiModeAvgMove = useMemo(
     () =>
-      selectedThreeModePair && selectedThreeModePair.changes.length > 0
+      selectedMultiModePair && selectedMultiModePair.changes.length > 0
         ? (
-            selectedThreeModePair.changes.reduce((sum, item) => sum + Math.abs(item.delta), 0) /
-            selectedThreeModePair.changes.length
+            selectedMultiModePair.changes.reduce((sum, item) => sum + Math.abs(item.delta), 0) /
+            selectedMultiModePair.changes.length
           ).toFixed(1)
         : '0.0',
-    [selectedThreeModePair]
+    [selectedMultiModePair]
   );
 
-  const threeModeSequenceColumns = useMemo<TableColumnsType<SequenceChangeItem>>(
+  const multiModeSequenceColumns = useMemo<TableColumnsType<SequenceChangeItem>>(
     () => [
       { title: '卷号', dataIndex: 'coil_id', width: 140, ellipsis: true },
       {
-        title: `${selectedThreeModePair?.plan_a_label ?? '左侧'}序号`,
+        title: `${selectedMultiModePair?.plan_a_label ?? '左侧'}序号`,
         dataIndex: 'sequence_a',
         width: 120,
         align: 'right',
         render: (v: number) => `#${v}`,
       },
       {
-        title: `${selectedThreeModePair?.plan_b_label ?? '右侧'}序号`,
+        title: `${selectedMultiModePair?.plan_b_label ?? '右侧'}序号`,
         dataIndex: 'sequence_b',
         width: 120,
         align: 'right',
@@ -359,11 +360,11 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
               type="link"
               size="small"
               style={{ paddingInline: 2 }}
-              disabled={!selectedThreeModePair}
+              disabled={!selectedMultiModePair}
               onClick={() => {
-                if (selectedThreeModePair)
+                if (selectedMultiModePair)
                   navigate(
-                    `/?planId=${selectedThreeModePair.plan_a_id}&focusSeq=${row.sequence_a}`
+                    `/?planId=${selectedMultiModePair.plan_a_id}&focusSeq=${row.sequence_a}`
                   );
               }}
             >
@@ -373,11 +374,11 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
               type="link"
               size="small"
               style={{ paddingInline: 2 }}
-              disabled={!selectedThreeModePair}
+              disabled={!selectedMultiModePair}
               onClick={() => {
-                if (selectedThreeModePair)
+                if (selectedMultiModePair)
                   navigate(
-                    `/?planId=${selectedThreeModePair.plan_b_id}&focusSeq=${row.sequence_b}`
+                    `/?planId=${selectedMultiModePair.plan_b_id}&focusSeq=${row.sequence_b}`
                   );
               }}
             >
@@ -387,7 +388,7 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
         ),
       },
     ],
-    [selectedThreeModePair, navigate]
+    [selectedMultiModePair, navigate]
   );
 
   // ─── Tables: Multi-mode metrics ───
@@ -508,59 +509,40 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
     []
   );
 
-  const handleConfirmPlan = useCallback(
-    async (planId: number) => {
+  const updatePlanStatus = useCallback(
+    async (planId: number, status: PlanStatus, label: string) => {
       try {
-        await scheduleApi.updatePlanStatus(planId, 'confirmed');
-        message.success('方案已确认生效');
+        await scheduleApi.updatePlanStatus(planId, status);
+        message.success(`方案已${label}`);
         await loadPlans();
         // Re-run comparison to refresh status display
         if (result || multiResult) {
-          const planIds = isThreeMode
-            ? [planAId, planBId, planCId].filter(Boolean) as number[]
-            : [planAId, planBId].filter(Boolean) as number[];
-          if (planIds.length >= 2) {
-            if (isThreeMode && planIds.length === 3) {
-              const data = await scheduleApi.comparePlansMulti(planIds);
-              setMultiResult(data);
-            } else if (!isThreeMode) {
-              const data = await scheduleApi.comparePlans(planIds[0], planIds[1]);
-              setResult(data);
-            }
+          if (isMultiMode) {
+            setMultiResult(await scheduleApi.comparePlansMulti(selectedPlanIds));
+          } else if (planAId && planBId) {
+            setResult(await scheduleApi.comparePlans(planAId, planBId));
           }
         }
       } catch (error: unknown) {
-        message.error(`确认生效失败: ${getErrorMessage(error)}`);
+        message.error(`${label}失败: ${getErrorMessage(error)}`);
       }
     },
-    [loadPlans, result, multiResult, isThreeMode, planAId, planBId, planCId]
+    [loadPlans, result, multiResult, isMultiMode, selectedPlanIds, planAId, planBId]
+  );
+
+  const handleConfirmPlan = useCallback(
+    (planId: number) => updatePlanStatus(planId, 'confirmed', '确认生效'),
+    [updatePlanStatus]
+  );
+
+  const handleExecutePlan = useCallback(
+    (planId: number) => updatePlanStatus(planId, 'executing', '开始执行'),
+    [updatePlanStatus]
   );
 
   const handleArchivePlan = useCallback(
-    async (planId: number) => {
-      try {
-        await scheduleApi.updatePlanStatus(planId, 'archived');
-        message.success('方案已归档');
-        await loadPlans();
-        if (result || multiResult) {
-          const planIds = isThreeMode
-            ? [planAId, planBId, planCId].filter(Boolean) as number[]
-            : [planAId, planBId].filter(Boolean) as number[];
-          if (planIds.length >= 2) {
-            if (isThreeMode && planIds.length === 3) {
-              const data = await scheduleApi.comparePlansMulti(planIds);
-              setMultiResult(data);
-            } else if (!isThreeMode) {
-              const data = await scheduleApi.comparePlans(planIds[0], planIds[1]);
-              setResult(data);
-            }
-          }
-        }
-      } catch (error: unknown) {
-        message.error(`归档失败: ${getErrorMessage(error)}`);
-      }
-    },
-    [loadPlans, result, multiResult, isThreeMode, planAId, planBId, planCId]
+    (planId: number) => updatePlanStatus(planId, 'archived', '归档'),
+    [updatePlanStatus]
   );
 
   return {
@@ -570,20 +552,21 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
     setPlanAId,
     planBId,
     setPlanBId,
-    planCId,
-    setPlanCId,
+    extraPlanIds,
+    setExtraPlanIds,
+    selectedPlanIds,
     result,
     multiResult,
     loading,
     exportingSequence,
-    isThreeMode,
+    isMultiMode,
     handleCompare,
     // plan options
     planOptions,
     planLabelById,
     // recommendations
     twoModeRecommendation,
-    threeModeRecommendation,
+    multiModeRecommendation,
     // coil data
     coilColumns,
     coilData,
@@ -592,12 +575,12 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
     sequenceChangeData,
     sequenceAvgMove,
     // three-mode sequence
-    threeModePairs,
-    threeModePairKey,
-    setThreeModePairKey,
-    selectedThreeModePair,
-    selectedThreeModeAvgMove,
-    threeModeSequenceColumns,
+    multiModePairs,
+    multiModePairKey,
+    setMultiModePairKey,
+    selectedMultiModePair,
+    selectedMultiModeAvgMove,
+    multiModeSequenceColumns,
     // multi metrics
     multiMetricRows,
     multiMetricColumns,
@@ -607,6 +590,7 @@ export function useCompareData({ navigate, searchParams }: UseCompareDataParams)
     handleExportSequence,
     // plan status actions
     handleConfirmPlan,
+    handleExecutePlan,
     handleArchivePlan,
   };
 }
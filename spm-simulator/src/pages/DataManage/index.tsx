@@ -533,6 +533,8 @@ export default function DataManage() {
             options={[
               { value: '', label: '全部状态' },
               { value: 'pending', label: '待排' },
+              { value: 'planned', label: '已锁定' },
+              { value: 'in_production', label: '生产中' },
               { value: 'completed', label: '已完成' },
               { value: 'frozen', label: '冻结' },
             ]}
@@ -693,7 +695,11 @@ export default function DataManage() {
               { value: '', label: '全部状态' },
               { value: 'draft', label: '草稿' },
               { value: 'saved', label: '已保存' },
+              { value: 'submitted', label: '待审批' },
+              { value: 'approved', label: '已审批' },
+              { value: 'rejected', label: '已驳回' },
               { value: 'confirmed', label: '已确认' },
+              { value: 'executing', label: '执行中' },
               { value: 'archived', label: '已归档' },
             ]}
           />
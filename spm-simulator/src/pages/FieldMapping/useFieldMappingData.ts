@@ -562,8 +562,8 @@ export function useFieldMappingData() {
       const filePath = await open({
         multiple: false,
         filters: [
-          { name: 'Excel', extensions: ['xlsx', 'xls'] },
-          { name: 'CSV', extensions: ['csv'] },
+          { name: 'Excel', extensions: ['xlsx', 'xlsm', 'xlsb', 'xls', 'ods'] },
+          { name: 'CSV', extensions: ['csv', 'txt'] },
         ],
       });
       if (!filePath || Array.isArray(filePath)) return;
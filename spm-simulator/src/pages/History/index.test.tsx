@@ -7,6 +7,7 @@ import { save } from '@tauri-apps/plugin-dialog';
 import type {
   OperationLogEntry,
   PlanComparisonResult,
+  PlanPairAnalysis,
   PlanVersionItem,
   RiskAnalysis,
   SchedulePlan,
@@ -127,6 +128,33 @@ const buildLogs = (count: number): OperationLogEntry[] =>
     created_at: '2026-02-13T08:30:00Z',
   }));
 
+const pairAnalysis: PlanPairAnalysis = {
+  similarity: {
+    common_count: 0,
+    kendall_tau: null,
+    discordant_pairs: 0,
+    lcs_length: 0,
+    lcs_similarity: 0,
+  },
+  shift_diffs: [],
+  roll_change_alignment: {
+    count_a: 0,
+    count_b: 0,
+    aligned_count: 0,
+    mean_abs_offset: 0,
+    items: [],
+  },
+  due_outcome: {
+    late_count_a: 0,
+    late_count_b: 0,
+    total_late_minutes_a: 0,
+    total_late_minutes_b: 0,
+    became_late: 0,
+    became_on_time: 0,
+    items: [],
+  },
+};
+
 const compareResult: PlanComparisonResult = {
   plan_a: {
     plan_id: 1,
@@ -171,6 +199,7 @@ const compareResult: PlanComparisonResult = {
   only_a_coils: [],
   only_b_coils: [],
   sequence_changes: [],
+  analysis: pairAnalysis,
 };
 
 const riskTemplate: RiskAnalysis = {
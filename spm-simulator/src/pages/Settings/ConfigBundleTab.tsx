@@ -0,0 +1,299 @@
+import { useState, useCallback } from 'react';
+import {
+  Alert,
+  Button,
+  Card,
+  Checkbox,
+  Popconfirm,
+  Select,
+  Space,
+  Table,
+  Tag,
+  Typography,
+  message,
+} from 'antd';
+import type { TableColumnsType } from 'antd';
+import { DownloadOutlined, UploadOutlined } from '@ant-design/icons';
+import { open, save } from '@tauri-apps/plugin-dialog';
+import dayjs from 'dayjs';
+import { configApi } from '../../services/configApi';
+import type {
+  BundleRecordChange,
+  BundleSectionDiff,
+  ConfigBundleMode,
+  ConfigBundleReport,
+} from '../../types/config';
+import { getErrorMessage } from '../../utils/error';
+
+const { Text } = Typography;
+
+const bundleSections = [
+  { value: 'system_config', label: '系统配置' },
+  { value: 'strategies', label: '策略模板' },
+  { value: 'priority_weights', label: '优先级维度权重' },
+  { value: 'priority_dimensions', label: '优先级维度配置' },
+  { value: 'customer_priorities', label: '客户优先级' },
+  { value: 'batch_priorities', label: '集批优先级' },
+  { value: 'product_type_priorities', label: '产品大类优先级' },
+  { value: 'field_mappings', label: '字段映射' },
+  { value: 'export_templates', label: '导出模板' },
+  { value: 'maintenance_plans', label: '检修计划' },
+];
+
+const modeOptions: { value: ConfigBundleMode; label: string }[] = [
+  { value: 'merge', label: '合并' },
+  { value: 'replace', label: '替换' },
+  { value: 'skip', label: '跳过' },
+];
+
+const actionTagMap: Record<BundleRecordChange['action'], { color: string; label: string }> = {
+  add: { color: 'green', label: '新增' },
+  update: { color: 'blue', label: '修改' },
+  remove: { color: 'red', label: '删除' },
+};
+
+export default function ConfigBundleTab({ onImported }: { onImported: () => void }) {
+  const [exportSections, setExportSections] = useState<string[]>(
+    bundleSections.map((s) => s.value)
+  );
+  const [filePath, setFilePath] = useState<string | null>(null);
+  const [modes, setModes] = useState<Record<string, ConfigBundleMode>>({});
+  const [report, setReport] = useState<ConfigBundleReport | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  const handleExport = async () => {
+    try {
+      const path = await save({
+        defaultPath: `配置包_${dayjs().format('YYYYMMDD_HHmmss')}.json`,
+        filters: [{ name: 'JSON', extensions: ['json'] }],
+      });
+      if (!path) return;
+      const counts = await configApi.exportConfigBundle(path, exportSections);
+      const total = Object.values(counts).reduce((sum, n) => sum + n, 0);
+      message.success(`配置包导出成功，共 ${total} 条记录`);
+    } catch (error: unknown) {
+      message.error(`导出失败: ${getErrorMessage(error)}`);
+    }
+  };
+
+  const runPreview = useCallback(
+    async (path: string, nextModes: Record<string, ConfigBundleMode>) => {
+      setLoading(true);
+      try {
+        setReport(await configApi.importConfigBundle(path, nextModes, true));
+      } catch (error: unknown) {
+        setReport(null);
+        message.error(`解析配置包失败: ${getErrorMessage(error)}`);
+      } finally {
+        setLoading(false);
+      }
+    },
+    []
+  );
+
+  const handleSelectFile = async () => {
+    try {
+      const path = await open({
+        multiple: false,
+        filters: [{ name: 'JSON', extensions: ['json'] }],
+      });
+      if (!path || Array.isArray(path)) return;
+      setFilePath(path);
+      setModes({});
+      await runPreview(path, {});
+    } catch (error: unknown) {
+      message.error(`选择文件失败: ${getErrorMessage(error)}`);
+    }
+  };
+
+  const handleModeChange = async (section: string, mode: ConfigBundleMode) => {
+    if (!filePath) return;
+    const nextModes = { ...modes, [section]: mode };
+    setModes(nextModes);
+    await runPreview(filePath, nextModes);
+  };
+
+  const handleApply = async () => {
+    if (!filePath) return;
+    setLoading(true);
+    try {
+      const result = await configApi.importConfigBundle(filePath, modes, false);
+      if (!result.applied) {
+        setReport(result);
+        message.error('配置包存在错误，未导入');
+        return;
+      }
+      message.success('配置包导入成功');
+      setFilePath(null);
+      setReport(null);
+      onImported();
+    } catch (error: unknown) {
+      message.error(`导入失败: ${getErrorMessage(error)}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const hasChanges = report?.sections.some((s) => s.changes.length > 0) ?? false;
+
+  const sectionColumns: TableColumnsType<BundleSectionDiff> = [
+    { title: '分区', dataIndex: 'label', width: 140 },
+    {
+      title: '导入方式',
+      dataIndex: 'mode',
+      width: 110,
+      render: (v: ConfigBundleMode, row: BundleSectionDiff) => (
+        <Select
+          size="small"
+          style={{ width: 90 }}
+          value={v}
+          options={modeOptions}
+          onChange={(mode) => handleModeChange(row.section, mode)}
+        />
+      ),
+    },
+    { title: '新增', dataIndex: 'added', width: 70 },
+    { title: '修改', dataIndex: 'updated', width: 70 },
+    { title: '删除', dataIndex: 'removed', width: 70 },
+    { title: '不变', dataIndex: 'unchanged', width: 70 },
+    {
+      title: '冲突',
+      dataIndex: 'conflicts',
+      width: 70,
+      render: (v: string[]) => (v.length > 0 ? <Tag color="orange">{v.length}</Tag> : 0),
+    },
+  ];
+
+  const changeColumns: TableColumnsType<BundleRecordChange> = [
+    {
+      title: '变更',
+      dataIndex: 'action',
+      width: 70,
+      render: (v: BundleRecordChange['action']) => (
+        <Tag color={actionTagMap[v]?.color}>{actionTagMap[v]?.label ?? v}</Tag>
+      ),
+    },
+    { title: '记录', dataIndex: 'key', ellipsis: true },
+    {
+      title: '字段',
+      dataIndex: 'fields',
+      ellipsis: true,
+      render: (v: string[]) => (v.length > 0 ? v.join(', ') : '-'),
+    },
+  ];
+
+  return (
+    <Space direction="vertical" style={{ width: '100%' }}>
+      <Card size="small" title="导出配置包">
+        <Checkbox.Group
+          options={bundleSections}
+          value={exportSections}
+          onChange={(values) => setExportSections(values as string[])}
+        />
+        <div style={{ marginTop: 12 }}>
+          <Button
+            icon={<DownloadOutlined />}
+            onClick={handleExport}
+            disabled={exportSections.length === 0}
+          >
+            导出配置包
+          </Button>
+          <Text type="secondary" style={{ marginLeft: 12, fontSize: 12 }}>
+            备份、下发、目录监控等本机路径配置不随配置包迁移
+          </Text>
+        </div>
+      </Card>
+      <Card size="small" title="导入配置包">
+        <Space wrap>
+          <Button icon={<UploadOutlined />} onClick={handleSelectFile} loading={loading}>
+            选择配置包
+          </Button>
+          {filePath && <Text type="secondary">{filePath}</Text>}
+        </Space>
+        {report && (
+          <div style={{ marginTop: 12 }}>
+            <Text type="secondary" style={{ fontSize: 12 }}>
+              配置包 v{report.version} / 应用 {report.app_version} / 导出于{' '}
+              {dayjs(report.exported_at).format('YYYY-MM-DD HH:mm:ss')}
+            </Text>
+            {report.errors.length > 0 && (
+              <Alert
+                type="error"
+                showIcon
+                style={{ marginTop: 8 }}
+                message="配置包存在错误，无法导入"
+                description={report.errors.map((e) => (
+                  <div key={e}>{e}</div>
+                ))}
+              />
+            )}
+            {report.warnings.length > 0 && (
+              <Alert
+                type="warning"
+                showIcon
+                style={{ marginTop: 8 }}
+                message={report.warnings.map((w) => (
+                  <div key={w}>{w}</div>
+                ))}
+              />
+            )}
+            <Table
+              size="small"
+              rowKey="section"
+              style={{ marginTop: 8 }}
+              loading={loading}
+              pagination={false}
+              dataSource={report.sections}
+              columns={sectionColumns}
+              expandable={{
+                rowExpandable: (row) => row.changes.length > 0 || row.conflicts.length > 0,
+                expandedRowRender: (row) => (
+                  <>
+                    {row.conflicts.length > 0 && (
+                      <Alert
+                        type="warning"
+                        showIcon
+                        style={{ marginBottom: 8 }}
+                        message={row.conflicts.map((c) => (
+                          <div key={c}>{c}</div>
+                        ))}
+                      />
+                    )}
+                    <Table
+                      size="small"
+                      rowKey={(c) => `${c.action}:${c.key}`}
+                      pagination={false}
+                      dataSource={row.changes}
+                      columns={changeColumns}
+                      scroll={{ y: 240 }}
+                    />
+                  </>
+                ),
+              }}
+            />
+            <div style={{ marginTop: 12 }}>
+              <Popconfirm
+                title="按当前差异导入配置包？"
+                onConfirm={handleApply}
+                disabled={!hasChanges || report.errors.length > 0}
+              >
+                <Button
+                  type="primary"
+                  loading={loading}
+                  disabled={!hasChanges || report.errors.length > 0}
+                >
+                  确认导入
+                </Button>
+              </Popconfirm>
+              {!hasChanges && report.errors.length === 0 && (
+                <Text type="secondary" style={{ marginLeft: 12 }}>
+                  与本地配置一致，无需导入
+                </Text>
+              )}
+            </div>
+          </div>
+        )}
+      </Card>
+    </Space>
+  );
+}
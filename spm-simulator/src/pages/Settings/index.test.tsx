@@ -41,6 +41,8 @@ vi.mock('../../services/configApi', () => ({
     deleteCustomerPriorityConfig: vi.fn(),
     deleteBatchPriorityConfig: vi.fn(),
     deleteProductTypePriorityConfig: vi.fn(),
+    exportConfigBundle: vi.fn(),
+    importConfigBundle: vi.fn(),
   },
 }));
 
@@ -1220,4 +1222,105 @@ describe('Settings 导航与定位', () => {
     await user.click(await screen.findByText('产品大类优先级'));
     expect(screen.queryByDisplayValue('P001')).not.toBeInTheDocument();
   });
+
+  itSlow('happy path: 配置包试运行展示差异后确认导入', async () => {
+    const user = userEvent.setup();
+    const preview = {
+      version: 1,
+      app_version: '0.1.0',
+      exported_at: '2026-10-01T00:00:00Z',
+      dry_run: true,
+      applied: false,
+      sections: [
+        {
+          section: 'strategies',
+          label: '策略模板',
+          mode: 'merge',
+          added: 1,
+          updated: 0,
+          removed: 0,
+          unchanged: 2,
+          changes: [{ key: 'A厂基线', action: 'add', fields: [] }],
+          conflicts: [],
+        },
+      ],
+      errors: [],
+      warnings: [],
+    };
+    mockedOpen.mockResolvedValue('/tmp/bundle.json');
+    mockedConfigApi.importConfigBundle
+      .mockResolvedValueOnce(preview)
+      .mockResolvedValueOnce({ ...preview, dry_run: false, applied: true });
+
+    renderSettings('/settings?tab=bundle');
+    await user.click(await screen.findByRole('button', { name: /选择配置包/ }));
+
+    await waitFor(() => {
+      expect(mockedConfigApi.importConfigBundle).toHaveBeenCalledWith('/tmp/bundle.json', {}, true);
+    });
+    expect(await screen.findByText('策略模板')).toBeInTheDocument();
+
+    await user.click(screen.getByRole('button', { name: /确认导入/ }));
+    await user.click(await screen.findByRole('button', { name: /确定|ok/i }));
+
+    await waitFor(() => {
+      expect(mockedConfigApi.importConfigBundle).toHaveBeenLastCalledWith(
+        '/tmp/bundle.json',
+        {},
+        false
+      );
+    });
+  });
+
+  itSlow('edge case: 替换策略时仍被方案引用的模板显示为冲突', async () => {
+    const user = userEvent.setup();
+    mockedOpen.mockResolvedValue('/tmp/bundle.json');
+    mockedConfigApi.importConfigBundle.mockResolvedValueOnce({
+      version: 1,
+      app_version: '0.1.0',
+      exported_at: '2026-10-01T00:00:00Z',
+      dry_run: true,
+      applied: false,
+      sections: [
+        {
+          section: 'strategies',
+          label: '策略模板',
+          mode: 'replace',
+          added: 0,
+          updated: 0,
+          removed: 0,
+          unchanged: 1,
+          changes: [],
+          conflicts: ['策略模板 旧策略 仍被 2 个排程方案引用，保留不删除'],
+        },
+      ],
+      errors: [],
+      warnings: [],
+    });
+
+    renderSettings('/settings?tab=bundle');
+    await user.click(await screen.findByRole('button', { name: /选择配置包/ }));
+
+    expect(await screen.findByText('策略模板')).toBeInTheDocument();
+    await user.click(screen.getByRole('button', { name: /展开行|expand row/i }));
+    expect(
+      await screen.findByText('策略模板 旧策略 仍被 2 个排程方案引用，保留不删除')
+    ).toBeInTheDocument();
+  });
+
+  itSlow('happy path: 导出配置包会调用导出接口', async () => {
+    const user = userEvent.setup();
+    mockedSave.mockResolvedValue('/tmp/out.json');
+    mockedConfigApi.exportConfigBundle.mockResolvedValue({ strategies: 2, system_config: 10 });
+
+    renderSettings('/settings?tab=bundle');
+    await user.click(await screen.findByRole('button', { name: /导出配置包/ }));
+
+    await waitFor(() => {
+      expect(mockedConfigApi.exportConfigBundle).toHaveBeenCalledWith(
+        '/tmp/out.json',
+        expect.arrayContaining(['system_config', 'strategies', 'maintenance_plans'])
+      );
+    });
+  });
 });
@@ -11,6 +11,7 @@ import PriorityConfigPanel from './PriorityConfigPanel';
 import PerformanceTab from './PerformanceTab';
 import MaintenanceTab from './MaintenanceTab';
 import ErrorLogsTab from './ErrorLogsTab';
+import ConfigBundleTab from './ConfigBundleTab';
 
 const { Text } = Typography;
 
@@ -93,6 +94,7 @@ export default function Settings() {
       { key: 'maintenance', label: '检修计划' },
       { key: 'performance', label: '性能验收' },
       { key: 'error_logs', label: '错误日志' },
+      { key: 'bundle', label: '配置包' },
     ],
     []
   );
@@ -157,6 +159,8 @@ export default function Settings() {
                     onClearFocus={handleClearPriorityFocus}
                   />
                 </Card>
+              ) : tab.key === 'bundle' ? (
+                <ConfigBundleTab onImported={handleRefresh} />
               ) : tab.key === 'maintenance' ? (
                 <MaintenanceTab refreshTrigger={refreshCounter} />
               ) : configGroups[tab.key] ? (
@@ -14,6 +14,7 @@ import HardConstraintsEditor from './HardConstraintsEditor';
 import SoftConstraintsEditor from './SoftConstraintsEditor';
 import EvalWeightsViewer from './EvalWeightsViewer';
 import TemperRulesViewer from './TemperRulesViewer';
+import StrategyRevisionPanel from './StrategyRevisionPanel';
 
 export interface StrategyDetailViewProps {
   selected: StrategyTemplate | null;
@@ -23,6 +24,7 @@ export interface StrategyDetailViewProps {
   evalWeights: Record<string, EvalWeight>;
   temperRules: TemperRules | null;
   onEdit: (t: StrategyTemplate) => void;
+  onRevisionRestored: (t: StrategyTemplate) => Promise<void>;
 }
 
 export default memo(function StrategyDetailView({
@@ -33,6 +35,7 @@ export default memo(function StrategyDetailView({
   evalWeights,
   temperRules,
   onEdit,
+  onRevisionRestored,
 }: StrategyDetailViewProps) {
   return (
     <Card
@@ -90,6 +93,13 @@ export default memo(function StrategyDetailView({
                 </div>
               ),
             },
+            {
+              key: 'revisions',
+              label: '修订历史',
+              children: (
+                <StrategyRevisionPanel template={selected} onRestored={onRevisionRestored} />
+              ),
+            },
           ]}
         />
       ) : (
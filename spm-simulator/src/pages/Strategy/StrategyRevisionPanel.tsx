@@ -0,0 +1,208 @@
+import { useState, useCallback, useEffect } from 'react';
+import { Table, Tag, Button, Popconfirm, Space, Select, message } from 'antd';
+import type { TableColumnsType } from 'antd';
+import { DownloadOutlined, RollbackOutlined } from '@ant-design/icons';
+import { save } from '@tauri-apps/plugin-dialog';
+import dayjs from 'dayjs';
+import { configApi } from '../../services/configApi';
+import type {
+  StrategyChange,
+  StrategyRevision,
+  StrategyRevisionDiff,
+  StrategyTemplate,
+} from '../../types/config';
+import { getErrorMessage } from '../../utils/error';
+
+interface StrategyRevisionPanelProps {
+  template: StrategyTemplate;
+  onRestored: (template: StrategyTemplate) => Promise<void>;
+}
+
+const sectionLabelMap: Record<string, string> = {
+  sort_weights: '排序权重',
+  constraints: '硬约束',
+  soft_constraints: '软约束',
+  eval_weights: '评估权重',
+  temper_rules: '适温规则',
+};
+
+const kindTagMap: Record<StrategyChange['kind'], { color: string; label: string }> = {
+  added: { color: 'green', label: '新增' },
+  removed: { color: 'red', label: '删除' },
+  changed: { color: 'blue', label: '修改' },
+  reordered: { color: 'orange', label: '调序' },
+};
+
+const formatValue = (v: unknown) => (v === null || v === undefined ? '-' : JSON.stringify(v));
+
+export default function StrategyRevisionPanel({ template, onRestored }: StrategyRevisionPanelProps) {
+  const [revisions, setRevisions] = useState<StrategyRevision[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [fromId, setFromId] = useState<number | null>(null);
+  const [toId, setToId] = useState<number | null>(null);
+  const [diff, setDiff] = useState<StrategyRevisionDiff | null>(null);
+
+  const refresh = useCallback(async () => {
+    setLoading(true);
+    try {
+      const data = await configApi.getStrategyRevisions(template.id);
+      setRevisions(data);
+      // 默认对比最近两个修订
+      setToId(data[0]?.id ?? null);
+      setFromId(data[1]?.id ?? data[0]?.id ?? null);
+      setDiff(null);
+    } catch (error: unknown) {
+      message.error(`加载修订失败: ${getErrorMessage(error)}`);
+    } finally {
+      setLoading(false);
+    }
+  }, [template.id]);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh, template.updated_at]);
+
+  const handleDiff = useCallback(async () => {
+    if (fromId === null || toId === null) return;
+    try {
+      setDiff(await configApi.diffStrategyRevisions(template.id, fromId, toId));
+    } catch (error: unknown) {
+      message.error(`对比失败: ${getErrorMessage(error)}`);
+    }
+  }, [template.id, fromId, toId]);
+
+  const handleExportDiff = useCallback(async () => {
+    if (fromId === null || toId === null) return;
+    try {
+      const filePath = await save({
+        defaultPath: `策略修订差异_${template.name}_${dayjs().format('YYYYMMDD_HHmmss')}.csv`,
+        filters: [{ name: 'CSV', extensions: ['csv'] }],
+      });
+      if (!filePath) return;
+      const rows = await configApi.exportStrategyRevisionDiff(template.id, fromId, toId, filePath);
+      message.success(`已导出 ${rows} 项差异`);
+    } catch (error: unknown) {
+      message.error(`导出失败: ${getErrorMessage(error)}`);
+    }
+  }, [template.id, template.name, fromId, toId]);
+
+  const handleRestore = useCallback(
+    async (revision: StrategyRevision) => {
+      try {
+        const restored = await configApi.restoreStrategyRevision(template.id, revision.id);
+        message.success(`已恢复修订 r${revision.revision_no}`);
+        await onRestored(restored);
+      } catch (error: unknown) {
+        message.error(`恢复失败: ${getErrorMessage(error)}`);
+      }
+    },
+    [template.id, onRestored]
+  );
+
+  const revisionOptions = revisions.map((r) => ({
+    value: r.id,
+    label: `r${r.revision_no}${r.change_note ? ` ${r.change_note}` : ''}`,
+  }));
+
+  const revisionColumns: TableColumnsType<StrategyRevision> = [
+    { title: '修订', dataIndex: 'revision_no', width: 70, render: (v: number) => `r${v}` },
+    { title: '说明', dataIndex: 'change_note', ellipsis: true },
+    {
+      title: '时间',
+      dataIndex: 'created_at',
+      width: 160,
+      render: (v?: string) => (v ? dayjs(v).format('YYYY-MM-DD HH:mm:ss') : '-'),
+    },
+    {
+      title: '操作',
+      key: 'actions',
+      width: 90,
+      render: (_: unknown, row: StrategyRevision, index: number) =>
+        index === 0 || template.is_system ? null : (
+          <Popconfirm
+            title={`将 r${row.revision_no} 恢复为当前内容？`}
+            onConfirm={() => handleRestore(row)}
+          >
+            <Button size="small" type="link" icon={<RollbackOutlined />}>
+              恢复
+            </Button>
+          </Popconfirm>
+        ),
+    },
+  ];
+
+  const diffColumns: TableColumnsType<StrategyChange> = [
+    {
+      title: '部分',
+      dataIndex: 'section',
+      width: 90,
+      render: (v: string) => sectionLabelMap[v] ?? v,
+    },
+    { title: '路径', dataIndex: 'path', ellipsis: true },
+    {
+      title: '类型',
+      dataIndex: 'kind',
+      width: 70,
+      render: (v: StrategyChange['kind']) => (
+        <Tag color={kindTagMap[v]?.color}>{kindTagMap[v]?.label ?? v}</Tag>
+      ),
+    },
+    { title: '原值', dataIndex: 'before', ellipsis: true, render: formatValue },
+    { title: '新值', dataIndex: 'after', ellipsis: true, render: formatValue },
+  ];
+
+  return (
+    <div>
+      <Table
+        size="small"
+        rowKey="id"
+        loading={loading}
+        pagination={false}
+        dataSource={revisions}
+        columns={revisionColumns}
+        scroll={{ y: 240 }}
+      />
+      <Space style={{ margin: '12px 0 8px' }} wrap>
+        <Select
+          size="small"
+          style={{ width: 200 }}
+          value={fromId ?? undefined}
+          options={revisionOptions}
+          onChange={setFromId}
+          placeholder="原修订"
+        />
+        <span>→</span>
+        <Select
+          size="small"
+          style={{ width: 200 }}
+          value={toId ?? undefined}
+          options={revisionOptions}
+          onChange={setToId}
+          placeholder="新修订"
+        />
+        <Button size="small" onClick={handleDiff} disabled={fromId === null || toId === null}>
+          对比
+        </Button>
+        <Button
+          size="small"
+          icon={<DownloadOutlined />}
+          onClick={handleExportDiff}
+          disabled={fromId === null || toId === null}
+        >
+          导出差异
+        </Button>
+      </Space>
+      {diff && (
+        <Table
+          size="small"
+          rowKey={(row) => `${row.section}:${row.path}:${row.kind}`}
+          pagination={false}
+          dataSource={diff.changes}
+          columns={diffColumns}
+          locale={{ emptyText: '两个修订内容一致' }}
+          scroll={{ y: 240 }}
+        />
+      )}
+    </div>
+  );
+}
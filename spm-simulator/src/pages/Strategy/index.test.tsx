@@ -20,6 +20,7 @@ vi.mock('../../services/configApi', () => ({
     setDefaultStrategy: vi.fn(),
     exportStrategyTemplate: vi.fn(),
     importStrategyTemplate: vi.fn(),
+    validateStrategyConfig: vi.fn(),
   },
 }));
 
@@ -154,6 +155,11 @@ describe('Strategy 模板管理', () => {
       name: '新策略',
     });
     mockedConfigApi.updateStrategyTemplate.mockResolvedValue(templates[1]);
+    mockedConfigApi.validateStrategyConfig.mockResolvedValue({
+      valid: true,
+      errors: [],
+      warnings: [],
+    });
     mockedConfigApi.deleteStrategyTemplate.mockResolvedValue(undefined);
     mockedConfigApi.setDefaultStrategy.mockImplementation(async (id: number) => {
       if (id === 3) {
@@ -291,6 +297,33 @@ describe('Strategy 模板管理', () => {
     expect(screen.getByText('新建策略模板')).toBeInTheDocument();
   }, 15000);
 
+  it('fail path: 后端结构校验有错误时不会创建模板', async () => {
+    const user = userEvent.setup();
+    mockedConfigApi.validateStrategyConfig.mockResolvedValueOnce({
+      valid: false,
+      errors: [
+        {
+          path: 'constraints.constraints[0].type',
+          level: 'error',
+          message: '未知硬约束类型 max_speed',
+        },
+      ],
+      warnings: [],
+    });
+    render(<Strategy />);
+
+    await user.click(await screen.findByRole('button', { name: /新建/ }));
+    const modal = await screen.findByRole('dialog');
+    await user.type(within(modal).getByPlaceholderText('如：宽幅板优先策略'), '校验失败策略');
+    await user.click(within(modal).getByRole('button', { name: /确定|ok/i }));
+
+    await waitFor(() => {
+      expect(mockedConfigApi.validateStrategyConfig).toHaveBeenCalled();
+    });
+    expect(mockedConfigApi.createStrategyTemplate).not.toHaveBeenCalled();
+    expect(screen.getByText('新建策略模板')).toBeInTheDocument();
+  }, 15000);
+
   it('happy path: 新建并设为默认会调用创建和设默认接口', async () => {
     const user = userEvent.setup();
     render(<Strategy />);
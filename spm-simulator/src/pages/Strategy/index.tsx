@@ -37,6 +37,7 @@ export default function Strategy() {
           evalWeights={d.selectedEvalWeights}
           temperRules={d.selectedTemperRules}
           onEdit={d.handleEdit}
+          onRevisionRestored={d.handleRevisionRestored}
         />
       </Col>
 
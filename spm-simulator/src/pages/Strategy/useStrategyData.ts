@@ -45,6 +45,7 @@ export interface UseStrategyDataReturn {
   handleDuplicate: (t: StrategyTemplate) => void;
   handleDelete: (id: number) => Promise<void>;
   handleSetDefault: (id: number) => Promise<void>;
+  handleRevisionRestored: (t: StrategyTemplate) => Promise<void>;
   handleSave: () => Promise<void>;
   handleExportTemplate: () => Promise<void>;
   handleImportTemplate: () => Promise<void>;
@@ -183,6 +184,11 @@ export function useStrategyData({ form }: UseStrategyDataParams): UseStrategyDat
     }
   };
 
+  const handleRevisionRestored = async (restored: StrategyTemplate) => {
+    await fetchTemplates();
+    setSelectedId(restored.id);
+  };
+
   const handleSave = async () => {
     try {
       const values = await form.validateFields();
@@ -208,6 +214,23 @@ export function useStrategyData({ form }: UseStrategyDataParams): UseStrategyDat
         temper_rules: JSON.stringify(temperRules ?? createDefaultTemperRules()),
       };
 
+      const report = await configApi.validateStrategyConfig(payload);
+      if (!report.valid) {
+        const first = report.errors[0];
+        message.error(
+          `策略配置有 ${report.errors.length} 项错误: ${first.path} ${first.message}`
+        );
+        return;
+      }
+      if (report.warnings.length > 0) {
+        message.warning(
+          `策略配置有 ${report.warnings.length} 项警告: ${report.warnings
+            .map((w) => `${w.path} ${w.message}`)
+            .slice(0, 3)
+            .join('；')}`
+        );
+      }
+
       let saved: StrategyTemplate;
       if (editingId) {
         saved = await configApi.updateStrategyTemplate(editingId, payload);
@@ -302,6 +325,7 @@ export function useStrategyData({ form }: UseStrategyDataParams): UseStrategyDat
     handleDuplicate,
     handleDelete,
     handleSetDefault,
+    handleRevisionRestored,
     handleSave,
     handleExportTemplate,
     handleImportTemplate,
@@ -0,0 +1,181 @@
+import { useState, useCallback, useEffect } from 'react';
+import { Table, Tag, Button, Space, Modal, Form, Input, message } from 'antd';
+import type { TableColumnsType } from 'antd';
+import { AuditOutlined, CheckOutlined, CloseOutlined } from '@ant-design/icons';
+import { scheduleApi } from '../../services/scheduleApi';
+import type { PlanApproval, SchedulePlan } from '../../types/schedule';
+import { planStatusColorMap, planStatusLabelMap } from '../../constants/schedule';
+import { getErrorMessage } from '../../utils/error';
+
+interface PlanApprovalPanelProps {
+  plan: SchedulePlan;
+  onApprove: (approver: string, comment?: string) => Promise<void>;
+  onReject: (approver: string, comment?: string) => Promise<void>;
+}
+
+type ReviewDecision = 'approved' | 'rejected';
+
+interface ReviewFormValues {
+  approver: string;
+  comment?: string;
+}
+
+function renderStatus(status?: string | null) {
+  if (!status) return '-';
+  return (
+    <Tag color={planStatusColorMap[status] ?? 'default'}>
+      {planStatusLabelMap[status] ?? status}
+    </Tag>
+  );
+}
+
+export default function PlanApprovalPanel({ plan, onApprove, onReject }: PlanApprovalPanelProps) {
+  const [decision, setDecision] = useState<ReviewDecision | null>(null);
+  const [submitting, setSubmitting] = useState(false);
+  const [historyOpen, setHistoryOpen] = useState(false);
+  const [approvals, setApprovals] = useState<PlanApproval[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [form] = Form.useForm<ReviewFormValues>();
+
+  const refresh = useCallback(async () => {
+    setLoading(true);
+    try {
+      setApprovals(await scheduleApi.getPlanApprovals(plan.id));
+    } catch (error: unknown) {
+      message.error(`加载审批记录失败: ${getErrorMessage(error)}`);
+    } finally {
+      setLoading(false);
+    }
+  }, [plan.id]);
+
+  useEffect(() => {
+    if (historyOpen) refresh();
+  }, [historyOpen, refresh]);
+
+  const openReview = useCallback(
+    (next: ReviewDecision) => {
+      form.resetFields();
+      setDecision(next);
+    },
+    [form]
+  );
+
+  const handleReview = useCallback(async () => {
+    if (!decision) return;
+    const values = await form.validateFields();
+    const approver = values.approver.trim();
+    const comment = values.comment?.trim() || undefined;
+    setSubmitting(true);
+    try {
+      await (decision === 'approved' ? onApprove : onReject)(approver, comment);
+      setDecision(null);
+    } catch {
+      // 失败提示已由调用方给出，保留弹窗便于修改后重试
+    } finally {
+      setSubmitting(false);
+    }
+  }, [decision, form, onApprove, onReject]);
+
+  const columns: TableColumnsType<PlanApproval> = [
+    {
+      title: '时间',
+      dataIndex: 'created_at',
+      width: 160,
+      render: (v: string | null) => (v ? new Date(v).toLocaleString() : '-'),
+    },
+    {
+      title: '原状态',
+      dataIndex: 'from_status',
+      width: 90,
+      render: (v: string | null) => renderStatus(v),
+    },
+    {
+      title: '新状态',
+      dataIndex: 'to_status',
+      width: 90,
+      render: (v: string) => renderStatus(v),
+    },
+    { title: '审批人', dataIndex: 'approver', width: 100, render: (v: string | null) => v || '-' },
+    { title: '意见', dataIndex: 'comment', ellipsis: true, render: (v: string | null) => v || '-' },
+  ];
+
+  return (
+    <>
+      {plan.status === 'submitted' && (
+        <>
+          <Button
+            size="small"
+            type="primary"
+            icon={<CheckOutlined />}
+            onClick={() => openReview('approved')}
+          >
+            审批通过
+          </Button>
+          <Button
+            size="small"
+            danger
+            icon={<CloseOutlined />}
+            onClick={() => openReview('rejected')}
+          >
+            驳回
+          </Button>
+        </>
+      )}
+      <Button size="small" icon={<AuditOutlined />} onClick={() => setHistoryOpen(true)}>
+        审批记录
+      </Button>
+      <Modal
+        title={decision === 'rejected' ? '驳回方案' : '审批通过'}
+        open={decision !== null}
+        okText={decision === 'rejected' ? '驳回' : '通过'}
+        okButtonProps={{ danger: decision === 'rejected' }}
+        confirmLoading={submitting}
+        onOk={handleReview}
+        onCancel={() => setDecision(null)}
+        forceRender
+      >
+        <Form form={form} layout="vertical">
+          <Form.Item
+            name="approver"
+            label="审批人"
+            rules={[{ required: true, whitespace: true, message: '请填写审批人' }]}
+          >
+            <Input placeholder="审批人姓名" maxLength={50} />
+          </Form.Item>
+          <Form.Item
+            name="comment"
+            label="审批意见"
+            rules={
+              decision === 'rejected'
+                ? [{ required: true, whitespace: true, message: '驳回须填写审批意见' }]
+                : []
+            }
+          >
+            <Input.TextArea rows={3} maxLength={500} />
+          </Form.Item>
+        </Form>
+      </Modal>
+      <Modal
+        title={`审批记录 - ${plan.name}`}
+        open={historyOpen}
+        width={760}
+        footer={null}
+        onCancel={() => setHistoryOpen(false)}
+      >
+        <Space style={{ marginBottom: 8 }}>
+          <span style={{ fontSize: 12, color: '#999' }}>当前状态</span>
+          {renderStatus(plan.status)}
+        </Space>
+        <Table
+          size="small"
+          rowKey="id"
+          loading={loading}
+          pagination={false}
+          dataSource={approvals}
+          columns={columns}
+          scroll={{ y: 360 }}
+        />
+      </Modal>
+    </>
+  );
+}
@@ -0,0 +1,210 @@
+import { useState, useCallback, useEffect } from 'react';
+import { Table, Tag, Button, Popconfirm, Space, Modal, message } from 'antd';
+import type { TableColumnsType } from 'antd';
+import { HistoryOutlined } from '@ant-design/icons';
+import { scheduleApi } from '../../services/scheduleApi';
+import type {
+  PlanStatePreview,
+  SchedulePlan,
+  UndoDiffSummary,
+  UndoHistoryEntry,
+} from '../../types/schedule';
+import { getErrorMessage } from '../../utils/error';
+
+interface UndoHistoryPanelProps {
+  planId: number;
+  editable: boolean;
+  onChanged: () => Promise<void>;
+  onForked?: (plan: SchedulePlan) => void;
+}
+
+function renderSummary(summary: UndoDiffSummary) {
+  return (
+    <Space size={4} wrap>
+      {summary.added > 0 && <Tag color="green">新增 {summary.added}</Tag>}
+      {summary.removed > 0 && <Tag color="red">移除 {summary.removed}</Tag>}
+      {summary.moved > 0 && <Tag color="blue">移动 {summary.moved}</Tag>}
+      {summary.updated > 0 && <Tag>修改 {summary.updated}</Tag>}
+      {summary.plan_changed && <Tag color="purple">方案统计</Tag>}
+    </Space>
+  );
+}
+
+export default function UndoHistoryPanel({
+  planId,
+  editable,
+  onChanged,
+  onForked,
+}: UndoHistoryPanelProps) {
+  const [open, setOpen] = useState(false);
+  const [entries, setEntries] = useState<UndoHistoryEntry[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [preview, setPreview] = useState<PlanStatePreview | null>(null);
+
+  const refresh = useCallback(async () => {
+    setLoading(true);
+    try {
+      setEntries(await scheduleApi.getUndoHistory(planId));
+    } catch (error: unknown) {
+      message.error(`加载操作历史失败: ${getErrorMessage(error)}`);
+    } finally {
+      setLoading(false);
+    }
+  }, [planId]);
+
+  useEffect(() => {
+    if (open) {
+      setPreview(null);
+      refresh();
+    }
+  }, [open, refresh]);
+
+  const handlePreview = useCallback(
+    async (entryId: number | null) => {
+      try {
+        setPreview(await scheduleApi.previewUndoEntry(planId, entryId));
+      } catch (error: unknown) {
+        message.error(`预览失败: ${getErrorMessage(error)}`);
+      }
+    },
+    [planId]
+  );
+
+  const handleJump = useCallback(
+    async (entryId: number | null) => {
+      try {
+        const replayed = await scheduleApi.jumpToUndoEntry(planId, entryId);
+        message.success(`已回放 ${replayed} 步`);
+        await refresh();
+        await onChanged();
+      } catch (error: unknown) {
+        message.error(`跳转失败: ${getErrorMessage(error)}`);
+      }
+    },
+    [planId, refresh, onChanged]
+  );
+
+  const handleFork = useCallback(
+    async (entryId: number | null) => {
+      try {
+        const forked = await scheduleApi.forkPlanFromUndoEntry(planId, entryId);
+        message.success(`已派生新版本 v${forked.version ?? 1}`);
+        onForked?.(forked);
+      } catch (error: unknown) {
+        message.error(`派生失败: ${getErrorMessage(error)}`);
+      }
+    },
+    [planId, onForked]
+  );
+
+  const renderActions = (entryId: number | null) => (
+    <Space size={4}>
+      <Button size="small" type="link" onClick={() => handlePreview(entryId)}>
+        预览
+      </Button>
+      {editable && (
+        <Popconfirm title="跳转到该状态？" onConfirm={() => handleJump(entryId)}>
+          <Button size="small" type="link">
+            跳转
+          </Button>
+        </Popconfirm>
+      )}
+      <Popconfirm title="以该状态派生新版本方案？" onConfirm={() => handleFork(entryId)}>
+        <Button size="small" type="link">
+          派生
+        </Button>
+      </Popconfirm>
+    </Space>
+  );
+
+  const columns: TableColumnsType<UndoHistoryEntry> = [
+    { title: '#', dataIndex: 'id', width: 60 },
+    { title: '操作', dataIndex: 'description', ellipsis: true },
+    {
+      title: '变更',
+      dataIndex: 'summary',
+      width: 200,
+      render: (v: UndoDiffSummary) => renderSummary(v),
+    },
+    {
+      title: '状态',
+      dataIndex: 'is_undone',
+      width: 80,
+      render: (v: boolean) => (v ? <Tag>已撤销</Tag> : <Tag color="green">已生效</Tag>),
+    },
+    {
+      title: '时间',
+      dataIndex: 'created_at',
+      width: 160,
+      render: (v: string | null) => (v ? new Date(v).toLocaleString() : '-'),
+    },
+    {
+      title: '操作',
+      key: 'actions',
+      width: 170,
+      render: (_: unknown, row: UndoHistoryEntry) => renderActions(row.id),
+    },
+  ];
+
+  return (
+    <>
+      <Button size="small" icon={<HistoryOutlined />} onClick={() => setOpen(true)}>
+        操作历史
+      </Button>
+      <Modal
+        title="操作历史"
+        open={open}
+        width={900}
+        footer={null}
+        onCancel={() => setOpen(false)}
+      >
+        <Space style={{ marginBottom: 8 }}>
+          <span style={{ fontSize: 12, color: '#999' }}>初始状态</span>
+          {renderActions(null)}
+        </Space>
+        <Table
+          size="small"
+          rowKey="id"
+          loading={loading}
+          pagination={false}
+          dataSource={entries}
+          columns={columns}
+          scroll={{ y: 320 }}
+        />
+        {preview && (
+          <div style={{ marginTop: 12 }}>
+            <div style={{ fontSize: 12, color: '#999', marginBottom: 4 }}>
+              预览 {preview.entry_id ? `#${preview.entry_id}` : '初始状态'}: {preview.items.length}{' '}
+              卷 / 换辊 {preview.plan.roll_change_count ?? 0} 次
+            </div>
+            <Table
+              size="small"
+              rowKey="id"
+              pagination={false}
+              dataSource={preview.items}
+              scroll={{ y: 200 }}
+              columns={[
+                { title: '序号', dataIndex: 'sequence', width: 70 },
+                {
+                  title: '卷号',
+                  dataIndex: 'material_id',
+                  width: 140,
+                  ellipsis: true,
+                  render: (v: number) => preview.coil_ids[v] ?? `#${v}`,
+                },
+                { title: '班次日期', dataIndex: 'shift_date', width: 110 },
+                { title: '班次', dataIndex: 'shift_type', width: 70 },
+                {
+                  title: '锁定',
+                  dataIndex: 'is_locked',
+                  width: 60,
+                  render: (v: boolean | null) => (v ? '是' : ''),
+                },
+              ]}
+            />
+          </div>
+        )}
+      </Modal>
+    </>
+  );
+}
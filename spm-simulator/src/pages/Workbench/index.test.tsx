@@ -36,11 +36,16 @@ vi.mock('../../services/scheduleApi', () => ({
     addToSchedule: vi.fn(),
     moveScheduleItem: vi.fn(),
     savePlan: vi.fn(),
+    updatePlanStatus: vi.fn(),
+    getPlanApprovals: vi.fn(),
     lockScheduleItems: vi.fn(),
     removeFromSchedule: vi.fn(),
-    pushUndo: vi.fn(),
     undoAction: vi.fn(),
     redoAction: vi.fn(),
+    getUndoHistory: vi.fn(),
+    previewUndoEntry: vi.fn(),
+    jumpToUndoEntry: vi.fn(),
+    forkPlanFromUndoEntry: vi.fn(),
   },
 }));
 
@@ -86,9 +91,10 @@ const mockedScheduleApi = scheduleApi as unknown as {
   addToSchedule: ReturnType<typeof vi.fn>;
   moveScheduleItem: ReturnType<typeof vi.fn>;
   savePlan: ReturnType<typeof vi.fn>;
+  updatePlanStatus: ReturnType<typeof vi.fn>;
+  getPlanApprovals: ReturnType<typeof vi.fn>;
   lockScheduleItems: ReturnType<typeof vi.fn>;
   removeFromSchedule: ReturnType<typeof vi.fn>;
-  pushUndo: ReturnType<typeof vi.fn>;
   undoAction: ReturnType<typeof vi.fn>;
   redoAction: ReturnType<typeof vi.fn>;
 };
@@ -296,9 +302,14 @@ describe('Workbench 配置命中联动', () => {
     mockedScheduleApi.addToSchedule.mockResolvedValue(undefined);
     mockedScheduleApi.moveScheduleItem.mockResolvedValue(undefined);
     mockedScheduleApi.savePlan.mockResolvedValue(plans[0]);
+    mockedScheduleApi.updatePlanStatus.mockImplementation(async (id: number, status: string) => ({
+      ...plans[0],
+      id,
+      status,
+    }));
+    mockedScheduleApi.getPlanApprovals.mockResolvedValue([]);
     mockedScheduleApi.lockScheduleItems.mockResolvedValue(undefined);
     mockedScheduleApi.removeFromSchedule.mockResolvedValue(undefined);
-    mockedScheduleApi.pushUndo.mockResolvedValue(undefined);
     mockedScheduleApi.undoAction.mockResolvedValue({ action_type: 'undo', remaining: 0 });
     mockedScheduleApi.redoAction.mockResolvedValue({ action_type: 'redo', remaining: 0 });
     mockedMaterialApi.getMaterials.mockResolvedValue({
@@ -428,14 +439,6 @@ describe('Workbench 配置命中联动', () => {
     await waitFor(() => {
       expect(mockedScheduleApi.addToSchedule).toHaveBeenCalledWith(1, [101], undefined);
     });
-    await waitFor(() => {
-      expect(mockedScheduleApi.pushUndo).toHaveBeenCalledWith(
-        1,
-        'add_materials',
-        expect.any(String),
-        expect.any(String)
-      );
-    });
   }, 20000);
 
   it('Esc 会清空材料面板选择', async () => {
@@ -550,6 +553,139 @@ describe('Workbench 配置命中联动', () => {
     expect(mockedScheduleApi.savePlan).not.toHaveBeenCalled();
   }, 12000);
 
+  it('待审批方案填写审批人后可审批通过', async () => {
+    const user = userEvent.setup();
+    mockedScheduleApi.getPlans.mockResolvedValue([{ ...plans[0], status: 'submitted' as const }]);
+    renderWorkbench();
+
+    await waitForLoaded();
+    await user.click(screen.getByRole('button', { name: /审批通过/ }));
+    const dialog = await screen.findByRole('dialog');
+    await user.type(within(dialog).getByLabelText('审批人'), ' 张工 ');
+    await user.type(within(dialog).getByLabelText('审批意见'), '同意');
+    await user.click(within(dialog).getByRole('button', { name: /通\s*过/ }));
+
+    await waitFor(() => {
+      expect(mockedScheduleApi.updatePlanStatus).toHaveBeenCalledWith(
+        1,
+        'approved',
+        '张工',
+        '同意'
+      );
+    });
+    await waitFor(() => {
+      expect(screen.queryByRole('button', { name: /驳回/ })).toBeNull();
+    });
+  }, 20000);
+
+  it('驳回方案须填写审批意见', async () => {
+    const user = userEvent.setup();
+    mockedScheduleApi.getPlans.mockResolvedValue([{ ...plans[0], status: 'submitted' as const }]);
+    renderWorkbench();
+
+    await waitForLoaded();
+    await user.click(screen.getByRole('button', { name: /驳回/ }));
+    const dialog = await screen.findByRole('dialog');
+    await user.type(within(dialog).getByLabelText('审批人'), '张工');
+    await user.click(within(dialog).getByRole('button', { name: /驳\s*回/ }));
+    expect(await within(dialog).findByText('驳回须填写审批意见')).toBeInTheDocument();
+    expect(mockedScheduleApi.updatePlanStatus).not.toHaveBeenCalled();
+
+    await user.type(within(dialog).getByLabelText('审批意见'), '宽度跳跃过大');
+    await user.click(within(dialog).getByRole('button', { name: /驳\s*回/ }));
+    await waitFor(() => {
+      expect(mockedScheduleApi.updatePlanStatus).toHaveBeenCalledWith(
+        1,
+        'rejected',
+        '张工',
+        '宽度跳跃过大'
+      );
+    });
+  }, 20000);
+
+  it('已确认方案可开始执行', async () => {
+    const user = userEvent.setup();
+    mockedScheduleApi.getPlans.mockResolvedValue([{ ...plans[0], status: 'confirmed' as const }]);
+    renderWorkbench();
+
+    await waitForLoaded();
+    await user.click(screen.getByRole('button', { name: /开始执行/ }));
+    const popconfirm = await screen.findByRole('tooltip');
+    await user.click(within(popconfirm).getByRole('button', { name: /执\s*行/ }));
+
+    await waitFor(() => {
+      expect(mockedScheduleApi.updatePlanStatus).toHaveBeenCalledWith(1, 'executing');
+    });
+  }, 20000);
+
+  it('审批记录弹窗展示状态流转与审批意见', async () => {
+    const user = userEvent.setup();
+    mockedScheduleApi.getPlanApprovals.mockResolvedValue([
+      {
+        id: 1,
+        plan_id: 1,
+        from_status: 'submitted',
+        to_status: 'rejected',
+        approver: '李工',
+        comment: '换辊过于频繁',
+        created_at: '2026-02-01T08:00:00Z',
+      },
+    ]);
+    renderWorkbench();
+
+    await waitForLoaded();
+    await user.click(screen.getByRole('button', { name: /审批记录/ }));
+    const dialog = await screen.findByRole('dialog');
+
+    await waitFor(() => {
+      expect(mockedScheduleApi.getPlanApprovals).toHaveBeenCalledWith(1);
+    });
+    expect(await within(dialog).findByText('换辊过于频繁')).toBeInTheDocument();
+    expect(within(dialog).getByText('李工')).toBeInTheDocument();
+    expect(within(dialog).getByText('已驳回')).toBeInTheDocument();
+  }, 20000);
+
+  it('操作历史预览显示卷号并可跳转回该状态', async () => {
+    const user = userEvent.setup();
+    mockedScheduleApi.getUndoHistory.mockResolvedValue([
+      {
+        id: 11,
+        action_type: 'move_item',
+        description: '移动排程项',
+        is_undone: false,
+        created_at: '2026-02-01T08:00:00Z',
+        summary: { added: 0, removed: 0, moved: 1, updated: 0, plan_changed: false },
+      },
+    ]);
+    mockedScheduleApi.previewUndoEntry.mockResolvedValue({
+      entry_id: 11,
+      plan: { ...plans[0], roll_change_count: 0 },
+      items: [scheduleItemsWithRows[0]],
+      coil_ids: { 101: 'C101' },
+    });
+    mockedScheduleApi.jumpToUndoEntry.mockResolvedValue(1);
+    renderWorkbench();
+
+    await waitForLoaded();
+    await user.click(screen.getByRole('button', { name: /操作历史/ }));
+    const dialog = await screen.findByRole('dialog');
+    const row = (await within(dialog).findByText('移动排程项')).closest('tr') as HTMLElement;
+
+    await user.click(within(row).getByRole('button', { name: /预\s*览/ }));
+    await waitFor(() => {
+      expect(mockedScheduleApi.previewUndoEntry).toHaveBeenCalledWith(1, 11);
+    });
+    expect(await within(dialog).findByText('C101')).toBeInTheDocument();
+    expect(within(dialog).getByText(/1 卷 \/ 换辊/)).toBeInTheDocument();
+
+    await user.click(within(row).getByRole('button', { name: /跳\s*转/ }));
+    const popconfirm = await screen.findByRole('tooltip');
+    await user.click(within(popconfirm).getByRole('button', { name: /确\s*定|ok/i }));
+    await waitFor(() => {
+      expect(mockedScheduleApi.jumpToUndoEntry).toHaveBeenCalledWith(1, 11);
+    });
+  }, 20000);
+
   it('Ctrl+A 在无可入排材料时不会选中任何材料', async () => {
     mockedScheduleApi.getScheduleItems.mockResolvedValue(scheduleItems);
     mockedMaterialApi.getMaterials.mockResolvedValue({
@@ -626,14 +762,6 @@ describe('Workbench 配置命中联动', () => {
     await waitFor(() => {
       expect(mockedScheduleApi.removeFromSchedule).toHaveBeenCalledWith(1, [201]);
     });
-    await waitFor(() => {
-      expect(mockedScheduleApi.pushUndo).toHaveBeenCalledWith(
-        1,
-        'remove_items',
-        expect.any(String),
-        expect.any(String)
-      );
-    });
   }, 20000);
 
   it('排程锁定失败时页面仍可继续交互', async () => {
@@ -958,14 +1086,6 @@ describe('Workbench 配置命中联动', () => {
     await waitFor(() => {
       expect(mockedScheduleApi.moveScheduleItem).toHaveBeenCalledWith(1, 201, 2);
     });
-    await waitFor(() => {
-      expect(mockedScheduleApi.pushUndo).toHaveBeenCalledWith(
-        1,
-        'move_item',
-        expect.any(String),
-        expect.any(String)
-      );
-    });
   }, 20000);
 
   it('甘特图拖拽经过目标行时会设置 dropEffect 并可触发 dragLeave 分支', async () => {
@@ -1314,14 +1434,6 @@ describe('Workbench 配置命中联动', () => {
     await waitFor(() => {
       expect(mockedScheduleApi.autoSchedule).toHaveBeenCalledWith(1, 7);
     });
-    await waitFor(() => {
-      expect(mockedScheduleApi.pushUndo).toHaveBeenCalledWith(
-        1,
-        'auto_schedule',
-        expect.any(String),
-        expect.any(String)
-      );
-    });
     await waitFor(() => {
       expect(mockedScheduleApi.getPlan).toHaveBeenCalledWith(1);
     });
@@ -28,6 +28,7 @@ import {
   SaveOutlined,
   CheckCircleOutlined,
   InboxOutlined,
+  PlayCircleOutlined,
 } from '@ant-design/icons';
 import { useNavigate, useSearchParams } from 'react-router-dom';
 
@@ -35,6 +36,7 @@ import type { PriorityHitCodeSets } from '../../utils/priorityHit';
 import { nextPriorityHitFilter } from '../../utils/priorityHit';
 import type { ScheduleRow } from './types';
 import { statusColorMap, statusLabelMap } from './constants';
+import { isPlanEditable } from '../../constants/schedule';
 import {
   scheduleStatusLabelMap,
   scheduleStatusColorMap,
@@ -51,6 +53,8 @@ import GanttView from './GanttView';
 import MaterialTable from '../../components/MaterialTable';
 import WorkbenchModals from './WorkbenchModals';
 import ImportBatchPanel from './ImportBatchPanel';
+import UndoHistoryPanel from './UndoHistoryPanel';
+import PlanApprovalPanel from './PlanApprovalPanel';
 
 export default function Workbench() {
   const navigate = useNavigate();
@@ -582,7 +586,7 @@ export default function Workbench() {
               >
                 导入材料
               </Button>
-              {data.currentPlan && data.currentPlan.status !== 'confirmed' && data.currentPlan.status !== 'archived' && (
+              {data.currentPlan && isPlanEditable(data.currentPlan.status) && (
                 <Button
                   size="small"
                   icon={<SaveOutlined />}
@@ -591,7 +595,27 @@ export default function Workbench() {
                   保存
                 </Button>
               )}
-              {data.currentPlan && data.currentPlan.status !== 'confirmed' && data.currentPlan.status !== 'archived' && (
+              {data.currentPlan && isPlanEditable(data.currentPlan.status) && (
+                <Popconfirm
+                  title="提交审批"
+                  description="提交后方案将不可编辑，直至审批驳回，是否继续？"
+                  onConfirm={ops.handleSubmitPlan}
+                  okText="提交"
+                  cancelText="取消"
+                >
+                  <Button size="small" icon={<CheckCircleOutlined />}>
+                    提交审批
+                  </Button>
+                </Popconfirm>
+              )}
+              {data.currentPlan && (
+                <PlanApprovalPanel
+                  plan={data.currentPlan}
+                  onApprove={ops.handleApprovePlan}
+                  onReject={ops.handleRejectPlan}
+                />
+              )}
+              {data.currentPlan && data.currentPlan.status === 'approved' && (
                 <Popconfirm
                   title="确认生效"
                   description="方案确认生效后将不可编辑，是否继续？"
@@ -605,6 +629,21 @@ export default function Workbench() {
                 </Popconfirm>
               )}
               {data.currentPlan && data.currentPlan.status === 'confirmed' && (
+                <Popconfirm
+                  title="开始执行"
+                  description="方案将下发执行，执行中的方案只能归档，是否继续？"
+                  onConfirm={ops.handleExecutePlan}
+                  okText="执行"
+                  cancelText="取消"
+                >
+                  <Button size="small" icon={<PlayCircleOutlined />}>
+                    开始执行
+                  </Button>
+                </Popconfirm>
+              )}
+              {data.currentPlan &&
+                (data.currentPlan.status === 'confirmed' ||
+                  data.currentPlan.status === 'executing') && (
                 <Popconfirm
                   title="归档方案"
                   description="归档后方案将标记为历史版本，是否继续？"
@@ -628,9 +667,19 @@ export default function Workbench() {
                 alignItems: 'center',
               }}
             >
-              <span style={{ fontSize: 12, color: '#999' }}>
-                撤销栈: {data.undoCount}步 / 重做栈: {data.redoCount}步
-              </span>
+              <Space size="small">
+                <span style={{ fontSize: 12, color: '#999' }}>
+                  撤销栈: {data.undoCount}步 / 重做栈: {data.redoCount}步
+                </span>
+                {data.currentPlan && (
+                  <UndoHistoryPanel
+                    planId={data.currentPlan.id}
+                    editable={isPlanEditable(data.currentPlan.status)}
+                    onChanged={ops.handleUndoHistoryChanged}
+                    onForked={ops.handleForkedPlan}
+                  />
+                )}
+              </Space>
               <Button
                 size="small"
                 onClick={() => setScheduleViewMode(scheduleViewMode === 'list' ? 'gantt' : 'list')}
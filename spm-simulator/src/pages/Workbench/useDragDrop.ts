@@ -34,14 +34,7 @@ export function useDragDrop(params: UseDragDropParams) {
       const targetItem = scheduleItems.find((r) => r.id === targetItemId);
       if (!sourceItem || !targetItem || sourceItem.is_locked || targetItem.is_locked) return;
       try {
-        const beforeJson = JSON.stringify(scheduleItems.map((r) => r.id));
         await scheduleApi.moveScheduleItem(currentPlan.id, sourceId, targetItem.sequence);
-        await scheduleApi.pushUndo(
-          currentPlan.id,
-          'move_item',
-          beforeJson,
-          JSON.stringify({ itemId: sourceId, newSeq: targetItem.sequence })
-        );
         await refreshAfterMutation();
       } catch (error: unknown) {
         message.error(`移动失败: ${getErrorMessage(error)}`);
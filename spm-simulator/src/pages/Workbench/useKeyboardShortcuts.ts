@@ -5,6 +5,7 @@ import { message } from 'antd';
 import type { Material } from '../../types/material';
 import type { SchedulePlan } from '../../types/schedule';
 import type { ScheduleRow } from './types';
+import { isPlanEditable } from '../../constants/schedule';
 
 export interface UseKeyboardShortcutsParams {
   activePanel: 'materials' | 'schedule';
@@ -79,7 +80,7 @@ export function useKeyboardShortcuts(params: UseKeyboardShortcutsParams): void {
       }
       if (event.ctrlKey && key === 's') {
         event.preventDefault();
-        if (currentPlan && currentPlan.status !== 'confirmed') void handleSavePlan();
+        if (currentPlan && isPlanEditable(currentPlan.status)) void handleSavePlan();
         return;
       }
       if (event.ctrlKey && key === 'f') {
@@ -8,6 +8,7 @@ import { materialApi } from '../../services/materialApi';
 import type { ScheduleResult, SchedulePlan } from '../../types/schedule';
 import type { Material, ConflictMode } from '../../types/material';
 import { getErrorMessage, isFormValidateError } from '../../utils/error';
+import { isPlanEditable } from '../../constants/schedule';
 import type { ScheduleRow } from './types';
 
 export interface UseScheduleOperationsParams {
@@ -130,8 +131,23 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
     }
   }, [currentPlan, redoCount, refreshAfterMutation]);
 
+  const handleUndoHistoryChanged = useCallback(async () => {
+    if (!currentPlan) return;
+    const updatedPlan = await scheduleApi.getPlan(currentPlan.id);
+    setCurrentPlan(updatedPlan);
+    await refreshAfterMutation();
+  }, [currentPlan, setCurrentPlan, refreshAfterMutation]);
+
+  const handleForkedPlan = useCallback(
+    (plan: SchedulePlan) => {
+      setPlans((prev) => [plan, ...prev]);
+      setCurrentPlan(plan);
+    },
+    [setPlans, setCurrentPlan]
+  );
+
   const handleSavePlan = useCallback(async () => {
-    if (!currentPlan || currentPlan.status === 'confirmed') return;
+    if (!currentPlan || !isPlanEditable(currentPlan.status)) return;
     try {
       await scheduleApi.savePlan(currentPlan.id);
       const updatedPlan = await scheduleApi.getPlan(currentPlan.id);
@@ -142,9 +158,53 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
     }
   }, [currentPlan, setCurrentPlan]);
 
+  const handleSubmitPlan = useCallback(async () => {
+    if (!currentPlan || !isPlanEditable(currentPlan.status)) return;
+    try {
+      const updatedPlan = await scheduleApi.updatePlanStatus(currentPlan.id, 'submitted');
+      setCurrentPlan(updatedPlan);
+      setPlans((prev) => prev.map((p) => (p.id === updatedPlan.id ? updatedPlan : p)));
+      message.success('方案已提交审批');
+    } catch (error: unknown) {
+      message.error(`提交审批失败: ${getErrorMessage(error)}`);
+    }
+  }, [currentPlan, setCurrentPlan, setPlans]);
+
+  const handleReviewPlan = useCallback(
+    async (decision: 'approved' | 'rejected', approver: string, comment?: string) => {
+      if (!currentPlan || currentPlan.status !== 'submitted') return;
+      const label = decision === 'approved' ? '审批通过' : '驳回';
+      try {
+        const updatedPlan = await scheduleApi.updatePlanStatus(
+          currentPlan.id,
+          decision,
+          approver,
+          comment
+        );
+        setCurrentPlan(updatedPlan);
+        setPlans((prev) => prev.map((p) => (p.id === updatedPlan.id ? updatedPlan : p)));
+        message.success(`方案已${label}`);
+      } catch (error: unknown) {
+        message.error(`${label}失败: ${getErrorMessage(error)}`);
+        throw error;
+      }
+    },
+    [currentPlan, setCurrentPlan, setPlans]
+  );
+
+  const handleApprovePlan = useCallback(
+    (approver: string, comment?: string) => handleReviewPlan('approved', approver, comment),
+    [handleReviewPlan]
+  );
+
+  const handleRejectPlan = useCallback(
+    (approver: string, comment?: string) => handleReviewPlan('rejected', approver, comment),
+    [handleReviewPlan]
+  );
+
   const handleConfirmPlan = useCallback(async () => {
     if (!currentPlan) return;
-    if (currentPlan.status === 'confirmed' || currentPlan.status === 'archived') return;
+    if (currentPlan.status !== 'approved') return;
     try {
       const updatedPlan = await scheduleApi.updatePlanStatus(currentPlan.id, 'confirmed');
       setCurrentPlan(updatedPlan);
@@ -155,6 +215,19 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
     }
   }, [currentPlan, setCurrentPlan, setPlans]);
 
+  const handleExecutePlan = useCallback(async () => {
+    if (!currentPlan) return;
+    if (currentPlan.status !== 'confirmed') return;
+    try {
+      const updatedPlan = await scheduleApi.updatePlanStatus(currentPlan.id, 'executing');
+      setCurrentPlan(updatedPlan);
+      setPlans((prev) => prev.map((p) => (p.id === updatedPlan.id ? updatedPlan : p)));
+      message.success('方案已开始执行');
+    } catch (error: unknown) {
+      message.error(`开始执行失败: ${getErrorMessage(error)}`);
+    }
+  }, [currentPlan, setCurrentPlan, setPlans]);
+
   const handleArchivePlan = useCallback(async () => {
     if (!currentPlan) return;
     if (currentPlan.status === 'archived') return;
@@ -190,14 +263,7 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
       });
       if (readyPendingIds.length === 0) return;
       try {
-        const beforeJson = JSON.stringify(scheduleItems.map((r) => r.id));
         await scheduleApi.addToSchedule(currentPlan.id, readyPendingIds, position);
-        await scheduleApi.pushUndo(
-          currentPlan.id,
-          'add_materials',
-          beforeJson,
-          JSON.stringify(readyPendingIds)
-        );
         setSelectedMaterialIds([]);
         setAddModalOpen(false);
         setInsertPosition(null);
@@ -211,7 +277,6 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
       currentPlan,
       selectedMaterialIds,
       materials,
-      scheduleItems,
       refreshAfterMutation,
       setSelectedMaterialIds,
       setAddModalOpen,
@@ -227,14 +292,7 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
       const newSeq = direction === 'up' ? item.sequence - 1 : item.sequence + 1;
       if (newSeq < 1 || newSeq > scheduleItems.length) return;
       try {
-        const beforeJson = JSON.stringify(scheduleItems.map((r) => r.id));
         await scheduleApi.moveScheduleItem(currentPlan.id, itemId, newSeq);
-        await scheduleApi.pushUndo(
-          currentPlan.id,
-          'move_item',
-          beforeJson,
-          JSON.stringify({ itemId, newSeq })
-        );
         await refreshAfterMutation();
       } catch (error: unknown) {
         message.error(`移动失败: ${getErrorMessage(error)}`);
@@ -246,20 +304,13 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
   const handleRemoveItems = useCallback(async () => {
     if (!currentPlan || selectedItemIds.length === 0) return;
     try {
-      const beforeJson = JSON.stringify(scheduleItems.map((r) => r.id));
       await scheduleApi.removeFromSchedule(currentPlan.id, selectedItemIds);
-      await scheduleApi.pushUndo(
-        currentPlan.id,
-        'remove_items',
-        beforeJson,
-        JSON.stringify(selectedItemIds)
-      );
       setSelectedItemIds([]);
       await refreshAfterMutation();
     } catch (error: unknown) {
       message.error(`移除失败: ${getErrorMessage(error)}`);
     }
-  }, [currentPlan, selectedItemIds, scheduleItems, refreshAfterMutation, setSelectedItemIds]);
+  }, [currentPlan, selectedItemIds, refreshAfterMutation, setSelectedItemIds]);
 
   const handleLockItems = useCallback(
     async (locked: boolean) => {
@@ -278,17 +329,10 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
     if (!currentPlan || !selectedStrategyId) return;
     try {
       setScheduling(true);
-      const beforeJson = JSON.stringify(scheduleItems.map((r) => r.id));
       const result: ScheduleResult = await scheduleApi.autoSchedule(
         currentPlan.id,
         selectedStrategyId
       );
-      await scheduleApi.pushUndo(
-        currentPlan.id,
-        'auto_schedule',
-        beforeJson,
-        JSON.stringify(result)
-      );
       setScheduleModalOpen(false);
       const updatedPlan = await scheduleApi.getPlan(currentPlan.id);
       setCurrentPlan(updatedPlan);
@@ -326,7 +370,6 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
   }, [
     currentPlan,
     selectedStrategyId,
-    scheduleItems,
     refreshAfterMutation,
     setScheduling,
     setScheduleModalOpen,
@@ -338,7 +381,7 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
       const { open } = await import('@tauri-apps/plugin-dialog');
       const selected = await open({
         multiple: false,
-        filters: [{ name: 'Excel/CSV', extensions: ['xlsx', 'xls', 'csv'] }],
+        filters: [{ name: 'Excel/CSV', extensions: ['xlsx', 'xlsm', 'xlsb', 'xls', 'ods', 'csv'] }],
       });
       if (typeof selected === 'string') {
         params.setImportFilePath(selected);
@@ -526,8 +569,14 @@ export function useScheduleOperations(params: UseScheduleOperationsParams) {
     refreshAfterMutation,
     handleUndo,
     handleRedo,
+    handleUndoHistoryChanged,
+    handleForkedPlan,
     handleSavePlan,
+    handleSubmitPlan,
+    handleApprovePlan,
+    handleRejectPlan,
     handleConfirmPlan,
+    handleExecutePlan,
     handleArchivePlan,
     handleRefreshTemper,
     handleAddToSchedule,
@@ -473,6 +473,81 @@ describe('configApi', () => {
         expect(result).toEqual(mockTemplate);
       });
     });
+
+    describe('diffStrategyRevisions', () => {
+      it('应该调用 diff_strategy_revisions 命令', async () => {
+        vi.mocked(invoke).mockResolvedValue({ changes: [] });
+
+        await configApi.diffStrategyRevisions(1, 2, 3);
+
+        expect(invoke).toHaveBeenCalledWith('diff_strategy_revisions', {
+          strategyId: 1,
+          fromRevisionId: 2,
+          toRevisionId: 3,
+        });
+      });
+    });
+
+    describe('restoreStrategyRevision', () => {
+      it('应该调用 restore_strategy_revision 命令', async () => {
+        vi.mocked(invoke).mockResolvedValue({ id: 1 });
+
+        await configApi.restoreStrategyRevision(1, 2);
+
+        expect(invoke).toHaveBeenCalledWith('restore_strategy_revision', {
+          strategyId: 1,
+          revisionId: 2,
+        });
+      });
+    });
+
+    describe('validateStrategyConfig', () => {
+      it('应该调用 validate_strategy_config 命令', async () => {
+        const report = { valid: true, errors: [], warnings: [] };
+        vi.mocked(invoke).mockResolvedValue(report);
+        const input = {
+          sort_weights: '{"priorities":[]}',
+          constraints: '{"constraints":[]}',
+          eval_weights: '{"weights":{}}',
+          temper_rules: '{}',
+        };
+
+        const result = await configApi.validateStrategyConfig(input);
+
+        expect(invoke).toHaveBeenCalledWith('validate_strategy_config', { input });
+        expect(result).toEqual(report);
+      });
+    });
+  });
+
+  describe('Config Bundle', () => {
+    describe('exportConfigBundle', () => {
+      it('应该调用 export_config_bundle 命令', async () => {
+        vi.mocked(invoke).mockResolvedValue({ strategies: 2 });
+
+        const result = await configApi.exportConfigBundle('/tmp/bundle.json', ['strategies']);
+
+        expect(invoke).toHaveBeenCalledWith('export_config_bundle', {
+          filePath: '/tmp/bundle.json',
+          sections: ['strategies'],
+        });
+        expect(result).toEqual({ strategies: 2 });
+      });
+    });
+
+    describe('importConfigBundle', () => {
+      it('应该调用 import_config_bundle 命令', async () => {
+        vi.mocked(invoke).mockResolvedValue({ applied: false, sections: [] });
+
+        await configApi.importConfigBundle('/tmp/bundle.json', { strategies: 'replace' }, true);
+
+        expect(invoke).toHaveBeenCalledWith('import_config_bundle', {
+          filePath: '/tmp/bundle.json',
+          modes: { strategies: 'replace' },
+          dryRun: true,
+        });
+      });
+    });
   });
 
   describe('Maintenance Plan', () => {
@@ -1,6 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
   StrategyTemplate,
+  StrategyRevision,
+  StrategyRevisionDiff,
+  StrategyValidationReport,
+  ConfigBundleMode,
+  ConfigBundleReport,
   SystemConfig,
   ShiftConfig,
   MaintenancePlan,
@@ -38,8 +43,14 @@ interface UpdateStrategyInput {
   soft_constraints?: string;
   eval_weights?: string;
   temper_rules?: string;
+  change_note?: string;
 }
 
+type ValidateStrategyInput = Pick<
+  CreateStrategyInput,
+  'sort_weights' | 'constraints' | 'soft_constraints' | 'eval_weights' | 'temper_rules'
+>;
+
 export const configApi = {
   getSystemConfig: () => invoke<SystemConfig>('get_system_config'),
 
@@ -122,6 +133,44 @@ export const configApi = {
   importStrategyTemplate: (filePath: string) =>
     invoke<StrategyTemplate>('import_strategy_template', { filePath }),
 
+  getStrategyRevisions: (strategyId: number) =>
+    invoke<StrategyRevision[]>('get_strategy_revisions', { strategyId }),
+
+  diffStrategyRevisions: (strategyId: number, fromRevisionId: number, toRevisionId: number) =>
+    invoke<StrategyRevisionDiff>('diff_strategy_revisions', {
+      strategyId,
+      fromRevisionId,
+      toRevisionId,
+    }),
+
+  exportStrategyRevisionDiff: (
+    strategyId: number,
+    fromRevisionId: number,
+    toRevisionId: number,
+    filePath: string
+  ) =>
+    invoke<number>('export_strategy_revision_diff', {
+      strategyId,
+      fromRevisionId,
+      toRevisionId,
+      filePath,
+    }),
+
+  restoreStrategyRevision: (strategyId: number, revisionId: number) =>
+    invoke<StrategyTemplate>('restore_strategy_revision', { strategyId, revisionId }),
+
+  validateStrategyConfig: (input: ValidateStrategyInput) =>
+    invoke<StrategyValidationReport>('validate_strategy_config', { input }),
+
+  exportConfigBundle: (filePath: string, sections?: string[]) =>
+    invoke<Record<string, number>>('export_config_bundle', { filePath, sections }),
+
+  importConfigBundle: (
+    filePath: string,
+    modes: Record<string, ConfigBundleMode>,
+    dryRun: boolean
+  ) => invoke<ConfigBundleReport>('import_config_bundle', { filePath, modes, dryRun }),
+
   getMaintenancePlans: () => invoke<MaintenancePlan[]>('get_maintenance_plans'),
 
   createMaintenancePlan: (input: CreateMaintenancePlanInput) =>
@@ -97,6 +97,8 @@ describe('fieldMappingApi', () => {
 
       expect(invoke).toHaveBeenCalledWith('preview_file_headers', {
         filePath: '/path/to/file.xlsx',
+        mappingId: null,
+        readOptions: null,
       });
       expect(result).toEqual(mockResult);
     });
@@ -4,6 +4,8 @@ import type {
   CreateFieldMappingInput,
   UpdateFieldMappingInput,
   FilePreviewResult,
+  MappingSuggestionResult,
+  SourceReadOptions,
 } from '../types/fieldMapping';
 
 export const fieldMappingApi = {
@@ -19,6 +21,34 @@ export const fieldMappingApi = {
 
   deleteFieldMapping: (id: number) => invoke<void>('delete_field_mapping', { id }),
 
-  previewFileHeaders: (filePath: string) =>
-    invoke<FilePreviewResult>('preview_file_headers', { filePath }),
+  previewFileHeaders: (filePath: string, mappingId?: number, readOptions?: SourceReadOptions) =>
+    invoke<FilePreviewResult>('preview_file_headers', {
+      filePath,
+      mappingId: mappingId ?? null,
+      readOptions: readOptions ?? null,
+    }),
+
+  suggestFieldMappings: (
+    filePath: string,
+    readOptions?: SourceReadOptions,
+    minConfidence?: number
+  ) =>
+    invoke<MappingSuggestionResult>('suggest_field_mappings', {
+      filePath,
+      readOptions: readOptions ?? null,
+      minConfidence: minConfidence ?? null,
+    }),
+
+  createFieldMappingFromSuggestions: (
+    filePath: string,
+    templateName: string,
+    readOptions?: SourceReadOptions,
+    minConfidence?: number
+  ) =>
+    invoke<FieldMapping>('create_field_mapping_from_suggestions', {
+      filePath,
+      templateName,
+      readOptions: readOptions ?? null,
+      minConfidence: minConfidence ?? null,
+    }),
 };
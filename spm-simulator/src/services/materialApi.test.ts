@@ -31,6 +31,9 @@ describe('materialApi', () => {
         failed: 0,
         skipped: 0,
         overwritten: 0,
+        new_count: 10,
+        duplicate_count: 0,
+        changed_count: 0,
         errors: [],
       };
       vi.mocked(invoke).mockResolvedValue(mockResult);
@@ -53,6 +56,9 @@ describe('materialApi', () => {
         failed: 0,
         skipped: 0,
         overwritten: 0,
+        new_count: 10,
+        duplicate_count: 0,
+        changed_count: 0,
         errors: [],
       };
       vi.mocked(invoke).mockResolvedValue(mockResult);
@@ -85,6 +91,7 @@ describe('materialApi', () => {
         mappingsJson: '{}',
         valueTransforms: '{}',
         sampleLimit: 10,
+        validationRules: null,
       });
       expect(result).toEqual(mockResult);
     });
@@ -106,6 +113,7 @@ describe('materialApi', () => {
         mappingsJson: null,
         valueTransforms: null,
         sampleLimit: null,
+        validationRules: null,
       });
     });
   });
@@ -146,11 +154,12 @@ describe('materialApi', () => {
     it('应该调用 update_material_status 命令', async () => {
       vi.mocked(invoke).mockResolvedValue(3);
 
-      const result = await materialApi.updateMaterialStatus([1, 2, 3], 'frozen');
+      const result = await materialApi.updateMaterialStatus([1, 2, 3], 'frozen', '质量异议');
 
       expect(invoke).toHaveBeenCalledWith('update_material_status', {
         ids: [1, 2, 3],
         status: 'frozen',
+        reason: '质量异议',
       });
       expect(result).toBe(3);
     });
@@ -249,6 +258,9 @@ describe('materialApi', () => {
           failed: 2,
           skipped: 0,
           overwritten: 0,
+          new_count: 28,
+          duplicate_count: 0,
+          changed_count: 0,
           errors: [],
         },
       };
@@ -273,6 +285,9 @@ describe('materialApi', () => {
           failed: 0,
           skipped: 0,
           overwritten: 0,
+          new_count: 28,
+          duplicate_count: 0,
+          changed_count: 0,
           errors: [],
         },
       };
@@ -4,10 +4,17 @@ import type {
   MaterialFilter,
   ImportResult,
   ImportTestResult,
+  ImportErrorWorkbook,
+  ImportChangePreview,
+  MergePolicy,
   ImportBatch,
   DeleteBatchResult,
   ReplaceResult,
   RefreshResult,
+  MaterialChangeLog,
+  RestoreFieldsResult,
+  ProductionFeedback,
+  FeedbackResult,
 } from '../types/material';
 import type { PagedResult, Pagination } from '../types/schedule';
 
@@ -24,7 +31,8 @@ export const materialApi = {
     mappingId?: number,
     mappingsJson?: string,
     valueTransforms?: string,
-    sampleLimit?: number
+    sampleLimit?: number,
+    validationRules?: string
   ) =>
     invoke<ImportTestResult>('test_import_materials', {
       filePath,
@@ -32,6 +40,37 @@ export const materialApi = {
       mappingsJson: mappingsJson ?? null,
       valueTransforms: valueTransforms ?? null,
       sampleLimit: sampleLimit ?? null,
+      validationRules: validationRules ?? null,
+    }),
+
+  exportImportErrorWorkbook: (
+    filePath: string,
+    outputPath: string,
+    mappingId?: number,
+    mappingsJson?: string,
+    valueTransforms?: string,
+    validationRules?: string
+  ) =>
+    invoke<ImportErrorWorkbook>('export_import_error_workbook', {
+      filePath,
+      outputPath,
+      mappingId: mappingId ?? null,
+      mappingsJson: mappingsJson ?? null,
+      valueTransforms: valueTransforms ?? null,
+      validationRules: validationRules ?? null,
+    }),
+
+  previewMaterialImport: (
+    filePath: string,
+    mappingId?: number,
+    conflictMode?: string,
+    mergePolicy?: MergePolicy
+  ) =>
+    invoke<ImportChangePreview>('preview_material_import', {
+      filePath,
+      mappingId: mappingId ?? null,
+      conflictMode: conflictMode ?? null,
+      mergePolicy: mergePolicy ? JSON.stringify(mergePolicy) : null,
     }),
 
   getMaterials: (filter?: MaterialFilter, pagination?: Pagination) =>
@@ -40,12 +79,31 @@ export const materialApi = {
       pagination: pagination ?? null,
     }),
 
-  updateMaterialStatus: (ids: number[], status: string) =>
-    invoke<number>('update_material_status', { ids, status }),
+  updateMaterialStatus: (ids: number[], status: string, reason?: string) =>
+    invoke<number>('update_material_status', { ids, status, reason: reason ?? null }),
+
+  reportProductionFeedback: (entries: ProductionFeedback[]) =>
+    invoke<FeedbackResult>('report_production_feedback', { entries }),
 
   updateMaterialPriority: (ids: number[], priority: number) =>
     invoke<number>('update_material_priority', { ids, priority }),
 
+  getMaterialHistory: (params: {
+    materialId?: number;
+    coilId?: string;
+    field?: string;
+    limit?: number;
+  }) =>
+    invoke<MaterialChangeLog[]>('get_material_history', {
+      materialId: params.materialId ?? null,
+      coilId: params.coilId ?? null,
+      field: params.field ?? null,
+      limit: params.limit ?? null,
+    }),
+
+  restoreMaterialFields: (changeIds: number[], force?: boolean) =>
+    invoke<RestoreFieldsResult>('restore_material_fields', { changeIds, force: force ?? null }),
+
   refreshTemperStatus: () => invoke<RefreshResult>('refresh_temper_status'),
 
   deleteMaterials: (ids: number[]) => invoke<number>('delete_materials', { ids }),
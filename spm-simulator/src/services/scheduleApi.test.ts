@@ -110,7 +110,12 @@ describe('scheduleApi', () => {
 
         const result = await scheduleApi.updatePlanStatus(1, 'completed');
 
-        expect(invoke).toHaveBeenCalledWith('update_plan_status', { id: 1, status: 'completed' });
+        expect(invoke).toHaveBeenCalledWith('update_plan_status', {
+          id: 1,
+          status: 'completed',
+          approver: null,
+          comment: null,
+        });
         expect(clearInvokeCache).toHaveBeenCalled();
         expect(result).toEqual(mockPlan);
       });
@@ -252,22 +257,6 @@ describe('scheduleApi', () => {
   });
 
   describe('Undo/Redo', () => {
-    describe('pushUndo', () => {
-      it('应该调用 push_undo 命令并清除缓存', async () => {
-        vi.mocked(invoke).mockResolvedValue(undefined);
-
-        await scheduleApi.pushUndo(1, 'move', 'before', 'after');
-
-        expect(invoke).toHaveBeenCalledWith('push_undo', {
-          planId: 1,
-          actionType: 'move',
-          beforeState: 'before',
-          afterState: 'after',
-        });
-        expect(clearInvokeCache).toHaveBeenCalled();
-      });
-    });
-
     describe('undoAction', () => {
       it('应该调用 undo_action 命令并清除缓存', async () => {
         const mockResult: UndoRedoResult = { success: true };
@@ -324,6 +313,32 @@ describe('scheduleApi', () => {
         expect(invoke).toHaveBeenCalledWith('clear_undo_stack', { planId: null });
       });
     });
+
+    describe('jumpToUndoEntry', () => {
+      it('应该调用 jump_to_undo_entry 命令并清除缓存', async () => {
+        vi.mocked(invoke).mockResolvedValue(2);
+
+        const result = await scheduleApi.jumpToUndoEntry(1, null);
+
+        expect(invoke).toHaveBeenCalledWith('jump_to_undo_entry', { planId: 1, entryId: null });
+        expect(clearInvokeCache).toHaveBeenCalled();
+        expect(result).toBe(2);
+      });
+    });
+
+    describe('forkPlanFromUndoEntry', () => {
+      it('应该在未指定名称时传递 null', async () => {
+        vi.mocked(invoke).mockResolvedValue({ id: 9 });
+
+        await scheduleApi.forkPlanFromUndoEntry(1, 5);
+
+        expect(invoke).toHaveBeenCalledWith('fork_plan_from_undo_entry', {
+          planId: 1,
+          entryId: 5,
+          name: null,
+        });
+      });
+    });
   });
 
   describe('Risk Analysis', () => {
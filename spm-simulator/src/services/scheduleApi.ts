@@ -2,11 +2,14 @@ import { invoke } from '@tauri-apps/api/core';
 import { clearInvokeCache, invokeDeduped } from './requestCache';
 import type {
   SchedulePlan,
+  PlanApproval,
   ScheduleItem,
   CreatePlanInput,
   ScheduleResult,
   ScheduleIdleGapSummary,
   UndoRedoResult,
+  UndoHistoryEntry,
+  PlanStatePreview,
   RiskAnalysis,
   ApplyRiskSuggestionResult,
   IgnoredRiskEntry,
@@ -60,8 +63,16 @@ export const scheduleApi = {
 
   deletePlan: (id: number) => invokeWithCacheClear<void>('delete_plan', { id }),
 
-  updatePlanStatus: (id: number, status: string) =>
-    invokeWithCacheClear<SchedulePlan>('update_plan_status', { id, status }),
+  updatePlanStatus: (id: number, status: string, approver?: string, comment?: string) =>
+    invokeWithCacheClear<SchedulePlan>('update_plan_status', {
+      id,
+      status,
+      approver: approver ?? null,
+      comment: comment ?? null,
+    }),
+
+  getPlanApprovals: (planId: number) =>
+    invokeDeduped<PlanApproval[]>('get_plan_approvals', { planId }, CACHE_TTL_MS.short),
 
   autoSchedule: (planId: number, strategyId: number) =>
     invokeWithCacheClear<ScheduleResult>('auto_schedule', { planId, strategyId }),
@@ -93,9 +104,6 @@ export const scheduleApi = {
     invokeDeduped<ScheduleItem[]>('get_schedule_items', { planId }, CACHE_TTL_MS.short),
 
   // Undo/Redo
-  pushUndo: (planId: number, actionType: string, beforeState: string, afterState: string) =>
-    invokeWithCacheClear<void>('push_undo', { planId, actionType, beforeState, afterState }),
-
   undoAction: (planId: number) => invokeWithCacheClear<UndoRedoResult>('undo_action', { planId }),
 
   redoAction: (planId: number) => invokeWithCacheClear<UndoRedoResult>('redo_action', { planId }),
@@ -106,6 +114,15 @@ export const scheduleApi = {
   clearUndoStack: (planId?: number) =>
     invokeWithCacheClear<number>('clear_undo_stack', { planId: planId ?? null }),
 
+  getUndoHistory: (planId: number) =>
+    invokeDeduped<UndoHistoryEntry[]>('get_undo_history', { planId }, CACHE_TTL_MS.short),
+
+  previewUndoEntry: (planId: number, entryId: number | null) =>
+    invoke<PlanStatePreview>('preview_undo_entry', { planId, entryId }),
+
+  jumpToUndoEntry: (planId: number, entryId: number | null) =>
+    invokeWithCacheClear<number>('jump_to_undo_entry', { planId, entryId }),
+
   // Risk Analysis
   getRiskAnalysis: (planId: number) =>
     invokeDeduped<RiskAnalysis>('get_risk_analysis', { planId }, CACHE_TTL_MS.normal),
@@ -164,6 +181,13 @@ export const scheduleApi = {
   rollbackPlanVersion: (planId: number, targetPlanId: number) =>
     invokeWithCacheClear<SchedulePlan>('rollback_plan_version', { planId, targetPlanId }),
 
+  forkPlanFromUndoEntry: (planId: number, entryId: number | null, name?: string) =>
+    invokeWithCacheClear<SchedulePlan>('fork_plan_from_undo_entry', {
+      planId,
+      entryId,
+      name: name ?? null,
+    }),
+
   getOperationLogs: (filter?: OperationLogFilter) =>
     invokeDeduped<OperationLogEntry[]>(
       'get_operation_logs',
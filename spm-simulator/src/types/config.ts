@@ -13,6 +13,82 @@ export interface StrategyTemplate {
   updated_at?: string;
 }
 
+export interface StrategyRevision {
+  id: number;
+  strategy_id: number;
+  revision_no: number;
+  name: string;
+  description?: string;
+  sort_weights: string;
+  constraints: string;
+  soft_constraints?: string;
+  eval_weights: string;
+  temper_rules: string;
+  change_note?: string;
+  created_at?: string;
+}
+
+export interface StrategyChange {
+  section: string;
+  path: string;
+  kind: 'added' | 'removed' | 'changed' | 'reordered';
+  before: unknown;
+  after: unknown;
+}
+
+export interface StrategyRevisionDiff {
+  strategy_id: number;
+  from_revision_no: number;
+  to_revision_no: number;
+  changes: StrategyChange[];
+}
+
+export interface StrategySchemaIssue {
+  /** 如 constraints.constraints[1].max_value */
+  path: string;
+  level: 'error' | 'warning';
+  message: string;
+}
+
+export interface StrategyValidationReport {
+  valid: boolean;
+  errors: StrategySchemaIssue[];
+  warnings: StrategySchemaIssue[];
+}
+
+export type ConfigBundleMode = 'skip' | 'merge' | 'replace';
+
+export interface BundleRecordChange {
+  /** 业务键，多字段以 / 连接 */
+  key: string;
+  action: 'add' | 'update' | 'remove';
+  fields: string[];
+}
+
+export interface BundleSectionDiff {
+  section: string;
+  label: string;
+  mode: ConfigBundleMode;
+  added: number;
+  updated: number;
+  removed: number;
+  unchanged: number;
+  changes: BundleRecordChange[];
+  /** 保留本地记录的冲突说明，如替换时仍被方案引用的策略模板 */
+  conflicts: string[];
+}
+
+export interface ConfigBundleReport {
+  version: number;
+  app_version: string;
+  exported_at: string;
+  dry_run: boolean;
+  applied: boolean;
+  sections: BundleSectionDiff[];
+  errors: string[];
+  warnings: string[];
+}
+
 export interface ConfigValue {
   value: string;
   value_type: string;
@@ -5,6 +5,18 @@ export interface FieldMapping {
   source_type: string;
   mappings: string;
   value_transforms?: string;
+  /** 导入校验规则 JSON（ValidationRule[]） */
+  validation_rules?: string;
+  /** 工作表名称或序号（1 起始） */
+  sheet?: string;
+  /** 表头行（1 起始） */
+  header_row?: number;
+  /** 数据起始行（1 起始） */
+  data_start_row?: number;
+  /** CSV 分隔符（auto 自动识别） */
+  csv_delimiter?: string;
+  /** CSV 编码（auto / utf-8 / gbk） */
+  csv_encoding?: string;
   created_at?: string;
   updated_at?: string;
 }
@@ -29,6 +41,12 @@ export interface CreateFieldMappingInput {
   source_type: string;
   mappings: string;
   value_transforms?: string;
+  validation_rules?: string;
+  sheet?: string;
+  header_row?: number;
+  data_start_row?: number;
+  csv_delimiter?: string;
+  csv_encoding?: string;
   is_default?: boolean;
 }
 
@@ -37,13 +55,82 @@ export interface UpdateFieldMappingInput {
   source_type?: string;
   mappings?: string;
   value_transforms?: string;
+  validation_rules?: string;
+  sheet?: string;
+  header_row?: number;
+  data_start_row?: number;
+  csv_delimiter?: string;
+  csv_encoding?: string;
   is_default?: boolean;
 }
 
+/** 源文件读取选项（未设置的项取映射模板配置） */
+export interface SourceReadOptions {
+  sheet?: string;
+  header_row?: number;
+  data_start_row?: number;
+  csv_delimiter?: string;
+  csv_encoding?: string;
+}
+
+/** 导入校验规则（挂在映射模板上，作用于转换后的目标字段值） */
+export type ValidationRule =
+  | { type: 'range'; field: string; min?: number; max?: number; message?: string }
+  | {
+      type: 'allowed_values';
+      field: string;
+      values: string[];
+      ignore_case?: boolean;
+      message?: string;
+    }
+  | { type: 'pattern'; field: string; pattern: string; message?: string }
+  | {
+      type: 'required_if';
+      field: string;
+      when_field: string;
+      when_values?: string[];
+      message?: string;
+    }
+  | {
+      type: 'compare';
+      field: string;
+      op: '>' | '>=' | '<' | '<=' | '==' | '!=';
+      other: string;
+      message?: string;
+    };
+
+/** 单列映射建议 */
+export interface MappingSuggestion {
+  source_field: string;
+  column_index: number;
+  target_field: string;
+  mapping_type: FieldMappingItem['mapping_type'];
+  source_format?: string;
+  /** 综合置信度 0~1 */
+  confidence: number;
+  header_score: number;
+  value_score: number;
+  reason: 'header_exact' | 'header_pinyin' | 'header_similar' | 'value_pattern';
+  /** 列值统一形态（可用作 pattern 校验规则） */
+  detected_pattern?: string;
+}
+
+export interface MappingSuggestionResult {
+  suggestions: MappingSuggestion[];
+  /** 可直接保存为模板的映射配置 */
+  mappings: FieldMappingItem[];
+  unmapped_headers: string[];
+  missing_required: string[];
+}
+
 export interface FilePreviewResult {
   headers: string[];
   sample_rows: string[][];
   total_rows: number;
+  sheet_names?: string[];
+  sheet_name?: string;
+  encoding?: string;
+  delimiter?: string;
 }
 
 /** 系统内置的目标字段列表 */
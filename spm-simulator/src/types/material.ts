@@ -1,3 +1,6 @@
+/** 材料生命周期状态：待排 → 已锁定 → 生产中 → 已完工，另可冻结 */
+export type MaterialStatus = 'pending' | 'planned' | 'in_production' | 'completed' | 'frozen';
+
 export interface Material {
   id: number;
   coil_id: string;
@@ -26,7 +29,7 @@ export interface Material {
   storage_days?: number;
   storage_loc?: string;
   due_date?: string;
-  status?: 'pending' | 'frozen';
+  status?: MaterialStatus;
   priority_auto?: number;
   priority_manual_adjust?: number;
   priority_final?: number;
@@ -35,6 +38,10 @@ export interface Material {
   remarks?: string;
   created_at?: string;
   updated_at?: string;
+  /** 锁定该材料的已确认方案 */
+  reserved_plan_id?: number | null;
+  /** 最近一次状态变更原因 */
+  status_reason?: string | null;
 }
 
 export interface MaterialFilter {
@@ -48,7 +55,14 @@ export interface MaterialFilter {
   keyword?: string;
 }
 
-export type ConflictMode = 'skip' | 'overwrite' | 'replace_all';
+export type ConflictMode = 'skip' | 'overwrite' | 'merge' | 'replace_all';
+
+export type FieldMergePolicy = 'source' | 'keep_local' | 'fill_empty';
+
+export interface MergePolicy {
+  default?: FieldMergePolicy;
+  fields?: Record<string, FieldMergePolicy>;
+}
 
 export interface ImportResult {
   batch_id: number;
@@ -57,7 +71,33 @@ export interface ImportResult {
   failed: number;
   skipped: number;
   overwritten: number;
+  new_count: number;
+  duplicate_count: number;
+  changed_count: number;
+  errors: string[];
+}
+
+export interface FieldChange {
+  field: string;
+  from: string | null;
+  to: string | null;
+}
+
+export interface CoilChangePreview {
+  line_no: number;
+  coil_id: string;
+  changes: FieldChange[];
+}
+
+export interface ImportChangePreview {
+  conflict_mode: string;
+  total: number;
+  new_count: number;
+  duplicate_count: number;
+  changed_count: number;
+  failed: number;
   errors: string[];
+  changed: CoilChangePreview[];
 }
 
 export interface ImportBatch {
@@ -101,6 +141,47 @@ export interface ImportTestResult {
   rows: ImportTestRow[];
 }
 
+/** 行级错误工作簿（仅含未通过的行，出错单元格标红） */
+export interface ImportErrorWorkbook {
+  total: number;
+  error_rows: number;
+  file_path: string;
+}
+
+/** 材料字段变更记录 */
+export interface MaterialChangeLog {
+  id: number;
+  material_id: number;
+  coil_id: string;
+  field_name: string;
+  old_value?: string | null;
+  new_value?: string | null;
+  /** import / manual / priority_recompute / restore */
+  source: string;
+  source_ref?: string | null;
+  changed_at?: string | null;
+}
+
+/** 字段恢复结果 */
+export interface RestoreFieldsResult {
+  restored: number;
+  conflicts: string[];
+  skipped: string[];
+}
+
+/** 生产实绩反馈 */
+export interface ProductionFeedback {
+  coil_id: string;
+  event: 'start' | 'finish' | 'abort';
+  plan_id?: number | null;
+  reason?: string | null;
+}
+
+export interface FeedbackResult {
+  applied: number;
+  errors: string[];
+}
+
 export interface RefreshResult {
   total: number;
   tempered: number;
@@ -1,3 +1,25 @@
+/** 方案状态：draft/saved → submitted → approved/rejected → confirmed → executing → archived */
+export type PlanStatus =
+  | 'draft'
+  | 'saved'
+  | 'submitted'
+  | 'approved'
+  | 'rejected'
+  | 'confirmed'
+  | 'executing'
+  | 'archived';
+
+/** 方案审批/状态流转记录 */
+export interface PlanApproval {
+  id: number;
+  plan_id: number;
+  from_status?: string | null;
+  to_status: string;
+  approver?: string | null;
+  comment?: string | null;
+  created_at?: string | null;
+}
+
 export interface SchedulePlan {
   id: number;
   plan_no: string;
@@ -6,7 +28,7 @@ export interface SchedulePlan {
   start_date: string;
   end_date: string;
   strategy_id?: number;
-  status?: 'draft' | 'saved' | 'confirmed' | 'archived';
+  status?: PlanStatus;
   version?: number;
   parent_id?: number;
   total_count?: number;
@@ -24,6 +46,7 @@ export interface SchedulePlan {
   updated_at?: string;
   remarks?: string;
   ignored_risks?: string;
+  strategy_revision_id?: number;
 }
 
 export interface ScheduleItem {
@@ -106,6 +129,31 @@ export interface UndoRedoResult {
   remaining: number;
 }
 
+export interface UndoDiffSummary {
+  added: number;
+  removed: number;
+  moved: number;
+  updated: number;
+  plan_changed: boolean;
+}
+
+export interface UndoHistoryEntry {
+  id: number;
+  action_type: string;
+  description: string;
+  is_undone: boolean;
+  created_at: string | null;
+  summary: UndoDiffSummary;
+}
+
+export interface PlanStatePreview {
+  entry_id: number | null;
+  plan: SchedulePlan;
+  items: ScheduleItem[];
+  /** 材料 id -> 卷号 */
+  coil_ids: Record<number, string>;
+}
+
 // ─── Risk Analysis ───
 
 export interface IgnoredRiskEntry {
@@ -248,6 +296,7 @@ export interface PlanComparisonResult {
   only_a_coils: string[];
   only_b_coils: string[];
   sequence_changes: SequenceChangeItem[];
+  analysis: PlanPairAnalysis;
 }
 
 export interface SequenceChangeItem {
@@ -263,6 +312,73 @@ export interface PlanOverlapItem {
   common_count: number;
   only_a_count: number;
   only_b_count: number;
+  analysis: PlanPairAnalysis;
+}
+
+export interface SequenceSimilarity {
+  common_count: number;
+  kendall_tau: number | null;
+  discordant_pairs: number;
+  lcs_length: number;
+  lcs_similarity: number;
+}
+
+export interface ShiftDiffItem {
+  shift_key: string;
+  count_a: number;
+  count_b: number;
+  count_delta: number;
+  weight_a: number;
+  weight_b: number;
+  weight_delta: number;
+  only_a_coils: string[];
+  only_b_coils: string[];
+}
+
+export interface RollChangePoint {
+  position: number;
+  coil_id: string;
+  cumulative_weight: number;
+}
+
+export interface RollChangeAlignItem {
+  point_a: RollChangePoint | null;
+  point_b: RollChangePoint | null;
+  offset: number | null;
+}
+
+export interface RollChangeAlignment {
+  count_a: number;
+  count_b: number;
+  aligned_count: number;
+  mean_abs_offset: number;
+  items: RollChangeAlignItem[];
+}
+
+export interface DueOutcomeItem {
+  coil_id: string;
+  due_date: string;
+  end_a: string;
+  end_b: string;
+  late_minutes_a: number;
+  late_minutes_b: number;
+}
+
+export interface DueOutcomeDiff {
+  late_count_a: number;
+  late_count_b: number;
+  total_late_minutes_a: number;
+  total_late_minutes_b: number;
+  became_late: number;
+  became_on_time: number;
+  items: DueOutcomeItem[];
+}
+
+export interface PlanPairAnalysis {
+  similarity: SequenceSimilarity;
+  shift_diffs: ShiftDiffItem[];
+  roll_change_alignment: RollChangeAlignment;
+  due_outcome: DueOutcomeDiff;
 }
 
 export interface MultiPlanComparisonResult {